@@ -1,7 +1,10 @@
 use camino::{Utf8Path, Utf8PathBuf};
 
 use glistix_core::{
+    dependency::VersionPreference,
     error::{FileIoAction, FileKind},
+    io::make_relative,
+    requirement::Requirement,
     Error, Result,
 };
 
@@ -11,12 +14,46 @@ use crate::{
     fs,
 };
 
-pub fn command(packages_to_add: Vec<String>, dev: bool) -> Result<()> {
+pub fn command(
+    packages_to_add: Vec<String>,
+    dev: bool,
+    path: Option<Utf8PathBuf>,
+    git: Option<String>,
+    resolution_log: Option<Utf8PathBuf>,
+) -> Result<()> {
     let paths = crate::find_project_paths()?;
 
+    // A package coming from a local path or a git repository is added by
+    // name, with no version specifier.
+    let source = match (path, git) {
+        (Some(path), _) => {
+            // The path is given relative to the current directory, but
+            // gleam.toml wants it relative to the project root.
+            let path = fs::get_current_directory()?.join(path);
+            let path = make_relative(paths.root(), &path);
+            Some(Requirement::path(path.as_str()))
+        }
+        (None, Some(git)) => Some(Requirement::git(&git)),
+        (None, None) => None,
+    };
+
     let mut new_package_requirements = Vec::with_capacity(packages_to_add.len());
-    for specifier in packages_to_add {
-        new_package_requirements.push(parse_gleam_add_specifier(&specifier)?);
+    match source {
+        Some(requirement) => {
+            if packages_to_add.len() > 1 {
+                return Err(Error::AddedPackagesShareSource {
+                    packages: packages_to_add,
+                });
+            }
+            for package in packages_to_add {
+                new_package_requirements.push((package.into(), requirement.clone()));
+            }
+        }
+        None => {
+            for specifier in packages_to_add {
+                new_package_requirements.push(parse_gleam_add_specifier(&specifier)?);
+            }
+        }
     }
 
     // Insert the new packages into the manifest and perform dependency
@@ -27,6 +64,8 @@ pub fn command(packages_to_add: Vec<String>, dev: bool) -> Result<()> {
         Some((new_package_requirements.clone(), dev)),
         Vec::new(),
         UseManifest::Yes,
+        VersionPreference::Newest,
+        resolution_log.as_deref(),
     )?;
 
     // Read gleam.toml and manifest.toml so we can insert new deps into it
@@ -34,7 +73,7 @@ pub fn command(packages_to_add: Vec<String>, dev: bool) -> Result<()> {
     let mut manifest_toml = read_toml_edit("manifest.toml")?;
 
     // Insert the new deps
-    for (added_package, _) in new_package_requirements {
+    for (added_package, requirement) in new_package_requirements {
         let added_package = added_package.to_string();
 
         // Pull the selected version out of the new manifest so we know what it is
@@ -47,6 +86,23 @@ pub fn command(packages_to_add: Vec<String>, dev: bool) -> Result<()> {
 
         tracing::info!(version=%version, "new_package_version_resolved");
 
+        let dependencies = if dev {
+            "dev-dependencies"
+        } else {
+            "dependencies"
+        };
+
+        // Path and git dependencies are written as they were given, the
+        // manifest already records them the same way.
+        if let Some(source) = source_table(&requirement) {
+            #[allow(clippy::indexing_slicing)]
+            {
+                gleam_toml[dependencies][&added_package] = toml_edit::value(source);
+            }
+            cli::print_added(&format!("{added_package} v{version}"));
+            continue;
+        }
+
         // Produce a version requirement locked to the major version.
         // i.e. if 1.2.3 is selected we want >= 1.2.3 and < 2.0.0
         let range = format!(
@@ -60,11 +116,7 @@ pub fn command(packages_to_add: Vec<String>, dev: bool) -> Result<()> {
         // False positive. This package doesn't use the indexing API correctly.
         #[allow(clippy::indexing_slicing)]
         {
-            if dev {
-                gleam_toml["dev-dependencies"][&added_package] = toml_edit::value(range.clone());
-            } else {
-                gleam_toml["dependencies"][&added_package] = toml_edit::value(range.clone());
-            };
+            gleam_toml[dependencies][&added_package] = toml_edit::value(range.clone());
             manifest_toml["requirements"][&added_package]
                 .as_inline_table_mut()
                 .expect("Invalid manifest format")["version"] = range.into();
@@ -80,6 +132,19 @@ pub fn command(packages_to_add: Vec<String>, dev: bool) -> Result<()> {
     Ok(())
 }
 
+/// The inline table to write in gleam.toml for a dependency that doesn't come
+/// from Hex.
+fn source_table(requirement: &Requirement) -> Option<toml_edit::InlineTable> {
+    let (key, value) = match requirement {
+        Requirement::Hex { .. } => return None,
+        Requirement::Path { path } => ("path", path.as_str().replace('\\', "/")),
+        Requirement::Git { git } => ("git", git.to_string()),
+    };
+    let mut table = toml_edit::InlineTable::new();
+    _ = table.insert(key, value.into());
+    Some(table)
+}
+
 fn read_toml_edit(name: &str) -> Result<toml_edit::DocumentMut, Error> {
     fs::read(name)?
         .parse::<toml_edit::DocumentMut>()
@@ -90,3 +155,30 @@ fn read_toml_edit(name: &str) -> Result<toml_edit::DocumentMut, Error> {
             err: Some(e.to_string()),
         })
 }
+
+#[test]
+fn source_table_of_hex_requirement() {
+    assert!(source_table(&Requirement::hex(">= 1.0.0 and < 2.0.0")).is_none());
+}
+
+#[test]
+fn source_table_of_path_requirement() {
+    let table = source_table(&Requirement::path("../wibble")).map(|table| table.to_string());
+    assert_eq!(table.as_deref(), Some(r#"{ path = "../wibble" }"#));
+}
+
+#[test]
+fn source_table_of_windows_path_requirement() {
+    let table = source_table(&Requirement::path("..\\libs\\wibble")).map(|table| table.to_string());
+    assert_eq!(table.as_deref(), Some(r#"{ path = "../libs/wibble" }"#));
+}
+
+#[test]
+fn source_table_of_git_requirement() {
+    let table = source_table(&Requirement::git("https://github.com/wibble/wobble.git"))
+        .map(|table| table.to_string());
+    assert_eq!(
+        table.as_deref(),
+        Some(r#"{ git = "https://github.com/wibble/wobble.git" }"#)
+    );
+}
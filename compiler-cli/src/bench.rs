@@ -0,0 +1,402 @@
+//! The `glistix bench` command, which runs the benchmark functions of the
+//! project repeatedly and reports how long they took.
+
+use std::time::{Duration, Instant};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use glistix_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Compile, Mode, Options, Runtime, Target, Telemetry},
+    error::Error,
+    io::{CommandExecutor, Stdio},
+    paths::ProjectPaths,
+};
+use itertools::Itertools;
+use serde_json::json;
+
+use crate::fs::ProjectIO;
+
+/// The file the generated benchmark runners write the time taken by each
+/// iteration to, in the build directory of the benchmarked package.
+const TIMINGS_FILE_NAME: &str = "gleam.bench.timings";
+
+/// A public function without arguments whose name starts with `bench_`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Benchmark {
+    package: EcoString,
+    module: EcoString,
+    function: EcoString,
+}
+
+impl Benchmark {
+    fn name(&self) -> String {
+        format!("{}.{}", self.module, self.function)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Statistics {
+    min: Duration,
+    median: Duration,
+    mean: Duration,
+    standard_deviation: Duration,
+}
+
+pub fn command(
+    target: Option<Target>,
+    runtime: Option<Runtime>,
+    iterations: u32,
+    filter: Option<String>,
+    json: bool,
+) -> Result<(), Error> {
+    let paths = crate::find_project_paths()?;
+    let manifest = crate::build::download_dependencies(crate::cli::Reporter::new())?;
+    let config = crate::config::root_config()?;
+    let target = target.unwrap_or(config.target);
+
+    let options = Options {
+        warnings_as_errors: false,
+        compile: Compile::All,
+        codegen: Codegen::All,
+        mode: Mode::Dev,
+        target: Some(target),
+        root_target_support: TargetSupport::Enforced,
+        no_print_progress: false,
+        changed_files: None,
+    };
+    let built = crate::build::main(options, manifest)?;
+
+    let benchmarks = built
+        .module_interfaces()
+        .values()
+        .filter(|module| module.package == config.name)
+        .flat_map(|module| {
+            module
+                .benchmark_functions(target)
+                .into_iter()
+                .map(|function| Benchmark {
+                    package: module.package.clone(),
+                    module: module.name.clone(),
+                    function,
+                })
+        })
+        .filter(|benchmark| match &filter {
+            Some(filter) => benchmark.name().contains(filter.as_str()),
+            None => true,
+        })
+        .sorted_by(|one, other| one.name().cmp(&other.name()))
+        .collect_vec();
+
+    if benchmarks.is_empty() {
+        println!("No benchmarks found");
+        return Ok(());
+    }
+
+    let runtime = runtime.unwrap_or(config.javascript.runtime);
+    let mut results = vec![];
+    for benchmark in benchmarks {
+        crate::cli::Reporter.running(&benchmark.name());
+        let durations = match target {
+            Target::Erlang => run_erlang(&paths, &benchmark, iterations)?,
+            Target::JavaScript => run_javascript(&paths, runtime, &benchmark, iterations)?,
+            Target::Nix => run_nix(&paths, &benchmark, iterations)?,
+        };
+        if let Some(statistics) = statistics(&durations) {
+            results.push((benchmark, durations.len(), statistics));
+        }
+    }
+
+    if json {
+        let report = json_report(target, &results);
+        let json = serde_json::to_string_pretty(&report).expect("Benchmarks serialisation");
+        println!("{json}");
+    } else {
+        print!("{}", text_report(&results));
+    }
+    Ok(())
+}
+
+/// Times each iteration within a single Erlang VM, so starting it isn't
+/// counted.
+fn run_erlang(
+    paths: &ProjectPaths,
+    benchmark: &Benchmark,
+    iterations: u32,
+) -> Result<Vec<Duration>, Error> {
+    let mut args = vec![];
+    let packages = paths.build_directory_for_target(Mode::Dev, Target::Erlang);
+    for entry in crate::fs::read_dir(packages)?.filter_map(Result::ok) {
+        args.push("-pa".into());
+        args.push(entry.path().join("ebin").into());
+    }
+
+    // gleam modules are separated by `/`. Erlang modules are separated by `@`.
+    let module = benchmark.module.replace("/", "@");
+    let function = &benchmark.function;
+    let timings = timings_path(paths, benchmark, Target::Erlang);
+    args.push("-noshell".into());
+    args.push("-eval".into());
+    args.push(format!(
+        "Times = [element(1, timer:tc(fun '{module}':'{function}'/0)) \
+|| _ <- lists:seq(1, {iterations})], \
+ok = file:write_file({timings:?}, [io_lib:format(\"~b~n\", [T]) || T <- Times]), \
+erlang:halt()."
+    ));
+
+    run_timed("erl", &args, &timings, benchmark, Target::Erlang)
+}
+
+/// Times each iteration within a single JavaScript process, so starting it
+/// isn't counted.
+fn run_javascript(
+    paths: &ProjectPaths,
+    runtime: Runtime,
+    benchmark: &Benchmark,
+    iterations: u32,
+) -> Result<Vec<Duration>, Error> {
+    let path = paths
+        .build_directory_for_package(Mode::Dev, Target::JavaScript, &benchmark.package)
+        .join("gleam.bench.mjs");
+    let module = &benchmark.module;
+    let function = &benchmark.function;
+    let timings = timings_path(paths, benchmark, Target::JavaScript);
+    let entrypoint = format!(
+        r#"import {{ writeFileSync }} from "node:fs";
+import {{ {function} }} from "./{module}.mjs";
+let times = "";
+for (let i = 0; i < {iterations}; i++) {{
+  const start = performance.now();
+  {function}();
+  times += `${{Math.round((performance.now() - start) * 1000)}}\n`;
+}}
+writeFileSync({timings:?}, times);
+"#
+    );
+    crate::fs::write(&path, &entrypoint)?;
+
+    let (program, mut args) = match runtime {
+        Runtime::NodeJs => ("node", vec![]),
+        Runtime::Deno => (
+            "deno",
+            vec!["run".to_string(), format!("--allow-write={timings}")],
+        ),
+        Runtime::Bun => ("bun", vec!["run".to_string()]),
+    };
+    args.push(path.to_string());
+    run_timed(program, &args, &timings, benchmark, Target::JavaScript)
+}
+
+/// Nix can't read the clock, so each iteration is a separate evaluation of
+/// the benchmark with `nix-instantiate`, and its time includes starting the
+/// evaluator and reading the generated files.
+fn run_nix(
+    paths: &ProjectPaths,
+    benchmark: &Benchmark,
+    iterations: u32,
+) -> Result<Vec<Duration>, Error> {
+    let file = paths
+        .build_directory_for_package(Mode::Dev, Target::Nix, &benchmark.package)
+        .join(format!("{}.nix", benchmark.module));
+    let args = vec![
+        "--eval".to_string(),
+        "--strict".to_string(),
+        "--attr".to_string(),
+        benchmark.function.to_string(),
+        // As with `gleam run`, an arbitrary argument makes Nix call the
+        // function once it has been selected.
+        "--arg".to_string(),
+        "null".to_string(),
+        "null".to_string(),
+        file.to_string(),
+    ];
+
+    let io = ProjectIO::new();
+    let mut durations = vec![];
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let status = io.exec("nix-instantiate", &args, &[], None, Stdio::Null)?;
+        let duration = start.elapsed();
+        if status != 0 {
+            return Err(Error::BenchmarkFailed {
+                benchmark: benchmark.name().into(),
+                target: Target::Nix,
+                status,
+            });
+        }
+        durations.push(duration);
+    }
+    Ok(durations)
+}
+
+/// The path of the file a benchmark runner writes its timings to.
+fn timings_path(paths: &ProjectPaths, benchmark: &Benchmark, target: Target) -> Utf8PathBuf {
+    paths
+        .build_directory_for_package(Mode::Dev, target, &benchmark.package)
+        .join(TIMINGS_FILE_NAME)
+}
+
+/// Runs a benchmark runner and reads the times it wrote for each iteration.
+fn run_timed(
+    program: &str,
+    args: &[String],
+    timings: &Utf8Path,
+    benchmark: &Benchmark,
+    target: Target,
+) -> Result<Vec<Duration>, Error> {
+    // Times left by an earlier run mustn't be taken for those of this one.
+    if timings.exists() {
+        crate::fs::delete_file(timings)?;
+    }
+
+    tracing::trace!(program=program, args=?args.join(" "), "running_benchmark");
+    let status = ProjectIO::new().exec(program, args, &[], None, Stdio::Inherit)?;
+    if status != 0 {
+        return Err(Error::BenchmarkFailed {
+            benchmark: benchmark.name().into(),
+            target,
+            status,
+        });
+    }
+    Ok(parse_timings(&crate::fs::read(timings)?))
+}
+
+/// The times written by a benchmark runner, in microseconds, one per line.
+fn parse_timings(output: &str) -> Vec<Duration> {
+    output
+        .lines()
+        .filter_map(|micros| micros.trim_end().parse().ok())
+        .map(Duration::from_micros)
+        .collect()
+}
+
+fn statistics(durations: &[Duration]) -> Option<Statistics> {
+    let sorted = durations.iter().copied().sorted().collect_vec();
+    let count = sorted.len() as u32;
+    let min = *sorted.first()?;
+    let middle = sorted.len() / 2;
+    let median = if sorted.len() % 2 == 0 {
+        (*sorted.get(middle - 1)? + *sorted.get(middle)?) / 2
+    } else {
+        *sorted.get(middle)?
+    };
+    let mean = sorted.iter().sum::<Duration>() / count;
+    let variance = sorted
+        .iter()
+        .map(|duration| (duration.as_secs_f64() - mean.as_secs_f64()).powi(2))
+        .sum::<f64>()
+        / f64::from(count);
+
+    Some(Statistics {
+        min,
+        median,
+        mean,
+        standard_deviation: Duration::from_secs_f64(variance.sqrt()),
+    })
+}
+
+fn text_report(results: &[(Benchmark, usize, Statistics)]) -> String {
+    let headers = ["Benchmark", "Min", "Median", "Mean", "Std dev"];
+    let rows = results
+        .iter()
+        .map(|(benchmark, _, statistics)| {
+            [
+                benchmark.name(),
+                format!("{:.2?}", statistics.min),
+                format!("{:.2?}", statistics.median),
+                format!("{:.2?}", statistics.mean),
+                format!("{:.2?}", statistics.standard_deviation),
+            ]
+        })
+        .collect_vec();
+
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut report = String::new();
+    for row in std::iter::once(headers.map(String::from)).chain(rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(index, (cell, width))| {
+                // Names are aligned to the left and times to the right.
+                if index == 0 {
+                    format!("{cell:<width$}")
+                } else {
+                    format!("{cell:>width$}")
+                }
+            })
+            .join("  ");
+        report.push_str(&line);
+        report.push('\n');
+    }
+    report
+}
+
+fn json_report(target: Target, results: &[(Benchmark, usize, Statistics)]) -> serde_json::Value {
+    let micros = |duration: Duration| duration.as_micros() as u64;
+    let benchmarks = results
+        .iter()
+        .map(|(benchmark, iterations, statistics)| {
+            json!({
+                "name": benchmark.name(),
+                "iterations": iterations,
+                "min": micros(statistics.min),
+                "median": micros(statistics.median),
+                "mean": micros(statistics.mean),
+                "standard_deviation": micros(statistics.standard_deviation),
+            })
+        })
+        .collect_vec();
+    json!({
+        "target": target.to_string(),
+        "unit": "microseconds",
+        "benchmarks": benchmarks,
+    })
+}
+
+#[test]
+fn benchmark_timings_are_parsed() {
+    let output = "12\n7\r\n";
+    assert_eq!(
+        parse_timings(output),
+        vec![Duration::from_micros(12), Duration::from_micros(7)]
+    );
+}
+
+#[test]
+fn benchmark_statistics() {
+    let durations = [4, 1, 3, 2].map(Duration::from_micros);
+    let statistics_ = statistics(&durations).expect("Statistics of durations");
+    assert_eq!(statistics_.min, Duration::from_micros(1));
+    assert_eq!(statistics_.median, Duration::from_nanos(2500));
+    assert_eq!(statistics_.mean, Duration::from_nanos(2500));
+    assert_eq!(statistics_.standard_deviation.as_nanos(), 1118);
+    assert_eq!(statistics(&[]), None);
+}
+
+#[test]
+fn benchmark_text_report() {
+    let benchmark = Benchmark {
+        package: "wibble".into(),
+        module: "wibble/wobble".into(),
+        function: "bench_wubble".into(),
+    };
+    let statistics = Statistics {
+        min: Duration::from_micros(10),
+        median: Duration::from_micros(12),
+        mean: Duration::from_micros(13),
+        standard_deviation: Duration::from_micros(2),
+    };
+    assert_eq!(
+        text_report(&[(benchmark, 10, statistics)]),
+        "Benchmark                       Min   Median     Mean  Std dev
+wibble/wobble.bench_wubble  10.00µs  12.00µs  13.00µs   2.00µs
+"
+    );
+}
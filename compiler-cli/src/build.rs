@@ -1,11 +1,22 @@
-use std::{rc::Rc, time::Instant};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+    time::Instant,
+};
 
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::{Args, ValueEnum};
 use glistix_core::{
-    build::{Built, Codegen, NullTelemetry, Options, ProjectCompiler, Telemetry},
+    build::{Built, Codegen, NullTelemetry, Options, Phase, ProjectCompiler, Telemetry},
+    config::PackageConfig,
+    dependency::VersionPreference,
+    diagnostic::{Diagnostic, JsonDiagnostic},
+    error::{codes::ErrorCode, FileIoAction, FileKind, OutputDifference},
+    io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter},
     manifest::Manifest,
-    paths::ProjectPaths,
-    warning::WarningEmitterIO,
-    Result,
+    paths::{ProjectPaths, ARTEFACT_DIRECTORY_NAME},
+    warning::{VectorWarningEmitterIO, Warning, WarningEmitterIO},
+    Error, Result,
 };
 
 use crate::{
@@ -13,32 +24,380 @@ use crate::{
     cli,
     dependencies::UseManifest,
     fs::{self, get_current_directory, get_project_root, ConsoleWarningEmitter},
+    timings::{Timings, TimingsOptions},
 };
 
+/// How warnings and errors are printed when building.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum DiagnosticFormat {
+    /// Pretty printed for humans, on stderr.
+    Text,
+    /// A JSON array of diagnostics, on stdout.
+    Json,
+}
+
+/// How warnings and errors printed as text are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum DiagnosticStyle {
+    /// Along with the source code they refer to.
+    #[default]
+    Full,
+    /// On a single `path:line:column: severity: message` line each.
+    Short,
+}
+
+/// How the paths of files are written in warnings and errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum DiagnosticPaths {
+    #[default]
+    Absolute,
+    /// Relative to the current directory.
+    Relative,
+}
+
+#[derive(Args, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DiagnosticOptions {
+    /// How to lay out warnings and errors printed as text
+    #[arg(long = "diagnostic-format", ignore_case = true, default_value = "full")]
+    pub style: DiagnosticStyle,
+
+    /// How to write the paths of files in warnings and errors
+    #[arg(
+        long = "diagnostic-paths",
+        ignore_case = true,
+        default_value = "absolute"
+    )]
+    pub paths: DiagnosticPaths,
+}
+
+impl DiagnosticOptions {
+    fn with_paths(
+        &self,
+        mut diagnostics: Vec<(Diagnostic, Option<ErrorCode>)>,
+    ) -> Vec<(Diagnostic, Option<ErrorCode>)> {
+        if self.paths == DiagnosticPaths::Relative {
+            // Without a current directory the paths are left absolute.
+            if let Ok(directory) = get_current_directory() {
+                for (diagnostic, _) in &mut diagnostics {
+                    diagnostic.make_paths_relative(&directory);
+                }
+            }
+        }
+        diagnostics
+    }
+
+    fn print(&self, diagnostics: Vec<(Diagnostic, Option<ErrorCode>)>) {
+        use std::io::Write;
+        let buffer_writer = cli::stderr_buffer_writer();
+        let mut buffer = buffer_writer.buffer();
+        for (diagnostic, code) in self.with_paths(diagnostics) {
+            match self.style {
+                DiagnosticStyle::Full => {
+                    diagnostic.write(&mut buffer);
+                    writeln!(buffer).expect("write new line after diagnostic");
+                }
+                DiagnosticStyle::Short => diagnostic.write_short(code, &mut buffer),
+            }
+        }
+        buffer_writer
+            .print(&buffer)
+            .expect("Writing diagnostics to stderr");
+    }
+}
+
+/// Prints warnings to stderr as they are emitted, laid out as requested.
+#[derive(Debug, Clone, Copy)]
+struct TextWarningEmitter(DiagnosticOptions);
+
+impl WarningEmitterIO for TextWarningEmitter {
+    fn emit_warning(&self, warning: Warning) {
+        self.0.print(vec![(warning.to_diagnostic(), None)]);
+    }
+}
+
 pub fn download_dependencies(telemetry: impl Telemetry) -> Result<Manifest> {
     let paths = crate::find_project_paths()?;
-    crate::dependencies::download(&paths, telemetry, None, Vec::new(), UseManifest::Yes)
+    crate::dependencies::download(
+        &paths,
+        telemetry,
+        None,
+        Vec::new(),
+        UseManifest::Yes,
+        VersionPreference::Newest,
+        None,
+    )
 }
 
 pub fn main(options: Options, manifest: Manifest) -> Result<Built> {
     main_with_warnings(options, manifest, Rc::new(ConsoleWarningEmitter))
 }
 
+/// Builds the project, printing warnings and errors in the given format.
+///
+/// If timings were requested they are reported once the build has finished.
+pub fn main_with_format(
+    options: Options,
+    format: DiagnosticFormat,
+    diagnostics: DiagnosticOptions,
+    timings_options: &TimingsOptions,
+    download_dependencies: impl FnOnce() -> Result<Manifest>,
+) -> Result<()> {
+    let telemetry = progress_telemetry(&options);
+    if !timings_options.enabled() {
+        return build_with_format(
+            options,
+            format,
+            diagnostics,
+            telemetry,
+            download_dependencies,
+        );
+    }
+
+    let timings = Timings::new(telemetry);
+    let package = crate::config::root_config()?.name;
+    let download_dependencies = || {
+        timings.phase_started(Phase::DownloadDependencies, &package, None);
+        let manifest = download_dependencies();
+        timings.phase_finished();
+        manifest
+    };
+    let result = build_with_format(
+        options,
+        format,
+        diagnostics,
+        &timings,
+        download_dependencies,
+    );
+
+    timings.print_report();
+    if let Some(path) = &timings_options.timings_trace {
+        timings.write_chrome_trace(path)?;
+    }
+    result
+}
+
+fn build_with_format(
+    options: Options,
+    format: DiagnosticFormat,
+    diagnostics: DiagnosticOptions,
+    telemetry: &dyn Telemetry,
+    download_dependencies: impl FnOnce() -> Result<Manifest>,
+) -> Result<()> {
+    match format {
+        // The default layout is left to the usual printing of warnings and
+        // of the error the command ends with.
+        DiagnosticFormat::Text if diagnostics == DiagnosticOptions::default() => {
+            let manifest = download_dependencies()?;
+            let warnings = Rc::new(ConsoleWarningEmitter);
+            let _ = main_with_telemetry(options, manifest, warnings, telemetry)?;
+            Ok(())
+        }
+        DiagnosticFormat::Text => {
+            let manifest = download_dependencies()?;
+            let warnings = Rc::new(TextWarningEmitter(diagnostics));
+            match main_with_telemetry(options, manifest, warnings, telemetry) {
+                Ok(_) => Ok(()),
+                Err(error) => {
+                    diagnostics.print(error.to_diagnostics_with_codes());
+                    Err(Error::AlreadyReported)
+                }
+            }
+        }
+        DiagnosticFormat::Json => {
+            main_with_json_diagnostics(options, diagnostics, download_dependencies, telemetry)
+        }
+    }
+}
+
+/// Builds the project, printing all warnings and errors as a single JSON
+/// array on stdout once compilation has finished, rather than pretty printing
+/// them to stderr as they occur.
+///
+/// If compilation fails the error is included in the JSON, so
+/// `Error::AlreadyReported` is returned in its place.
+fn main_with_json_diagnostics(
+    options: Options,
+    diagnostic_options: DiagnosticOptions,
+    download_dependencies: impl FnOnce() -> Result<Manifest>,
+    telemetry: &dyn Telemetry,
+) -> Result<()> {
+    let warnings = Rc::new(VectorWarningEmitterIO::new());
+    let result = download_dependencies()
+        .and_then(|manifest| main_with_telemetry(options, manifest, warnings.clone(), telemetry));
+
+    let mut diagnostics: Vec<(Diagnostic, Option<ErrorCode>)> = warnings
+        .take()
+        .iter()
+        .map(|warning| (warning.to_diagnostic(), None))
+        .collect();
+    if let Err(error) = &result {
+        diagnostics.extend(error.to_diagnostics_with_codes());
+    }
+    let diagnostics: Vec<JsonDiagnostic> = diagnostic_options
+        .with_paths(diagnostics)
+        .iter()
+        .map(|(diagnostic, code)| diagnostic.to_json(*code))
+        .collect();
+
+    let json = serde_json::to_string(&diagnostics).expect("diagnostics JSON serialisation");
+    println!("{json}");
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(_) => Err(Error::AlreadyReported),
+    }
+}
+
+/// Builds the project twice from scratch and checks that both builds write
+/// exactly the same files, as Nix derivations rely on reproducible outputs.
+pub fn check_determinism(options: Options, manifest: Manifest) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let target = options
+        .target
+        .unwrap_or(crate::config::root_config()?.target);
+    let directory = paths.build_directory_for_target(options.mode, target);
+
+    fs::delete_directory(&directory)?;
+    let _ = main(options.clone(), manifest.clone())?;
+    let first = read_files(&directory)?;
+
+    fs::delete_directory(&directory)?;
+    let _ = main(options, manifest)?;
+    let second = read_files(&directory)?;
+
+    let files: BTreeSet<&Utf8PathBuf> = first
+        .keys()
+        .chain(second.keys())
+        .filter(|path| first.get(*path) != second.get(*path))
+        .collect();
+    if files.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::NonDeterministicBuild {
+            files: files.into_iter().cloned().collect(),
+        })
+    }
+}
+
+/// Builds the project and checks that the code generated for the root package
+/// is the same as the copy committed in the given directory, so that
+/// forgetting to regenerate it fails CI.
+///
+/// The compiler's artefacts directory is left out of the comparison, as it
+/// holds caches rather than code.
+pub fn assert_no_diff(options: Options, manifest: Manifest, committed: &Utf8Path) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let target = options.target.unwrap_or(config.target);
+    let generated = paths.build_directory_for_package(options.mode, target, &config.name);
+    let committed = paths.root().join(committed);
+    let _ = main(options, manifest)?;
+
+    let generated_files = read_files(&generated)?
+        .into_iter()
+        .filter(|(path, _)| !path.starts_with(ARTEFACT_DIRECTORY_NAME))
+        .collect();
+    // Nothing having been committed yet is reported as every file being new.
+    let committed_files = if committed.is_dir() {
+        read_files(&committed)?
+    } else {
+        BTreeMap::new()
+    };
+
+    let differences = output_differences(&generated_files, &committed_files);
+    if differences.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::GeneratedOutputDiffers {
+            generated,
+            committed,
+            differences,
+        })
+    }
+}
+
+fn output_differences(
+    generated: &BTreeMap<Utf8PathBuf, Vec<u8>>,
+    committed: &BTreeMap<Utf8PathBuf, Vec<u8>>,
+) -> Vec<OutputDifference> {
+    let paths: BTreeSet<&Utf8PathBuf> = generated.keys().chain(committed.keys()).collect();
+    paths
+        .into_iter()
+        .filter_map(|path| match (generated.get(path), committed.get(path)) {
+            (Some(_), None) => Some(OutputDifference::Added { path: path.clone() }),
+            (None, Some(_)) => Some(OutputDifference::Removed { path: path.clone() }),
+            (Some(new), Some(old)) if new != old => Some(OutputDifference::Changed {
+                path: path.clone(),
+                line: first_different_line(new, old),
+            }),
+            (Some(_), Some(_)) | (None, None) => None,
+        })
+        .collect()
+}
+
+/// The number, starting from 1, of the first line on which the contents
+/// differ.
+fn first_different_line(new: &[u8], old: &[u8]) -> usize {
+    let same = new
+        .iter()
+        .zip(old)
+        .take_while(|(new, old)| new == old)
+        .count();
+    new.iter().take(same).filter(|&&byte| byte == b'\n').count() + 1
+}
+
+/// The contents of every file in the directory, by path relative to it.
+fn read_files(directory: &Utf8Path) -> Result<BTreeMap<Utf8PathBuf, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    for entry in walkdir::WalkDir::new(directory).sort_by_file_name() {
+        let entry = entry.map_err(|error| Error::FileIo {
+            action: FileIoAction::Read,
+            kind: FileKind::Directory,
+            path: directory.to_path_buf(),
+            err: Some(error.to_string()),
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = Utf8PathBuf::from_path_buf(entry.into_path())
+            .map_err(|path| Error::NonUtf8Path { path })?;
+        let relative = path
+            .strip_prefix(directory)
+            .expect("Path in directory")
+            .to_path_buf();
+        let _ = files.insert(relative, fs::read_bytes(&path)?);
+    }
+    Ok(files)
+}
+
 pub(crate) fn main_with_warnings(
     options: Options,
     manifest: Manifest,
     warnings: Rc<dyn WarningEmitterIO>,
 ) -> Result<Built> {
-    let paths = crate::find_project_paths()?;
-    let perform_codegen = options.codegen;
-    let root_config = crate::config::root_config()?;
-    let telemetry: &'static dyn Telemetry = if options.no_print_progress {
+    let telemetry = progress_telemetry(&options);
+    main_with_telemetry(options, manifest, warnings, telemetry)
+}
+
+fn progress_telemetry(options: &Options) -> &'static dyn Telemetry {
+    if options.no_print_progress {
         &NullTelemetry
     } else {
         &cli::Reporter
-    };
-    let io = fs::ProjectIO::new();
-    let start = Instant::now();
+    }
+}
+
+fn main_with_telemetry(
+    options: Options,
+    manifest: Manifest,
+    warnings: Rc<dyn WarningEmitterIO>,
+    telemetry: &dyn Telemetry,
+) -> Result<Built> {
+    let paths = crate::find_project_paths()?;
+    let root_config = crate::config::root_config()?;
     let lock = BuildLock::new_target(
         &paths,
         options.mode,
@@ -46,20 +405,48 @@ pub(crate) fn main_with_warnings(
     )?;
     let current_dir = get_project_root(get_current_directory()?)?;
 
+    let _guard = lock.lock(telemetry);
+    compile(
+        fs::ProjectIO::new(),
+        ProjectPaths::new(current_dir),
+        root_config,
+        options,
+        manifest,
+        warnings,
+        telemetry,
+    )
+}
+
+/// Compiles the project and its dependencies, reading and writing all files
+/// through `io`, so that the whole pipeline can run against an in-memory file
+/// system as well as the real one. The dependencies must already have been
+/// downloaded into the build directory.
+pub fn compile<IO>(
+    io: IO,
+    paths: ProjectPaths,
+    root_config: PackageConfig,
+    options: Options,
+    manifest: Manifest,
+    warnings: Rc<dyn WarningEmitterIO>,
+    telemetry: &dyn Telemetry,
+) -> Result<Built>
+where
+    IO: CommandExecutor + FileSystemReader + FileSystemWriter + BeamCompiler + Clone,
+{
+    let perform_codegen = options.codegen;
+    let start = Instant::now();
+
     tracing::info!("Compiling packages");
-    let result = {
-        let _guard = lock.lock(telemetry);
-        let compiler = ProjectCompiler::new(
-            root_config,
-            options,
-            manifest.packages,
-            telemetry,
-            warnings,
-            ProjectPaths::new(current_dir),
-            io,
-        );
-        compiler.compile()?
-    };
+    let compiler = ProjectCompiler::new(
+        root_config,
+        options,
+        manifest.packages,
+        telemetry,
+        warnings,
+        paths,
+        io,
+    );
+    let result = compiler.compile()?;
 
     match perform_codegen {
         Codegen::All | Codegen::DepsOnly => telemetry.compiled_package(start.elapsed()),
@@ -68,3 +455,102 @@ pub(crate) fn main_with_warnings(
 
     Ok(result)
 }
+
+#[test]
+fn output_differences_test() {
+    let files = |files: &[(&str, &str)]| -> BTreeMap<Utf8PathBuf, Vec<u8>> {
+        files
+            .iter()
+            .map(|(path, contents)| (Utf8PathBuf::from(*path), contents.as_bytes().to_vec()))
+            .collect()
+    };
+    let generated = files(&[
+        ("app.nix", "let\n  x = 1;\nin x\n"),
+        ("app/new.nix", "{ }\n"),
+        ("same.nix", "{ }\n"),
+    ]);
+    let committed = files(&[
+        ("app.nix", "let\n  x = 2;\nin x\n"),
+        ("app/old.nix", "{ }\n"),
+        ("same.nix", "{ }\n"),
+    ]);
+
+    // Paths are ordered by their components, so the files of the `app`
+    // directory come before `app.nix`.
+    assert_eq!(
+        output_differences(&generated, &committed),
+        vec![
+            OutputDifference::Added {
+                path: "app/new.nix".into()
+            },
+            OutputDifference::Removed {
+                path: "app/old.nix".into()
+            },
+            OutputDifference::Changed {
+                path: "app.nix".into(),
+                line: 2
+            },
+        ]
+    );
+    assert_eq!(output_differences(&generated, &generated), vec![]);
+}
+
+#[test]
+fn compile_in_memory() {
+    use glistix_core::{
+        analyse::TargetSupport,
+        build::{Compile, Mode, Target},
+        io::memory::InMemoryFileSystem,
+    };
+
+    let io = InMemoryFileSystem::new();
+    io.write(
+        Utf8Path::new("/app/src/app.gleam"),
+        "import app/wibble\npub fn main() { wibble.wobble() }",
+    )
+    .unwrap();
+    io.write(
+        Utf8Path::new("/app/src/app/wibble.gleam"),
+        "pub fn wobble() { 1 }",
+    )
+    .unwrap();
+    let config = PackageConfig {
+        name: "app".into(),
+        target: Target::Nix,
+        ..Default::default()
+    };
+    let options = Options {
+        mode: Mode::Dev,
+        target: None,
+        compile: Compile::All,
+        codegen: Codegen::All,
+        warnings_as_errors: false,
+        root_target_support: TargetSupport::Enforced,
+        no_print_progress: true,
+        changed_files: None,
+    };
+    let manifest = Manifest {
+        requirements: Default::default(),
+        packages: vec![],
+    };
+    let warnings = Rc::new(VectorWarningEmitterIO::new());
+
+    let built = compile(
+        io.clone(),
+        ProjectPaths::new("/app".into()),
+        config,
+        options,
+        manifest,
+        warnings.clone(),
+        &NullTelemetry,
+    )
+    .unwrap();
+
+    assert_eq!(built.root_package.modules.len(), 2);
+    let output = io
+        .read(Utf8Path::new("/app/build/dev/nix/app/app.nix"))
+        .unwrap();
+    assert!(output.contains("main ="));
+    assert!(io.is_file(Utf8Path::new("/app/build/dev/nix/app/app/wibble.nix")));
+    assert!(warnings.take().is_empty());
+}
@@ -0,0 +1,179 @@
+use camino::Utf8PathBuf;
+use glistix_core::{
+    build::{Mode, Target},
+    paths::ProjectPaths,
+    Result,
+};
+use strum::IntoEnumIterator;
+
+use crate::{config, fs};
+
+/// Which of the compiled packages to delete from the build directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    All,
+    /// Only the dependencies, so the project itself is not rebuilt.
+    Dependencies,
+    /// Only the project itself, keeping its compiled dependencies.
+    Package,
+}
+
+pub fn command(scope: Scope, target: Option<Target>, dry_run: bool) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let package = match scope {
+        Scope::All => None,
+        Scope::Dependencies | Scope::Package => Some(config::root_config()?.name),
+    };
+    let to_delete = paths_to_delete(&paths, scope, package.as_deref(), target)?;
+
+    if dry_run {
+        for path in to_delete {
+            let path = path.strip_prefix(paths.root()).unwrap_or(&path);
+            println!("Would delete {path}");
+        }
+        return Ok(());
+    }
+    for path in to_delete {
+        fs::delete_directory(&path)?;
+    }
+    Ok(())
+}
+
+/// The existing directories of the build directory to delete. Without a
+/// scope or target that is the whole build directory, including the
+/// downloaded packages, otherwise the matching compiled packages of each
+/// mode.
+fn paths_to_delete(
+    paths: &ProjectPaths,
+    scope: Scope,
+    package: Option<&str>,
+    target: Option<Target>,
+) -> Result<Vec<Utf8PathBuf>> {
+    if scope == Scope::All && target.is_none() {
+        let build = paths.build_directory();
+        return Ok(if build.is_dir() { vec![build] } else { vec![] });
+    }
+
+    let targets = match target {
+        Some(target) => vec![target],
+        None => Target::iter().collect(),
+    };
+    let mut to_delete = vec![];
+    for mode in Mode::iter() {
+        for &target in &targets {
+            let directory = paths.build_directory_for_target(mode, target);
+            if !directory.is_dir() {
+                continue;
+            }
+            match (scope, package) {
+                (Scope::All, _) | (_, None) => to_delete.push(directory),
+                (Scope::Package, Some(package)) => {
+                    let directory = directory.join(package);
+                    if directory.is_dir() {
+                        to_delete.push(directory);
+                    }
+                }
+                (Scope::Dependencies, Some(package)) => {
+                    for entry in fs::read_dir(&directory)?.filter_map(|entry| entry.ok()) {
+                        let path = entry.into_path();
+                        if path.is_dir() && path.file_name() != Some(package) {
+                            to_delete.push(path);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    to_delete.sort();
+    Ok(to_delete)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use camino::Utf8Path;
+
+    fn build_directory() -> (tempfile::TempDir, ProjectPaths) {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(tmp_dir.path()).expect("Non Utf-8 Path");
+        let paths = ProjectPaths::new(root.to_path_buf());
+        for (mode, target, package) in [
+            (Mode::Dev, Target::Nix, "app"),
+            (Mode::Dev, Target::Nix, "gleam_stdlib"),
+            (Mode::Dev, Target::Erlang, "app"),
+            (Mode::Dev, Target::Erlang, "gleam_stdlib"),
+            (Mode::Prod, Target::Nix, "gleam_stdlib"),
+        ] {
+            fs::mkdir(paths.build_directory_for_package(mode, target, package)).unwrap();
+        }
+        fs::write(
+            &paths
+                .build_directory_for_target(Mode::Dev, Target::Nix)
+                .join("prelude.nix"),
+            "",
+        )
+        .unwrap();
+        (tmp_dir, paths)
+    }
+
+    fn relative(paths: &ProjectPaths, to_delete: Vec<Utf8PathBuf>) -> Vec<String> {
+        to_delete
+            .iter()
+            .map(|path| path.strip_prefix(paths.root()).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn paths_to_delete_everything() {
+        let (_tmp_dir, paths) = build_directory();
+        let to_delete = paths_to_delete(&paths, Scope::All, None, None).unwrap();
+        assert_eq!(relative(&paths, to_delete), vec!["build"]);
+    }
+
+    #[test]
+    fn paths_to_delete_dependencies() {
+        let (_tmp_dir, paths) = build_directory();
+        let to_delete = paths_to_delete(&paths, Scope::Dependencies, Some("app"), None).unwrap();
+        assert_eq!(
+            relative(&paths, to_delete),
+            vec![
+                "build/dev/erlang/gleam_stdlib",
+                "build/dev/nix/gleam_stdlib",
+                "build/prod/nix/gleam_stdlib",
+            ]
+        );
+    }
+
+    #[test]
+    fn paths_to_delete_package_of_target() {
+        let (_tmp_dir, paths) = build_directory();
+        let to_delete =
+            paths_to_delete(&paths, Scope::Package, Some("app"), Some(Target::Nix)).unwrap();
+        assert_eq!(relative(&paths, to_delete), vec!["build/dev/nix/app"]);
+    }
+
+    #[test]
+    fn paths_to_delete_target() {
+        let (_tmp_dir, paths) = build_directory();
+        let to_delete = paths_to_delete(&paths, Scope::All, None, Some(Target::Nix)).unwrap();
+        assert_eq!(
+            relative(&paths, to_delete),
+            vec!["build/dev/nix", "build/prod/nix"]
+        );
+    }
+
+    #[test]
+    fn paths_to_delete_nothing_built() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(tmp_dir.path()).expect("Non Utf-8 Path");
+        let paths = ProjectPaths::new(root.to_path_buf());
+        assert_eq!(
+            paths_to_delete(&paths, Scope::All, None, None).unwrap(),
+            Vec::<Utf8PathBuf>::new()
+        );
+        assert_eq!(
+            paths_to_delete(&paths, Scope::Dependencies, Some("app"), None).unwrap(),
+            Vec::<Utf8PathBuf>::new()
+        );
+    }
+}
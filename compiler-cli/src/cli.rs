@@ -1,5 +1,6 @@
+use ecow::EcoString;
 use glistix_core::{
-    build::Telemetry,
+    build::{Phase, Telemetry},
     error::{Error, StandardIoAction},
 };
 use hexpm::version::Version;
@@ -35,6 +36,10 @@ impl Telemetry for Reporter {
         print_checking(name);
     }
 
+    fn skipped_modules(&self, modules: &[EcoString]) {
+        print_skipped(modules);
+    }
+
     fn downloading_package(&self, name: &str) {
         print_downloading(name)
     }
@@ -54,6 +59,10 @@ impl Telemetry for Reporter {
     fn waiting_for_build_directory_lock(&self) {
         print_waiting_for_build_directory_lock()
     }
+
+    fn phase_started(&self, _phase: Phase, _package: &str, _module: Option<&str>) {}
+
+    fn phase_finished(&self) {}
 }
 
 pub fn ask(question: &str) -> Result<String, Error> {
@@ -140,6 +149,10 @@ pub(crate) fn print_compiled(duration: Duration) {
     print_colourful_prefix("Compiled", &format!("in {}", seconds(duration)))
 }
 
+pub(crate) fn print_skipped(modules: &[EcoString]) {
+    print_colourful_prefix("Skipped", &modules.join(", "))
+}
+
 pub(crate) fn print_checked(duration: Duration) {
     print_colourful_prefix("Checked", &format!("in {}", seconds(duration)))
 }
@@ -9,22 +9,31 @@ use glistix_core::{
     build::{
         Mode, NullTelemetry, PackageCompiler, StaleTracker, Target, TargetCodegenConfiguration,
     },
-    metadata,
+    config::{ListRepresentation, PackageConfig},
+    metadata, nix,
     paths::{self, ProjectPaths},
     type_::ModuleInterface,
     uid::UniqueIdGenerator,
     warning::WarningEmitter,
     Error, Result,
 };
+use itertools::Itertools;
+use serde_json::json;
 use std::{collections::HashSet, rc::Rc};
 
 pub fn command(options: CompilePackage) -> Result<()> {
     let ids = UniqueIdGenerator::new();
-    let mut type_manifests = load_libraries(&ids, &options.libraries_directory)?;
-    let mut defined_modules = im::HashMap::new();
-    let warnings = WarningEmitter::new(Rc::new(ConsoleWarningEmitter));
     let paths = ProjectPaths::new(options.package_directory.clone());
     let config = config::read(paths.root_config())?;
+    let list_representation = config.glistix.nix.list_representation.unwrap_or_default();
+    let mut type_manifests = load_libraries(
+        &ids,
+        &options.libraries_directory,
+        options.target,
+        list_representation,
+    )?;
+    let mut defined_modules = im::HashMap::new();
+    let warnings = WarningEmitter::new(Rc::new(ConsoleWarningEmitter));
 
     let target = match options.target {
         Target::Erlang => TargetCodegenConfiguration::Erlang { app_file: None },
@@ -38,6 +47,7 @@ pub fn command(options: CompilePackage) -> Result<()> {
             prelude_location: options
                 .nix_prelude
                 .ok_or_else(|| Error::NixPreludeRequired)?,
+            list_representation,
         },
     };
 
@@ -56,7 +66,7 @@ pub fn command(options: CompilePackage) -> Result<()> {
     compiler.write_entrypoint = false;
     compiler.write_metadata = true;
     compiler.compile_beam_bytecode = !options.skip_beam_compilation;
-    compiler
+    let modules = compiler
         .compile(
             &warnings,
             &mut type_manifests,
@@ -65,17 +75,64 @@ pub fn command(options: CompilePackage) -> Result<()> {
             &mut HashSet::new(),
             &NullTelemetry,
         )
-        .into_result()
-        .map(|_| ())
+        .into_result()?;
+
+    if let TargetCodegenConfiguration::Nix {
+        prelude_location,
+        list_representation,
+    } = &target
+    {
+        let module_names = modules.iter().map(|module| module.name.clone()).collect();
+        let metadata = nix_package_metadata(
+            &config,
+            module_names,
+            prelude_location,
+            *list_representation,
+        );
+        let json = serde_json::to_string_pretty(&metadata).expect("Package metadata serialisation");
+        fs::write(
+            &options.output_directory.join(NIX_PACKAGE_METADATA_FILE),
+            &json,
+        )?;
+    }
+    Ok(())
+}
+
+/// The file written next to a package compiled to Nix, describing it so the
+/// `buildGlistixPackage` Nix builder can compose precompiled packages.
+const NIX_PACKAGE_METADATA_FILE: &str = "metadata.json";
+
+fn nix_package_metadata(
+    config: &PackageConfig,
+    mut module_names: Vec<EcoString>,
+    prelude_location: &Utf8Path,
+    list_representation: ListRepresentation,
+) -> serde_json::Value {
+    module_names.sort();
+    let dependencies = config.dependencies.keys().sorted().collect_vec();
+    json!({
+        "name": config.name,
+        "version": config.version.to_string(),
+        "modules": module_names,
+        "dependencies": dependencies,
+        "prelude": prelude_location,
+        "representation_version": nix::REPRESENTATION_VERSION,
+        "list_representation": list_representation.as_str(),
+    })
 }
 
 fn load_libraries(
     ids: &UniqueIdGenerator,
     lib: &Utf8Path,
+    target: Target,
+    list_representation: ListRepresentation,
 ) -> Result<im::HashMap<EcoString, ModuleInterface>> {
     tracing::info!("Reading precompiled module metadata files");
     let mut manifests = im::HashMap::new();
     for lib in fs::read_dir(lib)?.filter_map(Result::ok) {
+        if target == Target::Nix {
+            ensure_compatible_nix_representation(lib.path(), list_representation)?;
+        }
         let path = lib.path().join(paths::ARTEFACT_DIRECTORY_NAME);
         if !path.is_dir() {
             continue;
@@ -89,3 +146,69 @@ fn load_libraries(
 
     Ok(manifests)
 }
+
+/// Nix packages compiled by a compiler using another representation of values
+/// can't be mixed with the package being compiled. Each records the version it
+/// was generated for in its `gleam.nix` file, along with how its lists are
+/// represented.
+fn ensure_compatible_nix_representation(
+    package: &Utf8Path,
+    list_representation: ListRepresentation,
+) -> Result<()> {
+    let reexport = package.join("gleam.nix");
+    if !reexport.is_file() {
+        return Ok(());
+    }
+    let reexport = fs::read(&reexport)?;
+    let package = package.file_name().unwrap_or(package.as_str());
+    let found = nix::prelude_reexport_representation_version(&reexport);
+    if found != Some(nix::REPRESENTATION_VERSION) {
+        return Err(Error::IncompatibleNixRepresentation {
+            package: package.into(),
+            found,
+        });
+    }
+    let found = nix::prelude_reexport_list_representation(&reexport);
+    if found != list_representation {
+        return Err(Error::IncompatibleListRepresentation {
+            package: package.into(),
+            expected: list_representation,
+            found,
+        });
+    }
+    Ok(())
+}
+
+#[test]
+fn nix_package_metadata_test() {
+    use glistix_core::requirement::Requirement;
+
+    let mut config = PackageConfig {
+        name: "wibble".into(),
+        version: hexpm::version::Version::new(1, 2, 3),
+        ..Default::default()
+    };
+    for name in ["gleam_stdlib", "gleam_json"] {
+        let _ = config
+            .dependencies
+            .insert(name.into(), Requirement::hex("~> 1.0"));
+    }
+    let metadata = nix_package_metadata(
+        &config,
+        vec!["wibble/wobble".into(), "wibble".into()],
+        Utf8Path::new("../prelude.nix"),
+        ListRepresentation::Native,
+    );
+    assert_eq!(
+        metadata,
+        json!({
+            "name": "wibble",
+            "version": "1.2.3",
+            "modules": ["wibble", "wibble/wobble"],
+            "dependencies": ["gleam_json", "gleam_stdlib"],
+            "prelude": "../prelude.nix",
+            "representation_version": nix::REPRESENTATION_VERSION,
+            "list_representation": "native",
+        })
+    );
+}
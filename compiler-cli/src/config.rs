@@ -1,4 +1,4 @@
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 
 use glistix_core::{
     config::PackageConfig,
@@ -6,6 +6,7 @@ use glistix_core::{
     manifest::{Manifest, ManifestPackage, ManifestPackageSource},
     paths::ProjectPaths,
 };
+use itertools::Itertools;
 
 use crate::fs::{get_current_directory, get_project_root};
 
@@ -62,7 +63,7 @@ fn package_root(package: &ManifestPackage, project_paths: &ProjectPaths) -> Utf8
 
 pub fn read(config_path: Utf8PathBuf) -> Result<PackageConfig, Error> {
     let toml = crate::fs::read(&config_path)?;
-    let config: PackageConfig = toml::from_str(&toml).map_err(|e| Error::FileIo {
+    let config = PackageConfig::from_toml(&toml).map_err(|e| Error::FileIo {
         action: FileIoAction::Parse,
         kind: FileKind::File,
         path: config_path,
@@ -85,6 +86,104 @@ pub fn ensure_config_exists(paths: &ProjectPaths) -> Result<(), Error> {
     Ok(())
 }
 
+/// Prints the value of a key of gleam.toml, such as `javascript.runtime`.
+pub fn get(key: String) -> Result<(), Error> {
+    let paths = crate::find_project_paths()?;
+    let document = read_document(&paths.root_config())?;
+    let value = get_value(&document, &key).ok_or(Error::ConfigKeyNotFound { key: key.into() })?;
+    println!("{value}");
+    Ok(())
+}
+
+/// Sets the value of a key of gleam.toml, keeping the rest of the file,
+/// including its comments, as it is.
+pub fn set(key: String, value: String) -> Result<(), Error> {
+    let paths = crate::find_project_paths()?;
+    let path = paths.root_config();
+    let mut document = read_document(&path)?;
+    set_value(&mut document, &key, &value)?;
+
+    // Make sure the config is still valid before writing it.
+    let toml = document.to_string();
+    let _ = PackageConfig::from_toml(&toml).map_err(|e| Error::FileIo {
+        action: FileIoAction::Parse,
+        kind: FileKind::File,
+        path: path.clone(),
+        err: Some(e.to_string()),
+    })?;
+    crate::fs::write(&path, &toml)
+}
+
+fn read_document(path: &Utf8Path) -> Result<toml_edit::DocumentMut, Error> {
+    crate::fs::read(path)?
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| Error::FileIo {
+            action: FileIoAction::Parse,
+            kind: FileKind::File,
+            path: path.to_path_buf(),
+            err: Some(e.to_string()),
+        })
+}
+
+/// The value of a key whose tables are separated by dots. Strings are given
+/// without their quotes, and other values as they would be written in TOML.
+fn get_value(document: &toml_edit::DocumentMut, key: &str) -> Option<String> {
+    let mut item = document.as_item();
+    for segment in key.split('.') {
+        item = item.get(segment)?;
+    }
+    match item {
+        toml_edit::Item::None => None,
+        toml_edit::Item::Value(toml_edit::Value::String(string)) => Some(string.value().clone()),
+        toml_edit::Item::Value(value) => Some(value.clone().decorated("", "").to_string()),
+        toml_edit::Item::Table(table) => {
+            let table = toml_edit::DocumentMut::from(table.clone()).to_string();
+            Some(table.trim().to_string())
+        }
+        toml_edit::Item::ArrayOfTables(tables) => Some(tables.clone().into_array().to_string()),
+    }
+}
+
+/// Sets the value of a key whose tables are separated by dots, creating the
+/// tables that don't exist yet. The value is parsed as TOML if possible and is
+/// a string otherwise, so both `deno` and `"deno"` set a string.
+fn set_value(document: &mut toml_edit::DocumentMut, key: &str, value: &str) -> Result<(), Error> {
+    let segments = key.split('.').collect_vec();
+    let Some((name, tables)) = segments.split_last() else {
+        return Err(Error::ConfigKeyNotFound { key: key.into() });
+    };
+
+    let mut table: &mut dyn toml_edit::TableLike = document.as_table_mut();
+    for (index, segment) in tables.iter().enumerate() {
+        table = table
+            .entry(segment)
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .ok_or_else(|| Error::ConfigKeyInNonTable {
+                key: key.into(),
+                table: segments.iter().take(index + 1).join(".").into(),
+            })?;
+    }
+
+    let mut value = match value.trim().parse::<toml_edit::Value>() {
+        Ok(value) => value,
+        Err(_) => toml_edit::Value::from(value),
+    };
+    match table.get_mut(name) {
+        // Keep the whitespace and comments around the value being replaced.
+        Some(toml_edit::Item::Value(existing)) => {
+            *value.decor_mut() = existing.decor().clone();
+            *existing = value;
+        }
+        Some(item) => *item = toml_edit::Item::Value(value),
+        None => {
+            value.decor_mut().clear();
+            let _ = table.insert(name, toml_edit::Item::Value(value));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,4 +246,79 @@ mod tests {
             Utf8PathBuf::from("/app/../wibble")
         );
     }
+
+    const CONFIG: &str = r#"name = "wibble"
+# The version of the package
+version = "1.0.0" # bumped on release
+
+[javascript]
+runtime = "node"
+
+[dependencies]
+gleam_stdlib = ">= 0.34.0 and < 2.0.0"
+"#;
+
+    fn document() -> toml_edit::DocumentMut {
+        CONFIG.parse().expect("valid toml")
+    }
+
+    #[test]
+    fn get_config_values() {
+        let document = document();
+        assert_eq!(get_value(&document, "version"), Some("1.0.0".into()));
+        assert_eq!(
+            get_value(&document, "javascript.runtime"),
+            Some("node".into())
+        );
+        assert_eq!(
+            get_value(&document, "dependencies"),
+            Some(r#"gleam_stdlib = ">= 0.34.0 and < 2.0.0""#.into())
+        );
+        assert_eq!(get_value(&document, "javascript.deno"), None);
+        assert_eq!(get_value(&document, "version.major"), None);
+    }
+
+    #[test]
+    fn set_config_values_keeping_comments() {
+        let mut document = document();
+        set_value(&mut document, "version", "1.1.0").expect("set version");
+        set_value(&mut document, "javascript.runtime", "deno").expect("set runtime");
+        set_value(&mut document, "javascript.typescript_declarations", "true")
+            .expect("set typescript declarations");
+        set_value(
+            &mut document,
+            "erlang.application_start_module",
+            "\"wibble/app\"",
+        )
+        .expect("set start module");
+        assert_eq!(
+            document.to_string(),
+            r#"name = "wibble"
+# The version of the package
+version = "1.1.0" # bumped on release
+
+[javascript]
+runtime = "deno"
+typescript_declarations = true
+
+[dependencies]
+gleam_stdlib = ">= 0.34.0 and < 2.0.0"
+
+[erlang]
+application_start_module = "wibble/app"
+"#
+        );
+    }
+
+    #[test]
+    fn set_config_value_in_non_table() {
+        let mut document = document();
+        assert_eq!(
+            set_value(&mut document, "version.major", "2"),
+            Err(Error::ConfigKeyInNonTable {
+                key: "version.major".into(),
+                table: "version".into(),
+            })
+        );
+    }
 }
@@ -1,6 +1,7 @@
 use std::ops::Deref;
 use std::{
     collections::{HashMap, HashSet},
+    rc::Rc,
     time::Instant,
 };
 
@@ -10,8 +11,8 @@ use flate2::read::GzDecoder;
 use futures::future;
 use glistix_core::{
     build::{Mode, Target, Telemetry},
-    config::PackageConfig,
-    dependency,
+    config::{HexConfig, PackageConfig},
+    dependency::{self, ProvidedSource, VersionPreference},
     error::{FileIoAction, FileKind, StandardIoAction},
     hex::{self, HEXPM_PUBLIC_KEY},
     io::{HttpClient as _, TarUnpacker, WrappedReader},
@@ -25,8 +26,11 @@ use itertools::Itertools;
 use same_file::is_same_file;
 use strum::IntoEnumIterator;
 
+pub mod deprecations;
+pub mod licences;
 #[cfg(test)]
 mod tests;
+pub mod why;
 
 use crate::{
     build_lock::BuildLock,
@@ -48,6 +52,8 @@ pub fn list() -> Result<()> {
         &cli::Reporter::new(),
         UseManifest::Yes,
         Vec::new(),
+        VersionPreference::Newest,
+        None,
     )?;
     list_manifest_packages(std::io::stdout(), manifest)
 }
@@ -69,8 +75,31 @@ pub enum UseManifest {
     No,
 }
 
-pub fn update(packages: Vec<String>) -> Result<()> {
+/// How the packages of Git dependencies are provided when resolving versions.
+#[derive(Debug, Clone, Copy)]
+enum GitDependencies<'a> {
+    /// Their repositories are cloned to read their versions and requirements.
+    Clone,
+    /// They are provided at the versions locked in the manifest, so nothing is
+    /// cloned.
+    Locked(Option<&'a Manifest>),
+}
+
+pub fn update(
+    packages: Vec<String>,
+    resolution_log: Option<Utf8PathBuf>,
+    dry_run: bool,
+    minimal_versions: bool,
+) -> Result<()> {
     let paths = crate::find_project_paths()?;
+    let preference = if minimal_versions {
+        VersionPreference::Oldest
+    } else {
+        VersionPreference::Newest
+    };
+    if dry_run {
+        return preview_update(&paths, packages, preference, resolution_log.as_deref());
+    }
     let use_manifest = if packages.is_empty() {
         UseManifest::No
     } else {
@@ -84,11 +113,126 @@ pub fn update(packages: Vec<String>) -> Result<()> {
         None,
         packages.into_iter().map(EcoString::from).collect(),
         use_manifest,
+        preference,
+        resolution_log.as_deref(),
     )?;
 
     Ok(())
 }
 
+/// Resolve the versions `update` would select and print how they differ from
+/// the ones in the manifest, without writing the manifest or downloading any
+/// packages.
+fn preview_update(
+    paths: &ProjectPaths,
+    packages: Vec<String>,
+    preference: VersionPreference,
+    resolution_log: Option<&Utf8Path>,
+) -> Result<()> {
+    crate::config::ensure_config_exists(paths)?;
+    let config = crate::config::read(paths.root_config())?;
+    let current = if paths.manifest().exists() {
+        Some(read_manifest_from_disc(paths)?)
+    } else {
+        None
+    };
+
+    // As with `update`, the manifest only locks the versions of the packages
+    // that weren't asked to be updated.
+    let packages_to_update = packages.into_iter().map(EcoString::from).collect_vec();
+    let locked = if packages_to_update.is_empty() {
+        None
+    } else {
+        current.as_ref()
+    };
+
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let candidate = resolve_versions(
+        runtime.handle().clone(),
+        Mode::Dev,
+        paths,
+        &config,
+        locked,
+        &cli::Reporter::new(),
+        packages_to_update,
+        preference,
+        GitDependencies::Locked(current.as_ref()),
+        resolution_log,
+    )?;
+
+    print!("{}", update_preview(current.as_ref(), &candidate));
+    Ok(())
+}
+
+/// A table of the packages whose version would change, with links to the
+/// documentation of the new version and to the changes since the current one.
+fn update_preview(current: Option<&Manifest>, candidate: &Manifest) -> String {
+    let current_versions: HashMap<&str, &Version> = current
+        .iter()
+        .flat_map(|manifest| &manifest.packages)
+        .map(|package| (package.name.as_str(), &package.version))
+        .collect();
+    let candidate_versions: HashMap<&str, &Version> = candidate
+        .packages
+        .iter()
+        .map(|package| (package.name.as_str(), &package.version))
+        .collect();
+
+    let changes = current_versions
+        .keys()
+        .chain(candidate_versions.keys())
+        .unique()
+        .filter_map(|&name| {
+            let from = current_versions.get(name).copied();
+            let to = candidate_versions.get(name).copied();
+            (from != to).then_some((name, from, to))
+        })
+        .sorted_by_key(|(name, _, _)| *name)
+        .collect_vec();
+
+    if changes.is_empty() {
+        return "All dependencies are already at the versions `glistix update` would select.\n"
+            .into();
+    }
+
+    let version = |version: Option<&Version>| version.map_or("-".into(), |v| v.to_string());
+    let name_width = changes
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .fold("Package".len(), usize::max);
+    let from_width = changes
+        .iter()
+        .map(|(_, from, _)| version(*from).len())
+        .fold("Current".len(), usize::max);
+
+    let mut text = format!(
+        "{:name_width$}  {:from_width$}   Candidate\n",
+        "Package", "Current"
+    );
+    for (name, from, to) in changes {
+        text.push_str(&format!(
+            "{name:name_width$}  {:from_width$} → {}\n",
+            version(from),
+            version(to)
+        ));
+
+        let is_hex = candidate
+            .packages
+            .iter()
+            .any(|package| package.name == name && package.is_hex());
+        if let (true, Some(to)) = (is_hex, to) {
+            text.push_str(&format!("    docs: https://hexdocs.pm/{name}/{to}/\n"));
+            if let Some(from) = from {
+                text.push_str(&format!(
+                    "    changes: https://diff.hex.pm/diff/{name}/{from}..{to}\n"
+                ));
+            }
+        }
+    }
+    text.push_str("\nThis was a dry run, the manifest has not been changed.\n");
+    text
+}
+
 /// Edit the manifest.toml file in this proejct, removing all extra requirements and packages
 /// that are no longer present in the gleam.toml config.
 pub fn cleanup<Telem: Telemetry>(paths: &ProjectPaths, telemetry: Telem) -> Result<Manifest> {
@@ -220,6 +364,11 @@ pub fn download<Telem: Telemetry>(
     // manifest which will result in the latest versions of the dependency
     // packages being resolved (not the locked ones).
     use_manifest: UseManifest,
+    // Which of the allowed versions are picked if versions are resolved.
+    preference: VersionPreference,
+    // If set, the decisions made while resolving versions are written to
+    // this file.
+    resolution_log: Option<&Utf8Path>,
 ) -> Result<Manifest> {
     let span = tracing::info_span!("download_deps");
     let _enter = span.enter();
@@ -262,9 +411,20 @@ pub fn download<Telem: Telemetry>(
         &telemetry,
         use_manifest,
         packages_to_update,
+        preference,
+        resolution_log,
     )?;
     let local = LocalPackages::read_from_disc(paths)?;
 
+    // Refuse packages the project's dependency policy doesn't allow before
+    // they are added to the project
+    runtime.block_on(check_dependency_policy(
+        paths,
+        fs.clone(),
+        &manifest,
+        &config,
+    ))?;
+
     // Remove any packages that are no longer required due to gleam.toml changes
     remove_extra_packages(paths, &local, &manifest, &telemetry)?;
 
@@ -275,6 +435,7 @@ pub fn download<Telem: Telemetry>(
         &manifest,
         &local,
         project_name,
+        &config.hex,
         &telemetry,
     ))?;
 
@@ -289,12 +450,62 @@ pub fn download<Telem: Telemetry>(
     Ok(manifest)
 }
 
+/// Checks the licences of the Hex packages the project depends on against the
+/// `[dependencies.policy]` section of its config. The licences are read from
+/// the metadata of the packages, which are downloaded to the global cache.
+async fn check_dependency_policy(
+    paths: &ProjectPaths,
+    fs: Box<ProjectIO>,
+    manifest: &Manifest,
+    config: &PackageConfig,
+) -> Result<(), Error> {
+    if config.dependency_policy.allowed_licences.is_none() {
+        return Ok(());
+    }
+
+    let hex_packages = manifest
+        .packages
+        .iter()
+        .filter(|package| package.is_hex() && package.name != config.name)
+        .collect_vec();
+    let repository = crate::hex::repository(
+        &config.hex,
+        hex_packages.iter().map(|package| package.name.as_str()),
+    )?;
+    let http = HttpClient::boxed();
+    let downloader = hex::Downloader::new(
+        fs.clone(),
+        fs,
+        http,
+        Untar::boxed(),
+        repository,
+        paths.clone(),
+    );
+
+    let downloads = hex_packages
+        .iter()
+        .map(|package| downloader.ensure_package_downloaded(package));
+    for result in future::join_all(downloads).await {
+        let _ = result?;
+    }
+
+    let licences = hex_packages
+        .iter()
+        .map(|package| {
+            let licences = downloader.package_licences(&package.name, &package.version)?;
+            Ok((package.name.clone(), licences))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    config.dependency_policy.check_licences(licences)
+}
+
 async fn add_missing_packages<Telem: Telemetry>(
     paths: &ProjectPaths,
     fs: Box<ProjectIO>,
     manifest: &Manifest,
     local: &LocalPackages,
     project_name: EcoString,
+    hex_config: &HexConfig,
     telemetry: &Telem,
 ) -> Result<(), Error> {
     let missing_packages = local.missing_local_packages(manifest, &project_name);
@@ -310,12 +521,26 @@ async fn add_missing_packages<Telem: Telemetry>(
 
     // If we need to download at-least one package
     if missing_hex_packages.peek().is_some() {
+        let missing_hex_packages = missing_hex_packages.collect_vec();
+        let repository = crate::hex::repository(
+            hex_config,
+            missing_hex_packages
+                .iter()
+                .map(|package| package.name.as_str()),
+        )?;
         let http = HttpClient::boxed();
-        let downloader = hex::Downloader::new(fs.clone(), fs, http, Untar::boxed(), paths.clone());
+        let downloader = hex::Downloader::new(
+            fs.clone(),
+            fs,
+            http,
+            Untar::boxed(),
+            repository,
+            paths.clone(),
+        );
         let start = Instant::now();
         telemetry.downloading_package("packages");
         downloader
-            .download_hex_packages(missing_hex_packages, &project_name)
+            .download_hex_packages(missing_hex_packages.into_iter(), &project_name)
             .await?;
         telemetry.packages_downloaded(start, num_to_download);
     }
@@ -452,6 +677,7 @@ impl LocalPackages {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn get_manifest<Telem: Telemetry>(
     paths: &ProjectPaths,
     runtime: tokio::runtime::Handle,
@@ -460,6 +686,8 @@ fn get_manifest<Telem: Telemetry>(
     telemetry: &Telem,
     use_manifest: UseManifest,
     packages_to_update: Vec<EcoString>,
+    preference: VersionPreference,
+    resolution_log: Option<&Utf8Path>,
 ) -> Result<(bool, Manifest)> {
     // If there's no manifest (or we have been asked not to use it) then resolve
     // the versions anew
@@ -476,7 +704,18 @@ fn get_manifest<Telem: Telemetry>(
     };
 
     if should_resolve {
-        let manifest = resolve_versions(runtime, mode, paths, config, None, telemetry, Vec::new())?;
+        let manifest = resolve_versions(
+            runtime,
+            mode,
+            paths,
+            config,
+            None,
+            telemetry,
+            Vec::new(),
+            preference,
+            GitDependencies::Clone,
+            resolution_log,
+        )?;
         return Ok((true, manifest));
     }
 
@@ -492,6 +731,12 @@ fn get_manifest<Telem: Telemetry>(
         )?
     {
         tracing::debug!("manifest_up_to_date");
+        if let Some(path) = resolution_log {
+            fs::write(
+                path,
+                "The manifest is up to date, so no versions were resolved.\n",
+            )?;
+        }
         Ok((false, manifest))
     } else {
         tracing::debug!("manifest_outdated");
@@ -503,6 +748,9 @@ fn get_manifest<Telem: Telemetry>(
             Some(&manifest),
             telemetry,
             packages_to_update,
+            preference,
+            GitDependencies::Clone,
+            resolution_log,
         )?;
         Ok((true, manifest))
     }
@@ -603,6 +851,13 @@ impl ProvidedPackage {
 }
 
 impl ProvidedPackageSource {
+    fn provided_source(&self) -> ProvidedSource {
+        match self {
+            Self::Git { .. } => ProvidedSource::Git,
+            Self::Local { .. } => ProvidedSource::Path,
+        }
+    }
+
     fn to_manifest_package_source(&self) -> ManifestPackageSource {
         match self {
             Self::Git { repo, commit } => ManifestPackageSource::Git {
@@ -650,6 +905,7 @@ impl PartialEq for ProvidedPackageSource {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn resolve_versions<Telem: Telemetry>(
     runtime: tokio::runtime::Handle,
     mode: Mode,
@@ -658,10 +914,17 @@ fn resolve_versions<Telem: Telemetry>(
     manifest: Option<&Manifest>,
     telemetry: &Telem,
     packages_to_update: Vec<EcoString>,
+    preference: VersionPreference,
+    git_dependencies: GitDependencies<'_>,
+    resolution_log: Option<&Utf8Path>,
 ) -> Result<Manifest, Error> {
     telemetry.resolving_package_versions();
+    // Dependencies restricted to some targets are resolved for every target,
+    // so that the manifest doesn't depend on the one being compiled for, and
+    // are only skipped when compiling.
     let dependencies = config.dependencies_for(mode)?;
     let mut locked = config.locked(manifest)?;
+    let repository = Rc::new(crate::hex::LazyRepository::new(&config.hex));
 
     if !packages_to_update.is_empty() {
         unlock_packages(&mut locked, &packages_to_update, manifest)?;
@@ -682,12 +945,17 @@ fn resolve_versions<Telem: Telemetry>(
                 project_paths.root(),
                 project_paths,
                 config,
+                git_dependencies,
                 &mut provided_packages,
                 &mut vec![],
             )?,
-            Requirement::Git { git } => {
-                provide_git_package(name.clone(), &git, project_paths, &mut provided_packages)?
-            }
+            Requirement::Git { git } => provide_git_package(
+                name.clone(),
+                &git,
+                project_paths,
+                git_dependencies,
+                &mut provided_packages,
+            )?,
         };
         let _ = root_requirements.insert(name, version);
     }
@@ -695,23 +963,39 @@ fn resolve_versions<Telem: Telemetry>(
     // Convert provided packages into hex packages for pub-grub resolve
     let provided_hex_packages = provided_packages
         .iter()
-        .map(|(name, package)| (name.clone(), package.to_hex_package(name)))
+        .map(|(name, package)| {
+            let source = if config.glistix.preview.local_overrides.contains(name) {
+                ProvidedSource::LocalOverride
+            } else {
+                package.source.provided_source()
+            };
+            (name.clone(), (package.to_hex_package(name), source))
+        })
         .collect();
 
+    let log = resolution_log.map(|_| dependency::ResolutionLog::default());
     let resolved = dependency::resolve_versions(
-        PackageFetcher::boxed(runtime.clone()),
+        PackageFetcher::boxed(runtime.clone(), repository.clone()),
         provided_hex_packages,
         config.name.clone(),
         root_requirements.into_iter(),
         &locked,
-    )?;
+        preference,
+        log.as_ref(),
+    );
+    // The log is written even if resolution failed, as that is when it is
+    // most useful.
+    if let (Some(path), Some(log)) = (resolution_log, &log) {
+        fs::write(path, &log.to_text())?;
+    }
+    let resolved = resolved?;
+    let repository = repository.for_packages(resolved.keys().map(|name| name.as_str()))?;
 
     // Convert the hex packages and local packages into manifest packages
-    let manifest_packages = runtime.block_on(future::try_join_all(
-        resolved
-            .into_iter()
-            .map(|(name, version)| lookup_package(name, version, &provided_packages)),
-    ))?;
+    let manifest_packages =
+        runtime.block_on(future::try_join_all(resolved.into_iter().map(
+            |(name, version)| lookup_package(name, version, &provided_packages, &repository),
+        )))?;
 
     let manifest = Manifest {
         packages: manifest_packages,
@@ -728,6 +1012,7 @@ fn provide_local_package(
     parent_path: &Utf8Path,
     project_paths: &ProjectPaths,
     root_config: &PackageConfig,
+    git_dependencies: GitDependencies<'_>,
     provided: &mut HashMap<EcoString, ProvidedPackage>,
     parents: &mut Vec<EcoString>,
 ) -> Result<hexpm::version::Range> {
@@ -743,7 +1028,13 @@ fn provide_local_package(
             match root_override {
                 Requirement::Hex { version } => return Ok(version.clone()),
                 Requirement::Git { git } => {
-                    return provide_git_package(package_name, git, project_paths, provided)
+                    return provide_git_package(
+                        package_name,
+                        git,
+                        project_paths,
+                        git_dependencies,
+                        provided,
+                    )
                 }
                 Requirement::Path { path } => {
                     // Pretend we're on the root to apply patch
@@ -771,6 +1062,7 @@ fn provide_local_package(
         package_source,
         project_paths,
         root_config,
+        git_dependencies,
         provided,
         parents,
     )
@@ -778,11 +1070,15 @@ fn provide_local_package(
 
 /// Provide a package from a git repository
 fn provide_git_package(
-    _package_name: EcoString,
+    package_name: EcoString,
     _repo: &str,
     _project_paths: &ProjectPaths,
-    _provided: &mut HashMap<EcoString, ProvidedPackage>,
+    git_dependencies: GitDependencies<'_>,
+    provided: &mut HashMap<EcoString, ProvidedPackage>,
 ) -> Result<hexpm::version::Range> {
+    if let GitDependencies::Locked(manifest) = git_dependencies {
+        return provide_locked_git_package(package_name, manifest, provided);
+    }
     let _git = ProvidedPackageSource::Git {
         repo: "repo".into(),
         commit: "commit".into(),
@@ -790,6 +1086,48 @@ fn provide_git_package(
     Err(Error::GitDependencyUnsupported)
 }
 
+/// Provide a package from a git repository at the version locked in the
+/// manifest, without cloning the repository
+fn provide_locked_git_package(
+    package_name: EcoString,
+    manifest: Option<&Manifest>,
+    provided: &mut HashMap<EcoString, ProvidedPackage>,
+) -> Result<hexpm::version::Range> {
+    let locked = manifest
+        .into_iter()
+        .flat_map(|manifest| &manifest.packages)
+        .find(|package| package.name == package_name);
+    let Some(ManifestPackage {
+        version,
+        requirements,
+        source: ManifestPackageSource::Git { repo, commit },
+        ..
+    }) = locked
+    else {
+        return Err(Error::GitDependencyNotLocked {
+            package: package_name,
+        });
+    };
+    // The manifest only records the names of the package's requirements, so
+    // any version of them is accepted.
+    let requirements = requirements
+        .iter()
+        .map(|name| (name.clone(), hexpm::version::Range::new(">= 0.0.0".into())))
+        .collect();
+    let _ = provided.insert(
+        package_name,
+        ProvidedPackage {
+            version: version.clone(),
+            source: ProvidedPackageSource::Git {
+                repo: repo.clone(),
+                commit: commit.clone(),
+            },
+            requirements,
+        },
+    );
+    Ok(hexpm::version::Range::new(format!("== {version}")))
+}
+
 /// Adds a gleam project located at a specific path to the list of "provided packages"
 fn provide_package(
     package_name: EcoString,
@@ -797,6 +1135,7 @@ fn provide_package(
     package_source: ProvidedPackageSource,
     project_paths: &ProjectPaths,
     root_config: &PackageConfig,
+    git_dependencies: GitDependencies<'_>,
     provided: &mut HashMap<EcoString, ProvidedPackage>,
     parents: &mut Vec<EcoString>,
 ) -> Result<hexpm::version::Range> {
@@ -853,13 +1192,18 @@ fn provide_package(
                     &package_path,
                     project_paths,
                     root_config,
+                    git_dependencies,
                     provided,
                     parents,
                 )?
             }
-            Requirement::Git { git } => {
-                provide_git_package(name.clone(), &git, project_paths, provided)?
-            }
+            Requirement::Git { git } => provide_git_package(
+                name.clone(),
+                &git,
+                project_paths,
+                git_dependencies,
+                provided,
+            )?,
         };
         let _ = requirements.insert(name, version);
     }
@@ -936,13 +1280,13 @@ async fn lookup_package(
     name: String,
     version: Version,
     provided: &HashMap<EcoString, ProvidedPackage>,
+    repository: &hex::Repository,
 ) -> Result<ManifestPackage> {
     match provided.get(name.as_str()) {
         Some(provided_package) => Ok(provided_package.to_manifest_package(name.as_str())),
         None => {
-            let config = hexpm::Config::new();
             let release =
-                hex::get_package_release(&name, &version, &config, &HttpClient::new()).await?;
+                hex::get_package_release(&name, &version, repository, &HttpClient::new()).await?;
             let build_tools = release
                 .meta
                 .build_tools
@@ -971,13 +1315,18 @@ async fn lookup_package(
 struct PackageFetcher {
     runtime: tokio::runtime::Handle,
     http: HttpClient,
+    repository: Rc<crate::hex::LazyRepository>,
 }
 
 impl PackageFetcher {
-    pub fn boxed(runtime: tokio::runtime::Handle) -> Box<Self> {
+    pub fn boxed(
+        runtime: tokio::runtime::Handle,
+        repository: Rc<crate::hex::LazyRepository>,
+    ) -> Box<Self> {
         Box::new(Self {
             runtime,
             http: HttpClient::new(),
+            repository,
         })
     }
 }
@@ -1014,15 +1363,19 @@ impl dependency::PackageFetcher for PackageFetcher {
         package: &str,
     ) -> Result<hexpm::Package, Box<dyn std::error::Error>> {
         tracing::debug!(package = package, "looking_up_hex_package");
-        let config = hexpm::Config::new();
-        let request = hexpm::get_package_request(package, None, &config);
+        let repository = self.repository.for_packages([package])?;
+        let (config, name) = repository.config_for(package);
+        let request = hexpm::get_package_request(name, repository.api_key(), &config);
         let response = self
             .runtime
             .block_on(self.http.send(request))
             .map_err(Box::new)?;
 
         match hexpm::get_package_response(response, HEXPM_PUBLIC_KEY) {
-            Ok(a) => Ok(a),
+            Ok(mut a) => {
+                hex::qualify_organisation_dependencies(&mut a);
+                Ok(a)
+            }
             Err(e) => match e {
                 hexpm::ApiError::NotFound => {
                     Err(format!("I couldn't find a package called `{}`", package).into())
@@ -0,0 +1,360 @@
+//! The `glistix deps deprecations` command, which reports the deprecated
+//! modules, types and values of dependency packages that the project uses.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+use glistix_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Compile, Mode, Options},
+    line_numbers::LineNumbers,
+    paths::ProjectPaths,
+    type_,
+    warning::{VectorWarningEmitterIO, Warning},
+    Result,
+};
+use itertools::Itertools;
+use serde_json::json;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ItemKind {
+    Module,
+    Type,
+    Value,
+}
+
+impl ItemKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Module => "module",
+            Self::Type => "type",
+            Self::Value => "value",
+        }
+    }
+}
+
+/// A deprecated module, or a deprecated definition of a module, belonging to
+/// a dependency package.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct DeprecatedItem {
+    package: EcoString,
+    module: EcoString,
+    /// This is `None` when the whole module is deprecated.
+    name: Option<EcoString>,
+    kind: ItemKind,
+    message: EcoString,
+}
+
+impl DeprecatedItem {
+    fn qualified_name(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{}.{name}", self.module),
+            None => self.module.to_string(),
+        }
+    }
+}
+
+/// A place in the project where a deprecated item is used, with the path
+/// relative to the root of the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Usage {
+    path: Utf8PathBuf,
+    line: u32,
+    column: u32,
+}
+
+/// The usages of each deprecated item, ordered by package, then by module.
+type Report = BTreeMap<DeprecatedItem, Vec<Usage>>;
+
+pub fn command(json: bool) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let manifest = crate::build::download_dependencies(crate::cli::Reporter::new())?;
+    let warnings = Rc::new(VectorWarningEmitterIO::new());
+    let built = crate::build::main_with_warnings(
+        Options {
+            mode: Mode::Dev,
+            target: None,
+            codegen: Codegen::None,
+            compile: Compile::All,
+            warnings_as_errors: false,
+            root_target_support: TargetSupport::Enforced,
+            no_print_progress: true,
+            changed_files: None,
+        },
+        manifest,
+        warnings.clone(),
+    )?;
+
+    let module_packages = built
+        .module_interfaces()
+        .values()
+        .map(|module| (module.name.clone(), module.package.clone()))
+        .collect();
+    let report = deprecations(
+        &paths,
+        &built.root_package.config.name,
+        &module_packages,
+        warnings.take(),
+    );
+
+    if json {
+        let json = serde_json::to_string_pretty(&json_report(&report))
+            .expect("Deprecations serialisation");
+        println!("{json}");
+    } else {
+        print!("{}", text_report(&report));
+    }
+    Ok(())
+}
+
+/// Collects the deprecated items of other packages from the warnings emitted
+/// for the modules of the root package.
+fn deprecations(
+    paths: &ProjectPaths,
+    root_package: &str,
+    module_packages: &HashMap<EcoString, EcoString>,
+    warnings: Vec<Warning>,
+) -> Report {
+    let (src, test) = (paths.src_directory(), paths.test_directory());
+    let mut report = Report::new();
+
+    for warning in warnings {
+        let Warning::Type {
+            path,
+            src: code,
+            warning,
+        } = warning
+        else {
+            continue;
+        };
+        if !path.starts_with(&src) && !path.starts_with(&test) {
+            continue;
+        }
+
+        let (location, module, name, kind, message) = match warning {
+            type_::Warning::DeprecatedItem {
+                location,
+                message,
+                layer,
+                module,
+                name,
+            } => {
+                let kind = if layer.is_value() {
+                    ItemKind::Value
+                } else {
+                    ItemKind::Type
+                };
+                (location, module, Some(name), kind, message)
+            }
+            type_::Warning::DeprecatedModule {
+                location,
+                name,
+                message,
+            } => (location, name, None, ItemKind::Module, message),
+            _ => continue,
+        };
+
+        // Deprecated definitions of the project itself are left to the usual
+        // warnings.
+        let Some(package) = module_packages.get(&module) else {
+            continue;
+        };
+        if package == root_package {
+            continue;
+        }
+
+        let position = LineNumbers::new(&code).line_and_column_number(location.start);
+        let path = path
+            .strip_prefix(paths.root())
+            .map(|path| path.to_path_buf())
+            .unwrap_or(path);
+        report
+            .entry(DeprecatedItem {
+                package: package.clone(),
+                module,
+                name,
+                kind,
+                message,
+            })
+            .or_default()
+            .push(Usage {
+                path,
+                line: position.line,
+                column: position.column,
+            });
+    }
+    report
+}
+
+fn text_report(report: &Report) -> String {
+    if report.is_empty() {
+        return "No deprecated items of dependencies are used.\n".into();
+    }
+
+    let mut text = String::new();
+    for (package, items) in &report.iter().chunk_by(|(item, _)| item.package.clone()) {
+        text.push_str(&format!("{package}\n"));
+        for (item, usages) in items {
+            text.push_str(&format!(
+                "  {} ({}): {}\n",
+                item.qualified_name(),
+                item.kind.as_str(),
+                item.message
+            ));
+            for usage in usages {
+                text.push_str(&format!(
+                    "    {}:{}:{}\n",
+                    usage.path, usage.line, usage.column
+                ));
+            }
+        }
+    }
+    text
+}
+
+fn json_report(report: &Report) -> serde_json::Value {
+    let packages = report
+        .iter()
+        .chunk_by(|(item, _)| item.package.clone())
+        .into_iter()
+        .map(|(package, items)| {
+            let items = items
+                .map(|(item, usages)| {
+                    let usages = usages
+                        .iter()
+                        .map(|usage| {
+                            json!({
+                                "path": usage.path,
+                                "line": usage.line,
+                                "column": usage.column,
+                            })
+                        })
+                        .collect_vec();
+                    json!({
+                        "module": item.module,
+                        "name": item.name,
+                        "kind": item.kind.as_str(),
+                        "message": item.message,
+                        "usages": usages,
+                    })
+                })
+                .collect_vec();
+            json!({ "name": package, "items": items })
+        })
+        .collect_vec();
+    json!({ "packages": packages })
+}
+
+#[cfg(test)]
+fn type_warning(path: &str, src: &str, warning: type_::Warning) -> Warning {
+    Warning::Type {
+        path: Utf8PathBuf::from(path),
+        src: src.into(),
+        warning,
+    }
+}
+
+#[test]
+fn deprecations_are_grouped_by_package() {
+    use glistix_core::ast::{Layer, SrcSpan};
+
+    let paths = ProjectPaths::new("/app".into());
+    let module_packages = HashMap::from([
+        ("app".into(), "app".into()),
+        ("app/old".into(), "app".into()),
+        ("gleam/dict".into(), "gleam_stdlib".into()),
+        ("wibble/old".into(), "wibble".into()),
+    ]);
+    let src = "import wibble/old\nimport gleam/dict\n\npub fn main() {\n  dict.fold\n}\n";
+    let dict_fold = |start| type_::Warning::DeprecatedItem {
+        location: SrcSpan::new(start, start + 9),
+        message: "Use dict.each".into(),
+        layer: Layer::Value,
+        module: "gleam/dict".into(),
+        name: "fold".into(),
+    };
+    let warnings = vec![
+        type_warning("/app/src/app.gleam", src, dict_fold(55)),
+        type_warning(
+            "/app/src/app.gleam",
+            src,
+            type_::Warning::DeprecatedModule {
+                location: SrcSpan::new(0, 17),
+                name: "wibble/old".into(),
+                message: "Use wibble/new".into(),
+            },
+        ),
+        type_warning("/app/test/app_test.gleam", src, dict_fold(55)),
+        // Items of the project itself aren't reported.
+        type_warning(
+            "/app/src/app.gleam",
+            src,
+            type_::Warning::DeprecatedModule {
+                location: SrcSpan::new(0, 17),
+                name: "app/old".into(),
+                message: "Gone".into(),
+            },
+        ),
+        // Neither are warnings for dependency packages.
+        type_warning(
+            "/app/build/packages/wibble/src/wibble.gleam",
+            src,
+            dict_fold(55),
+        ),
+    ];
+
+    let report = deprecations(&paths, "app", &module_packages, warnings);
+    assert_eq!(
+        text_report(&report),
+        "gleam_stdlib
+  gleam/dict.fold (value): Use dict.each
+    src/app.gleam:5:3
+    test/app_test.gleam:5:3
+wibble
+  wibble/old (module): Use wibble/new
+    src/app.gleam:1:1
+"
+    );
+    assert_eq!(
+        json_report(&report),
+        json!({
+            "packages": [
+                {
+                    "name": "gleam_stdlib",
+                    "items": [{
+                        "module": "gleam/dict",
+                        "name": "fold",
+                        "kind": "value",
+                        "message": "Use dict.each",
+                        "usages": [
+                            { "path": "src/app.gleam", "line": 5, "column": 3 },
+                            { "path": "test/app_test.gleam", "line": 5, "column": 3 },
+                        ],
+                    }],
+                },
+                {
+                    "name": "wibble",
+                    "items": [{
+                        "module": "wibble/old",
+                        "name": null,
+                        "kind": "module",
+                        "message": "Use wibble/new",
+                        "usages": [{ "path": "src/app.gleam", "line": 1, "column": 1 }],
+                    }],
+                },
+            ]
+        })
+    );
+}
+
+#[test]
+fn no_deprecations() {
+    assert_eq!(
+        text_report(&Report::new()),
+        "No deprecated items of dependencies are used.\n"
+    );
+}
@@ -0,0 +1,295 @@
+//! The `glistix deps licenses` command, which reports the licences of the
+//! packages the project depends on.
+
+use std::collections::BTreeSet;
+
+use ecow::EcoString;
+use futures::future;
+use glistix_core::{
+    build::Mode,
+    config::PackageConfig,
+    dependency::VersionPreference,
+    hex,
+    manifest::{Manifest, ManifestPackage},
+    paths::{self, ProjectPaths},
+    Result,
+};
+use itertools::Itertools;
+use serde_json::json;
+
+use crate::{cli, http::HttpClient};
+
+use super::UseManifest;
+
+/// The value SPDX uses for licences which aren't known.
+const NO_ASSERTION: &str = "NOASSERTION";
+
+/// The licences declared by a package the project depends on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PackageLicences {
+    name: EcoString,
+    version: String,
+    licences: Vec<EcoString>,
+}
+
+pub fn command(json: bool) -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let (_, manifest) = super::get_manifest(
+        &paths,
+        runtime.handle().clone(),
+        Mode::Dev,
+        &config,
+        &cli::Reporter::new(),
+        UseManifest::Yes,
+        Vec::new(),
+        VersionPreference::Newest,
+        None,
+    )?;
+
+    let packages = runtime.block_on(package_licences(&paths, &config, &manifest))?;
+    let root_licences = config
+        .licences
+        .iter()
+        .map(|licence| EcoString::from(licence.to_string()))
+        .collect_vec();
+    let expression = summary_expression(
+        std::iter::once(root_licences.as_slice())
+            .chain(packages.iter().map(|package| package.licences.as_slice())),
+    );
+
+    if json {
+        let report = json_report(&packages, expression.as_deref());
+        let json = serde_json::to_string_pretty(&report).expect("Licences serialisation");
+        println!("{json}");
+    } else {
+        print!("{}", text_report(&packages, expression.as_deref()));
+    }
+    Ok(())
+}
+
+/// The licences of every package in the manifest, sorted by name.
+async fn package_licences(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    manifest: &Manifest,
+) -> Result<Vec<PackageLicences>> {
+    let repository = crate::hex::repository(
+        &config.hex,
+        manifest
+            .packages
+            .iter()
+            .filter(|package| package.is_hex())
+            .map(|package| package.name.as_str()),
+    )?;
+    let repository = &repository;
+    let http = &HttpClient::new();
+
+    let lookups = manifest
+        .packages
+        .iter()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .map(|package| async move {
+            let licences = if package.is_hex() {
+                hex_package_licences(package, repository, http).await
+            } else {
+                Ok(local_package_licences(paths, package))
+            };
+            licences.map(|licences| PackageLicences {
+                name: package.name.clone(),
+                version: package.version.to_string(),
+                licences,
+            })
+        });
+    future::join_all(lookups).await.into_iter().collect()
+}
+
+/// The licences of a Hex package. These are looked up with the Hex API the
+/// first time they are needed, and read from the global package cache after
+/// that.
+async fn hex_package_licences(
+    package: &ManifestPackage,
+    repository: &hex::Repository,
+    http: &HttpClient,
+) -> Result<Vec<EcoString>> {
+    let path =
+        paths::global_package_cache_package_licences(&package.name, &package.version.to_string());
+    if path.is_file() {
+        // A cache file which can't be read is looked up again.
+        if let Ok(licences) = serde_json::from_str(&crate::fs::read(&path)?) {
+            return Ok(licences);
+        }
+    }
+
+    let licences = hex::get_package_licences(&package.name, repository, http).await?;
+    let json = serde_json::to_string(&licences).expect("Licences serialisation");
+    crate::fs::write(&path, &json)?;
+    Ok(licences)
+}
+
+/// The licences of a package which isn't from Hex, taken from the config of
+/// its downloaded copy, which packages not built with Gleam or Glistix don't
+/// have.
+fn local_package_licences(paths: &ProjectPaths, package: &ManifestPackage) -> Vec<EcoString> {
+    let config_path = paths
+        .build_packages_package(&package.name)
+        .join("gleam.toml");
+    crate::config::read(config_path)
+        .map(|config| {
+            config
+                .licences
+                .iter()
+                .map(|licence| EcoString::from(licence.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// An SPDX expression covering all of the given lists of licences. A package
+/// declaring several licences can be used under any one of them, while the
+/// licences of all packages apply together. Packages without any licences are
+/// left out, as nothing is known about them.
+fn summary_expression<'a>(licences: impl IntoIterator<Item = &'a [EcoString]>) -> Option<String> {
+    let expressions: BTreeSet<String> = licences
+        .into_iter()
+        .filter_map(|licences| match licences {
+            [] => None,
+            [licence] => Some(licence.to_string()),
+            _ => Some(
+                licences
+                    .iter()
+                    .map(|licence| parenthesised(licence))
+                    .join(" OR "),
+            ),
+        })
+        .collect();
+
+    match expressions.len() {
+        0 => None,
+        1 => expressions.into_iter().next(),
+        _ => Some(
+            expressions
+                .iter()
+                .map(|expression| parenthesised(expression))
+                .join(" AND "),
+        ),
+    }
+}
+
+/// Wraps an SPDX expression in parentheses unless it is a single licence.
+fn parenthesised(expression: &str) -> String {
+    if expression.contains(' ') {
+        format!("({expression})")
+    } else {
+        expression.into()
+    }
+}
+
+fn text_report(packages: &[PackageLicences], expression: Option<&str>) -> String {
+    let mut report = String::new();
+    for package in packages {
+        let licences = if package.licences.is_empty() {
+            "unknown".into()
+        } else {
+            package.licences.iter().join(", ")
+        };
+        report.push_str(&format!(
+            "{} {} {licences}\n",
+            package.name, package.version
+        ));
+    }
+    report.push_str(&format!(
+        "\nSPDX expression: {}\n",
+        expression.unwrap_or(NO_ASSERTION)
+    ));
+    report
+}
+
+fn json_report(packages: &[PackageLicences], expression: Option<&str>) -> serde_json::Value {
+    let packages = packages
+        .iter()
+        .map(|package| {
+            json!({
+                "name": package.name,
+                "version": package.version,
+                "licenses": package.licences,
+            })
+        })
+        .collect_vec();
+    json!({
+        "packages": packages,
+        "expression": expression.unwrap_or(NO_ASSERTION),
+    })
+}
+
+#[cfg(test)]
+fn licences(licences: &[&str]) -> Vec<EcoString> {
+    licences.iter().map(|&licence| licence.into()).collect()
+}
+
+#[test]
+fn summary_expression_of_single_licences() {
+    let mit = licences(&["MIT"]);
+    let apache = licences(&["Apache-2.0"]);
+    assert_eq!(
+        summary_expression([mit.as_slice(), apache.as_slice(), mit.as_slice()]),
+        Some("Apache-2.0 AND MIT".into())
+    );
+}
+
+#[test]
+fn summary_expression_with_a_choice_of_licences() {
+    let dual = licences(&["MIT", "Apache-2.0"]);
+    let exception = licences(&["Apache-2.0 WITH LLVM-exception", "MPL-2.0"]);
+    let unknown = licences(&[]);
+    assert_eq!(
+        summary_expression([dual.as_slice(), unknown.as_slice()]),
+        Some("MIT OR Apache-2.0".into())
+    );
+    assert_eq!(
+        summary_expression([dual.as_slice(), exception.as_slice()]),
+        Some("((Apache-2.0 WITH LLVM-exception) OR MPL-2.0) AND (MIT OR Apache-2.0)".into())
+    );
+}
+
+#[test]
+fn summary_expression_without_licences() {
+    let unknown = licences(&[]);
+    assert_eq!(summary_expression([unknown.as_slice()]), None);
+}
+
+#[test]
+fn licences_reports() {
+    let packages = [
+        PackageLicences {
+            name: "gleam_stdlib".into(),
+            version: "0.40.0".into(),
+            licences: licences(&["Apache-2.0"]),
+        },
+        PackageLicences {
+            name: "wibble".into(),
+            version: "1.0.0".into(),
+            licences: licences(&[]),
+        },
+    ];
+
+    assert_eq!(
+        text_report(&packages, Some("Apache-2.0")),
+        "gleam_stdlib 0.40.0 Apache-2.0
+wibble 1.0.0 unknown
+
+SPDX expression: Apache-2.0
+"
+    );
+    assert_eq!(
+        json_report(&packages, None),
+        json!({
+            "packages": [
+                { "name": "gleam_stdlib", "version": "0.40.0", "licenses": ["Apache-2.0"] },
+                { "name": "wibble", "version": "1.0.0", "licenses": [] },
+            ],
+            "expression": "NOASSERTION",
+        })
+    );
+}
@@ -261,6 +261,7 @@ fn provide_wrong_package() {
         Utf8Path::new("./"),
         &project_paths,
         &PackageConfig::default(),
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
     );
@@ -286,6 +287,7 @@ fn provide_existing_package() {
         Utf8Path::new("./"),
         &project_paths,
         &PackageConfig::default(),
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
     );
@@ -297,6 +299,7 @@ fn provide_existing_package() {
         Utf8Path::new("./"),
         &project_paths,
         &PackageConfig::default(),
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
     );
@@ -313,6 +316,7 @@ fn provide_conflicting_package() {
         Utf8Path::new("./"),
         &project_paths,
         &PackageConfig::default(),
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
     );
@@ -326,6 +330,7 @@ fn provide_conflicting_package() {
         },
         &project_paths,
         &PackageConfig::default(),
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
     );
@@ -361,6 +366,7 @@ fn glistix_provide_conflicting_package_patched_by_root() {
         Utf8Path::new("./"),
         &project_paths,
         &root_config,
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
     );
@@ -372,6 +378,7 @@ fn glistix_provide_conflicting_package_patched_by_root() {
         Utf8Path::new("./"),
         &project_paths,
         &root_config,
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
     );
@@ -398,6 +405,7 @@ fn glistix_provide_conflicting_package_patched_by_root_but_not_root_dependency()
         Utf8Path::new("./"),
         &project_paths,
         &root_config,
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
     );
@@ -409,6 +417,7 @@ fn glistix_provide_conflicting_package_patched_by_root_but_not_root_dependency()
         Utf8Path::new("./"),
         &project_paths,
         &root_config,
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
     );
@@ -430,6 +439,7 @@ fn provided_is_absolute() {
         Utf8Path::new("./"),
         &project_paths,
         &PackageConfig::default(),
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
     );
@@ -452,6 +462,7 @@ fn provided_recursive() {
         Utf8Path::new("./"),
         &project_paths,
         &PackageConfig::default(),
+        GitDependencies::Clone,
         &mut provided,
         &mut vec!["root".into(), "hello_world".into(), "subpackage".into()],
     );
@@ -1023,7 +1034,10 @@ fn package_config(
         gleam_version: None,
         licences: vec![],
         description: "".into(),
-        documentation: Docs { pages: vec![] },
+        documentation: Docs {
+            pages: vec![],
+            assets: vec![],
+        },
         dependencies,
         dev_dependencies,
         repository: Repository::None,
@@ -1049,8 +1063,13 @@ fn package_config(
                 location: None,
             },
         },
+        format: Default::default(),
         target: Target::Erlang,
         internal_modules: None,
+        registry: None,
+        dependency_policy: Default::default(),
+        dependency_targets: Default::default(),
+        hex: Default::default(),
         glistix: Default::default(),
     }
 }
@@ -1174,3 +1193,224 @@ fn test_remove_package_that_is_also_a_transitive_dependency() {
     assert_eq!(manifest.requirements, config.dependencies);
     assert_eq!(manifest.packages, manifest_copy.packages);
 }
+
+#[test]
+fn update_preview_lists_changed_versions() {
+    let current = Manifest {
+        requirements: HashMap::new(),
+        packages: vec![
+            manifest_package("gleam_stdlib", "0.34.0", vec![]),
+            manifest_package("gleam_json", "1.0.0", vec![]),
+            manifest_package("old_dependency", "2.1.0", vec![]),
+        ],
+    };
+    let candidate = Manifest {
+        requirements: HashMap::new(),
+        packages: vec![
+            manifest_package("gleam_stdlib", "0.40.0", vec![]),
+            manifest_package("gleam_json", "1.0.0", vec![]),
+            manifest_package("new", "0.1.0", vec![]),
+        ],
+    };
+
+    assert_eq!(
+        update_preview(Some(&current), &candidate),
+        "Package         Current   Candidate
+gleam_stdlib    0.34.0  → 0.40.0
+    docs: https://hexdocs.pm/gleam_stdlib/0.40.0/
+    changes: https://diff.hex.pm/diff/gleam_stdlib/0.34.0..0.40.0
+new             -       → 0.1.0
+    docs: https://hexdocs.pm/new/0.1.0/
+old_dependency  2.1.0   → -
+
+This was a dry run, the manifest has not been changed.
+"
+    );
+}
+
+#[test]
+fn update_preview_without_changes() {
+    let manifest = Manifest {
+        requirements: HashMap::new(),
+        packages: vec![manifest_package("gleam_stdlib", "0.34.0", vec![])],
+    };
+    assert_eq!(
+        update_preview(Some(&manifest), &manifest),
+        "All dependencies are already at the versions `glistix update` would select.\n"
+    );
+}
+
+#[test]
+fn locked_git_package_is_provided_from_the_manifest() {
+    let manifest = Manifest {
+        requirements: HashMap::new(),
+        packages: vec![ManifestPackage {
+            name: "wibble".into(),
+            version: Version::parse("1.2.0").unwrap(),
+            build_tools: ["gleam".into()].into(),
+            otp_app: None,
+            requirements: vec!["gleam_stdlib".into()],
+            source: ManifestPackageSource::Git {
+                repo: "https://github.com/wobble/wibble".into(),
+                commit: "b1c2d3".into(),
+            },
+        }],
+    };
+    let mut provided = HashMap::new();
+    let result = provide_git_package(
+        "wibble".into(),
+        "https://github.com/wobble/wibble",
+        &crate::project_paths_at_current_directory_without_toml(),
+        GitDependencies::Locked(Some(&manifest)),
+        &mut provided,
+    );
+
+    assert_eq!(result, Ok(hexpm::version::Range::new("== 1.2.0".into())));
+    assert_eq!(
+        provided.get("wibble"),
+        Some(&ProvidedPackage {
+            version: Version::parse("1.2.0").unwrap(),
+            source: ProvidedPackageSource::Git {
+                repo: "https://github.com/wobble/wibble".into(),
+                commit: "b1c2d3".into(),
+            },
+            requirements: HashMap::from([(
+                "gleam_stdlib".into(),
+                hexpm::version::Range::new(">= 0.0.0".into())
+            )]),
+        })
+    );
+}
+
+#[test]
+fn unlocked_git_package_is_not_cloned_when_locked() {
+    let mut provided = HashMap::new();
+    let result = provide_git_package(
+        "wibble".into(),
+        "https://github.com/wobble/wibble",
+        &crate::project_paths_at_current_directory_without_toml(),
+        GitDependencies::Locked(None),
+        &mut provided,
+    );
+
+    assert_eq!(
+        result,
+        Err(Error::GitDependencyNotLocked {
+            package: "wibble".into()
+        })
+    );
+    assert!(provided.is_empty());
+}
+
+/// Writes a Hex package tarball whose contents have a single module.
+fn write_package_tarball(path: &Utf8Path) {
+    let source = b"pub fn main() { Nil }\n";
+    let mut header = tar::Header::new_gnu();
+    header.set_size(source.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    let mut contents = tar::Builder::new(flate2::write::GzEncoder::new(
+        vec![],
+        flate2::Compression::default(),
+    ));
+    contents
+        .append_data(&mut header, "src/wibble.gleam", &source[..])
+        .unwrap();
+    let contents = contents.into_inner().unwrap().finish().unwrap();
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    let mut tarball = tar::Builder::new(vec![]);
+    tarball
+        .append_data(&mut header, "contents.tar.gz", contents.as_slice())
+        .unwrap();
+    std::fs::write(path, tarball.into_inner().unwrap()).unwrap();
+}
+
+fn store_downloader(root: &Utf8Path) -> hex::Downloader {
+    hex::Downloader::new(
+        ProjectIO::boxed(),
+        ProjectIO::boxed(),
+        HttpClient::boxed(),
+        Untar::boxed(),
+        hex::Repository::default(),
+        ProjectPaths::new(root.join("project")),
+    )
+}
+
+#[test]
+fn package_is_linked_from_the_store() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create a temp directory");
+    let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+    let tarball = root.join("wibble-1.0.0.tar");
+    let stored = root.join("store").join("wibble-1.0.0-0316");
+    write_package_tarball(&tarball);
+
+    let downloader = store_downloader(&root);
+    assert_eq!(
+        downloader.link_package("wibble", &tarball, &stored),
+        Ok(true)
+    );
+    assert_eq!(
+        downloader.link_package("wibble", &tarball, &stored),
+        Ok(false)
+    );
+
+    let module = root.join("project/build/packages/wibble/src/wibble.gleam");
+    assert_eq!(fs::read(module).unwrap(), "pub fn main() { Nil }\n");
+    // Nothing is left behind from extracting the package
+    let stored_entries = std::fs::read_dir(root.join("store")).unwrap().count();
+    assert_eq!(stored_entries, 1);
+}
+
+#[test]
+fn failed_extraction_leaves_nothing_in_the_store() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create a temp directory");
+    let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+    let tarball = root.join("wibble-1.0.0.tar");
+    let stored = root.join("store").join("wibble-1.0.0-0316");
+    std::fs::write(&tarball, tar::Builder::new(vec![]).into_inner().unwrap()).unwrap();
+
+    let downloader = store_downloader(&root);
+    assert!(downloader
+        .link_package("wibble", &tarball, &stored)
+        .is_err());
+    assert!(!stored.exists());
+    assert!(!root.join("project/build/packages/wibble").exists());
+
+    write_package_tarball(&tarball);
+    assert_eq!(
+        downloader.link_package("wibble", &tarball, &stored),
+        Ok(true)
+    );
+    assert!(root
+        .join("project/build/packages/wibble/src/wibble.gleam")
+        .is_file());
+}
+
+#[cfg(unix)]
+#[test]
+fn dangling_link_to_the_store_is_replaced() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create a temp directory");
+    let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+    let tarball = root.join("wibble-1.0.0.tar");
+    let stored = root.join("store").join("wibble-1.0.0-0316");
+    write_package_tarball(&tarball);
+
+    let downloader = store_downloader(&root);
+    assert_eq!(
+        downloader.link_package("wibble", &tarball, &stored),
+        Ok(true)
+    );
+    std::fs::remove_dir_all(&stored).unwrap();
+
+    assert_eq!(
+        downloader.link_package("wibble", &tarball, &stored),
+        Ok(true)
+    );
+    assert!(root
+        .join("project/build/packages/wibble/src/wibble.gleam")
+        .is_file());
+}
@@ -0,0 +1,296 @@
+//! The `glistix deps why` command, which shows why a package is part of the
+//! build by listing the dependency paths leading to it from the project.
+
+use std::collections::{HashMap, HashSet};
+
+use ecow::EcoString;
+use glistix_core::{config::PackageConfig, manifest::Manifest, paths::ProjectPaths, Error, Result};
+use itertools::Itertools;
+
+use crate::fs;
+
+/// How a package of a dependency path is required by the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeKind {
+    Dependency,
+    /// A dev dependency of the project itself.
+    DevDependency,
+    /// A dependency of another package which is replaced by the project's own
+    /// through the `local-overrides` setting of the `[glistix.preview]`
+    /// section of gleam.toml.
+    LocalOverride,
+}
+
+/// A package of a dependency path, along with how it is required.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    package: EcoString,
+    kind: EdgeKind,
+}
+
+pub fn command(package: String) -> Result<()> {
+    let config = crate::config::root_config()?;
+    let project = fs::get_project_root(fs::get_current_directory()?)?;
+    let manifest = super::read_manifest_from_disc(&ProjectPaths::new(project))?;
+    let mut paths = dependency_paths(&config, &manifest, &package)?;
+    if paths.is_empty() {
+        println!("No dependency path leads from {} to {package}", config.name);
+    }
+    let truncated = paths.len() > MAX_PATHS;
+    paths.truncate(MAX_PATHS);
+    for path in paths {
+        println!("{}", format_path(&config.name, &path));
+    }
+    if truncated {
+        println!("Only the first {MAX_PATHS} dependency paths are shown");
+    }
+    Ok(())
+}
+
+/// The number of dependency paths shown by the command, as there can be
+/// exponentially many of them in a large dependency graph.
+const MAX_PATHS: usize = 20;
+
+/// The paths going from the project to the given package in the graph of the
+/// manifest, without going through the same package twice. They are ordered
+/// by the names of their packages, and at most `MAX_PATHS + 1` of them are
+/// returned so that callers can tell when some were left out.
+fn dependency_paths(
+    config: &PackageConfig,
+    manifest: &Manifest,
+    package: &str,
+) -> Result<Vec<Vec<Step>>> {
+    if !manifest.packages.iter().any(|p| p.name == package) {
+        return Err(Error::UnknownDependency {
+            package: package.into(),
+        });
+    }
+
+    let requirements: HashMap<_, _> = manifest
+        .packages
+        .iter()
+        .map(|p| (p.name.as_str(), p.requirements.as_slice()))
+        .collect();
+    let graph = Graph {
+        leading_to_target: packages_leading_to(&requirements, package),
+        requirements,
+        local_overrides: &config.glistix.preview.local_overrides,
+        target: package,
+    };
+    let mut paths = vec![];
+    let mut current = vec![];
+    for name in manifest.requirements.keys().sorted() {
+        let kind = if config.dev_dependencies.contains_key(name) {
+            EdgeKind::DevDependency
+        } else {
+            EdgeKind::Dependency
+        };
+        graph.visit(name, kind, &mut current, &mut paths);
+    }
+    Ok(paths)
+}
+
+/// The packages from which the target package can be reached, including the
+/// target itself, so that the search for paths doesn't wander into parts of
+/// the graph which cannot lead to it.
+fn packages_leading_to<'a>(
+    requirements: &HashMap<&'a str, &'a [EcoString]>,
+    target: &'a str,
+) -> HashSet<&'a str> {
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (package, package_requirements) in requirements {
+        for requirement in package_requirements.iter() {
+            dependents
+                .entry(requirement.as_str())
+                .or_default()
+                .push(*package);
+        }
+    }
+
+    let mut leading = HashSet::from([target]);
+    let mut stack = vec![target];
+    while let Some(package) = stack.pop() {
+        for dependent in dependents.get(package).into_iter().flatten() {
+            if leading.insert(*dependent) {
+                stack.push(*dependent);
+            }
+        }
+    }
+    leading
+}
+
+struct Graph<'a> {
+    requirements: HashMap<&'a str, &'a [EcoString]>,
+    leading_to_target: HashSet<&'a str>,
+    local_overrides: &'a [EcoString],
+    target: &'a str,
+}
+
+impl Graph<'_> {
+    fn visit(
+        &self,
+        package: &EcoString,
+        kind: EdgeKind,
+        current: &mut Vec<Step>,
+        paths: &mut Vec<Vec<Step>>,
+    ) {
+        if paths.len() > MAX_PATHS
+            || !self.leading_to_target.contains(package.as_str())
+            || current.iter().any(|step| &step.package == package)
+        {
+            return;
+        }
+        current.push(Step {
+            package: package.clone(),
+            kind,
+        });
+
+        if package == self.target {
+            paths.push(current.clone());
+        } else if let Some(requirements) = self.requirements.get(package.as_str()) {
+            for requirement in requirements.iter().sorted() {
+                let kind = if self.local_overrides.contains(requirement) {
+                    EdgeKind::LocalOverride
+                } else {
+                    EdgeKind::Dependency
+                };
+                self.visit(requirement, kind, current, paths);
+            }
+        }
+
+        let _ = current.pop();
+    }
+}
+
+fn format_path(root: &str, path: &[Step]) -> String {
+    let mut text = root.to_string();
+    for step in path {
+        text.push_str(" -> ");
+        text.push_str(&step.package);
+        match step.kind {
+            EdgeKind::Dependency => (),
+            EdgeKind::DevDependency => text.push_str(" (dev dependency)"),
+            EdgeKind::LocalOverride => text.push_str(" (local override)"),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glistix_core::{
+        manifest::{Base16Checksum, ManifestPackage, ManifestPackageSource},
+        requirement::Requirement,
+    };
+    use hexpm::version::Version;
+
+    fn package(name: &str, requirements: &[&str]) -> ManifestPackage {
+        ManifestPackage {
+            name: name.into(),
+            version: Version::new(1, 0, 0),
+            build_tools: ["gleam".into()].into(),
+            otp_app: None,
+            requirements: requirements.iter().map(|name| (*name).into()).collect(),
+            source: ManifestPackageSource::Hex {
+                outer_checksum: Base16Checksum(vec![1, 2, 3, 4]),
+            },
+        }
+    }
+
+    fn project() -> (PackageConfig, Manifest) {
+        let mut config = PackageConfig {
+            name: "app".into(),
+            ..Default::default()
+        };
+        let _ = config
+            .dependencies
+            .insert("wibble".into(), Requirement::hex("~> 1.0"));
+        let _ = config
+            .dependencies
+            .insert("gleam_stdlib".into(), Requirement::path("external/stdlib"));
+        let _ = config
+            .dev_dependencies
+            .insert("wobble".into(), Requirement::hex("~> 1.0"));
+        config.glistix.preview.local_overrides = vec!["gleam_stdlib".into()];
+
+        let manifest = Manifest {
+            requirements: config
+                .dependencies
+                .iter()
+                .chain(&config.dev_dependencies)
+                .map(|(name, requirement)| (name.clone(), requirement.clone()))
+                .collect(),
+            packages: vec![
+                package("gleam_stdlib", &[]),
+                package("wibble", &["gleam_stdlib", "wubble"]),
+                package("wobble", &["wibble"]),
+                package("wubble", &[]),
+            ],
+        };
+        (config, manifest)
+    }
+
+    fn formatted_paths(package: &str) -> Vec<String> {
+        let (config, manifest) = project();
+        dependency_paths(&config, &manifest, package)
+            .unwrap()
+            .iter()
+            .map(|path| format_path(&config.name, path))
+            .collect()
+    }
+
+    #[test]
+    fn paths_to_package() {
+        assert_eq!(
+            formatted_paths("gleam_stdlib"),
+            vec![
+                "app -> gleam_stdlib",
+                "app -> wibble -> gleam_stdlib (local override)",
+                "app -> wobble (dev dependency) -> wibble -> gleam_stdlib (local override)",
+            ]
+        );
+    }
+
+    #[test]
+    fn paths_to_transitive_package() {
+        assert_eq!(
+            formatted_paths("wubble"),
+            vec![
+                "app -> wibble -> wubble",
+                "app -> wobble (dev dependency) -> wibble -> wubble",
+            ]
+        );
+    }
+
+    #[test]
+    fn paths_are_capped() {
+        // Each of the 5 levels between `left0` and `left6` can be gone
+        // through by either of 2 packages, giving 32 paths.
+        let (config, mut manifest) = project();
+        let level = |n: usize| [format!("left{n}"), format!("right{n}")];
+        manifest.packages = (0..6)
+            .flat_map(|n| {
+                let next = level(n + 1);
+                level(n).map(|name| {
+                    let next = next.iter().map(String::as_str).collect_vec();
+                    package(&name, &next)
+                })
+            })
+            .chain([package("left6", &[]), package("right6", &[])])
+            .collect();
+        manifest.requirements = [("left0".into(), Requirement::hex("~> 1.0"))].into();
+
+        let paths = dependency_paths(&config, &manifest, "left6").unwrap();
+        assert_eq!(paths.len(), MAX_PATHS + 1);
+    }
+
+    #[test]
+    fn unknown_package() {
+        let (config, manifest) = project();
+        assert!(matches!(
+            dependency_paths(&config, &manifest, "wabble"),
+            Err(Error::UnknownDependency { .. })
+        ));
+    }
+}
@@ -2,7 +2,11 @@ use std::time::{Instant, SystemTime};
 
 use camino::{Utf8Path, Utf8PathBuf};
 
-use crate::{cli, fs::ProjectIO, http::HttpClient};
+use crate::{
+    cli,
+    fs::{ConsoleWarningEmitter, ProjectIO},
+    http::HttpClient,
+};
 use glistix_core::{
     analyse::TargetSupport,
     build::{Codegen, Compile, Mode, Options, Package, Target},
@@ -11,6 +15,7 @@ use glistix_core::{
     error::Error,
     hex,
     io::HttpClient as _,
+    warning::{Warning, WarningEmitterIO},
     Result,
 };
 
@@ -56,6 +61,7 @@ pub fn build(options: BuildOptions) -> Result<()> {
             warnings_as_errors: false,
             root_target_support: TargetSupport::Enforced,
             no_print_progress: false,
+            changed_files: None,
         },
         crate::build::download_dependencies(cli::Reporter::new())?,
     )?;
@@ -100,6 +106,19 @@ pub(crate) fn build_documentation(
     is_hex_publish: DocContext,
 ) -> Result<Vec<glistix_core::io::OutputFile>, Error> {
     compiled.attach_doc_and_module_comments();
+
+    // Packages targeting Nix are expected to be usable from Nix, so let the
+    // user know if part of the public API they're documenting isn't.
+    if config.target == Target::Nix {
+        let values = glistix_core::docs::nix_incompatible_values(config, &compiled.modules);
+        if !values.is_empty() {
+            ConsoleWarningEmitter.emit_warning(Warning::NixIncompatibleDocumentedApi {
+                package: config.name.clone(),
+                values,
+            });
+        }
+    }
+
     cli::print_generating_documentation();
     let paths = crate::find_project_paths()?;
     let mut pages = vec![DocsPage {
@@ -116,7 +135,7 @@ pub(crate) fn build_documentation(
         ProjectIO::new(),
         SystemTime::now(),
         is_hex_publish,
-    );
+    )?;
 
     outputs.push(glistix_core::docs::generate_json_package_interface(
         Utf8PathBuf::from("package-interface.json"),
@@ -146,6 +165,7 @@ pub fn publish() -> Result<()> {
             mode: Mode::Prod,
             target: None,
             no_print_progress: false,
+            changed_files: None,
         },
         crate::build::download_dependencies(cli::Reporter::new())?,
     )?;
@@ -0,0 +1,79 @@
+use std::io::Write;
+
+use glistix_core::{
+    error::codes::{ErrorCode, ErrorCodeInfo},
+    Error, Result,
+};
+use itertools::Itertools;
+use termcolor::Buffer;
+
+/// Prints the title of the error with the given code, followed by its
+/// extended explanation when it has one.
+pub fn command(code: String) -> Result<()> {
+    let info = ErrorCode::parse(&code)
+        .and_then(ErrorCode::info)
+        .ok_or(Error::UnknownErrorCode { code })?;
+    print!("{}", explanation(info));
+    Ok(())
+}
+
+/// Points to `glistix explain` for the codes of the diagnostics of an error
+/// that has extended explanations.
+pub fn write_hint(error: &Error, buffer: &mut Buffer) {
+    let codes = error
+        .to_diagnostics_with_codes()
+        .into_iter()
+        .filter_map(|(_, code)| code)
+        .filter(|code| code.info().is_some_and(|info| info.explanation.is_some()))
+        .unique()
+        .collect_vec();
+    let Some(first) = codes.first() else {
+        return;
+    };
+    let text = if codes.len() == 1 {
+        format!("For more information about this error, try `glistix explain {first}`.")
+    } else {
+        let codes = codes.iter().join(", ");
+        format!(
+            "Some errors have detailed explanations: {codes}.
+For more information about an error, try `glistix explain {first}`."
+        )
+    };
+    writeln!(buffer, "{text}").expect("write explain hint");
+}
+
+fn explanation(info: &ErrorCodeInfo) -> String {
+    match info.explanation {
+        Some(explanation) => explanation.to_string(),
+        None => format!(
+            "# {}
+
+There is no extended explanation for this error yet.
+",
+            info.title
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explanation_of_code_without_one() {
+        let info = ErrorCode::parse("E0009").unwrap().info().unwrap();
+        assert_eq!(
+            explanation(info),
+            "# File IO failure
+
+There is no extended explanation for this error yet.
+"
+        );
+    }
+
+    #[test]
+    fn explanation_of_code_with_one() {
+        let info = ErrorCode::parse("e2004").unwrap().info().unwrap();
+        assert!(explanation(info).starts_with("# Unknown variable\n"));
+    }
+}
@@ -1,9 +1,20 @@
-use camino::Utf8PathBuf;
+use std::collections::HashMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use flate2::{write::GzEncoder, Compression};
 use glistix_core::{
     analyse::TargetSupport,
     build::{Codegen, Compile, Mode, Options, Target},
-    Result,
+    config::{PackageConfig, SpdxLicense},
+    error::{FileIoAction, FileKind},
+    manifest::{Manifest, ManifestPackageSource},
+    paths::ARTEFACT_DIRECTORY_NAME,
+    version::COMPILER_VERSION,
+    Error, Result,
 };
+use itertools::Itertools;
+use serde_json::json;
 
 #[cfg(target_os = "windows")]
 static ENTRYPOINT_FILENAME: &str = "entrypoint.ps1";
@@ -49,6 +60,7 @@ pub(crate) fn erlang_shipment() -> Result<()> {
             mode,
             target: Some(target),
             no_print_progress: false,
+            changed_files: None,
         },
         crate::build::download_dependencies(crate::cli::Reporter::new())?,
     )?;
@@ -131,6 +143,279 @@ pub fn nix_prelude() -> Result<()> {
     Ok(())
 }
 
+/// Generate a Nix overlay adding the compiled package to the `glistixPackages`
+/// attribute set, so that it can be used by other Glistix-built packages and
+/// Nix code without any glue.
+///
+/// The package's compiled Nix code, along with the Nix prelude, is copied to a
+/// `lib` directory next to the `overlay.nix` file. The package's dependencies
+/// are expected to be added to `glistixPackages` by other overlays in the same
+/// way, and their own compiled code is linked next to the package's.
+pub fn nix_overlay() -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let target = Target::Nix;
+    let mode = Mode::Prod;
+    let out = paths.nix_overlay_directory();
+
+    // Reset the directory to ensure we have a clean slate and no old code
+    crate::fs::delete_directory(&out)?;
+
+    // Build project in production mode
+    let built = crate::build::main(
+        Options {
+            root_target_support: TargetSupport::Enforced,
+            warnings_as_errors: false,
+            codegen: Codegen::All,
+            compile: Compile::All,
+            mode,
+            target: Some(target),
+            no_print_progress: false,
+            changed_files: None,
+        },
+        crate::build::download_dependencies(crate::cli::Reporter::new())?,
+    )?;
+    let config = &built.root_package.config;
+
+    // Copy the package's compiled code
+    let lib = out.join("lib");
+    let package_build = paths.build_directory_for_package(mode, target, &config.name);
+    copy_compiled_nix_package(&package_build, &lib.join(config.name.as_str()))?;
+    crate::fs::write(&lib.join("prelude.nix"), glistix_core::nix::PRELUDE)?;
+
+    let dependencies = config.dependencies.keys().sorted().collect_vec();
+    let overlay = out.join("overlay.nix");
+    crate::fs::write(
+        &overlay,
+        &nix_overlay_file(&config.name, &config.version.to_string(), &dependencies),
+    )?;
+
+    crate::cli::print_exported(&config.name);
+
+    println!(
+        "
+Your Nix overlay has been generated to {out}.
+
+Once applied, the package is available as `glistixPackages.{name}`, and its
+main module can be imported with
+
+    import \"${{pkgs.glistixPackages.{name}}}/{name}/{name}.nix\"
+",
+        name = config.name,
+    );
+
+    Ok(())
+}
+
+/// Copies the compiled code of a package built for Nix, leaving out the
+/// compiler's cache.
+fn copy_compiled_nix_package(build: &Utf8Path, out: &Utf8Path) -> Result<()> {
+    crate::fs::mkdir(out)?;
+    for entry in crate::fs::read_dir(build)?.filter_map(Result::ok) {
+        let path = entry.path();
+        let name = path.file_name().expect("File name");
+        if name == ARTEFACT_DIRECTORY_NAME {
+            continue;
+        }
+        if path.is_dir() {
+            crate::fs::copy_dir(path, out.join(name))?;
+        } else {
+            crate::fs::copy(path, out.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Generate a directory with the project compiled to Nix, along with all of
+/// its dependencies and the Nix prelude, laid out as in the build directory so
+/// that the compiled modules import each other with relative paths. It can be
+/// copied anywhere, such as into another repository, and used without
+/// Glistix or Hex.
+///
+/// A `default.nix` file at its root evaluates to the project's modules. With
+/// `tarball`, the directory is also archived into a `.tar.gz` file.
+pub fn nix_closure(tarball: bool) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let target = Target::Nix;
+    let mode = Mode::Prod;
+    let build = paths.build_directory_for_target(mode, target);
+    let out = paths.nix_closure_directory();
+
+    // Reset the directory to ensure we have a clean slate and no old code
+    crate::fs::delete_directory(&out)?;
+
+    // Build project in production mode
+    let built = crate::build::main(
+        Options {
+            root_target_support: TargetSupport::Enforced,
+            warnings_as_errors: false,
+            codegen: Codegen::All,
+            compile: Compile::All,
+            mode,
+            target: Some(target),
+            no_print_progress: false,
+            changed_files: None,
+        },
+        crate::build::download_dependencies(crate::cli::Reporter::new())?,
+    )?;
+    let config = &built.root_package.config;
+
+    // Copy the packages compiled by this build, leaving out any left in the
+    // build directory by earlier builds
+    let packages = built
+        .module_interfaces()
+        .values()
+        .map(|interface| &interface.package)
+        .chain([&config.name])
+        .unique()
+        .sorted();
+    for package in packages {
+        let path = build.join(package.as_str());
+        if path.is_dir() {
+            copy_compiled_nix_package(&path, &out.join(package.as_str()))?;
+        }
+    }
+    crate::fs::write(&out.join("prelude.nix"), glistix_core::nix::PRELUDE)?;
+
+    let modules = built
+        .root_package
+        .modules
+        .iter()
+        .filter(|module| !module.is_test())
+        .map(|module| &module.name)
+        .sorted()
+        .collect_vec();
+    crate::fs::write(
+        &out.join("default.nix"),
+        &nix_closure_default_file(&config.name, &modules),
+    )?;
+
+    let exported = if tarball {
+        let path = paths.nix_closure_tarball();
+        crate::fs::write_bytes(&path, &directory_tarball(&out, "nix-closure")?)?;
+        path
+    } else {
+        out
+    };
+
+    crate::cli::print_exported(&config.name);
+
+    println!(
+        "
+Your Nix closure has been generated to {exported}.
+
+Its default.nix evaluates to the modules of the package, so its main module
+can be used with
+
+    (import ./nix-closure).{name}
+",
+        name = config.name,
+    );
+
+    Ok(())
+}
+
+fn nix_closure_default_file(name: &str, modules: &[&EcoString]) -> String {
+    let modules = modules
+        .iter()
+        .map(|module| format!("\n  \"{module}\" = import ./{name}/{module}.nix;"))
+        .join("");
+    format!(
+        r#"# This file was generated by `glistix export nix-closure`.
+#
+# It evaluates to the modules of the '{name}' package, compiled to Nix, by
+# their name. The compiled code of the package's dependencies and the Nix
+# prelude, which the modules import, are in this same directory.
+{{{modules}
+}}
+"#
+    )
+}
+
+/// Archives a directory into a gzipped tarball, under the given name. The
+/// files are added in order of their paths, and without their modification
+/// times or owners, so that the same files always give the same tarball.
+fn directory_tarball(directory: &Utf8Path, name: &str) -> Result<Vec<u8>> {
+    let mut tar_gz = Vec::new();
+    {
+        let mut tarball = tar::Builder::new(GzEncoder::new(&mut tar_gz, Compression::default()));
+        for entry in walkdir::WalkDir::new(directory).sort_by_file_name() {
+            let entry = entry.map_err(|error| Error::FileIo {
+                action: FileIoAction::Read,
+                kind: FileKind::Directory,
+                path: directory.to_path_buf(),
+                err: Some(error.to_string()),
+            })?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = Utf8PathBuf::from_path_buf(entry.into_path())
+                .map_err(|path| Error::NonUtf8Path { path })?;
+            let relative = path.strip_prefix(directory).expect("Path in directory");
+            let content = crate::fs::read_bytes(&path)?;
+
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(0);
+            header.set_uid(0);
+            header.set_gid(0);
+            tarball
+                .append_data(
+                    &mut header,
+                    Utf8Path::new(name).join(relative),
+                    content.as_slice(),
+                )
+                .map_err(|error| Error::add_tar(&path, error))?;
+        }
+        tarball.finish().map_err(Error::finish_tar)?;
+    }
+    Ok(tar_gz)
+}
+
+fn nix_overlay_file(name: &str, version: &str, dependencies: &[&EcoString]) -> String {
+    let dependency_paths = dependencies
+        .iter()
+        .map(|dependency| format!("\n        final.glistixPackages.{dependency}"))
+        .join("");
+    let dependency_names = if dependencies.is_empty() {
+        "[ ]".into()
+    } else {
+        let names = dependencies
+            .iter()
+            .map(|dependency| format!("\"{dependency}\""))
+            .join(" ");
+        format!("[ {names} ]")
+    };
+    format!(
+        r#"# This file was generated by `glistix export nix-overlay`.
+#
+# It adds the '{name}' package, compiled to Nix, to the 'glistixPackages'
+# attribute set. Its dependencies must be added to that set by other overlays,
+# such as the ones generated for them by the same command.
+#
+# Once applied, the package's main module can be imported with:
+#
+#   import "${{pkgs.glistixPackages.{name}}}/{name}/{name}.nix"
+final: prev: {{
+  glistixPackages = (prev.glistixPackages or {{ }}) // {{
+    {name} = final.symlinkJoin {{
+      name = "glistix-{name}-{version}";
+      paths = [
+        ./lib{dependency_paths}
+      ];
+      passthru = {{
+        glistixPackageName = "{name}";
+        version = "{version}";
+        glistixDependencies = {dependency_names};
+      }};
+    }};
+  }};
+}}
+"#
+    )
+}
+
 pub fn package_interface(path: Utf8PathBuf) -> Result<()> {
     // Build the project
     let mut built = crate::build::main(
@@ -142,6 +427,7 @@ pub fn package_interface(path: Utf8PathBuf) -> Result<()> {
             warnings_as_errors: false,
             root_target_support: TargetSupport::Enforced,
             no_print_progress: false,
+            changed_files: None,
         },
         crate::build::download_dependencies(crate::cli::Reporter::new())?,
     )?;
@@ -151,3 +437,266 @@ pub fn package_interface(path: Utf8PathBuf) -> Result<()> {
     crate::fs::write_outputs_under(&[out], crate::find_project_paths()?.root())?;
     Ok(())
 }
+
+/// Generate a CycloneDX software bill of materials listing every resolved
+/// package the project depends on, for use by supply chain tooling.
+pub fn sbom(path: Utf8PathBuf) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let manifest = crate::build::download_dependencies(crate::cli::Reporter::new())?;
+
+    // Licences are taken from the configs of the downloaded packages, which
+    // packages not built with Gleam or Glistix don't have.
+    let licences = manifest
+        .packages
+        .iter()
+        .filter_map(|package| {
+            let config_path = paths
+                .build_packages_package(&package.name)
+                .join("gleam.toml");
+            let package_config = crate::config::read(config_path).ok()?;
+            Some((package.name.clone(), package_config.licences))
+        })
+        .collect();
+
+    let sbom = sbom_json(&config, &manifest, &licences);
+    let json = serde_json::to_string_pretty(&sbom).expect("SBOM serialisation");
+    crate::fs::write(&path, &json)?;
+    crate::cli::print_exported(&config.name);
+    Ok(())
+}
+
+fn sbom_json(
+    config: &PackageConfig,
+    manifest: &Manifest,
+    licences: &HashMap<EcoString, Vec<SpdxLicense>>,
+) -> serde_json::Value {
+    let licences_json = |licences: &[SpdxLicense]| {
+        licences
+            .iter()
+            .map(|licence| json!({ "license": { "id": licence.to_string() } }))
+            .collect_vec()
+    };
+
+    let packages = manifest
+        .packages
+        .iter()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect_vec();
+
+    let components = packages
+        .iter()
+        .map(|package| {
+            let package_licences = licences.get(&package.name).map(Vec::as_slice);
+            let mut component = serde_json::Map::from_iter([
+                ("type".into(), json!("library")),
+                ("bom-ref".into(), json!(package.name)),
+                ("name".into(), json!(package.name)),
+                ("version".into(), json!(package.version.to_string())),
+                (
+                    "licenses".into(),
+                    json!(licences_json(package_licences.unwrap_or_default())),
+                ),
+            ]);
+            match &package.source {
+                ManifestPackageSource::Hex { outer_checksum } => {
+                    let purl = format!("pkg:hex/{}@{}", package.name, package.version);
+                    component.extend([
+                        ("purl".into(), json!(purl)),
+                        (
+                            "hashes".into(),
+                            json!([{ "alg": "SHA-256", "content": outer_checksum.to_string() }]),
+                        ),
+                    ]);
+                }
+                ManifestPackageSource::Git { repo, commit } => {
+                    let _ = component.insert(
+                        "externalReferences".into(),
+                        json!([{ "type": "vcs", "url": repo, "comment": commit }]),
+                    );
+                }
+                ManifestPackageSource::Local { .. } => {}
+            }
+            serde_json::Value::Object(component)
+        })
+        .collect_vec();
+
+    let root_dependencies = manifest.requirements.keys().sorted().collect_vec();
+    let dependencies = std::iter::once(json!({
+        "ref": config.name,
+        "dependsOn": root_dependencies,
+    }))
+    .chain(packages.iter().map(|package| {
+        json!({
+            "ref": package.name,
+            "dependsOn": package.requirements.iter().sorted().collect_vec(),
+        })
+    }))
+    .collect_vec();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "tools": [{ "vendor": "Glistix", "name": "glistix", "version": COMPILER_VERSION }],
+            "component": {
+                "type": "application",
+                "bom-ref": config.name,
+                "name": config.name,
+                "version": config.version.to_string(),
+                "licenses": licences_json(&config.licences),
+            },
+        },
+        "components": components,
+        "dependencies": dependencies,
+    })
+}
+
+#[test]
+fn nix_overlay_file_wires_dependencies() {
+    let stdlib = EcoString::from("gleam_stdlib");
+    let json = EcoString::from("gleam_json");
+    let overlay = nix_overlay_file("wibble", "1.0.0", &[&json, &stdlib]);
+    assert!(overlay.contains(
+        "      paths = [
+        ./lib
+        final.glistixPackages.gleam_json
+        final.glistixPackages.gleam_stdlib
+      ];"
+    ));
+    assert!(overlay.contains(r#"glistixDependencies = [ "gleam_json" "gleam_stdlib" ];"#));
+}
+
+#[test]
+fn nix_overlay_file_without_dependencies() {
+    let overlay = nix_overlay_file("wibble", "1.0.0", &[]);
+    assert!(overlay.contains("      paths = [\n        ./lib\n      ];"));
+    assert!(overlay.contains("glistixDependencies = [ ];"));
+}
+
+#[test]
+fn nix_closure_default_file_imports_modules() {
+    let app = EcoString::from("wibble");
+    let internal = EcoString::from("wibble/internal");
+    let default = nix_closure_default_file("wibble", &[&app, &internal]);
+    assert!(default.ends_with(
+        r#"
+{
+  "wibble" = import ./wibble/wibble.nix;
+  "wibble/internal" = import ./wibble/wibble/internal.nix;
+}
+"#
+    ));
+}
+
+#[test]
+fn directory_tarball_is_reproducible() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let directory = Utf8Path::from_path(tmp_dir.path()).unwrap();
+    crate::fs::write(&directory.join("wibble/wibble.nix"), "{ }").unwrap();
+    crate::fs::write(&directory.join("prelude.nix"), "{ }").unwrap();
+    crate::fs::write(&directory.join("default.nix"), "{ }").unwrap();
+
+    let tarball = directory_tarball(directory, "nix-closure").unwrap();
+    assert_eq!(
+        directory_tarball(directory, "nix-closure").unwrap(),
+        tarball
+    );
+
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(tarball.as_slice()));
+    let entries = archive
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            let header = entry.header();
+            assert_eq!(header.mtime().unwrap(), 0);
+            assert_eq!(header.uid().unwrap(), 0);
+            assert_eq!(header.gid().unwrap(), 0);
+            entry.path().unwrap().to_string_lossy().to_string()
+        })
+        .collect_vec();
+    assert_eq!(
+        entries,
+        vec![
+            "nix-closure/default.nix",
+            "nix-closure/prelude.nix",
+            "nix-closure/wibble/wibble.nix",
+        ]
+    );
+}
+
+#[test]
+fn sbom_lists_packages_with_hashes_and_licences() {
+    use glistix_core::{
+        manifest::{Base16Checksum, ManifestPackage},
+        requirement::Requirement,
+    };
+    use hexpm::version::Version;
+
+    let config = PackageConfig {
+        name: "wibble".into(),
+        ..Default::default()
+    };
+    let manifest = Manifest {
+        requirements: [("gleam_json".into(), Requirement::hex(">= 1.0.0"))].into(),
+        packages: vec![
+            ManifestPackage {
+                name: "gleam_json".into(),
+                version: Version::new(1, 0, 0),
+                build_tools: vec!["gleam".into()],
+                otp_app: None,
+                requirements: vec!["gleam_stdlib".into()],
+                source: ManifestPackageSource::Hex {
+                    outer_checksum: Base16Checksum(vec![0xab, 0xcd]),
+                },
+            },
+            ManifestPackage {
+                name: "gleam_stdlib".into(),
+                version: Version::new(0, 40, 0),
+                build_tools: vec!["gleam".into()],
+                otp_app: None,
+                requirements: vec![],
+                source: ManifestPackageSource::Git {
+                    repo: "https://github.com/gleam-lang/stdlib".into(),
+                    commit: "bd6fe5f".into(),
+                },
+            },
+        ],
+    };
+    let licences = [(
+        "gleam_json".into(),
+        vec![SpdxLicense {
+            licence: "Apache-2.0".into(),
+        }],
+    )]
+    .into();
+
+    let sbom = sbom_json(&config, &manifest, &licences);
+    assert_eq!(sbom["metadata"]["tools"][0]["version"], COMPILER_VERSION);
+    assert_eq!(
+        sbom["components"][0],
+        json!({
+            "type": "library",
+            "bom-ref": "gleam_json",
+            "name": "gleam_json",
+            "version": "1.0.0",
+            "licenses": [{ "license": { "id": "Apache-2.0" } }],
+            "purl": "pkg:hex/gleam_json@1.0.0",
+            "hashes": [{ "alg": "SHA-256", "content": "ABCD" }],
+        })
+    );
+    assert_eq!(
+        sbom["components"][1]["externalReferences"][0]["url"],
+        "https://github.com/gleam-lang/stdlib"
+    );
+    assert_eq!(
+        sbom["dependencies"],
+        json!([
+            { "ref": "wibble", "dependsOn": ["gleam_json"] },
+            { "ref": "gleam_json", "dependsOn": ["gleam_stdlib"] },
+            { "ref": "gleam_stdlib", "dependsOn": [] },
+        ])
+    );
+}
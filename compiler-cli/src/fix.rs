@@ -5,6 +5,7 @@ use glistix_core::{
     analyse::TargetSupport,
     build::{Codegen, Compile, Mode, Options},
     error::{FileIoAction, FileKind},
+    fix::{self, FixContext},
     type_,
     warning::VectorWarningEmitterIO,
     Error, Result, Warning,
@@ -27,6 +28,7 @@ pub fn run() -> Result<()> {
             mode: Mode::Dev,
             target: None,
             no_print_progress: false,
+            changed_files: None,
         },
         build::download_dependencies(cli::Reporter::new())?,
         warnings.clone(),
@@ -34,6 +36,7 @@ pub fn run() -> Result<()> {
     let warnings = warnings.take();
 
     fix_minimum_required_version(warnings)?;
+    apply_fix_rules()?;
 
     println!("Done!");
     Ok(())
@@ -66,6 +69,40 @@ fn fix_minimum_required_version(warnings: Vec<Warning>) -> Result<()> {
     Ok(())
 }
 
+/// Runs the rewrite rules over every module in the project, writing back the
+/// modules they changed.
+fn apply_fix_rules() -> Result<()> {
+    let target = crate::config::root_config()?.target;
+    let rules = fix::rules();
+
+    for directory in [Utf8Path::new("src"), Utf8Path::new("test")] {
+        for path in crate::fs::gleam_files_excluding_gitignore(directory) {
+            let module_name = path
+                .strip_prefix(directory)
+                .unwrap_or(&path)
+                .with_extension("")
+                .as_str()
+                .replace('\\', "/");
+            let context = FixContext {
+                target,
+                module_name: &module_name,
+            };
+
+            let src = crate::fs::read(&path)?.into();
+            let (fixed, applied) = fix::apply_rules(&src, &path, context, &rules)?;
+            if applied.is_empty() {
+                continue;
+            }
+
+            crate::fs::write(&path, &fixed)?;
+            for description in applied {
+                println!("- {description} in {path}");
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Returns the highest minimum required version among all warnings requiring a
 /// specific Gleam version that is not allowed by the `gleam` version contraint
 /// in the `gleam.toml`.
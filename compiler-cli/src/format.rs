@@ -1,24 +1,78 @@
+use ecow::EcoString;
 use glistix_core::{
+    ast::Definition,
+    build::nix_externals,
+    config::FormatConfig,
     error::{Error, FileIoAction, FileKind, Result, StandardIoAction, Unformatted},
     io::Content,
     io::OutputFile,
+    paths::ProjectPaths,
+    warning::WarningEmitter,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::Read,
+    str::FromStr,
 };
-use std::{io::Read, str::FromStr};
 
 use camino::{Utf8Path, Utf8PathBuf};
 
-pub fn run(stdin: bool, check: bool, files: Vec<String>) -> Result<()> {
+use crate::fs::{get_current_directory, get_project_root};
+
+#[cfg(test)]
+mod tests;
+
+pub fn run(stdin: bool, check: bool, nix: bool, files: Vec<String>) -> Result<()> {
+    let mut configs = FormatConfigs::default();
     if stdin {
-        process_stdin(check)
+        // Code read from stdin is formatted with the settings of the package
+        // the command is run in.
+        let config = configs.for_path(&get_current_directory()?)?;
+        process_stdin(check, nix, config)
     } else {
-        process_files(check, files)
+        process_files(check, nix, files, &mut configs)
+    }
+}
+
+/// The `[format]` settings of the packages the formatted files belong to.
+/// Each file uses the settings in the nearest `gleam.toml` above it, so
+/// nested packages are formatted with their own settings wherever the command
+/// is run from, and files outside of any package use the default ones.
+#[derive(Debug, Default)]
+pub struct FormatConfigs {
+    by_package_root: HashMap<Utf8PathBuf, FormatConfig>,
+}
+
+impl FormatConfigs {
+    fn for_path(&mut self, path: &Utf8Path) -> Result<FormatConfig> {
+        let directory = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(Utf8Path::new(""))
+        };
+        let Ok(root) = get_project_root(get_current_directory()?.join(directory)) else {
+            return Ok(FormatConfig::default());
+        };
+
+        if let Some(config) = self.by_package_root.get(&root) {
+            return Ok(*config);
+        }
+        let config = crate::config::read(ProjectPaths::new(root.clone()).root_config())?.format;
+        let _ = self.by_package_root.insert(root, config);
+        Ok(config)
     }
 }
 
-fn process_stdin(check: bool) -> Result<()> {
-    let src = read_stdin()?.into();
-    let mut out = String::new();
-    glistix_core::format::pretty(&mut out, &src, Utf8Path::new("<stdin>"))?;
+fn process_stdin(check: bool, nix: bool, config: FormatConfig) -> Result<()> {
+    let src: EcoString = read_stdin()?.into();
+    let path = Utf8Path::new("<stdin>");
+    let out = if nix {
+        format_nix(&src, path, config)?
+    } else {
+        let mut out = String::new();
+        glistix_core::format::pretty(&mut out, &src, path, config)?;
+        out
+    };
 
     if !check {
         print!("{out}");
@@ -39,16 +93,21 @@ fn process_stdin(check: bool) -> Result<()> {
     Ok(())
 }
 
-fn process_files(check: bool, files: Vec<String>) -> Result<()> {
+fn process_files(
+    check: bool,
+    nix: bool,
+    files: Vec<String>,
+    configs: &mut FormatConfigs,
+) -> Result<()> {
     if check {
-        check_files(files)
+        check_files(files, nix, configs)
     } else {
-        format_files(files)
+        format_files(files, nix, configs)
     }
 }
 
-fn check_files(files: Vec<String>) -> Result<()> {
-    let problem_files = unformatted_files(files)?;
+fn check_files(files: Vec<String>, nix: bool, configs: &mut FormatConfigs) -> Result<()> {
+    let problem_files = unformatted_files(files, nix, configs)?;
 
     if problem_files.is_empty() {
         Ok(())
@@ -57,8 +116,8 @@ fn check_files(files: Vec<String>) -> Result<()> {
     }
 }
 
-fn format_files(files: Vec<String>) -> Result<()> {
-    for file in unformatted_files(files)? {
+fn format_files(files: Vec<String>, nix: bool, configs: &mut FormatConfigs) -> Result<()> {
+    for file in unformatted_files(files, nix, configs)? {
         crate::fs::write_output(&OutputFile {
             path: file.destination,
             content: Content::Text(file.output),
@@ -67,8 +126,17 @@ fn format_files(files: Vec<String>) -> Result<()> {
     Ok(())
 }
 
-pub fn unformatted_files(files: Vec<String>) -> Result<Vec<Unformatted>> {
+/// The files which are not formatted, along with their formatted contents.
+/// When `nix` is set the Nix files in the package referenced by the Gleam
+/// files' `@external(nix, ...)` attributes are checked too. Nix files given
+/// explicitly are always formatted as Nix.
+pub fn unformatted_files(
+    files: Vec<String>,
+    nix: bool,
+    configs: &mut FormatConfigs,
+) -> Result<Vec<Unformatted>> {
     let mut problem_files = Vec::with_capacity(files.len());
+    let mut nix_files = BTreeSet::new();
 
     for file_path in files {
         let path = Utf8PathBuf::from_str(&file_path).map_err(|e| Error::FileIo {
@@ -80,20 +148,58 @@ pub fn unformatted_files(files: Vec<String>) -> Result<Vec<Unformatted>> {
 
         if path.is_dir() {
             for path in crate::fs::gleam_files_excluding_gitignore(&path) {
-                format_file(&mut problem_files, path)?;
+                let config = configs.for_path(&path)?;
+                if nix {
+                    nix_files.extend(nix_external_files(&path)?);
+                }
+                format_file(&mut problem_files, path, config)?;
             }
+        } else if path.extension() == Some("nix") {
+            let _ = nix_files.insert(path);
         } else {
-            format_file(&mut problem_files, path)?;
+            let config = configs.for_path(&path)?;
+            if nix {
+                nix_files.extend(nix_external_files(&path)?);
+            }
+            format_file(&mut problem_files, path, config)?;
         }
     }
 
+    for path in nix_files {
+        let config = configs.for_path(&path)?;
+        format_nix_file(&mut problem_files, path, config)?;
+    }
+
     Ok(problem_files)
 }
 
-fn format_file(problem_files: &mut Vec<Unformatted>, path: Utf8PathBuf) -> Result<()> {
+fn format_file(
+    problem_files: &mut Vec<Unformatted>,
+    path: Utf8PathBuf,
+    config: FormatConfig,
+) -> Result<()> {
     let src = crate::fs::read(&path)?.into();
     let mut output = String::new();
-    glistix_core::format::pretty(&mut output, &src, &path)?;
+    glistix_core::format::pretty(&mut output, &src, &path, config)?;
+
+    if src != output {
+        problem_files.push(Unformatted {
+            source: path.clone(),
+            destination: path,
+            input: src,
+            output,
+        });
+    }
+    Ok(())
+}
+
+fn format_nix_file(
+    problem_files: &mut Vec<Unformatted>,
+    path: Utf8PathBuf,
+    config: FormatConfig,
+) -> Result<()> {
+    let src: EcoString = crate::fs::read(&path)?.into();
+    let output = format_nix(&src, &path, config)?;
 
     if src != output {
         problem_files.push(Unformatted {
@@ -106,6 +212,69 @@ fn format_file(problem_files: &mut Vec<Unformatted>, path: Utf8PathBuf) -> Resul
     Ok(())
 }
 
+fn format_nix(src: &EcoString, path: &Utf8Path, config: FormatConfig) -> Result<String> {
+    glistix_core::nix::format::pretty(src, config.line_width as isize).map_err(|error| Error::Nix {
+        path: path.to_path_buf(),
+        src: src.clone(),
+        error,
+    })
+}
+
+/// The Nix files in the package that the `@external(nix, ...)` attributes of
+/// a Gleam module refer to. Nothing is returned for modules outside of a
+/// package's `src` and `test` directories, as their externals can't be
+/// resolved.
+fn nix_external_files(path: &Utf8Path) -> Result<Vec<Utf8PathBuf>> {
+    let current_directory = get_current_directory()?;
+    let absolute_path = current_directory.join(path);
+    let Some(module_name) = module_name(&absolute_path) else {
+        return Ok(vec![]);
+    };
+
+    let src = crate::fs::read(path)?;
+    let parsed = glistix_core::parse::parse_module(path.to_owned(), &src, &WarningEmitter::null())
+        .map_err(|error| Error::Parse {
+            path: path.to_path_buf(),
+            src: src.clone().into(),
+            error,
+        })?;
+
+    let io = crate::fs::ProjectIO::new();
+    let files = parsed
+        .module
+        .definitions
+        .iter()
+        .filter_map(|definition| match &definition.definition {
+            Definition::Function(function) => function.external_nix.as_ref(),
+            _ => None,
+        })
+        .filter_map(|(external_path, _, _)| {
+            nix_externals::package_nix_file(&io, &module_name, &absolute_path, external_path)
+        })
+        .map(|file| match file.strip_prefix(&current_directory) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => file,
+        })
+        .collect();
+    Ok(files)
+}
+
+/// The name of the module at the given absolute path, if it is within the
+/// `src` or `test` directory of a package.
+fn module_name(path: &Utf8Path) -> Option<String> {
+    let root = get_project_root(path.parent()?.to_path_buf()).ok()?;
+    ["src", "test"].iter().find_map(|directory| {
+        let relative = path.strip_prefix(root.join(directory)).ok()?;
+        let name = relative
+            .with_extension("")
+            .components()
+            .map(|component| component.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        Some(name)
+    })
+}
+
 pub fn read_stdin() -> Result<String> {
     let mut src = String::new();
     let _ = std::io::stdin()
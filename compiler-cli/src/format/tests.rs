@@ -0,0 +1,66 @@
+use camino::Utf8Path;
+
+use super::{unformatted_files, FormatConfigs};
+
+fn write(path: &Utf8Path, content: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn files_use_the_settings_of_their_package() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let root = Utf8Path::from_path(tmp_dir.path()).expect("Non Utf-8 Path");
+    write(&root.join("gleam.toml"), "name = \"outer\"\n");
+    write(
+        &root.join("nested/gleam.toml"),
+        "name = \"nested\"\n\n[format]\nline-width = 100\n",
+    );
+    write(&root.join("src/outer.gleam"), "");
+    write(&root.join("nested/src/nested.gleam"), "");
+
+    let mut configs = FormatConfigs::default();
+    let outer = configs.for_path(&root.join("src/outer.gleam")).unwrap();
+    let nested = configs
+        .for_path(&root.join("nested/src/nested.gleam"))
+        .unwrap();
+    let nested_directory = configs.for_path(&root.join("nested/src")).unwrap();
+
+    assert_eq!(outer.line_width, 80);
+    assert_eq!(nested.line_width, 100);
+    assert_eq!(nested_directory.line_width, 100);
+}
+
+#[test]
+fn files_outside_of_packages_use_the_default_settings() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let root = Utf8Path::from_path(tmp_dir.path()).expect("Non Utf-8 Path");
+    write(&root.join("wibble.gleam"), "");
+
+    let config = FormatConfigs::default()
+        .for_path(&root.join("wibble.gleam"))
+        .unwrap();
+
+    assert_eq!(config, Default::default());
+}
+
+#[test]
+fn nix_externals_are_formatted_with_the_nix_flag() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let root = Utf8Path::from_path(tmp_dir.path()).expect("Non Utf-8 Path");
+    write(&root.join("gleam.toml"), "name = \"wibble\"\n");
+    write(
+        &root.join("src/wibble/wobble.gleam"),
+        "@external(nix, \"./ffi.nix\", \"wabble\")\npub fn wabble() -> Int\n",
+    );
+    write(&root.join("src/wibble/ffi.nix"), "{wabble={}: 1;}");
+    let files = vec![root.to_string()];
+
+    let problem_files = unformatted_files(files.clone(), false, &mut FormatConfigs::default());
+    assert!(problem_files.unwrap().is_empty());
+
+    let problem_files = unformatted_files(files, true, &mut FormatConfigs::default()).unwrap();
+    assert_eq!(problem_files.len(), 1);
+    assert_eq!(problem_files[0].source, root.join("src/wibble/ffi.nix"));
+    assert_eq!(problem_files[0].output, "{ wabble = { }: 1; }\n");
+}
@@ -1,5 +1,6 @@
 use glistix_core::{
     build::{NullTelemetry, Target},
+    dependency::VersionPreference,
     error::{parse_os, Error, FileIoAction, FileKind, OS},
     io::{
         BeamCompiler, CommandExecutor, Content, DirEntry, FileSystemReader, FileSystemWriter,
@@ -96,6 +97,39 @@ impl ProjectIO {
     pub fn boxed() -> Box<Self> {
         Box::new(Self::new())
     }
+
+    /// Starts a program with its stdin and stdout piped to this process, for
+    /// programs which are talked to for a while, such as `nix repl`, rather
+    /// than run to completion with `exec`.
+    pub fn spawn_piped(
+        &self,
+        program: &str,
+        args: &[String],
+        cwd: Option<&Utf8Path>,
+    ) -> Result<std::process::Child, Error> {
+        tracing::trace!(program=program, args=?args.join(" "), cwd=?cwd, "command_spawn");
+        std::process::Command::new(program)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .current_dir(cwd.unwrap_or_else(|| Utf8Path::new("./")))
+            .spawn()
+            .map_err(|error| command_error(program, error))
+    }
+}
+
+pub fn command_error(program: &str, error: io::Error) -> Error {
+    match error.kind() {
+        io::ErrorKind::NotFound => Error::ShellProgramNotFound {
+            program: program.to_string(),
+            os: get_os(),
+        },
+
+        other => Error::ShellCommand {
+            program: program.to_string(),
+            err: Some(other),
+        },
+    }
 }
 
 impl FileSystemReader for ProjectIO {
@@ -138,6 +172,15 @@ impl FileSystemReader for ProjectIO {
             })
     }
 
+    fn file_size(&self, path: &Utf8Path) -> Result<u64, Error> {
+        path.metadata().map(|m| m.len()).map_err(|e| Error::FileIo {
+            action: FileIoAction::ReadMetadata,
+            kind: FileKind::File,
+            path: path.to_path_buf(),
+            err: Some(e.to_string()),
+        })
+    }
+
     fn canonicalise(&self, path: &Utf8Path) -> Result<Utf8PathBuf, Error> {
         canonicalise(path)
     }
@@ -149,7 +192,16 @@ impl FileSystemWriter for ProjectIO {
     }
 
     fn copy(&self, from: &Utf8Path, to: &Utf8Path) -> Result<()> {
-        copy(from, to)
+        copy(from, to)?;
+        // Keeping the modification time lets an unchanged copy be told apart
+        // without reading it. Failing to do so only makes that slower.
+        if let Ok(modified) = from.metadata().and_then(|m| m.modified()) {
+            let _ = File::options()
+                .write(true)
+                .open(to)
+                .and_then(|file| file.set_modified(modified));
+        }
+        Ok(())
     }
 
     fn copy_dir(&self, from: &Utf8Path, to: &Utf8Path) -> Result<()> {
@@ -168,6 +220,10 @@ impl FileSystemWriter for ProjectIO {
         symlink_dir(from, to)
     }
 
+    fn rename(&self, from: &Utf8Path, to: &Utf8Path) -> Result<(), Error> {
+        rename(from, to)
+    }
+
     fn delete_file(&self, path: &Utf8Path) -> Result<()> {
         delete_file(path)
     }
@@ -205,18 +261,7 @@ impl CommandExecutor for ProjectIO {
 
         match result {
             Ok(status) => Ok(status.code().unwrap_or_default()),
-
-            Err(error) => Err(match error.kind() {
-                io::ErrorKind::NotFound => Error::ShellProgramNotFound {
-                    program: program.to_string(),
-                    os: get_os(),
-                },
-
-                other => Error::ShellCommand {
-                    program: program.to_string(),
-                    err: Some(other),
-                },
-            }),
+            Err(error) => Err(command_error(program, error)),
         }
     }
 }
@@ -246,7 +291,15 @@ impl MakeLocker for ProjectIO {
 
 impl DownloadDependencies for ProjectIO {
     fn download_dependencies(&self, paths: &ProjectPaths) -> Result<Manifest> {
-        crate::dependencies::download(paths, NullTelemetry, None, Vec::new(), UseManifest::Yes)
+        crate::dependencies::download(
+            paths,
+            NullTelemetry,
+            None,
+            Vec::new(),
+            UseManifest::Yes,
+            VersionPreference::Newest,
+            None,
+        )
     }
 }
 
@@ -267,8 +320,14 @@ pub fn delete_directory(dir: &Utf8Path) -> Result<(), Error> {
 
 pub fn delete_file(file: &Utf8Path) -> Result<(), Error> {
     tracing::trace!("Deleting file {:?}", file);
-    if file.exists() {
-        std::fs::remove_file(file).map_err(|e| Error::FileIo {
+    // Unlike `exists`, this doesn't follow symlinks, so a symlink to a path
+    // which no longer exists is deleted too.
+    if file.symlink_metadata().is_ok() {
+        let result = std::fs::remove_file(file);
+        // Symlinks to directories are deleted as directories on Windows.
+        #[cfg(target_family = "windows")]
+        let result = result.or_else(|_| std::fs::remove_dir(file));
+        result.map_err(|e| Error::FileIo {
             action: FileIoAction::Delete,
             kind: FileKind::File,
             path: file.to_path_buf(),
@@ -563,19 +622,32 @@ pub fn copy(
         .map(|_| ())
 }
 
-// pub fn rename(path: impl AsRef<Utf8Path> + Debug, to: impl AsRef<Utf8Path> + Debug) -> Result<(), Error> {
-//     tracing::trace!(from=?path, to=?to, "renaming_file");
+pub fn rename(
+    path: impl AsRef<Utf8Path> + Debug,
+    to: impl AsRef<Utf8Path> + Debug,
+) -> Result<(), Error> {
+    tracing::trace!(from=?path, to=?to, "renaming_file");
+
+    // `std::fs::rename` replaces an existing file, and on some platforms an
+    // empty directory, at the destination.
+    if to.as_ref().symlink_metadata().is_ok() {
+        return Err(Error::FileIo {
+            action: FileIoAction::Rename,
+            kind: FileKind::File,
+            path: Utf8PathBuf::from(path.as_ref()),
+            err: Some(format!("{} already exists", to.as_ref())),
+        });
+    }
 
-//     // TODO: include the destination in the error message
-//     std::fs::rename(&path, &to)
-//         .map_err(|err| Error::FileIo {
-//             action: FileIoAction::Rename,
-//             kind: FileKind::File,
-//             path: Utf8PathBuf::from(path.as_ref()),
-//             err: Some(err.to_string()),
-//         })
-//         .map(|_| ())
-// }
+    std::fs::rename(path.as_ref(), to.as_ref())
+        .map_err(|err| Error::FileIo {
+            action: FileIoAction::Rename,
+            kind: FileKind::File,
+            path: Utf8PathBuf::from(path.as_ref()),
+            err: Some(err.to_string()),
+        })
+        .map(|_| ())
+}
 
 pub fn copy_dir(
     path: impl AsRef<Utf8Path> + Debug,
@@ -670,6 +742,79 @@ pub fn is_inside_git_work_tree(path: &Utf8Path) -> Result<bool, Error> {
     }
 }
 
+/// The files within the given path that have changed since the given git ref,
+/// including uncommitted and untracked files. The paths are relative to the
+/// given path.
+///
+pub fn git_changed_files(path: &Utf8Path, since: &str) -> Result<HashSet<Utf8PathBuf>, Error> {
+    tracing::trace!(path=?path, since=?since, "finding_changed_files");
+
+    let changed = git_output(
+        path,
+        since,
+        &["diff", "--name-only", "--relative", since, "--"],
+    )?;
+    let untracked = git_output(path, since, &["ls-files", "--others", "--exclude-standard"])?;
+    Ok(changed
+        .lines()
+        .chain(untracked.lines())
+        .map(Utf8PathBuf::from)
+        .collect())
+}
+
+fn git_output(path: &Utf8Path, since: &str, args: &[&str]) -> Result<String, Error> {
+    let output = run_git(path, args)?;
+    if !output.status.success() {
+        return Err(Error::GitChangedFiles {
+            since: since.into(),
+            error: String::from_utf8_lossy(&output.stderr).trim().into(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Runs git with the given arguments in the given path, returning what it
+/// printed to stdout.
+///
+pub fn git(path: &Utf8Path, args: &[&str]) -> Result<String, Error> {
+    tracing::trace!(path=?path, args=?args, "running_git");
+
+    let output = run_git(path, args)?;
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            command: args.join(" "),
+            error: String::from_utf8_lossy(&output.stderr).trim().into(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Whether the given git ref, such as `refs/tags/v1.0.0`, exists in the
+/// repository of the given path.
+///
+pub fn git_ref_exists(path: &Utf8Path, reference: &str) -> Result<bool, Error> {
+    let output = run_git(path, &["rev-parse", "--verify", "--quiet", reference])?;
+    Ok(output.status.success())
+}
+
+fn run_git(path: &Utf8Path, args: &[&str]) -> Result<std::process::Output, Error> {
+    std::process::Command::new("git")
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .current_dir(path)
+        .output()
+        .map_err(|error| match error.kind() {
+            io::ErrorKind::NotFound => Error::ShellProgramNotFound {
+                program: "git".into(),
+                os: get_os(),
+            },
+            other => Error::ShellCommand {
+                program: "git".into(),
+                err: Some(other),
+            },
+        })
+}
+
 /// Run `git init` in the given path.
 /// If git is not installed then we do nothing.
 pub fn git_init(path: &Utf8Path) -> Result<(), Error> {
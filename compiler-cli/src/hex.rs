@@ -1,14 +1,123 @@
 mod auth;
+mod keyring;
 
-use crate::{cli, http::HttpClient};
+use crate::{cli, http::HttpClient, registry::Registry};
 use glistix_core::{
+    config::{HexConfig, PackageConfig},
     hex::{self, RetirementReason},
     io::HttpClient as _,
     Error, Result,
 };
+use std::cell::RefCell;
 
 pub use auth::HexAuthentication;
 
+/// The Hex repository to download the given packages from, authenticated
+/// with the Hex API key when the config requires it or when one of the
+/// packages belongs to an organisation.
+///
+pub fn repository<'a>(
+    config: &HexConfig,
+    packages: impl IntoIterator<Item = &'a str>,
+) -> Result<hex::Repository> {
+    LazyRepository::new(config).for_packages(packages)
+}
+
+/// The Hex repository packages are looked up in while resolving versions.
+/// Organisation packages may only turn up as dependencies of other packages,
+/// so the Hex API key is only read once a package needs it.
+///
+#[derive(Debug)]
+pub struct LazyRepository {
+    config: HexConfig,
+    repository: RefCell<hex::Repository>,
+}
+
+impl LazyRepository {
+    pub fn new(config: &HexConfig) -> Self {
+        Self {
+            config: config.clone(),
+            repository: RefCell::new(hex::Repository::new(config, None)),
+        }
+    }
+
+    /// The repository to look up the given packages in, reading the Hex API
+    /// key first if one of them requires it and it hasn't been read yet.
+    ///
+    pub fn for_packages<'a>(
+        &self,
+        packages: impl IntoIterator<Item = &'a str>,
+    ) -> Result<hex::Repository> {
+        let requires_authentication = self.config.authenticate
+            || packages
+                .into_iter()
+                .any(|name| hex::split_organisation_package(name).0.is_some());
+        if requires_authentication && self.repository.borrow().api_key().is_none() {
+            let api_key = auth::read_api_key()?.ok_or(Error::HexCredentialsRequired)?;
+            let _ = self
+                .repository
+                .replace(hex::Repository::new(&self.config, Some(api_key)));
+        }
+        Ok(self.repository.borrow().clone())
+    }
+}
+
+/// The Hex package registry, which packages are published to unless another
+/// registry is configured.
+pub struct HexRegistry<'runtime> {
+    runtime: &'runtime tokio::runtime::Runtime,
+    config: hexpm::Config,
+    api_key: String,
+}
+
+impl<'runtime> HexRegistry<'runtime> {
+    /// Gets the Hex API key to publish with, asking the user to log in if
+    /// there is none yet.
+    ///
+    pub fn new(runtime: &'runtime tokio::runtime::Runtime) -> Result<Self> {
+        let config = hexpm::Config::new();
+        let api_key = HexAuthentication::new(runtime, config.clone()).get_or_create_api_key()?;
+        Ok(Self {
+            runtime,
+            config,
+            api_key,
+        })
+    }
+}
+
+impl Registry for HexRegistry<'_> {
+    fn publish_package(
+        &self,
+        config: &PackageConfig,
+        tarball: Vec<u8>,
+        replace: bool,
+    ) -> Result<()> {
+        self.runtime.block_on(hex::publish_package(
+            tarball,
+            config.version.to_string(),
+            &self.api_key,
+            &self.config,
+            replace,
+            &HttpClient::new(),
+        ))
+    }
+
+    fn publish_documentation(&self, config: &PackageConfig, tarball: Vec<u8>) -> Result<()> {
+        self.runtime.block_on(hex::publish_documentation(
+            &config.name,
+            &config.version,
+            tarball,
+            &self.api_key,
+            &self.config,
+            &HttpClient::new(),
+        ))
+    }
+
+    fn package_url(&self, config: &PackageConfig) -> Option<String> {
+        Some(format!("https://hex.pm/packages/{}", config.name))
+    }
+}
+
 pub fn retire(
     package: String,
     version: String,
@@ -1,3 +1,4 @@
+use super::keyring;
 use crate::{cli, http::HttpClient};
 use glistix_core::{encryption, hex, paths::global_hexpm_credentials_path, Error, Result};
 use std::time::SystemTime;
@@ -66,6 +67,9 @@ encrypt your Hex API key.
 
         crate::fs::write(&path, &format!("{name}\n{encrypted}"))?;
         println!("Encrypted Hex API key written to {path}");
+        if keyring::store_api_key(&api_key) {
+            println!("Hex API key stored in the system keyring");
+        }
 
         Ok(UnencryptedApiKey {
             unencrypted: api_key,
@@ -135,6 +139,20 @@ encrypt your Hex API key.
     }
 }
 
+/// Get an API key from
+/// 1. the HEXPM_API_KEY env var
+/// 2. the system keyring, as stored by `glistix hex authenticate`
+///
+/// Unlike `HexAuthentication::get_or_create_api_key` the user is never asked
+/// for anything, so dependencies can be resolved non-interactively, and no
+/// key is created when there is none.
+pub fn read_api_key() -> Result<Option<String>> {
+    if let Some(key) = HexAuthentication::read_env_api_key()? {
+        return Ok(Some(key));
+    }
+    Ok(keyring::read_api_key())
+}
+
 fn ask_local_password() -> std::result::Result<String, Error> {
     std::env::var(PASS_ENV_NAME).or_else(|_| cli::ask_password(LOCAL_PASS_PROMPT))
 }
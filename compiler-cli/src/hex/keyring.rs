@@ -0,0 +1,99 @@
+//! Storage of the Hex API key in the system keyring, through the `security`
+//! tool on macOS and the `secret-tool` tool of libsecret elsewhere, so that
+//! it can be read without asking for the local password.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+const SERVICE: &str = "glistix-hexpm";
+const ACCOUNT: &str = "api-key";
+
+/// Reads the Hex API key from the system keyring, if it has one. Any failure,
+/// such as there being no keyring tool installed, is treated as there being
+/// no key.
+///
+pub fn read_api_key() -> Option<String> {
+    let output = read_command()
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let key = String::from_utf8(output.stdout).ok()?;
+    let key = key.trim();
+    if key.is_empty() {
+        None
+    } else {
+        Some(key.to_string())
+    }
+}
+
+/// Stores the Hex API key in the system keyring, returning whether it could
+/// be stored.
+///
+pub fn store_api_key(key: &str) -> bool {
+    let Ok(mut child) = store_command(key)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    // `secret-tool` reads the secret from stdin rather than its arguments,
+    // while `security` ignores it.
+    if let Some(mut stdin) = child.stdin.take() {
+        if cfg!(not(target_os = "macos")) && stdin.write_all(key.as_bytes()).is_err() {
+            return false;
+        }
+    }
+    child.wait().is_ok_and(|status| status.success())
+}
+
+#[cfg(target_os = "macos")]
+fn read_command() -> Command {
+    let mut command = Command::new("security");
+    let _ = command.args(["find-generic-password", "-s", SERVICE, "-a", ACCOUNT, "-w"]);
+    command
+}
+
+#[cfg(not(target_os = "macos"))]
+fn read_command() -> Command {
+    let mut command = Command::new("secret-tool");
+    let _ = command.args(["lookup", "service", SERVICE, "account", ACCOUNT]);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn store_command(key: &str) -> Command {
+    let mut command = Command::new("security");
+    let _ = command.args([
+        "add-generic-password",
+        "-U",
+        "-s",
+        SERVICE,
+        "-a",
+        ACCOUNT,
+        "-w",
+        key,
+    ]);
+    command
+}
+
+#[cfg(not(target_os = "macos"))]
+fn store_command(_key: &str) -> Command {
+    let mut command = Command::new("secret-tool");
+    let _ = command.args([
+        "store",
+        "--label=Glistix Hex API key",
+        "service",
+        SERVICE,
+        "account",
+        ACCOUNT,
+    ]);
+    command
+}
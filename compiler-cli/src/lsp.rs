@@ -33,7 +33,7 @@ this program by pressing ctrl+c.
 
     // Run the server and wait for the two threads to end, typically by trigger
     // LSP Exit event.
-    LanguageServer::new(&connection, ProjectIO::new())?.run()?;
+    LanguageServer::new(&connection, ProjectIO::new(), resident_memory_bytes)?.run()?;
 
     // Shut down gracefully.
     drop(connection);
@@ -43,6 +43,27 @@ this program by pressing ctrl+c.
     Ok(())
 }
 
+/// The amount of physical memory used by the language server's process.
+/// This is only known on Linux, where it is read from `/proc`.
+#[cfg(target_os = "linux")]
+fn resident_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kilobytes = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_memory_bytes() -> Option<u64> {
+    None
+}
+
 #[derive(Debug)]
 pub struct LspLocker(BuildLock);
 
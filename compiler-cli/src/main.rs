@@ -50,13 +50,16 @@ extern crate pretty_assertions;
 
 mod add;
 mod beam_compiler;
+mod bench;
 mod build;
 mod build_lock;
+mod clean;
 mod cli;
 mod compile_package;
 mod config;
 mod dependencies;
 mod docs;
+mod explain;
 mod export;
 mod fix;
 mod format;
@@ -67,9 +70,16 @@ mod lsp;
 mod new;
 mod panic;
 mod publish;
+mod registry;
 mod remove;
+mod repl;
 mod run;
+mod search;
 mod shell;
+mod test_runner;
+mod test_targets;
+mod timings;
+mod version;
 
 use config::root_config;
 use dependencies::UseManifest;
@@ -79,7 +89,9 @@ pub use glistix_core::error::{Error, Result};
 use glistix_core::{
     analyse::TargetSupport,
     build::{Codegen, Compile, Mode, NullTelemetry, Options, Runtime, Target},
+    dependency::VersionPreference,
     hex::RetirementReason,
+    i18n,
     paths::ProjectPaths,
     version::COMPILER_VERSION,
 };
@@ -99,6 +111,40 @@ struct UpdateOptions {
     /// If omitted, all dependencies will be updated
     #[arg(verbatim_doc_comment)]
     packages: Vec<String>,
+
+    /// Write the decisions made while resolving versions to this file
+    #[arg(long)]
+    resolution_log: Option<Utf8PathBuf>,
+
+    /// Show the versions that would be selected without changing the manifest
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Select the oldest versions allowed by the requirements instead of the
+    /// newest, to check that their lower bounds are compatible
+    #[arg(long)]
+    minimal_versions: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+struct CleanOptions {
+    /// Only delete the compiled dependencies, keeping the project's own
+    /// compiled modules
+    #[arg(long, conflicts_with = "package")]
+    deps: bool,
+
+    /// Only delete the project's own compiled modules, keeping its compiled
+    /// dependencies
+    #[arg(long)]
+    package: bool,
+
+    /// Only delete the build output of this target
+    #[arg(short, long, ignore_case = true, help = target_doc())]
+    target: Option<Target>,
+
+    /// List the directories that would be deleted without deleting them
+    #[arg(long)]
+    dry_run: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -129,12 +175,80 @@ enum Command {
         /// Don't print progress information
         #[clap(long)]
         no_print_progress: bool,
+
+        /// How to print warnings and errors
+        #[arg(long, ignore_case = true, default_value = "text")]
+        format: build::DiagnosticFormat,
+
+        #[command(flatten)]
+        diagnostics: build::DiagnosticOptions,
+
+        /// Build the project twice from scratch and fail if the outputs differ
+        #[arg(
+            long,
+            conflicts_with_all = ["format", "style", "paths", "timings", "timings_trace"]
+        )]
+        check_determinism: bool,
+
+        /// Fail if the code generated for the project differs from the copy
+        /// committed in this directory, `output` by default
+        #[arg(
+            long,
+            value_name = "DIRECTORY",
+            num_args = 0..=1,
+            default_missing_value = "output",
+            conflicts_with_all = [
+                "check_determinism",
+                "format",
+                "style",
+                "paths",
+                "timings",
+                "timings_trace"
+            ]
+        )]
+        assert_no_diff: Option<Utf8PathBuf>,
+
+        #[command(flatten)]
+        timings: timings::TimingsOptions,
     },
 
     /// Type check the project
     Check {
         #[arg(short, long, ignore_case = true, help = target_doc())]
         target: Option<Target>,
+
+        /// How to print warnings and errors
+        #[arg(long, ignore_case = true, default_value = "text")]
+        format: build::DiagnosticFormat,
+
+        #[command(flatten)]
+        diagnostics: build::DiagnosticOptions,
+
+        /// Only check the modules affected by the files changed since this git
+        /// ref, along with the modules they depend on
+        #[arg(long, value_name = "REF")]
+        since: Option<String>,
+
+        #[command(flatten)]
+        timings: timings::TimingsOptions,
+    },
+
+    /// Search the project and its dependencies for functions by their type
+    ///
+    /// Functions match when their type is the same as the signature once type
+    /// variables are renamed, with their arguments in any order. For example:
+    ///
+    ///     glistix search "fn(List(a), fn(a) -> b) -> List(b)"
+    #[command(verbatim_doc_comment)]
+    Search {
+        /// The type to search for, such as `fn(String) -> Int`
+        signature: String,
+    },
+
+    /// Explain an error code, such as the `E2004` of `error[E2004]`
+    Explain {
+        /// The code of the error to explain
+        code: String,
     },
 
     /// Publish the project to the Hex package manager
@@ -183,13 +297,22 @@ enum Command {
         /// Check if inputs are formatted without changing them
         #[arg(long)]
         check: bool,
+
+        /// Also format the Nix files referenced by `@external(nix, ...)`
+        /// attributes, or format STDIN as Nix
+        #[arg(long)]
+        nix: bool,
     },
-    /// Rewrite deprecated Gleam code
+    /// Rewrite deprecated Gleam code and apply migration rules, such as
+    /// adding Nix external stubs in Nix projects
     Fix,
 
     /// Start an Erlang shell
     Shell,
 
+    /// Start an interactive session evaluating Gleam on Nix
+    Repl,
+
     /// Run the project
     #[command(trailing_var_arg = true)]
     Run {
@@ -203,23 +326,76 @@ enum Command {
         #[arg(short, long)]
         module: Option<String>,
 
+        /// Print the value of a Gleam expression instead of running a module.
+        /// Outside of Nix, printing uses `io.debug` from `gleam_stdlib`
+        #[arg(long, value_name = "EXPRESSION", conflicts_with = "module")]
+        eval: Option<String>,
+
+        /// A module to import for the `--eval` expression, e.g. `gleam/list`
+        /// or `gleam/dict.{type Dict}`
+        #[arg(long = "import", value_name = "MODULE", requires = "eval")]
+        imports: Vec<String>,
+
         /// Don't print progress information
         #[clap(long)]
         no_print_progress: bool,
 
+        /// A dotenv-style file of variables to set for the program
+        #[arg(long)]
+        env_file: Option<Utf8PathBuf>,
+
         arguments: Vec<String>,
     },
 
     /// Run the project tests
     #[command(trailing_var_arg = true)]
     Test {
+        #[arg(short, long = "target", value_name = "TARGET", help = test_target_doc())]
+        targets: Vec<test_targets::TargetSelection>,
+
+        /// Run the tests on every target the package supports, as listed by
+        /// `supported-targets` in the `[glistix]` section of gleam.toml
+        #[arg(long, conflicts_with = "targets")]
+        all_targets: bool,
+
+        #[arg(long, ignore_case = true, help = runtime_doc())]
+        runtime: Option<Runtime>,
+
+        /// A dotenv-style file of variables to set for the tests
+        #[arg(long)]
+        env_file: Option<Utf8PathBuf>,
+
+        /// Only run the tests whose `module.function` name contains this,
+        /// calling them directly instead of the `main` function of the tests
+        #[arg(long, value_name = "PATTERN", conflicts_with = "arguments")]
+        filter: Option<String>,
+
+        /// Only run the K-th of N parts the tests are split into, e.g. `2/4`,
+        /// calling them directly instead of the `main` function of the tests
+        #[arg(long, value_name = "K/N", conflicts_with = "arguments")]
+        shard: Option<test_runner::Shard>,
+
+        arguments: Vec<String>,
+    },
+
+    /// Time the public functions without arguments named `bench_*`
+    Bench {
         #[arg(short, long, ignore_case = true, help = target_doc())]
         target: Option<Target>,
 
         #[arg(long, ignore_case = true, help = runtime_doc())]
         runtime: Option<Runtime>,
 
-        arguments: Vec<String>,
+        /// How many times to run each benchmark
+        #[arg(short, long, default_value_t = 10)]
+        iterations: u32,
+
+        /// Only run the benchmarks whose `module.function` name contains this
+        filter: Option<String>,
+
+        /// Print the timings as JSON, in microseconds
+        #[arg(long)]
+        json: bool,
     },
 
     /// Compile a single Gleam package
@@ -232,13 +408,43 @@ enum Command {
 
     /// Add new project dependencies
     Add {
-        /// The names of Hex packages to add
+        /// The names of the packages to add
         #[arg(required = true)]
         packages: Vec<String>,
 
         /// Add the packages as dev-only dependencies
         #[arg(long)]
         dev: bool,
+
+        /// Add the package from a local directory instead of Hex
+        #[arg(long, conflicts_with = "git")]
+        path: Option<Utf8PathBuf>,
+
+        /// Add the package from a git repository instead of Hex
+        #[arg(long)]
+        git: Option<String>,
+
+        /// Write the decisions made while resolving versions to this file
+        #[arg(long)]
+        resolution_log: Option<Utf8PathBuf>,
+    },
+
+    /// Read and edit the keys of gleam.toml
+    #[command(subcommand)]
+    Config(Config),
+
+    /// Bump the version of the project in gleam.toml
+    Version {
+        /// Which part of the version to increase
+        bump: version::Bump,
+
+        /// Commit the new version and tag it as `v<version>` with git
+        #[arg(long)]
+        tag: bool,
+
+        /// Add a section for the new version to CHANGELOG.md
+        #[arg(long)]
+        changelog: bool,
     },
 
     /// Remove project dependencies
@@ -249,7 +455,7 @@ enum Command {
     },
 
     /// Clean build artifacts
-    Clean,
+    Clean(CleanOptions),
 
     /// Run the language server, to be used by editors
     #[command(name = "lsp")]
@@ -268,6 +474,13 @@ fn target_doc() -> String {
     format!("The platform to target ({})", Target::VARIANTS.join("|"))
 }
 
+fn test_target_doc() -> String {
+    format!(
+        "The platforms to target ({}|all)",
+        Target::VARIANTS.join("|")
+    )
+}
+
 fn runtime_doc() -> String {
     format!("The runtime to target ({})", Runtime::VARIANTS.join("|"))
 }
@@ -284,12 +497,26 @@ pub enum ExportTarget {
     TypescriptPrelude,
     /// The Nix prelude module
     NixPrelude,
+    /// A Nix overlay adding the package, compiled to Nix, to `glistixPackages`
+    NixOverlay,
+    /// The package and its dependencies compiled to Nix, with a `default.nix`, to use without Glistix
+    NixClosure {
+        /// Also archive the directory into a .tar.gz file
+        #[arg(long)]
+        tarball: bool,
+    },
     /// Information on the modules, functions, and types in the project in JSON format
     PackageInterface {
         #[arg(long = "out", required = true)]
         /// The path to write the JSON file to
         output: Utf8PathBuf,
     },
+    /// A CycloneDX software bill of materials of the project's dependencies in JSON format
+    Sbom {
+        #[arg(long = "out", required = true)]
+        /// The path to write the JSON file to
+        output: Utf8PathBuf,
+    },
 }
 
 #[derive(Args, Debug, Clone)]
@@ -362,10 +589,56 @@ enum Dependencies {
     List,
 
     /// Download all dependency packages
-    Download,
+    Download {
+        /// Write the decisions made while resolving versions to this file
+        #[arg(long)]
+        resolution_log: Option<Utf8PathBuf>,
+    },
 
     /// Update dependency packages to their latest versions
     Update(UpdateOptions),
+
+    /// List the licences of all dependency packages, along with an SPDX
+    /// expression for the whole project
+    Licenses {
+        /// Print the licences as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List the deprecated modules, types and values of dependency packages
+    /// that the project uses, grouped by package
+    Deprecations {
+        /// Print the deprecations as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show every dependency path leading from the project to a package
+    Why {
+        /// The name of the package
+        package: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum Config {
+    /// Print the value of a key, such as `version` or `javascript.runtime`
+    Get {
+        /// The key, with the names of its tables separated by dots
+        key: String,
+    },
+
+    /// Set the value of a key, keeping the formatting and comments of the file
+    ///
+    /// The value is read as TOML if possible, and as a string otherwise.
+    Set {
+        /// The key, with the names of its tables separated by dots
+        key: String,
+
+        /// The new value of the key
+        value: String,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -462,6 +735,7 @@ enum Docs {
 
 fn main() {
     initialise_logger();
+    initialise_language();
     panic::add_handler();
     let stderr = cli::stderr_buffer_writer();
 
@@ -470,9 +744,33 @@ fn main() {
             target,
             warnings_as_errors,
             no_print_progress,
-        } => command_build(target, warnings_as_errors, no_print_progress),
+            format,
+            diagnostics,
+            check_determinism,
+            assert_no_diff,
+            timings,
+        } => command_build(
+            target,
+            warnings_as_errors,
+            no_print_progress,
+            format,
+            diagnostics,
+            check_determinism,
+            assert_no_diff,
+            timings,
+        ),
+
+        Command::Check {
+            target,
+            format,
+            diagnostics,
+            since,
+            timings,
+        } => command_check(target, format, diagnostics, since, timings),
+
+        Command::Search { signature } => search::command(signature),
 
-        Command::Check { target } => command_check(target),
+        Command::Explain { code } => explain::command(code),
 
         Command::Docs(Docs::Build { open, target }) => {
             docs::build(docs::BuildOptions { open, target })
@@ -486,15 +784,31 @@ fn main() {
             stdin,
             files,
             check,
-        } => format::run(stdin, check, files),
+            nix,
+        } => format::run(stdin, check, nix, files),
 
         Command::Fix => fix::run(),
 
         Command::Deps(Dependencies::List) => dependencies::list(),
 
-        Command::Deps(Dependencies::Download) => download_dependencies(),
+        Command::Deps(Dependencies::Download { resolution_log }) => {
+            download_dependencies(resolution_log)
+        }
+
+        Command::Deps(Dependencies::Update(options)) => dependencies::update(
+            options.packages,
+            options.resolution_log,
+            options.dry_run,
+            options.minimal_versions,
+        ),
+
+        Command::Deps(Dependencies::Licenses { json }) => dependencies::licences::command(json),
 
-        Command::Deps(Dependencies::Update(options)) => dependencies::update(options.packages),
+        Command::Deps(Dependencies::Deprecations { json }) => {
+            dependencies::deprecations::command(json)
+        }
+
+        Command::Deps(Dependencies::Why { package }) => dependencies::why::command(package),
 
         Command::Hex(Hex::Authenticate) => hex::authenticate(),
 
@@ -502,12 +816,17 @@ fn main() {
 
         Command::Shell => shell::command(),
 
+        Command::Repl => repl::command(),
+
         Command::Run {
             target,
             arguments,
             runtime,
             module,
+            eval,
+            imports,
             no_print_progress,
+            env_file,
         } => run::command(
             arguments,
             target,
@@ -515,13 +834,65 @@ fn main() {
             module,
             run::Which::Src,
             no_print_progress,
+            env_file,
+            eval.map(|expression| run::Eval {
+                expression,
+                imports,
+            }),
         ),
 
-        Command::Test {
+        Command::Bench {
             target,
+            runtime,
+            iterations,
+            filter,
+            json,
+        } => bench::command(target, runtime, iterations, filter, json),
+
+        Command::Test {
+            all_targets: true,
+            arguments,
+            runtime,
+            env_file,
+            filter,
+            shard,
+            ..
+        } => test_targets::all_targets_command(
             arguments,
             runtime,
-        } => run::command(arguments, target, runtime, None, run::Which::Test, false),
+            env_file,
+            test_runner::Selection { filter, shard },
+        ),
+
+        Command::Test {
+            targets,
+            arguments,
+            runtime,
+            env_file,
+            filter,
+            shard,
+            ..
+        } => {
+            let selection = test_runner::Selection { filter, shard };
+            match test_targets::resolve(&targets) {
+                targets if targets.len() > 1 => {
+                    test_targets::command(targets, arguments, runtime, env_file, selection)
+                }
+                targets if !selection.is_everything() => {
+                    test_runner::command(targets.first().copied(), runtime, selection, env_file)
+                }
+                targets => run::command(
+                    arguments,
+                    targets.first().copied(),
+                    runtime,
+                    None,
+                    run::Which::Test,
+                    false,
+                    env_file,
+                    None,
+                ),
+            }
+        }
 
         Command::CompilePackage(opts) => compile_package::command(opts),
 
@@ -540,13 +911,43 @@ fn main() {
 
         Command::Hex(Hex::Revert { package, version }) => hex::revert(package, version),
 
-        Command::Add { packages, dev } => add::command(packages, dev),
+        Command::Add {
+            packages,
+            dev,
+            path,
+            git,
+            resolution_log,
+        } => add::command(packages, dev, path, git, resolution_log),
+
+        Command::Config(Config::Get { key }) => config::get(key),
+
+        Command::Config(Config::Set { key, value }) => config::set(key, value),
+
+        Command::Version {
+            bump,
+            tag,
+            changelog,
+        } => version::command(bump, tag, changelog),
 
         Command::Remove { packages } => remove::command(packages),
 
-        Command::Update(options) => dependencies::update(options.packages),
+        Command::Update(options) => dependencies::update(
+            options.packages,
+            options.resolution_log,
+            options.dry_run,
+            options.minimal_versions,
+        ),
 
-        Command::Clean => clean(),
+        Command::Clean(options) => {
+            let scope = if options.deps {
+                clean::Scope::Dependencies
+            } else if options.package {
+                clean::Scope::Package
+            } else {
+                clean::Scope::All
+            };
+            clean::command(scope, options.target, options.dry_run)
+        }
 
         Command::LanguageServer => lsp::main(),
 
@@ -555,9 +956,12 @@ fn main() {
         Command::Export(ExportTarget::JavascriptPrelude) => export::javascript_prelude(),
         Command::Export(ExportTarget::TypescriptPrelude) => export::typescript_prelude(),
         Command::Export(ExportTarget::NixPrelude) => export::nix_prelude(),
+        Command::Export(ExportTarget::NixOverlay) => export::nix_overlay(),
+        Command::Export(ExportTarget::NixClosure { tarball }) => export::nix_closure(tarball),
         Command::Export(ExportTarget::PackageInterface { output }) => {
             export::package_interface(output)
         }
+        Command::Export(ExportTarget::Sbom { output }) => export::sbom(output),
     };
 
     match result {
@@ -568,51 +972,80 @@ fn main() {
             tracing::error!(error = ?error, "Failed");
             let mut buffer = stderr.buffer();
             error.pretty(&mut buffer);
+            explain::write_hint(&error, &mut buffer);
             stderr.print(&buffer).expect("Final result error writing");
             std::process::exit(1);
         }
     }
 }
 
-fn command_check(target: Option<Target>) -> Result<()> {
-    let _ = build::main(
-        Options {
-            root_target_support: TargetSupport::Enforced,
-            warnings_as_errors: false,
-            codegen: Codegen::DepsOnly,
-            compile: Compile::All,
-            mode: Mode::Dev,
-            target,
-            no_print_progress: false,
-        },
-        build::download_dependencies(cli::Reporter::new())?,
-    )?;
-    Ok(())
+fn command_check(
+    target: Option<Target>,
+    format: build::DiagnosticFormat,
+    diagnostics: build::DiagnosticOptions,
+    since: Option<String>,
+    timings: timings::TimingsOptions,
+) -> Result<()> {
+    let changed_files = match since {
+        Some(since) => Some(fs::git_changed_files(find_project_paths()?.root(), &since)?),
+        None => None,
+    };
+    let options = Options {
+        root_target_support: TargetSupport::Enforced,
+        warnings_as_errors: false,
+        codegen: Codegen::DepsOnly,
+        compile: Compile::All,
+        mode: Mode::Dev,
+        target,
+        no_print_progress: false,
+        changed_files,
+    };
+    build::main_with_format(options, format, diagnostics, &timings, || {
+        build::download_dependencies(cli::Reporter::new())
+    })
 }
 
+#[allow(clippy::too_many_arguments)]
 fn command_build(
     target: Option<Target>,
     warnings_as_errors: bool,
     no_print_progress: bool,
+    format: build::DiagnosticFormat,
+    diagnostics: build::DiagnosticOptions,
+    check_determinism: bool,
+    assert_no_diff: Option<Utf8PathBuf>,
+    timings: timings::TimingsOptions,
 ) -> Result<()> {
-    let manifest = if no_print_progress {
-        build::download_dependencies(NullTelemetry)?
-    } else {
-        build::download_dependencies(cli::Reporter::new())?
+    let download_dependencies = || {
+        if no_print_progress {
+            build::download_dependencies(NullTelemetry)
+        } else {
+            build::download_dependencies(cli::Reporter::new())
+        }
     };
-    let _ = build::main(
-        Options {
-            root_target_support: TargetSupport::Enforced,
-            warnings_as_errors,
-            codegen: Codegen::All,
-            compile: Compile::All,
-            mode: Mode::Dev,
-            target,
-            no_print_progress,
-        },
-        manifest,
-    )?;
-    Ok(())
+    let options = Options {
+        root_target_support: TargetSupport::Enforced,
+        warnings_as_errors,
+        codegen: Codegen::All,
+        compile: Compile::All,
+        mode: Mode::Dev,
+        target,
+        no_print_progress,
+        changed_files: None,
+    };
+    if check_determinism {
+        return build::check_determinism(options, download_dependencies()?);
+    }
+    if let Some(committed) = assert_no_diff {
+        return build::assert_no_diff(options, download_dependencies()?, &committed);
+    }
+    build::main_with_format(
+        options,
+        format,
+        diagnostics,
+        &timings,
+        download_dependencies,
+    )
 }
 
 fn print_config() -> Result<()> {
@@ -621,9 +1054,15 @@ fn print_config() -> Result<()> {
     Ok(())
 }
 
-fn clean() -> Result<()> {
-    let paths = find_project_paths()?;
-    fs::delete_directory(&paths.build_directory())
+/// Selects the language diagnostics are printed in from `GLEAM_LANG`.
+/// Unknown or unset languages fall back to English.
+fn initialise_language() {
+    if let Some(language) = std::env::var("GLEAM_LANG")
+        .ok()
+        .and_then(|tag| i18n::Language::from_tag(&tag))
+    {
+        i18n::set_language(language);
+    }
 }
 
 fn initialise_logger() {
@@ -648,7 +1087,7 @@ fn project_paths_at_current_directory_without_toml() -> ProjectPaths {
     ProjectPaths::new(current_dir)
 }
 
-fn download_dependencies() -> Result<()> {
+fn download_dependencies(resolution_log: Option<Utf8PathBuf>) -> Result<()> {
     let paths = find_project_paths()?;
     _ = dependencies::download(
         &paths,
@@ -656,6 +1095,8 @@ fn download_dependencies() -> Result<()> {
         None,
         Vec::new(),
         UseManifest::Yes,
+        VersionPreference::Newest,
+        resolution_log.as_deref(),
     )?;
     Ok(())
 }
@@ -2,11 +2,11 @@ use camino::{Utf8Path, Utf8PathBuf};
 use flate2::{write::GzEncoder, Compression};
 use glistix_core::{
     analyse::TargetSupport,
+    ast::Definition,
     build::{Codegen, Compile, Mode, Options, Package, Target},
     config::{PackageConfig, SpdxLicense},
     docs::DocContext,
     error::{wrap, SmallVersion},
-    hex,
     paths::{self, ProjectPaths},
     requirement::Requirement,
     Error, Result,
@@ -16,7 +16,7 @@ use itertools::Itertools;
 use sha2::Digest;
 use std::{io::Write, path::PathBuf, time::Instant};
 
-use crate::{build, cli, docs, fs, http::HttpClient};
+use crate::{build, cli, docs, fs, registry};
 
 pub fn command(replace: bool, i_am_sure: bool) -> Result<()> {
     let paths = crate::find_project_paths()?;
@@ -31,6 +31,8 @@ pub fn command(replace: bool, i_am_sure: bool) -> Result<()> {
         return Ok(());
     }
 
+    check_supported_targets_compile(&config)?;
+
     let Tarball {
         mut compile_result,
         data: package_tarball,
@@ -40,6 +42,7 @@ pub fn command(replace: bool, i_am_sure: bool) -> Result<()> {
 
     check_for_name_squatting(&compile_result)?;
     check_for_multiple_top_level_modules(&compile_result, i_am_sure)?;
+    check_for_unreleased_since_versions(&compile_result);
 
     // Build HTML documentation
     let docs_tarball = fs::create_tar_archive(docs::build_documentation(
@@ -69,35 +72,18 @@ pub fn command(replace: bool, i_am_sure: bool) -> Result<()> {
     }
 
     let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
-    let hex_config = hexpm::Config::new();
-    let api_key =
-        crate::hex::HexAuthentication::new(&runtime, hex_config.clone()).get_or_create_api_key()?;
+    let registry = registry::for_package(&runtime, &config)?;
     let start = Instant::now();
     cli::print_publishing(&config.name, &config.version);
 
-    runtime.block_on(hex::publish_package(
-        package_tarball,
-        config.version.to_string(),
-        &api_key,
-        &hex_config,
-        replace,
-        &HttpClient::new(),
-    ))?;
+    registry.publish_package(&config, package_tarball, replace)?;
 
     cli::print_publishing_documentation();
-    runtime.block_on(hex::publish_documentation(
-        &config.name,
-        &config.version,
-        docs_tarball,
-        &api_key,
-        &hex_config,
-        &HttpClient::new(),
-    ))?;
+    registry.publish_documentation(&config, docs_tarball)?;
     cli::print_published(start.elapsed());
-    println!(
-        "\nView your package at https://hex.pm/packages/{}",
-        &config.name
-    );
+    if let Some(url) = registry.package_url(&config) {
+        println!("\nView your package at {url}");
+    }
 
     // Prompt the user to make a git tag if they have not.
     let has_repo = config.repository.url().is_some();
@@ -118,6 +104,54 @@ HTML documentation will work:
     Ok(())
 }
 
+/// Compiles the package for each of the targets listed by `supported-targets`
+/// in the `[glistix]` section of gleam.toml, so that a package failing to
+/// compile for one of them, such as Nix, isn't published. Packages without
+/// the field are not checked.
+///
+/// The errors of each failing target are printed before publishing is
+/// aborted.
+fn check_supported_targets_compile(config: &PackageConfig) -> Result<()> {
+    if config.glistix.supported_targets.is_empty() {
+        return Ok(());
+    }
+
+    let manifest = build::download_dependencies(cli::Reporter::new())?;
+    let mut failing_targets = vec![];
+    for target in config.glistix.all_targets() {
+        let result = build::main(
+            Options {
+                root_target_support: TargetSupport::Enforced,
+                warnings_as_errors: false,
+                codegen: Codegen::All,
+                compile: Compile::All,
+                mode: Mode::Prod,
+                target: Some(target),
+                no_print_progress: false,
+                changed_files: None,
+            },
+            manifest.clone(),
+        );
+        if let Err(error) = result {
+            let buffer_writer = cli::stderr_buffer_writer();
+            let mut buffer = buffer_writer.buffer();
+            error.pretty(&mut buffer);
+            buffer_writer
+                .print(&buffer)
+                .expect("Writing error to stderr");
+            failing_targets.push(target);
+        }
+    }
+
+    if failing_targets.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::CannotPublishFailingTargets {
+            targets: failing_targets,
+        })
+    }
+}
+
 fn check_for_name_squatting(package: &Package) -> Result<(), Error> {
     if package.modules.len() > 1 {
         return Ok(());
@@ -192,6 +226,68 @@ For example:
     Ok(())
 }
 
+/// Warn about public definitions with a `@since` attribute naming a version
+/// newer than the one being published, as the documentation would claim they
+/// are not available in this release.
+fn check_for_unreleased_since_versions(package: &Package) {
+    let definitions = unreleased_since_versions(package);
+    if definitions.is_empty() {
+        return;
+    }
+
+    let list = definitions
+        .iter()
+        .map(|(module, name, since)| format!("  - {module}.{name} (since {since})"))
+        .join("\n");
+    println!(
+        "Warning: these definitions have a `@since` attribute with a version newer
+than the version being published, {}:
+
+{list}
+",
+        package.config.version
+    );
+}
+
+/// The module, name and `@since` version of each public definition available
+/// since a version newer than the package's.
+fn unreleased_since_versions(package: &Package) -> Vec<(&str, &str, &str)> {
+    let version = &package.config.version;
+    package
+        .modules
+        .iter()
+        .flat_map(|module| {
+            module.ast.definitions.iter().filter_map(move |definition| {
+                let (publicity, name, since) = match definition {
+                    Definition::Function(function) => (
+                        function.publicity,
+                        function.name.as_ref().map(|(_, name)| name)?,
+                        function.since.as_ref()?,
+                    ),
+                    Definition::TypeAlias(alias) => {
+                        (alias.publicity, &alias.alias, alias.since.as_ref()?)
+                    }
+                    Definition::CustomType(custom_type) => (
+                        custom_type.publicity,
+                        &custom_type.name,
+                        custom_type.since.as_ref()?,
+                    ),
+                    Definition::ModuleConstant(constant) => {
+                        (constant.publicity, &constant.name, constant.since.as_ref()?)
+                    }
+                    Definition::Import(_) => return None,
+                };
+                let unreleased = Version::parse(since).is_ok_and(|since| since > *version);
+                (publicity.is_public() && unreleased).then_some((
+                    module.name.as_str(),
+                    name.as_str(),
+                    since.as_str(),
+                ))
+            })
+        })
+        .collect()
+}
+
 fn check_repo_url(config: &PackageConfig, i_am_sure: bool) -> Result<bool, Error> {
     let Some(url) = config.repository.url() else {
         return Ok(true);
@@ -283,6 +379,7 @@ fn do_build_hex_tarball(paths: &ProjectPaths, config: &mut PackageConfig) -> Res
             codegen: Codegen::All,
             compile: Compile::All,
             no_print_progress: false,
+            changed_files: None,
         },
         build::download_dependencies(cli::Reporter::new())?,
     )?;
@@ -0,0 +1,199 @@
+//! The package registries `glistix publish` can upload packages to.
+
+use glistix_core::{
+    config::{PackageConfig, RegistryConfig},
+    io::HttpClient as _,
+    Error, Result,
+};
+use hexpm::version::Version;
+use http::{Method, Request, Response, StatusCode};
+
+use crate::{hex::HexRegistry, http::HttpClient};
+
+/// A registry to which packages and their documentation can be published.
+pub trait Registry {
+    /// Upload the package's tarball, replacing any release previously
+    /// published with the same version if `replace` is true.
+    fn publish_package(
+        &self,
+        config: &PackageConfig,
+        tarball: Vec<u8>,
+        replace: bool,
+    ) -> Result<()>;
+
+    /// Upload the tarball of the package's HTML documentation.
+    fn publish_documentation(&self, config: &PackageConfig, tarball: Vec<u8>) -> Result<()>;
+
+    /// Where the published package can be viewed, if the registry has a
+    /// page for it.
+    fn package_url(&self, config: &PackageConfig) -> Option<String>;
+}
+
+/// The registry configured in the `[registry]` section of `gleam.toml`, or
+/// Hex if there is none.
+///
+pub fn for_package<'runtime>(
+    runtime: &'runtime tokio::runtime::Runtime,
+    config: &PackageConfig,
+) -> Result<Box<dyn Registry + 'runtime>> {
+    match &config.registry {
+        Some(registry) => Ok(Box::new(GenericRegistry::new(runtime, registry)?)),
+        None => Ok(Box::new(HexRegistry::new(runtime)?)),
+    }
+}
+
+/// A generic package registry, such as the one provided by Forgejo and Gitea,
+/// where each file of a package version is uploaded on its own to
+/// `<url>/<package>/<version>/<file name>`.
+pub struct GenericRegistry<'runtime> {
+    runtime: &'runtime tokio::runtime::Runtime,
+    http: HttpClient,
+    url: String,
+    token: String,
+}
+
+impl<'runtime> GenericRegistry<'runtime> {
+    /// Reads the token to authenticate with from the environment variable
+    /// named in the config.
+    ///
+    pub fn new(
+        runtime: &'runtime tokio::runtime::Runtime,
+        config: &RegistryConfig,
+    ) -> Result<Self> {
+        let token = std::env::var(&config.token_env).map_err(|_| Error::RegistryTokenMissing {
+            name: config.token_env.clone(),
+        })?;
+        Ok(Self {
+            runtime,
+            http: HttpClient::new(),
+            url: config.url.to_string(),
+            token,
+        })
+    }
+
+    fn upload(
+        &self,
+        config: &PackageConfig,
+        file_name: &str,
+        contents: Vec<u8>,
+        replace: bool,
+    ) -> Result<()> {
+        let url = file_url(&self.url, &config.name, &config.version, file_name);
+
+        // Generic registries refuse to overwrite files, so the previous one
+        // has to be deleted first.
+        if replace {
+            let request = file_request(Method::DELETE, &url, &self.token, vec![])?;
+            let response = self.runtime.block_on(self.http.send(request))?;
+            if response.status() != StatusCode::NOT_FOUND {
+                check_response(&response, file_name, &config.version)?;
+            }
+        }
+
+        let request = file_request(Method::PUT, &url, &self.token, contents)?;
+        let response = self.runtime.block_on(self.http.send(request))?;
+        check_response(&response, file_name, &config.version)
+    }
+}
+
+impl Registry for GenericRegistry<'_> {
+    fn publish_package(
+        &self,
+        config: &PackageConfig,
+        tarball: Vec<u8>,
+        replace: bool,
+    ) -> Result<()> {
+        let file_name = format!("{}-{}.tar", config.name, config.version);
+        self.upload(config, &file_name, tarball, replace)
+    }
+
+    fn publish_documentation(&self, config: &PackageConfig, tarball: Vec<u8>) -> Result<()> {
+        // The documentation is always replaced along with the package, as it
+        // can only be published once the package has been.
+        let file_name = format!("{}-{}-docs.tar.gz", config.name, config.version);
+        self.upload(config, &file_name, tarball, true)
+    }
+
+    fn package_url(&self, _config: &PackageConfig) -> Option<String> {
+        None
+    }
+}
+
+fn file_url(registry_url: &str, package: &str, version: &Version, file_name: &str) -> String {
+    let registry_url = registry_url.trim_end_matches('/');
+    format!("{registry_url}/{package}/{version}/{file_name}")
+}
+
+fn file_request(
+    method: Method,
+    url: &str,
+    token: &str,
+    contents: Vec<u8>,
+) -> Result<Request<Vec<u8>>> {
+    Request::builder()
+        .method(method)
+        .uri(url)
+        .header("authorization", format!("token {token}"))
+        .header("content-type", "application/octet-stream")
+        .body(contents)
+        .map_err(Error::http)
+}
+
+fn check_response(response: &Response<Vec<u8>>, file_name: &str, version: &Version) -> Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else if status == StatusCode::CONFLICT {
+        Err(Error::HexPublishReplaceRequired {
+            version: version.to_string(),
+        })
+    } else {
+        Err(Error::RegistryUploadFailed {
+            file: file_name.into(),
+            status: status.as_u16(),
+        })
+    }
+}
+
+#[test]
+fn generic_registry_file_url() {
+    let version = Version::new(1, 2, 3);
+    for registry_url in [
+        "https://git.example.com/api/packages/wobble/generic",
+        "https://git.example.com/api/packages/wobble/generic/",
+    ] {
+        assert_eq!(
+            file_url(registry_url, "wibble", &version, "wibble-1.2.3.tar"),
+            "https://git.example.com/api/packages/wobble/generic/wibble/1.2.3/wibble-1.2.3.tar"
+        );
+    }
+}
+
+#[test]
+fn generic_registry_responses() {
+    let version = Version::new(1, 2, 3);
+    let response = |status: StatusCode| {
+        Response::builder()
+            .status(status)
+            .body(vec![])
+            .expect("valid response")
+    };
+
+    assert_eq!(
+        check_response(&response(StatusCode::CREATED), "wibble.tar", &version),
+        Ok(())
+    );
+    assert_eq!(
+        check_response(&response(StatusCode::CONFLICT), "wibble.tar", &version),
+        Err(Error::HexPublishReplaceRequired {
+            version: "1.2.3".into()
+        })
+    );
+    assert_eq!(
+        check_response(&response(StatusCode::UNAUTHORIZED), "wibble.tar", &version),
+        Err(Error::RegistryUploadFailed {
+            file: "wibble.tar".into(),
+            status: 401
+        })
+    );
+}
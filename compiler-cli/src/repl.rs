@@ -0,0 +1,528 @@
+//! The `glistix repl` command, an interactive session evaluating Gleam on Nix.
+//!
+//! Each entry is type checked against the project by compiling it, together
+//! with the imports and bindings of the earlier entries, into the `main`
+//! function of a scratch module. The function is then called in a `nix repl`
+//! session kept open for the whole REPL, with the values of the earlier
+//! bindings as its arguments, so that they are evaluated only once. Only the
+//! entries that succeeded are kept.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout},
+    rc::Rc,
+    sync::OnceLock,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use glistix_core::{
+    analyse::TargetSupport,
+    ast::{Definition, Statement, TargetedDefinition},
+    build::{Built, Codegen, Compile, Mode, Options, Target},
+    error::{Error, StandardIoAction},
+    manifest::Manifest,
+    parse,
+    paths::ProjectPaths,
+    type_,
+    warning::{VectorWarningEmitterIO, WarningEmitter},
+};
+use itertools::Itertools;
+
+use crate::{fs::ProjectIO, run::ScratchModule};
+
+/// The module each entry is compiled in, followed by the number of the entry.
+const REPL_MODULE: &str = "glistix_repl";
+
+/// The function of the scratch module giving the type of the entry. It is
+/// never called.
+const TYPE_FUNCTION: &str = "glistix_repl_type";
+
+/// Printed by `nix repl` once it has evaluated everything sent before it.
+const DONE_MARKER: &str = "glistix-repl-done";
+
+/// A line of input to the REPL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    /// An `import` statement, without the keyword.
+    Import(String),
+    /// A `let` statement, whose variables are in scope for later entries.
+    Binding(Binding),
+    Expression(String),
+}
+
+impl Entry {
+    fn parse(input: &str) -> Self {
+        let input = input.trim();
+        if let Some(import) = input.strip_prefix("import ") {
+            return Self::Import(import.trim().into());
+        }
+        // A `let` statement which doesn't parse is compiled as an expression,
+        // so that the syntax error is reported.
+        match Binding::parse(input) {
+            Some(binding) => Self::Binding(binding),
+            None => Self::Expression(input.into()),
+        }
+    }
+}
+
+/// A `let` statement, split around the expression of its value so that later
+/// entries can bind its variables to the value kept in `nix repl` instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Binding {
+    /// Everything before the value, e.g. `let assert [x, ..] = `.
+    pattern: String,
+    value: String,
+    /// Everything after the value, e.g. ` as "message"`.
+    rest: String,
+}
+
+impl Binding {
+    fn parse(input: &str) -> Option<Self> {
+        if !input.starts_with("let ") {
+            return None;
+        }
+        let prefix = "fn main() {\n";
+        let source = format!("{prefix}{input}\n}}\n");
+        let parsed =
+            parse::parse_module(Utf8PathBuf::new(), &source, &WarningEmitter::null()).ok()?;
+        let [TargetedDefinition {
+            definition: Definition::Function(function),
+            ..
+        }] = parsed.module.definitions.as_slice()
+        else {
+            return None;
+        };
+        let [Statement::Assignment(assignment)] = function.body.as_slice() else {
+            return None;
+        };
+
+        let value = assignment.value.location();
+        let start = (value.start as usize).checked_sub(prefix.len())?;
+        let end = (value.end as usize).checked_sub(prefix.len())?;
+        Some(Self {
+            pattern: input.get(..start)?.into(),
+            value: input.get(start..end)?.into(),
+            rest: input.get(end..)?.into(),
+        })
+    }
+
+    /// The statement binding the variables of the pattern to another value.
+    fn bind_to(&self, value: &str) -> String {
+        format!("{}{value}{}", self.pattern, self.rest)
+    }
+}
+
+/// The name of the parameter of `main`, and of the variable of `nix repl`,
+/// holding the value of the binding with this index.
+fn binding_variable(index: usize) -> String {
+    format!("glistix_binding_{index}")
+}
+
+/// The entries which have been evaluated successfully so far.
+#[derive(Debug, Default)]
+struct Session {
+    imports: Vec<String>,
+    bindings: Vec<Binding>,
+}
+
+impl Session {
+    /// The source of the module evaluating the entry. Its `main` function
+    /// takes the values of the earlier bindings and returns the value of the
+    /// entry, or `Nil` for an import.
+    fn module_source(&self, entry: &Entry) -> String {
+        let mut source = String::new();
+        let new_import = match entry {
+            Entry::Import(import) => Some(import),
+            Entry::Binding(_) | Entry::Expression(_) => None,
+        };
+        for import in self.imports.iter().chain(new_import) {
+            source.push_str(&format!("import {import}\n"));
+        }
+        if !source.is_empty() {
+            source.push('\n');
+        }
+
+        let variables = (0..self.bindings.len()).map(binding_variable).collect_vec();
+        source.push_str(&format!("pub fn main({}) {{\n", variables.join(", ")));
+        for (binding, variable) in self.bindings.iter().zip(&variables) {
+            source.push_str(&format!("  {}\n", binding.bind_to(variable)));
+        }
+        match entry {
+            Entry::Import(_) => source.push_str("  Nil\n"),
+            Entry::Binding(binding) => {
+                let variable = binding_variable(self.bindings.len());
+                source.push_str(&format!("  let {variable} = {}\n", binding.value));
+                source.push_str(&format!("  {}\n", binding.bind_to(&variable)));
+                source.push_str(&format!("  {variable}\n"));
+            }
+            Entry::Expression(code) => source.push_str(&format!("  {code}\n")),
+        }
+        source.push_str("}\n\n");
+
+        // Calling `main` with the earlier bindings checks that it is given
+        // values of the types they have, and gives the type of the entry.
+        source.push_str(&format!("pub fn {TYPE_FUNCTION}() {{\n"));
+        for (binding, variable) in self.bindings.iter().zip(&variables) {
+            source.push_str(&format!("  let {variable} = {}\n", binding.value));
+            source.push_str(&format!("  {}\n", binding.bind_to(variable)));
+        }
+        source.push_str(&format!("  main({})\n}}\n", variables.join(", ")));
+        source
+    }
+
+    fn keep(&mut self, entry: Entry) {
+        match entry {
+            Entry::Import(import) => self.imports.push(import),
+            Entry::Binding(binding) => self.bindings.push(binding),
+            Entry::Expression(_) => (),
+        }
+    }
+}
+
+/// A `nix repl` process, which keeps the values of the bindings of the
+/// session for as long as it runs.
+struct NixRepl {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl NixRepl {
+    fn start(io: &ProjectIO) -> Result<Self, Error> {
+        let args = ["repl", "--extra-experimental-features", "nix-command"].map(String::from);
+        let mut child = io.spawn_piped("nix", &args, None)?;
+        let stdin = child.stdin.take().expect("nix repl stdin");
+        let stdout = BufReader::new(child.stdout.take().expect("nix repl stdout"));
+        let mut repl = Self {
+            child,
+            stdin,
+            stdout,
+        };
+        // Skip the greeting
+        let _ = repl.send(&[])?;
+        Ok(repl)
+    }
+
+    /// Sends the lines to `nix repl`, returning what it printed on stdout in
+    /// response. Errors are printed on stderr, which is not captured.
+    fn send(&mut self, lines: &[String]) -> Result<String, Error> {
+        let write_error = |error: io::Error| Error::StandardIo {
+            action: StandardIoAction::Write,
+            err: Some(error.kind()),
+        };
+        for line in lines {
+            tracing::trace!(line, "sending_to_nix_repl");
+            writeln!(self.stdin, "{line}").map_err(write_error)?;
+        }
+        writeln!(self.stdin, "\"{DONE_MARKER}\"").map_err(write_error)?;
+        self.stdin.flush().map_err(write_error)?;
+
+        let mut output = String::new();
+        loop {
+            let mut line = String::new();
+            let read = self
+                .stdout
+                .read_line(&mut line)
+                .map_err(|error| Error::StandardIo {
+                    action: StandardIoAction::Read,
+                    err: Some(error.kind()),
+                })?;
+            if read == 0 {
+                return Err(Error::ShellCommand {
+                    program: "nix repl".into(),
+                    err: None,
+                });
+            }
+            let line = repl_output(&line);
+            if line.contains(DONE_MARKER) {
+                return Ok(output.trim().into());
+            }
+            if !line.is_empty() {
+                output.push_str(&line);
+                output.push('\n');
+            }
+        }
+    }
+}
+
+impl Drop for NixRepl {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin, ":quit");
+        let _ = self.stdin.flush();
+        let _ = self.child.wait();
+    }
+}
+
+/// A line printed by `nix repl`, without its prompts or colours.
+fn repl_output(line: &str) -> String {
+    static COLOUR: OnceLock<regex::Regex> = OnceLock::new();
+    let colour = COLOUR.get_or_init(|| regex::Regex::new("\x1b\\[[0-9;]*m").expect("colour regex"));
+    let mut line = colour.replace_all(line, "").trim().to_string();
+    while let Some(rest) = line.strip_prefix("nix-repl>") {
+        line = rest.trim_start().to_string();
+    }
+    line
+}
+
+/// A string literal of the Nix language.
+fn nix_string(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$");
+    format!("\"{escaped}\"")
+}
+
+pub fn command() -> Result<(), Error> {
+    let paths = crate::find_project_paths()?;
+    let manifest = crate::build::download_dependencies(crate::cli::Reporter::new())?;
+
+    // Build the project up front, so that its errors are reported once
+    // rather than for every entry.
+    let _ = build(manifest.clone(), false)?;
+
+    // Ctrl+C interrupts the evaluation in `nix repl`, which is in the same
+    // process group, rather than the REPL, so that no scratch module is left
+    // behind.
+    ctrlc::set_handler(move || {}).expect("Error setting Ctrl-C handler");
+    let mut nix = NixRepl::start(&ProjectIO::new())?;
+
+    println!("Type a Gleam expression, `let` binding or `import` to evaluate it on Nix.");
+    println!("Press Ctrl+D or type `:quit` to exit.");
+
+    let stderr = crate::cli::stderr_buffer_writer();
+    let mut session = Session::default();
+    let mut stdin = io::stdin().lock();
+    for number in 0.. {
+        print!("> ");
+        io::stdout().flush().expect("REPL stdout flush");
+
+        let mut input = String::new();
+        let read = stdin.read_line(&mut input).map_err(|e| Error::StandardIo {
+            action: StandardIoAction::Read,
+            err: Some(e.kind()),
+        })?;
+        let input = input.trim();
+        if read == 0 || input == ":quit" {
+            println!();
+            break;
+        }
+        if input.is_empty() {
+            continue;
+        }
+
+        let entry = Entry::parse(input);
+        match evaluate(&paths, &manifest, &mut nix, &session, &entry, number) {
+            Ok(Some(result)) => {
+                if !matches!(entry, Entry::Import(_)) {
+                    println!("{result}");
+                }
+                session.keep(entry);
+            }
+            // Nix has already printed why the evaluation failed.
+            Ok(None) => (),
+            Err(error) => {
+                let mut buffer = stderr.buffer();
+                error.pretty(&mut buffer);
+                stderr.print(&buffer).expect("REPL error writing");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Compiles and evaluates the entry, returning its value and type, or `None`
+/// if Nix failed to evaluate it. Each entry is compiled to a module of its
+/// own, as `nix repl` would otherwise reuse the file it first imported.
+fn evaluate(
+    paths: &ProjectPaths,
+    manifest: &Manifest,
+    nix: &mut NixRepl,
+    session: &Session,
+    entry: &Entry,
+    number: usize,
+) -> Result<Option<String>, Error> {
+    let name = format!("{REPL_MODULE}_{number}");
+    let module = ScratchModule::write(paths, &name, &session.module_source(entry))?;
+    let built = build(manifest.clone(), true);
+    // The generated Nix code is all that is needed from here on.
+    drop(module);
+    let built = built?;
+
+    let type_ = built
+        .module_interfaces()
+        .get(name.as_str())
+        .and_then(|module| module.values.get(TYPE_FUNCTION))
+        .and_then(|function| function.type_.return_type())
+        .map(|type_| type_::pretty::Printer::new().pretty_print(&type_, 0))
+        .unwrap_or_default();
+
+    let file = paths
+        .build_directory_for_package(Mode::Dev, Target::Nix, &built.root_package.config.name)
+        .join(format!("{name}.nix"));
+    let value = format!("glistix_value_{number}");
+    let output = nix.send(&[
+        format!("{value} = {}", main_call(&file, session.bindings.len())),
+        format!(":p {value}"),
+    ])?;
+    if output.is_empty() {
+        return Ok(None);
+    }
+
+    if let Entry::Binding(_) = entry {
+        let variable = binding_variable(session.bindings.len());
+        let _ = nix.send(&[format!("{variable} = {value}")])?;
+    }
+    Ok(Some(format!("{output} : {type_}")))
+}
+
+/// The Nix expression calling the `main` function of the compiled module with
+/// the values of the bindings kept so far.
+fn main_call(file: &Utf8Path, bindings: usize) -> String {
+    let arguments = if bindings == 0 {
+        "{ }".into()
+    } else {
+        (0..bindings).map(binding_variable).join(" ")
+    };
+    format!(
+        "(builtins.import {}).main {arguments}",
+        nix_string(file.as_str())
+    )
+}
+
+/// Builds the project for Nix. Warnings are only printed for the first build,
+/// as the scratch module would otherwise warn about every unused binding.
+fn build(manifest: Manifest, quiet: bool) -> Result<Built, Error> {
+    let options = Options {
+        warnings_as_errors: false,
+        compile: Compile::All,
+        codegen: Codegen::All,
+        mode: Mode::Dev,
+        target: Some(Target::Nix),
+        root_target_support: TargetSupport::Enforced,
+        no_print_progress: quiet,
+        changed_files: None,
+    };
+    if quiet {
+        let warnings = Rc::new(VectorWarningEmitterIO::new());
+        crate::build::main_with_warnings(options, manifest, warnings)
+    } else {
+        crate::build::main(options, manifest)
+    }
+}
+
+#[cfg(test)]
+fn binding(input: &str) -> Binding {
+    Binding::parse(input).expect("binding")
+}
+
+#[test]
+fn entries_are_parsed() {
+    assert_eq!(
+        Entry::parse("  import gleam/list.{map}\n"),
+        Entry::Import("gleam/list.{map}".into())
+    );
+    assert_eq!(
+        Entry::parse("let x = 1"),
+        Entry::Binding(Binding {
+            pattern: "let x = ".into(),
+            value: "1".into(),
+            rest: "".into(),
+        })
+    );
+    assert_eq!(
+        Entry::parse("let assert [x]: List(Int) = [1] as \"one\""),
+        Entry::Binding(Binding {
+            pattern: "let assert [x]: List(Int) = ".into(),
+            value: "[1]".into(),
+            rest: " as \"one\"".into(),
+        })
+    );
+    assert_eq!(Entry::parse("letter"), Entry::Expression("letter".into()));
+    assert_eq!(Entry::parse("let x ="), Entry::Expression("let x =".into()));
+}
+
+#[test]
+fn module_source_takes_earlier_bindings_as_arguments() {
+    let mut session = Session::default();
+    session.keep(Entry::Import("gleam/list".into()));
+    session.keep(Entry::Binding(binding("let xs = [1, 2, 3]")));
+    session.keep(Entry::Expression("xs".into()));
+
+    assert_eq!(
+        session.module_source(&Entry::Expression("list.reverse(xs)".into())),
+        "import gleam/list
+
+pub fn main(glistix_binding_0) {
+  let xs = glistix_binding_0
+  list.reverse(xs)
+}
+
+pub fn glistix_repl_type() {
+  let glistix_binding_0 = [1, 2, 3]
+  let xs = glistix_binding_0
+  main(glistix_binding_0)
+}
+"
+    );
+    assert_eq!(
+        session.module_source(&Entry::Binding(binding("let assert [x, ..] = xs"))),
+        "import gleam/list
+
+pub fn main(glistix_binding_0) {
+  let xs = glistix_binding_0
+  let glistix_binding_1 = xs
+  let assert [x, ..] = glistix_binding_1
+  glistix_binding_1
+}
+
+pub fn glistix_repl_type() {
+  let glistix_binding_0 = [1, 2, 3]
+  let xs = glistix_binding_0
+  main(glistix_binding_0)
+}
+"
+    );
+}
+
+#[test]
+fn module_source_without_earlier_entries() {
+    assert_eq!(
+        Session::default().module_source(&Entry::Import("gleam/int".into())),
+        "import gleam/int
+
+pub fn main() {
+  Nil
+}
+
+pub fn glistix_repl_type() {
+  main()
+}
+"
+    );
+}
+
+#[test]
+fn main_is_called_with_the_bindings() {
+    let file = Utf8Path::new("/project/build/dev/nix/app/glistix_repl_2.nix");
+    assert_eq!(
+        main_call(file, 0),
+        "(builtins.import \"/project/build/dev/nix/app/glistix_repl_2.nix\").main { }"
+    );
+    assert_eq!(
+        main_call(file, 2),
+        "(builtins.import \"/project/build/dev/nix/app/glistix_repl_2.nix\").main \
+glistix_binding_0 glistix_binding_1"
+    );
+}
+
+#[test]
+fn repl_output_without_prompts_or_colours() {
+    assert_eq!(repl_output("nix-repl> nix-repl> 3\n"), "3");
+    assert_eq!(repl_output("\x1b[35;1m[ 1 2 ]\x1b[0m\n"), "[ 1 2 ]");
+}
+
+#[test]
+fn nix_strings_are_escaped() {
+    assert_eq!(nix_string("a\"b\\c${d}"), "\"a\\\"b\\\\c\\${d}\"");
+}
@@ -1,6 +1,6 @@
 use std::sync::OnceLock;
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use ecow::EcoString;
 use glistix_core::{
     analyse::TargetSupport,
@@ -20,7 +20,55 @@ pub enum Which {
     Test,
 }
 
+/// The module an expression given with `--eval` is wrapped in.
+const EVAL_MODULE: &str = "glistix_eval";
+
+/// An expression to evaluate in place of a module's `main` function.
+#[derive(Debug, Clone)]
+pub struct Eval {
+    pub expression: String,
+    /// Modules to import, in the syntax of an `import` statement without
+    /// the keyword, e.g. `gleam/list.{map}`.
+    pub imports: Vec<String>,
+}
+
+/// A module generated to be compiled along with the project, such as the one
+/// wrapping an `--eval` expression. It is deleted once it is dropped.
+pub(crate) struct ScratchModule {
+    path: Utf8PathBuf,
+}
+
+/// The first line of every scratch module, which tells one left behind by an
+/// interrupted run apart from a module of the project.
+const SCRATCH_MODULE_HEADER: &str =
+    "//// Written by glistix to be compiled with the project, and deleted afterwards.\n\n";
+
+impl ScratchModule {
+    /// Writes the module to the `test` directory, so that it can import dev
+    /// dependencies too. A scratch module left behind by an earlier run is
+    /// overwritten, but a module of the project never is.
+    pub(crate) fn write(paths: &ProjectPaths, name: &str, source: &str) -> Result<Self, Error> {
+        let path = paths.test_directory().join(format!("{name}.gleam"));
+        if path.exists() && !crate::fs::read(&path)?.starts_with(SCRATCH_MODULE_HEADER) {
+            return Err(Error::OutputFilesAlreadyExist {
+                file_names: vec![path],
+            });
+        }
+        crate::fs::write(&path, &format!("{SCRATCH_MODULE_HEADER}{source}"))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for ScratchModule {
+    fn drop(&mut self) {
+        if let Err(error) = crate::fs::delete_file(&self.path) {
+            tracing::warn!(path = %self.path, ?error, "failed_to_delete_scratch_module");
+        }
+    }
+}
+
 // TODO: test
+#[allow(clippy::too_many_arguments)]
 pub fn command(
     arguments: Vec<String>,
     target: Option<Target>,
@@ -28,9 +76,22 @@ pub fn command(
     module: Option<String>,
     which: Which,
     no_print_progress: bool,
+    env_file: Option<Utf8PathBuf>,
+    eval: Option<Eval>,
 ) -> Result<(), Error> {
     let paths = crate::find_project_paths()?;
 
+    // Load the variables to set for the program before building, so that a
+    // broken env file is reported straight away
+    let env_vars = match &env_file {
+        Some(path) => parse_env_file(path, &crate::fs::read(path)?)?,
+        None => vec![],
+    };
+    let env: Vec<(&str, String)> = env_vars
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.clone()))
+        .collect();
+
     // Validate the module path
     if let Some(mod_path) = &module {
         if !is_gleam_module(mod_path) {
@@ -66,13 +127,25 @@ pub fn command(
     // The root config is required to run the project.
     let root_config = crate::config::root_config()?;
 
+    let target = target.unwrap_or(mod_config.target);
+
     // Determine which module to run
-    let module = module.unwrap_or(match which {
-        Which::Src => root_config.name.to_string(),
-        Which::Test => format!("{}_test", &root_config.name),
-    });
+    let module = match (&eval, module) {
+        (Some(_), _) => EVAL_MODULE.to_string(),
+        (None, Some(module)) => module,
+        (None, None) => match which {
+            Which::Src => root_config.name.to_string(),
+            Which::Test => format!("{}_test", &root_config.name),
+        },
+    };
 
-    let target = target.unwrap_or(mod_config.target);
+    let eval_module = match &eval {
+        Some(eval) => {
+            let source = eval_module_source(eval, target);
+            Some(ScratchModule::write(&paths, EVAL_MODULE, &source)?)
+        }
+        None => None,
+    };
 
     let options = Options {
         warnings_as_errors: false,
@@ -95,13 +168,27 @@ pub fn command(
             PackageKind::Dependency => TargetSupport::NotEnforced,
         },
         no_print_progress,
+        changed_files: None,
     };
 
     let built = crate::build::main(options, manifest)?;
 
+    // The compiled code is all that is needed to run the expression, and the
+    // process exits without running destructors, so clean up straight away.
+    drop(eval_module);
+
     // A module can not be run if it does not exist or does not have a public main function.
     let main_function = get_or_suggest_main_function(built, &module, target)?;
 
+    // Nix prints the value returned by `main`, which has to be fully
+    // evaluated for nested values to be shown.
+    let arguments = match (&eval, target) {
+        (Some(_), Target::Nix) => std::iter::once("--strict".into())
+            .chain(arguments)
+            .collect(),
+        _ => arguments,
+    };
+
     // Don't exit on ctrl+c as it is used by child erlang shell
     ctrlc::set_handler(move || {}).expect("Error setting Ctrl-C handler");
 
@@ -114,7 +201,7 @@ pub fn command(
                 target: Target::Erlang,
                 invalid_runtime: r,
             }),
-            _ => run_erlang(&paths, &root_config.name, &module, arguments),
+            _ => run_erlang(&paths, &root_config.name, &module, arguments, &env),
         },
         Target::JavaScript => match runtime.unwrap_or(mod_config.javascript.runtime) {
             Runtime::Deno => run_javascript_deno(
@@ -123,13 +210,16 @@ pub fn command(
                 &main_function.package,
                 &module,
                 arguments,
+                &env,
             ),
             Runtime::NodeJs => {
-                run_javascript_node(&paths, &main_function.package, &module, arguments)
+                run_javascript_node(&paths, &main_function.package, &module, arguments, &env)
+            }
+            Runtime::Bun => {
+                run_javascript_bun(&paths, &main_function.package, &module, arguments, &env)
             }
-            Runtime::Bun => run_javascript_bun(&paths, &main_function.package, &module, arguments),
         },
-        Target::Nix => instantiate_nix(&paths, &main_function.package, &module, arguments),
+        Target::Nix => instantiate_nix(&paths, &main_function.package, &module, arguments, &env),
     }?;
 
     std::process::exit(status);
@@ -140,6 +230,7 @@ fn run_erlang(
     package: &str,
     module: &str,
     arguments: Vec<String>,
+    env: &[(&str, String)],
 ) -> Result<i32, Error> {
     let mut args = vec![];
 
@@ -166,7 +257,7 @@ fn run_erlang(
         args.push(argument);
     }
 
-    ProjectIO::new().exec("erl", &args, &[], None, Stdio::Inherit)
+    ProjectIO::new().exec("erl", &args, env, None, Stdio::Inherit)
 }
 
 fn run_javascript_bun(
@@ -174,6 +265,7 @@ fn run_javascript_bun(
     package: &str,
     module: &str,
     arguments: Vec<String>,
+    env: &[(&str, String)],
 ) -> Result<i32, Error> {
     let mut args = vec!["run".to_string()];
     let entry = write_javascript_entrypoint(paths, package, module)?;
@@ -184,7 +276,7 @@ fn run_javascript_bun(
         args.push(arg);
     }
 
-    ProjectIO::new().exec("bun", &args, &[], None, Stdio::Inherit)
+    ProjectIO::new().exec("bun", &args, env, None, Stdio::Inherit)
 }
 
 fn run_javascript_node(
@@ -192,6 +284,7 @@ fn run_javascript_node(
     package: &str,
     module: &str,
     arguments: Vec<String>,
+    env: &[(&str, String)],
 ) -> Result<i32, Error> {
     let mut args = vec![];
     let entry = write_javascript_entrypoint(paths, package, module)?;
@@ -202,7 +295,7 @@ fn run_javascript_node(
         args.push(argument);
     }
 
-    ProjectIO::new().exec("node", &args, &[], None, Stdio::Inherit)
+    ProjectIO::new().exec("node", &args, env, None, Stdio::Inherit)
 }
 
 fn write_javascript_entrypoint(
@@ -229,11 +322,26 @@ fn run_javascript_deno(
     package: &str,
     module: &str,
     arguments: Vec<String>,
+    env: &[(&str, String)],
 ) -> Result<i32, Error> {
-    let mut args = vec![];
-
     // Run the main function.
-    args.push("run".into());
+    let mut args = vec!["run".to_string()];
+    args.extend(deno_arguments(config));
+
+    let entrypoint = write_javascript_entrypoint(paths, package, module)?;
+    args.push(entrypoint.to_string());
+
+    for argument in arguments.into_iter() {
+        args.push(argument);
+    }
+
+    ProjectIO::new().exec("deno", &args, env, None, Stdio::Inherit)
+}
+
+/// The flags given to `deno run` for the settings of the `[javascript.deno]`
+/// section of gleam.toml.
+pub(crate) fn deno_arguments(config: &PackageConfig) -> Vec<String> {
+    let mut args = vec![];
 
     // Enable unstable features and APIs
     if config.javascript.deno.unstable {
@@ -289,14 +397,7 @@ fn run_javascript_deno(
         );
     }
 
-    let entrypoint = write_javascript_entrypoint(paths, package, module)?;
-    args.push(entrypoint.to_string());
-
-    for argument in arguments.into_iter() {
-        args.push(argument);
-    }
-
-    ProjectIO::new().exec("deno", &args, &[], None, Stdio::Inherit)
+    args
 }
 
 fn add_deno_flag(args: &mut Vec<String>, flag: &str, flags: &DenoFlag) {
@@ -317,6 +418,7 @@ fn instantiate_nix(
     package: &str,
     module: &str,
     arguments: Vec<String>,
+    env: &[(&str, String)],
 ) -> Result<i32, Error> {
     let mut args = vec![
         "--eval".to_string(),
@@ -340,7 +442,38 @@ fn instantiate_nix(
         args.push(arg);
     }
 
-    ProjectIO::new().exec("nix-instantiate", &args, &[], None, Stdio::Inherit)
+    ProjectIO::new().exec("nix-instantiate", &args, env, None, Stdio::Inherit)
+}
+
+/// The source of the module wrapping an `--eval` expression in a `main`
+/// function. On Nix the value is returned for `nix-instantiate` to print,
+/// while elsewhere it is printed with `io.debug` from `gleam_stdlib`.
+fn eval_module_source(eval: &Eval, target: Target) -> String {
+    let mut imports = eval.imports.clone();
+    let imports_io = imports.iter().any(|import| {
+        let module = import.split(['.', ' ']).next().unwrap_or_default();
+        module == "gleam/io"
+    });
+    if target != Target::Nix && !imports_io {
+        imports.insert(0, "gleam/io".into());
+    }
+
+    let mut source = String::new();
+    for import in imports {
+        source.push_str(&format!("import {import}\n"));
+    }
+    let expression = eval.expression.trim();
+    let body = match target {
+        Target::Nix => format!("  {{\n    {expression}\n  }}"),
+        Target::Erlang | Target::JavaScript => {
+            format!("  io.debug({{\n    {expression}\n  }})")
+        }
+    };
+    if !source.is_empty() {
+        source.push('\n');
+    }
+    source.push_str(&format!("pub fn main() {{\n{body}\n}}\n"));
+    source
 }
 
 /// Check if a module name is a valid gleam module name.
@@ -359,6 +492,61 @@ fn is_gleam_module(module: &str) -> bool {
     .is_match(module)
 }
 
+/// Parse the contents of a dotenv-style file into the variables it assigns.
+///
+/// Each line is either empty, a `#` comment, or a `NAME=value` assignment,
+/// optionally prefixed with `export`. Values may be wrapped in single or
+/// double quotes, and unquoted values end at the first ` #` comment.
+pub(crate) fn parse_env_file(
+    path: &Utf8Path,
+    contents: &str,
+) -> Result<Vec<(String, String)>, Error> {
+    let mut variables = vec![];
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid_line = || Error::InvalidEnvFile {
+            path: path.to_path_buf(),
+            line: index + 1,
+        };
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (name, value) = line.split_once('=').ok_or_else(invalid_line)?;
+        let name = name.trim();
+        if !is_env_variable_name(name) {
+            return Err(invalid_line());
+        }
+
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value
+                .strip_prefix(quote)
+                .and_then(|value| value.strip_suffix(quote))
+                .ok_or_else(invalid_line)?,
+            _ => match value.split_once(" #") {
+                Some((value, _comment)) => value.trim_end(),
+                None => value,
+            },
+        };
+
+        variables.push((name.to_string(), value.to_string()));
+    }
+
+    Ok(variables)
+}
+
+fn is_env_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
 /// If provided module is not executable, suggest a possible valid module.
 fn get_or_suggest_main_function(
     built: Built,
@@ -409,3 +597,135 @@ fn valid_module_names() {
         assert!(is_gleam_module(mod_name));
     }
 }
+
+#[test]
+fn env_file_variables() {
+    let contents = r#"
+# Database settings
+DATABASE_URL=postgres://localhost/dev
+export PORT = 8080
+GREETING="Hello, world! # not a comment"
+QUOTED='single'
+EMPTY=
+DEBUG=true # enables logging
+"#;
+    assert_eq!(
+        parse_env_file(Utf8Path::new(".env"), contents).expect("valid env file"),
+        vec![
+            ("DATABASE_URL".into(), "postgres://localhost/dev".into()),
+            ("PORT".into(), "8080".into()),
+            ("GREETING".into(), "Hello, world! # not a comment".into()),
+            ("QUOTED".into(), "single".into()),
+            ("EMPTY".into(), "".into()),
+            ("DEBUG".into(), "true".into()),
+        ]
+    );
+}
+
+#[test]
+fn invalid_env_file_lines() {
+    for (contents, line) in [
+        ("NO_EQUALS_SIGN", 1),
+        ("OK=1\n1NAME=value", 2),
+        ("OK=1\n\nSPACED NAME=value", 3),
+        ("UNCLOSED=\"value", 1),
+    ] {
+        assert_eq!(
+            parse_env_file(Utf8Path::new(".env"), contents),
+            Err(Error::InvalidEnvFile {
+                path: Utf8PathBuf::from(".env"),
+                line,
+            })
+        );
+    }
+}
+
+#[test]
+fn eval_module_prints_the_expression() {
+    let eval = Eval {
+        expression: "list.reverse([1, 2, 3])".into(),
+        imports: vec!["gleam/list".into()],
+    };
+    assert_eq!(
+        eval_module_source(&eval, Target::Erlang),
+        "import gleam/io
+import gleam/list
+
+pub fn main() {
+  io.debug({
+    list.reverse([1, 2, 3])
+  })
+}
+"
+    );
+}
+
+#[test]
+fn eval_module_does_not_import_io_twice() {
+    let eval = Eval {
+        expression: "1 + 1".into(),
+        imports: vec!["gleam/io.{debug}".into()],
+    };
+    assert_eq!(
+        eval_module_source(&eval, Target::JavaScript),
+        "import gleam/io.{debug}
+
+pub fn main() {
+  io.debug({
+    1 + 1
+  })
+}
+"
+    );
+}
+
+#[test]
+fn eval_module_returns_the_expression_on_nix() {
+    let eval = Eval {
+        expression: "[1, 2]".into(),
+        imports: vec![],
+    };
+    assert_eq!(
+        eval_module_source(&eval, Target::Nix),
+        "pub fn main() {
+  {
+    [1, 2]
+  }
+}
+"
+    );
+}
+
+#[test]
+fn scratch_module_left_behind_is_overwritten() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create a temp directory");
+    let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+    let paths = ProjectPaths::new(root.clone());
+    let path = root.join("test/glistix_eval.gleam");
+
+    // As if an earlier run had been interrupted before deleting its module
+    std::mem::forget(ScratchModule::write(&paths, EVAL_MODULE, "pub fn main() { 1 }\n").unwrap());
+
+    let module = ScratchModule::write(&paths, EVAL_MODULE, "pub fn main() { 2 }\n").unwrap();
+    assert_eq!(
+        crate::fs::read(&path).unwrap(),
+        format!("{SCRATCH_MODULE_HEADER}pub fn main() {{ 2 }}\n")
+    );
+    drop(module);
+    assert!(!path.exists());
+}
+
+#[test]
+fn scratch_module_never_overwrites_project_module() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create a temp directory");
+    let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+    let paths = ProjectPaths::new(root.clone());
+    let path = root.join("test/glistix_eval.gleam");
+    crate::fs::write(&path, "pub fn main() { Nil }\n").unwrap();
+
+    assert!(matches!(
+        ScratchModule::write(&paths, EVAL_MODULE, "pub fn main() { 1 }\n"),
+        Err(Error::OutputFilesAlreadyExist { .. })
+    ));
+    assert_eq!(crate::fs::read(&path).unwrap(), "pub fn main() { Nil }\n");
+}
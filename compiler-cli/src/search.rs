@@ -0,0 +1,44 @@
+use camino::Utf8PathBuf;
+use glistix_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Compile, Mode, Options},
+    type_,
+    type_search::{self, TypeQuery},
+    Error, Result,
+};
+
+/// Prints the functions of the project and its dependencies whose type
+/// matches the given signature.
+pub fn command(signature: String) -> Result<()> {
+    let query = TypeQuery::parse(&signature).map_err(|error| Error::Parse {
+        path: Utf8PathBuf::from("signature"),
+        src: signature.as_str().into(),
+        error,
+    })?;
+
+    let built = crate::build::main(
+        Options {
+            mode: Mode::Dev,
+            target: None,
+            codegen: Codegen::None,
+            compile: Compile::All,
+            warnings_as_errors: false,
+            root_target_support: TargetSupport::Enforced,
+            no_print_progress: true,
+            changed_files: None,
+        },
+        crate::build::download_dependencies(crate::cli::Reporter::new())?,
+    )?;
+
+    let results = type_search::search(&query, built.module_interfaces().values());
+    if results.is_empty() {
+        println!("No functions found matching {signature}");
+        return Ok(());
+    }
+
+    for result in results {
+        let type_ = type_::pretty::Printer::new().pretty_print(&result.type_, 0);
+        println!("{}.{}: {type_}", result.module, result.name);
+    }
+    Ok(())
+}
@@ -0,0 +1,616 @@
+//! Running a selection of the tests of the project, for the `--filter` and
+//! `--shard` options of `glistix test`. Rather than the `main` function of the
+//! test module, which leaves finding the tests to the test framework, a
+//! runner generated for the target calls each of the selected test functions,
+//! so they are selected the same way on every target.
+
+use std::{
+    fmt,
+    io::{BufRead, BufReader},
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use glistix_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Compile, Mode, Options, Origin, Runtime, Target, Telemetry},
+    config::PackageConfig,
+    error::Error,
+    io::{CommandExecutor, Stdio as ExecStdio},
+    paths::ProjectPaths,
+};
+use itertools::Itertools;
+
+use crate::fs::{command_error, ProjectIO};
+
+/// The prefix of the lines in which the generated runners report the result
+/// of a test, so they can't be confused with anything printed by the test
+/// itself.
+const RESULT_PREFIX: &str = "glistix-test:";
+
+/// One of the `count` parts the tests are split into with `--shard k/n`,
+/// numbered from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    index: usize,
+    count: usize,
+}
+
+impl Shard {
+    /// The tests are dealt to the shards in turn, so that the tests of a
+    /// module are spread over all of them.
+    fn contains(&self, position: usize) -> bool {
+        position % self.count == self.index - 1
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected a shard such as `1/4`, got `{string}`");
+        let (index, count) = string.split_once('/').ok_or_else(invalid)?;
+        let index: usize = index.trim().parse().map_err(|_| invalid())?;
+        let count: usize = count.trim().parse().map_err(|_| invalid())?;
+        if count == 0 {
+            return Err("the number of shards must be at least 1".into());
+        }
+        if index == 0 || index > count {
+            return Err(format!("the shard must be between 1 and {count}"));
+        }
+        Ok(Self { index, count })
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// Which of the tests of the project to run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selection {
+    /// Only the tests whose `module.function` name contains this.
+    pub filter: Option<String>,
+    pub shard: Option<Shard>,
+}
+
+impl Selection {
+    pub fn is_everything(&self) -> bool {
+        self.filter.is_none() && self.shard.is_none()
+    }
+
+    /// The arguments to pass on to `gleam test` for the same selection.
+    pub fn arguments(&self) -> Vec<String> {
+        let mut arguments = vec![];
+        if let Some(filter) = &self.filter {
+            arguments.extend(["--filter".into(), filter.clone()]);
+        }
+        if let Some(shard) = &self.shard {
+            arguments.extend(["--shard".into(), shard.to_string()]);
+        }
+        arguments
+    }
+
+    /// The selected tests, ordered by name. The shards are taken from the
+    /// tests matching the filter, so that together they run each of them
+    /// exactly once.
+    fn select(&self, tests: Vec<TestFunction>) -> Vec<TestFunction> {
+        tests
+            .into_iter()
+            .filter(|test| match &self.filter {
+                Some(filter) => test.name().contains(filter.as_str()),
+                None => true,
+            })
+            .sorted_by(|one, other| one.name().cmp(&other.name()))
+            .enumerate()
+            .filter(|(position, _)| match &self.shard {
+                Some(shard) => shard.contains(*position),
+                None => true,
+            })
+            .map(|(_, test)| test)
+            .collect_vec()
+    }
+}
+
+/// A public function without arguments of a test module whose name ends
+/// with `_test`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TestFunction {
+    module: EcoString,
+    function: EcoString,
+}
+
+impl TestFunction {
+    fn name(&self) -> String {
+        format!("{}.{}", self.module, self.function)
+    }
+}
+
+pub fn command(
+    target: Option<Target>,
+    runtime: Option<Runtime>,
+    selection: Selection,
+    env_file: Option<Utf8PathBuf>,
+) -> Result<(), Error> {
+    let paths = crate::find_project_paths()?;
+
+    // Load the variables to set for the tests before building, so that a
+    // broken env file is reported straight away
+    let env = match &env_file {
+        Some(path) => crate::run::parse_env_file(path, &crate::fs::read(path)?)?,
+        None => vec![],
+    };
+
+    let manifest = crate::build::download_dependencies(crate::cli::Reporter::new())?;
+    let config = crate::config::root_config()?;
+    let target = target.unwrap_or(config.target);
+    if let (Target::Erlang, Some(runtime)) = (target, runtime) {
+        return Err(Error::InvalidRuntime {
+            target: Target::Erlang,
+            invalid_runtime: runtime,
+        });
+    }
+
+    let options = Options {
+        warnings_as_errors: false,
+        compile: Compile::All,
+        codegen: Codegen::All,
+        mode: Mode::Dev,
+        target: Some(target),
+        root_target_support: TargetSupport::Enforced,
+        no_print_progress: false,
+        changed_files: None,
+    };
+    let built = crate::build::main(options, manifest)?;
+
+    let tests = built
+        .module_interfaces()
+        .values()
+        .filter(|module| {
+            module.package == config.name
+                && module.origin == Origin::Test
+                && module.name.ends_with("_test")
+        })
+        .flat_map(|module| {
+            module
+                .test_functions(target)
+                .into_iter()
+                .map(|function| TestFunction {
+                    module: module.name.clone(),
+                    function,
+                })
+        })
+        .collect_vec();
+    let tests = selection.select(tests);
+
+    if tests.is_empty() {
+        println!("No tests selected");
+        return Ok(());
+    }
+
+    // Don't exit on ctrl+c as it is used by child erlang shell
+    ctrlc::set_handler(move || {}).expect("Error setting Ctrl-C handler");
+
+    crate::cli::Reporter.running(&format!("{} tests", tests.len()));
+    let env = env
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.clone()))
+        .collect_vec();
+    let passed = match target {
+        Target::Erlang => run_erlang(&paths, &config, &tests, &env)?,
+        Target::JavaScript => {
+            let runtime = runtime.unwrap_or(config.javascript.runtime);
+            run_javascript(&paths, &config, runtime, &tests, &env)?
+        }
+        Target::Nix => run_nix(&paths, &config, &tests, &env)?,
+    };
+
+    let failures = passed.iter().filter(|passed| !**passed).count();
+    println!("\n{}", summary(tests.len() - failures, failures));
+    if failures > 0 {
+        // The failures have been reported by the summary.
+        return Err(Error::AlreadyReported);
+    }
+    Ok(())
+}
+
+fn run_erlang(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    tests: &[TestFunction],
+    env: &[(&str, String)],
+) -> Result<Vec<bool>, Error> {
+    let mut args = vec![];
+    let packages = paths.build_directory_for_target(Mode::Dev, Target::Erlang);
+    for entry in crate::fs::read_dir(packages)?.filter_map(Result::ok) {
+        args.push("-pa".into());
+        args.push(entry.path().join("ebin").into());
+    }
+    args.push("-noshell".into());
+    args.push("-eval".into());
+    args.push(erlang_runner(&config.name, tests));
+
+    run_streamed("erl", &args, env, tests)
+}
+
+/// The expression evaluated by the Erlang VM to run the tests, which reports
+/// each error to stderr.
+fn erlang_runner(package: &str, tests: &[TestFunction]) -> String {
+    let tests = tests
+        .iter()
+        .enumerate()
+        .map(|(index, test)| {
+            // gleam modules are separated by `/`. Erlang modules are separated by `@`.
+            let module = test.module.replace("/", "@");
+            format!("{{{index}, '{module}', '{}'}}", test.function)
+        })
+        .join(", ");
+    format!(
+        "{{ok, _}} = application:ensure_all_started('{package}'), \
+lists:foreach(fun({{Index, Module, Function}}) -> \
+Result = try Module:Function() of _ -> ok \
+catch Class:Reason:Stacktrace -> \
+io:format(standard_error, \"~p: ~p~n~p~n\", [Class, Reason, Stacktrace]), failed end, \
+io:format(\"{RESULT_PREFIX}~b:~s~n\", [Index, Result]) end, [{tests}]), \
+init:stop()."
+    )
+}
+
+fn run_javascript(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    runtime: Runtime,
+    tests: &[TestFunction],
+    env: &[(&str, String)],
+) -> Result<Vec<bool>, Error> {
+    let path = paths
+        .build_directory_for_package(Mode::Dev, Target::JavaScript, &config.name)
+        .join("gleam.test.mjs");
+    crate::fs::write(&path, &javascript_runner(tests))?;
+
+    let (program, mut args) = match runtime {
+        Runtime::NodeJs => ("node", vec![]),
+        Runtime::Deno => {
+            let mut args = vec!["run".to_string()];
+            args.extend(crate::run::deno_arguments(config));
+            ("deno", args)
+        }
+        Runtime::Bun => ("bun", vec!["run".to_string()]),
+    };
+    args.push(path.to_string());
+    run_streamed(program, &args, env, tests)
+}
+
+/// The module run to call the tests, which reports each error to stderr.
+/// Tests returning a promise are awaited.
+fn javascript_runner(tests: &[TestFunction]) -> String {
+    let modules = tests.iter().map(|test| &test.module).unique().collect_vec();
+    let mut source = String::new();
+    for (index, module) in modules.iter().enumerate() {
+        source.push_str(&format!(
+            "import * as $module{index} from \"./{module}.mjs\";\n"
+        ));
+    }
+    source.push_str("\nconst tests = [\n");
+    for test in tests {
+        let module = modules
+            .iter()
+            .position(|module| *module == &test.module)
+            .expect("Module of the test");
+        source.push_str(&format!("  $module{module}.{},\n", test.function));
+    }
+    source.push_str(&format!(
+        r#"];
+
+for (const [index, test] of tests.entries()) {{
+  let result = "ok";
+  try {{
+    await test();
+  }} catch (error) {{
+    console.error(error);
+    result = "failed";
+  }}
+  console.log(`{RESULT_PREFIX}${{index}}:${{result}}`);
+}}
+"#
+    ));
+    source
+}
+
+/// All the tests are run by a single evaluation, in which `tryEval` only
+/// tells whether each of them failed. The failed tests are then evaluated
+/// again on their own for Nix to print their errors, and the result of that
+/// evaluation is the one kept, as `tryEval` can't catch every error.
+fn run_nix(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    tests: &[TestFunction],
+    env: &[(&str, String)],
+) -> Result<Vec<bool>, Error> {
+    let directory = paths.build_directory_for_package(Mode::Dev, Target::Nix, &config.name);
+    let path = directory.join("gleam.test.nix");
+    crate::fs::write(&path, &nix_runner(tests))?;
+
+    let args = vec![
+        "--eval".to_string(),
+        "--strict".to_string(),
+        "--json".to_string(),
+        path.to_string(),
+    ];
+    let output = Command::new("nix-instantiate")
+        .args(&args)
+        .envs(env.iter().map(|(name, value)| (name, value)))
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|error| command_error("nix-instantiate", error))?;
+    let passed: Vec<bool> = if output.status.success() {
+        serde_json::from_slice(&output.stdout).unwrap_or_default()
+    } else {
+        vec![]
+    };
+    // Tests without a result, if the evaluation itself failed, are failures.
+    let mut passed = (0..tests.len())
+        .map(|index| passed.get(index).copied().unwrap_or(false))
+        .collect_vec();
+
+    let io = ProjectIO::new();
+    for (test, passed) in tests.iter().zip(&mut passed) {
+        if !*passed {
+            *passed = rerun_nix_test(&io, &directory, test, env)? == 0;
+        }
+        print_result(test, *passed);
+    }
+    Ok(passed)
+}
+
+/// Evaluates a single test on its own, returning the exit status of Nix.
+fn rerun_nix_test(
+    io: &ProjectIO,
+    directory: &Utf8Path,
+    test: &TestFunction,
+    env: &[(&str, String)],
+) -> Result<i32, Error> {
+    let file = directory.join(format!("{}.nix", test.module));
+    let args = vec![
+        "--eval".to_string(),
+        "--strict".to_string(),
+        "--attr".to_string(),
+        test.function.to_string(),
+        // As with `gleam run`, an arbitrary argument makes Nix call the
+        // function once it has been selected.
+        "--arg".to_string(),
+        "null".to_string(),
+        "null".to_string(),
+        file.to_string(),
+    ];
+    io.exec("nix-instantiate", &args, env, None, ExecStdio::Null)
+}
+
+/// The expression evaluating to whether each test passed. Only errors thrown
+/// with `throw`, such as Gleam panics, are caught.
+fn nix_runner(tests: &[TestFunction]) -> String {
+    let tests = tests
+        .iter()
+        .map(|test| format!("  (import ./{}.nix).{}\n", test.module, test.function))
+        .join("");
+    format!(
+        "let
+  run = test: (builtins.tryEval (builtins.deepSeq (test {{ }}) true)).success;
+in
+builtins.map run [
+{tests}]
+"
+    )
+}
+
+/// Runs a generated runner, passing on what the tests print and reading the
+/// results it reports for them. A test without a result, because the runner
+/// crashed, has failed.
+fn run_streamed(
+    program: &str,
+    args: &[String],
+    env: &[(&str, String)],
+    tests: &[TestFunction],
+) -> Result<Vec<bool>, Error> {
+    tracing::trace!(program=program, args=?args.join(" "), "running_tests");
+    let mut child = Command::new(program)
+        .args(args)
+        .envs(env.iter().map(|(name, value)| (name, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|error| command_error(program, error))?;
+
+    let mut passed = vec![false; tests.len()];
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            let result = parse_result(&line)
+                .and_then(|(index, result)| Some((index, tests.get(index)?, result)));
+            match result {
+                Some((index, test, result)) => {
+                    if let Some(passed) = passed.get_mut(index) {
+                        *passed = result;
+                    }
+                    print_result(test, result);
+                }
+                None => println!("{line}"),
+            }
+        }
+    }
+    let _ = child
+        .wait()
+        .map_err(|error| command_error(program, error))?;
+    Ok(passed)
+}
+
+/// The index of the test and whether it passed, from a line reporting its
+/// result.
+fn parse_result(line: &str) -> Option<(usize, bool)> {
+    let (index, result) = line.strip_prefix(RESULT_PREFIX)?.split_once(':')?;
+    let passed = match result {
+        "ok" => true,
+        "failed" => false,
+        _ => return None,
+    };
+    Some((index.parse().ok()?, passed))
+}
+
+fn print_result(test: &TestFunction, passed: bool) {
+    let result = if passed { "ok" } else { "FAILED" };
+    println!("{} ... {result}", test.name());
+}
+
+fn summary(passed: usize, failed: usize) -> String {
+    let noun = |count: usize| if count == 1 { "test" } else { "tests" };
+    match failed {
+        0 => format!("{passed} {} passed", noun(passed)),
+        _ => format!("{failed} {} failed, {passed} passed", noun(failed)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test(module: &str, function: &str) -> TestFunction {
+        TestFunction {
+            module: module.into(),
+            function: function.into(),
+        }
+    }
+
+    fn tests() -> Vec<TestFunction> {
+        vec![
+            test("wobble_test", "one_test"),
+            test("wibble_test", "two_test"),
+            test("wibble_test", "one_test"),
+            test("wibble/wubble_test", "one_test"),
+            test("wobble_test", "two_test"),
+        ]
+    }
+
+    fn selected_names(selection: Selection) -> Vec<String> {
+        selection
+            .select(tests())
+            .iter()
+            .map(TestFunction::name)
+            .collect()
+    }
+
+    #[test]
+    fn parse_shard() {
+        assert_eq!("2/4".parse(), Ok(Shard { index: 2, count: 4 }));
+        assert_eq!("1/1".parse(), Ok(Shard { index: 1, count: 1 }));
+        assert!("0/4".parse::<Shard>().is_err());
+        assert!("5/4".parse::<Shard>().is_err());
+        assert!("1/0".parse::<Shard>().is_err());
+        assert!("2".parse::<Shard>().is_err());
+        assert!("a/b".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn select_everything() {
+        assert_eq!(
+            selected_names(Selection::default()),
+            vec![
+                "wibble/wubble_test.one_test",
+                "wibble_test.one_test",
+                "wibble_test.two_test",
+                "wobble_test.one_test",
+                "wobble_test.two_test",
+            ]
+        );
+    }
+
+    #[test]
+    fn select_with_filter() {
+        let selection = Selection {
+            filter: Some("wibble_test.".into()),
+            shard: None,
+        };
+        assert_eq!(
+            selected_names(selection),
+            vec!["wibble_test.one_test", "wibble_test.two_test"]
+        );
+    }
+
+    #[test]
+    fn shards_run_every_test_once() {
+        let shard = |string: &str| Selection {
+            filter: None,
+            shard: Some(string.parse().unwrap()),
+        };
+        assert_eq!(
+            selected_names(shard("1/2")),
+            vec![
+                "wibble/wubble_test.one_test",
+                "wibble_test.two_test",
+                "wobble_test.two_test",
+            ]
+        );
+        assert_eq!(
+            selected_names(shard("2/2")),
+            vec!["wibble_test.one_test", "wobble_test.one_test"]
+        );
+    }
+
+    #[test]
+    fn shard_of_filtered_tests() {
+        let selection = Selection {
+            filter: Some("one".into()),
+            shard: Some("2/2".parse().unwrap()),
+        };
+        assert_eq!(selected_names(selection), vec!["wibble_test.one_test"]);
+    }
+
+    #[test]
+    fn selection_arguments() {
+        let selection = Selection {
+            filter: Some("wibble".into()),
+            shard: Some("1/3".parse().unwrap()),
+        };
+        assert_eq!(
+            selection.arguments(),
+            vec!["--filter", "wibble", "--shard", "1/3"]
+        );
+    }
+
+    #[test]
+    fn parse_result_lines() {
+        assert_eq!(parse_result("glistix-test:3:ok"), Some((3, true)));
+        assert_eq!(parse_result("glistix-test:0:failed"), Some((0, false)));
+        assert_eq!(parse_result("glistix-test:wibble"), None);
+        assert_eq!(parse_result("3:ok"), None);
+    }
+
+    #[test]
+    fn nix_runner_imports_each_test() {
+        assert_eq!(
+            nix_runner(&[
+                test("wibble_test", "one_test"),
+                test("wibble/wubble_test", "two_test")
+            ]),
+            "let
+  run = test: (builtins.tryEval (builtins.deepSeq (test { }) true)).success;
+in
+builtins.map run [
+  (import ./wibble_test.nix).one_test
+  (import ./wibble/wubble_test.nix).two_test
+]
+"
+        );
+    }
+
+    #[test]
+    fn summary_of_results() {
+        assert_eq!(summary(3, 0), "3 tests passed");
+        assert_eq!(summary(0, 1), "1 test failed, 0 passed");
+    }
+}
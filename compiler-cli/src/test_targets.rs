@@ -0,0 +1,266 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    process::{Child, Command, Stdio},
+    str::FromStr,
+    sync::mpsc,
+};
+
+use camino::Utf8PathBuf;
+use glistix_core::{
+    build::{NullTelemetry, Runtime, Target},
+    error::Error,
+};
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+use crate::test_runner::Selection;
+
+/// A target given to `glistix test`, which can also be `all` to run the tests on
+/// every target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetSelection {
+    All,
+    Target(Target),
+}
+
+impl FromStr for TargetSelection {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let string = string.to_lowercase();
+        if string == "all" {
+            return Ok(Self::All);
+        }
+        Target::from_str(&string)
+            .map(Self::Target)
+            .map_err(|_| format!("unknown target `{string}`"))
+    }
+}
+
+/// The distinct targets selected, in the order they were given.
+pub fn resolve(selections: &[TargetSelection]) -> Vec<Target> {
+    let mut targets = vec![];
+    for selection in selections {
+        let selected = match selection {
+            TargetSelection::All => Target::iter().collect_vec(),
+            TargetSelection::Target(target) => vec![*target],
+        };
+        for target in selected {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+    }
+    targets
+}
+
+/// Runs the tests of the project on every target it supports, reporting the
+/// results of all of them together even when only one target is supported.
+pub fn all_targets_command(
+    arguments: Vec<String>,
+    runtime: Option<Runtime>,
+    env_file: Option<Utf8PathBuf>,
+    selection: Selection,
+) -> Result<(), Error> {
+    let config = crate::config::root_config()?;
+    command(
+        config.glistix.all_targets(),
+        arguments,
+        runtime,
+        env_file,
+        selection,
+    )
+}
+
+/// A line written by the test suite of a target.
+enum Output {
+    Stdout(Target, String),
+    Stderr(Target, String),
+}
+
+/// Runs the tests of the project on each of the given targets at the same time,
+/// each in its own `glistix test` process running the same selection of tests.
+/// Their output is printed as it comes, with each line prefixed by its target,
+/// and the process exits with an error if the tests failed on any target.
+pub fn command(
+    targets: Vec<Target>,
+    arguments: Vec<String>,
+    runtime: Option<Runtime>,
+    env_file: Option<Utf8PathBuf>,
+    selection: Selection,
+) -> Result<(), Error> {
+    // Download the dependencies once up front so the processes don't all try
+    // to do it at the same time.
+    let _ = crate::build::download_dependencies(NullTelemetry)?;
+
+    let program = std::env::current_exe().map_err(|error| Error::ShellCommand {
+        program: "glistix".into(),
+        err: Some(error.kind()),
+    })?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut children = vec![];
+    for target in &targets {
+        let mut args = vec!["test".to_string(), "--target".into(), target.to_string()];
+        if let (Target::JavaScript, Some(runtime)) = (target, runtime) {
+            args.extend(["--runtime".into(), runtime.to_string()]);
+        }
+        if let Some(env_file) = &env_file {
+            args.extend(["--env-file".into(), env_file.to_string()]);
+        }
+        args.extend(selection.arguments());
+        args.push("--".into());
+        args.extend(arguments.iter().cloned());
+
+        tracing::trace!(args=?args.join(" "), "spawning_target_tests");
+        let mut child = Command::new(&program)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| Error::ShellCommand {
+                program: program.to_string_lossy().into(),
+                err: Some(error.kind()),
+            })?;
+        stream_lines(&mut child, *target, &sender);
+        children.push((*target, child));
+    }
+    // Only the threads reading the output of the processes hold a sender now,
+    // so the loop below ends once all of them are done.
+    drop(sender);
+
+    // Ctrl+C is passed on to the test processes, which end and close their
+    // output, and then the summary is still printed.
+    ctrlc::set_handler(move || {}).expect("Error setting Ctrl-C handler");
+
+    let width = targets
+        .iter()
+        .map(|target| target.to_string().len())
+        .max()
+        .unwrap_or_default();
+    for output in receiver {
+        match output {
+            Output::Stdout(target, line) => println!("{}", prefix_line(target, width, &line)),
+            Output::Stderr(target, line) => eprintln!("{}", prefix_line(target, width, &line)),
+        }
+    }
+
+    let mut statuses = vec![];
+    for (target, mut child) in children {
+        let status = child.wait().map_err(|error| Error::ShellCommand {
+            program: program.to_string_lossy().into(),
+            err: Some(error.kind()),
+        })?;
+        // A process killed by a signal has no exit code.
+        statuses.push((target, status.code().unwrap_or(1)));
+    }
+
+    let mut stdout = io::stdout().lock();
+    let _ = stdout.write_all(summary(&statuses).as_bytes());
+    let _ = stdout.flush();
+
+    if statuses.iter().any(|(_, code)| *code != 0) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Sends the lines the process writes to its stdout and stderr to the channel,
+/// from a thread for each of them.
+fn stream_lines(child: &mut Child, target: Target, sender: &mpsc::Sender<Output>) {
+    if let Some(stdout) = child.stdout.take() {
+        spawn_reader(stdout, sender.clone(), move |line| {
+            Output::Stdout(target, line)
+        });
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_reader(stderr, sender.clone(), move |line| {
+            Output::Stderr(target, line)
+        });
+    }
+}
+
+fn spawn_reader(
+    reader: impl Read + Send + 'static,
+    sender: mpsc::Sender<Output>,
+    to_output: impl Fn(String) -> Output + Send + 'static,
+) {
+    let _ = std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else { break };
+            if sender.send(to_output(line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+fn prefix_line(target: Target, width: usize, line: &str) -> String {
+    let target = format!("[{target}]");
+    format!("{target:width$} {line}", width = width + 2)
+}
+
+fn summary(statuses: &[(Target, i32)]) -> String {
+    let width = statuses
+        .iter()
+        .map(|(target, _)| target.to_string().len())
+        .max()
+        .unwrap_or_default();
+    let mut summary = String::from("\nTest results by target:\n");
+    for (target, code) in statuses {
+        let result = match code {
+            0 => "passed".to_string(),
+            code => format!("failed (exit code {code})"),
+        };
+        summary.push_str(&format!("  {:width$}  {result}\n", target.to_string()));
+    }
+    summary
+}
+
+#[test]
+fn parse_target_selection() {
+    assert_eq!("all".parse(), Ok(TargetSelection::All));
+    assert_eq!("ALL".parse(), Ok(TargetSelection::All));
+    assert_eq!("nix".parse(), Ok(TargetSelection::Target(Target::Nix)));
+    assert_eq!(
+        "js".parse(),
+        Ok(TargetSelection::Target(Target::JavaScript))
+    );
+    assert_eq!(
+        "Erlang".parse(),
+        Ok(TargetSelection::Target(Target::Erlang))
+    );
+    assert!("wibble".parse::<TargetSelection>().is_err());
+}
+
+#[test]
+fn resolve_target_selections() {
+    assert_eq!(resolve(&[]), vec![]);
+    assert_eq!(
+        resolve(&[
+            TargetSelection::Target(Target::Nix),
+            TargetSelection::All,
+            TargetSelection::Target(Target::Erlang),
+        ]),
+        vec![Target::Nix, Target::Erlang, Target::JavaScript]
+    );
+}
+
+#[test]
+fn prefixed_lines_are_aligned() {
+    assert_eq!(prefix_line(Target::Nix, 10, "ok"), "[nix]        ok");
+    assert_eq!(prefix_line(Target::JavaScript, 10, "ok"), "[javascript] ok");
+}
+
+#[test]
+fn summary_of_target_results() {
+    assert_eq!(
+        summary(&[(Target::Erlang, 0), (Target::Nix, 1)]),
+        "
+Test results by target:
+  erlang  passed
+  nix     failed (exit code 1)
+"
+    );
+}
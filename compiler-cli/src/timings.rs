@@ -0,0 +1,250 @@
+use std::{
+    cell::RefCell,
+    time::{Duration, Instant},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use ecow::EcoString;
+use glistix_core::{
+    build::{Phase, Telemetry},
+    Result,
+};
+use itertools::Itertools;
+
+use crate::fs;
+
+/// The number of slowest modules listed in the timings report.
+const SLOWEST_MODULES_SHOWN: usize = 10;
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct TimingsOptions {
+    /// Report how long each phase of compilation took
+    #[arg(long)]
+    pub timings: bool,
+
+    /// Write the timings to the given file as a Chrome trace, implies --timings
+    #[arg(long, value_name = "FILE")]
+    pub timings_trace: Option<Utf8PathBuf>,
+}
+
+impl TimingsOptions {
+    pub fn enabled(&self) -> bool {
+        self.timings || self.timings_trace.is_some()
+    }
+}
+
+/// Telemetry that measures how long each phase of compilation takes, passing
+/// all other events on to the telemetry it wraps.
+#[derive(Debug)]
+pub struct Timings<'a> {
+    telemetry: &'a dyn Telemetry,
+    start: Instant,
+    running: RefCell<Vec<Timing>>,
+    finished: RefCell<Vec<Timing>>,
+}
+
+#[derive(Debug)]
+struct Timing {
+    phase: Phase,
+    package: String,
+    module: Option<String>,
+    start: Instant,
+    duration: Duration,
+}
+
+impl<'a> Timings<'a> {
+    pub fn new(telemetry: &'a dyn Telemetry) -> Self {
+        Self {
+            telemetry,
+            start: Instant::now(),
+            running: RefCell::new(vec![]),
+            finished: RefCell::new(vec![]),
+        }
+    }
+
+    /// Prints the total time taken by each phase of each package, followed by
+    /// the modules that took the longest to analyse, slowest first.
+    ///
+    /// The report is printed to stderr so it doesn't get mixed with any
+    /// diagnostics printed as JSON.
+    pub fn print_report(&self) {
+        let finished = self.finished.borrow();
+
+        let mut phases: Vec<(String, &str, Duration)> = vec![];
+        for timing in finished.iter() {
+            let phase = timing.phase.to_string();
+            match phases
+                .iter_mut()
+                .find(|(name, package, _)| *name == phase && *package == timing.package)
+            {
+                Some((_, _, duration)) => *duration += timing.duration,
+                None => phases.push((phase, &timing.package, timing.duration)),
+            }
+        }
+        let phases = phases
+            .into_iter()
+            .sorted_by(|(_, _, one), (_, _, other)| other.cmp(one))
+            .map(|(phase, package, duration)| {
+                [phase, package.to_string(), format!("{duration:.2?}")]
+            })
+            .collect_vec();
+
+        let modules = finished
+            .iter()
+            .filter_map(|timing| Some((timing.module.as_ref()?, timing)))
+            .sorted_by(|(_, one), (_, other)| other.duration.cmp(&one.duration))
+            .take(SLOWEST_MODULES_SHOWN)
+            .map(|(module, timing)| {
+                [
+                    module.clone(),
+                    timing.phase.to_string(),
+                    format!("{:.2?}", timing.duration),
+                ]
+            })
+            .collect_vec();
+
+        eprintln!();
+        print_table(["Phase", "Package", "Time"], &phases);
+        if !modules.is_empty() {
+            eprintln!();
+            print_table(["Module", "Phase", "Time"], &modules);
+        }
+    }
+
+    /// Writes every measured phase to a file in the Chrome trace event format,
+    /// which can be opened with `chrome://tracing` or https://ui.perfetto.dev.
+    pub fn write_chrome_trace(&self, path: &Utf8Path) -> Result<()> {
+        let events = self
+            .finished
+            .borrow()
+            .iter()
+            .map(|timing| {
+                serde_json::json!({
+                    "name": timing.module.as_ref().unwrap_or(&timing.package),
+                    "cat": timing.phase.to_string(),
+                    "ph": "X",
+                    "ts": timing.start.saturating_duration_since(self.start).as_micros() as u64,
+                    "dur": timing.duration.as_micros() as u64,
+                    "pid": 1,
+                    "tid": 1,
+                    "args": { "package": timing.package },
+                })
+            })
+            .collect_vec();
+        let trace = serde_json::json!({ "traceEvents": events });
+        let json = serde_json::to_string(&trace).expect("Chrome trace JSON serialisation");
+        fs::write(path, &json)
+    }
+}
+
+fn print_table(headers: [&str; 3], rows: &[[String; 3]]) {
+    let width = |column: usize| {
+        rows.iter()
+            .filter_map(|row| row.get(column))
+            .map(|cell| cell.len())
+            .chain(headers.get(column).map(|header| header.len()))
+            .max()
+            .unwrap_or(0)
+    };
+    let (first, second, third) = (width(0), width(1), width(2));
+
+    let [header_1, header_2, header_3] = headers;
+    eprintln!("{header_1:<first$}  {header_2:<second$}  {header_3:>third$}");
+    for [cell_1, cell_2, cell_3] in rows {
+        eprintln!("{cell_1:<first$}  {cell_2:<second$}  {cell_3:>third$}");
+    }
+}
+
+impl Telemetry for Timings<'_> {
+    fn waiting_for_build_directory_lock(&self) {
+        self.telemetry.waiting_for_build_directory_lock()
+    }
+
+    fn running(&self, name: &str) {
+        self.telemetry.running(name)
+    }
+
+    fn resolving_package_versions(&self) {
+        self.telemetry.resolving_package_versions()
+    }
+
+    fn downloading_package(&self, name: &str) {
+        self.telemetry.downloading_package(name)
+    }
+
+    fn packages_downloaded(&self, start: Instant, count: usize) {
+        self.telemetry.packages_downloaded(start, count)
+    }
+
+    fn compiled_package(&self, duration: Duration) {
+        self.telemetry.compiled_package(duration)
+    }
+
+    fn compiling_package(&self, name: &str) {
+        self.telemetry.compiling_package(name)
+    }
+
+    fn checked_package(&self, duration: Duration) {
+        self.telemetry.checked_package(duration)
+    }
+
+    fn checking_package(&self, name: &str) {
+        self.telemetry.checking_package(name)
+    }
+
+    fn skipped_modules(&self, modules: &[EcoString]) {
+        self.telemetry.skipped_modules(modules)
+    }
+
+    fn phase_started(&self, phase: Phase, package: &str, module: Option<&str>) {
+        self.running.borrow_mut().push(Timing {
+            phase,
+            package: package.into(),
+            module: module.map(Into::into),
+            start: Instant::now(),
+            duration: Duration::ZERO,
+        });
+    }
+
+    fn phase_finished(&self) {
+        let Some(mut timing) = self.running.borrow_mut().pop() else {
+            return;
+        };
+        timing.duration = timing.start.elapsed();
+        self.finished.borrow_mut().push(timing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glistix_core::build::{NullTelemetry, Target};
+
+    use super::*;
+
+    #[test]
+    fn nested_phases_are_recorded() {
+        let timings = Timings::new(&NullTelemetry);
+        timings.phase_started(Phase::Codegen(Target::Erlang), "wibble", None);
+        timings.phase_started(Phase::CompileBeam, "wibble", None);
+        timings.phase_finished();
+        timings.phase_finished();
+        timings.phase_started(Phase::Analyse, "wibble", Some("wibble/wobble"));
+        timings.phase_finished();
+
+        let finished = timings.finished.borrow();
+        let phases = finished
+            .iter()
+            .map(|timing| (timing.phase, timing.module.as_deref()))
+            .collect_vec();
+        assert_eq!(
+            phases,
+            vec![
+                (Phase::CompileBeam, None),
+                (Phase::Codegen(Target::Erlang), None),
+                (Phase::Analyse, Some("wibble/wobble")),
+            ]
+        );
+        assert!(timings.running.borrow().is_empty());
+    }
+}
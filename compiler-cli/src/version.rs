@@ -0,0 +1,295 @@
+use camino::Utf8Path;
+use clap::ValueEnum;
+use glistix_core::{
+    error::{Error, Result},
+    paths::ProjectPaths,
+};
+use hexpm::version::Version;
+
+use crate::{cli, config};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Bump {
+    /// For backwards compatible bug fixes, e.g. 1.2.3 -> 1.2.4
+    Patch,
+    /// For backwards compatible additions, e.g. 1.2.3 -> 1.3.0
+    Minor,
+    /// For breaking changes, e.g. 1.2.3 -> 2.0.0
+    Major,
+}
+
+/// What the public API of the package looks to have gone through since the
+/// last release, going by the `pub` definitions added and removed in `src`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ApiChange {
+    None,
+    Additions,
+    Breaking,
+}
+
+pub fn command(bump: Bump, tag: bool, changelog: bool) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = config::root_config()?;
+    let old = config.version;
+    let new = bumped(&old, bump);
+
+    check_api_changes(paths.root(), &old, bump)?;
+
+    config::set("version".into(), format!("\"{new}\""))?;
+
+    let changelog_path = paths.root().join("CHANGELOG.md");
+    if changelog {
+        let existing = if changelog_path.is_file() {
+            Some(crate::fs::read(&changelog_path)?)
+        } else {
+            None
+        };
+        crate::fs::write(
+            &changelog_path,
+            &with_changelog_stub(existing.as_deref(), &new),
+        )?;
+    }
+
+    if tag {
+        commit_and_tag(&paths, &new, changelog)?;
+    }
+
+    cli::print_colourful_prefix("Bumped", &format!("{} v{old} -> v{new}", config.name));
+    Ok(())
+}
+
+/// The version after the given bump. A pre-release is bumped to its release
+/// when that release is what the bump would give, so `1.0.0-rc1` becomes
+/// `1.0.0` with any of the bumps.
+fn bumped(version: &Version, bump: Bump) -> Version {
+    let pre = !version.pre.is_empty();
+    let Version {
+        major,
+        minor,
+        patch,
+        ..
+    } = *version;
+    match bump {
+        Bump::Patch if pre => Version::new(major, minor, patch),
+        Bump::Patch => Version::new(major, minor, patch + 1),
+        Bump::Minor if pre && patch == 0 => Version::new(major, minor, 0),
+        Bump::Minor => Version::new(major, minor + 1, 0),
+        Bump::Major if pre && minor == 0 && patch == 0 => Version::new(major, 0, 0),
+        Bump::Major => Version::new(major + 1, 0, 0),
+    }
+}
+
+/// Warns if the changes to `src` since the tag of the current version look to
+/// need a bigger bump than the one asked for. Nothing is checked if the
+/// project isn't in a git repository or the tag doesn't exist.
+fn check_api_changes(root: &Utf8Path, version: &Version, bump: Bump) -> Result<()> {
+    if !crate::fs::is_inside_git_work_tree(root)? {
+        return Ok(());
+    }
+    let tag = format!("v{version}");
+    if !crate::fs::git_ref_exists(root, &format!("refs/tags/{tag}"))? {
+        tracing::debug!(tag = %tag, "skipping_api_check_without_tag");
+        return Ok(());
+    }
+
+    let diff = crate::fs::git(root, &["diff", "--unified=0", &tag, "--", "src"])?;
+    let change = api_change(&diff);
+    let required = required_bump(version, change);
+    if bump >= required {
+        return Ok(());
+    }
+
+    let reason = match change {
+        ApiChange::Breaking => "removes or changes public definitions",
+        ApiChange::Additions | ApiChange::None => "adds public definitions",
+    };
+    eprintln!(
+        "WARNING: The code in `src` {reason} since {tag}, which usually \
+calls for a {} bump rather than a {} one.",
+        bump_name(required),
+        bump_name(bump)
+    );
+    Ok(())
+}
+
+/// Finds out what happened to the public API from a `git diff --unified=0`,
+/// looking at the lines starting with `pub`. A removed line that isn't added
+/// back as it was is taken to be a breaking change.
+fn api_change(diff: &str) -> ApiChange {
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for line in diff.lines() {
+        if line.starts_with("---") || line.starts_with("+++") {
+            continue;
+        }
+        if let Some(line) = line.strip_prefix('-') {
+            if line.starts_with("pub ") {
+                removed.push(line.trim());
+            }
+        } else if let Some(line) = line.strip_prefix('+') {
+            if line.starts_with("pub ") {
+                added.push(line.trim());
+            }
+        }
+    }
+
+    if removed.iter().any(|line| !added.contains(line)) {
+        ApiChange::Breaking
+    } else if added.iter().any(|line| !removed.contains(line)) {
+        ApiChange::Additions
+    } else {
+        ApiChange::None
+    }
+}
+
+/// The smallest bump for the given API change. Before 1.0.0 breaking changes
+/// only need a minor bump and additions can go in a patch.
+fn required_bump(version: &Version, change: ApiChange) -> Bump {
+    match (change, version.major) {
+        (ApiChange::None, _) | (ApiChange::Additions, 0) => Bump::Patch,
+        (ApiChange::Additions, _) | (ApiChange::Breaking, 0) => Bump::Minor,
+        (ApiChange::Breaking, _) => Bump::Major,
+    }
+}
+
+fn bump_name(bump: Bump) -> &'static str {
+    match bump {
+        Bump::Patch => "patch",
+        Bump::Minor => "minor",
+        Bump::Major => "major",
+    }
+}
+
+/// Adds a heading for the new version above the existing entries of the
+/// changelog, after its title if it has one.
+fn with_changelog_stub(changelog: Option<&str>, version: &Version) -> String {
+    let stub = format!("## v{version}\n\n");
+    let Some(changelog) = changelog else {
+        return format!("# Changelog\n\n{stub}");
+    };
+    match changelog.split_once('\n') {
+        Some((title, rest)) if title.starts_with("# ") => {
+            format!("{title}\n\n{stub}{}", rest.trim_start_matches('\n'))
+        }
+        Some(_) | None if changelog.trim().is_empty() => format!("# Changelog\n\n{stub}"),
+        Some(_) | None => format!("{stub}{changelog}"),
+    }
+}
+
+/// Commits the updated files and tags the commit as `v<version>`.
+fn commit_and_tag(paths: &ProjectPaths, version: &Version, changelog: bool) -> Result<()> {
+    let root = paths.root();
+    let tag = format!("v{version}");
+    if crate::fs::git_ref_exists(root, &format!("refs/tags/{tag}"))? {
+        return Err(Error::GitCommand {
+            command: format!("tag {tag}"),
+            error: format!("The tag {tag} already exists."),
+        });
+    }
+
+    let mut files = vec!["gleam.toml"];
+    if changelog {
+        files.push("CHANGELOG.md");
+    }
+    let _ = crate::fs::git(root, &[&["add", "--"][..], &files].concat())?;
+    let _ = crate::fs::git(root, &["commit", "--quiet", "--message", &tag])?;
+    let _ = crate::fs::git(root, &["tag", &tag])?;
+    cli::print_colourful_prefix("Tagged", &tag);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(text: &str) -> Version {
+        Version::parse(text).expect("version")
+    }
+
+    #[test]
+    fn bumped_release() {
+        let old = version("1.2.3");
+        assert_eq!(bumped(&old, Bump::Patch), version("1.2.4"));
+        assert_eq!(bumped(&old, Bump::Minor), version("1.3.0"));
+        assert_eq!(bumped(&old, Bump::Major), version("2.0.0"));
+    }
+
+    #[test]
+    fn bumped_pre_release() {
+        assert_eq!(bumped(&version("1.0.0-rc1"), Bump::Patch), version("1.0.0"));
+        assert_eq!(bumped(&version("1.0.0-rc1"), Bump::Minor), version("1.0.0"));
+        assert_eq!(bumped(&version("1.0.0-rc1"), Bump::Major), version("1.0.0"));
+        assert_eq!(bumped(&version("1.2.0-rc1"), Bump::Major), version("2.0.0"));
+        assert_eq!(bumped(&version("1.2.3-rc1"), Bump::Minor), version("1.3.0"));
+    }
+
+    const DIFF: &str = "diff --git a/src/wibble.gleam b/src/wibble.gleam
+--- a/src/wibble.gleam
++++ b/src/wibble.gleam
+@@ -1 +1 @@
+-pub fn wibble(x: Int) -> Int {
++pub fn wibble(x: Float) -> Float {
+";
+
+    #[test]
+    fn api_change_changed_signature() {
+        assert_eq!(api_change(DIFF), ApiChange::Breaking);
+    }
+
+    #[test]
+    fn api_change_additions() {
+        let diff = "@@ -3,0 +4,2 @@
++pub type Wobble
++  let x = 1
+";
+        assert_eq!(api_change(diff), ApiChange::Additions);
+    }
+
+    #[test]
+    fn api_change_moved_definition() {
+        let diff = "@@ -1 +0,0 @@
+-pub const wibble = 1
+@@ -9,0 +9 @@
++pub const wibble = 1
+-  private_stuff()
+";
+        assert_eq!(api_change(diff), ApiChange::None);
+    }
+
+    #[test]
+    fn required_bump_before_one() {
+        assert_eq!(
+            required_bump(&version("0.3.0"), ApiChange::Breaking),
+            Bump::Minor
+        );
+        assert_eq!(
+            required_bump(&version("0.3.0"), ApiChange::Additions),
+            Bump::Patch
+        );
+        assert_eq!(
+            required_bump(&version("1.3.0"), ApiChange::Breaking),
+            Bump::Major
+        );
+        assert_eq!(
+            required_bump(&version("1.3.0"), ApiChange::Additions),
+            Bump::Minor
+        );
+    }
+
+    #[test]
+    fn changelog_stub_after_title() {
+        let changelog = "# Changelog\n\n## v1.0.0\n\n- Initial release.\n";
+        assert_eq!(
+            with_changelog_stub(Some(changelog), &version("1.1.0")),
+            "# Changelog\n\n## v1.1.0\n\n## v1.0.0\n\n- Initial release.\n"
+        );
+    }
+
+    #[test]
+    fn changelog_stub_new_file() {
+        assert_eq!(
+            with_changelog_stub(None, &version("0.1.1")),
+            "# Changelog\n\n## v0.1.1\n\n"
+        );
+    }
+}
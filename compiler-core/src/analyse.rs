@@ -1,5 +1,7 @@
+pub(crate) mod derive;
 mod imports;
 pub(crate) mod name;
+mod nix_paths;
 
 #[cfg(test)]
 mod tests;
@@ -15,7 +17,7 @@ use crate::{
     },
     build::{Origin, Outcome, Target},
     call_graph::{into_dependency_order, CallGraphNode},
-    config::PackageConfig,
+    config::{PackageConfig, PrivateTypeLeaks},
     dep_tree,
     line_numbers::LineNumbers,
     parse::SpannedString,
@@ -40,8 +42,9 @@ use ecow::EcoString;
 use hexpm::version::Version;
 use itertools::Itertools;
 use name::{check_argument_names, check_name_case};
+use regex::Regex;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     ops::Deref,
     sync::{Arc, OnceLock},
 };
@@ -199,6 +202,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         }
 
         let documentation = std::mem::take(&mut module.documentation);
+        let deprecation = std::mem::take(&mut module.deprecation);
         let env = Environment::new(
             self.ids.clone(),
             self.package_config.name.clone(),
@@ -209,7 +213,21 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             self.target_support,
         );
 
-        let statements = GroupedStatements::new(module.into_iter_statements(self.target));
+        let mut statements = GroupedStatements::new(module.into_iter_statements(self.target));
+
+        // Generate the functions asked for with `@derive` attributes so they
+        // can be registered and inferred along with the rest of the module.
+        let derived_functions = self.derive_decoders(&mut statements);
+        self.check_nix_derives(&statements, &derived_functions);
+        let private_derived_functions = derived_functions
+            .iter()
+            .filter(|function| function.publicity.is_private())
+            .filter_map(|function| function.name.as_ref().map(|(_, name)| name.clone()))
+            .collect_vec();
+        statements.functions.extend(derived_functions);
+        for function in &mut statements.functions {
+            self.use_any_external(function);
+        }
         let statements_count = statements.len();
 
         // Register any modules, types, and values being imported
@@ -239,6 +257,12 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             }
         }
 
+        // A derived function is generated whether or not it ends up being
+        // used, so it's never reported as unused.
+        for name in &private_derived_functions {
+            env.increment_usage(name);
+        }
+
         // Infer the types of each statement in the module
         let mut typed_statements = Vec::with_capacity(statements_count);
         for i in statements.imports {
@@ -282,6 +306,16 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         // Generate warnings for unused items
         env.convert_unused_to_warnings(&mut self.problems);
 
+        for error in derive::nixos_options_type_errors(&typed_statements, &self.module_name) {
+            self.problems.error(error);
+        }
+
+        if !self.package_config.glistix.allow_invalid_nix_paths {
+            for warning in nix_paths::invalid_path_warnings(&typed_statements) {
+                self.problems.warning(warning);
+            }
+        }
+
         // Remove imported types and values to create the public interface
         // Private types and values are retained so they can be used in the language
         // server, but are filtered out when type checking to prevent using private
@@ -289,9 +323,23 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         env.module_types
             .retain(|_, info| info.module == self.module_name);
 
-        // Ensure no exported values have private types in their type signature
+        // Ensure no exported values or type aliases have private types in their
+        // type signature
         for value in env.module_values.values() {
-            self.check_for_type_leaks(value)
+            self.check_for_type_leaks(
+                value.publicity,
+                value.variant.definition_location(),
+                &value.type_,
+                &env.module_types,
+            )
+        }
+        for type_ in env.module_types.values() {
+            self.check_for_type_leaks(
+                type_.publicity,
+                type_.origin,
+                &type_.type_,
+                &env.module_types,
+            )
         }
 
         let Environment {
@@ -335,8 +383,10 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
                 src_path: self.src_path,
                 warnings,
                 minimum_required_version: self.minimum_required_version,
+                deprecation: deprecation.clone(),
             },
             names: type_names,
+            deprecation,
         };
 
         match Vec1::try_from_vec(self.problems.take_errors()) {
@@ -345,6 +395,83 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         }
     }
 
+    /// Generates the decoder function of each custom type with the
+    /// `@derive(decode)` attribute, recording an error for any type a decoder
+    /// can't be generated for. The prelude is imported for the decoders if
+    /// the module doesn't import it already.
+    fn derive_decoders(&mut self, statements: &mut GroupedStatements) -> Vec<UntypedFunction> {
+        let imports = derive::DecoderImports::new(&statements.imports);
+        let derived_types: HashSet<_> = statements
+            .custom_types
+            .iter()
+            .filter(|custom_type| custom_type.derive_decode.is_some())
+            .map(|custom_type| custom_type.name.clone())
+            .collect();
+
+        let mut functions = vec![];
+        for custom_type in &statements.custom_types {
+            let Some(location) = custom_type.derive_decode else {
+                continue;
+            };
+            match derive::decoder_function(location, custom_type, &imports, &derived_types) {
+                Ok(function) => functions.push(function),
+                Err(error) => self.problems.error(error),
+            }
+        }
+
+        if !functions.is_empty() {
+            statements.imports.extend(imports.prelude_import());
+        }
+        functions
+    }
+
+    /// Records an error for each custom type with the `@derive(nix_attrs)` or
+    /// `@derive(nixos_options)` attribute whose Nix functions can't be
+    /// generated.
+    fn check_nix_derives(
+        &mut self,
+        statements: &GroupedStatements,
+        derived_functions: &[UntypedFunction],
+    ) {
+        let mut module_names: HashSet<EcoString> = statements
+            .functions
+            .iter()
+            .chain(derived_functions)
+            .filter_map(|function| function.name.as_ref().map(|(_, name)| name.clone()))
+            .chain(
+                statements
+                    .constants
+                    .iter()
+                    .map(|constant| constant.name.clone()),
+            )
+            .chain(statements.imports.iter().flat_map(|import| {
+                import
+                    .unqualified_values
+                    .iter()
+                    .map(|value| value.used_name().clone())
+            }))
+            .collect();
+
+        for custom_type in &statements.custom_types {
+            if let Some(location) = custom_type.derive_nix_attrs {
+                match derive::check_nix_attrs(location, custom_type, &module_names) {
+                    Ok(()) => {
+                        module_names.extend(derive::nix_attrs_function_names(&custom_type.name))
+                    }
+                    Err(error) => self.problems.error(error),
+                }
+            }
+            if let Some(location) = custom_type.derive_nixos_options {
+                match derive::check_nixos_options(location, custom_type, &module_names) {
+                    Ok(()) => {
+                        module_names.extend(derive::nixos_options_function_names(&custom_type.name))
+                    }
+                    Err(error) => self.problems.error(error),
+                }
+            }
+        }
+    }
+
     fn all_errors<T>(&mut self, error: Error) -> Outcome<T, Vec1<Error>> {
         Outcome::TotalFailure(Vec1::from_vec_push(self.problems.take_errors(), error))
     }
@@ -363,9 +490,13 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             publicity,
             value,
             deprecation,
+            since,
+            embedded_file,
+            strict,
             ..
         } = c;
         self.check_name_case(name_location, &name, Named::Constant);
+        self.check_prelude_name_shadowing(name_location, &name, Named::Constant);
 
         let definition = FunctionDefinition {
             has_body: true,
@@ -398,12 +529,13 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         let variant = ValueConstructor {
             publicity,
             deprecation: deprecation.clone(),
+            since: since.clone(),
             variant: ValueConstructorVariant::ModuleConstant {
                 documentation: doc.as_ref().map(|(_, doc)| doc.clone()),
                 location,
                 literal: typed_expr.clone(),
                 module: self.module_name.clone(),
-                implementations,
+                implementations: implementations.clone(),
             },
             type_: type_.clone(),
         };
@@ -414,6 +546,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             type_.clone(),
             publicity,
             Deprecation::NotDeprecated,
+            since.clone(),
         );
         environment.insert_module_value(name.clone(), variant);
 
@@ -436,7 +569,10 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             value: Box::new(typed_expr),
             type_,
             deprecation,
+            since,
             implementations,
+            embedded_file,
+            strict,
         })
     }
 
@@ -458,11 +594,14 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             return_annotation,
             end_position: end_location,
             deprecation,
+            since,
             external_erlang,
             external_javascript,
             external_nix,
+            external_any,
             return_type: (),
             implementations: _,
+            inline,
         } = f;
         let (name_location, name) = name.expect("Function in a definition must be named");
         let target = environment.target;
@@ -484,6 +623,15 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         // the implementation for Nix externals.
         self.assert_valid_nix_external(&name, external_nix.as_ref(), location);
 
+        // Ensure that the package does not forbid externals for any of the
+        // targets this function has an external implementation for.
+        self.ensure_externals_are_allowed(
+            &name,
+            &external_erlang,
+            &external_javascript,
+            &external_nix,
+        );
+
         // Find the external implementation for the current target, if one has been given.
         let external = target_function_implementation(
             target,
@@ -633,7 +781,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             module: environment.current_module.clone(),
             arity: typed_args.len(),
             location,
-            implementations,
+            implementations: implementations.clone(),
         };
 
         environment.insert_variable(
@@ -642,6 +790,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             preregistered_type.clone(),
             publicity,
             deprecation.clone(),
+            since.clone(),
         );
 
         Definition::Function(Function {
@@ -650,6 +799,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             name: Some((name_location, name)),
             publicity,
             deprecation,
+            since,
             arguments: typed_args,
             end_position: end_location,
             return_annotation,
@@ -660,7 +810,9 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             external_erlang,
             external_javascript,
             external_nix,
+            external_any,
             implementations,
+            inline,
         })
     }
 
@@ -670,29 +822,18 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         external_javascript: Option<&(EcoString, EcoString, SrcSpan)>,
         location: SrcSpan,
     ) {
-        use regex::Regex;
-
-        static MODULE: OnceLock<Regex> = OnceLock::new();
-        static FUNCTION: OnceLock<Regex> = OnceLock::new();
-
         let (module, function) = match external_javascript {
             None => return,
             Some((module, function, _location)) => (module, function),
         };
-        if !MODULE
-            .get_or_init(|| Regex::new("^[@a-zA-Z0-9\\./:_-]+$").expect("regex"))
-            .is_match(module)
-        {
+        if !is_valid_javascript_external_module(module) {
             self.problems.error(Error::InvalidExternalJavascriptModule {
                 location,
                 module: module.clone(),
                 name: function_name.clone(),
             });
         }
-        if !FUNCTION
-            .get_or_init(|| Regex::new("^[a-zA-Z_][a-zA-Z0-9_]*$").expect("regex"))
-            .is_match(function)
-        {
+        if !is_valid_javascript_external_function(function) {
             self.problems
                 .error(Error::InvalidExternalJavascriptFunction {
                     location,
@@ -708,34 +849,18 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         external_nix: Option<&(EcoString, EcoString, SrcSpan)>,
         location: SrcSpan,
     ) {
-        use regex::Regex;
-
-        static MODULE: OnceLock<Regex> = OnceLock::new();
-        static FUNCTION: OnceLock<Regex> = OnceLock::new();
-
         let (module, function) = match external_nix {
             None => return,
             Some((module, function, _location)) => (module, function),
         };
-        // TODO(NIX): Consider allowing arbitrary paths, incl. <...> notation
-        // Currently, we force paths to be relative to something, that is,
-        // you can't import an external function from "word", but you can from
-        // "./word" or "../word". You can also import from "." or "..".
-        // We should expand this in the future.
-        if !MODULE
-            .get_or_init(|| Regex::new("^(?:\\.\\.?|\\.\\.?/[a-zA-Z0-9\\./:_-]*)$").expect("regex"))
-            .is_match(module)
-        {
+        if !is_valid_nix_external_module(module) {
             self.problems.error(Error::InvalidExternalNixModule {
                 location,
                 module: module.clone(),
                 name: function_name.clone(),
             });
         }
-        if !FUNCTION
-            .get_or_init(|| Regex::new("^[a-zA-Z_][a-zA-Z0-9_'-]*$").expect("regex"))
-            .is_match(function)
-        {
+        if !is_valid_nix_external_function(function) {
             self.problems.error(Error::InvalidExternalNixFunction {
                 location,
                 function: function.clone(),
@@ -744,6 +869,81 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         }
     }
 
+    /// Uses the `@external(any, ...)` implementation of a function, if it has
+    /// one, for each target without an external implementation of its own
+    /// that its module and function names are valid on. From then on the
+    /// function is treated as if it had been given all those target-specific
+    /// externals.
+    fn use_any_external(&mut self, function: &mut UntypedFunction) {
+        let Some(external_any) = &function.external_any else {
+            return;
+        };
+        let (module, function_name, _) = external_any;
+        let externals = [
+            (
+                &mut function.external_erlang,
+                is_valid_erlang_external_name(module)
+                    && is_valid_erlang_external_name(function_name),
+            ),
+            (
+                &mut function.external_javascript,
+                is_valid_javascript_external_module(module)
+                    && is_valid_javascript_external_function(function_name),
+            ),
+            (
+                &mut function.external_nix,
+                is_valid_nix_external(module, function_name),
+            ),
+        ];
+        let mut used = false;
+        for (external, valid) in externals {
+            if external.is_none() && valid {
+                *external = Some(external_any.clone());
+                used = true;
+            }
+        }
+        if !used {
+            let (_, name) = function
+                .name
+                .as_ref()
+                .expect("A module's function must be named");
+            self.problems.error(Error::UnusedAnyExternal {
+                location: external_any.2,
+                name: name.clone(),
+            });
+        }
+    }
+
+    fn ensure_externals_are_allowed(
+        &mut self,
+        function_name: &EcoString,
+        external_erlang: &Option<(EcoString, EcoString, SrcSpan)>,
+        external_javascript: &Option<(EcoString, EcoString, SrcSpan)>,
+        external_nix: &Option<(EcoString, EcoString, SrcSpan)>,
+    ) {
+        let denied = &self.package_config.glistix.deny_externals;
+        if denied.is_empty() {
+            return;
+        }
+        let externals = [
+            (Target::Erlang, external_erlang),
+            (Target::JavaScript, external_javascript),
+            (Target::Nix, external_nix),
+        ];
+        for (target, external) in externals {
+            let Some((_, _, location)) = external else {
+                continue;
+            };
+            if denied.contains(&target) {
+                self.problems.error(Error::DeniedExternal {
+                    location: *location,
+                    name: function_name.clone(),
+                    target,
+                });
+            }
+        }
+    }
+
     fn ensure_annotations_present(
         &mut self,
         arguments: &[UntypedArg],
@@ -867,6 +1067,10 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             parameters,
             constructors,
             deprecation,
+            since,
+            derive_decode,
+            derive_nix_attrs,
+            derive_nixos_options,
             ..
         } = t;
 
@@ -987,6 +1191,10 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             constructors,
             typed_parameters,
             deprecation,
+            since,
+            derive_decode,
+            derive_nix_attrs,
+            derive_nixos_options,
         }))
     }
 
@@ -1002,6 +1210,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             name,
             constructors,
             deprecation,
+            since,
             ..
         } = t;
 
@@ -1049,6 +1258,15 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
                 continue;
             }
 
+            let targets = prelude_constructor_collisions(&constructor.name);
+            if !targets.is_empty() {
+                self.problems.warning(Warning::PreludeConstructorShadowed {
+                    location: constructor.name_location,
+                    name: constructor.name.clone(),
+                    targets,
+                });
+            }
+
             let mut field_map = FieldMap::new(constructor.arguments.len() as u32);
             let mut args_types = Vec::with_capacity(constructor.arguments.len());
             let mut fields = Vec::with_capacity(constructor.arguments.len());
@@ -1124,6 +1342,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
                 ValueConstructor {
                     publicity: value_constructor_publicity,
                     deprecation: deprecate_constructor.clone(),
+                    since: since.clone(),
                     type_: type_.clone(),
                     variant: constructor_info.clone(),
                 },
@@ -1148,6 +1367,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
                 type_,
                 value_constructor_publicity,
                 deprecate_constructor.clone(),
+                since.clone(),
             );
 
             environment.names.named_constructor_in_scope(
@@ -1177,7 +1397,9 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             publicity,
             parameters,
             location,
+            end_position,
             deprecation,
+            since,
             opaque,
             constructors,
             documentation,
@@ -1193,6 +1415,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         environment.assert_unique_type_name(name, *location)?;
 
         self.check_name_case(*name_location, name, Named::Type);
+        self.check_prelude_name_shadowing(*name_location, name, Named::Type);
 
         let mut hydrator = Hydrator::new();
         let parameters = self.make_type_vars(parameters, &mut hydrator, environment);
@@ -1230,6 +1453,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
                     origin: *location,
                     module: self.module_name.clone(),
                     deprecation: deprecation.clone(),
+                    since: since.clone(),
                     parameters,
                     publicity,
                     type_,
@@ -1253,7 +1477,13 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         if publicity.is_private() {
             environment.init_usage(
                 name.clone(),
-                EntityKind::PrivateType,
+                EntityKind::PrivateType {
+                    definition_location: whole_definition_location(
+                        SrcSpan::new(location.start, *end_position),
+                        documentation,
+                        &[],
+                    ),
+                },
                 *location,
                 &mut self.problems,
             );
@@ -1270,6 +1500,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             name_location,
             type_ast: resolved_type,
             deprecation,
+            since,
             type_: _,
             documentation,
         } = t;
@@ -1283,6 +1514,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         }
 
         self.check_name_case(*name_location, name, Named::TypeAlias);
+        self.check_prelude_name_shadowing(*name_location, name, Named::TypeAlias);
 
         // Use the hydrator to convert the AST into a type, erroring if the AST was invalid
         // in some fashion.
@@ -1305,6 +1537,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
                     parameters,
                     type_,
                     deprecation: deprecation.clone(),
+                    since: since.clone(),
                     publicity: *publicity,
                     documentation: documentation.as_ref().map(|(_, doc)| doc.clone()),
                 },
@@ -1326,7 +1559,9 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         if publicity.is_private() {
             environment.init_usage(
                 name.clone(),
-                EntityKind::PrivateType,
+                EntityKind::PrivateType {
+                    definition_location: whole_definition_location(*location, documentation, &[]),
+                },
                 *location,
                 &mut self.problems,
             );
@@ -1377,15 +1612,19 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             external_erlang,
             external_javascript,
             external_nix,
+            external_any: _,
             deprecation,
-            end_position: _,
+            since,
+            end_position,
             body: _,
             return_type: _,
             implementations,
+            inline: _,
         } = f;
         let (name_location, name) = name.as_ref().expect("A module's function must be named");
 
         self.check_name_case(*name_location, name, Named::Function);
+        self.check_prelude_name_shadowing(*name_location, name, Named::Function);
 
         let mut builder = FieldMapBuilder::new(args.len() as u32);
         for Arg {
@@ -1432,7 +1671,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             module: environment.current_module.clone(),
             arity: args.len(),
             location: *location,
-            implementations: *implementations,
+            implementations: implementations.clone(),
         };
         environment.insert_variable(
             name.clone(),
@@ -1440,11 +1679,23 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             type_,
             *publicity,
             deprecation.clone(),
+            since.clone(),
         );
         if publicity.is_private() {
+            let attributes = [external_erlang, external_javascript, external_nix]
+                .into_iter()
+                .flatten()
+                .map(|(_, _, location)| *location)
+                .collect_vec();
             environment.init_usage(
                 name.clone(),
-                EntityKind::PrivateFunction,
+                EntityKind::PrivateFunction {
+                    definition_location: whole_definition_location(
+                        SrcSpan::new(location.start, *end_position),
+                        documentation,
+                        &attributes,
+                    ),
+                },
                 *location,
                 &mut self.problems,
             );
@@ -1452,18 +1703,43 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         Ok(())
     }
 
-    fn check_for_type_leaks(&mut self, value: &ValueConstructor) {
+    fn check_for_type_leaks(
+        &mut self,
+        publicity: Publicity,
+        location: SrcSpan,
+        type_: &Type,
+        module_types: &HashMap<EcoString, TypeConstructor>,
+    ) {
         // A private value doesn't export anything so it can't leak anything.
-        if value.publicity.is_private() {
+        if publicity.is_private() {
             return;
         }
 
         // If a private or internal value references a private type
-        if let Some(leaked) = value.type_.find_private_type() {
-            self.problems.error(Error::PrivateTypeLeak {
-                location: value.variant.definition_location(),
+        let Some(leaked) = type_.find_private_type() else {
+            return;
+        };
+
+        // Private types can only be used in the module that defines them, but
+        // one could still come from a dependency reporting leaks as warnings.
+        let type_location = match &leaked {
+            Type::Named { module, name, .. } if *module == self.module_name => {
+                module_types.get(name).map(|type_| type_.origin)
+            }
+            _ => None,
+        };
+
+        match self.package_config.glistix.private_type_leaks {
+            PrivateTypeLeaks::Error => self.problems.error(Error::PrivateTypeLeak {
+                location,
                 leaked,
-            });
+                type_location,
+            }),
+            PrivateTypeLeaks::Warning => self.problems.warning(Warning::PrivateTypeLeak {
+                location,
+                leaked,
+                type_location,
+            }),
         }
     }
 
@@ -1473,6 +1749,20 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         }
     }
 
+    /// Warns when a type, function or constant of a module compiled to Nix has
+    /// the name of something the prelude exports through the package's
+    /// `gleam.nix`.
+    fn check_prelude_name_shadowing(&mut self, location: SrcSpan, name: &EcoString, kind: Named) {
+        if self.target == Target::Nix && crate::nix::prelude_exports().any(|export| export == name)
+        {
+            self.problems.warning(Warning::PreludeNameShadowed {
+                location,
+                name: name.clone(),
+                kind,
+            });
+        }
+    }
+
     fn track_feature_usage(&mut self, feature_kind: FeatureKind, location: SrcSpan) {
         let minimum_required_version = feature_kind.required_version();
 
@@ -1523,6 +1813,26 @@ fn validate_module_name(name: &EcoString) -> Result<(), Error> {
     Ok(())
 }
 
+/// The span of a whole definition, extended to cover its documentation and any
+/// attributes that come before it.
+fn whole_definition_location(
+    location: SrcSpan,
+    documentation: &Option<(u32, EcoString)>,
+    attributes: &[SrcSpan],
+) -> SrcSpan {
+    let start = attributes
+        .iter()
+        .map(|attribute| attribute.start)
+        // The documentation starts after the first `///`.
+        .chain(
+            documentation
+                .iter()
+                .map(|(start, _)| start.saturating_sub(3)),
+        )
+        .fold(location.start, u32::min);
+    SrcSpan::new(start, location.end)
+}
+
 fn target_function_implementation<'a>(
     target: Target,
     external_erlang: &'a Option<(EcoString, EcoString, SrcSpan)>,
@@ -1546,6 +1856,7 @@ fn analyse_type_alias(t: UntypedTypeAlias, environment: &mut Environment<'_>) ->
         parameters: args,
         type_ast: resolved_type,
         deprecation,
+        since,
         ..
     } = t;
 
@@ -1567,6 +1878,7 @@ fn analyse_type_alias(t: UntypedTypeAlias, environment: &mut Environment<'_>) ->
         type_ast: resolved_type,
         type_,
         deprecation,
+        since,
     })
 }
 
@@ -1649,7 +1961,10 @@ fn generalise_module_constant(
         value,
         type_,
         deprecation,
+        since,
         implementations,
+        embedded_file,
+        strict,
     } = constant;
     let type_ = type_.clone();
     let type_ = type_::generalise(type_);
@@ -1658,7 +1973,7 @@ fn generalise_module_constant(
         location,
         literal: *value.clone(),
         module: module_name.clone(),
-        implementations,
+        implementations: implementations.clone(),
     };
     environment.insert_variable(
         name.clone(),
@@ -1666,6 +1981,7 @@ fn generalise_module_constant(
         type_.clone(),
         publicity,
         deprecation.clone(),
+        since.clone(),
     );
 
     environment.insert_module_value(
@@ -1674,6 +1990,7 @@ fn generalise_module_constant(
             publicity,
             variant,
             deprecation: deprecation.clone(),
+            since: since.clone(),
             type_: type_.clone(),
         },
     );
@@ -1688,10 +2005,60 @@ fn generalise_module_constant(
         value,
         type_,
         deprecation,
+        since,
         implementations,
+        embedded_file,
+        strict,
     })
 }
 
+/// Whether a name can be used as an Erlang module or function without being
+/// quoted, so that an `any` external doesn't refer to something like a file
+/// path on Erlang.
+fn is_valid_erlang_external_name(name: &str) -> bool {
+    static NAME: OnceLock<Regex> = OnceLock::new();
+    NAME.get_or_init(|| Regex::new("^[a-z][a-zA-Z0-9_@]*$").expect("regex"))
+        .is_match(name)
+}
+
+fn is_valid_javascript_external_module(module: &str) -> bool {
+    static MODULE: OnceLock<Regex> = OnceLock::new();
+    MODULE
+        .get_or_init(|| Regex::new("^[@a-zA-Z0-9\\./:_-]+$").expect("regex"))
+        .is_match(module)
+}
+
+fn is_valid_javascript_external_function(function: &str) -> bool {
+    static FUNCTION: OnceLock<Regex> = OnceLock::new();
+    FUNCTION
+        .get_or_init(|| Regex::new("^[a-zA-Z_][a-zA-Z0-9_]*$").expect("regex"))
+        .is_match(function)
+}
+
+fn is_valid_nix_external_module(module: &str) -> bool {
+    static MODULE: OnceLock<Regex> = OnceLock::new();
+    // TODO(NIX): Consider allowing arbitrary paths, incl. <...> notation
+    // Currently, we force paths to be relative to something, that is,
+    // you can't import an external function from "word", but you can from
+    // "./word" or "../word". You can also import from "." or "..".
+    // We should expand this in the future.
+    MODULE
+        .get_or_init(|| Regex::new("^(?:\\.\\.?|\\.\\.?/[a-zA-Z0-9\\./:_-]*)$").expect("regex"))
+        .is_match(module)
+}
+
+/// Whether a module and function can be used as a Nix external.
+pub(crate) fn is_valid_nix_external(module: &str, function: &str) -> bool {
+    is_valid_nix_external_module(module) && is_valid_nix_external_function(function)
+}
+
+fn is_valid_nix_external_function(function: &str) -> bool {
+    static FUNCTION: OnceLock<Regex> = OnceLock::new();
+    FUNCTION
+        .get_or_init(|| Regex::new("^[a-zA-Z_][a-zA-Z0-9_'-]*$").expect("regex"))
+        .is_match(function)
+}
+
 fn generalise_function(
     function: TypedFunction,
     environment: &mut Environment<'_>,
@@ -1703,6 +2070,7 @@ fn generalise_function(
         name,
         publicity,
         deprecation,
+        since,
         arguments: args,
         body,
         return_annotation,
@@ -1711,7 +2079,9 @@ fn generalise_function(
         external_erlang,
         external_javascript,
         external_nix,
+        external_any,
         implementations,
+        inline,
     } = function;
 
     let (name_location, name) = name.expect("Function in a definition must be named");
@@ -1742,7 +2112,7 @@ fn generalise_function(
         module: module_name.clone(),
         arity: args.len(),
         location,
-        implementations,
+        implementations: implementations.clone(),
     };
     environment.insert_variable(
         name.clone(),
@@ -1750,12 +2120,14 @@ fn generalise_function(
         type_.clone(),
         publicity,
         deprecation.clone(),
+        since.clone(),
     );
     environment.insert_module_value(
         name.clone(),
         ValueConstructor {
             publicity,
             deprecation: deprecation.clone(),
+            since: since.clone(),
             type_,
             variant,
         },
@@ -1767,6 +2139,7 @@ fn generalise_function(
         name: Some((name_location, name)),
         publicity,
         deprecation,
+        since,
         arguments: args,
         end_position: end_location,
         return_annotation,
@@ -1775,7 +2148,9 @@ fn generalise_function(
         external_erlang,
         external_javascript,
         external_nix,
+        external_any,
         implementations,
+        inline,
     })
 }
 
@@ -1794,6 +2169,19 @@ fn assert_unique_name(
     }
 }
 
+/// The targets on which a constructor with the given name can't be told apart
+/// from the prelude constructor of the same name in the generated code.
+fn prelude_constructor_collisions(name: &str) -> Vec<Target> {
+    match name {
+        // On Erlang constructors are atoms, while on JavaScript and Nix the
+        // prelude's `Ok` and `Error` are imported into the module's scope.
+        "Ok" | "Error" => vec![Target::Erlang, Target::JavaScript, Target::Nix],
+        // On Erlang these are the atoms `true`, `false` and `nil`.
+        "True" | "False" | "Nil" => vec![Target::Erlang],
+        _ => vec![],
+    }
+}
+
 struct Accessors {
     shared_accessors: HashMap<EcoString, RecordAccessor>,
     variant_specific_accessors: Vec<HashMap<EcoString, RecordAccessor>>,
@@ -0,0 +1,747 @@
+//! Generation of the `decode_<type>` functions for custom types with the
+//! `@derive(decode)` attribute. The functions are built as untyped Gleam code
+//! calling the `gleam/dynamic` module, so they are type checked and compiled
+//! for every target like any other function of the module.
+//!
+//! The `from_attrs_<type>` and `to_attrs_<type>` functions of types with the
+//! `@derive(nix_attrs)` attribute, and the NixOS option declarations of types
+//! with `@derive(nixos_options)`, are instead only generated on Nix, so here
+//! they are just checked for problems.
+
+use std::{collections::HashSet, sync::Arc};
+
+use ecow::{eco_format, EcoString};
+use heck::ToSnakeCase;
+use vec1::vec1;
+
+use crate::{
+    ast::{
+        Arg, ArgNames, CallArg, Clause, Definition, Function, FunctionLiteralKind, Import, Pattern,
+        RecordConstructor, SrcSpan, Statement, TypeAst, TypeAstConstructor, TypeAstTuple,
+        TypedCustomType, TypedDefinition, UntypedCustomType, UntypedExpr, UntypedFunction,
+        UntypedImport, UntypedPattern,
+    },
+    type_::{
+        error::{
+            DeriveDecoderProblem, DeriveNixAttrsProblem, DeriveNixosOptionsProblem, Error,
+            VariableOrigin,
+        },
+        expression::Implementations,
+        prelude::is_prelude_module,
+        Deprecation, Type, PRELUDE_MODULE_NAME,
+    },
+};
+
+use super::Inferred;
+
+/// The `gleam/dynamic` module has `decode1` up to `decode9` to decode records
+/// with, so that's as many fields as a derived decoder can handle.
+const MAX_DECODED_FIELDS: usize = 9;
+
+/// The name of the variable the generated functions take the data to decode
+/// in.
+const DATA: &str = "data";
+
+pub fn decoder_name(type_name: &str) -> EcoString {
+    eco_format!("decode_{}", type_name.to_snake_case())
+}
+
+/// The names of the functions converting attribute sets to records of the
+/// type and back, in that order.
+pub fn nix_attrs_function_names(type_name: &str) -> [EcoString; 2] {
+    let name = type_name.to_snake_case();
+    [
+        eco_format!("from_attrs_{name}"),
+        eco_format!("to_attrs_{name}"),
+    ]
+}
+
+/// Checks that the attribute set conversion functions can be generated for a
+/// custom type with the `@derive(nix_attrs)` attribute at the given location.
+/// `module_names` are the names already taken at the top level of the
+/// module, which the functions' names mustn't clash with.
+///
+pub fn check_nix_attrs(
+    location: SrcSpan,
+    custom_type: &UntypedCustomType,
+    module_names: &HashSet<EcoString>,
+) -> Result<(), Error> {
+    let error = |reason| Error::CannotDeriveNixAttrs { location, reason };
+    if custom_type.publicity.is_private() || custom_type.opaque {
+        return Err(error(DeriveNixAttrsProblem::NotPublic));
+    }
+    if custom_type.constructors.is_empty() {
+        return Err(error(DeriveNixAttrsProblem::NoConstructors));
+    }
+
+    // With several constructors, the `type` attribute holds the name of the
+    // record's constructor, so it can't be the name of a field too.
+    let tagged = custom_type.constructors.len() > 1;
+    for constructor in &custom_type.constructors {
+        if let Some(argument) = constructor
+            .arguments
+            .iter()
+            .find(|argument| argument.label.is_none())
+        {
+            return Err(Error::CannotDeriveNixAttrs {
+                location: argument.location,
+                reason: DeriveNixAttrsProblem::UnlabelledField {
+                    constructor: constructor.name.clone(),
+                },
+            });
+        }
+        if let Some(argument) = constructor.arguments.iter().find(|argument| {
+            tagged
+                && argument
+                    .label
+                    .as_ref()
+                    .is_some_and(|(_, label)| label == "type")
+        }) {
+            return Err(Error::CannotDeriveNixAttrs {
+                location: argument.location,
+                reason: DeriveNixAttrsProblem::TypeField {
+                    constructor: constructor.name.clone(),
+                },
+            });
+        }
+    }
+
+    for name in nix_attrs_function_names(&custom_type.name) {
+        if module_names.contains(&name) {
+            return Err(error(DeriveNixAttrsProblem::NameClash { name }));
+        }
+    }
+    Ok(())
+}
+
+/// The names of the NixOS option declaration of the type and of the function
+/// reading its values from the NixOS configuration, in that order.
+pub fn nixos_options_function_names(type_name: &str) -> [EcoString; 2] {
+    let name = type_name.to_snake_case();
+    [
+        eco_format!("nixos_options_{name}"),
+        eco_format!("from_nixos_options_{name}"),
+    ]
+}
+
+/// Checks that NixOS options can be derived for a custom type with the
+/// `@derive(nixos_options)` attribute at the given location. The types of
+/// its fields can only be checked once they are known, with
+/// [`nixos_options_type_errors`].
+///
+pub fn check_nixos_options(
+    location: SrcSpan,
+    custom_type: &UntypedCustomType,
+    module_names: &HashSet<EcoString>,
+) -> Result<(), Error> {
+    let error = |reason| Error::CannotDeriveNixosOptions { location, reason };
+    if custom_type.publicity.is_private() || custom_type.opaque {
+        return Err(error(DeriveNixosOptionsProblem::NotPublic));
+    }
+    if !custom_type.parameters.is_empty() {
+        return Err(error(DeriveNixosOptionsProblem::TypeParameters));
+    }
+
+    match custom_type.constructors.as_slice() {
+        [] => return Err(error(DeriveNixosOptionsProblem::UnsupportedShape)),
+        [constructor] => {
+            if let Some(argument) = constructor
+                .arguments
+                .iter()
+                .find(|argument| argument.label.is_none())
+            {
+                return Err(Error::CannotDeriveNixosOptions {
+                    location: argument.location,
+                    reason: DeriveNixosOptionsProblem::UnlabelledField {
+                        constructor: constructor.name.clone(),
+                    },
+                });
+            }
+        }
+        constructors => {
+            if constructors
+                .iter()
+                .any(|constructor| !constructor.arguments.is_empty())
+            {
+                return Err(error(DeriveNixosOptionsProblem::UnsupportedShape));
+            }
+        }
+    }
+
+    for name in nixos_options_function_names(&custom_type.name) {
+        if module_names.contains(&name) {
+            return Err(error(DeriveNixosOptionsProblem::NameClash { name }));
+        }
+    }
+    Ok(())
+}
+
+/// The NixOS option type a field of a type with `@derive(nixos_options)` is
+/// declared with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NixosOptionType {
+    Int,
+    Float,
+    String,
+    Bool,
+    /// Any value, given to Gleam as it is.
+    Dynamic,
+    List(Box<NixosOptionType>),
+    /// An `Option` of the `gleam/option` module, which is `null` when unset.
+    Option(Box<NixosOptionType>),
+    /// Another type of the module with `@derive(nixos_options)`.
+    Derived(EcoString),
+}
+
+impl NixosOptionType {
+    /// The option type of values of the given type, if there is one.
+    /// `derived_types` are the types of the module, named `module`, which
+    /// have `@derive(nixos_options)`.
+    pub fn from_type(
+        type_: &Arc<Type>,
+        module: &str,
+        derived_types: &HashSet<EcoString>,
+    ) -> Option<Self> {
+        let (type_module, name, arguments) = type_.named_type_information()?;
+        let argument = |arguments: &[Arc<Type>]| match arguments {
+            [argument] => Self::from_type(argument, module, derived_types).map(Box::new),
+            _ => None,
+        };
+        match (type_module.as_str(), name.as_str()) {
+            (type_module, "Int") if is_prelude_module(type_module) => Some(Self::Int),
+            (type_module, "Float") if is_prelude_module(type_module) => Some(Self::Float),
+            (type_module, "String") if is_prelude_module(type_module) => Some(Self::String),
+            (type_module, "Bool") if is_prelude_module(type_module) => Some(Self::Bool),
+            (type_module, "List") if is_prelude_module(type_module) => {
+                argument(&arguments).map(Self::List)
+            }
+            ("gleam/option", "Option") => argument(&arguments).map(Self::Option),
+            ("gleam/dynamic", "Dynamic") => Some(Self::Dynamic),
+            (type_module, _) if type_module == module && derived_types.contains(&name) => {
+                Some(Self::Derived(name))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns an error for each field of a type with `@derive(nixos_options)`
+/// in the given definitions of the module whose type has no matching NixOS
+/// option type.
+pub fn nixos_options_type_errors(definitions: &[TypedDefinition], module: &str) -> Vec<Error> {
+    let derived_types: HashSet<_> = nixos_options_types(definitions)
+        .map(|custom_type| custom_type.name.clone())
+        .collect();
+
+    nixos_options_types(definitions)
+        .flat_map(|custom_type| &custom_type.constructors)
+        .flat_map(|constructor| &constructor.arguments)
+        .filter(|argument| {
+            NixosOptionType::from_type(&argument.type_, module, &derived_types).is_none()
+        })
+        .map(|argument| Error::CannotDeriveNixosOptions {
+            location: argument.ast.location(),
+            reason: DeriveNixosOptionsProblem::UnsupportedType,
+        })
+        .collect()
+}
+
+/// The custom types with `@derive(nixos_options)` among the definitions.
+pub fn nixos_options_types(
+    definitions: &[TypedDefinition],
+) -> impl Iterator<Item = &TypedCustomType> {
+    definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::CustomType(custom_type) if custom_type.derive_nixos_options.is_some() => {
+                Some(custom_type)
+            }
+            _ => None,
+        })
+}
+
+/// How a module refers to the modules the derived decoders are built out of.
+///
+#[derive(Debug, Default)]
+pub struct DecoderImports {
+    /// The name the `gleam/dynamic` module is imported as, if it is.
+    pub dynamic_module: Option<EcoString>,
+    /// The name the decoders refer to the prelude by, so that its types and
+    /// constructors can't be confused with ones of the module with the same
+    /// names.
+    pub prelude_module: EcoString,
+    /// Whether the prelude isn't imported yet, so an import has to be added
+    /// for the decoders.
+    pub prelude_import_missing: bool,
+    /// The module imported with the name the prelude would be imported as,
+    /// if there is one.
+    pub prelude_alias_taken_by: Option<EcoString>,
+    /// The name the `gleam/option` module is imported as, if it is.
+    pub option_module: Option<EcoString>,
+    /// The name its `Option` type is imported as unqualified, if it is.
+    pub option_type: Option<EcoString>,
+}
+
+impl DecoderImports {
+    pub fn new(imports: &[UntypedImport]) -> Self {
+        let mut decoder_imports = Self {
+            prelude_module: PRELUDE_MODULE_NAME.into(),
+            prelude_import_missing: true,
+            ..Default::default()
+        };
+        for import in imports {
+            let used_name = import.used_name();
+            match import.module.as_str() {
+                "gleam/dynamic" => decoder_imports.dynamic_module = used_name,
+                "gleam/option" => {
+                    decoder_imports.option_type = import
+                        .unqualified_types
+                        .iter()
+                        .find(|type_| type_.name == "Option")
+                        .map(|type_| type_.used_name().clone());
+                    decoder_imports.option_module = used_name;
+                }
+                PRELUDE_MODULE_NAME => {
+                    if let Some(used_name) = used_name {
+                        decoder_imports.prelude_module = used_name;
+                        decoder_imports.prelude_import_missing = false;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if decoder_imports.prelude_import_missing {
+            decoder_imports.prelude_alias_taken_by = imports
+                .iter()
+                .find(|import| import.used_name().as_deref() == Some(PRELUDE_MODULE_NAME))
+                .map(|import| import.module.clone());
+        }
+        decoder_imports
+    }
+
+    /// The import of the prelude to add to the module for the decoders, if
+    /// it doesn't import it already.
+    pub fn prelude_import(&self) -> Option<UntypedImport> {
+        self.prelude_import_missing.then(|| Import {
+            documentation: None,
+            location: SrcSpan::default(),
+            module: PRELUDE_MODULE_NAME.into(),
+            as_name: None,
+            unqualified_values: vec![],
+            unqualified_types: vec![],
+            package: (),
+        })
+    }
+}
+
+/// Generates the decoder function for a custom type with the
+/// `@derive(decode)` attribute at the given location.
+///
+/// A record is decoded from data with a field for each of its labelled
+/// fields, and a constructor without fields is decoded from a string holding
+/// its name. If the type has more than one constructor each of them is tried
+/// in turn.
+///
+pub fn decoder_function(
+    location: SrcSpan,
+    custom_type: &UntypedCustomType,
+    imports: &DecoderImports,
+    derived_types: &HashSet<EcoString>,
+) -> Result<UntypedFunction, Error> {
+    let error = |reason| Error::CannotDeriveDecoder { location, reason };
+    let dynamic_module = imports
+        .dynamic_module
+        .as_ref()
+        .ok_or_else(|| error(DeriveDecoderProblem::MissingDynamicImport))?;
+    if let Some(module) = &imports.prelude_alias_taken_by {
+        return Err(error(DeriveDecoderProblem::PreludeAliasTaken {
+            module: module.clone(),
+        }));
+    }
+    if !custom_type.parameters.is_empty() {
+        return Err(error(DeriveDecoderProblem::TypeParameters));
+    }
+
+    let builder = DecoderBuilder {
+        location,
+        dynamic_module,
+        prelude_module: &imports.prelude_module,
+        option_module: imports.option_module.as_ref(),
+        option_type: imports.option_type.as_ref(),
+        derived_types,
+    };
+
+    let mut decoders = Vec::with_capacity(custom_type.constructors.len());
+    for constructor in &custom_type.constructors {
+        decoders.push(builder.constructor_decoder(constructor)?);
+    }
+    let decoder = match decoders.len() {
+        0 => return Err(error(DeriveDecoderProblem::NoConstructors)),
+        1 => decoders.pop().expect("one decoder"),
+        _ => builder.call(
+            builder.dynamic("any"),
+            vec![UntypedExpr::List {
+                location,
+                elements: decoders,
+                tail: None,
+            }],
+        ),
+    };
+    let body = builder.call(decoder, vec![builder.var(DATA)]);
+
+    Ok(Function {
+        location,
+        end_position: location.end,
+        name: Some((location, decoder_name(&custom_type.name))),
+        arguments: vec![Arg {
+            names: ArgNames::Named {
+                name: DATA.into(),
+                location,
+            },
+            location,
+            annotation: Some(builder.dynamic_type("Dynamic", vec![])),
+            type_: (),
+        }],
+        body: vec1![Statement::Expression(body)],
+        publicity: custom_type.publicity,
+        deprecation: Deprecation::NotDeprecated,
+        since: custom_type.since.clone(),
+        return_annotation: Some(builder.prelude_type(
+            "Result",
+            vec![
+                builder.type_(&custom_type.name, vec![]),
+                builder.prelude_type("List", vec![builder.dynamic_type("DecodeError", vec![])]),
+            ],
+        )),
+        return_type: (),
+        documentation: None,
+        external_erlang: None,
+        external_javascript: None,
+        external_nix: None,
+        external_any: None,
+        implementations: Implementations::supporting_all(),
+        inline: false,
+    })
+}
+
+struct DecoderBuilder<'a> {
+    location: SrcSpan,
+    /// The name the `gleam/dynamic` module is imported as.
+    dynamic_module: &'a EcoString,
+    /// The name the prelude is imported as.
+    prelude_module: &'a EcoString,
+    /// The name the `gleam/option` module is imported as, if it is.
+    option_module: Option<&'a EcoString>,
+    /// The name its `Option` type is imported as unqualified, if it is.
+    option_type: Option<&'a EcoString>,
+    /// The custom types of the module that also have a derived decoder.
+    derived_types: &'a HashSet<EcoString>,
+}
+
+impl DecoderBuilder<'_> {
+    fn constructor_decoder(
+        &self,
+        constructor: &RecordConstructor<()>,
+    ) -> Result<UntypedExpr, Error> {
+        if constructor.arguments.is_empty() {
+            return Ok(self.name_decoder(&constructor.name));
+        }
+        if constructor.arguments.len() > MAX_DECODED_FIELDS {
+            return Err(Error::CannotDeriveDecoder {
+                location: constructor.location,
+                reason: DeriveDecoderProblem::TooManyFields {
+                    constructor: constructor.name.clone(),
+                    limit: MAX_DECODED_FIELDS,
+                },
+            });
+        }
+
+        // dynamic.decodeN(Constructor, dynamic.field("label", decoder), ...)
+        let mut arguments = vec![self.var(&constructor.name)];
+        for argument in &constructor.arguments {
+            let Some((_, label)) = &argument.label else {
+                return Err(Error::CannotDeriveDecoder {
+                    location: argument.location,
+                    reason: DeriveDecoderProblem::UnlabelledField {
+                        constructor: constructor.name.clone(),
+                    },
+                });
+            };
+            let field = self.call(
+                self.dynamic("field"),
+                vec![self.string(label), self.field_decoder(&argument.ast)?],
+            );
+            arguments.push(field);
+        }
+        let decode = eco_format!("decode{}", constructor.arguments.len());
+        Ok(self.call(self.dynamic(&decode), arguments))
+    }
+
+    /// A decoder for a constructor without fields, which expects a string
+    /// holding the name of the constructor:
+    ///
+    /// ```gleam
+    /// fn(data) {
+    ///   case dynamic.string(data) {
+    ///     Ok("Name") -> Ok(Name)
+    ///     Ok(found) ->
+    ///       Error([dynamic.DecodeError(expected: "Name", found: found, path: [])])
+    ///     Error(errors) -> Error(errors)
+    ///   }
+    /// }
+    /// ```
+    ///
+    fn name_decoder(&self, name: &EcoString) -> UntypedExpr {
+        let location = self.location;
+        let decode_error = self.call_labelled(
+            self.dynamic("DecodeError"),
+            vec![
+                (Some("expected"), self.string(name)),
+                (Some("found"), self.var("found")),
+                (
+                    Some("path"),
+                    UntypedExpr::List {
+                        location,
+                        elements: vec![],
+                        tail: None,
+                    },
+                ),
+            ],
+        );
+        let errors = UntypedExpr::List {
+            location,
+            elements: vec![decode_error],
+            tail: None,
+        };
+
+        let clauses = vec![
+            self.clause(
+                self.prelude_pattern(
+                    "Ok",
+                    Pattern::String {
+                        location,
+                        value: name.clone(),
+                    },
+                ),
+                self.call(self.prelude("Ok"), vec![self.var(name)]),
+            ),
+            self.clause(
+                self.prelude_pattern("Ok", self.variable_pattern("found")),
+                self.call(self.prelude("Error"), vec![errors]),
+            ),
+            self.clause(
+                self.prelude_pattern("Error", self.variable_pattern("errors")),
+                self.call(self.prelude("Error"), vec![self.var("errors")]),
+            ),
+        ];
+        let case = UntypedExpr::Case {
+            location,
+            subjects: vec![self.call(self.dynamic("string"), vec![self.var(DATA)])],
+            clauses,
+        };
+
+        UntypedExpr::Fn {
+            location,
+            kind: FunctionLiteralKind::Anonymous { head: location },
+            end_of_head_byte_index: location.end,
+            arguments: vec![Arg {
+                names: ArgNames::Named {
+                    name: DATA.into(),
+                    location,
+                },
+                location,
+                annotation: None,
+                type_: (),
+            }],
+            body: vec1![Statement::Expression(case)],
+            return_annotation: None,
+        }
+    }
+
+    /// The decoder for a field of the given type.
+    fn field_decoder(&self, type_: &TypeAst) -> Result<UntypedExpr, Error> {
+        let unsupported = || Error::CannotDeriveDecoder {
+            location: type_.location(),
+            reason: DeriveDecoderProblem::UnsupportedType,
+        };
+
+        match type_ {
+            TypeAst::Constructor(TypeAstConstructor {
+                module,
+                name,
+                arguments,
+                ..
+            }) => {
+                let module = module.as_ref().map(|(module, _)| module);
+                // Built-in types may also be written qualified with the name
+                // the prelude is imported as.
+                let builtin = module.is_none_or(|module| module == self.prelude_module);
+                match (module, name.as_str(), arguments.as_slice()) {
+                    (_, "Int", []) if builtin => Ok(self.dynamic("int")),
+                    (_, "Float", []) if builtin => Ok(self.dynamic("float")),
+                    (_, "String", []) if builtin => Ok(self.dynamic("string")),
+                    (_, "Bool", []) if builtin => Ok(self.dynamic("bool")),
+                    (_, "BitArray", []) if builtin => Ok(self.dynamic("bit_array")),
+                    (_, "List", [element]) if builtin => {
+                        Ok(self.call(self.dynamic("list"), vec![self.field_decoder(element)?]))
+                    }
+                    (Some(module), "Option", [inner]) if Some(module) == self.option_module => {
+                        Ok(self.call(self.dynamic("optional"), vec![self.field_decoder(inner)?]))
+                    }
+                    (None, name, [inner]) if Some(name) == self.option_type.map(|n| n.as_str()) => {
+                        Ok(self.call(self.dynamic("optional"), vec![self.field_decoder(inner)?]))
+                    }
+                    (None, "Dynamic", []) => Ok(self.dynamic("dynamic")),
+                    (Some(module), "Dynamic", []) if module == self.dynamic_module => {
+                        Ok(self.dynamic("dynamic"))
+                    }
+                    (None, name, []) if self.derived_types.contains(name) => {
+                        Ok(self.var(&decoder_name(name)))
+                    }
+                    _ => Err(unsupported()),
+                }
+            }
+
+            TypeAst::Tuple(TypeAstTuple { elems, .. }) if (2..=6).contains(&elems.len()) => {
+                let decoders = elems
+                    .iter()
+                    .map(|element| self.field_decoder(element))
+                    .collect::<Result<_, _>>()?;
+                let tuple = eco_format!("tuple{}", elems.len());
+                Ok(self.call(self.dynamic(&tuple), decoders))
+            }
+
+            TypeAst::Tuple(_) | TypeAst::Fn(_) | TypeAst::Var(_) | TypeAst::Hole(_) => {
+                Err(unsupported())
+            }
+        }
+    }
+
+    fn var(&self, name: &str) -> UntypedExpr {
+        UntypedExpr::Var {
+            location: self.location,
+            name: name.into(),
+        }
+    }
+
+    fn string(&self, value: &EcoString) -> UntypedExpr {
+        UntypedExpr::String {
+            location: self.location,
+            value: value.clone(),
+        }
+    }
+
+    /// A value of the `gleam/dynamic` module: `dynamic.name`.
+    fn dynamic(&self, name: &str) -> UntypedExpr {
+        UntypedExpr::FieldAccess {
+            location: self.location,
+            label_location: self.location,
+            label: name.into(),
+            container: Box::new(self.var(self.dynamic_module)),
+        }
+    }
+
+    /// A value of the prelude: `gleam.Name`.
+    fn prelude(&self, name: &str) -> UntypedExpr {
+        UntypedExpr::FieldAccess {
+            location: self.location,
+            label_location: self.location,
+            label: name.into(),
+            container: Box::new(self.var(self.prelude_module)),
+        }
+    }
+
+    fn call(&self, fun: UntypedExpr, arguments: Vec<UntypedExpr>) -> UntypedExpr {
+        self.call_labelled(
+            fun,
+            arguments.into_iter().map(|value| (None, value)).collect(),
+        )
+    }
+
+    fn call_labelled(
+        &self,
+        fun: UntypedExpr,
+        arguments: Vec<(Option<&str>, UntypedExpr)>,
+    ) -> UntypedExpr {
+        UntypedExpr::Call {
+            location: self.location,
+            fun: Box::new(fun),
+            arguments: arguments
+                .into_iter()
+                .map(|(label, value)| CallArg {
+                    label: label.map(EcoString::from),
+                    location: self.location,
+                    value,
+                    implicit: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn clause(&self, pattern: UntypedPattern, then: UntypedExpr) -> Clause<UntypedExpr, (), ()> {
+        Clause {
+            location: self.location,
+            pattern: vec![pattern],
+            alternative_patterns: vec![],
+            guard: None,
+            then,
+        }
+    }
+
+    /// A pattern matching a constructor of the prelude: `gleam.Name(argument)`.
+    fn prelude_pattern(&self, name: &str, argument: UntypedPattern) -> UntypedPattern {
+        Pattern::Constructor {
+            location: self.location,
+            name: name.into(),
+            arguments: vec![CallArg {
+                label: None,
+                location: self.location,
+                value: argument,
+                implicit: None,
+            }],
+            module: Some((self.prelude_module.clone(), self.location)),
+            constructor: Inferred::Unknown,
+            spread: None,
+            type_: (),
+        }
+    }
+
+    fn variable_pattern(&self, name: &str) -> UntypedPattern {
+        Pattern::Variable {
+            location: self.location,
+            name: name.into(),
+            type_: (),
+            origin: VariableOrigin::Generated,
+        }
+    }
+
+    fn type_(&self, name: &str, arguments: Vec<TypeAst>) -> TypeAst {
+        TypeAst::Constructor(TypeAstConstructor {
+            location: self.location,
+            module: None,
+            name: name.into(),
+            arguments,
+        })
+    }
+
+    /// A type of the prelude: `gleam.Name`.
+    fn prelude_type(&self, name: &str, arguments: Vec<TypeAst>) -> TypeAst {
+        TypeAst::Constructor(TypeAstConstructor {
+            location: self.location,
+            module: Some((self.prelude_module.clone(), self.location)),
+            name: name.into(),
+            arguments,
+        })
+    }
+
+    /// A type of the `gleam/dynamic` module: `dynamic.Name`.
+    fn dynamic_type(&self, name: &str, arguments: Vec<TypeAst>) -> TypeAst {
+        TypeAst::Constructor(TypeAstConstructor {
+            location: self.location,
+            module: Some((self.dynamic_module.clone(), self.location)),
+            name: name.into(),
+            arguments,
+        })
+    }
+}
@@ -2,10 +2,10 @@ use ecow::EcoString;
 
 use crate::{
     ast::{SrcSpan, UnqualifiedImport, UntypedImport},
-    build::Origin,
+    build::{Origin, Target},
     type_::{
-        EntityKind, Environment, Error, ModuleInterface, Problems, UnusedModuleAlias,
-        ValueConstructorVariant,
+        Deprecation, EntityKind, Environment, Error, ModuleInterface, Problems, UnusedModuleAlias,
+        ValueConstructorVariant, Warning,
     },
 };
 
@@ -63,6 +63,14 @@ impl<'context, 'problems> Importer<'context, 'problems> {
             return;
         }
 
+        if let Deprecation::Deprecated { message } = &module_info.deprecation {
+            self.problems.warning(Warning::DeprecatedModule {
+                location,
+                name: name.clone(),
+                message: message.clone(),
+            });
+        }
+
         if let Err(e) = self.register_module(import, module_info) {
             self.problems.error(e);
             return;
@@ -133,6 +141,17 @@ impl<'context, 'problems> Importer<'context, 'problems> {
                     self.problems.error(Error::UnsupportedExpressionTarget {
                         target: self.environment.target,
                         location,
+                        module: module.name.clone(),
+                        name: import_name.clone(),
+                        package: Some(module.package.clone())
+                            .filter(|package| *package != self.environment.current_package),
+                        supported_targets: implementations.supported_targets(),
+                        nix_blocker_calls: match self.environment.target {
+                            Target::Nix => self
+                                .environment
+                                .nix_blocker_calls(&module.name, &implementations),
+                            Target::Erlang | Target::JavaScript => vec![],
+                        },
                     })
                 }
 
@@ -142,6 +161,7 @@ impl<'context, 'problems> Importer<'context, 'problems> {
                     value.type_.clone(),
                     value.publicity,
                     value.deprecation.clone(),
+                    value.since.clone(),
                 );
                 &value.variant
             }
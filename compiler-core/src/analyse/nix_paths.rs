@@ -0,0 +1,105 @@
+//! Checks on the string literals passed as paths to the functions of the
+//! `glistix/nix` module. Nix only evaluates those paths once the generated
+//! code runs, so mistakes in them would otherwise go unnoticed until then.
+
+use std::sync::Arc;
+
+use crate::{
+    ast::{
+        visit::{self, Visit},
+        Definition, SrcSpan, TypedDefinition, TypedExpr,
+    },
+    type_::{
+        error::InvalidNixPathReason, ModuleValueConstructor, Type, TypedCallArg, ValueConstructor,
+        ValueConstructorVariant, Warning, NIX_MODULE_NAME,
+    },
+};
+
+/// The functions of the `glistix/nix` module taking a path relative to
+/// another one as a string, along with the position of that argument.
+const RELATIVE_PATH_ARGUMENTS: [(&str, usize); 1] = [("path_join", 1)];
+
+/// Returns a warning for each string literal passed as a relative path to a
+/// function of the `glistix/nix` module which isn't a valid one.
+///
+pub fn invalid_path_warnings(definitions: &[TypedDefinition]) -> Vec<Warning> {
+    let mut checker = InvalidPathChecker { warnings: vec![] };
+    for definition in definitions {
+        if let Definition::Function(function) = definition {
+            checker.visit_typed_function(function);
+        }
+    }
+    checker.warnings
+}
+
+struct InvalidPathChecker {
+    warnings: Vec<Warning>,
+}
+
+impl<'ast> Visit<'ast> for InvalidPathChecker {
+    fn visit_typed_expr_call(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        fun: &'ast TypedExpr,
+        args: &'ast [TypedCallArg],
+    ) {
+        let path_argument = nix_function_name(fun).and_then(|function| {
+            RELATIVE_PATH_ARGUMENTS
+                .iter()
+                .find(|(name, _)| *name == function)
+                .and_then(|(_, index)| args.get(*index))
+        });
+
+        if let Some(TypedCallArg {
+            value: TypedExpr::String {
+                location, value, ..
+            },
+            ..
+        }) = path_argument
+        {
+            if let Some(reason) = invalid_relative_path_reason(value.as_str()) {
+                self.warnings.push(Warning::InvalidNixPath {
+                    location: *location,
+                    path: value.clone(),
+                    reason,
+                });
+            }
+        }
+
+        visit::visit_typed_expr_call(self, location, type_, fun, args);
+    }
+}
+
+/// The name of the `glistix/nix` function being called, if any.
+fn nix_function_name(fun: &TypedExpr) -> Option<&str> {
+    match fun {
+        TypedExpr::ModuleSelect {
+            constructor: ModuleValueConstructor::Fn { module, name, .. },
+            ..
+        }
+        | TypedExpr::Var {
+            constructor:
+                ValueConstructor {
+                    variant: ValueConstructorVariant::ModuleFn { module, name, .. },
+                    ..
+                },
+            ..
+        } if module == NIX_MODULE_NAME => Some(name.as_str()),
+        _ => None,
+    }
+}
+
+fn invalid_relative_path_reason(path: &str) -> Option<InvalidNixPathReason> {
+    if path.is_empty() {
+        Some(InvalidNixPathReason::Empty)
+    } else if path.starts_with('/') {
+        Some(InvalidNixPathReason::Absolute)
+    } else if path.starts_with('~') {
+        Some(InvalidNixPathReason::HomeRelative)
+    } else if path.contains(char::is_whitespace) {
+        Some(InvalidNixPathReason::Whitespace)
+    } else {
+        None
+    }
+}
@@ -50,6 +50,8 @@ pub struct Module<Info, Statements> {
     pub type_info: Info,
     pub definitions: Vec<Statements>,
     pub names: Names,
+    /// Set by a `@deprecated_module` attribute at the top of the module.
+    pub deprecation: Deprecation,
 }
 
 impl TypedModule {
@@ -647,13 +649,22 @@ pub struct Function<T, Expr> {
     pub body: Vec1<Statement<T, Expr>>,
     pub publicity: Publicity,
     pub deprecation: Deprecation,
+    pub since: Option<EcoString>,
     pub return_annotation: Option<TypeAst>,
     pub return_type: T,
     pub documentation: Option<(u32, EcoString)>,
     pub external_erlang: Option<(EcoString, EcoString, SrcSpan)>,
     pub external_javascript: Option<(EcoString, EcoString, SrcSpan)>,
     pub external_nix: Option<(EcoString, EcoString, SrcSpan)>,
+    /// Given with `@external(any, ...)`, this is used on every target the
+    /// function has no external implementation specific to. Once analysed,
+    /// those targets' externals are set to it.
+    pub external_any: Option<(EcoString, EcoString, SrcSpan)>,
     pub implementations: Implementations,
+    /// Whether the function has the `@inline` attribute. On the Nix target,
+    /// calls to such a function from its own module are replaced with its
+    /// body, saving the cost of calling it.
+    pub inline: bool,
 }
 
 pub type TypedFunction = Function<Arc<Type>, TypedExpr>;
@@ -726,7 +737,32 @@ pub struct ModuleConstant<T, ConstantRecordTag> {
     pub value: Box<Constant<T, ConstantRecordTag>>,
     pub type_: T,
     pub deprecation: Deprecation,
+    /// The version of the package the constant was added in, given with the
+    /// `@since` attribute.
+    pub since: Option<EcoString>,
     pub implementations: Implementations,
+    /// The file given with the `@embed_file` attribute, if any. The value of
+    /// such a constant is the contents of the file, read when the module is
+    /// loaded.
+    pub embedded_file: Option<EmbeddedFile>,
+    /// Whether the constant has the `@strict` attribute. On the Nix target,
+    /// such a constant is evaluated when its module is imported instead of
+    /// when it is first used.
+    pub strict: bool,
+}
+
+/// A file whose contents are embedded into a module constant at compile time.
+///
+/// ```gleam
+/// @embed_file("priv/data.json")
+/// pub const data: String
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedFile {
+    /// The path of the file, relative to the root of the package.
+    pub path: EcoString,
+    /// The location of the `@embed_file` attribute.
+    pub location: SrcSpan,
 }
 
 pub type UntypedCustomType = CustomType<()>;
@@ -757,12 +793,25 @@ pub struct CustomType<T> {
     pub constructors: Vec<RecordConstructor<T>>,
     pub documentation: Option<(u32, EcoString)>,
     pub deprecation: Deprecation,
+    /// The version of the package the type was added in, given with the
+    /// `@since` attribute. Its constructors share it.
+    pub since: Option<EcoString>,
     pub opaque: bool,
     /// The names of the type parameters.
     pub parameters: Vec<SpannedString>,
     /// Once type checked this field will contain the type information for the
     /// type parameters.
     pub typed_parameters: Vec<T>,
+    /// The location of the `@derive(decode)` attribute, if the type has one.
+    pub derive_decode: Option<SrcSpan>,
+    /// The location of the `@derive(nix_attrs)` attribute, if the type has
+    /// one. Its Nix module then exports functions converting its records to
+    /// and from plain attribute sets.
+    pub derive_nix_attrs: Option<SrcSpan>,
+    /// The location of the `@derive(nixos_options)` attribute, if the type
+    /// has one. Its Nix module then exports a NixOS option declaration for
+    /// the type, and a function reading its values from the configuration.
+    pub derive_nixos_options: Option<SrcSpan>,
 }
 
 impl<T> CustomType<T> {
@@ -795,6 +844,7 @@ pub struct TypeAlias<T> {
     pub publicity: Publicity,
     pub documentation: Option<(u32, EcoString)>,
     pub deprecation: Deprecation,
+    pub since: Option<EcoString>,
 }
 
 pub type TypedDefinition = Definition<Arc<Type>, TypedExpr, EcoString, EcoString>;
@@ -832,7 +882,21 @@ impl TypedDefinition {
                     return None;
                 }
 
-                if let Some(found) = function.body.iter().find_map(|s| s.find_node(byte_index)) {
+                // A function implemented only by externals has a placeholder
+                // body spanning its head, which is not searched so that the
+                // head itself is found instead.
+                let is_placeholder_body = matches!(
+                    function.body.first(),
+                    Statement::Expression(TypedExpr::Panic { location, message: None, .. })
+                        if location.start == function.location.start
+                );
+                let body = if is_placeholder_body {
+                    &[]
+                } else {
+                    function.body.as_slice()
+                };
+
+                if let Some(found) = body.iter().find_map(|s| s.find_node(byte_index)) {
                     return Some(found);
                 }
 
@@ -844,8 +908,7 @@ impl TypedDefinition {
                     return Some(found_arg);
                 };
 
-                if let Some(found_statement) = function
-                    .body
+                if let Some(found_statement) = body
                     .iter()
                     .find(|statement| statement.location().contains(byte_index))
                 {
@@ -1666,7 +1729,18 @@ impl TypedClauseGuard {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Default, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Default,
+    Clone,
+    Copy,
+    serde::Serialize,
+    serde::Deserialize,
+)]
 pub struct SrcSpan {
     pub start: u32,
     pub end: u32,
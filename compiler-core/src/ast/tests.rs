@@ -112,6 +112,7 @@ fn compile_expression(src: &str) -> TypedStatement {
         type_::fn_(vec![type_::string(), type_::int()], cat_type.clone()),
         Publicity::Public,
         Deprecation::NotDeprecated,
+        None,
     );
 
     let accessors = [
@@ -232,6 +233,7 @@ wibble}"#,
         location: SrcSpan { start: 16, end: 22 },
         constructor: ValueConstructor {
             deprecation: Deprecation::NotDeprecated,
+            since: None,
             publicity: Publicity::Private,
             variant: ValueConstructorVariant::LocalVariable {
                 location: SrcSpan { start: 5, end: 11 },
@@ -538,6 +540,7 @@ fn find_node_bool() {
         location: SrcSpan { start: 1, end: 5 },
         constructor: ValueConstructor {
             deprecation: Deprecation::NotDeprecated,
+            since: None,
             publicity: Publicity::Public,
             variant: ValueConstructorVariant::Record {
                 documentation: None,
@@ -3,6 +3,8 @@ use std::sync::OnceLock;
 use type_::{FieldMap, TypedCallArg};
 
 use super::*;
+use crate::build::LabelledArgument;
+use crate::exhaustiveness::CompiledCase;
 use crate::type_::{bool, HasType, Type, ValueConstructorVariant};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -84,6 +86,9 @@ pub enum TypedExpr {
         type_: Arc<Type>,
         subjects: Vec<Self>,
         clauses: Vec<Clause<Self, Arc<Type>, EcoString>>,
+        /// The decision tree built when checking the exhaustiveness of the
+        /// clauses.
+        compiled_case: CompiledCase,
     },
 
     RecordAccess {
@@ -282,7 +287,12 @@ impl TypedExpr {
 
             Self::Call { fun, args, .. } => args
                 .iter()
-                .find_map(|arg| arg.find_node(byte_index))
+                .find_map(|arg| match arg.find_node(byte_index)? {
+                    Located::Label(location, type_) => {
+                        Some(fun.locate_call_label(arg.label.as_ref(), location, type_))
+                    }
+                    located => Some(located),
+                })
                 .or_else(|| fun.find_node(byte_index))
                 .or_else(|| self.self_if_contains_location(byte_index)),
 
@@ -722,6 +732,96 @@ impl TypedExpr {
             },
         }
     }
+
+    /// Locates the label of an argument this function is called with, along
+    /// with the function or record constructor it is given to, when it is
+    /// known.
+    ///
+    fn locate_call_label<'a>(
+        &'a self,
+        label: Option<&EcoString>,
+        location: SrcSpan,
+        type_: Arc<Type>,
+    ) -> Located<'a> {
+        let located: fn(LabelledArgument<'a>) -> Located<'a>;
+        let (module, name, definition, documentation, field_map) = match self {
+            TypedExpr::Var { constructor, .. } => match &constructor.variant {
+                ValueConstructorVariant::ModuleFn {
+                    module,
+                    name,
+                    location,
+                    documentation,
+                    field_map,
+                    ..
+                } => {
+                    located = Located::FunctionLabel;
+                    (module, name, *location, documentation, field_map)
+                }
+                ValueConstructorVariant::Record {
+                    module,
+                    name,
+                    location,
+                    documentation,
+                    field_map,
+                    ..
+                } => {
+                    located = Located::RecordLabel;
+                    (module, name, *location, documentation, field_map)
+                }
+                ValueConstructorVariant::LocalVariable { .. }
+                | ValueConstructorVariant::ModuleConstant { .. }
+                | ValueConstructorVariant::LocalConstant { .. } => {
+                    return Located::Label(location, type_);
+                }
+            },
+            TypedExpr::ModuleSelect {
+                module_name,
+                constructor,
+                ..
+            } => match constructor {
+                ModuleValueConstructor::Fn {
+                    module,
+                    name,
+                    location,
+                    documentation,
+                    field_map,
+                    ..
+                } => {
+                    located = Located::FunctionLabel;
+                    (module, name, *location, documentation, field_map)
+                }
+                ModuleValueConstructor::Record {
+                    name,
+                    location,
+                    documentation,
+                    field_map,
+                    ..
+                } => {
+                    located = Located::RecordLabel;
+                    (module_name, name, *location, documentation, field_map)
+                }
+                ModuleValueConstructor::Constant { .. } => return Located::Label(location, type_),
+            },
+            _ => return Located::Label(location, type_),
+        };
+
+        let index = field_map
+            .as_ref()
+            .zip(label)
+            .and_then(|(field_map, label)| field_map.fields.get(label));
+        match index {
+            Some(index) => located(LabelledArgument {
+                location,
+                type_,
+                module,
+                name,
+                definition,
+                documentation: documentation.as_deref(),
+                index: *index,
+            }),
+            None => Located::Label(location, type_),
+        }
+    }
 }
 
 impl<'a> From<&'a TypedExpr> for Located<'a> {
@@ -673,6 +673,7 @@ where
             type_,
             subjects,
             clauses,
+            compiled_case: _,
         } => v.visit_typed_expr_case(location, type_, subjects, clauses),
         TypedExpr::RecordAccess {
             location,
@@ -1,8 +1,11 @@
 #![allow(warnings)]
 
 mod elixir_libraries;
+mod embedded_files;
+pub mod in_memory;
 mod module_loader;
 mod native_file_copier;
+pub mod nix_externals;
 pub mod package_compiler;
 mod package_loader;
 mod project_compiler;
@@ -11,10 +14,11 @@ mod telemetry;
 #[cfg(test)]
 mod tests;
 
+pub use self::embedded_files::MAX_EMBEDDED_FILE_SIZE;
 pub use self::package_compiler::PackageCompiler;
 pub use self::package_loader::StaleTracker;
 pub use self::project_compiler::{Built, Options, ProjectCompiler};
-pub use self::telemetry::{NullTelemetry, Telemetry};
+pub use self::telemetry::{NullTelemetry, Phase, Telemetry};
 
 use crate::ast::{
     CallArg, CustomType, DefinitionLocation, Pattern, TypeAst, TypedArg, TypedDefinition,
@@ -23,7 +27,7 @@ use crate::ast::{
 use crate::type_::Type;
 use crate::{
     ast::{Definition, SrcSpan, TypedModule},
-    config::{self, PackageConfig},
+    config::{self, ListRepresentation, PackageConfig},
     erlang,
     error::{Error, FileIoAction, FileKind},
     io::OutputFile,
@@ -95,6 +99,17 @@ impl Target {
     pub fn is_erlang(&self) -> bool {
         matches!(self, Self::Erlang)
     }
+
+    /// Returns `true` if this build of the compiler can generate code for the
+    /// target, as the code generators can be disabled with Cargo features.
+    #[must_use]
+    pub fn has_backend(&self) -> bool {
+        match self {
+            Self::Erlang => cfg!(feature = "erlang-backend"),
+            Self::JavaScript => cfg!(feature = "javascript-backend"),
+            Self::Nix => cfg!(feature = "nix-backend"),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -153,6 +168,7 @@ impl Default for Runtime {
 pub enum TargetCodegenConfiguration {
     Nix {
         prelude_location: Utf8PathBuf,
+        list_representation: ListRepresentation,
     },
     JavaScript {
         emit_typescript_definitions: bool,
@@ -356,6 +372,29 @@ pub enum Located<'a> {
     Annotation(SrcSpan, std::sync::Arc<Type>),
     UnqualifiedImport(UnqualifiedImport<'a>),
     Label(SrcSpan, std::sync::Arc<Type>),
+    /// The label of an argument given to a module function, such as `label`
+    /// in `wibble(label: 1)`.
+    FunctionLabel(LabelledArgument<'a>),
+    /// The label of an argument given to a record constructor, such as `name`
+    /// in `Person(name: "Lucy")`.
+    RecordLabel(LabelledArgument<'a>),
+}
+
+/// A labelled argument, along with the function or record constructor it is
+/// given to.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelledArgument<'a> {
+    pub location: SrcSpan,
+    pub type_: std::sync::Arc<Type>,
+    /// The module defining the function or record constructor.
+    pub module: &'a EcoString,
+    pub name: &'a EcoString,
+    /// Where the function or record constructor is defined.
+    pub definition: SrcSpan,
+    pub documentation: Option<&'a str>,
+    /// The position of the labelled parameter among all the parameters.
+    pub index: u32,
 }
 
 impl<'a> Located<'a> {
@@ -410,6 +449,12 @@ impl<'a> Located<'a> {
             Self::Arg(_) => None,
             Self::Annotation(_, type_) => self.type_location(importable_modules, type_.clone()),
             Self::Label(_, _) => None,
+            Self::FunctionLabel(argument) | Self::RecordLabel(argument) => {
+                Some(DefinitionLocation {
+                    module: Some(argument.module.as_str()),
+                    span: argument.definition,
+                })
+            }
         }
     }
 }
@@ -479,8 +524,22 @@ pub(crate) struct SourceFingerprint(u64);
 
 impl SourceFingerprint {
     pub(crate) fn new(source: &str) -> Self {
-        SourceFingerprint(xxhash_rust::xxh3::xxh3_64(source.as_bytes()))
+        Self::from_bytes(source.as_bytes())
     }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        SourceFingerprint(xxhash_rust::xxh3::xxh3_64(bytes))
+    }
+}
+
+/// Whether a modification time can be trusted to change along with the
+/// contents of a file. Tools such as Nix normalise modification times to (or
+/// near to) the Unix epoch, or to the start of 1980 as zip archives do, in
+/// which case files must be compared by their contents instead.
+pub(crate) fn is_meaningful_mtime(mtime: SystemTime) -> bool {
+    // 1980-01-02T00:00:00Z
+    let normalised_before = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(315_619_200);
+    mtime >= normalised_before
 }
 
 /// Like a `Result`, but the operation can partially succeed or fail.
@@ -0,0 +1,409 @@
+//! Embedding of files into module constants with the `@embed_file` attribute.
+//!
+//! The contents of the file replace the constant's value when the module is
+//! loaded, as a `String` or `BitArray` literal depending on the constant's
+//! annotation, so each target generates code for them like any other literal.
+
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use num_bigint::BigInt;
+
+use crate::{
+    ast::{
+        BitArrayOption, BitArraySegment, Constant, Definition, EmbeddedFile, SrcSpan, TypeAst,
+        TypeAstConstructor, TypedModule, UntypedConstant, UntypedModule,
+    },
+    build::{nix_externals, SourceFingerprint},
+    error::EmbeddedFileError,
+    io::FileSystemReader,
+    Error, Result,
+};
+
+/// The largest file that can be embedded into a constant, in bytes.
+pub const MAX_EMBEDDED_FILE_SIZE: usize = 1024 * 1024;
+
+/// Gives each constant with an `@embed_file` attribute the contents of its
+/// file as its value.
+///
+pub fn embed_files(
+    io: &impl FileSystemReader,
+    module_path: &Utf8Path,
+    code: &EcoString,
+    ast: &mut UntypedModule,
+) -> Result<()> {
+    let package_root = package_root(&ast.name, module_path);
+    for definition in ast.definitions.iter_mut() {
+        let Definition::ModuleConstant(constant) = &mut definition.definition else {
+            continue;
+        };
+        let Some(EmbeddedFile { path, location }) = constant.embedded_file.clone() else {
+            continue;
+        };
+        let error = |error: EmbeddedFileError| Error::EmbeddedFile {
+            path: module_path.to_path_buf(),
+            src: code.clone(),
+            location,
+            file: path.clone(),
+            error,
+        };
+
+        let value = match embedded_type(constant.annotation.as_ref()) {
+            Some(EmbeddedType::String) => {
+                let bytes = read(io, &package_root, &path).map_err(error)?;
+                let contents =
+                    String::from_utf8(bytes).map_err(|_| error(EmbeddedFileError::InvalidUtf8))?;
+                Constant::String {
+                    location,
+                    value: string_literal(&contents),
+                }
+            }
+            Some(EmbeddedType::BitArray) => {
+                let bytes = read(io, &package_root, &path).map_err(error)?;
+                bit_array_literal(&bytes, location)
+            }
+            None => return Err(error(EmbeddedFileError::UnsupportedType)),
+        };
+        constant.value = Box::new(value);
+    }
+    Ok(())
+}
+
+/// The fingerprint of each file embedded into the module, recorded in the
+/// module's cache so it can be invalidated when one of the files changes.
+///
+pub fn fingerprints(
+    io: &impl FileSystemReader,
+    module_path: &Utf8Path,
+    ast: &TypedModule,
+) -> Result<Vec<(EcoString, SourceFingerprint)>> {
+    let package_root = package_root(&ast.name, module_path);
+    ast.definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::ModuleConstant(constant) => constant.embedded_file.as_ref(),
+            _ => None,
+        })
+        .map(|EmbeddedFile { path, .. }| {
+            let bytes = io.read_bytes(&package_root.join(path.as_str()))?;
+            Ok((path.clone(), SourceFingerprint::from_bytes(&bytes)))
+        })
+        .collect()
+}
+
+/// Whether any of the embedded files recorded in a module's cache has changed
+/// or has been removed since.
+///
+pub fn any_changed(
+    io: &impl FileSystemReader,
+    module_name: &str,
+    module_path: &Utf8Path,
+    fingerprints: &[(EcoString, SourceFingerprint)],
+) -> bool {
+    let package_root = package_root(module_name, module_path);
+    fingerprints.iter().any(|(path, fingerprint)| {
+        match io.read_bytes(&package_root.join(path.as_str())) {
+            Ok(bytes) => SourceFingerprint::from_bytes(&bytes) != *fingerprint,
+            Err(_) => true,
+        }
+    })
+}
+
+enum EmbeddedType {
+    String,
+    BitArray,
+}
+
+fn embedded_type(annotation: Option<&TypeAst>) -> Option<EmbeddedType> {
+    match annotation? {
+        TypeAst::Constructor(TypeAstConstructor {
+            module: None,
+            name,
+            arguments,
+            ..
+        }) if arguments.is_empty() => match name.as_str() {
+            "String" => Some(EmbeddedType::String),
+            "BitArray" => Some(EmbeddedType::BitArray),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn package_root(module_name: &str, module_path: &Utf8Path) -> Utf8PathBuf {
+    nix_externals::source_root(module_name, module_path)
+        .parent()
+        .map(Utf8Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+fn read(
+    io: &impl FileSystemReader,
+    package_root: &Utf8Path,
+    path: &str,
+) -> Result<Vec<u8>, EmbeddedFileError> {
+    // Only files within the package can be embedded, so that a package builds
+    // the same wherever it is.
+    let is_within_package = Utf8Path::new(path)
+        .components()
+        .all(|component| matches!(component, Utf8Component::Normal(_) | Utf8Component::CurDir));
+    if !is_within_package {
+        return Err(EmbeddedFileError::OutsidePackage);
+    }
+
+    let path = package_root.join(path);
+    if !io.is_file(&path) {
+        return Err(EmbeddedFileError::NotFound);
+    }
+    let bytes = io
+        .read_bytes(&path)
+        .map_err(|_| EmbeddedFileError::NotFound)?;
+    if bytes.len() > MAX_EMBEDDED_FILE_SIZE {
+        return Err(EmbeddedFileError::TooLarge { size: bytes.len() });
+    }
+    Ok(bytes)
+}
+
+/// The contents of a string literal evaluating to the given text.
+///
+fn string_literal(text: &str) -> EcoString {
+    let mut literal = EcoString::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\\' => literal.push_str("\\\\"),
+            '"' => literal.push_str("\\\""),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            character if character.is_control() => {
+                literal.push_str(&format!("\\u{{{:x}}}", character as u32))
+            }
+            character => literal.push(character),
+        }
+    }
+    literal
+}
+
+/// A bit array literal holding the given bytes. Text is kept as a single UTF-8
+/// string segment, so targets can generate it as a string literal rather than
+/// one element per byte. Nix strings can't hold a NUL byte, so any other data
+/// is written out byte by byte.
+///
+fn bit_array_literal(bytes: &[u8], location: SrcSpan) -> UntypedConstant {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        if !text.contains('\0') {
+            let segment = BitArraySegment {
+                location,
+                value: Box::new(Constant::String {
+                    location,
+                    value: string_literal(text),
+                }),
+                options: vec![BitArrayOption::Utf8 { location }],
+                type_: (),
+            };
+            return Constant::BitArray {
+                location,
+                segments: vec![segment],
+            };
+        }
+    }
+
+    let segments = bytes
+        .iter()
+        .map(|byte| BitArraySegment {
+            location,
+            value: Box::new(Constant::Int {
+                location,
+                value: byte.to_string().into(),
+                int_value: BigInt::from(*byte),
+            }),
+            options: vec![],
+            type_: (),
+        })
+        .collect();
+    Constant::BitArray { location, segments }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{memory::InMemoryFileSystem, FileSystemWriter};
+
+    fn module(src: &str) -> UntypedModule {
+        let mut ast = crate::parse::parse_module(
+            Utf8PathBuf::from("/app/src/wibble.gleam"),
+            src,
+            &crate::warning::WarningEmitter::null(),
+        )
+        .expect("syntax error")
+        .module;
+        ast.name = "wibble".into();
+        ast
+    }
+
+    fn constant_value(ast: &UntypedModule) -> &UntypedConstant {
+        match ast
+            .definitions
+            .first()
+            .map(|definition| &definition.definition)
+        {
+            Some(Definition::ModuleConstant(constant)) => &constant.value,
+            _ => panic!("expected a constant"),
+        }
+    }
+
+    fn embed(io: &InMemoryFileSystem, src: &str) -> Result<UntypedModule> {
+        let mut ast = module(src);
+        let path = Utf8Path::new("/app/src/wibble.gleam");
+        embed_files(io, path, &src.into(), &mut ast).map(|()| ast)
+    }
+
+    #[test]
+    fn string_literal_escapes() {
+        assert_eq!(
+            string_literal("a \"b\"\n\\c\t\u{1}"),
+            "a \\\"b\\\"\\n\\\\c\\t\\u{1}"
+        );
+    }
+
+    #[test]
+    fn embeds_string() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/app/priv/data.txt"), "Hello\n")
+            .unwrap();
+        let ast = embed(
+            &io,
+            "@embed_file(\"priv/data.txt\")\npub const data: String",
+        )
+        .unwrap();
+        assert!(matches!(
+            constant_value(&ast),
+            Constant::String { value, .. } if value == "Hello\\n"
+        ));
+    }
+
+    #[test]
+    fn embeds_bit_array() {
+        let io = InMemoryFileSystem::new();
+        io.write_bytes(Utf8Path::new("/app/priv/data.bin"), &[1, 255])
+            .unwrap();
+        let ast = embed(
+            &io,
+            "@embed_file(\"priv/data.bin\")\npub const data: BitArray",
+        )
+        .unwrap();
+        let Constant::BitArray { segments, .. } = constant_value(&ast) else {
+            panic!("expected a bit array");
+        };
+        let bytes = segments
+            .iter()
+            .map(|segment| match segment.value.as_ref() {
+                Constant::Int { value, .. } => value.clone(),
+                _ => panic!("expected an int"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(bytes, vec![EcoString::from("1"), EcoString::from("255")]);
+    }
+
+    #[test]
+    fn embeds_text_bit_array_as_string_segment() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/app/priv/data.json"), "{\"a\": 1}\n")
+            .unwrap();
+        let ast = embed(
+            &io,
+            "@embed_file(\"priv/data.json\")\npub const data: BitArray",
+        )
+        .unwrap();
+        let Constant::BitArray { segments, .. } = constant_value(&ast) else {
+            panic!("expected a bit array");
+        };
+        let [segment] = segments.as_slice() else {
+            panic!("expected a single segment");
+        };
+        assert!(matches!(
+            segment.value.as_ref(),
+            Constant::String { value, .. } if value == "{\\\"a\\\": 1}\\n"
+        ));
+        assert!(matches!(
+            segment.options.as_slice(),
+            [BitArrayOption::Utf8 { .. }]
+        ));
+    }
+
+    #[test]
+    fn missing_file() {
+        let io = InMemoryFileSystem::new();
+        let result = embed(&io, "@embed_file(\"priv/data.txt\")\nconst data: String");
+        assert!(matches!(
+            result,
+            Err(Error::EmbeddedFile {
+                error: EmbeddedFileError::NotFound,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn file_outside_package() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/data.txt"), "").unwrap();
+        let result = embed(&io, "@embed_file(\"../data.txt\")\nconst data: String");
+        assert!(matches!(
+            result,
+            Err(Error::EmbeddedFile {
+                error: EmbeddedFileError::OutsidePackage,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn file_too_large() {
+        let io = InMemoryFileSystem::new();
+        let contents = vec![0; MAX_EMBEDDED_FILE_SIZE + 1];
+        io.write_bytes(Utf8Path::new("/app/priv/data.bin"), &contents)
+            .unwrap();
+        let result = embed(&io, "@embed_file(\"priv/data.bin\")\nconst data: BitArray");
+        assert!(matches!(
+            result,
+            Err(Error::EmbeddedFile {
+                error: EmbeddedFileError::TooLarge { .. },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn unsupported_type() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/app/priv/data.txt"), "").unwrap();
+        let result = embed(&io, "@embed_file(\"priv/data.txt\")\nconst data: Int");
+        assert!(matches!(
+            result,
+            Err(Error::EmbeddedFile {
+                error: EmbeddedFileError::UnsupportedType,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn changed_files() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/app/priv/data.txt"), "one")
+            .unwrap();
+        let path = Utf8Path::new("/app/src/wibble.gleam");
+        let fingerprints = vec![(
+            EcoString::from("priv/data.txt"),
+            SourceFingerprint::from_bytes(b"one"),
+        )];
+        assert!(!any_changed(&io, "wibble", path, &fingerprints));
+
+        io.write(Utf8Path::new("/app/priv/data.txt"), "two")
+            .unwrap();
+        assert!(any_changed(&io, "wibble", path, &fingerprints));
+
+        io.delete_file(Utf8Path::new("/app/priv/data.txt")).unwrap();
+        assert!(any_changed(&io, "wibble", path, &fingerprints));
+    }
+}
@@ -0,0 +1,83 @@
+//! Compiling a single package without a real file system or any external
+//! programs, as done by the browser playground. Nothing here may depend on
+//! the host, so that it keeps building for `wasm32-unknown-unknown`.
+
+use std::{collections::HashSet, rc::Rc};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use hexpm::version::Version;
+
+use crate::{
+    analyse::TargetSupport,
+    build::{
+        Mode, NullTelemetry, PackageCompiler, StaleTracker, Target, TargetCodegenConfiguration,
+    },
+    config::{ListRepresentation, PackageConfig},
+    io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter},
+    uid::UniqueIdGenerator,
+    warning::{WarningEmitter, WarningEmitterIO},
+    Result,
+};
+
+/// The directory the package's `src` and `test` directories are read from.
+pub const IN_MEMORY_PACKAGE_ROOT: &str = "/";
+
+/// The directory the generated code is written to.
+pub const IN_MEMORY_OUTPUT_DIRECTORY: &str = "/build";
+
+/// Compiles the modules of the package held by `io` to the given target.
+///
+/// The package is called `library` and has no dependencies. Its modules are
+/// read from `/src`, and the generated code is written to `/build` with
+/// module names kept as paths, e.g. `/build/wibble/wobble.nix`. Erlang
+/// modules go to `/build/_gleam_artefacts` instead, with `/` replaced by `@`.
+pub fn compile_package<IO>(io: IO, target: Target, warnings: Rc<dyn WarningEmitterIO>) -> Result<()>
+where
+    IO: FileSystemReader + FileSystemWriter + CommandExecutor + BeamCompiler + Clone,
+{
+    let ids = UniqueIdGenerator::new();
+    let mut type_manifests = im::HashMap::new();
+    let mut defined_modules = im::HashMap::new();
+    let warning_emitter = WarningEmitter::new(warnings);
+    let config = PackageConfig {
+        name: "library".into(),
+        version: Version::new(1, 0, 0),
+        target,
+        ..Default::default()
+    };
+
+    let target = match target {
+        Target::Erlang => TargetCodegenConfiguration::Erlang { app_file: None },
+        Target::JavaScript => TargetCodegenConfiguration::JavaScript {
+            emit_typescript_definitions: false,
+            prelude_location: Utf8PathBuf::from("./gleam_prelude.mjs"),
+        },
+        Target::Nix => TargetCodegenConfiguration::Nix {
+            prelude_location: Utf8PathBuf::from("./gleam_prelude.nix"),
+            list_representation: ListRepresentation::default(),
+        },
+    };
+
+    tracing::info!("Compiling package");
+
+    let lib = Utf8PathBuf::from("/lib");
+    let out = Utf8Path::new(IN_MEMORY_OUTPUT_DIRECTORY);
+    let package = Utf8Path::new(IN_MEMORY_PACKAGE_ROOT);
+    let mut compiler =
+        PackageCompiler::new(&config, Mode::Dev, package, out, &lib, &target, ids, io);
+    compiler.write_entrypoint = false;
+    compiler.write_metadata = false;
+    compiler.compile_beam_bytecode = true;
+    compiler.target_support = TargetSupport::Enforced;
+    compiler
+        .compile(
+            &warning_emitter,
+            &mut type_manifests,
+            &mut defined_modules,
+            &mut StaleTracker::default(),
+            &mut HashSet::new(),
+            &NullTelemetry,
+        )
+        .into_result()
+        .map(|_| ())
+}
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests;
 
-use std::{collections::HashSet, time::SystemTime};
+use std::{collections::HashSet, io::Read, time::SystemTime};
 
 use camino::{Utf8Path, Utf8PathBuf};
 
@@ -9,13 +9,14 @@ use ecow::EcoString;
 use serde::{Deserialize, Serialize};
 
 use super::{
+    embedded_files, is_meaningful_mtime,
     package_compiler::{module_name, CacheMetadata, CachedModule, Input, UncompiledModule},
     package_loader::CodegenRequired,
     Mode, Origin, SourceFingerprint, Target,
 };
 use crate::{
-    error::{FileIoAction, FileKind},
     io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    metadata,
     warning::{TypeWarningEmitter, WarningEmitter},
     Error, Result,
 };
@@ -46,20 +47,29 @@ where
     /// changed since then, load the precompiled data instead.
     ///
     /// Whether the module has changed or not is determined by comparing the
-    /// modification time of the source file with the value recorded in the
-    /// `.timestamp` file in the artefact directory.
+    /// fingerprint of the source file with the one recorded in the
+    /// `.cache_meta` file in the artefact directory. When the modification
+    /// time of the source is exactly the one recorded there, the source is
+    /// assumed to be unchanged without being read.
     pub fn load(&self, path: Utf8PathBuf) -> Result<Input> {
         let name = module_name(self.source_directory, &path);
         let artefact = name.replace("/", "@");
         let source_mtime = self.io.modification_time(&path)?;
 
-        let read_source = |name| self.read_source(path, name, source_mtime);
+        let read_source = |name| self.read_source(path.clone(), name, source_mtime);
 
         let meta = match self.read_cache_metadata(&artefact)? {
             Some(meta) => meta,
             None => return read_source(name).map(Input::New),
         };
 
+        // Interfaces written in another format by a different compiler
+        // version can't be read, so the module is compiled again.
+        if !self.cached_interface_is_readable(&artefact)? {
+            tracing::debug!(?name, "cache_format_outdated");
+            return read_source(name).map(Input::New);
+        }
+
         // The cache currently does not contain enough data to perform codegen,
         // so if codegen is required in this compiler run then we must check
         // that codegen has already been performed before using a cache.
@@ -68,10 +78,19 @@ where
             return read_source(name).map(Input::New);
         }
 
-        // If the timestamp of the source is newer than the cache entry and
-        // the hash of the source differs from the one in the cache entry,
-        // then we need to recompile.
-        if meta.mtime < source_mtime {
+        // Embedded files are not covered by the source's modification time, so
+        // the module must be recompiled whenever one of them changes.
+        if embedded_files::any_changed(&self.io, &name, &path, &meta.embedded_files) {
+            tracing::debug!(?name, "embedded_file_changed");
+            return read_source(name).map(Input::New);
+        }
+
+        // Modification times are only a hint: they may go back in time when
+        // caches are restored, or be normalised to the same value for every
+        // file. Unless the source's modification time is unchanged and
+        // meaningful, its hash decides whether the module must be recompiled.
+        let unchanged_mtime = meta.mtime == source_mtime && is_meaningful_mtime(source_mtime);
+        if !unchanged_mtime {
             let source_module = read_source(name.clone())?;
             if meta.fingerprint != SourceFingerprint::new(&source_module.code) {
                 tracing::debug!(?name, "cache_stale");
@@ -100,15 +119,32 @@ where
         }
 
         let binary = self.io.read_bytes(&meta_path)?;
-        let cache_metadata = CacheMetadata::from_binary(&binary).map_err(|e| -> Error {
-            Error::FileIo {
-                action: FileIoAction::Parse,
-                kind: FileKind::File,
-                path: meta_path,
-                err: Some(e),
+        match CacheMetadata::from_binary(&binary) {
+            Ok(cache_metadata) => Ok(Some(cache_metadata)),
+            // The metadata may have been written by a compiler version that
+            // recorded different information, in which case the module is
+            // compiled again as if it had no cache.
+            Err(error) => {
+                tracing::debug!(path = %meta_path, %error, "unreadable_cache_metadata");
+                Ok(None)
             }
-        })?;
-        Ok(Some(cache_metadata))
+        }
+    }
+
+    /// Whether the module interface cached for the given artefact slug is in
+    /// the format this compiler reads. Only its header is read.
+    fn cached_interface_is_readable(&self, artefact: &str) -> Result<bool> {
+        let path = self
+            .artefact_directory
+            .join(artefact)
+            .with_extension("cache");
+        if !self.io.is_file(&path) {
+            return Ok(false);
+        }
+        let mut header = [0; metadata::HEADER_LENGTH];
+        // A cache too short to hold a header is in no format at all.
+        Ok(self.io.reader(&path)?.read_exact(&mut header).is_ok()
+            && metadata::is_current_format(&header))
     }
 
     fn read_source(
@@ -167,6 +203,7 @@ where
     let dependencies = ast.dependencies(target);
 
     ast.name = name.clone();
+    embedded_files::embed_files(&io, &path, &code, &mut ast)?;
     let module = UncompiledModule {
         package: package_name,
         dependencies,
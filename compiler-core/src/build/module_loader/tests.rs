@@ -3,7 +3,9 @@ use crate::{
     build::SourceFingerprint,
     io::{memory::InMemoryFileSystem, FileSystemWriter},
     line_numbers::LineNumbers,
+    type_,
 };
+use hexpm::version::Version;
 use std::time::Duration;
 
 #[test]
@@ -89,6 +91,75 @@ fn cache_present_and_stale_but_source_is_the_same() {
     assert!(result.is_cached());
 }
 
+#[test]
+fn cache_present_and_source_changed_with_older_mtime() {
+    let name = "package".into();
+    let src = Utf8Path::new("/src");
+    let artefact = Utf8Path::new("/artefact");
+    let fs = InMemoryFileSystem::new();
+    let warnings = WarningEmitter::null();
+    let incomplete_modules = HashSet::new();
+    let loader = make_loader(&warnings, &name, &fs, src, artefact, &incomplete_modules);
+
+    // The mtime of the source went back in time, as when a cache is restored
+    write_src(&fs, TEST_SOURCE_2, "/src/main.gleam", 0);
+    write_cache(&fs, TEST_SOURCE_1, "/artefact/main.cache_meta", 1, false);
+
+    let result = loader
+        .load(Utf8Path::new("/src/main.gleam").to_path_buf())
+        .unwrap();
+
+    assert!(result.is_new());
+}
+
+#[test]
+fn cache_present_and_source_changed_with_normalised_mtime() {
+    let name = "package".into();
+    let src = Utf8Path::new("/src");
+    let artefact = Utf8Path::new("/artefact");
+    let fs = InMemoryFileSystem::new();
+    let warnings = WarningEmitter::null();
+    let incomplete_modules = HashSet::new();
+    let loader = make_loader(&warnings, &name, &fs, src, artefact, &incomplete_modules);
+
+    // Nix sets the mtime of every file to one second after the epoch
+    write_src(&fs, TEST_SOURCE_2, "/src/main.gleam", 1);
+    write_cache(&fs, TEST_SOURCE_1, "/artefact/main.cache_meta", 1, false);
+
+    let result = loader
+        .load(Utf8Path::new("/src/main.gleam").to_path_buf())
+        .unwrap();
+
+    assert!(result.is_new());
+}
+
+#[test]
+fn cache_present_and_mtime_unchanged() {
+    let name = "package".into();
+    let src = Utf8Path::new("/src");
+    let artefact = Utf8Path::new("/artefact");
+    let fs = InMemoryFileSystem::new();
+    let warnings = WarningEmitter::null();
+    let incomplete_modules = HashSet::new();
+    let loader = make_loader(&warnings, &name, &fs, src, artefact, &incomplete_modules);
+
+    // The source isn't read when its meaningful mtime is the recorded one
+    write_src(&fs, TEST_SOURCE_2, "/src/main.gleam", 663112800);
+    write_cache(
+        &fs,
+        TEST_SOURCE_1,
+        "/artefact/main.cache_meta",
+        663112800,
+        false,
+    );
+
+    let result = loader
+        .load(Utf8Path::new("/src/main.gleam").to_path_buf())
+        .unwrap();
+
+    assert!(result.is_cached());
+}
+
 #[test]
 fn cache_present_and_stale_source_is_the_same_lsp_mode() {
     let name = "package".into();
@@ -200,6 +271,53 @@ fn cache_present_without_codegen_when_not_required() {
     assert!(result.is_cached());
 }
 
+#[test]
+fn cache_interface_in_other_format() {
+    let name = "package".into();
+    let src = Utf8Path::new("/src");
+    let artefact = Utf8Path::new("/artefact");
+    let fs = InMemoryFileSystem::new();
+    let warnings = WarningEmitter::null();
+    let incomplete_modules = HashSet::new();
+    let loader = make_loader(&warnings, &name, &fs, src, artefact, &incomplete_modules);
+
+    // The cache is fresh, but its interface was written by a compiler using
+    // another format
+    write_src(&fs, TEST_SOURCE_1, "/src/main.gleam", 0);
+    write_cache(&fs, TEST_SOURCE_1, "/artefact/main.cache_meta", 1, false);
+    fs.write_bytes(Utf8Path::new("/artefact/main.cache"), b"\x10\x00\x00")
+        .unwrap();
+
+    let result = loader
+        .load(Utf8Path::new("/src/main.gleam").to_path_buf())
+        .unwrap();
+
+    assert!(result.is_new());
+}
+
+#[test]
+fn cache_metadata_unreadable() {
+    let name = "package".into();
+    let src = Utf8Path::new("/src");
+    let artefact = Utf8Path::new("/artefact");
+    let fs = InMemoryFileSystem::new();
+    let warnings = WarningEmitter::null();
+    let incomplete_modules = HashSet::new();
+    let loader = make_loader(&warnings, &name, &fs, src, artefact, &incomplete_modules);
+
+    // The cache metadata was written by a compiler recording other fields
+    write_src(&fs, TEST_SOURCE_1, "/src/main.gleam", 0);
+    write_cache(&fs, TEST_SOURCE_1, "/artefact/main.cache_meta", 1, false);
+    fs.write_bytes(Utf8Path::new("/artefact/main.cache_meta"), b"\x01")
+        .unwrap();
+
+    let result = loader
+        .load(Utf8Path::new("/src/main.gleam").to_path_buf())
+        .unwrap();
+
+    assert!(result.is_new());
+}
+
 const TEST_SOURCE_1: &'static str = "const x = 1";
 const TEST_SOURCE_2: &'static str = "const x = 2";
 
@@ -217,9 +335,29 @@ fn write_cache(
         dependencies: vec![],
         fingerprint: SourceFingerprint::new(source),
         line_numbers,
+        embedded_files: vec![],
     };
     let path = Utf8Path::new(path);
     fs.write_bytes(&path, &cache_metadata.to_binary()).unwrap();
+
+    let interface = type_::ModuleInterface {
+        name: "main".into(),
+        origin: Origin::Src,
+        package: "package".into(),
+        types: Default::default(),
+        types_value_constructors: Default::default(),
+        values: Default::default(),
+        accessors: Default::default(),
+        line_numbers: LineNumbers::new(source),
+        is_internal: false,
+        src_path: "/src/main.gleam".into(),
+        warnings: vec![],
+        minimum_required_version: Version::new(0, 1, 0),
+        deprecation: type_::Deprecation::NotDeprecated,
+    };
+    let bytes = metadata::ModuleEncoder::new(&interface).encode().unwrap();
+    fs.write_bytes(&path.with_extension("cache"), &bytes)
+        .unwrap();
 }
 
 fn write_src(fs: &InMemoryFileSystem, source: &str, path: &str, seconds: u64) {
@@ -7,6 +7,7 @@ use camino::{Utf8Path, Utf8PathBuf};
 use ecow::{eco_format, EcoString};
 
 use crate::{
+    build::is_meaningful_mtime,
     io::{DirWalker, FileSystemReader, FileSystemWriter},
     Error, Result,
 };
@@ -121,14 +122,12 @@ where
         // the tree.
         self.check_for_conflicting_erlang_modules(&relative_path)?;
 
-        // If the source file's mtime is older than the destination file's mtime
+        // If the destination file has the same contents as the source file
         // then it has not changed and as such does not need to be copied.
         //
         // This makes no practical difference for JavaScript etc files, but for
         // Erlang and Elixir files it mean we can skip compiling them.
-        if self.io.is_file(&destination)
-            && self.io.modification_time(&file)? <= self.io.modification_time(&destination)?
-        {
+        if self.io.is_file(&destination) && self.is_unchanged_copy(&file, &destination)? {
             tracing::debug!(?file, "skipping_unchanged_native_file_unchanged");
             return Ok(());
         }
@@ -151,6 +150,25 @@ where
         Ok(())
     }
 
+    /// Whether a previously copied native file still has the contents of its
+    /// source. Files of different sizes differ, and a copy with the same
+    /// meaningful modification time as its source, which copying keeps, is
+    /// taken to be unchanged. Otherwise the contents are compared, as
+    /// restoring a cached build directory can make a copy look newer than a
+    /// changed source.
+    fn is_unchanged_copy(&self, source: &Utf8Path, destination: &Utf8Path) -> Result<bool> {
+        if self.io.file_size(source)? != self.io.file_size(destination)? {
+            return Ok(false);
+        }
+        let source_mtime = self.io.modification_time(source)?;
+        if is_meaningful_mtime(source_mtime)
+            && source_mtime == self.io.modification_time(destination)?
+        {
+            return Ok(true);
+        }
+        Ok(self.io.read_bytes(source)? == self.io.read_bytes(destination)?)
+    }
+
     fn check_for_duplicate(&mut self, relative_path: &Utf8PathBuf) -> Result<(), Error> {
         if !self.seen_native_files.insert(relative_path.clone()) {
             return Err(Error::DuplicateSourceFile {
@@ -1,7 +1,7 @@
 use super::NativeFileCopier;
 use crate::{
     build::native_file_copier::CopiedNativeFiles,
-    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    io::{memory::InMemoryFileSystem, FileSystemReader, FileSystemWriter},
 };
 use std::{
     collections::HashMap,
@@ -400,7 +400,26 @@ fn other_files_are_ignored() {
 }
 
 #[test]
-fn files_do_not_get_copied_if_there_already_is_a_new_version() {
+fn files_do_not_get_copied_if_they_are_unchanged() {
+    let fs = InMemoryFileSystem::new();
+    let out = Utf8Path::new("/out/wibble.erl");
+    let src = Utf8Path::new("/src/wibble.erl");
+    fs.write(&out, "same").unwrap();
+    fs.write(&src, "same").unwrap();
+    fs.set_modification_time(&out, UNIX_EPOCH);
+    fs.set_modification_time(&src, UNIX_EPOCH + Duration::from_secs(1));
+
+    let copier = NativeFileCopier::new(fs.clone(), root(), root_out());
+    let copied = copier.run().unwrap();
+
+    assert!(!copied.any_elixir);
+    assert!(copied.to_compile.is_empty());
+}
+
+// Restoring a cached build directory can make a previous copy newer than a
+// changed source file.
+#[test]
+fn files_get_copied_if_changed_even_if_the_previously_copied_version_is_newer() {
     let fs = InMemoryFileSystem::new();
     let out = Utf8Path::new("/out/wibble.mjs");
     let src = Utf8Path::new("/src/wibble.mjs");
@@ -413,6 +432,32 @@ fn files_do_not_get_copied_if_there_already_is_a_new_version() {
     let copied = copier.run().unwrap();
 
     assert!(!copied.any_elixir);
+    assert!(copied.to_compile.is_empty());
+    assert_eq!(
+        HashMap::from([
+            (Utf8PathBuf::from("/src/wibble.mjs"), "in-src".into()),
+            (Utf8PathBuf::from("/out/wibble.mjs"), "in-src".into())
+        ]),
+        fs.into_contents(),
+    );
+}
+
+// Copies keep the modification time of their source, so a copy with the same
+// size and modification time is taken to be unchanged without being read.
+#[test]
+fn files_with_the_same_size_and_modification_time_are_not_compared() {
+    let fs = InMemoryFileSystem::new();
+    let out = Utf8Path::new("/out/wibble.mjs");
+    let src = Utf8Path::new("/src/wibble.mjs");
+    let modification_time = UNIX_EPOCH + Duration::from_secs(663112800);
+    fs.write(&out, "in-out").unwrap();
+    fs.write(&src, "in-src").unwrap();
+    fs.set_modification_time(&out, modification_time);
+    fs.set_modification_time(&src, modification_time);
+
+    let copier = NativeFileCopier::new(fs.clone(), root(), root_out());
+    let copied = copier.run().unwrap();
+
     assert!(copied.to_compile.is_empty());
     assert_eq!(
         HashMap::from([
@@ -423,6 +468,24 @@ fn files_do_not_get_copied_if_there_already_is_a_new_version() {
     );
 }
 
+#[test]
+fn copied_files_keep_their_modification_time() {
+    let fs = InMemoryFileSystem::new();
+    let src = Utf8Path::new("/src/wibble.mjs");
+    let modification_time = UNIX_EPOCH + Duration::from_secs(663112800);
+    fs.write(&src, "1").unwrap();
+    fs.set_modification_time(&src, modification_time);
+
+    let copier = NativeFileCopier::new(fs.clone(), root(), root_out());
+    let _ = copier.run().unwrap();
+
+    assert_eq!(
+        fs.modification_time(Utf8Path::new("/out/wibble.mjs"))
+            .unwrap(),
+        modification_time
+    );
+}
+
 #[test]
 fn files_get_copied_if_the_previously_copied_version_is_older() {
     let fs = InMemoryFileSystem::new();
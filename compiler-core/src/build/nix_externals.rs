@@ -0,0 +1,301 @@
+//! Checks on the Nix files referenced by `@external(nix, ...)` attributes.
+//!
+//! The native files in `src` and `test` are copied next to the generated code,
+//! so the path given to a Nix external is resolved relative to the module's
+//! position within those directories rather than to the Gleam file itself.
+
+mod exports;
+
+use std::collections::HashMap;
+
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+
+use crate::{
+    ast::{Definition, TypedModule},
+    io::FileSystemReader,
+    type_,
+};
+
+use self::exports::Export;
+
+/// The generated prelude, which lives next to the compiled modules.
+pub const PRELUDE: &str = "gleam.nix";
+
+/// Returns a warning for each Nix external in the module that refers to a file
+/// which doesn't exist in the package.
+///
+pub fn missing_external_files(
+    io: &impl FileSystemReader,
+    module_name: &str,
+    module_path: &Utf8Path,
+    ast: &TypedModule,
+) -> Vec<type_::Warning> {
+    let package_root = source_root(module_name, module_path)
+        .parent()
+        .map(Utf8Path::to_path_buf)
+        .unwrap_or_default();
+    ast.definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Function(function) => function.external_nix.as_ref(),
+            _ => None,
+        })
+        .filter(|(path, _, _)| match resolve(module_name, path) {
+            Some(path) => find_external_file(io, &package_root, &path).is_none(),
+            // Files outside of the package, such as in other packages, can't
+            // be checked.
+            None => false,
+        })
+        .map(
+            |(path, _, location)| type_::Warning::MissingNixExternalFile {
+                location: *location,
+                module: path.clone(),
+            },
+        )
+        .collect()
+}
+
+/// Returns an error for each Nix external in the module whose function is not
+/// exported by the Nix file it refers to, or doesn't take as many arguments as
+/// the Gleam function. Files that can't be read or understood are skipped.
+///
+pub fn arity_errors(
+    io: &impl FileSystemReader,
+    module_name: &str,
+    module_path: &Utf8Path,
+    ast: &TypedModule,
+) -> Vec<type_::Error> {
+    let package_root = source_root(module_name, module_path)
+        .parent()
+        .map(Utf8Path::to_path_buf)
+        .unwrap_or_default();
+    let mut files: HashMap<EcoString, Option<HashMap<EcoString, Export>>> = HashMap::new();
+    let mut errors = vec![];
+
+    for definition in &ast.definitions {
+        let Definition::Function(function) = definition else {
+            continue;
+        };
+        let Some((module, name, _)) = &function.external_nix else {
+            continue;
+        };
+        let exports = files.entry(module.clone()).or_insert_with(|| {
+            let path = resolve(module_name, module)?;
+            let src = match find_external_file(io, &package_root, &path)? {
+                ExternalFile::Prelude => crate::nix::PRELUDE.to_string(),
+                ExternalFile::GleamModule => return None,
+                ExternalFile::Nix(path) => io.read(&path).ok()?,
+            };
+            exports::exports(&src)
+        });
+        let Some(exports) = exports else {
+            continue;
+        };
+
+        // Functions without arguments are called with an empty attribute set.
+        let arity = function.arguments.len().max(1);
+        match exports.get(name) {
+            None => errors.push(type_::Error::UnknownNixExternalFunction {
+                location: function.location,
+                module: module.clone(),
+                function: name.clone(),
+            }),
+            Some(export) if !export.accepts(arity) => {
+                errors.push(type_::Error::IncorrectNixExternalArity {
+                    location: function.location,
+                    module: module.clone(),
+                    function: name.clone(),
+                    expected: function.arguments.len(),
+                    given: export.arity().unwrap_or_default(),
+                });
+            }
+            Some(_) => (),
+        }
+    }
+    errors
+}
+
+/// The Nix file in the package that a Nix external declared in the given
+/// module refers to, if it is one written by hand rather than the prelude or
+/// a compiled Gleam module.
+///
+pub fn package_nix_file(
+    io: &impl FileSystemReader,
+    module_name: &str,
+    module_path: &Utf8Path,
+    external_path: &str,
+) -> Option<Utf8PathBuf> {
+    let package_root = source_root(module_name, module_path)
+        .parent()?
+        .to_path_buf();
+    let path = resolve(module_name, external_path)?;
+    match find_external_file(io, &package_root, &path)? {
+        ExternalFile::Nix(path) => Some(path),
+        ExternalFile::Prelude | ExternalFile::GleamModule => None,
+    }
+}
+
+/// The directory the module's name is relative to, e.g. `src` for the module
+/// `wibble/wobble` at `src/wibble/wobble.gleam`.
+///
+pub fn source_root(module_name: &str, module_path: &Utf8Path) -> Utf8PathBuf {
+    let segments = module_name.split('/').count();
+    module_path
+        .ancestors()
+        .nth(segments)
+        .unwrap_or(Utf8Path::new(""))
+        .to_path_buf()
+}
+
+/// Resolves the path of a Nix external declared in the given module to a path
+/// relative to the source directories. Returns `None` if the path points
+/// outside of the package.
+///
+pub fn resolve(module_name: &str, external_path: &str) -> Option<Utf8PathBuf> {
+    let directory = Utf8Path::new(module_name)
+        .parent()
+        .unwrap_or(Utf8Path::new(""));
+    let mut resolved = Utf8PathBuf::new();
+    for component in directory.join(external_path).components() {
+        match component {
+            Utf8Component::CurDir => (),
+            Utf8Component::ParentDir => {
+                if !resolved.pop() {
+                    return None;
+                }
+            }
+            Utf8Component::Normal(name) => resolved.push(name),
+            Utf8Component::RootDir | Utf8Component::Prefix(_) => return None,
+        }
+    }
+    Some(resolved)
+}
+
+/// The path to use in a Nix external declared in the given module to refer to
+/// a file at the given path relative to the source directories.
+///
+pub fn external_path(module_name: &str, path: &Utf8Path) -> String {
+    let directory = Utf8Path::new(module_name)
+        .parent()
+        .unwrap_or(Utf8Path::new(""));
+    match pathdiff::diff_utf8_paths(path, directory) {
+        Some(relative) if relative.starts_with("..") => relative.to_string(),
+        Some(relative) => format!("./{relative}"),
+        None => format!("./{path}"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExternalFile {
+    Prelude,
+    /// A Gleam module, which is compiled to a Nix file of the same name.
+    GleamModule,
+    Nix(Utf8PathBuf),
+}
+
+/// Finds the file a resolved Nix external path refers to in the package.
+fn find_external_file(
+    io: &impl FileSystemReader,
+    package_root: &Utf8Path,
+    path: &Utf8Path,
+) -> Option<ExternalFile> {
+    if path == PRELUDE {
+        return Some(ExternalFile::Prelude);
+    }
+    ["src", "test"].iter().find_map(|directory| {
+        let path = package_root.join(directory).join(path);
+        // Importing a directory imports its `default.nix`.
+        let path = if io.is_directory(&path) {
+            path.join("default.nix")
+        } else {
+            path
+        };
+        if io.is_file(&path) {
+            Some(ExternalFile::Nix(path))
+        } else if path.extension() == Some("nix") && io.is_file(&path.with_extension("gleam")) {
+            Some(ExternalFile::GleamModule)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{memory::InMemoryFileSystem, FileSystemWriter};
+
+    #[test]
+    fn resolve_relative_to_module() {
+        assert_eq!(
+            resolve("wibble/wobble", "./ffi.nix"),
+            Some("wibble/ffi.nix".into())
+        );
+        assert_eq!(
+            resolve("wibble/wobble", "../ffi.nix"),
+            Some("ffi.nix".into())
+        );
+        assert_eq!(resolve("wibble/wobble", "."), Some("wibble".into()));
+        assert_eq!(resolve("wibble", "../other/ffi.nix"), None);
+    }
+
+    #[test]
+    fn external_path_relative_to_module() {
+        assert_eq!(
+            external_path("wibble/wobble", Utf8Path::new("wibble/ffi.nix")),
+            "./ffi.nix"
+        );
+        assert_eq!(
+            external_path("wibble/wobble", Utf8Path::new("ffi.nix")),
+            "../ffi.nix"
+        );
+        assert_eq!(
+            external_path("wibble", Utf8Path::new("ffi.nix")),
+            "./ffi.nix"
+        );
+    }
+
+    #[test]
+    fn source_root_of_nested_module() {
+        let module = Utf8Path::new("/app/src/wibble/wobble.gleam");
+        assert_eq!(source_root("wibble/wobble", module), "/app/src");
+    }
+
+    #[test]
+    fn external_files() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/app/src/ffi.nix"), "").unwrap();
+        io.write(Utf8Path::new("/app/src/other.gleam"), "").unwrap();
+        io.write(Utf8Path::new("/app/src/lib/default.nix"), "")
+            .unwrap();
+        io.write(Utf8Path::new("/app/test/test_ffi.nix"), "")
+            .unwrap();
+        io.mkdir(Utf8Path::new("/app/src/empty")).unwrap();
+        let root = Utf8Path::new("/app");
+        let exists = |path: &str| find_external_file(&io, root, Utf8Path::new(path)).is_some();
+
+        assert!(exists("ffi.nix"));
+        assert!(exists("other.nix"));
+        assert!(exists("lib"));
+        assert!(exists("test_ffi.nix"));
+        assert!(exists("gleam.nix"));
+        assert!(!exists("empty"));
+        assert!(!exists("missing.nix"));
+    }
+
+    #[test]
+    fn package_nix_files() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/app/src/wibble/ffi.nix"), "")
+            .unwrap();
+        io.write(Utf8Path::new("/app/src/other.gleam"), "").unwrap();
+        let module = Utf8Path::new("/app/src/wibble/wobble.gleam");
+        let file = |path: &str| package_nix_file(&io, "wibble/wobble", module, path);
+
+        assert_eq!(file("./ffi.nix"), Some("/app/src/wibble/ffi.nix".into()));
+        assert_eq!(file("../other.nix"), None);
+        assert_eq!(file("../gleam.nix"), None);
+        assert_eq!(file("./missing.nix"), None);
+    }
+}
@@ -0,0 +1,534 @@
+//! A minimal reader for the Nix files used to implement externals.
+//!
+//! This is not a full Nix parser: it only understands enough of the language
+//! to find the attributes exported by the usual shapes of FFI files, such as
+//! `{ wibble = x: ...; }` or `let wibble = x: ...; in { inherit wibble; }`,
+//! and how many curried arguments each of them takes. Anything it doesn't
+//! understand is reported as unknown so that it is never wrongly rejected.
+
+use std::collections::HashMap;
+
+use ecow::EcoString;
+
+/// What is known about an attribute exported by a Nix file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Export {
+    /// A function taking this many curried arguments. When the arity isn't
+    /// exact the function might return another function, so it could take
+    /// more arguments.
+    Function { arity: usize, exact: bool },
+    /// A value which is not a function.
+    Value,
+    /// Anything this reader doesn't understand.
+    Unknown,
+}
+
+impl Export {
+    /// Whether this can be called with the given number of curried arguments.
+    /// Unknown exports are always assumed to be callable.
+    pub fn accepts(self, arguments: usize) -> bool {
+        match self {
+            Export::Function { arity, exact } => {
+                arity == arguments || (!exact && arity < arguments)
+            }
+            Export::Value => arguments == 0,
+            Export::Unknown => true,
+        }
+    }
+
+    /// The number of arguments this is known to take, if any.
+    pub fn arity(self) -> Option<usize> {
+        match self {
+            Export::Function { arity, .. } => Some(arity),
+            Export::Value => Some(0),
+            Export::Unknown => None,
+        }
+    }
+}
+
+/// The attributes exported by a Nix file, or `None` if the file doesn't have a
+/// shape this reader understands.
+pub fn exports(src: &str) -> Option<HashMap<EcoString, Export>> {
+    let tokens = tokenise(src)?;
+    top_level(&tokens, &HashMap::new())
+}
+
+type Scope = HashMap<EcoString, Export>;
+
+fn top_level(tokens: &[Token], scope: &Scope) -> Option<Scope> {
+    match tokens {
+        [Token::Ident(keyword), rest @ ..] if keyword == "let" => {
+            let in_ = closing(tokens, 0)?;
+            let let_tokens = rest.get(..in_ - 1)?;
+            let let_bindings = bindings(let_tokens, scope)?;
+            let mut scope = scope.clone();
+            scope.extend(let_bindings);
+            match tokens.get(in_ + 1..)? {
+                // A function defined by the `let` which is called to build
+                // the attribute set, as done by the prelude:
+                // `let mkPrelude = listRepresentation: { ... }; in mkPrelude "cons"`.
+                [Token::Ident(function), Token::String(_) | Token::Number | Token::Path] => {
+                    top_level(function_body(let_tokens, function)?, &scope)
+                }
+                body => top_level(body, &scope),
+            }
+        }
+        [Token::Ident(keyword), rest @ ..] if keyword == "rec" => top_level(rest, scope),
+        [Token::Punct('{'), inner @ .., Token::Punct('}')]
+            if closing(tokens, 0)? == tokens.len() - 1 =>
+        {
+            Some(bindings(inner, scope)?.into_iter().collect())
+        }
+        [Token::Punct('('), inner @ .., Token::Punct(')')]
+            if closing(tokens, 0)? == tokens.len() - 1 =>
+        {
+            top_level(inner, scope)
+        }
+        _ => None,
+    }
+}
+
+/// Reads the bindings of an attribute set or `let` expression.
+fn bindings(tokens: &[Token], scope: &Scope) -> Option<Vec<(EcoString, Export)>> {
+    let mut bindings = vec![];
+    let mut position = 0;
+    while position < tokens.len() {
+        let end = position + binding_end(tokens.get(position..)?)?;
+        let binding = tokens.get(position..end)?;
+        position = end + 1;
+
+        match binding {
+            [Token::Ident(keyword), Token::Punct('('), ..] if keyword == "inherit" => {
+                let source_end = closing(binding, 1)?;
+                for token in binding.get(source_end + 1..)? {
+                    bindings.push((name(token)?, Export::Unknown));
+                }
+            }
+            [Token::Ident(keyword), names @ ..] if keyword == "inherit" => {
+                for token in names {
+                    let name = name(token)?;
+                    let export = scope.get(&name).copied().unwrap_or(Export::Unknown);
+                    bindings.push((name, export));
+                }
+            }
+            [attribute, Token::Punct('='), value @ ..] => {
+                bindings.push((name(attribute)?, shape(value, scope)));
+            }
+            // A nested attribute such as `wibble.wobble = ...` makes `wibble`
+            // an attribute set.
+            [attribute, Token::Punct('.'), ..] => {
+                bindings.push((name(attribute)?, Export::Value));
+            }
+            _ => return None,
+        }
+    }
+    Some(bindings)
+}
+
+/// The body of the function taking a single argument which is bound to the
+/// given name by the bindings of a `let` expression.
+fn function_body<'a>(tokens: &'a [Token], function: &str) -> Option<&'a [Token]> {
+    let mut position = 0;
+    while position < tokens.len() {
+        let end = position + binding_end(tokens.get(position..)?)?;
+        let binding = tokens.get(position..end)?;
+        position = end + 1;
+
+        if let [Token::Ident(name), Token::Punct('='), Token::Ident(_), Token::Punct(':'), body @ ..] =
+            binding
+        {
+            if name == function {
+                return Some(body);
+            }
+        }
+    }
+    None
+}
+
+fn name(token: &Token) -> Option<EcoString> {
+    match token {
+        Token::Ident(name) | Token::String(Some(name)) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+/// What the given expression evaluates to, as far as can be told from its
+/// first few tokens.
+fn shape(tokens: &[Token], scope: &Scope) -> Export {
+    match tokens {
+        [Token::Ident(_), Token::Punct(':'), body @ ..] => lambda(body, scope),
+        [Token::Ident(_), Token::Punct('@'), Token::Punct('{'), ..] => {
+            match closing(tokens, 2).and_then(|end| tokens.get(end + 1..)) {
+                Some([Token::Punct(':'), body @ ..]) => lambda(body, scope),
+                _ => Export::Unknown,
+            }
+        }
+        [Token::Punct('{'), ..] => {
+            let Some(end) = closing(tokens, 0) else {
+                return Export::Unknown;
+            };
+            match tokens.get(end + 1..) {
+                Some(
+                    [Token::Punct(':'), body @ ..]
+                    | [Token::Punct('@'), Token::Ident(_), Token::Punct(':'), body @ ..],
+                ) => lambda(body, scope),
+                _ => Export::Value,
+            }
+        }
+        [Token::Ident(keyword), ..] if keyword == "let" => {
+            let Some(in_) = closing(tokens, 0) else {
+                return Export::Unknown;
+            };
+            let Some(let_bindings) = tokens
+                .get(1..in_)
+                .and_then(|tokens| bindings(tokens, scope))
+            else {
+                return Export::Unknown;
+            };
+            let mut scope = scope.clone();
+            scope.extend(let_bindings);
+            shape(tokens.get(in_ + 1..).unwrap_or_default(), &scope)
+        }
+        [Token::Ident(keyword), Token::Punct('{'), ..] if keyword == "rec" => Export::Value,
+        [Token::Ident(variable)] => scope.get(variable).copied().unwrap_or(Export::Unknown),
+        [Token::String(_) | Token::Number | Token::Path | Token::Punct('['), ..] => Export::Value,
+        _ => Export::Unknown,
+    }
+}
+
+fn lambda(body: &[Token], scope: &Scope) -> Export {
+    match shape(body, scope) {
+        Export::Function { arity, exact } => Export::Function {
+            arity: arity + 1,
+            exact,
+        },
+        Export::Value => Export::Function {
+            arity: 1,
+            exact: true,
+        },
+        Export::Unknown => Export::Function {
+            arity: 1,
+            exact: false,
+        },
+    }
+}
+
+/// The index of the token closing the bracket, or `let`, at the given index.
+fn closing(tokens: &[Token], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (index, token) in tokens.iter().enumerate().skip(start) {
+        match token {
+            Token::Punct('{' | '(' | '[') => depth += 1,
+            Token::Ident(keyword) if keyword == "let" => depth += 1,
+            Token::Punct('}' | ')' | ']') => depth -= 1,
+            Token::Ident(keyword) if keyword == "in" => depth -= 1,
+            _ => (),
+        }
+        if depth == 0 {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// The index of the `;` ending the binding the tokens start with.
+fn binding_end(tokens: &[Token]) -> Option<usize> {
+    let mut depth = 0;
+    // `with` and `assert` are followed by a `;` which doesn't end the binding.
+    let mut clauses = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Punct('{' | '(' | '[') => depth += 1,
+            Token::Ident(keyword) if keyword == "let" => depth += 1,
+            Token::Punct('}' | ')' | ']') => depth -= 1,
+            Token::Ident(keyword) if keyword == "in" => depth -= 1,
+            Token::Ident(keyword) if depth == 0 && (keyword == "with" || keyword == "assert") => {
+                clauses += 1;
+            }
+            Token::Punct(';') if depth == 0 && clauses > 0 => clauses -= 1,
+            Token::Punct(';') if depth == 0 => return Some(index),
+            _ => (),
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(EcoString),
+    /// A string, with its contents if it has no interpolation or escapes.
+    String(Option<EcoString>),
+    Number,
+    Path,
+    Punct(char),
+    Operator,
+}
+
+fn tokenise(src: &str) -> Option<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = src.char_indices().peekable();
+    while let Some((start, char)) = chars.next() {
+        let rest = src.get(start..)?;
+        match char {
+            _ if char.is_whitespace() => (),
+            '#' => while chars.next_if(|(_, char)| *char != '\n').is_some() {},
+            '/' if rest.starts_with("/*") => {
+                let end = rest.get(2..)?.find("*/")? + 4;
+                skip(&mut chars, start + end);
+            }
+            '"' => {
+                let end = string_end(rest)?;
+                let contents = rest.get(1..end - 1)?;
+                let simple = !contents.contains(['\\', '$']);
+                tokens.push(Token::String(simple.then(|| contents.into())));
+                skip(&mut chars, start + end);
+            }
+            // An interpolated attribute name, such as in `attrs.${name}`.
+            '$' if rest.starts_with("${") => {
+                let end = interpolation_end(rest)?;
+                tokens.push(Token::String(None));
+                skip(&mut chars, start + end);
+            }
+            '\'' if rest.starts_with("''") => {
+                let end = indented_string_end(rest)?;
+                tokens.push(Token::String(None));
+                skip(&mut chars, start + end);
+            }
+            '.' | '~'
+                if ["./", "../", "~/"]
+                    .iter()
+                    .any(|start| rest.starts_with(start)) =>
+            {
+                let end = rest
+                    .find(|char: char| !is_path_char(char))
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Path);
+                skip(&mut chars, start + end);
+            }
+            // A path from the search path, such as `<nixpkgs>`.
+            '<' if search_path_end(rest).is_some() => {
+                tokens.push(Token::Path);
+                skip(&mut chars, start + search_path_end(rest)?);
+            }
+            '.' if rest.starts_with("...") => {
+                tokens.push(Token::Ident("...".into()));
+                skip(&mut chars, start + 3);
+            }
+            _ if char.is_ascii_alphabetic() || char == '_' => {
+                let end = rest
+                    .find(|char: char| !(char.is_ascii_alphanumeric() || "_'-".contains(char)))
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Ident(rest.get(..end)?.into()));
+                skip(&mut chars, start + end);
+            }
+            _ if char.is_ascii_digit() => {
+                let end = rest
+                    .find(|char: char| !(char.is_ascii_digit() || char == '.'))
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Number);
+                skip(&mut chars, start + end);
+            }
+            '{' | '}' | '(' | ')' | '[' | ']' | ';' | ':' | '=' | '@' | ',' | '.' => {
+                // `==` is an operator rather than a binding.
+                if rest.starts_with("==") {
+                    tokens.push(Token::Operator);
+                    skip(&mut chars, start + 2);
+                } else {
+                    tokens.push(Token::Punct(char));
+                }
+            }
+            '+' | '-' | '*' | '/' | '<' | '>' | '!' | '&' | '|' | '?' => {
+                // `!=`, `<=` and `>=` are operators too.
+                _ = chars.next_if(|(_, char)| *char == '=');
+                tokens.push(Token::Operator);
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+fn skip(chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>, end: usize) {
+    while chars.next_if(|(index, _)| *index < end).is_some() {}
+}
+
+fn is_path_char(char: char) -> bool {
+    char.is_ascii_alphanumeric() || "._-+/~".contains(char)
+}
+
+fn search_path_end(src: &str) -> Option<usize> {
+    let end = src.find('>')?;
+    let path = src.get(1..end)?;
+    let is_path =
+        path.starts_with(|char: char| char.is_ascii_alphabetic()) && path.chars().all(is_path_char);
+    is_path.then_some(end + 1)
+}
+
+/// The index just after the end of the double quoted string `src` starts
+/// with.
+fn string_end(src: &str) -> Option<usize> {
+    let mut index = 1;
+    while index < src.len() {
+        let rest = src.get(index..)?;
+        if rest.starts_with('\\') {
+            index += 1 + rest.chars().nth(1).map_or(0, char::len_utf8);
+        } else if rest.starts_with('"') {
+            return Some(index + 1);
+        } else if rest.starts_with("${") {
+            index += interpolation_end(rest)?;
+        } else {
+            index += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    None
+}
+
+/// The index just after the end of the indented string `src` starts with.
+fn indented_string_end(src: &str) -> Option<usize> {
+    let mut index = 2;
+    while index < src.len() {
+        let rest = src.get(index..)?;
+        if rest.starts_with("''$") || rest.starts_with("'''") {
+            index += 3;
+        } else if rest.starts_with("''\\") {
+            index += 3 + rest.chars().nth(3).map_or(0, char::len_utf8);
+        } else if rest.starts_with("''") {
+            return Some(index + 2);
+        } else if rest.starts_with("${") {
+            index += interpolation_end(rest)?;
+        } else {
+            index += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    None
+}
+
+/// The index just after the end of the interpolation `src` starts with.
+fn interpolation_end(src: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut index = 0;
+    while index < src.len() {
+        let rest = src.get(index..)?;
+        if rest.starts_with('"') {
+            index += string_end(rest)?;
+            continue;
+        }
+        if rest.starts_with("''") {
+            index += indented_string_end(rest)?;
+            continue;
+        }
+        match rest.chars().next()? {
+            '{' => depth += 1,
+            '}' if depth == 1 => return Some(index + 1),
+            '}' => depth -= 1,
+            _ => (),
+        }
+        index += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(src: &str, name: &str) -> Option<Export> {
+        exports(src)?.get(name).copied()
+    }
+
+    #[test]
+    fn attribute_set() {
+        let src = r#"{
+  # A comment
+  wibble = x: y: x + y;
+  wobble = { }: "wobble";
+  wubble = builtins.length;
+}"#;
+        assert_eq!(
+            export(src, "wibble"),
+            Some(Export::Function {
+                arity: 2,
+                exact: false
+            })
+        );
+        assert_eq!(
+            export(src, "wobble"),
+            Some(Export::Function {
+                arity: 1,
+                exact: true
+            })
+        );
+        assert_eq!(export(src, "wubble"), Some(Export::Unknown));
+        assert_eq!(export(src, "missing"), None);
+    }
+
+    #[test]
+    fn let_in_attribute_set() {
+        let src = r#"
+let
+  inherit (builtins) length;
+  wibble = { a, b ? 1, ... }@args: with args; a;
+  text = ''
+    ${toString 1} ''${not interpolated}
+  '';
+in { inherit wibble length text; wobble = wibble; }
+"#;
+        assert_eq!(
+            export(src, "wibble"),
+            Some(Export::Function {
+                arity: 1,
+                exact: false
+            })
+        );
+        assert_eq!(export(src, "wobble"), export(src, "wibble"));
+        assert_eq!(export(src, "length"), Some(Export::Unknown));
+        assert_eq!(export(src, "text"), Some(Export::Value));
+    }
+
+    #[test]
+    fn called_let_function() {
+        let src = r#"let
+  mk = representation: let wibble = x: "wibble"; in { inherit wibble representation; };
+in
+mk "cons"
+"#;
+        assert_eq!(
+            export(src, "wibble"),
+            Some(Export::Function {
+                arity: 1,
+                exact: true
+            })
+        );
+        assert_eq!(export(src, "representation"), Some(Export::Unknown));
+    }
+
+    #[test]
+    fn prelude() {
+        let exports = exports(crate::nix::PRELUDE).unwrap();
+        assert!(crate::nix::prelude_exports().all(|name| exports.contains_key(name)));
+    }
+
+    #[test]
+    fn unsupported_shape() {
+        assert_eq!(exports("{ lib }: { wibble = lib.id; }"), None);
+        assert_eq!(exports("import ./other.nix"), None);
+    }
+
+    #[test]
+    fn accepts() {
+        let exact = Export::Function {
+            arity: 2,
+            exact: true,
+        };
+        let inexact = Export::Function {
+            arity: 2,
+            exact: false,
+        };
+        assert!(exact.accepts(2));
+        assert!(!exact.accepts(3));
+        assert!(inexact.accepts(3));
+        assert!(!inexact.accepts(1));
+        assert!(!Export::Value.accepts(1));
+        assert!(Export::Unknown.accepts(5));
+    }
+}
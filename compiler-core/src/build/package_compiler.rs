@@ -1,16 +1,18 @@
 use crate::analyse::{ModuleAnalyzerConstructor, TargetSupport};
 use crate::line_numbers::{self, LineNumbers};
-use crate::type_::PRELUDE_MODULE_NAME;
+use crate::type_::{NIX_MODULE_NAME, PRELUDE_MODULE_NAME};
 use crate::{
     ast::{SrcSpan, TypedModule, UntypedModule},
     build::{
         elixir_libraries::ElixirLibraries,
+        embedded_files,
         native_file_copier::NativeFileCopier,
+        nix_externals,
         package_loader::{CodegenRequired, PackageLoader, StaleTracker},
         Mode, Module, Origin, Outcome, Package, SourceFingerprint, Target,
     },
     codegen::{Erlang, ErlangApp, JavaScript, TypeScriptDeclarations},
-    config::PackageConfig,
+    config::{ListRepresentation, PackageConfig},
     dep_tree, error,
     io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter, Stdio},
     metadata::ModuleEncoder,
@@ -22,6 +24,7 @@ use crate::{
 };
 use askama::Template;
 use ecow::EcoString;
+use itertools::Itertools;
 use std::collections::HashSet;
 use std::{collections::HashMap, fmt::write, time::SystemTime};
 use vec1::Vec1;
@@ -29,7 +32,7 @@ use vec1::Vec1;
 use crate::codegen::Nix;
 use camino::{Utf8Path, Utf8PathBuf};
 
-use super::{ErlangAppCodegenConfiguration, TargetCodegenConfiguration, Telemetry};
+use super::{ErlangAppCodegenConfiguration, Phase, TargetCodegenConfiguration, Telemetry};
 
 #[derive(Debug)]
 pub struct PackageCompiler<'a, IO> {
@@ -56,6 +59,13 @@ pub struct PackageCompiler<'a, IO> {
     pub subprocess_stdio: Stdio,
     pub target_support: TargetSupport,
     pub cached_warnings: CachedWarnings,
+    /// If set, only the modules affected by these files and the modules they
+    /// depend on are type checked, with the rest being skipped.
+    pub changed_files: Option<&'a HashSet<Utf8PathBuf>>,
+    /// If set the hooks declared under `[glistix.hooks]` in the package's
+    /// config are run on the generated files once code generation is done.
+    /// This is only enabled for the root package.
+    pub run_codegen_hooks: bool,
 }
 
 impl<'a, IO> PackageCompiler<'a, IO>
@@ -90,6 +100,8 @@ where
             subprocess_stdio: Stdio::Inherit,
             target_support: TargetSupport::NotEnforced,
             cached_warnings: CachedWarnings::Ignore,
+            changed_files: None,
+            run_codegen_hooks: false,
         }
     }
 
@@ -138,7 +150,10 @@ where
         );
 
         let loaded = if self.compile_modules {
-            match loader.run() {
+            telemetry.phase_started(Phase::Parse, &self.config.name, None);
+            let loaded = loader.run();
+            telemetry.phase_finished();
+            match loaded {
                 Ok(loaded) => loaded,
                 Err(error) => return error.into(),
             }
@@ -146,6 +161,16 @@ where
             Loaded::empty()
         };
 
+        // The names of all the modules of the package, including the cached
+        // ones which are not compiled again, for the generated Nix index.
+        let package_modules = loaded
+            .cached
+            .iter()
+            .map(|module| module.name.clone())
+            .chain(loaded.to_compile.iter().map(|module| module.name.clone()))
+            .sorted()
+            .collect_vec();
+
         // Load the cached modules that have previously been compiled
         for module in loaded.cached.into_iter() {
             // Emit any cached warnings.
@@ -160,7 +185,24 @@ where
             _ = existing_modules.insert(module.name.clone(), module);
         }
 
-        if !loaded.to_compile.is_empty() {
+        let to_compile = match self.changed_files {
+            Some(changed_files) => {
+                let required = required_modules(&loaded.to_compile, changed_files);
+                let (to_compile, skipped): (Vec<_>, Vec<_>) = loaded
+                    .to_compile
+                    .into_iter()
+                    .partition(|module| required.contains(&module.name));
+                if !skipped.is_empty() {
+                    let mut skipped = skipped.into_iter().map(|module| module.name).collect_vec();
+                    skipped.sort();
+                    telemetry.skipped_modules(&skipped);
+                }
+                to_compile
+            }
+            None => loaded.to_compile,
+        };
+
+        if !to_compile.is_empty() {
             // Print that work is being done
             if self.perform_codegen {
                 telemetry.compiling_package(&self.config.name);
@@ -170,17 +212,19 @@ where
         }
 
         // Type check the modules that are new or have changed
-        tracing::info!(count=%loaded.to_compile.len(), "analysing_modules");
+        tracing::info!(count=%to_compile.len(), "analysing_modules");
         let outcome = analyse(
+            &self.io,
             &self.config,
             self.target.target(),
             self.mode,
             &self.ids,
-            loaded.to_compile,
+            to_compile,
             existing_modules,
             warnings,
             self.target_support,
             incomplete_modules,
+            telemetry,
         );
 
         let modules = match outcome {
@@ -190,7 +234,7 @@ where
 
         tracing::debug!("performing_code_generation");
 
-        if let Err(error) = self.perform_codegen(&modules) {
+        if let Err(error) = self.perform_codegen(&modules, &package_modules, telemetry) {
             return error.into();
         }
 
@@ -201,7 +245,11 @@ where
         Outcome::Ok(modules)
     }
 
-    fn compile_erlang_to_beam(&mut self, modules: &HashSet<Utf8PathBuf>) -> Result<(), Error> {
+    fn compile_erlang_to_beam(
+        &mut self,
+        modules: &HashSet<Utf8PathBuf>,
+        telemetry: &dyn Telemetry,
+    ) -> Result<(), Error> {
         if modules.is_empty() {
             tracing::debug!("no_erlang_to_compile");
             return Ok(());
@@ -209,8 +257,14 @@ where
 
         tracing::debug!("compiling_erlang");
 
-        self.io
-            .compile_beam(self.out, self.lib, modules, self.subprocess_stdio)
+        // The BEAM compiler compiles any Elixir modules too, after the Erlang
+        // ones so that they can make use of them.
+        telemetry.phase_started(Phase::CompileBeam, &self.config.name, None);
+        let result = self
+            .io
+            .compile_beam(self.out, self.lib, modules, self.subprocess_stdio);
+        telemetry.phase_finished();
+        result
     }
 
     fn copy_project_native_files(
@@ -276,6 +330,11 @@ where
                 dependencies: module.dependencies.clone(),
                 fingerprint: SourceFingerprint::new(&module.code),
                 line_numbers: module.ast.type_info.line_numbers.clone(),
+                embedded_files: embedded_files::fingerprints(
+                    &self.io,
+                    &module.input_path,
+                    &module.ast,
+                )?,
             };
             self.io.write_bytes(&path, &info.to_binary())?;
 
@@ -294,13 +353,24 @@ where
         Ok(())
     }
 
-    fn perform_codegen(&mut self, modules: &[Module]) -> Result<()> {
+    fn perform_codegen(
+        &mut self,
+        modules: &[Module],
+        package_modules: &[EcoString],
+        telemetry: &dyn Telemetry,
+    ) -> Result<()> {
         if !self.perform_codegen {
             tracing::debug!("skipping_codegen");
             return Ok(());
         }
 
-        match self.target {
+        let target = self.target.target();
+        if !target.has_backend() {
+            return Err(Error::TargetNotEnabled { target });
+        }
+
+        telemetry.phase_started(Phase::Codegen(target), &self.config.name, None);
+        let result = match self.target {
             TargetCodegenConfiguration::JavaScript {
                 emit_typescript_definitions,
                 prelude_location,
@@ -310,18 +380,27 @@ where
                 prelude_location,
             ),
             TargetCodegenConfiguration::Erlang { app_file } => {
-                self.perform_erlang_codegen(modules, app_file.as_ref())
+                self.perform_erlang_codegen(modules, app_file.as_ref(), telemetry)
             }
-            TargetCodegenConfiguration::Nix { prelude_location } => {
-                self.perform_nix_codegen(modules, prelude_location)
-            }
-        }
+            TargetCodegenConfiguration::Nix {
+                prelude_location,
+                list_representation,
+            } => self.perform_nix_codegen(
+                modules,
+                package_modules,
+                prelude_location,
+                *list_representation,
+            ),
+        };
+        telemetry.phase_finished();
+        result
     }
 
     fn perform_erlang_codegen(
         &mut self,
         modules: &[Module],
         app_file_config: Option<&ErlangAppCodegenConfiguration>,
+        telemetry: &dyn Telemetry,
     ) -> Result<(), Error> {
         let mut written = HashSet::new();
         let build_dir = self.out.join(paths::ARTEFACT_DIRECTORY_NAME);
@@ -356,9 +435,17 @@ where
         // version and not the newly compiled version.
         Erlang::new(&build_dir, &include_dir).render(io, modules)?;
 
+        // Hooks are run before compiling to BEAM bytecode so that their
+        // changes are picked up by it.
+        let generated = modules
+            .iter()
+            .map(|module| build_dir.join(module.compiled_erlang_path()))
+            .collect_vec();
+        self.run_codegen_hooks(Target::Erlang, &generated)?;
+
         if self.compile_beam_bytecode {
             written.extend(modules.iter().map(Module::compiled_erlang_path));
-            self.compile_erlang_to_beam(&written)?;
+            self.compile_erlang_to_beam(&written, telemetry)?;
         } else {
             tracing::debug!("skipping_erlang_bytecode_compilation");
         }
@@ -381,6 +468,15 @@ where
         JavaScript::new(&self.out, typescript, prelude_location, self.target_support)
             .render(&self.io, modules)?;
 
+        let mut generated = Vec::with_capacity(modules.len());
+        for module in modules {
+            generated.push(self.out.join(format!("{}.mjs", module.name)));
+            if typescript == TypeScriptDeclarations::Emit {
+                generated.push(self.out.join(format!("{}.d.mts", module.name)));
+            }
+        }
+        self.run_codegen_hooks(Target::JavaScript, &generated)?;
+
         if self.copy_native_files {
             self.copy_project_native_files(&self.out, &mut written)?;
         } else {
@@ -393,11 +489,26 @@ where
     fn perform_nix_codegen(
         &mut self,
         modules: &[Module],
+        package_modules: &[EcoString],
         prelude_location: &Utf8Path,
+        list_representation: ListRepresentation,
     ) -> Result<(), Error> {
         let mut written = HashSet::new();
 
-        Nix::new(&self.out, prelude_location, self.target_support).render(&self.io, modules)?;
+        Nix::new(
+            &self.out,
+            prelude_location,
+            self.target_support,
+            self.config.glistix.optimize,
+            list_representation,
+        )
+        .render(&self.io, modules, package_modules)?;
+
+        let generated = modules
+            .iter()
+            .map(|module| self.out.join(format!("{}.nix", module.name)))
+            .collect_vec();
+        self.run_codegen_hooks(Target::Nix, &generated)?;
 
         if self.copy_native_files {
             self.copy_project_native_files(&self.out, &mut written)?;
@@ -408,6 +519,42 @@ where
         Ok(())
     }
 
+    /// Runs the hooks declared for the given target on the files generated
+    /// for this package's modules, in the order they were declared. Each hook
+    /// receives the paths of the files as extra arguments and may rewrite
+    /// them.
+    fn run_codegen_hooks(&self, target: Target, generated: &[Utf8PathBuf]) -> Result<(), Error> {
+        if !self.run_codegen_hooks || generated.is_empty() {
+            return Ok(());
+        }
+
+        let env = [("GLISTIX_TARGET", target.to_string())];
+        for hook in self.config.glistix.hooks.for_target(target) {
+            tracing::debug!(command = ?hook.command, ?target, "running_codegen_hook");
+            let args = hook
+                .args
+                .iter()
+                .cloned()
+                .chain(generated.iter().map(|path| path.to_string()))
+                .collect_vec();
+            let status = self.io.exec(
+                &hook.command,
+                &args,
+                &env,
+                Some(self.root),
+                self.subprocess_stdio,
+            )?;
+            if status != 0 {
+                return Err(Error::CodegenHook {
+                    command: hook.command.clone(),
+                    target,
+                    status,
+                });
+            }
+        }
+        Ok(())
+    }
+
     fn render_erlang_entrypoint_module(
         &mut self,
         out: &Utf8Path,
@@ -452,6 +599,7 @@ where
 }
 
 fn analyse(
+    io: &impl FileSystemReader,
     package_config: &PackageConfig,
     target: Target,
     mode: Mode,
@@ -461,9 +609,12 @@ fn analyse(
     warnings: &WarningEmitter,
     target_support: TargetSupport,
     incomplete_modules: &mut HashSet<EcoString>,
+    telemetry: &dyn Telemetry,
 ) -> Outcome<Vec<Module>, Error> {
     let mut modules = Vec::with_capacity(parsed_modules.len() + 1);
-    let direct_dependencies = package_config.dependencies_for(mode).expect("Package deps");
+    let direct_dependencies = package_config
+        .dependencies_for_target(mode, target)
+        .expect("Package deps");
 
     // Insert the prelude
     // DUPE: preludeinsertion
@@ -471,6 +622,11 @@ fn analyse(
     // to have one place where we create all this required state for use in each
     // place.
     let _ = module_types.insert(PRELUDE_MODULE_NAME.into(), type_::build_prelude(ids));
+    // The `glistix/nix` module is implemented in Nix, so it can only be
+    // imported on that target.
+    if target == Target::Nix {
+        let _ = module_types.insert(NIX_MODULE_NAME.into(), type_::build_nix_module());
+    }
 
     for UncompiledModule {
         name,
@@ -485,6 +641,7 @@ fn analyse(
     } in parsed_modules
     {
         tracing::debug!(module = ?name, "Type checking");
+        telemetry.phase_started(Phase::Analyse, &package_config.name, Some(&name));
 
         let line_numbers = LineNumbers::new(&code);
 
@@ -500,8 +657,32 @@ fn analyse(
         }
         .infer_module(ast, line_numbers, path.clone());
 
+        // The Nix files referred to by externals are only read when opted
+        // into, as their exports can't always be determined statically.
+        let analysis = match analysis {
+            Outcome::Ok(ast) if package_config.glistix.check_nix_externals => {
+                match Vec1::try_from_vec(nix_externals::arity_errors(io, &name, &path, &ast)) {
+                    Ok(errors) => Outcome::PartialFailure(ast, errors),
+                    Err(_) => Outcome::Ok(ast),
+                }
+            }
+            analysis => analysis,
+        };
+        telemetry.phase_finished();
+
         match analysis {
-            Outcome::Ok(ast) => {
+            Outcome::Ok(mut ast) => {
+                // A Nix external referring to a file that doesn't exist would
+                // only fail once the generated code is evaluated, so we warn
+                // about it here.
+                for warning in nix_externals::missing_external_files(io, &name, &path, &ast) {
+                    ast.type_info.warnings.push(warning.clone());
+                    warnings.emit(Warning::Type {
+                        path: path.clone(),
+                        src: code.clone(),
+                        warning,
+                    });
+                }
                 // Module has compiled successfully. Make sure it isn't marked as incomplete.
                 let _ = incomplete_modules.remove(&name.clone());
                 // Register the types from this module so they can be imported into
@@ -579,6 +760,60 @@ pub(crate) fn module_name(package_path: &Utf8Path, full_module_path: &Utf8Path)
     name.replace("\\", "/").into()
 }
 
+/// The names of the modules that need to be type checked when only the given
+/// files have changed: the modules in those files, the modules that import
+/// them (directly or not), and every module these depend on.
+///
+pub(crate) fn required_modules(
+    modules: &[UncompiledModule],
+    changed_files: &HashSet<Utf8PathBuf>,
+) -> HashSet<EcoString> {
+    let dependencies: HashMap<&EcoString, Vec<&EcoString>> = modules
+        .iter()
+        .map(|module| {
+            let dependencies = module.dependencies.iter().map(|(name, _)| name);
+            (&module.name, dependencies.collect())
+        })
+        .collect();
+
+    let mut affected: HashSet<EcoString> = changed_files
+        .iter()
+        .filter(|path| path.extension() == Some("gleam"))
+        .filter_map(|path| {
+            let directory = ["src", "test"]
+                .into_iter()
+                .find(|directory| path.starts_with(directory))?;
+            Some(module_name(Utf8Path::new(directory), path))
+        })
+        .collect();
+
+    // A module importing an affected module is affected too.
+    loop {
+        let newly_affected = dependencies
+            .iter()
+            .filter(|(name, _)| !affected.contains(**name))
+            .filter(|(_, imports)| imports.iter().any(|import| affected.contains(*import)))
+            .map(|(name, _)| EcoString::clone(name))
+            .collect_vec();
+        if newly_affected.is_empty() {
+            break;
+        }
+        affected.extend(newly_affected);
+    }
+
+    // The affected modules can't be checked without the modules they import.
+    let mut required = affected.clone();
+    let mut to_visit = affected.into_iter().collect_vec();
+    while let Some(name) = to_visit.pop() {
+        for import in dependencies.get(&name).into_iter().flatten() {
+            if required.insert(EcoString::clone(import)) {
+                to_visit.push(EcoString::clone(import));
+            }
+        }
+    }
+    required
+}
+
 #[derive(Debug)]
 pub(crate) enum Input {
     New(UncompiledModule),
@@ -640,6 +875,9 @@ pub(crate) struct CacheMetadata {
     pub dependencies: Vec<(EcoString, SrcSpan)>,
     pub fingerprint: SourceFingerprint,
     pub line_numbers: LineNumbers,
+    /// The files embedded into the module's constants with `@embed_file`,
+    /// along with their fingerprint when the module was compiled.
+    pub embedded_files: Vec<(EcoString, SourceFingerprint)>,
 }
 
 impl CacheMetadata {
@@ -699,3 +937,6 @@ impl CachedWarnings {
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
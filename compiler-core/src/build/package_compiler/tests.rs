@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+use super::PackageCompiler;
+use crate::{
+    build::{Mode, NullTelemetry, Target, TargetCodegenConfiguration},
+    config::{CodegenHook, ListRepresentation, PackageConfig},
+    io::memory::{InMemoryFileSystem, RecordedCommand},
+    uid::UniqueIdGenerator,
+    Error,
+};
+
+fn compile_to_beam(modules: &[&str]) -> Vec<RecordedCommand> {
+    let io = InMemoryFileSystem::new();
+    let config = PackageConfig::default();
+    let target = TargetCodegenConfiguration::Erlang { app_file: None };
+    let mut compiler = PackageCompiler::new(
+        &config,
+        Mode::Dev,
+        Utf8Path::new("/"),
+        Utf8Path::new("/out/lib/wibble"),
+        Utf8Path::new("/out/lib"),
+        &target,
+        UniqueIdGenerator::new(),
+        io.clone(),
+    );
+    let modules: HashSet<Utf8PathBuf> = modules.iter().map(Utf8PathBuf::from).collect();
+    compiler
+        .compile_erlang_to_beam(&modules, &NullTelemetry)
+        .unwrap();
+    io.commands()
+}
+
+#[test]
+fn elixir_modules_are_compiled_with_erlang_modules() {
+    assert_eq!(
+        compile_to_beam(&["wibble.erl", "wobble.ex", "wubble.erl"]),
+        vec![RecordedCommand::CompileBeam {
+            out: Utf8PathBuf::from("/out/lib/wibble"),
+            lib: Utf8PathBuf::from("/out/lib"),
+            modules: vec![
+                Utf8PathBuf::from("wibble.erl"),
+                Utf8PathBuf::from("wobble.ex"),
+                Utf8PathBuf::from("wubble.erl"),
+            ],
+        }]
+    );
+}
+
+#[test]
+fn elixir_only_packages_are_compiled() {
+    assert_eq!(
+        compile_to_beam(&["wobble.ex"]),
+        vec![RecordedCommand::CompileBeam {
+            out: Utf8PathBuf::from("/out/lib/wibble"),
+            lib: Utf8PathBuf::from("/out/lib"),
+            modules: vec![Utf8PathBuf::from("wobble.ex")],
+        }]
+    );
+}
+
+#[test]
+fn nothing_is_compiled_without_modules() {
+    assert_eq!(compile_to_beam(&[]), vec![]);
+}
+
+fn run_nix_hooks(io: &InMemoryFileSystem, generated: &[&str]) -> Result<(), Error> {
+    let mut config = PackageConfig::default();
+    config.glistix.hooks.nix = vec![
+        CodegenHook {
+            command: "nixfmt".into(),
+            args: vec![],
+        },
+        CodegenHook {
+            command: "license-header".into(),
+            args: vec!["--year".into(), "2024".into()],
+        },
+    ];
+    let target = TargetCodegenConfiguration::Nix {
+        prelude_location: Utf8PathBuf::from("/out/lib/wibble/gleam.nix"),
+        list_representation: ListRepresentation::default(),
+    };
+    let mut compiler = PackageCompiler::new(
+        &config,
+        Mode::Dev,
+        Utf8Path::new("/"),
+        Utf8Path::new("/out/lib/wibble"),
+        Utf8Path::new("/out/lib"),
+        &target,
+        UniqueIdGenerator::new(),
+        io.clone(),
+    );
+    compiler.run_codegen_hooks = true;
+    let generated: Vec<Utf8PathBuf> = generated.iter().map(Utf8PathBuf::from).collect();
+    compiler.run_codegen_hooks(Target::Nix, &generated)
+}
+
+#[test]
+fn codegen_hooks_are_run_with_generated_files() {
+    let io = InMemoryFileSystem::new();
+    run_nix_hooks(&io, &["/out/lib/wibble/one.nix", "/out/lib/wibble/two.nix"]).unwrap();
+    let env = vec![("GLISTIX_TARGET".to_string(), "nix".to_string())];
+    assert_eq!(
+        io.commands(),
+        vec![
+            RecordedCommand::Exec {
+                program: "nixfmt".into(),
+                args: vec![
+                    "/out/lib/wibble/one.nix".into(),
+                    "/out/lib/wibble/two.nix".into(),
+                ],
+                env: env.clone(),
+                cwd: Some(Utf8PathBuf::from("/")),
+            },
+            RecordedCommand::Exec {
+                program: "license-header".into(),
+                args: vec![
+                    "--year".into(),
+                    "2024".into(),
+                    "/out/lib/wibble/one.nix".into(),
+                    "/out/lib/wibble/two.nix".into(),
+                ],
+                env,
+                cwd: Some(Utf8PathBuf::from("/")),
+            },
+        ]
+    );
+}
+
+#[test]
+fn codegen_hooks_are_not_run_without_generated_files() {
+    let io = InMemoryFileSystem::new();
+    run_nix_hooks(&io, &[]).unwrap();
+    assert_eq!(io.commands(), vec![]);
+}
+
+#[test]
+fn failing_codegen_hook_is_an_error() {
+    let io = InMemoryFileSystem::new();
+    io.set_exit_status(3);
+    let result = run_nix_hooks(&io, &["/out/lib/wibble/one.nix"]);
+    assert_eq!(
+        result,
+        Err(Error::CodegenHook {
+            command: "nixfmt".into(),
+            target: Target::Nix,
+            status: 3,
+        })
+    );
+    // The hooks after the failing one are not run.
+    assert_eq!(io.commands().len(), 1);
+}
@@ -19,7 +19,7 @@ use crate::{
     build::{module_loader::ModuleLoader, package_compiler::module_name, Module, Origin},
     config::PackageConfig,
     dep_tree,
-    error::{FileIoAction, FileKind, ImportCycleLocationDetails},
+    error::ImportCycleLocationDetails,
     io::{
         gleam_cache_files, gleam_source_files, CommandExecutor, FileSystemReader, FileSystemWriter,
     },
@@ -190,12 +190,15 @@ where
             let path = dir.join(name.as_ref()).with_extension("cache_warnings");
             if self.io.exists(&path) {
                 let bytes = self.io.read_bytes(&path)?;
-                module.warnings = bincode::deserialize(&bytes).map_err(|e| Error::FileIo {
-                    kind: FileKind::File,
-                    action: FileIoAction::Parse,
-                    path,
-                    err: Some(e.to_string()),
-                })?;
+                // Warnings cached by a compiler version with different
+                // warnings can't be decoded, and are left out rather than
+                // recompiling the module for them.
+                match bincode::deserialize(&bytes) {
+                    Ok(warnings) => module.warnings = warnings,
+                    Err(error) => {
+                        tracing::debug!(%path, %error, "unreadable_cached_warnings");
+                    }
+                }
             }
         }
 
@@ -5,7 +5,7 @@ use crate::{
         project_compiler, telemetry::Telemetry, Mode, Module, Origin, Package, Target,
     },
     codegen::{self, ErlangApp},
-    config::PackageConfig,
+    config::{ListRepresentation, PackageConfig},
     dep_tree,
     error::{FileIoAction, FileKind},
     io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter, Stdio},
@@ -14,7 +14,7 @@ use crate::{
     paths::{self, ProjectPaths},
     type_::{self, ModuleFunction},
     uid::UniqueIdGenerator,
-    version::COMPILER_VERSION,
+    version::{COMPILER_VERSION, GLISTIX_VERSION},
     warning::{self, WarningEmitter, WarningEmitterIO},
     Error, Result, Warning,
 };
@@ -51,7 +51,7 @@ const ELIXIR_EXECUTABLE: &str = "elixir";
 #[cfg(target_os = "windows")]
 const ELIXIR_EXECUTABLE: &str = "elixir.bat";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Options {
     pub mode: Mode,
     pub target: Option<Target>,
@@ -60,6 +60,10 @@ pub struct Options {
     pub warnings_as_errors: bool,
     pub root_target_support: TargetSupport,
     pub no_print_progress: bool,
+    /// If set, only the root package's modules in these files, the modules
+    /// that depend on them, and the modules they depend on are type checked.
+    /// Paths are relative to the root package.
+    pub changed_files: Option<HashSet<Utf8PathBuf>>,
 }
 
 #[derive(Debug)]
@@ -92,10 +96,14 @@ impl Built {
             .map(|minimum_required_version| minimum_required_version.clone())
             .unwrap_or(Version::new(0, 1, 0))
     }
+
+    pub fn module_interfaces(&self) -> &im::HashMap<EcoString, type_::ModuleInterface> {
+        &self.module_interfaces
+    }
 }
 
 #[derive(Debug)]
-pub struct ProjectCompiler<IO> {
+pub struct ProjectCompiler<'a, IO> {
     // The gleam.toml config for the root package of the project
     pub(crate) config: PackageConfig,
     pub(crate) packages: HashMap<String, ManifestPackage>,
@@ -106,7 +114,7 @@ pub struct ProjectCompiler<IO> {
     /// successful compilation.
     incomplete_modules: HashSet<EcoString>,
     warnings: WarningEmitter,
-    telemetry: &'static dyn Telemetry,
+    telemetry: &'a dyn Telemetry,
     options: Options,
     paths: ProjectPaths,
     ids: UniqueIdGenerator,
@@ -119,7 +127,7 @@ pub struct ProjectCompiler<IO> {
 // TODO: test that tests cannot be imported into src
 // TODO: test that dep cycles are not allowed between packages
 
-impl<IO> ProjectCompiler<IO>
+impl<'a, IO> ProjectCompiler<'a, IO>
 where
     IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone,
 {
@@ -127,7 +135,7 @@ where
         config: PackageConfig,
         options: Options,
         packages: Vec<ManifestPackage>,
-        telemetry: &'static dyn Telemetry,
+        telemetry: &'a dyn Telemetry,
         warning_emitter: Rc<dyn WarningEmitterIO>,
         paths: ProjectPaths,
         io: IO,
@@ -166,6 +174,16 @@ where
         self.options.target.unwrap_or(self.config.target)
     }
 
+    /// The representation of lists in the Nix code generated for every
+    /// package, chosen by the root package.
+    pub fn list_representation(&self) -> ListRepresentation {
+        self.config
+            .glistix
+            .nix
+            .list_representation
+            .unwrap_or_default()
+    }
+
     /// Compiles all packages in the project and returns the compiled
     /// information from the root package
     pub fn compile(mut self) -> Result<Built> {
@@ -211,18 +229,20 @@ where
             .map(|modules| Package { config, modules })
     }
 
-    /// Checks that version file found in the build directory matches the
-    /// current version of gleam. If not, we will clear the build directory
-    /// before continuing. This will ensure that upgrading gleam will not leave
-    /// one with confusing or hard to debug states.
+    /// Checks that the version file found in the build directory was written
+    /// by this version of Glistix, with the same formats. If not, we will
+    /// clear the build directory before continuing. This will ensure that
+    /// upgrading glistix will not leave one with confusing or hard to debug
+    /// states.
     pub fn check_gleam_version(&self) -> Result<(), Error> {
         let build_path = self
             .paths
             .build_directory_for_target(self.mode(), self.target());
         let version_path = self.paths.build_gleam_version(self.mode(), self.target());
+        let contents = build_version_file(self.target(), self.list_representation());
         if self.io.is_file(&version_path) {
             let version = self.io.read(&version_path)?;
-            if version == COMPILER_VERSION {
+            if version == contents {
                 return Ok(());
             }
         }
@@ -234,7 +254,7 @@ where
         // Recreate build directory with new updated version file
         self.io.mkdir(&build_path)?;
         self.io
-            .write(&version_path, COMPILER_VERSION)
+            .write(&version_path, &contents)
             .map_err(|e| Error::FileIo {
                 action: FileIoAction::WriteTo,
                 kind: FileKind::File,
@@ -245,9 +265,14 @@ where
 
     pub fn compile_dependencies(&mut self) -> Result<Vec<Module>, Error> {
         let sequence = order_packages(&self.packages)?;
+        let skipped = packages_skipped_on_target(&self.config, &self.packages, self.target());
         let mut modules = vec![];
 
         for name in sequence {
+            if skipped.contains(&name) {
+                tracing::debug!(package = %name, "skipping_package_of_other_target");
+                continue;
+            }
             let compiled = self.load_cache_or_compile_package(&name)?;
             modules.extend(compiled);
         }
@@ -515,10 +540,27 @@ where
         };
         let config_path = package_root.join("gleam.toml");
         let config = PackageConfig::read(config_path, &self.io)?;
+        self.ensure_compatible_list_representation(&config)?;
         self.compile_gleam_package(&config, false, package_root)
             .into_result()
     }
 
+    /// The Nix externals of a dependency rely on the representation of lists
+    /// it declares, or on cons cells if it doesn't declare one, so it can't
+    /// be compiled with the other one.
+    fn ensure_compatible_list_representation(&self, config: &PackageConfig) -> Result<(), Error> {
+        let expected = self.list_representation();
+        let found = config.glistix.nix.list_representation.unwrap_or_default();
+        if self.target() == Target::Nix && found != expected {
+            return Err(Error::IncompatibleListRepresentation {
+                package: config.name.clone(),
+                expected,
+                found,
+            });
+        }
+        Ok(())
+    }
+
     fn compile_gleam_package(
         &mut self,
         config: &PackageConfig,
@@ -559,6 +601,7 @@ where
             Target::Nix => super::TargetCodegenConfiguration::Nix {
                 // This path is relative to each package output directory
                 prelude_location: Utf8PathBuf::from("../prelude.nix"),
+                list_representation: self.list_representation(),
             },
         };
 
@@ -574,6 +617,7 @@ where
         );
         compiler.write_metadata = true;
         compiler.write_entrypoint = is_root;
+        compiler.run_codegen_hooks = is_root;
         compiler.perform_codegen = self.options.codegen.should_codegen(is_root);
         compiler.compile_beam_bytecode = self.options.codegen.should_codegen(is_root);
         compiler.compile_modules = !(self.options.compile == Compile::DepsOnly && is_root);
@@ -592,6 +636,11 @@ where
             // unaccessible so long as they are not used by the root package.
             TargetSupport::NotEnforced
         };
+        compiler.changed_files = if is_root {
+            self.options.changed_files.as_ref()
+        } else {
+            None
+        };
         compiler.cached_warnings = if is_root {
             CachedWarnings::Use
         } else {
@@ -610,6 +659,27 @@ where
     }
 }
 
+/// The contents of the version file of a build directory: the versions of
+/// Glistix and of the Gleam compiler it is based on which wrote the build,
+/// followed by the versions of the formats its caches and generated code were
+/// written in. On Nix, these include the
+/// representation of lists, so that switching it regenerates every package.
+pub(crate) fn build_version_file(
+    target: Target,
+    list_representation: ListRepresentation,
+) -> String {
+    let mut contents = format!(
+        "{GLISTIX_VERSION}\ngleam {COMPILER_VERSION}\ninterface-format {}\n",
+        metadata::FORMAT_VERSION
+    );
+    if target == Target::Nix {
+        let representation = crate::nix::REPRESENTATION_VERSION;
+        contents.push_str(&format!("nix-representation {representation}\n"));
+        contents.push_str(&format!("nix-lists {}\n", list_representation.as_str()));
+    }
+    contents
+}
+
 fn order_packages(packages: &HashMap<String, ManifestPackage>) -> Result<Vec<EcoString>, Error> {
     dep_tree::toposort_deps(
         packages
@@ -634,6 +704,44 @@ fn order_packages(packages: &HashMap<String, ManifestPackage>) -> Result<Vec<Eco
     .map_err(convert_deps_tree_error)
 }
 
+/// The packages of the manifest which are only required by direct
+/// dependencies restricted to targets other than the given one, and so are
+/// not compiled for it.
+pub(crate) fn packages_skipped_on_target(
+    config: &PackageConfig,
+    packages: &HashMap<String, ManifestPackage>,
+    target: Target,
+) -> HashSet<EcoString> {
+    let (used, unused): (Vec<_>, Vec<_>) = config
+        .dependencies
+        .keys()
+        .chain(config.dev_dependencies.keys())
+        .partition(|name| config.dependency_supports(name, target));
+    let used = required_packages(used, packages);
+    required_packages(unused, packages)
+        .difference(&used)
+        .cloned()
+        .collect()
+}
+
+/// The given packages and all the packages they require, directly or not.
+fn required_packages(
+    names: Vec<&EcoString>,
+    packages: &HashMap<String, ManifestPackage>,
+) -> HashSet<EcoString> {
+    let mut required = HashSet::new();
+    let mut queue: Vec<EcoString> = names.into_iter().cloned().collect();
+    while let Some(name) = queue.pop() {
+        if !required.insert(name.clone()) {
+            continue;
+        }
+        if let Some(package) = packages.get(name.as_str()) {
+            queue.extend(package.requirements.iter().cloned());
+        }
+    }
+    required
+}
+
 fn convert_deps_tree_error(e: dep_tree::Error) -> Error {
     match e {
         dep_tree::Error::Cycle(packages) => Error::PackageCycle { packages },
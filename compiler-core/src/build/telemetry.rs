@@ -1,9 +1,11 @@
 use std::{
-    fmt::Debug,
+    fmt::{Debug, Display},
     time::{Duration, Instant},
 };
 
-use crate::Warning;
+use ecow::EcoString;
+
+use crate::{build::Target, Warning};
 
 pub trait Telemetry: Debug {
     fn waiting_for_build_directory_lock(&self);
@@ -15,6 +17,43 @@ pub trait Telemetry: Debug {
     fn compiling_package(&self, name: &str);
     fn checked_package(&self, duration: Duration);
     fn checking_package(&self, name: &str);
+    /// Called with the modules of a package that were not type checked as
+    /// they aren't affected by the files that changed.
+    fn skipped_modules(&self, modules: &[EcoString]);
+    /// Called when a phase of compilation begins. Every call is matched by a
+    /// later call to `phase_finished`, and phases may be nested within each
+    /// other.
+    fn phase_started(&self, phase: Phase, package: &str, module: Option<&str>);
+    fn phase_finished(&self);
+}
+
+/// A phase of compilation whose duration can be measured by the telemetry.
+///
+/// The compiler itself doesn't read the clock, as it isn't available on all
+/// platforms it runs on.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    DownloadDependencies,
+    Parse,
+    Analyse,
+    /// Includes compiling the generated Erlang to BEAM bytecode, if any.
+    Codegen(Target),
+    CompileBeam,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::DownloadDependencies => f.write_str("download dependencies"),
+            Phase::Parse => f.write_str("parse"),
+            Phase::Analyse => f.write_str("analyse"),
+            Phase::Codegen(Target::Erlang) => f.write_str("codegen (erlang)"),
+            Phase::Codegen(Target::JavaScript) => f.write_str("codegen (javascript)"),
+            Phase::Codegen(Target::Nix) => f.write_str("codegen (nix)"),
+            Phase::CompileBeam => f.write_str("compile beam"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -29,5 +68,8 @@ impl Telemetry for NullTelemetry {
     fn compiling_package(&self, _name: &str) {}
     fn checked_package(&self, _duration: Duration) {}
     fn checking_package(&self, _name: &str) {}
+    fn skipped_modules(&self, _modules: &[EcoString]) {}
     fn packages_downloaded(&self, _start: Instant, _count: usize) {}
+    fn phase_started(&self, _phase: Phase, _package: &str, _module: Option<&str>) {}
+    fn phase_finished(&self) {}
 }
@@ -1,6 +1,28 @@
-use crate::{manifest::ManifestPackage, Error};
+use std::{collections::HashSet, rc::Rc, time::SystemTime};
 
-use super::project_compiler::{usable_build_tools, BuildTool};
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+
+use crate::{
+    ast::SrcSpan,
+    config::{ListRepresentation, PackageConfig},
+    io::{memory::InMemoryFileSystem, FileSystemReader, FileSystemWriter},
+    manifest::ManifestPackage,
+    parse::extra::ModuleExtra,
+    requirement::Requirement,
+    version::{COMPILER_VERSION, GLISTIX_VERSION},
+    warning::VectorWarningEmitterIO,
+    Error,
+};
+
+use super::{
+    in_memory,
+    package_compiler::{required_modules, UncompiledModule},
+    project_compiler::{
+        build_version_file, packages_skipped_on_target, usable_build_tools, BuildTool,
+    },
+    Origin, Target,
+};
 
 #[test]
 fn usable_build_tool_unknown() {
@@ -55,3 +77,156 @@ fn usable_build_tool_mix_then_rebar3() {
         Ok(vec![BuildTool::Mix, BuildTool::Rebar3])
     )
 }
+
+#[test]
+fn build_version_file_records_versions_and_formats() {
+    let erlang = build_version_file(Target::Erlang, ListRepresentation::Cons);
+    assert!(erlang.starts_with(&format!("{GLISTIX_VERSION}\ngleam {COMPILER_VERSION}\n")));
+    assert!(erlang.contains("interface-format"));
+    assert!(!erlang.contains("nix-representation"));
+    let nix = build_version_file(Target::Nix, ListRepresentation::Cons);
+    assert!(nix.contains("nix-representation"));
+    assert!(nix.contains("nix-lists cons"));
+}
+
+#[test]
+fn build_version_file_depends_on_list_representation() {
+    assert_ne!(
+        build_version_file(Target::Nix, ListRepresentation::Cons),
+        build_version_file(Target::Nix, ListRepresentation::Native)
+    );
+}
+
+fn manifest_package(name: &str, requirements: &[&str]) -> (String, ManifestPackage) {
+    let package = ManifestPackage {
+        name: name.into(),
+        requirements: requirements.iter().map(|name| (*name).into()).collect(),
+        ..Default::default()
+    };
+    (name.into(), package)
+}
+
+#[test]
+fn packages_skipped_on_target_are_only_required_by_other_targets() {
+    let mut config = PackageConfig::default();
+    config.dependencies = [
+        ("stdlib".into(), Requirement::hex("~> 1.0")),
+        ("beam_only".into(), Requirement::hex("~> 1.0")),
+    ]
+    .into();
+    config.dev_dependencies = [("nix_test".into(), Requirement::hex("~> 1.0"))].into();
+    config.dependency_targets = [
+        ("beam_only".into(), vec![Target::Erlang]),
+        ("nix_test".into(), vec![Target::Nix]),
+    ]
+    .into();
+    let packages = [
+        manifest_package("stdlib", &[]),
+        manifest_package("beam_only", &["stdlib", "otp"]),
+        manifest_package("otp", &[]),
+        manifest_package("nix_test", &["stdlib"]),
+    ]
+    .into();
+
+    let skipped = packages_skipped_on_target(&config, &packages, Target::Nix);
+    assert_eq!(skipped, ["beam_only".into(), "otp".into()].into());
+
+    let skipped = packages_skipped_on_target(&config, &packages, Target::Erlang);
+    assert_eq!(skipped, ["nix_test".into()].into());
+
+    let skipped = packages_skipped_on_target(&config, &packages, Target::JavaScript);
+    assert_eq!(
+        skipped,
+        ["beam_only".into(), "nix_test".into(), "otp".into()].into()
+    );
+}
+
+fn uncompiled_module(name: &str, dependencies: &[&str]) -> UncompiledModule {
+    let path = Utf8PathBuf::from(format!("/src/{name}.gleam"));
+    let ast = crate::parse::parse_module(path.clone(), "", &crate::warning::WarningEmitter::null())
+        .expect("empty module")
+        .module;
+    UncompiledModule {
+        path,
+        name: name.into(),
+        code: "".into(),
+        mtime: SystemTime::UNIX_EPOCH,
+        origin: Origin::Src,
+        package: "wibble".into(),
+        dependencies: dependencies
+            .iter()
+            .map(|name| (EcoString::from(*name), SrcSpan::default()))
+            .collect(),
+        ast,
+        extra: ModuleExtra::new(),
+    }
+}
+
+fn required(modules: &[UncompiledModule], changed_files: &[&str]) -> Vec<String> {
+    let changed_files: HashSet<Utf8PathBuf> = changed_files
+        .iter()
+        .map(|path| Utf8PathBuf::from(*path))
+        .collect();
+    let mut required = required_modules(modules, &changed_files)
+        .into_iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    required.sort();
+    required
+}
+
+#[test]
+fn required_modules_include_dependents_and_their_dependencies() {
+    let modules = [
+        uncompiled_module("one", &[]),
+        uncompiled_module("two", &["one"]),
+        uncompiled_module("three", &["two", "four"]),
+        uncompiled_module("four", &[]),
+        uncompiled_module("five", &[]),
+    ];
+    assert_eq!(
+        required(&modules, &["src/two.gleam"]),
+        vec!["four", "one", "three", "two"]
+    );
+}
+
+#[test]
+fn required_modules_ignore_files_that_are_not_modules() {
+    let modules = [
+        uncompiled_module("one", &[]),
+        uncompiled_module("nested/two", &["one"]),
+    ];
+    assert_eq!(
+        required(
+            &modules,
+            &["README.md", "priv/data.gleam", "src/nested/two.gleam"]
+        ),
+        vec!["nested/two", "one"]
+    );
+}
+
+#[test]
+fn required_modules_include_dependents_of_changed_cached_modules() {
+    let modules = [
+        uncompiled_module("two", &["one"]),
+        uncompiled_module("three", &[]),
+    ];
+    assert_eq!(required(&modules, &["src/one.gleam"]), vec!["one", "two"]);
+}
+
+#[test]
+fn compile_package_in_memory_to_nix() {
+    let fs = InMemoryFileSystem::new();
+    fs.write(
+        Utf8Path::new("/src/wibble/wobble.gleam"),
+        "pub fn main() { 1 }",
+    )
+    .unwrap();
+    let warnings = Rc::new(VectorWarningEmitterIO::new());
+
+    in_memory::compile_package(fs.clone(), Target::Nix, warnings.clone()).unwrap();
+
+    let output = fs.read(Utf8Path::new("/build/wibble/wobble.nix")).unwrap();
+    assert!(output.contains("main ="));
+    assert!(warnings.take().is_empty());
+}
@@ -10,12 +10,13 @@ use crate::{
         UntypedClauseGuard, UntypedExpr, UntypedFunction, UntypedModuleConstant, UntypedPattern,
         UntypedStatement,
     },
+    dep_tree,
     type_::Error,
     Result,
 };
 use itertools::Itertools;
 use petgraph::stable_graph::NodeIndex;
-use petgraph::{stable_graph::StableGraph, Directed};
+use petgraph::{stable_graph::StableGraph, Directed, Direction};
 
 #[derive(Debug, Default)]
 struct CallGraphBuilder<'a> {
@@ -502,6 +503,10 @@ pub fn into_dependency_order(
     // Consume the grapher to get the graph
     let graph = grapher.into_graph();
 
+    // Unlike functions, constants are evaluated as soon as the module is
+    // loaded, so they cannot depend on each other in a cycle.
+    ensure_constants_are_acyclic(&graph, functions.len(), &constants)?;
+
     // Determine the order in which the functions should be compiled by looking
     // at which other functions they depend on.
     let indices = crate::graph::into_dependency_order(graph);
@@ -534,3 +539,50 @@ pub fn into_dependency_order(
 
     Ok(ordered)
 }
+
+/// Returns an error if some constants reference each other in a cycle. The
+/// constants' nodes are expected to come after the functions' ones in the
+/// graph, in the same order as the constants themselves.
+///
+/// A constant referencing itself is not a cycle: its name can only refer to an
+/// imported value there.
+///
+fn ensure_constants_are_acyclic(
+    graph: &StableGraph<(), (), Directed>,
+    functions_count: usize,
+    constants: &[UntypedModuleConstant],
+) -> Result<(), Error> {
+    let constant_name = |index: NodeIndex| {
+        let constant = constants.get(index.index().checked_sub(functions_count)?)?;
+        Some(constant.name.clone())
+    };
+
+    let dependencies = constants
+        .iter()
+        .enumerate()
+        .map(|(index, constant)| {
+            let node = NodeIndex::new(functions_count + index);
+            let referenced = graph
+                .neighbors_directed(node, Direction::Outgoing)
+                .filter(|referenced| *referenced != node)
+                .filter_map(constant_name)
+                .collect_vec();
+            (constant.name.clone(), referenced)
+        })
+        .collect_vec();
+
+    match dep_tree::toposort_deps(dependencies) {
+        Ok(_) => Ok(()),
+        Err(dep_tree::Error::Cycle(cycle)) => {
+            let last = cycle.last().expect("Cycle should not be empty");
+            let constant = constants
+                .iter()
+                .find(|constant| constant.name == *last)
+                .expect("Could not find constant for cycle");
+            Err(Error::RecursiveConstant {
+                location: constant.location,
+                cycle,
+            })
+        }
+    }
+}
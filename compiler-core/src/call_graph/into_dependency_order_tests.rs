@@ -33,12 +33,14 @@ fn parse_and_order(
             return_annotation: None,
             publicity: Publicity::Public,
             deprecation: Deprecation::NotDeprecated,
+            since: None,
             end_position: src.len() as u32,
             return_type: (),
             documentation: None,
             external_erlang: None,
             external_javascript: None,
             external_nix: None,
+            external_any: None,
             implementations: Implementations {
                 gleam: true,
                 uses_erlang_externals: true,
@@ -47,7 +49,9 @@ fn parse_and_order(
                 can_run_on_erlang: true,
                 can_run_on_javascript: true,
                 can_run_on_nix: true,
+                nix_blocker: None,
             },
+            inline: false,
         })
         .collect_vec();
     let constants = constants
@@ -70,9 +74,13 @@ fn parse_and_order(
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: true,
+                    nix_blocker: None,
                 },
                 type_: (),
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
+                embedded_file: None,
+                strict: false,
             }
         })
         .collect_vec();
@@ -692,3 +700,28 @@ fn bug_2275() {
         vec![vec!["two"], vec!["one"]]
     );
 }
+
+#[test]
+fn constants_cycle() {
+    let constants = [("a", r#"b"#), ("b", r#"c"#), ("c", r#"a"#)];
+    assert!(matches!(
+        parse_and_order([].as_slice(), constants.as_slice()),
+        Err(Error::RecursiveConstant { .. })
+    ));
+}
+
+#[test]
+fn constant_referencing_itself_is_not_a_cycle() {
+    let constants = [("a", r#"a"#)];
+    assert_eq!(
+        parse_and_order([].as_slice(), constants.as_slice()).unwrap(),
+        vec![vec!["a"]]
+    );
+}
+
+#[test]
+fn constants_and_functions_cycle() {
+    let functions = [("a", [].as_slice(), r#"b"#)];
+    let constants = [("b", r#"a"#)];
+    assert!(parse_and_order(functions.as_slice(), constants.as_slice()).is_ok());
+}
@@ -1,7 +1,7 @@
 use crate::{
     analyse::TargetSupport,
     build::{ErlangAppCodegenConfiguration, Module},
-    config::PackageConfig,
+    config::{ListRepresentation, PackageConfig},
     erlang,
     io::FileSystemWriter,
     javascript,
@@ -12,6 +12,7 @@ use itertools::Itertools;
 use std::fmt::Debug;
 
 use camino::Utf8Path;
+use ecow::EcoString;
 
 /// A code generator that creates a .erl Erlang module and record header files
 /// for each Gleam module in the package.
@@ -257,6 +258,8 @@ pub struct Nix<'a> {
     output_directory: &'a Utf8Path,
     prelude_location: &'a Utf8Path,
     target_support: TargetSupport,
+    optimize: u8,
+    list_representation: ListRepresentation,
 }
 
 impl<'a> Nix<'a> {
@@ -264,28 +267,58 @@ impl<'a> Nix<'a> {
         output_directory: &'a Utf8Path,
         prelude_location: &'a Utf8Path,
         target_support: TargetSupport,
+        optimize: u8,
+        list_representation: ListRepresentation,
     ) -> Self {
         Self {
             prelude_location,
             output_directory,
             target_support,
+            optimize,
+            list_representation,
         }
     }
 
-    pub fn render(&self, writer: &impl FileSystemWriter, modules: &[Module]) -> Result<()> {
+    /// Generates the given modules, along with the index of all the modules
+    /// of the package.
+    ///
+    pub fn render(
+        &self,
+        writer: &impl FileSystemWriter,
+        modules: &[Module],
+        package_modules: &[EcoString],
+    ) -> Result<()> {
         for module in modules {
             let nix_name = module.name.clone();
             self.nix_module(writer, module, &nix_name)?
         }
         self.write_prelude(writer)?;
+        self.write_package_index(writer, package_modules)?;
         Ok(())
     }
 
+    fn write_package_index(
+        &self,
+        writer: &impl FileSystemWriter,
+        package_modules: &[EcoString],
+    ) -> Result<()> {
+        // The index would take the place of a top-level module named after it.
+        let index_module = nix::PACKAGE_INDEX_FILE.trim_end_matches(".nix");
+        let index_module_exists = package_modules
+            .iter()
+            .any(|name| name.as_str() == index_module);
+        if package_modules.is_empty() || index_module_exists {
+            tracing::debug!("skipping_nix_package_index");
+            return Ok(());
+        }
+
+        let path = self.output_directory.join(nix::PACKAGE_INDEX_FILE);
+        writer.write(&path, &nix::package_index(package_modules))
+    }
+
     fn write_prelude(&self, writer: &impl FileSystemWriter) -> Result<()> {
-        let rexport = format!(
-            "builtins.import {}\n",
-            nix::syntax::path(self.prelude_location.as_str())
-        );
+        let rexport =
+            nix::prelude_reexport(self.prelude_location.as_str(), self.list_representation);
         let prelude_path = &self.output_directory.join("gleam.nix");
 
         // This check skips unnecessary `gleam.nix` writes which confuse
@@ -294,6 +327,11 @@ impl<'a> Nix<'a> {
             writer.write(prelude_path, &rexport)?;
         }
 
+        let nix_module_path = &self.output_directory.join("glistix/nix.nix");
+        if !writer.exists(nix_module_path) {
+            writer.write(nix_module_path, nix::NIX_MODULE)?;
+        }
+
         Ok(())
     }
 
@@ -312,8 +350,13 @@ impl<'a> Nix<'a> {
             &module.input_path,
             &module.code,
             self.target_support,
+            self.optimize,
+            self.list_representation,
         );
         tracing::debug!(name = ?nix_name, "Generated nix module");
         writer.write(&path, &output?)
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,43 @@
+use camino::Utf8Path;
+
+use super::{JavaScript, Nix, TypeScriptDeclarations};
+use crate::{
+    analyse::TargetSupport,
+    config::ListRepresentation,
+    io::{memory::InMemoryFileSystem, FileSystemReader, FileSystemWriter},
+    nix,
+};
+
+#[test]
+fn nix_module_is_written_with_the_nix_prelude() {
+    let fs = InMemoryFileSystem::new();
+    let nix = Nix::new(
+        Utf8Path::new("/out"),
+        Utf8Path::new("../prelude.nix"),
+        TargetSupport::NotEnforced,
+        0,
+        ListRepresentation::default(),
+    );
+    nix.render(&fs, &[], &[]).unwrap();
+
+    assert!(fs.is_file(Utf8Path::new("/out/gleam.nix")));
+    assert_eq!(
+        fs.read(Utf8Path::new("/out/glistix/nix.nix")).unwrap(),
+        nix::NIX_MODULE
+    );
+}
+
+#[test]
+fn nix_module_is_not_written_with_the_javascript_prelude() {
+    let fs = InMemoryFileSystem::new();
+    let javascript = JavaScript::new(
+        Utf8Path::new("/out"),
+        TypeScriptDeclarations::None,
+        Utf8Path::new("../prelude.mjs"),
+        TargetSupport::NotEnforced,
+    );
+    javascript.render(&fs, &[]).unwrap();
+
+    assert!(fs.is_file(Utf8Path::new("/out/gleam.mjs")));
+    assert!(!fs.exists(Utf8Path::new("/out/glistix/nix.nix")));
+}
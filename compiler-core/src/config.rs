@@ -9,10 +9,12 @@ use ecow::EcoString;
 use globset::{Glob, GlobSetBuilder};
 use hexpm::version::{self, Version};
 use http::Uri;
+use itertools::Itertools;
 use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
 use std::fmt::{self};
 use std::marker::PhantomData;
+use strum::IntoEnumIterator;
 
 #[cfg(test)]
 use crate::manifest::ManifestPackage;
@@ -86,10 +88,19 @@ pub struct PackageConfig {
     pub description: EcoString,
     #[serde(default, alias = "docs")]
     pub documentation: Docs,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialise_dependencies")]
     pub dependencies: Dependencies,
+    /// Read from the `policy` table of the `[dependencies]` section by
+    /// `PackageConfig::from_toml`.
+    #[serde(skip)]
+    pub dependency_policy: DependencyPolicy,
     #[serde(default, rename = "dev-dependencies")]
     pub dev_dependencies: Dependencies,
+    /// The targets the dependencies with a `targets` key are restricted to,
+    /// read by `PackageConfig::from_toml`. Dependencies without one are used
+    /// on every target.
+    #[serde(skip)]
+    pub dependency_targets: HashMap<EcoString, Vec<Target>>,
     #[serde(default)]
     pub repository: Repository,
     #[serde(default)]
@@ -98,11 +109,17 @@ pub struct PackageConfig {
     pub erlang: ErlangConfig,
     #[serde(default)]
     pub javascript: JavaScriptConfig,
+    #[serde(default)]
+    pub format: FormatConfig,
     #[serde(default = "erlang_target")]
     pub target: Target,
     #[serde(default)]
     pub internal_modules: Option<Vec<Glob>>,
     #[serde(default)]
+    pub registry: Option<RegistryConfig>,
+    #[serde(default)]
+    pub hex: HexConfig,
+    #[serde(default)]
     pub glistix: GlistixConfig,
 }
 
@@ -135,7 +152,191 @@ where
     }
 }
 
+/// The key of the `[dependencies.policy]` table, which sits among the
+/// dependencies of the package.
+const DEPENDENCY_POLICY_KEY: &str = "policy";
+
+/// A `[dependencies]` or `[dev-dependencies]` section, which holds more than
+/// the requirements of the dependencies.
+#[derive(Debug, Default)]
+struct DependencySection {
+    requirements: Dependencies,
+    /// The `policy` table, unless `policy` is the name of a dependency.
+    policy: Option<DependencyPolicy>,
+    /// The targets the dependencies with a `targets` key are restricted to,
+    /// as in `{ version = "~> 1.0", targets = ["erlang"] }`.
+    targets: HashMap<EcoString, Vec<Target>>,
+}
+
+impl<'de> Deserialize<'de> for DependencySection {
+    fn deserialize<D>(deserialiser: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialiser.deserialize_map(DependencySectionVisitor)
+    }
+}
+
+struct DependencySectionVisitor;
+
+impl<'de> serde::de::Visitor<'de> for DependencySectionVisitor {
+    type Value = DependencySection;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a table of dependencies")
+    }
+
+    fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+    where
+        M: serde::de::MapAccess<'de>,
+    {
+        let mut section = DependencySection::default();
+        while let Some(name) = map.next_key::<EcoString>()? {
+            // Only the entry named `policy` can be the dependency policy, and
+            // only when it isn't a requirement.
+            let entry = if name == DEPENDENCY_POLICY_KEY {
+                map.next_value()?
+            } else {
+                DependencyEntry::Dependency(map.next_value()?)
+            };
+            match entry {
+                DependencyEntry::Policy(policy) => section.policy = Some(policy),
+                DependencyEntry::Dependency(dependency) => {
+                    if let Some(targets) = dependency.targets {
+                        let _ = section.targets.insert(name.clone(), targets);
+                    }
+                    let _ = section.requirements.insert(name, dependency.requirement);
+                }
+            }
+        }
+        Ok(section)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DependencyEntry {
+    Dependency(DependencyRequirement),
+    Policy(DependencyPolicy),
+}
+
+/// The requirement of a dependency, along with the targets it is used on.
+struct DependencyRequirement {
+    requirement: Requirement,
+    targets: Option<Vec<Target>>,
+}
+
+#[derive(Deserialize)]
+struct DependencyRequirementTable {
+    #[serde(flatten)]
+    requirement: Requirement,
+    #[serde(default)]
+    targets: Option<Vec<Target>>,
+}
+
+impl<'de> Deserialize<'de> for DependencyRequirement {
+    fn deserialize<D>(deserialiser: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialiser.deserialize_any(DependencyRequirementVisitor)
+    }
+}
+
+struct DependencyRequirementVisitor;
+
+impl<'de> serde::de::Visitor<'de> for DependencyRequirementVisitor {
+    type Value = DependencyRequirement;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("string or map")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(DependencyRequirement {
+            requirement: Requirement::hex(value),
+            targets: None,
+        })
+    }
+
+    fn visit_map<M>(self, map: M) -> Result<Self::Value, M::Error>
+    where
+        M: serde::de::MapAccess<'de>,
+    {
+        let table = DependencyRequirementTable::deserialize(
+            serde::de::value::MapAccessDeserializer::new(map),
+        )?;
+        Ok(DependencyRequirement {
+            requirement: table.requirement,
+            targets: table.targets,
+        })
+    }
+}
+
+fn deserialise_dependencies<'de, D>(deserialiser: D) -> Result<Dependencies, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    DependencySection::deserialize(deserialiser).map(|section| section.requirements)
+}
+
+/// The contents of a `gleam.toml` file. The dependency sections are read here
+/// rather than by `PackageConfig`, as they also set its dependency policy and
+/// the targets of its dependencies.
+#[derive(Deserialize)]
+struct PackageConfigFile {
+    #[serde(default)]
+    dependencies: DependencySection,
+    #[serde(default, rename = "dev-dependencies")]
+    dev_dependencies: DependencySection,
+    #[serde(flatten)]
+    config: PackageConfig,
+}
+
 impl PackageConfig {
+    /// Parses the contents of a `gleam.toml` file.
+    ///
+    pub fn from_toml(toml: &str) -> Result<Self, toml::de::Error> {
+        let file: PackageConfigFile = toml::from_str(toml)?;
+        if file.dev_dependencies.policy.is_some() {
+            return Err(serde::de::Error::custom(
+                "the dependency policy must be set in the `[dependencies]` section",
+            ));
+        }
+        let mut config = file.config;
+        config.dependencies = file.dependencies.requirements;
+        config.dev_dependencies = file.dev_dependencies.requirements;
+        config.dependency_policy = file.dependencies.policy.unwrap_or_default();
+        config.dependency_targets = file
+            .dependencies
+            .targets
+            .into_iter()
+            .chain(file.dev_dependencies.targets)
+            .collect();
+        Ok(config)
+    }
+
+    /// Whether the named direct dependency is used when compiling for the
+    /// given target, which is not the case when its requirement lists other
+    /// `targets` only.
+    pub fn dependency_supports(&self, name: &str, target: Target) -> bool {
+        match self.dependency_targets.get(name) {
+            Some(targets) => targets.contains(&target),
+            None => true,
+        }
+    }
+
+    /// The direct dependencies of the given mode which are used when
+    /// compiling for the given target.
+    pub fn dependencies_for_target(&self, mode: Mode, target: Target) -> Result<Dependencies> {
+        let mut dependencies = self.dependencies_for(mode)?;
+        dependencies.retain(|name, _| self.dependency_supports(name, target));
+        Ok(dependencies)
+    }
+
     pub fn dependencies_for(&self, mode: Mode) -> Result<Dependencies> {
         match mode {
             Mode::Dev | Mode::Lsp => self.all_direct_dependencies(),
@@ -162,7 +363,7 @@ impl PackageConfig {
         fs: &FS,
     ) -> Result<PackageConfig, Error> {
         let toml = fs.read(path.as_ref())?;
-        let config: PackageConfig = toml::from_str(&toml).map_err(|e| Error::FileIo {
+        let config = PackageConfig::from_toml(&toml).map_err(|e| Error::FileIo {
             action: FileIoAction::Parse,
             kind: FileKind::File,
             path: path.as_ref().to_path_buf(),
@@ -640,13 +841,18 @@ impl Default for PackageConfig {
             description: Default::default(),
             documentation: Default::default(),
             dependencies: Default::default(),
+            dependency_policy: Default::default(),
+            dependency_targets: Default::default(),
             erlang: Default::default(),
             javascript: Default::default(),
+            format: Default::default(),
             repository: Default::default(),
             dev_dependencies: Default::default(),
             licences: Default::default(),
             links: Default::default(),
             internal_modules: Default::default(),
+            registry: Default::default(),
+            hex: Default::default(),
             glistix: Default::default(),
             target: Target::Erlang,
         }
@@ -661,6 +867,38 @@ pub struct ErlangConfig {
     pub extra_applications: Vec<EcoString>,
 }
 
+/// The settings of the formatter, from the `[format]` section of gleam.toml.
+///
+/// ```toml
+/// [format]
+/// line-width = 100
+/// trailing-commas = true
+/// ```
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FormatConfig {
+    /// The width the formatter tries to fit each line within.
+    #[serde(default = "default_line_width", rename = "line-width")]
+    pub line_width: u16,
+    /// Whether the list and tuple literals written with a comma after their
+    /// last element are always split with one element per line, keeping the
+    /// comma.
+    #[serde(default, rename = "trailing-commas")]
+    pub trailing_commas: bool,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            line_width: default_line_width(),
+            trailing_commas: false,
+        }
+    }
+}
+
+fn default_line_width() -> u16 {
+    80
+}
+
 #[derive(Deserialize, Debug, PartialEq, Default, Clone)]
 pub struct JavaScriptConfig {
     #[serde(default)]
@@ -754,6 +992,150 @@ pub struct GlistixConfig {
     /// Can change in the future.
     #[serde(default)]
     pub preview: GlistixPreviewConfig,
+
+    /// Targets for which this package may not declare external
+    /// implementations. Any function in the package (but not in its
+    /// dependencies) with an external for one of these targets is an error.
+    #[serde(default, rename = "deny-externals")]
+    pub deny_externals: Vec<Target>,
+
+    /// The targets this package supports, which `glistix test --all-targets`
+    /// runs the tests on and `glistix publish` checks the package compiles
+    /// for. When none are given, every target is supported.
+    #[serde(default, rename = "supported-targets")]
+    pub supported_targets: Vec<Target>,
+
+    /// Whether to check that the functions referred to by Nix externals are
+    /// exported by their Nix files with the same number of arguments.
+    #[serde(default, rename = "check-nix-externals")]
+    pub check_nix_externals: bool,
+
+    /// How public definitions using private types in their signature are
+    /// reported.
+    #[serde(default, rename = "private-type-leaks")]
+    pub private_type_leaks: PrivateTypeLeaks,
+
+    /// Whether to allow string literals passed as paths to the functions of
+    /// the `glistix/nix` module which are not valid relative Nix paths,
+    /// instead of warning about them.
+    #[serde(default, rename = "allow-invalid-nix-paths")]
+    pub allow_invalid_nix_paths: bool,
+
+    /// Commands to run on the files generated for each target once code
+    /// generation for this package is done.
+    #[serde(default)]
+    pub hooks: GlistixHooksConfig,
+
+    /// Options for the Nix code generated for this package.
+    #[serde(default)]
+    pub nix: GlistixNixConfig,
+
+    /// How much the Nix code generated for this package is optimised.
+    /// From level 2, the tuples and `Result`s returned by functions and
+    /// immediately destructured by their callers in the same module are
+    /// passed to continuations instead of being built.
+    #[serde(default)]
+    pub optimize: u8,
+}
+
+impl GlistixConfig {
+    /// The targets the package supports, in the order they were declared, or
+    /// every target if none were.
+    pub fn all_targets(&self) -> Vec<Target> {
+        if self.supported_targets.is_empty() {
+            Target::iter().collect()
+        } else {
+            let mut targets = Vec::with_capacity(self.supported_targets.len());
+            for target in &self.supported_targets {
+                if !targets.contains(target) {
+                    targets.push(*target);
+                }
+            }
+            targets
+        }
+    }
+}
+
+/// The commands run after code generation for each target. Each command is
+/// given the paths of the files which have just been generated as extra
+/// arguments, and may rewrite them, such as to format them.
+///
+/// ```toml
+/// [[glistix.hooks.nix]]
+/// command = "nixfmt"
+/// ```
+///
+/// Only the hooks of the package being built are run, not those of its
+/// dependencies.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+pub struct GlistixHooksConfig {
+    #[serde(default)]
+    pub erlang: Vec<CodegenHook>,
+    #[serde(default)]
+    pub javascript: Vec<CodegenHook>,
+    #[serde(default)]
+    pub nix: Vec<CodegenHook>,
+}
+
+impl GlistixHooksConfig {
+    pub fn for_target(&self, target: Target) -> &[CodegenHook] {
+        match target {
+            Target::Erlang => &self.erlang,
+            Target::JavaScript => &self.javascript,
+            Target::Nix => &self.nix,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct CodegenHook {
+    pub command: String,
+    /// Arguments passed to the command before the paths of the generated
+    /// files.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone, Copy)]
+pub struct GlistixNixConfig {
+    /// How Gleam lists are represented in the generated Nix code. The
+    /// representation chosen by the root package is used for every package
+    /// of the build, so every dependency must declare that same one, with
+    /// those declaring none using cons cells.
+    #[serde(default, rename = "list-representation")]
+    pub list_representation: Option<ListRepresentation>,
+}
+
+/// How Gleam lists are represented on the Nix target.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ListRepresentation {
+    /// Nested attribute sets, each holding an element and the rest of the
+    /// list, so that prepending an element doesn't copy the list.
+    #[default]
+    Cons,
+    /// Plain Nix lists, which can be passed to and returned from Nixpkgs
+    /// functions without conversions, but are copied when prepended to.
+    Native,
+}
+
+impl ListRepresentation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ListRepresentation::Cons => "cons",
+            ListRepresentation::Native => "native",
+        }
+    }
+}
+
+/// Whether a private type used by a public definition is an error, which is
+/// the default, or only a warning.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivateTypeLeaks {
+    #[default]
+    Error,
+    Warning,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
@@ -860,10 +1242,87 @@ impl Default for Repository {
     }
 }
 
+/// Restrictions on the packages the package may depend on, set in the
+/// `[dependencies.policy]` section.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+pub struct DependencyPolicy {
+    /// The SPDX identifiers of the licences Hex dependencies may use. A
+    /// package using several licences is allowed if any of them is. When
+    /// absent any licence is allowed.
+    #[serde(default, rename = "allowed-licences")]
+    pub allowed_licences: Option<Vec<EcoString>>,
+}
+
+impl DependencyPolicy {
+    /// Finds the packages whose licences aren't allowed by the policy, given
+    /// the licences of each package.
+    ///
+    pub fn check_licences(&self, packages: Vec<(EcoString, Vec<EcoString>)>) -> Result<()> {
+        let Some(allowed_licences) = &self.allowed_licences else {
+            return Ok(());
+        };
+        let is_allowed = |licence: &EcoString| {
+            allowed_licences
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(licence))
+        };
+
+        let mut disallowed = packages
+            .into_iter()
+            .filter(|(_, licences)| !licences.iter().any(is_allowed))
+            .collect_vec();
+        if disallowed.is_empty() {
+            return Ok(());
+        }
+        disallowed.sort();
+        Err(Error::DisallowedDependencyLicences {
+            packages: disallowed,
+            allowed_licences: allowed_licences.clone(),
+        })
+    }
+}
+
+/// Where the Hex packages the package depends on are downloaded from.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+pub struct HexConfig {
+    /// A mirror of the Hex repository to download packages from instead of
+    /// `https://repo.hex.pm/`.
+    #[serde(default, deserialize_with = "uri_serde::deserialize_option")]
+    pub repo_url: Option<Uri>,
+
+    /// Whether to authenticate with the Hex API key when downloading
+    /// packages, as required by private mirrors. Packages of a Hex
+    /// organisation, named `organisation/package`, are always downloaded
+    /// with authentication.
+    #[serde(default)]
+    pub authenticate: bool,
+}
+
+/// A registry to publish the package to instead of Hex, such as the generic
+/// package registry of a Forgejo or Gitea instance.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RegistryConfig {
+    /// The URL under which packages are uploaded, for example
+    /// `https://codeberg.org/api/packages/my-org/generic`.
+    #[serde(with = "uri_serde")]
+    pub url: Uri,
+
+    /// The environment variable holding the token used to authenticate with
+    /// the registry.
+    #[serde(default = "default_registry_token_env", rename = "token-env")]
+    pub token_env: String,
+}
+
+fn default_registry_token_env() -> String {
+    "GLISTIX_REGISTRY_TOKEN".into()
+}
+
 #[derive(Deserialize, Default, Debug, PartialEq, Eq, Clone)]
 pub struct Docs {
     #[serde(default)]
     pub pages: Vec<DocsPage>,
+    #[serde(default)]
+    pub assets: Vec<DocsAssets>,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -873,6 +1332,16 @@ pub struct DocsPage {
     pub source: Utf8PathBuf,
 }
 
+/// A directory whose files, such as the images shown by the pages, are copied
+/// into the documentation.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DocsAssets {
+    /// The directory to put the files in, relative to the root of the
+    /// documentation, which it can't be outside of.
+    pub path: String,
+    pub source: Utf8PathBuf,
+}
+
 #[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct Link {
     pub title: String,
@@ -990,3 +1459,378 @@ name = "1"
         "Package names may only contain lowercase letters, numbers, and underscores for key `name` at line 1 column 1"
     )
 }
+
+#[test]
+fn registry() {
+    let input = r#"
+name = "wibble"
+
+[registry]
+url = "https://git.example.com/api/packages/wobble/generic"
+token-env = "WOBBLE_TOKEN"
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.registry,
+        Some(RegistryConfig {
+            url: Uri::from_static("https://git.example.com/api/packages/wobble/generic"),
+            token_env: "WOBBLE_TOKEN".into(),
+        })
+    );
+}
+
+#[test]
+fn registry_default_token_env() {
+    let input = r#"
+name = "wibble"
+
+[registry]
+url = "https://git.example.com/api/packages/wobble/generic"
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.registry.map(|registry| registry.token_env),
+        Some("GLISTIX_REGISTRY_TOKEN".into())
+    );
+}
+
+#[test]
+fn hex_mirror() {
+    let input = r#"
+name = "wibble"
+
+[hex]
+repo_url = "https://hex.example.com/repo/"
+authenticate = true
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.hex,
+        HexConfig {
+            repo_url: Some(Uri::from_static("https://hex.example.com/repo/")),
+            authenticate: true,
+        }
+    );
+}
+
+#[test]
+fn dependency_policy() {
+    let input = r#"
+name = "wibble"
+
+[dependencies]
+gleam_stdlib = ">= 0.34.0 and < 2.0.0"
+
+[dependencies.policy]
+allowed-licences = ["Apache-2.0", "MIT"]
+"#;
+    let config = PackageConfig::from_toml(input).unwrap();
+    assert_eq!(
+        config.dependency_policy.allowed_licences,
+        Some(vec!["Apache-2.0".into(), "MIT".into()])
+    );
+    assert_eq!(
+        config.dependencies.keys().collect_vec(),
+        vec![&EcoString::from("gleam_stdlib")]
+    );
+}
+
+#[test]
+fn dependency_named_policy() {
+    let input = r#"
+name = "wibble"
+
+[dependencies]
+policy = "~> 1.0"
+"#;
+    let config = PackageConfig::from_toml(input).unwrap();
+    assert_eq!(config.dependency_policy, DependencyPolicy::default());
+    assert_eq!(config.dependencies["policy"], Requirement::hex("~> 1.0"));
+}
+
+#[test]
+fn dependency_policy_in_dev_dependencies() {
+    let input = r#"
+name = "wibble"
+
+[dev-dependencies.policy]
+allowed-licences = ["MIT"]
+"#;
+    assert!(PackageConfig::from_toml(input).is_err());
+}
+
+#[test]
+fn documentation_pages_and_assets() {
+    let input = r#"
+name = "wibble"
+
+[[documentation.pages]]
+title = "Using from Nix"
+path = "using-from-nix.html"
+source = "docs/using-from-nix.md"
+
+[[documentation.assets]]
+path = "images"
+source = "docs/images"
+"#;
+    let config = PackageConfig::from_toml(input).unwrap();
+    assert_eq!(
+        config.documentation,
+        Docs {
+            pages: vec![DocsPage {
+                title: "Using from Nix".into(),
+                path: "using-from-nix.html".into(),
+                source: "docs/using-from-nix.md".into(),
+            }],
+            assets: vec![DocsAssets {
+                path: "images".into(),
+                source: "docs/images".into(),
+            }],
+        }
+    );
+}
+
+#[test]
+fn dependency_targets() {
+    let input = r#"
+name = "wibble"
+
+[dependencies]
+gleam_stdlib = ">= 0.34.0 and < 2.0.0"
+gleam_erlang = { version = "~> 0.25", targets = ["erlang"] }
+nix_helpers = { path = "../nix_helpers", targets = ["nix", "js"] }
+
+[dev-dependencies]
+gleeunit = { version = "~> 1.0", targets = ["erl", "javascript"] }
+"#;
+    let config = PackageConfig::from_toml(input).unwrap();
+    assert_eq!(
+        config.dependencies["gleam_erlang"],
+        Requirement::hex("~> 0.25")
+    );
+    assert!(config.dependency_supports("gleam_stdlib", Target::Nix));
+    assert!(config.dependency_supports("gleam_erlang", Target::Erlang));
+    assert!(!config.dependency_supports("gleam_erlang", Target::Nix));
+    assert!(config.dependency_supports("nix_helpers", Target::JavaScript));
+    assert!(!config.dependency_supports("gleeunit", Target::Nix));
+
+    let nix = config
+        .dependencies_for_target(Mode::Dev, Target::Nix)
+        .unwrap();
+    assert_eq!(
+        nix.keys().sorted().collect_vec(),
+        vec![&EcoString::from("gleam_stdlib"), &"nix_helpers".into()]
+    );
+    let erlang = config
+        .dependencies_for_target(Mode::Prod, Target::Erlang)
+        .unwrap();
+    assert_eq!(
+        erlang.keys().sorted().collect_vec(),
+        vec![&EcoString::from("gleam_erlang"), &"gleam_stdlib".into()]
+    );
+}
+
+#[test]
+fn dependency_targets_invalid() {
+    let input = r#"
+name = "wibble"
+
+[dependencies]
+gleam_erlang = { version = "~> 0.25", targets = ["beam"] }
+"#;
+    assert!(PackageConfig::from_toml(input).is_err());
+}
+
+#[test]
+fn locked_keeps_dependencies_of_other_targets() {
+    let mut config = PackageConfig::default();
+    config.target = Target::Nix;
+    config.dependencies = [
+        ("prod1".into(), Requirement::hex("~> 1.0")),
+        ("prod2".into(), Requirement::hex("~> 2.0")),
+    ]
+    .into();
+    let _ = config
+        .dependency_targets
+        .insert("prod2".into(), vec![Target::Erlang]);
+    let manifest = Manifest {
+        requirements: config.dependencies.clone(),
+        packages: vec![
+            manifest_package("prod1", "1.1.0", &[]),
+            manifest_package("prod2", "1.2.0", &[]),
+        ],
+    };
+    assert_eq!(
+        config.locked(Some(&manifest)).unwrap(),
+        [
+            locked_version("prod1", "1.1.0"),
+            locked_version("prod2", "1.2.0")
+        ]
+        .into()
+    );
+}
+
+#[test]
+fn dependency_policy_licences() {
+    let policy = DependencyPolicy {
+        allowed_licences: Some(vec!["Apache-2.0".into(), "MIT".into()]),
+    };
+    let packages = vec![
+        ("wibble".into(), vec!["mit".into()]),
+        ("wobble".into(), vec!["GPL-3.0".into(), "Apache-2.0".into()]),
+        ("wubble".into(), vec!["GPL-3.0".into()]),
+        ("unlicensed".into(), vec![]),
+    ];
+    let error = policy.check_licences(packages).unwrap_err();
+    assert_eq!(
+        error,
+        Error::DisallowedDependencyLicences {
+            packages: vec![
+                ("unlicensed".into(), vec![]),
+                ("wubble".into(), vec!["GPL-3.0".into()]),
+            ],
+            allowed_licences: vec!["Apache-2.0".into(), "MIT".into()],
+        }
+    );
+
+    assert!(DependencyPolicy::default()
+        .check_licences(vec![("wubble".into(), vec![])])
+        .is_ok());
+}
+
+#[test]
+fn glistix_deny_externals() {
+    let input = r#"
+name = "wibble"
+
+[glistix]
+deny-externals = ["nix", "erl"]
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.glistix.deny_externals,
+        vec![Target::Nix, Target::Erlang]
+    );
+}
+
+#[test]
+fn glistix_supported_targets() {
+    let input = r#"
+name = "wibble"
+
+[glistix]
+supported-targets = ["erlang", "nix"]
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.glistix.supported_targets,
+        vec![Target::Erlang, Target::Nix]
+    );
+    assert_eq!(
+        config.glistix.all_targets(),
+        vec![Target::Erlang, Target::Nix]
+    );
+
+    let config = toml::from_str::<PackageConfig>(r#"name = "wibble""#).unwrap();
+    assert_eq!(
+        config.glistix.all_targets(),
+        vec![Target::Erlang, Target::JavaScript, Target::Nix]
+    );
+}
+
+#[test]
+fn format_config() {
+    let input = r#"
+name = "wibble"
+
+[format]
+line-width = 100
+trailing-commas = true
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.format,
+        FormatConfig {
+            line_width: 100,
+            trailing_commas: true,
+        }
+    );
+}
+
+#[test]
+fn format_config_defaults() {
+    let config = toml::from_str::<PackageConfig>(r#"name = "wibble""#).unwrap();
+    assert_eq!(config.format.line_width, 80);
+    assert!(!config.format.trailing_commas);
+}
+
+#[test]
+fn glistix_hooks() {
+    let input = r#"
+name = "wibble"
+
+[[glistix.hooks.nix]]
+command = "nixfmt"
+
+[[glistix.hooks.nix]]
+command = "sh"
+args = ["scripts/add-licence.sh"]
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.glistix.hooks.for_target(Target::Nix),
+        [
+            CodegenHook {
+                command: "nixfmt".into(),
+                args: vec![],
+            },
+            CodegenHook {
+                command: "sh".into(),
+                args: vec!["scripts/add-licence.sh".into()],
+            },
+        ]
+    );
+    assert!(config.glistix.hooks.for_target(Target::Erlang).is_empty());
+}
+
+#[test]
+fn glistix_optimize() {
+    let input = r#"
+name = "wibble"
+
+[glistix]
+optimize = 2
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(config.glistix.optimize, 2);
+}
+
+#[test]
+fn glistix_nix_list_representation() {
+    let input = r#"
+name = "wibble"
+
+[glistix.nix]
+list-representation = "native"
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.glistix.nix.list_representation,
+        Some(ListRepresentation::Native)
+    );
+
+    let config = toml::from_str::<PackageConfig>("name = \"wibble\"").unwrap();
+    assert_eq!(config.glistix.nix.list_representation, None);
+}
+
+#[test]
+fn glistix_check_nix_externals() {
+    let input = r#"
+name = "wibble"
+
+[glistix]
+check-nix-externals = true
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert!(config.glistix.check_nix_externals);
+}
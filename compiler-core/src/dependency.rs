@@ -1,14 +1,22 @@
-use std::{borrow::Borrow, cell::RefCell, collections::HashMap, error::Error as StdError};
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    collections::{BTreeSet, HashMap, HashSet},
+    error::Error as StdError,
+};
 
-use crate::{Error, Result};
+use crate::{error::wrap, Error, Result};
 
 use ecow::EcoString;
 use hexpm::{
     version::{Range, ResolutionError, Version},
     Dependency, Release,
 };
+use itertools::Itertools;
 use pubgrub::{
+    report::{DerivationTree, External},
     solver::{choose_package_with_fewest_versions, Dependencies},
+    term::Term,
     type_aliases::Map,
 };
 
@@ -16,20 +24,90 @@ pub type PackageVersions = HashMap<String, Version>;
 
 type PubgrubRange = pubgrub::range::Range<Version>;
 
+/// Which of the versions allowed by the requirements are picked when
+/// resolving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionPreference {
+    /// The newest versions, as used when adding and updating packages.
+    #[default]
+    Newest,
+    /// The oldest versions, to check that the lower bounds of the
+    /// requirements really are compatible.
+    Oldest,
+}
+
 pub fn resolve_versions<Requirements>(
     package_fetcher: Box<dyn PackageFetcher>,
-    provided_packages: HashMap<EcoString, hexpm::Package>,
+    provided_packages: HashMap<EcoString, (hexpm::Package, ProvidedSource)>,
     root_name: EcoString,
     dependencies: Requirements,
     locked: &HashMap<EcoString, Version>,
+    preference: VersionPreference,
+    log: Option<&ResolutionLog>,
 ) -> Result<PackageVersions>
 where
     Requirements: Iterator<Item = (EcoString, Range)>,
 {
     tracing::info!("resolving_versions");
+    let result = resolve_root_versions(
+        package_fetcher,
+        provided_packages,
+        root_name,
+        dependencies,
+        locked,
+        preference,
+        log,
+    );
+    if let (Some(log), Err(Error::DependencyResolutionFailed(message))) = (log, &result) {
+        log.record(format!("Failed to resolve versions: {message}"));
+    }
+    result
+}
+
+fn resolve_root_versions<Requirements>(
+    package_fetcher: Box<dyn PackageFetcher>,
+    provided_packages: HashMap<EcoString, (hexpm::Package, ProvidedSource)>,
+    root_name: EcoString,
+    dependencies: Requirements,
+    locked: &HashMap<EcoString, Version>,
+    preference: VersionPreference,
+    log: Option<&ResolutionLog>,
+) -> Result<PackageVersions>
+where
+    Requirements: Iterator<Item = (EcoString, Range)>,
+{
     let root_version = Version::new(0, 0, 0);
-    let requirements =
-        root_dependencies(dependencies, locked).map_err(Error::dependency_resolution_failed)?;
+    let context = ConflictContext {
+        root_name: &root_name,
+        locked,
+        provided: provided_packages
+            .iter()
+            .filter_map(|(name, (package, source))| {
+                let release = package.releases.first()?;
+                Some((name.clone(), (release.version.clone(), *source)))
+            })
+            .collect(),
+    };
+    let provided_packages = provided_packages
+        .into_iter()
+        .map(|(name, (package, _))| (name, package))
+        .collect();
+    let requirements = root_dependencies(dependencies, locked)
+        .map_err(|error| Error::dependency_resolution_failed(error, &context))?;
+
+    if let Some(log) = log {
+        log.record(format!(
+            "Resolving versions for {root_name} with the requirements:"
+        ));
+        for (name, dependency) in requirements.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+            let locked = if locked.contains_key(name.as_str()) {
+                " (locked)"
+            } else {
+                ""
+            };
+            log.record(format!("  {name} {}{locked}", dependency.requirement));
+        }
+    }
 
     // Creating a map of all the required packages that have exact versions specified
     let exact_deps = &requirements
@@ -50,19 +128,104 @@ where
         }],
     };
 
-    let packages = pubgrub::solver::resolve(
-        &DependencyProvider::new(package_fetcher, provided_packages, root, locked, exact_deps),
+    let packages: PackageVersions = pubgrub::solver::resolve(
+        &DependencyProvider::new(
+            package_fetcher,
+            provided_packages,
+            root,
+            locked,
+            exact_deps,
+            preference,
+            log,
+        ),
         root_name.as_str().into(),
         root_version,
     )
-    .map_err(Error::dependency_resolution_failed)?
+    .map_err(|error| Error::dependency_resolution_failed(error, &context))?
     .into_iter()
     .filter(|(name, _)| name.as_str() != root_name.as_str())
     .collect();
 
+    if let Some(log) = log {
+        log.record("Resolved versions:".into());
+        for (name, version) in packages.iter().sorted() {
+            log.record(format!("  {name} {version}"));
+        }
+    }
     Ok(packages)
 }
 
+/// Where a package provided rather than downloaded from Hex comes from. Such
+/// packages only have the one version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvidedSource {
+    /// A dependency on the project in a local directory.
+    Path,
+    /// A dependency on the project in a Git repository.
+    Git,
+    /// A dependency of a dependency which is replaced by the root package's
+    /// own requirement on it, as listed in `glistix.preview.local-overrides`.
+    LocalOverride,
+}
+
+/// A record of the decisions made while resolving versions: the candidate
+/// versions considered for each package, the constraints its dependencies
+/// add, and the packages reconsidered after backtracking. It can be written
+/// to a file so that it can be seen why a version was chosen without
+/// enabling tracing.
+#[derive(Debug, Default)]
+pub struct ResolutionLog {
+    entries: RefCell<Vec<String>>,
+    // The versions chosen for packages so far. Choosing a version for one of
+    // these again means the solver backtracked on the previous decision.
+    decisions: RefCell<HashMap<PackageName, Option<Version>>>,
+}
+
+impl ResolutionLog {
+    fn record(&self, entry: String) {
+        self.entries.borrow_mut().push(entry);
+    }
+
+    fn record_decision(
+        &self,
+        name: &PackageName,
+        version: Option<&Version>,
+        range: &PubgrubRange,
+        candidates: usize,
+    ) {
+        let previous = self
+            .decisions
+            .borrow_mut()
+            .insert(name.clone(), version.cloned());
+        match previous {
+            Some(Some(previous)) => self.record(format!(
+                "Backtracked on {name} {previous}, which conflicted with later decisions"
+            )),
+            Some(None) => self.record(format!("Backtracked on {name}, which had no candidates")),
+            None => (),
+        }
+
+        self.record(match version {
+            Some(version) if candidates == 1 => {
+                format!("Chose {name} {version}, the only version in {range}")
+            }
+            Some(version) => {
+                format!("Chose {name} {version} out of {candidates} versions in {range}")
+            }
+            None => format!("No version of {name} is in {range}"),
+        });
+    }
+
+    /// The recorded decisions, one per line.
+    pub fn to_text(&self) -> String {
+        self.entries
+            .borrow()
+            .iter()
+            .map(|entry| format!("{entry}\n"))
+            .collect()
+    }
+}
+
 // If the string would parse to an exact version then return the version
 fn parse_exact_version(ver: &str) -> Option<Version> {
     let version = ver.trim();
@@ -154,6 +317,8 @@ struct DependencyProvider<'a> {
     // and the version 1 bump ahead. That default breaks on prerelease builds since a bump includes the whole patch
     exact_only: &'a HashMap<String, Version>,
     optional_dependencies: RefCell<HashMap<EcoString, pubgrub::range::Range<Version>>>,
+    preference: VersionPreference,
+    log: Option<&'a ResolutionLog>,
 }
 
 impl<'a> DependencyProvider<'a> {
@@ -163,6 +328,8 @@ impl<'a> DependencyProvider<'a> {
         root: hexpm::Package,
         locked: &'a HashMap<EcoString, Version>,
         exact_only: &'a HashMap<String, Version>,
+        preference: VersionPreference,
+        log: Option<&'a ResolutionLog>,
     ) -> Self {
         let _ = packages.insert(root.name.as_str().into(), root);
         Self {
@@ -171,13 +338,23 @@ impl<'a> DependencyProvider<'a> {
             remote,
             exact_only,
             optional_dependencies: RefCell::new(Default::default()),
+            preference,
+            log,
+        }
+    }
+
+    /// Records an entry in the resolution log, if there is one.
+    fn log_with(&self, entry: impl FnOnce() -> String) {
+        if let Some(log) = self.log {
+            log.record(entry());
         }
     }
 
     /// Download information about the package from the registry into the local
     /// store. Does nothing if the packages are already known.
     ///
-    /// Package versions are sorted from newest to oldest, with all pre-releases
+    /// Package versions are sorted from newest to oldest, or from oldest to
+    /// newest when the oldest versions are preferred, with all pre-releases
     /// at the end to ensure that a non-prerelease version will be picked first
     /// if there is one.
     //
@@ -192,7 +369,9 @@ impl<'a> DependencyProvider<'a> {
             let mut package = self.remote.get_dependencies(name)?;
             // Sort the packages from newest to oldest, pres after all others
             package.releases.sort_by(|a, b| a.version.cmp(&b.version));
-            package.releases.reverse();
+            if self.preference == VersionPreference::Newest {
+                package.releases.reverse();
+            }
             let (pre, mut norm): (_, Vec<_>) = package
                 .releases
                 .into_iter()
@@ -237,10 +416,28 @@ impl pubgrub::solver::DependencyProvider<PackageName, Version> for DependencyPro
                 })
                 .map(|p| p.version)
         };
-        Ok(choose_package_with_fewest_versions(
+
+        let Some(log) = self.log else {
+            return Ok(choose_package_with_fewest_versions(
+                list_available_versions,
+                potential_packages.into_iter(),
+            ));
+        };
+        let ranges: HashMap<PackageName, PubgrubRange> = potential_packages
+            .iter()
+            .map(|(name, range)| (name.borrow().clone(), range.borrow().clone()))
+            .collect();
+        let (name, version) = choose_package_with_fewest_versions(
             list_available_versions,
             potential_packages.into_iter(),
-        ))
+        );
+        if let Some(range) = ranges.get(name.borrow()) {
+            let candidates = list_available_versions(name.borrow())
+                .filter(|version| range.contains(version))
+                .count();
+            log.record_decision(name.borrow(), version.as_ref(), range, candidates);
+        }
+        Ok((name, version))
     }
 
     fn get_dependencies(
@@ -257,20 +454,26 @@ impl pubgrub::solver::DependencyProvider<PackageName, Version> for DependencyPro
             .find(|r| &r.version == version)
         {
             Some(release) => release,
-            None => return Ok(Dependencies::Unknown),
+            None => {
+                self.log_with(|| format!("{name} {version} could not be found"));
+                return Ok(Dependencies::Unknown);
+            }
         };
 
         // Only use retired versions if they have been locked
         if release.is_retired() && self.locked.get(name.as_str()) != Some(version) {
+            self.log_with(|| format!("{name} {version} is retired, so it was not used"));
             return Ok(Dependencies::Unknown);
         }
 
         let mut deps: Map<PackageName, PubgrubRange> = Default::default();
+        let mut skipped_optional = vec![];
         for (name, d) in &release.requirements {
             let mut range = d.requirement.to_pubgrub()?;
             let mut opt_deps = self.optional_dependencies.borrow_mut();
             // if it's optional and it was not provided yet, store and skip
             if d.optional && !packages.contains_key(name.as_str()) {
+                skipped_optional.push(name.as_str());
                 let _ = opt_deps
                     .entry(name.into())
                     .and_modify(|stored_range| {
@@ -287,10 +490,239 @@ impl pubgrub::solver::DependencyProvider<PackageName, Version> for DependencyPro
 
             let _ = deps.insert(name.clone(), range);
         }
+
+        self.log_with(|| {
+            let mut entry = if deps.is_empty() {
+                format!("{name} {version} has no dependencies")
+            } else {
+                let dependencies = deps
+                    .iter()
+                    .sorted_by(|a, b| a.0.cmp(b.0))
+                    .map(|(name, range)| format!("{name} {range}"))
+                    .join(", ");
+                format!("{name} {version} depends on {dependencies}")
+            };
+            if !skipped_optional.is_empty() {
+                entry.push_str(&format!(
+                    ", skipping the optional {} as nothing else requires them yet",
+                    skipped_optional.iter().sorted().join(", ")
+                ));
+            }
+            entry
+        });
         Ok(Dependencies::Known(deps))
     }
 }
 
+/// What is known about the project when its versions can't be resolved, used
+/// to explain which requirements conflict and how that could be solved.
+#[derive(Debug)]
+pub struct ConflictContext<'a> {
+    root_name: &'a str,
+    locked: &'a HashMap<EcoString, Version>,
+    /// Packages provided from a local path or a Git repository rather than
+    /// Hex, with their only version.
+    provided: HashMap<EcoString, (Version, ProvidedSource)>,
+}
+
+impl ConflictContext<'_> {
+    /// Explains a failed resolution as a tree of causes, each incompatibility
+    /// followed by the two it was derived from, and then suggests how the
+    /// conflict could be solved.
+    pub fn explain(&self, derivation_tree: &DerivationTree<PackageName, Version>) -> String {
+        let mut packages = BTreeSet::new();
+        let mut direct = BTreeSet::new();
+        self.collect_packages(derivation_tree, &mut packages, &mut direct);
+
+        let mut text = wrap(
+            "Unable to find compatible versions for the version constraints in \
+your gleam.toml, because:",
+        );
+        text.push('\n');
+        match derivation_tree {
+            // The root incompatibility only says that the project can't be
+            // built, so its causes are shown directly.
+            DerivationTree::Derived(derived)
+                if derived
+                    .terms
+                    .keys()
+                    .all(|package| package == self.root_name) =>
+            {
+                let mut explained = HashSet::new();
+                self.explain_cause(&derived.cause1, 0, &mut explained, &mut text);
+                self.explain_cause(&derived.cause2, 0, &mut explained, &mut text);
+            }
+            _ => self.explain_cause(derivation_tree, 0, &mut HashSet::new(), &mut text),
+        }
+
+        let mut suggestions = vec![];
+        let locked = packages
+            .iter()
+            .filter(|package| {
+                self.locked.contains_key(**package) && !self.provided.contains_key(**package)
+            })
+            .join(" ");
+        if !locked.is_empty() {
+            suggestions.push(format!(
+                "Run `glistix update {locked}` so that their locked versions can change."
+            ));
+        }
+        if !direct.is_empty() {
+            suggestions.push(format!(
+                "Relax the version ranges of {} in your gleam.toml.",
+                direct.iter().join(", ")
+            ));
+        }
+        for (package, (version, source)) in packages
+            .iter()
+            .filter_map(|package| Some((package, self.provided.get(*package)?)))
+        {
+            let provided = match source {
+                ProvidedSource::Path => "is a path dependency",
+                ProvidedSource::Git => "is a Git dependency",
+                ProvidedSource::LocalOverride => {
+                    "is replaced with your project's requirement on it by \
+`glistix.preview.local-overrides`"
+                }
+            };
+            suggestions.push(format!(
+                "{package} {provided}, so only its version {version} can be used. \
+Change the requirements on it to include that version, or provide a different \
+version of it."
+            ));
+        }
+
+        if !suggestions.is_empty() {
+            text.push_str("\n\nYou could try to solve this with the following:\n");
+            for suggestion in suggestions {
+                text.push_str(&format!("\n{}", wrap(&format!("- {suggestion}"))));
+            }
+        }
+        text.push('\n');
+        text
+    }
+
+    /// Adds a line for a cause of the conflict, followed by the causes it was
+    /// derived from, nested below it. Causes shared by several others are
+    /// only explained the first time.
+    fn explain_cause(
+        &self,
+        derivation_tree: &DerivationTree<PackageName, Version>,
+        depth: usize,
+        explained: &mut HashSet<usize>,
+        text: &mut String,
+    ) {
+        let indent = "  ".repeat(depth);
+        match derivation_tree {
+            DerivationTree::External(External::NotRoot(..)) => (),
+            DerivationTree::External(external) => {
+                text.push_str(&format!("\n{indent}- {}", self.external(external)));
+            }
+            DerivationTree::Derived(derived) => {
+                let terms = self.terms(&derived.terms);
+                if let Some(id) = derived.shared_id {
+                    if !explained.insert(id) {
+                        text.push_str(&format!("\n{indent}- {terms}, as explained above"));
+                        return;
+                    }
+                }
+                text.push_str(&format!("\n{indent}- {terms}, because:"));
+                self.explain_cause(&derived.cause1, depth + 1, explained, text);
+                self.explain_cause(&derived.cause2, depth + 1, explained, text);
+            }
+        }
+    }
+
+    fn external(&self, external: &External<PackageName, Version>) -> String {
+        match external {
+            External::NotRoot(package, version) => format!("{package} {version} is the root"),
+            External::NoVersions(package, range) => match self.provided.get(package.as_str()) {
+                Some((version, _)) => {
+                    format!("{package} is only provided at version {version}, not {range}")
+                }
+                None => format!("no versions of {package} match {range}"),
+            },
+            External::UnavailableDependencies(package, range) => {
+                format!("the dependencies of {package} {range} could not be retrieved")
+            }
+            External::FromDependencyOf(dependent, dependent_range, package, range) => {
+                let requirement = if dependent == self.root_name {
+                    format!("your project requires {package} {range}")
+                } else {
+                    format!("{dependent} {dependent_range} requires {package} {range}")
+                };
+                match self.provided.get(package.as_str()) {
+                    Some((version, _)) if !range.contains(version) => {
+                        format!("{requirement}, but only its version {version} is provided")
+                    }
+                    _ => requirement,
+                }
+            }
+        }
+    }
+
+    /// Describes an incompatibility: a set of terms which can't all hold.
+    fn terms(&self, terms: &Map<PackageName, Term<Version>>) -> String {
+        let terms = terms.iter().sorted_by(|a, b| a.0.cmp(b.0)).collect_vec();
+        match terms.as_slice() {
+            [] => "no versions can be chosen".into(),
+            [(package, Term::Positive(range))] => format!("{package} {range} can't be used"),
+            [(package, Term::Negative(range))] => format!("{package} {range} is required"),
+            [(dependent, Term::Positive(dependent_range)), (package, Term::Negative(range))]
+            | [(package, Term::Negative(range)), (dependent, Term::Positive(dependent_range))] => {
+                if dependent.as_str() == self.root_name {
+                    format!("your project requires {package} {range}")
+                } else {
+                    format!("{dependent} {dependent_range} requires {package} {range}")
+                }
+            }
+            terms => {
+                let terms = terms
+                    .iter()
+                    .map(|(package, term)| match term {
+                        Term::Positive(_) if package.as_str() == self.root_name => {
+                            "your project".into()
+                        }
+                        Term::Positive(range) => format!("{package} {range}"),
+                        Term::Negative(range) => format!("{package} outside of {range}"),
+                    })
+                    .join(" and ");
+                format!("{terms} can't be used together")
+            }
+        }
+    }
+
+    /// Collects the packages involved in the conflict, and the ones the
+    /// project requires directly.
+    fn collect_packages<'t>(
+        &self,
+        derivation_tree: &'t DerivationTree<PackageName, Version>,
+        packages: &mut BTreeSet<&'t str>,
+        direct: &mut BTreeSet<&'t str>,
+    ) {
+        let package = match derivation_tree {
+            DerivationTree::Derived(derived) => {
+                self.collect_packages(&derived.cause1, packages, direct);
+                self.collect_packages(&derived.cause2, packages, direct);
+                return;
+            }
+            DerivationTree::External(External::NotRoot(..)) => return,
+            DerivationTree::External(
+                External::NoVersions(package, _) | External::UnavailableDependencies(package, _),
+            ) => package,
+            DerivationTree::External(External::FromDependencyOf(dependent, _, package, _)) => {
+                if dependent == self.root_name {
+                    let _ = direct.insert(package.as_str());
+                }
+                package
+            }
+        };
+        if package != self.root_name {
+            let _ = packages.insert(package.as_str());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -483,6 +915,8 @@ mod tests {
             "app".into(),
             vec![("gleam_stdlib".into(), Range::new("~> 0.1".into()))].into_iter(),
             &vec![locked_stdlib].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -501,6 +935,8 @@ mod tests {
             "app".into(),
             vec![].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(result, vec![].into_iter().collect())
@@ -514,6 +950,8 @@ mod tests {
             "app".into(),
             vec![("gleam_stdlib".into(), Range::new("~> 0.1".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -532,6 +970,8 @@ mod tests {
             "app".into(),
             vec![("gleam_otp".into(), Range::new("~> 0.1".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -545,6 +985,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resolution_preferring_oldest_versions() {
+        let result = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("gleam_otp".into(), Range::new("~> 0.1".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+            VersionPreference::Oldest,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            vec![
+                ("gleam_otp".into(), Version::try_from("0.1.0").unwrap()),
+                ("gleam_stdlib".into(), Version::try_from("0.1.0").unwrap())
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
     #[test]
     fn resolution_with_optional_deps() {
         let result = resolve_versions(
@@ -553,6 +1016,8 @@ mod tests {
             "app".into(),
             vec![("package_with_optional".into(), Range::new("~> 0.1".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -578,6 +1043,8 @@ mod tests {
             ]
             .into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -606,10 +1073,75 @@ mod tests {
             ]
             .into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         );
         assert!(result.is_err());
     }
 
+    #[test]
+    fn resolution_conflict_explains_requirements() {
+        let err = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![
+                ("package_with_optional".into(), Range::new("~> 0.1".into())),
+                ("gleam_stdlib".into(), Range::new("~> 0.3".into())),
+            ]
+            .into_iter(),
+            &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
+        )
+        .unwrap_err();
+
+        let Error::DependencyResolutionFailed(message) = err else {
+            panic!("wrong error: {err}");
+        };
+        assert!(message.contains("your project requires gleam_stdlib"));
+        assert!(message.lines().any(|line| {
+            line.contains("- package_with_optional ") && line.contains(" requires gleam_stdlib ")
+        }));
+        // The causes of derived incompatibilities are nested below them
+        assert!(message.contains(", because:\n  - "));
+        assert!(message.contains("- Relax the version ranges of gleam_stdlib"));
+        assert!(!message.contains("glistix update"));
+    }
+
+    #[test]
+    fn resolution_conflict_explains_provided_package() {
+        let provided = hexpm::Package {
+            name: "gleam_stdlib".into(),
+            repository: "local".into(),
+            releases: vec![Release {
+                version: Version::new(0, 1, 0),
+                requirements: [].into(),
+                retirement_status: None,
+                outer_checksum: vec![],
+                meta: (),
+            }],
+        };
+        let err = resolve_versions(
+            make_remote(),
+            vec![("gleam_stdlib".into(), (provided, ProvidedSource::Git))]
+                .into_iter()
+                .collect(),
+            "app".into(),
+            vec![("gleam_stdlib".into(), Range::new("~> 0.3".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
+        )
+        .unwrap_err();
+
+        let Error::DependencyResolutionFailed(message) = err else {
+            panic!("wrong error: {err}");
+        };
+        assert!(message.contains("but only its version 0.1.0 is provided"));
+        assert!(message.contains("- gleam_stdlib is a Git dependency, so only its version 0.1.0"));
+    }
+
     #[test]
     fn resolution_with_optional_deps_required_by_nested_deps() {
         let result = resolve_versions(
@@ -622,6 +1154,8 @@ mod tests {
             ]
             .into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -650,6 +1184,8 @@ mod tests {
             "app".into(),
             vec![("gleam_otp".into(), Range::new("~> 0.1.0".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -671,6 +1207,8 @@ mod tests {
             "app".into(),
             vec![("package_with_retired".into(), Range::new("> 0.0.0".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -695,6 +1233,8 @@ mod tests {
             &vec![("package_with_retired".into(), Version::new(0, 2, 0))]
                 .into_iter()
                 .collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -717,6 +1257,8 @@ mod tests {
             "app".into(),
             vec![("gleam_otp".into(), Range::new("~> 0.3.0-rc1".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -738,6 +1280,8 @@ mod tests {
             "app".into(),
             vec![("gleam_otp".into(), Range::new("0.3.0-rc1".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -759,6 +1303,8 @@ mod tests {
             "app".into(),
             vec![("unknown".into(), Range::new("~> 0.1".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap_err();
     }
@@ -771,6 +1317,8 @@ mod tests {
             "app".into(),
             vec![("gleam_stdlib".into(), Range::new("~> 99.0".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap_err();
     }
@@ -785,6 +1333,8 @@ mod tests {
             &vec![("gleam_stdlib".into(), Version::new(0, 2, 0))]
                 .into_iter()
                 .collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap_err();
 
@@ -805,6 +1355,8 @@ mod tests {
             "app".into(),
             vec![("gleam_stdlib".into(), Range::new("0.1.0".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -815,6 +1367,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resolution_log_records_decisions() {
+        let log = ResolutionLog::default();
+        let _ = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("gleam_otp".into(), Range::new("~> 0.1".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            Some(&log),
+        )
+        .unwrap();
+        let text = log.to_text();
+        assert!(text.starts_with(
+            "Resolving versions for app with the requirements:\n  gleam_otp ~> 0.1\n"
+        ));
+        assert!(text.contains("Chose gleam_otp 0.2.0 out of "));
+        assert!(text.contains("gleam_otp 0.2.0 depends on gleam_stdlib "));
+        assert!(text.contains("Chose gleam_stdlib 0.3.0 out of "));
+        assert!(text.contains("gleam_stdlib 0.3.0 has no dependencies\n"));
+        assert!(text.ends_with("Resolved versions:\n  gleam_otp 0.2.0\n  gleam_stdlib 0.3.0\n"));
+    }
+
+    #[test]
+    fn resolution_log_records_failure() {
+        let log = ResolutionLog::default();
+        let _ = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("gleam_stdlib".into(), Range::new("~> 99.0".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+            VersionPreference::Newest,
+            Some(&log),
+        )
+        .unwrap_err();
+        let text = log.to_text();
+        assert!(text.contains("No version of gleam_stdlib is in "));
+        assert!(text.contains("Failed to resolve versions: "));
+    }
+
     #[test]
     fn parse_exact_version_test() {
         assert_eq!(
@@ -1,15 +1,19 @@
 use std::collections::HashMap;
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 
 pub use codespan_reporting::diagnostic::{LabelStyle, Severity};
 use codespan_reporting::{diagnostic::Label as CodespanLabel, files::SimpleFiles};
 use ecow::EcoString;
+use serde::Serialize;
 use termcolor::Buffer;
 
-use crate::ast::SrcSpan;
+use itertools::Itertools;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use crate::{ast::SrcSpan, error::codes::ErrorCode, line_numbers::LineNumbers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Level {
     Error,
     Warning,
@@ -76,6 +80,109 @@ impl Diagnostic {
         }
     }
 
+    /// Writes this diagnostic on a single line, as
+    /// `path:line:column: severity: message`, which is the form the error
+    /// parsers of editors such as Vim's quickfix list and Emacs' compilation
+    /// mode recognise. Diagnostics without a location are written as
+    /// `severity: message`, and errors with a code as `error[E2004]`.
+    pub fn write_short(&self, code: Option<ErrorCode>, buffer: &mut Buffer) {
+        use std::io::Write;
+        let severity = match (self.level, code) {
+            (Level::Error, Some(code)) => format!("error[{code}]"),
+            (Level::Error, None) => "error".into(),
+            (Level::Warning, _) => "warning".into(),
+        };
+        let message = match self.location.as_ref().and_then(|l| l.label.text.as_ref()) {
+            Some(label) => format!("{}: {label}", self.title),
+            None => self.title.clone(),
+        };
+        // The message must not span several lines, or it would be read as
+        // several diagnostics.
+        let message = message.lines().map(str::trim).join(" ");
+        match &self.location {
+            Some(location) => {
+                let position = LineNumbers::new(&location.src)
+                    .line_and_column_number(location.label.span.start);
+                writeln!(
+                    buffer,
+                    "{}:{}:{}: {severity}: {message}",
+                    location.path, position.line, position.column
+                )
+            }
+            None => writeln!(buffer, "{severity}: {message}"),
+        }
+        .expect("write short diagnostic");
+    }
+
+    /// Makes the paths of the files this diagnostic refers to relative to the
+    /// given directory. Paths outside of it are left as they are.
+    pub fn make_paths_relative(&mut self, directory: &Utf8Path) {
+        let relative = |path: &mut Utf8PathBuf| {
+            if let Ok(relative) = path.strip_prefix(directory) {
+                *path = relative.to_path_buf();
+            }
+        };
+        if let Some(location) = &mut self.location {
+            relative(&mut location.path);
+            for extra in &mut location.extra_labels {
+                if let Some((_, path)) = &mut extra.src_info {
+                    relative(path);
+                }
+            }
+        }
+    }
+
+    /// Converts this diagnostic into its machine-readable form, as printed by
+    /// `glistix build --format json`, with the code of the kind of error it
+    /// reports, if any.
+    pub fn to_json(&self, code: Option<ErrorCode>) -> JsonDiagnostic {
+        let (file, span, label, related) = match &self.location {
+            None => (None, None, None, vec![]),
+            Some(location) => {
+                let line_numbers = LineNumbers::new(&location.src);
+                let related = location
+                    .extra_labels
+                    .iter()
+                    .map(|extra| {
+                        let (file, span) = match &extra.src_info {
+                            Some((src, path)) => (
+                                path.clone(),
+                                JsonSpan::new(extra.label.span, &LineNumbers::new(src)),
+                            ),
+                            None => (
+                                location.path.clone(),
+                                JsonSpan::new(extra.label.span, &line_numbers),
+                            ),
+                        };
+                        JsonRelated {
+                            file,
+                            span,
+                            message: extra.label.text.clone(),
+                        }
+                    })
+                    .collect();
+                (
+                    Some(location.path.clone()),
+                    Some(JsonSpan::new(location.label.span, &line_numbers)),
+                    location.label.text.clone(),
+                    related,
+                )
+            }
+        };
+
+        JsonDiagnostic {
+            file,
+            span,
+            severity: self.level,
+            code,
+            message: self.title.clone(),
+            label,
+            text: self.text.clone(),
+            hint: self.hint.clone(),
+            related,
+        }
+    }
+
     fn write_span(&self, location: &Location, buffer: &mut Buffer) {
         let mut file_map = HashMap::new();
         let mut files = SimpleFiles::new();
@@ -140,3 +247,157 @@ impl Diagnostic {
             .expect("write_title_reset");
     }
 }
+
+/// A diagnostic in a form suitable for consumption by other tools, such as
+/// editor plugins and CI annotations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JsonDiagnostic {
+    pub file: Option<Utf8PathBuf>,
+    pub span: Option<JsonSpan>,
+    pub severity: Level,
+    /// The stable code of errors, such as `E2004`. Warnings don't have codes.
+    pub code: Option<ErrorCode>,
+    pub message: String,
+    pub label: Option<String>,
+    pub text: String,
+    pub hint: Option<String>,
+    pub related: Vec<JsonRelated>,
+}
+
+/// A region of a source file. Byte offsets are 0 indexed, while lines and
+/// columns are 1 indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct JsonSpan {
+    pub start: u32,
+    pub end: u32,
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+impl JsonSpan {
+    fn new(span: SrcSpan, line_numbers: &LineNumbers) -> Self {
+        let start = line_numbers.line_and_column_number(span.start);
+        let end = line_numbers.line_and_column_number(span.end);
+        Self {
+            start: span.start,
+            end: span.end,
+            start_line: start.line,
+            start_column: start.column,
+            end_line: end.line,
+            end_column: end.column,
+        }
+    }
+}
+
+/// A secondary location related to a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JsonRelated {
+    pub file: Utf8PathBuf,
+    pub span: JsonSpan,
+    pub message: Option<String>,
+}
+
+#[test]
+fn diagnostic_to_json() {
+    let diagnostic = Diagnostic {
+        title: "Unknown variable".into(),
+        text: "The name `wibble` is not in scope here.".into(),
+        level: Level::Error,
+        location: Some(Location {
+            src: "pub fn main() {\n  wibble\n}\n".into(),
+            path: Utf8PathBuf::from("src/main.gleam"),
+            label: Label {
+                text: None,
+                span: SrcSpan::new(18, 24),
+            },
+            extra_labels: vec![ExtraLabel {
+                src_info: None,
+                label: Label {
+                    text: Some("In this function".into()),
+                    span: SrcSpan::new(7, 11),
+                },
+            }],
+        }),
+        hint: None,
+    };
+
+    assert_eq!(
+        serde_json::to_value(diagnostic.to_json(ErrorCode::parse("E2004"))).unwrap(),
+        serde_json::json!({
+            "file": "src/main.gleam",
+            "span": {
+                "start": 18,
+                "end": 24,
+                "start_line": 2,
+                "start_column": 3,
+                "end_line": 2,
+                "end_column": 9,
+            },
+            "severity": "error",
+            "code": "E2004",
+            "message": "Unknown variable",
+            "label": null,
+            "text": "The name `wibble` is not in scope here.",
+            "hint": null,
+            "related": [{
+                "file": "src/main.gleam",
+                "span": {
+                    "start": 7,
+                    "end": 11,
+                    "start_line": 1,
+                    "start_column": 8,
+                    "end_line": 1,
+                    "end_column": 12,
+                },
+                "message": "In this function",
+            }],
+        })
+    );
+}
+
+#[test]
+fn short_diagnostics() {
+    let mut diagnostic = Diagnostic {
+        title: "Unknown variable".into(),
+        text: "The name `wibble` is not in scope here.".into(),
+        level: Level::Error,
+        location: Some(Location {
+            src: "pub fn main() {\n  wibble\n}\n".into(),
+            path: Utf8PathBuf::from("/home/lucy/project/src/main.gleam"),
+            label: Label {
+                text: Some("Did you mean\n`wobble`?".into()),
+                span: SrcSpan::new(18, 24),
+            },
+            extra_labels: vec![],
+        }),
+        hint: None,
+    };
+    let short = |diagnostic: &Diagnostic, code: Option<ErrorCode>| {
+        let mut buffer = Buffer::no_color();
+        diagnostic.write_short(code, &mut buffer);
+        String::from_utf8(buffer.into_inner()).unwrap()
+    };
+
+    assert_eq!(
+        short(&diagnostic, None),
+        "/home/lucy/project/src/main.gleam:2:3: error: Unknown variable: Did you mean `wobble`?\n"
+    );
+
+    diagnostic.make_paths_relative(Utf8Path::new("/home/lucy/project"));
+    assert_eq!(
+        short(&diagnostic, None),
+        "src/main.gleam:2:3: error: Unknown variable: Did you mean `wobble`?\n"
+    );
+
+    assert_eq!(
+        short(&diagnostic, ErrorCode::parse("E2004")),
+        "src/main.gleam:2:3: error[E2004]: Unknown variable: Did you mean `wobble`?\n"
+    );
+
+    diagnostic.make_paths_relative(Utf8Path::new("/home/nubi"));
+    diagnostic.level = Level::Warning;
+    diagnostic.location = None;
+    assert_eq!(short(&diagnostic, None), "warning: Unknown variable\n");
+}
@@ -2,27 +2,29 @@ mod source_links;
 #[cfg(test)]
 mod tests;
 
-use std::time::SystemTime;
+use std::{collections::HashSet, time::SystemTime};
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 
 use crate::{
     ast::{
         CustomType, Definition, Function, ModuleConstant, Publicity, TypeAlias, TypedDefinition,
     },
-    build::{Module, Package},
+    build::{Module, Package, Target},
     config::{DocsPage, PackageConfig},
     docs::source_links::SourceLinker,
+    error::{Error, FileIoAction, FileKind},
     format,
     io::{Content, FileSystemReader, OutputFile},
     package_interface::PackageInterface,
     paths::ProjectPaths,
     pretty,
-    type_::Deprecation,
+    type_::{expression::Implementations, Deprecation},
     version::COMPILER_VERSION,
+    Result,
 };
 use askama::Template;
-use ecow::EcoString;
+use ecow::{eco_format, EcoString};
 use itertools::Itertools;
 use serde::Serialize;
 use serde_json::to_string as serde_to_string;
@@ -43,7 +45,7 @@ pub fn generate_html<IO: FileSystemReader>(
     fs: IO,
     rendering_timestamp: SystemTime,
     is_hex_publish: DocContext,
-) -> Vec<OutputFile> {
+) -> Result<Vec<OutputFile>> {
     let modules = analysed
         .iter()
         .filter(|module| !module.is_test())
@@ -156,6 +158,32 @@ pub fn generate_html<IO: FileSystemReader>(
         })
     }
 
+    // Read user-supplied assets, such as the images used by the pages. They
+    // are added once everything else is generated, so that they can't
+    // replace any of it.
+    let mut assets = vec![];
+    for asset in &config.documentation.assets {
+        let path = Utf8Path::new(&asset.path);
+        if path.is_absolute()
+            || path
+                .components()
+                .any(|component| !matches!(component, Utf8Component::Normal(_)))
+        {
+            return Err(Error::DocumentationAssetsOutsideDocs {
+                path: asset.path.clone(),
+            });
+        }
+        if !fs.is_directory(&asset.source) {
+            return Err(Error::FileIo {
+                kind: FileKind::Directory,
+                action: FileIoAction::Read,
+                path: asset.source.clone(),
+                err: Some("No such directory".into()),
+            });
+        }
+        copy_assets(&fs, &asset.source, path, &mut HashSet::new(), &mut assets)?;
+    }
+
     // Generate module documentation pages
     for module in modules {
         let name = module.name.clone();
@@ -462,7 +490,56 @@ pub fn generate_html<IO: FileSystemReader>(
         ),
     });
 
-    files
+    // Assets may not replace the generated pages, or the files they rely on
+    let generated: HashSet<_> = files.iter().map(|file| file.path.clone()).collect();
+    if let Some(asset) = assets.iter().find(|asset| generated.contains(&asset.path)) {
+        return Err(Error::DocumentationAssetOverwritesPage {
+            path: asset.path.clone(),
+        });
+    }
+    files.extend(assets);
+
+    Ok(files)
+}
+
+/// Adds the files of a directory of assets and of its subdirectories to the
+/// documentation, under the given path. As with pages, files that can't be
+/// read are left out. `visited` holds the canonical paths of the directories
+/// already copied, so that a symbolic link to one of them isn't followed
+/// forever.
+fn copy_assets<IO: FileSystemReader>(
+    fs: &IO,
+    source: &Utf8Path,
+    path: &Utf8Path,
+    visited: &mut HashSet<Utf8PathBuf>,
+    files: &mut Vec<OutputFile>,
+) -> Result<()> {
+    if !visited.insert(fs.canonicalise(source)?) {
+        return Ok(());
+    }
+    let Ok(entries) = fs.read_dir(source) else {
+        return Ok(());
+    };
+    let entries = entries
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.pathbuf)
+        .sorted();
+    for entry in entries {
+        let Some(name) = entry.file_name() else {
+            continue;
+        };
+        let destination = path.join(name);
+        if fs.is_directory(&entry) {
+            copy_assets(fs, &entry, &destination, visited, files)?;
+        } else if let Ok(content) = fs.read_bytes(&entry) {
+            files.push(OutputFile {
+                path: destination,
+                content: Content::Binary(content),
+            });
+        }
+    }
+    Ok(())
 }
 
 pub fn generate_json_package_interface(path: Utf8PathBuf, package: &Package) -> OutputFile {
@@ -475,6 +552,45 @@ pub fn generate_json_package_interface(path: Utf8PathBuf, package: &Package) ->
     }
 }
 
+/// Returns the fully qualified names of all the documented public functions
+/// and constants that cannot be used on the Nix target, sorted by name.
+///
+/// This is used to warn when documenting a package that targets Nix but whose
+/// public API is not fully Nix-compatible.
+///
+pub fn nix_incompatible_values(config: &PackageConfig, analysed: &[Module]) -> Vec<EcoString> {
+    analysed
+        .iter()
+        .filter(|module| !module.is_test())
+        .filter(|module| !config.is_internal_module(&module.name))
+        .flat_map(|module| {
+            module
+                .ast
+                .definitions
+                .iter()
+                .filter(|definition| !definition.is_internal())
+                .filter_map(|definition| match definition {
+                    Definition::Function(Function {
+                        publicity: Publicity::Public,
+                        name: Some((_, name)),
+                        implementations,
+                        ..
+                    })
+                    | Definition::ModuleConstant(ModuleConstant {
+                        publicity: Publicity::Public,
+                        name,
+                        implementations,
+                        ..
+                    }) if !implementations.supports(Target::Nix) => {
+                        Some(eco_format!("{}.{name}", module.name))
+                    }
+                    _ => None,
+                })
+        })
+        .sorted()
+        .collect()
+}
+
 fn page_unnest(path: &str) -> String {
     let unnest = path
         .strip_prefix('/')
@@ -543,12 +659,59 @@ fn function<'a>(
             return_type: ret,
             location,
             deprecation,
+            since,
+            implementations,
+            external_erlang,
+            external_javascript,
+            external_nix,
             ..
         }) => {
             let (_, name) = name
                 .as_ref()
                 .expect("Function in a definition must be named");
 
+            let externals = [
+                external_erlang
+                    .as_ref()
+                    .map(|(module, function, _)| External {
+                        target: "Erlang",
+                        module,
+                        function,
+                        // Erlang modules are found by their name anywhere in the
+                        // project, so there is no path to link to.
+                        source_url: "".into(),
+                    }),
+                external_javascript
+                    .as_ref()
+                    .map(|(module, function, _)| External {
+                        target: "JavaScript",
+                        module,
+                        function,
+                        // Other modules are imported from npm packages or
+                        // from the runtime.
+                        source_url: if module.starts_with('.') {
+                            source_links.relative_file_url(module)
+                        } else {
+                            "".into()
+                        },
+                    }),
+                external_nix.as_ref().map(|(module, function, _)| External {
+                    target: "Nix",
+                    module,
+                    function,
+                    // Only relative paths are files of the package, rather than
+                    // lookup paths such as `<nixpkgs>`. Importing a directory
+                    // imports its `default.nix`.
+                    source_url: if !is_relative_nix_path(module) {
+                        "".into()
+                    } else if module.ends_with(".nix") {
+                        source_links.relative_file_url(module)
+                    } else {
+                        source_links.relative_file_url(&format!("{module}/default.nix"))
+                    },
+                }),
+            ];
+
             Some(DocsFunction {
                 name,
                 documentation: markdown_documentation(doc),
@@ -563,6 +726,9 @@ fn function<'a>(
                     Deprecation::NotDeprecated => "".to_string(),
                     Deprecation::Deprecated { message } => message.to_string(),
                 },
+                since: since.as_deref(),
+                target_badges: TargetBadges::from_implementations(implementations),
+                externals: externals.into_iter().flatten().collect(),
             })
         }
 
@@ -570,6 +736,10 @@ fn function<'a>(
     }
 }
 
+fn is_relative_nix_path(module: &str) -> bool {
+    matches!(module, "." | "..") || module.starts_with("./") || module.starts_with("../")
+}
+
 fn text_documentation(doc: &Option<(u32, EcoString)>) -> String {
     let raw_text = doc
         .as_ref()
@@ -629,6 +799,7 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
                 Deprecation::NotDeprecated => "".to_string(),
                 Deprecation::Deprecated { message } => message.to_string(),
             },
+            since: ct.since.as_deref(),
             constructors: ct
                 .constructors
                 .iter()
@@ -660,6 +831,7 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
             documentation: doc,
             location,
             deprecation,
+            since,
             ..
         }) => Some(Type {
             name,
@@ -676,6 +848,7 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
                 Deprecation::NotDeprecated => "".to_string(),
                 Deprecation::Deprecated { message } => message.to_string(),
             },
+            since: since.as_deref(),
             opaque: true,
         }),
 
@@ -687,12 +860,21 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
             parameters: args,
             location,
             deprecation,
+            since,
             ..
         }) => Some(Type {
             name,
             definition: print(
                 formatter
-                    .type_alias(Publicity::Public, name, args, type_, deprecation, location)
+                    .type_alias(
+                        Publicity::Public,
+                        name,
+                        args,
+                        type_,
+                        deprecation,
+                        &None,
+                        location,
+                    )
                     .group(),
             ),
             documentation: markdown_documentation(doc),
@@ -703,6 +885,7 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
                 Deprecation::NotDeprecated => "".to_string(),
                 Deprecation::Deprecated { message } => message.to_string(),
             },
+            since: since.as_deref(),
             opaque: false,
         }),
 
@@ -722,6 +905,8 @@ fn constant<'a>(
             name,
             value,
             location,
+            implementations,
+            since,
             ..
         }) => Some(Constant {
             name,
@@ -729,6 +914,8 @@ fn constant<'a>(
             documentation: markdown_documentation(doc),
             text_documentation: text_documentation(doc),
             source_url: source_links.url(*location),
+            since: since.as_deref(),
+            target_badges: TargetBadges::from_implementations(implementations),
         }),
 
         _ => None,
@@ -753,6 +940,48 @@ struct DocsFunction<'a> {
     text_documentation: String,
     source_url: String,
     deprecation_message: String,
+    since: Option<&'a str>,
+    target_badges: Option<TargetBadges>,
+    externals: Vec<External<'a>>,
+}
+
+/// An external implementation of a documented function, for one of the
+/// targets.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct External<'a> {
+    target: &'static str,
+    module: &'a str,
+    function: &'a str,
+    /// Where the file implementing the function can be seen in the
+    /// repository, if it is part of it.
+    source_url: String,
+}
+
+/// The targets a documented function or constant can be used on, rendered as
+/// badges next to its name.
+///
+/// Values with a pure Gleam implementation run on every target, so badges are
+/// only shown for values that rely on external code.
+///
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+struct TargetBadges {
+    erlang: bool,
+    javascript: bool,
+    nix: bool,
+}
+
+impl TargetBadges {
+    fn from_implementations(implementations: &Implementations) -> Option<Self> {
+        if implementations.gleam {
+            return None;
+        }
+
+        Some(Self {
+            erlang: implementations.supports(Target::Erlang),
+            javascript: implementations.supports(Target::JavaScript),
+            nix: implementations.supports(Target::Nix),
+        })
+    }
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
@@ -778,6 +1007,7 @@ struct Type<'a> {
     text_documentation: String,
     source_url: String,
     deprecation_message: String,
+    since: Option<&'a str>,
     opaque: bool,
 }
 
@@ -788,6 +1018,8 @@ struct Constant<'a> {
     documentation: String,
     text_documentation: String,
     source_url: String,
+    since: Option<&'a str>,
+    target_badges: Option<TargetBadges>,
 }
 
 #[derive(Template)]
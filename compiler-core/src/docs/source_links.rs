@@ -10,7 +10,21 @@ use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 
 pub struct SourceLinker {
     line_numbers: LineNumbers,
-    url_pattern: Option<(String, String)>,
+    /// The path of the module's source file in the repository.
+    path_in_repo: Utf8PathBuf,
+    repository: Option<RepositoryUrls>,
+}
+
+/// How to link to the files of the repository at the tag of the version being
+/// documented.
+struct RepositoryUrls {
+    /// The URL of the root of the repository, which paths are appended to.
+    base: String,
+    /// What goes between a path and the first line of the range of lines to
+    /// link to.
+    line_anchor: &'static str,
+    /// What goes between the first and the last line of that range.
+    line_separator: &'static str,
 }
 
 impl SourceLinker {
@@ -26,84 +40,104 @@ impl SourceLinker {
             .expect("path is not in root")
             .with_extension("gleam");
 
-        let path_in_repo = if let Some(repo_path) = project_config.repository.path() {
-            to_url_path(&Utf8PathBuf::from(repo_path).join(path))
-        } else {
-            to_url_path(&path)
-        }
-        .unwrap_or_default();
+        let path_in_repo = match project_config.repository.path() {
+            Some(repo_path) => Utf8PathBuf::from(repo_path).join(path),
+            None => path,
+        };
 
-        let url_pattern = match &project_config.repository {
-            Repository::GitHub { user, repo, .. } => Some((
-                format!(
-                    "https://github.com/{}/{}/blob/v{}/{}#L",
-                    user, repo, project_config.version, path_in_repo
-                ),
-                "-L".into(),
-            )),
-            Repository::GitLab { user, repo, .. } => Some((
-                format!(
-                    "https://gitlab.com/{}/{}/-/blob/v{}/{}#L",
-                    user, repo, project_config.version, path_in_repo
-                ),
-                "-".into(),
-            )),
-            Repository::BitBucket { user, repo, .. } => Some((
-                format!(
-                    "https://bitbucket.com/{}/{}/src/v{}/{}#lines-",
-                    user, repo, project_config.version, path_in_repo
-                ),
-                ":".into(),
-            )),
-            Repository::Codeberg { user, repo, .. } => Some((
-                format!(
-                    "https://codeberg.org/{}/{}/src/tag/v{}/{}#L",
-                    user, repo, project_config.version, path_in_repo
-                ),
-                "-".into(),
-            )),
-            Repository::SourceHut { user, repo, .. } => Some((
-                format!(
-                    "https://git.sr.ht/~{}/{}/tree/v{}/item/{}#L",
-                    user, repo, project_config.version, path_in_repo
-                ),
-                "-".into(),
-            )),
+        let version = &project_config.version;
+        let repository = match &project_config.repository {
+            Repository::GitHub { user, repo, .. } => Some(RepositoryUrls {
+                base: format!("https://github.com/{user}/{repo}/blob/v{version}/"),
+                line_anchor: "#L",
+                line_separator: "-L",
+            }),
+            Repository::GitLab { user, repo, .. } => Some(RepositoryUrls {
+                base: format!("https://gitlab.com/{user}/{repo}/-/blob/v{version}/"),
+                line_anchor: "#L",
+                line_separator: "-",
+            }),
+            Repository::BitBucket { user, repo, .. } => Some(RepositoryUrls {
+                base: format!("https://bitbucket.com/{user}/{repo}/src/v{version}/"),
+                line_anchor: "#lines-",
+                line_separator: ":",
+            }),
+            Repository::Codeberg { user, repo, .. } => Some(RepositoryUrls {
+                base: format!("https://codeberg.org/{user}/{repo}/src/tag/v{version}/"),
+                line_anchor: "#L",
+                line_separator: "-",
+            }),
+            Repository::SourceHut { user, repo, .. } => Some(RepositoryUrls {
+                base: format!("https://git.sr.ht/~{user}/{repo}/tree/v{version}/item/"),
+                line_anchor: "#L",
+                line_separator: "-",
+            }),
             Repository::Gitea {
                 user, repo, host, ..
-            } => Some((
-                format!(
-                    "{host}/{user}/{repo}/src/tag/v{}/{}#L",
-                    project_config.version, path_in_repo
-                ),
-                "-".into(),
-            )),
+            } => Some(RepositoryUrls {
+                base: format!("{host}/{user}/{repo}/src/tag/v{version}/"),
+                line_anchor: "#L",
+                line_separator: "-",
+            }),
             Repository::Custom { .. } | Repository::None => None,
         };
 
         SourceLinker {
             line_numbers: LineNumbers::new(&module.code),
-            url_pattern,
+            path_in_repo,
+            repository,
         }
     }
+
     pub fn url(&self, span: SrcSpan) -> String {
-        match &self.url_pattern {
-            Some((base, line_sep)) => {
+        match &self.repository {
+            Some(repository) => {
                 let start_line = self.line_numbers.line_number(span.start);
                 let end_line = self.line_numbers.line_number(span.end);
-                format!("{base}{start_line}{line_sep}{end_line}")
+                format!(
+                    "{}{}{}{start_line}{}{end_line}",
+                    repository.base,
+                    to_url_path(&self.path_in_repo).unwrap_or_default(),
+                    repository.line_anchor,
+                    repository.line_separator,
+                )
             }
 
             None => "".into(),
         }
     }
+
+    /// The URL of a file given by a path relative to the module's source
+    /// file, such as the `./ffi.nix` of an external implementation. Paths
+    /// leaving the repository can't be linked to.
+    pub fn relative_file_url(&self, relative_path: &str) -> String {
+        let Some(repository) = &self.repository else {
+            return "".into();
+        };
+        let mut path = self.path_in_repo.clone();
+        let _ = path.pop();
+        for component in Utf8Path::new(relative_path).components() {
+            match component {
+                Utf8Component::CurDir => (),
+                Utf8Component::ParentDir if path.pop() => (),
+                Utf8Component::Normal(name) => path.push(name),
+                Utf8Component::ParentDir | Utf8Component::RootDir | Utf8Component::Prefix(_) => {
+                    return "".into()
+                }
+            }
+        }
+        match to_url_path(&path) {
+            Some(path) if !path.is_empty() => format!("{}{path}", repository.base),
+            _ => "".into(),
+        }
+    }
 }
 
 fn to_url_path(path: &Utf8Path) -> Option<String> {
     let mut buf = String::new();
     for c in path.components() {
         if let Utf8Component::Normal(s) = c {
-            buf.push_str(s);
+            percent_encode(s, &mut buf);
         }
         buf.push('/');
     }
@@ -112,3 +146,15 @@ fn to_url_path(path: &Utf8Path) -> Option<String> {
 
     Some(buf)
 }
+
+/// Percent-encodes a path segment, leaving only the characters that never
+/// need escaping in a URL as they are.
+fn percent_encode(segment: &str, buf: &mut String) {
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            buf.push(byte as char);
+        } else {
+            buf.push_str(&format!("%{byte:02X}"));
+        }
+    }
+}
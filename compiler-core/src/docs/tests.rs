@@ -2,9 +2,10 @@ use std::{collections::HashSet, time::SystemTime};
 
 use crate::{
     build::{Mode, NullTelemetry, PackageCompiler, StaleTracker, TargetCodegenConfiguration},
-    config::{DocsPage, PackageConfig, Repository},
+    config::{DocsAssets, DocsPage, PackageConfig, Repository},
     docs::DocContext,
-    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    error::{Error, FileIoAction, FileKind},
+    io::{memory::InMemoryFileSystem, Content, FileSystemWriter, OutputFile},
     paths::ProjectPaths,
     uid::UniqueIdGenerator,
     version::COMPILER_VERSION,
@@ -96,6 +97,7 @@ fn compile_with_markdown_pages(
             DocContext::HexPublish
         },
     )
+    .expect("documentation should be generated")
     .into_iter()
     .filter(|file| file.path.extension() == Some("html"))
     .sorted_by(|a, b| a.path.cmp(&b.path))
@@ -494,3 +496,211 @@ pub fn one() {
         }
     ));
 }
+
+#[test]
+fn target_badges_are_shown_for_values_using_externals() {
+    let mut config = PackageConfig::default();
+    config.name = EcoString::from("test_project_name");
+    let modules = vec![(
+        "app.gleam",
+        r#"
+@external(erlang, "wibble", "wobble")
+pub fn erlang_only() -> Int
+
+pub fn pure() -> Int {
+  1
+}
+"#,
+    )];
+
+    let output = compile(config, modules);
+    assert!(output.contains(
+        r#"<span class="target-badge" title="Can be used on the Erlang target">Erlang</span>"#
+    ));
+    assert!(output.contains(
+        r#"<span class="target-badge target-unsupported" title="Cannot be used on the Nix target">Nix</span>"#
+    ));
+    assert_eq!(output.matches(r#"<span class="target-badges">"#).count(), 1);
+}
+
+#[test]
+fn assets_are_copied_into_the_documentation() {
+    let mut config = PackageConfig::default();
+    config.documentation.assets = vec![DocsAssets {
+        path: "images".into(),
+        source: "docs/images".into(),
+    }];
+    let fs = InMemoryFileSystem::new();
+    fs.write_bytes(&Utf8PathBuf::from("docs/images/logo.png"), &[1, 2, 3])
+        .unwrap();
+    fs.write(&Utf8PathBuf::from("docs/images/diagram.svg"), "<svg/>")
+        .unwrap();
+    fs.write(&Utf8PathBuf::from("docs/images/icons/nix.svg"), "<svg/>")
+        .unwrap();
+    fs.write(&Utf8PathBuf::from("docs/guide.md"), "# Guide")
+        .unwrap();
+
+    let files = super::generate_html(
+        &ProjectPaths::new(Utf8PathBuf::from("/")),
+        &config,
+        &[],
+        &[],
+        fs,
+        SystemTime::UNIX_EPOCH,
+        DocContext::Build,
+    )
+    .expect("documentation should be generated");
+    let assets = files
+        .iter()
+        .filter(|file| file.path.starts_with("images"))
+        .map(|file| (file.path.as_str(), &file.content))
+        .collect_vec();
+    assert_eq!(
+        assets,
+        vec![
+            ("images/diagram.svg", &Content::Binary(b"<svg/>".to_vec())),
+            ("images/icons/nix.svg", &Content::Binary(b"<svg/>".to_vec())),
+            ("images/logo.png", &Content::Binary(vec![1, 2, 3])),
+        ]
+    );
+}
+
+fn generate_with_assets(path: &str, fs: InMemoryFileSystem) -> Result<Vec<OutputFile>, Error> {
+    let mut config = PackageConfig::default();
+    config.documentation.assets = vec![DocsAssets {
+        path: path.into(),
+        source: "docs/images".into(),
+    }];
+    super::generate_html(
+        &ProjectPaths::new(Utf8PathBuf::from("/")),
+        &config,
+        &[],
+        &[],
+        fs,
+        SystemTime::UNIX_EPOCH,
+        DocContext::Build,
+    )
+}
+
+#[test]
+fn assets_cannot_be_copied_outside_the_documentation() {
+    let fs = InMemoryFileSystem::new();
+    fs.write(&Utf8PathBuf::from("docs/images/logo.svg"), "<svg/>")
+        .unwrap();
+
+    for path in ["../images", "images/../..", "/images"] {
+        assert_eq!(
+            generate_with_assets(path, fs.clone()),
+            Err(Error::DocumentationAssetsOutsideDocs { path: path.into() })
+        );
+    }
+}
+
+#[test]
+fn assets_cannot_overwrite_generated_files() {
+    let fs = InMemoryFileSystem::new();
+    fs.write(&Utf8PathBuf::from("docs/images/index.css"), "body {}")
+        .unwrap();
+
+    assert_eq!(
+        generate_with_assets("css", fs),
+        Err(Error::DocumentationAssetOverwritesPage {
+            path: "css/index.css".into()
+        })
+    );
+}
+
+#[test]
+fn missing_assets_directory_is_an_error() {
+    let result = generate_with_assets("images", InMemoryFileSystem::new());
+    assert!(matches!(
+        result,
+        Err(Error::FileIo {
+            kind: FileKind::Directory,
+            action: FileIoAction::Read,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn externals_link_to_their_source() {
+    let mut config = PackageConfig::default();
+    config.name = EcoString::from("test_project_name");
+    config.repository = Repository::GitHub {
+        user: "wibble".to_string(),
+        repo: "wobble".to_string(),
+        path: None,
+    };
+    let modules = vec![(
+        "wibble/app.gleam",
+        r#"
+@external(erlang, "wibble_ffi", "wobble")
+@external(javascript, "../ffi.mjs", "wobble")
+@external(nix, "./ffi", "wobble")
+pub fn wobble() -> Int
+"#,
+    )];
+
+    let output = compile(config, modules);
+    assert!(output.contains(r#"<span class="target-badge">Erlang</span>"#));
+    assert!(output.contains("<code>wibble_ffi</code>"));
+    assert!(output.contains(r#"href="https://github.com/wibble/wobble/blob/v0.1.0/src/ffi.mjs""#));
+    assert!(output.contains(
+        r#"href="https://github.com/wibble/wobble/blob/v0.1.0/src/wibble/ffi/default.nix""#
+    ));
+}
+
+#[test]
+fn only_relative_nix_paths_are_linked() {
+    assert!(super::is_relative_nix_path("./ffi.nix"));
+    assert!(super::is_relative_nix_path("../ffi"));
+    assert!(super::is_relative_nix_path(".."));
+    assert!(!super::is_relative_nix_path("<nixpkgs>"));
+    assert!(!super::is_relative_nix_path("/nix/store/ffi.nix"));
+}
+
+#[test]
+fn external_source_links_are_percent_encoded() {
+    let mut config = PackageConfig::default();
+    config.name = EcoString::from("test_project_name");
+    config.repository = Repository::GitHub {
+        user: "wibble".to_string(),
+        repo: "wobble".to_string(),
+        path: None,
+    };
+    let modules = vec![(
+        "app.gleam",
+        r#"
+@external(nix, "./ffi:v1.nix", "wobble")
+pub fn wobble() -> Int
+"#,
+    )];
+
+    let output = compile(config, modules);
+    assert!(
+        output.contains(r#"href="https://github.com/wibble/wobble/blob/v0.1.0/src/ffi%3Av1.nix""#)
+    );
+}
+
+#[test]
+fn externals_outside_the_repository_are_not_linked() {
+    let mut config = PackageConfig::default();
+    config.name = EcoString::from("test_project_name");
+    config.repository = Repository::GitHub {
+        user: "wibble".to_string(),
+        repo: "wobble".to_string(),
+        path: None,
+    };
+    let modules = vec![(
+        "app.gleam",
+        r#"
+@external(nix, "../../ffi.nix", "wobble")
+pub fn wobble() -> Int
+"#,
+    )];
+
+    let output = compile(config, modules);
+    assert!(output.contains(r#"<span class="target-badge">Nix</span>"#));
+    assert!(!output.contains("ffi.nix\""));
+}
@@ -1,7 +1,12 @@
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 use crate::build::{Outcome, Runtime, Target};
+use crate::config::ListRepresentation;
+use crate::dependency::ConflictContext;
 use crate::diagnostic::{Diagnostic, ExtraLabel, Label, Location};
+use crate::i18n::{self, Key};
+use crate::line_numbers::LineNumbers;
 use crate::type_::error::{
+    CallSite, DeriveDecoderProblem, DeriveNixAttrsProblem, DeriveNixosOptionsProblem,
     MissingAnnotation, ModuleValueUsageContext, Named, UnknownField, UnknownTypeHint,
     UnsafeRecordUpdateReason,
 };
@@ -13,11 +18,7 @@ use ecow::EcoString;
 use heck::{ToSnakeCase, ToTitleCase, ToUpperCamelCase};
 use hexpm::version::ResolutionError;
 use itertools::Itertools;
-use pubgrub::package::Package;
-use pubgrub::report::DerivationTree;
-use pubgrub::version::Version;
 use std::borrow::Cow;
-use std::collections::HashSet;
 use std::fmt::{Debug, Display};
 use std::io::Write;
 use std::path::PathBuf;
@@ -31,6 +32,7 @@ pub type Name = EcoString;
 
 pub type Result<Ok, Err = Error> = std::result::Result<Ok, Err>;
 
+pub mod codes;
 #[cfg(test)]
 pub mod tests;
 
@@ -65,6 +67,15 @@ pub enum Error {
         error: crate::parse::error::ParseError,
     },
 
+    #[error("file {file} could not be embedded")]
+    EmbeddedFile {
+        path: Utf8PathBuf,
+        src: EcoString,
+        location: crate::ast::SrcSpan,
+        file: EcoString,
+        error: EmbeddedFileError,
+    },
+
     #[error("type checking failed")]
     Type {
         path: Utf8PathBuf,
@@ -126,6 +137,12 @@ pub enum Error {
     #[error("{error}")]
     GitInitialization { error: String },
 
+    #[error("failed to find files changed since {since}")]
+    GitChangedFiles { since: String, error: String },
+
+    #[error("git {command} failed")]
+    GitCommand { command: String, error: String },
+
     #[error("io operation failed")]
     StandardIo {
         action: StandardIoAction,
@@ -159,6 +176,20 @@ pub enum Error {
         err: Option<std::io::ErrorKind>,
     },
 
+    #[error("codegen hook `{command}` failed for the {target} target")]
+    CodegenHook {
+        command: String,
+        target: Target,
+        status: i32,
+    },
+
+    #[error("benchmark {benchmark} failed on the {target} target")]
+    BenchmarkFailed {
+        benchmark: EcoString,
+        target: Target,
+        status: i32,
+    },
+
     #[error("{name} is not a valid project name")]
     InvalidProjectName {
         name: String,
@@ -168,6 +199,15 @@ pub enum Error {
     #[error("{module} is not a valid module name")]
     InvalidModuleName { module: String },
 
+    #[error("line {line} of the env file {path} is invalid")]
+    InvalidEnvFile { path: Utf8PathBuf, line: usize },
+
+    #[error("the config key {key} is not set")]
+    ConfigKeyNotFound { key: EcoString },
+
+    #[error("the config key {key} can't be set as {table} is not a table")]
+    ConfigKeyInNonTable { key: EcoString, table: EcoString },
+
     #[error("{module} is not module")]
     ModuleDoesNotExist {
         module: EcoString,
@@ -183,6 +223,19 @@ pub enum Error {
     #[error("{module}'s main function does not support the current target")]
     MainFunctionDoesNotSupportTarget { module: EcoString, target: Target },
 
+    #[error("building the project twice produced different files")]
+    NonDeterministicBuild { files: Vec<Utf8PathBuf> },
+
+    #[error("the generated code differs from the committed copy")]
+    GeneratedOutputDiffers {
+        generated: Utf8PathBuf,
+        committed: Utf8PathBuf,
+        differences: Vec<OutputDifference>,
+    },
+
+    #[error("this build of the compiler can't generate code for {target}")]
+    TargetNotEnabled { target: Target },
+
     #[error("{input} is not a valid version. {error}")]
     InvalidVersionFormat { input: String, error: String },
 
@@ -196,6 +249,9 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
     #[error("Packages not exist: {}", packages.iter().join(", "))]
     RemovedPackagesNotExist { packages: Vec<String> },
 
+    #[error("Only one package can be added from a path or git repository")]
+    AddedPackagesShareSource { packages: Vec<String> },
+
     #[error("unable to find project root")]
     UnableToFindProjectRoot { path: String },
 
@@ -298,6 +354,21 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
     #[error("The --nix-prelude flag must be given when compiling to Nix")]
     NixPreludeRequired,
 
+    #[error("Precompiled package {package} uses an incompatible Nix representation")]
+    IncompatibleNixRepresentation {
+        package: EcoString,
+        /// `None` when the package was compiled before representation
+        /// versions were recorded.
+        found: Option<u32>,
+    },
+
+    #[error("Package {package} uses an incompatible Nix list representation")]
+    IncompatibleListRepresentation {
+        package: EcoString,
+        expected: ListRepresentation,
+        found: ListRepresentation,
+    },
+
     #[error("Cannot patch Hex dependency {name} through [glistix.preview.hex-patch]")]
     CannotPatchHexWithHex { name: EcoString },
 
@@ -319,6 +390,21 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
     #[error("Version already published")]
     HexPublishReplaceRequired { version: String },
 
+    #[error("Downloading the dependencies requires Hex credentials")]
+    HexCredentialsRequired,
+
+    #[error("The registry token environment variable {name} is not set")]
+    RegistryTokenMissing { name: String },
+
+    #[error("The registry responded to the upload of {file} with status {status}")]
+    RegistryUploadFailed { file: String, status: u16 },
+
+    #[error("Dependencies use licences not allowed by the dependency policy")]
+    DisallowedDependencyLicences {
+        packages: Vec<(EcoString, Vec<EcoString>)>,
+        allowed_licences: Vec<EcoString>,
+    },
+
     #[error("The gleam version constraint is wrong and so cannot be published")]
     CannotPublishWrongVersion {
         minimum_required_version: SmallVersion,
@@ -330,6 +416,29 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
 
     #[error("Failed to decrypt data")]
     FailedToDecrypt { detail: String },
+
+    #[error("{code} is not an error code")]
+    UnknownErrorCode { code: String },
+
+    #[error("The package failed to compile for the supported targets {targets:?}")]
+    CannotPublishFailingTargets { targets: Vec<Target> },
+
+    #[error("{package} is not a dependency")]
+    UnknownDependency { package: EcoString },
+
+    #[error("Git dependency {package} is not locked in the manifest")]
+    GitDependencyNotLocked { package: EcoString },
+
+    #[error("Documentation assets at {path} would be outside the documentation")]
+    DocumentationAssetsOutsideDocs { path: String },
+
+    #[error("Documentation asset {path} would overwrite a generated file")]
+    DocumentationAssetOverwritesPage { path: Utf8PathBuf },
+
+    /// The error has already been reported, in the format the user asked for,
+    /// so there is nothing more to print.
+    #[error("The error has already been reported")]
+    AlreadyReported,
 }
 
 /// This is to make clippy happy and not make the error variant too big by
@@ -424,48 +533,14 @@ impl Error {
         Self::TarFinish(error.to_string())
     }
 
-    pub fn dependency_resolution_failed(error: ResolutionError) -> Error {
-        fn collect_conflicting_packages<'dt, P: Package, V: Version>(
-            derivation_tree: &'dt DerivationTree<P, V>,
-            conflicting_packages: &mut HashSet<&'dt P>,
-        ) {
-            match derivation_tree {
-                DerivationTree::External(external) => match external {
-                    pubgrub::report::External::NotRoot(package, _) => {
-                        let _ = conflicting_packages.insert(package);
-                    }
-                    pubgrub::report::External::NoVersions(package, _) => {
-                        let _ = conflicting_packages.insert(package);
-                    }
-                    pubgrub::report::External::UnavailableDependencies(package, _) => {
-                        let _ = conflicting_packages.insert(package);
-                    }
-                    pubgrub::report::External::FromDependencyOf(package, _, dep_package, _) => {
-                        let _ = conflicting_packages.insert(package);
-                        let _ = conflicting_packages.insert(dep_package);
-                    }
-                },
-                DerivationTree::Derived(derived) => {
-                    collect_conflicting_packages(&derived.cause1, conflicting_packages);
-                    collect_conflicting_packages(&derived.cause2, conflicting_packages);
-                }
-            }
-        }
-
+    pub fn dependency_resolution_failed(
+        error: ResolutionError,
+        context: &ConflictContext<'_>,
+    ) -> Error {
         Self::DependencyResolutionFailed(match error {
             ResolutionError::NoSolution(mut derivation_tree) => {
                 derivation_tree.collapse_no_versions();
-
-                let mut conflicting_packages = HashSet::new();
-                collect_conflicting_packages(&derivation_tree, &mut conflicting_packages);
-
-                wrap_format!("Unable to find compatible versions for \
-the version constraints in your gleam.toml. \
-The conflicting packages are:
-
-{}
-",
-                    conflicting_packages.into_iter().map(|s| format!("- {s}")).join("\n"))
+                context.explain(&derivation_tree)
             }
 
             ResolutionError::ErrorRetrievingDependencies {
@@ -567,7 +642,7 @@ pub enum FileIoAction {
     Read,
     Parse,
     Delete,
-    // Rename,
+    Rename,
     Create,
     WriteTo,
     Canonicalise,
@@ -585,7 +660,7 @@ impl FileIoAction {
             FileIoAction::Read => "read",
             FileIoAction::Parse => "parse",
             FileIoAction::Delete => "delete",
-            // FileIoAction::Rename => "rename",
+            FileIoAction::Rename => "rename",
             FileIoAction::Create => "create",
             FileIoAction::WriteTo => "write to",
             FileIoAction::FindParent => "find the parent of",
@@ -596,6 +671,27 @@ impl FileIoAction {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddedFileError {
+    NotFound,
+    OutsidePackage,
+    TooLarge { size: usize },
+    InvalidUtf8,
+    UnsupportedType,
+}
+
+/// How a file generated by the compiler differs from its committed copy, by
+/// path relative to the output directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputDifference {
+    /// The file is generated but wasn't committed.
+    Added { path: Utf8PathBuf },
+    /// The file was committed but isn't generated anymore.
+    Removed { path: Utf8PathBuf },
+    /// Both copies exist, and they first differ on this line.
+    Changed { path: Utf8PathBuf, line: usize },
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileKind {
     File,
@@ -796,6 +892,30 @@ impl Error {
         }
     }
 
+    /// The diagnostics of this error, each along with the stable code of the
+    /// kind of error it reports.
+    pub fn to_diagnostics_with_codes(&self) -> Vec<(Diagnostic, Option<codes::ErrorCode>)> {
+        let diagnostics = self.to_diagnostics();
+        match self {
+            // Each type error is reported as a diagnostic of its own
+            Error::Type { errors, .. } => diagnostics
+                .into_iter()
+                .zip(
+                    errors
+                        .iter()
+                        .map(|error| Some(codes::type_error_code(error))),
+                )
+                .collect(),
+            _ => {
+                let code = codes::error_code(self);
+                diagnostics
+                    .into_iter()
+                    .map(|diagnostic| (diagnostic, code))
+                    .collect()
+            }
+        }
+    }
+
     pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
         use crate::type_::Error as TypeError;
         match self {
@@ -880,6 +1000,37 @@ forward slash and must not end with a slash."
                 hint: None,
             }],
 
+            Error::InvalidEnvFile { path, line } => vec![Diagnostic {
+                title: "Invalid env file".into(),
+                text: format!(
+                    "Line {line} of the env file `{path}` could not be read.
+Each line must either be empty, be a comment starting with `#`, or
+assign a value to a variable name using `NAME=value`."
+                ),
+                level: Level::Error,
+                location: None,
+                hint: None,
+            }],
+
+            Error::ConfigKeyNotFound { key } => vec![Diagnostic {
+                title: "Config key not found".into(),
+                text: format!("The key `{key}` is not set in gleam.toml."),
+                level: Level::Error,
+                location: None,
+                hint: None,
+            }],
+
+            Error::ConfigKeyInNonTable { key, table } => vec![Diagnostic {
+                title: "Invalid config key".into(),
+                text: format!(
+                    "The key `{key}` can't be set in gleam.toml as `{table}` is
+already set to a value which is not a table."
+                ),
+                level: Level::Error,
+                location: None,
+                hint: None,
+            }],
+
             Error::ModuleDoesNotExist { module, suggestion } => {
                 let hint = match suggestion {
                     Some(suggestion) => format!("Did you mean `{suggestion}`?"),
@@ -918,6 +1069,66 @@ target, so it cannot be run."
                 hint: None,
             }],
 
+            Error::NonDeterministicBuild { files } => {
+                let files = files.iter().map(|file| format!("  - {file}")).join("\n");
+                vec![Diagnostic {
+                    title: "Build is not deterministic".into(),
+                    text: format!(
+                        "Building the project twice from scratch produced different \
+contents for these files:\n\n{files}"
+                    ),
+                    level: Level::Error,
+                    location: None,
+                    hint: Some(
+                        "Please report this, as reproducible builds are needed for Nix \
+derivations."
+                            .into(),
+                    ),
+                }]
+            }
+
+            Error::GeneratedOutputDiffers {
+                generated,
+                committed,
+                differences,
+            } => {
+                let differences = differences
+                    .iter()
+                    .map(|difference| match difference {
+                        OutputDifference::Added { path } => format!("  - added: {path}"),
+                        OutputDifference::Removed { path } => format!("  - removed: {path}"),
+                        OutputDifference::Changed { path, line } => {
+                            format!("  - changed: {path} (from line {line})")
+                        }
+                    })
+                    .join("\n");
+                vec![Diagnostic {
+                    title: "Generated code differs from the committed copy".into(),
+                    text: format!(
+                        "The code generated for this project doesn't match the copy \
+committed in {committed}:\n\n{differences}"
+                    ),
+                    level: Level::Error,
+                    location: None,
+                    hint: Some(format!(
+                        "Replace the contents of {committed} with those of {generated}, \
+leaving out the `{}` directory, and commit them.",
+                        crate::paths::ARTEFACT_DIRECTORY_NAME
+                    )),
+                }]
+            }
+
+            Error::TargetNotEnabled { target } => vec![Diagnostic {
+                title: "Target not enabled".into(),
+                text: wrap_format!(
+                    "This build of the compiler doesn't include the code generator for the \
+{target} target, so packages can't be compiled to it."
+                ),
+                level: Level::Error,
+                location: None,
+                hint: None,
+            }],
+
             Error::MainFunctionHasWrongArity { module, arity } => vec![Diagnostic {
                 title: "Main function has wrong arity".into(),
                 text: format!(
@@ -980,6 +1191,24 @@ be removed.
                 }
             ],
 
+            Error::AddedPackagesShareSource { packages } => vec![Diagnostic {
+                title: "Too many packages".into(),
+                text: format!(
+                    "A path or git repository can only be given when adding a single
+package, but these packages were listed:
+
+{}
+",
+                    packages
+                        .iter()
+                        .map(|p| format!("  - {}", p.as_str()))
+                        .join("\n")
+                ),
+                level: Level::Error,
+                hint: Some("Add each package with its own `glistix add` command.".into()),
+                location: None,
+            }],
+
             Error::CannotPublishTodo { unfinished } => vec![Diagnostic {
                 title: "Cannot publish unfinished code".into(),
                 text: format!(
@@ -999,6 +1228,72 @@ Please remove them and try again.
                 location: None,
             }],
 
+            Error::CannotPublishFailingTargets { targets } => vec![Diagnostic {
+                title: "Cannot publish code failing on supported targets".into(),
+                text: format!(
+                    "The package could not be compiled for these targets, which the \
+`supported-targets` field of the `[glistix]` section of gleam.toml lists as supported:
+
+{}
+
+Please fix the errors reported above and try again.
+",
+                    targets
+                        .iter()
+                        .map(|target| format!("  - {target}"))
+                        .join("\n")
+                ),
+                level: Level::Error,
+                hint: None,
+                location: None,
+            }],
+
+            Error::UnknownDependency { package } => vec![Diagnostic {
+                title: "Package not found".into(),
+                text: wrap_format!(
+                    "The package `{package}` is not one of the dependencies of your \
+project, so it is not part of the build."
+                ),
+                level: Level::Error,
+                hint: None,
+                location: None,
+            }],
+
+            Error::GitDependencyNotLocked { package } => vec![Diagnostic {
+                title: "Git dependency not locked".into(),
+                text: wrap_format!(
+                    "The Git dependency `{package}` is not in the manifest, so its \
+version can't be previewed without cloning its repository."
+                ),
+                level: Level::Error,
+                hint: Some("Run `glistix update` without `--dry-run` to resolve it.".into()),
+                location: None,
+            }],
+
+            Error::DocumentationAssetsOutsideDocs { path } => vec![Diagnostic {
+                title: "Invalid documentation assets path".into(),
+                text: wrap_format!(
+                    "The documentation assets path `{path}` is not a relative path \
+within the documentation directory."
+                ),
+                level: Level::Error,
+                hint: Some(
+                    "Use a relative path without `..`, such as `images`, in `gleam.toml`.".into(),
+                ),
+                location: None,
+            }],
+
+            Error::DocumentationAssetOverwritesPage { path } => vec![Diagnostic {
+                title: "Documentation asset overwrites a generated file".into(),
+                text: wrap_format!(
+                    "The documentation asset `{path}` has the same path as a file \
+generated for the documentation, which it would replace."
+                ),
+                level: Level::Error,
+                hint: Some("Rename the asset or copy it to a different path.".into()),
+                location: None,
+            }],
+
             Error::CannotPublishWrongVersion { minimum_required_version, wrongfully_allowed_version } => vec![Diagnostic {
                 title: "Cannot publish package with wrong Gleam version range".into(),
                 text: wrap(&format!(
@@ -1188,6 +1483,43 @@ The error from the shell command library was:
                 }]
             }
 
+            Error::CodegenHook {
+                command,
+                target,
+                status,
+            } => {
+                let text = wrap_format!(
+                    "The hook `{command}` declared under `[glistix.hooks]` \
+in gleam.toml exited with status {status} after generating code for the \
+{target} target. The generated files may have been left partially rewritten."
+                );
+                vec![Diagnostic {
+                    title: "Codegen hook failure".into(),
+                    text,
+                    hint: None,
+                    level: Level::Error,
+                    location: None,
+                }]
+            }
+
+            Error::BenchmarkFailed {
+                benchmark,
+                target,
+                status,
+            } => {
+                let text = wrap_format!(
+                    "The benchmark `{benchmark}` exited with status {status} \
+when run on the {target} target, so no timings could be reported for it."
+                );
+                vec![Diagnostic {
+                    title: "Benchmark failure".into(),
+                    text,
+                    hint: None,
+                    level: Level::Error,
+                    location: None,
+                }]
+            }
+
             Error::Gzip(detail) => {
                 let text = format!(
                     "There was a problem when applying gzip compression.
@@ -1417,6 +1749,16 @@ The error from the encryption library was:
                 }]
             }
 
+            Error::AlreadyReported => vec![],
+
+            Error::UnknownErrorCode { code } => vec![Diagnostic {
+                title: "Unknown error code".into(),
+                text: format!("No error has the code `{code}`."),
+                hint: Some("Error codes are written as `E` followed by four digits, such as `E2004`.".into()),
+                level: Level::Error,
+                location: None,
+            }],
+
             Error::NonUtf8Path { path } => {
                 let text = format!(
                     "Encountered a non UTF-8 path '{}', but only UTF-8 paths are supported.",
@@ -1446,6 +1788,39 @@ The error from the encryption library was:
                 }]
             }
 
+            Error::GitChangedFiles { since, error } => {
+                let text = format!(
+                    "An error occurred while trying to find the files changed since `{since}`:
+
+    {error}"
+                );
+                vec![Diagnostic {
+                    title: "Failed to find changed files".into(),
+                    text,
+                    hint: Some(
+                        "Check that the project is in a git repository and that the ref exists."
+                            .into(),
+                    ),
+                    level: Level::Error,
+                    location: None,
+                }]
+            }
+
+            Error::GitCommand { command, error } => {
+                let text = format!(
+                    "An error occurred while running `git {command}`:
+
+    {error}"
+                );
+                vec![Diagnostic {
+                    title: "Git command failed".into(),
+                    text,
+                    hint: None,
+                    level: Level::Error,
+                    location: None,
+                }]
+            }
+
             Error::Type { path, src, errors: error, names } => error
                 .iter()
                 .map(|error| {
@@ -1757,7 +2132,7 @@ Hint: Add some type annotations and try again.")
                         printer.print_type(type_)
                     );
                     Diagnostic {
-                        title: "Type mismatch".into(),
+                        title: i18n::title(Key::TypeMismatch).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -1849,7 +2224,7 @@ to call a method on this value you may want to use the function syntax instead."
                     let label = did_you_mean(label, fields)
                         .unwrap_or_else(|| "This field does not exist".into());
                     Diagnostic {
-                        title: "Unknown record field".into(),
+                        title: i18n::title(Key::UnknownRecordField).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -1890,7 +2265,7 @@ But this argument has this type:
                         text.push_str(&hint);
                     }
                     Diagnostic {
-                        title: "Type mismatch".into(),
+                        title: i18n::title(Key::TypeMismatch).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -1939,7 +2314,7 @@ But function expects:
                     );
 
                     Diagnostic {
-                        title: "Type mismatch".into(),
+                        title: i18n::title(Key::TypeMismatch).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -1975,7 +2350,7 @@ But function expects:
                     text.push_str("\n\nFound type:\n\n    ");
                     text.push_str(&printer.print_type(given));
                     Diagnostic {
-                        title: "Type mismatch".into(),
+                        title: i18n::title(Key::TypeMismatch).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -2005,7 +2380,7 @@ called with their expected number of arguments.");
                         _ => format!("{expected} arguments"),
                     };
                     Diagnostic {
-                        title: "Incorrect arity".into(),
+                        title: i18n::title(Key::IncorrectArity).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -2044,7 +2419,7 @@ called with their expected number of arguments.");
                     };
                     let label = format!("Expected {expected}, got {given}");
                     Diagnostic {
-                        title: "Incorrect arity".into(),
+                        title: i18n::title(Key::IncorrectArity).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -2185,7 +2560,7 @@ but no type in scope with that name."
                     };
 
                     Diagnostic {
-                        title: "Unknown type".into(),
+                        title: i18n::title(Key::UnknownType).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -2207,13 +2582,14 @@ but no type in scope with that name."
                     name,
                     type_with_name_in_scope,
                 } => {
-                    let text = if *type_with_name_in_scope {
-                        wrap_format!("`{name}` is a type, it cannot be used as a value.")
+                    let key = if *type_with_name_in_scope {
+                        Key::TypeUsedAsValue
                     } else {
-                        wrap_format!("The name `{name}` is not in scope here.")
+                        Key::UnknownVariable
                     };
+                    let text = wrap(&i18n::text(key, &[("name", name.as_str())]));
                     Diagnostic {
-                        title: "Unknown variable".into(),
+                        title: i18n::title(key).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -2229,8 +2605,18 @@ but no type in scope with that name."
                     }
                 }
 
-                TypeError::PrivateTypeLeak { location, leaked } => {
-                    let mut printer = Printer::new(names);
+                TypeError::PrivateTypeLeak {
+                    location,
+                    leaked,
+                    type_location,
+                } => {
+                    // The leaked type is printed by its own name, rather
+                    // than by the name of a public alias of it.
+                    let mut names = names.clone();
+                    if let Type::Named { module, name, .. } = leaked {
+                        names.named_type_in_scope(module.clone(), name.clone(), name.clone());
+                    }
+                    let mut printer = Printer::new(&names);
 
                     // TODO: be more precise.
                     // - is being returned by this public function
@@ -2258,7 +2644,7 @@ Private types can only be used within the module that defines them.",
                             },
                             path: path.clone(),
                             src: src.clone(),
-                            extra_labels: vec![],
+                            extra_labels: private_type_label(*type_location),
                         }),
                     }
                 }
@@ -2268,8 +2654,8 @@ Private types can only be used within the module that defines them.",
                     name,
                     suggestions
                 } => Diagnostic {
-                    title: "Unknown module".into(),
-                    text: format!("No module has been found with the name `{name}`."),
+                    title: i18n::title(Key::UnknownModule).into(),
+                    text: i18n::text(Key::UnknownModule, &[("name", name.as_str())]),
                     hint: suggestions.first().map(|suggestion| suggestion.suggestion(name)),
                     level: Level::Error,
                     location: Some(Location {
@@ -2556,7 +2942,7 @@ tuple has {} elements so the highest valid index is {}.",
                         printer.print_type(given),
                     );
                     Diagnostic {
-                        title: "Type mismatch".into(),
+                        title: i18n::title(Key::TypeMismatch).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -2578,7 +2964,7 @@ know its size, but we don't know anything about this type yet. \
 Please add some type annotations so we can continue."
                         );
                     Diagnostic {
-                        title: "Type mismatch".into(),
+                        title: i18n::title(Key::TypeMismatch).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -2872,6 +3258,30 @@ forever in a loop, and we'd never get the final type.",
                     }
                 }
 
+                TypeError::RecursiveConstant { location, cycle } => {
+                    let mut text = "This constant is defined in terms of itself.\n".into();
+                    write_cycle(&mut text, cycle);
+                    text.push_str(
+                        "Each of these constants needs the others to be evaluated
+first, so there is no order in which they could be evaluated.",
+                    );
+                    Diagnostic {
+                        title: "Constant cycle".into(),
+                        text,
+                        hint: None,
+                        level: Level::Error,
+                        location: Some(Location {
+                            label: Label {
+                                text: None,
+                                span: *location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: vec![],
+                        }),
+                    }
+                }
+
                 TypeError::ExternalMissingAnnotation { location, kind } => {
                     let kind = match kind {
                         MissingAnnotation::Parameter => "parameter",
@@ -3073,7 +3483,7 @@ The missing patterns are:\n"
                         text.push_str(missing);
                     }
                     Diagnostic {
-                        title: "Inexhaustive patterns".into(),
+                        title: i18n::title(Key::InexhaustivePatterns).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -3089,11 +3499,327 @@ The missing patterns are:\n"
                     }
                 }
 
+                TypeError::DeniedExternal {
+                    location,
+                    name,
+                    target,
+                } => {
+                    let (target_name, target_key) = match target {
+                        Target::Erlang => ("Erlang", "erlang"),
+                        Target::JavaScript => ("JavaScript", "javascript"),
+                        Target::Nix => ("Nix", "nix"),
+                    };
+                    let text = wrap_format!(
+                        "The function `{name}` has an external {target_name} \
+implementation, but this package does not allow externals for the \
+{target_name} target.\n"
+                    );
+                    let hint = wrap_format!(
+                        "Implement this function in Gleam, or remove `\"{target_key}\"` \
+from `deny-externals` in the `[glistix]` section of your gleam.toml."
+                    );
+                    Diagnostic {
+                        title: "External not allowed".into(),
+                        text,
+                        hint: Some(hint),
+                        level: Level::Error,
+                        location: Some(Location {
+                            label: Label {
+                                text: Some("Not allowed by this package".into()),
+                                span: *location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: vec![],
+                        }),
+                    }
+                }
+
+                TypeError::UnusedAnyExternal { location, name } => {
+                    let text = wrap_format!(
+                        "The function `{name}` has an external implementation of \
+its own for every target its `any` external implementation is valid on, so \
+the `any` one would never be used.
+
+It is only used on Erlang when its module and function are Erlang names \
+that don't need quoting, and only on Nix when its module is a relative path.\n"
+                    );
+                    Diagnostic {
+                        title: "Unused `any` external".into(),
+                        text,
+                        hint: Some("Remove the `any` external implementation.".into()),
+                        level: Level::Error,
+                        location: Some(Location {
+                            label: Label {
+                                text: Some("This is never used".into()),
+                                span: *location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: vec![],
+                        }),
+                    }
+                }
+
+                TypeError::UnknownNixExternalFunction {
+                    location,
+                    module,
+                    function,
+                } => {
+                    let text = wrap_format!(
+                        "The Nix implementation of this function is `{function}` in \
+`{module}`, but that file doesn't define `{function}`.\n"
+                    );
+                    Diagnostic {
+                        title: "Unknown Nix external function".into(),
+                        text,
+                        hint: None,
+                        level: Level::Error,
+                        location: Some(Location {
+                            label: Label {
+                                text: Some(format!("Not defined in `{module}`")),
+                                span: *location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: vec![],
+                        }),
+                    }
+                }
+
+                TypeError::IncorrectNixExternalArity {
+                    location,
+                    module,
+                    function,
+                    expected,
+                    given,
+                } => {
+                    let text = wrap_format!(
+                        "This function takes {expected} {}, but its Nix implementation \
+`{function}` in `{module}` takes {given}.\n",
+                        if *expected == 1 { "argument" } else { "arguments" }
+                    );
+                    let hint = (*expected == 0).then(|| {
+                        wrap("Functions without arguments are called with an empty \
+attribute set on Nix, so their implementation should take `{ }`.")
+                    });
+                    Diagnostic {
+                        title: "Incorrect Nix external arity".into(),
+                        text,
+                        hint,
+                        level: Level::Error,
+                        location: Some(Location {
+                            label: Label {
+                                text: Some(format!("Implemented with {given} curried arguments")),
+                                span: *location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: vec![],
+                        }),
+                    }
+                }
+
+                TypeError::CannotDeriveDecoder { location, reason } => {
+                    let (text, label, hint) = match reason {
+                        DeriveDecoderProblem::MissingDynamicImport => (
+                            wrap("Decoders are built out of the functions of the \
+`gleam/dynamic` module, but it hasn't been imported.\n"),
+                            None,
+                            Some("Add `import gleam/dynamic` to the top of this module.".into()),
+                        ),
+                        DeriveDecoderProblem::PreludeAliasTaken { module } => (
+                            wrap_format!(
+                                "Decoders refer to the types and constructors of the \
+prelude through the `gleam` module, but `{module}` is already imported with that name."
+                            ),
+                            None,
+                            Some(format!("Import `{module}` with a different name using `as`.")),
+                        ),
+                        DeriveDecoderProblem::TypeParameters => (
+                            wrap("A decoder can't be derived for a type with type \
+parameters, as there is no way to know how to decode the values they stand for."),
+                            None,
+                            None,
+                        ),
+                        DeriveDecoderProblem::NoConstructors => (
+                            wrap("A decoder can't be derived for a type without any \
+constructors."),
+                            None,
+                            None,
+                        ),
+                        DeriveDecoderProblem::UnlabelledField { constructor } => (
+                            wrap_format!(
+                                "Every field of `{constructor}` needs a label so the \
+decoder knows which field of the data to read it from."
+                            ),
+                            Some("This field has no label".into()),
+                            None,
+                        ),
+                        DeriveDecoderProblem::TooManyFields { constructor, limit } => (
+                            wrap_format!(
+                                "A decoder can only be derived for constructors with at \
+most {limit} fields, but `{constructor}` has more.\n"
+                            ),
+                            None,
+                            Some("Try grouping some of the fields into a separate type.".into()),
+                        ),
+                        DeriveDecoderProblem::UnsupportedType => (
+                            wrap("I don't know how to decode values of this type. \
+Decoders can be derived for fields of the built-in types, `Option`, `Dynamic`, \
+tuples, and types of this module which also have `@derive(decode)`."),
+                            Some("No decoder for this type".into()),
+                            None,
+                        ),
+                    };
+                    Diagnostic {
+                        title: "Cannot derive decoder".into(),
+                        text,
+                        hint,
+                        level: Level::Error,
+                        location: Some(Location {
+                            label: Label {
+                                text: label,
+                                span: *location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: vec![],
+                        }),
+                    }
+                }
+
+                TypeError::CannotDeriveNixAttrs { location, reason } => {
+                    let (text, label, hint) = match reason {
+                        DeriveNixAttrsProblem::NotPublic => (
+                            wrap("Attribute set conversion functions can only be derived \
+for public types which aren't opaque, as they expose the fields of the type's \
+records to Nix code outside of this module."),
+                            None,
+                            None,
+                        ),
+                        DeriveNixAttrsProblem::NoConstructors => (
+                            wrap("Attribute set conversion functions can't be derived \
+for a type without any constructors."),
+                            None,
+                            None,
+                        ),
+                        DeriveNixAttrsProblem::UnlabelledField { constructor } => (
+                            wrap_format!(
+                                "Every field of `{constructor}` needs a label to be used \
+as the name of its attribute."
+                            ),
+                            Some("This field has no label".into()),
+                            None,
+                        ),
+                        DeriveNixAttrsProblem::TypeField { constructor } => (
+                            wrap_format!(
+                                "The `type` attribute of the attribute sets made for this \
+type holds the name of their constructor, so the `type` field of `{constructor}` \
+can't be an attribute too."
+                            ),
+                            Some("This field is labelled `type`".into()),
+                            Some("Rename the field.".into()),
+                        ),
+                        DeriveNixAttrsProblem::NameClash { name } => (
+                            wrap_format!(
+                                "The `{name}` function generated for this type on Nix \
+would clash with a value of the same name defined in this module.\n"
+                            ),
+                            None,
+                            Some(format!("Rename the `{name}` value.")),
+                        ),
+                    };
+                    Diagnostic {
+                        title: "Cannot derive attribute set conversion".into(),
+                        text,
+                        hint,
+                        level: Level::Error,
+                        location: Some(Location {
+                            label: Label {
+                                text: label,
+                                span: *location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: vec![],
+                        }),
+                    }
+                }
+
+                TypeError::CannotDeriveNixosOptions { location, reason } => {
+                    let (text, label, hint) = match reason {
+                        DeriveNixosOptionsProblem::NotPublic => (
+                            wrap("NixOS options can only be derived for public types \
+which aren't opaque, as their values are built by Nix code outside of this module."),
+                            None,
+                            None,
+                        ),
+                        DeriveNixosOptionsProblem::TypeParameters => (
+                            wrap("NixOS options can't be derived for a type with type \
+parameters, as there is no way to know which option types they stand for."),
+                            None,
+                            None,
+                        ),
+                        DeriveNixosOptionsProblem::UnsupportedShape => (
+                            wrap("NixOS options can only be derived for a type with a \
+single constructor, whose fields become options, or for a type whose \
+constructors have no fields, whose values become an enumeration."),
+                            None,
+                            None,
+                        ),
+                        DeriveNixosOptionsProblem::UnlabelledField { constructor } => (
+                            wrap_format!(
+                                "Every field of `{constructor}` needs a label to be used \
+as the name of its option."
+                            ),
+                            Some("This field has no label".into()),
+                            None,
+                        ),
+                        DeriveNixosOptionsProblem::NameClash { name } => (
+                            wrap_format!(
+                                "The `{name}` function generated for this type on Nix \
+would clash with a value of the same name defined in this module.\n"
+                            ),
+                            None,
+                            Some(format!("Rename the `{name}` value.")),
+                        ),
+                        DeriveNixosOptionsProblem::UnsupportedType => (
+                            wrap("I don't know which NixOS option type to give values of \
+this type. Options can be derived for fields of type `Int`, `Float`, `String`, \
+`Bool`, `Dynamic`, `List` and `Option` of those, and types of this module which also \
+have `@derive(nixos_options)`."),
+                            Some("No option type for this type".into()),
+                            None,
+                        ),
+                    };
+                    Diagnostic {
+                        title: "Cannot derive NixOS options".into(),
+                        text,
+                        hint,
+                        level: Level::Error,
+                        location: Some(Location {
+                            label: Label {
+                                text: label,
+                                span: *location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: vec![],
+                        }),
+                    }
+                }
+
                 TypeError::UnsupportedExpressionTarget {
                     location,
                     target: current_target,
+                    module,
+                    name,
+                    package,
+                    supported_targets,
+                    nix_blocker_calls,
                 } => {
-                    let text = wrap_format!(
+                    let mut text = wrap_format!(
                         "This value is not available as it is defined using externals, \
 and there is no implementation for the {} target.\n",
                         match current_target {
@@ -3102,7 +3828,76 @@ and there is no implementation for the {} target.\n",
                             Target::Nix => "Nix",
                         }
                     );
-                    let hint = wrap("Did you mean to build for a different target?");
+                    let hint = match current_target {
+                        Target::Erlang | Target::JavaScript => {
+                            wrap("Did you mean to build for a different target?")
+                        }
+                        // Nix support is often missing from packages written
+                        // for the other targets, so point at what lacks it and
+                        // how to work around it.
+                        Target::Nix => {
+                            let origin = match package {
+                                Some(package) => format!(" from the `{package}` package"),
+                                None => String::new(),
+                            };
+                            let implemented = match supported_targets.as_slice() {
+                                [] => "has no implementation for any target".into(),
+                                targets => format!(
+                                    "is only implemented for {}",
+                                    targets
+                                        .iter()
+                                        .map(|target| match target {
+                                            Target::Erlang => "Erlang",
+                                            Target::JavaScript => "JavaScript",
+                                            Target::Nix => "Nix",
+                                        })
+                                        .join(" and ")
+                                ),
+                            };
+                            text.push('\n');
+                            text.push_str(&wrap_format!(
+                                "`{module}.{name}`{origin} {implemented}, using externals either \
+itself or through the functions it calls.\n"
+                            ));
+                            if !nix_blocker_calls.is_empty() {
+                                text.push('\n');
+                                text.push_str(&wrap_format!(
+                                    "It can't run on Nix because of these calls:\n"
+                                ));
+                                text.push('\n');
+                                let line_numbers = LineNumbers::new(src);
+                                let mut caller = format!("{module}.{name}");
+                                for call in nix_blocker_calls {
+                                    let site = match &call.site {
+                                        CallSite::Local(location) => format!(
+                                            "{path}:{}",
+                                            line_numbers.line_number(location.start)
+                                        ),
+                                        CallSite::Module { path, line } => {
+                                            format!("{path}:{line}")
+                                        }
+                                    };
+                                    let callee = format!("{}.{}", call.module, call.name);
+                                    text.push_str(&format!(
+                                        "    `{caller}` calls `{callee}` ({site})\n"
+                                    ));
+                                    caller = callee;
+                                }
+                            }
+                            match package {
+                                Some(package) => wrap_format!(
+                                    "Give the function using it a Nix implementation with \
+`@external(nix, \"./file.nix\", \"function\")`, or depend on a local copy of the \
+`{package}` package patched to support Nix, listing it in `local-overrides` \
+under `[glistix.preview]` in your gleam.toml if other dependencies use it too."
+                                ),
+                                None => wrap_format!(
+                                    "Give `{name}`, or the function using it, a Nix \
+implementation with `@external(nix, \"./file.nix\", \"function\")`."
+                                ),
+                            }
+                        }
+                    };
                     Diagnostic {
                         title: "Unsupported target".into(),
                         text,
@@ -3208,7 +4003,7 @@ See: https://tour.gleam.run/advanced-features/use/",
                     );
 
                     Diagnostic {
-                        title: "Type mismatch".into(),
+                        title: i18n::title(Key::TypeMismatch).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -3232,7 +4027,7 @@ one argument, a callback function.
 
 See: https://tour.gleam.run/advanced-features/use/");
                     Diagnostic {
-                        title: "Incorrect arity".into(),
+                        title: i18n::title(Key::IncorrectArity).into(),
                         text,
                         hint: None,
                         level: Level::Error,
@@ -3280,7 +4075,7 @@ so it cannot take the `use` callback function as a final argument.\n")
                     text.push_str("\nSee: https://tour.gleam.run/advanced-features/use/");
 
                     Diagnostic {
-                        title: "Incorrect arity".into(),
+                        title: i18n::title(Key::IncorrectArity).into(),
                         text: wrap(&text),
                         hint: None,
                         level: Level::Error,
@@ -3308,7 +4103,7 @@ See: https://tour.gleam.run/advanced-features/use/",
                         printer.print_type(actual)
                     );
                     Diagnostic {
-                        title: "Type mismatch".into(),
+                        title: i18n::title(Key::TypeMismatch).into(),
                         text: wrap(&text),
                         hint: None,
                         level: Level::Error,
@@ -3325,39 +4120,46 @@ See: https://tour.gleam.run/advanced-features/use/",
                 },
 
                 TypeError::UseCallbackIncorrectArity { pattern_location, call_location, expected, given } => {
-                    let expected = match expected {
+                    let expected_arguments = match expected {
                         0 => "no arguments".into(),
                         1 => "1 argument".into(),
                         _ => format!("{expected} arguments"),
                     };
 
+                    let expected_names = match expected {
+                        0 => "no names".into(),
+                        1 => "1 name".into(),
+                        _ => format!("{expected} names"),
+                    };
+
                     let specified = match given {
                         0 => "none were provided".into(),
                         1 => "1 was provided".into(),
                         _ => format!("{given} were provided"),
                     };
 
-                    let text = wrap_format!("This function takes a callback that expects {expected}. \
-But {specified} on the left hand side of `<-`.
+                    let text = wrap_format!("This function takes a callback that expects {expected_arguments}, \
+so the `use` expression must have {expected_names} on the left hand side of `<-`. \
+But {specified}.
 
 See: https://tour.gleam.run/advanced-features/use/");
                     Diagnostic {
-                        title: "Incorrect arity".into(),
+                        title: i18n::title(Key::IncorrectArity).into(),
                         text,
                         hint: None,
                         level: Level::Error,
                         location: Some(Location {
                             label: Label {
-                                text: None,
-                                span: *call_location,
+                                text: Some(format!("Expected {expected_names} before `<-`, found {given}")),
+                                span: *pattern_location,
                             },
                             path: path.clone(),
                             src: src.clone(),
                             extra_labels: vec![ExtraLabel {
                               src_info: None,
                               label: Label {
-                                  text: Some(format!("Expected {expected}, got {given}")),
-                                  span: *pattern_location
+                                  text: None,
+                                  span: *call_location
                               }
                             }],
                         }),
@@ -3483,6 +4285,71 @@ Consider removing the deprecation attribute on the variant.");
                 }]
             }
 
+            Error::EmbeddedFile {
+                path,
+                src,
+                location,
+                file,
+                error,
+            } => {
+                let (label, text) = match error {
+                    EmbeddedFileError::NotFound => (
+                        "File not found",
+                        wrap_format!(
+                            "The file `{file}` could not be found. Embedded files \
+are looked up relative to the root of the package."
+                        ),
+                    ),
+                    EmbeddedFileError::OutsidePackage => (
+                        "File outside of package",
+                        wrap_format!(
+                            "The file `{file}` is not within this package. Only \
+files within the package can be embedded, so the path must be relative and \
+cannot contain `..`."
+                        ),
+                    ),
+                    EmbeddedFileError::TooLarge { size } => (
+                        "File too large",
+                        wrap_format!(
+                            "The file `{file}` is {size} bytes long, but embedded \
+files can be at most {} bytes long.",
+                            crate::build::MAX_EMBEDDED_FILE_SIZE
+                        ),
+                    ),
+                    EmbeddedFileError::InvalidUtf8 => (
+                        "Invalid UTF-8",
+                        wrap_format!(
+                            "The file `{file}` is not valid UTF-8, so it cannot be \
+embedded as a `String`. Annotate the constant as a `BitArray` to embed its bytes \
+instead."
+                        ),
+                    ),
+                    EmbeddedFileError::UnsupportedType => (
+                        "Unsupported type",
+                        wrap_format!(
+                            "Files can only be embedded into constants annotated \
+as a `String` or a `BitArray`."
+                        ),
+                    ),
+                };
+
+                vec![Diagnostic {
+                    title: "Invalid embedded file".into(),
+                    text,
+                    hint: None,
+                    level: Level::Error,
+                    location: Some(Location {
+                        label: Label {
+                            text: Some(label.into()),
+                            span: *location,
+                        },
+                        path: path.clone(),
+                        src: src.clone(),
+                        extra_labels: vec![],
+                    }),
+                }]
+            }
+
             Error::ImportCycle { modules } => {
                 let first_location = &modules.first().1;
                 let rest_locations = modules.iter().skip(1).map(|(_, l)| ExtraLabel {
@@ -3657,6 +4524,21 @@ Fix the warnings and try again."
                         extra_labels: vec![],
                     }),
                 }],
+                crate::nix::Error::Syntax { problem, location } => vec![Diagnostic {
+                    title: "Syntax error".into(),
+                    text: "This Nix file could not be parsed.".into(),
+                    hint: None,
+                    level: Level::Error,
+                    location: Some(Location {
+                        label: Label {
+                            text: Some(problem.clone()),
+                            span: *location,
+                        },
+                        path: path.clone(),
+                        src: src.clone(),
+                        extra_labels: vec![],
+                    }),
+                }],
             },
 
             Error::DownloadPackageError {
@@ -3948,6 +4830,51 @@ satisfying {required_version} but you are using v{gleam_version}.",
                 hint: None,
             }],
 
+            Error::IncompatibleNixRepresentation { package, found } => {
+                let expected = crate::nix::REPRESENTATION_VERSION;
+                let found = match found {
+                    Some(version) => format!("version {version}"),
+                    None => "an unknown version".into(),
+                };
+                let text = wrap_format!(
+                    "The precompiled package `{package}` was generated for {found} of \
+the Nix representation of Gleam values, but this compiler generates version \
+{expected}. Code generated for different versions cannot be used together."
+                );
+                vec![Diagnostic {
+                    title: "Incompatible precompiled package".into(),
+                    text,
+                    level: Level::Error,
+                    location: None,
+                    hint: Some(
+                        "Recompile all packages with the same version of Glistix.".into(),
+                    ),
+                }]
+            }
+
+            Error::IncompatibleListRepresentation {
+                package,
+                expected,
+                found,
+            } => {
+                let (expected, found) = (expected.as_str(), found.as_str());
+                let text = wrap_format!(
+                    "The package `{package}` uses the `{found}` representation of lists \
+in its Nix code, but this project uses the `{expected}` one. Code generated for \
+different representations cannot be used together."
+                );
+                vec![Diagnostic {
+                    title: "Incompatible list representation".into(),
+                    text,
+                    level: Level::Error,
+                    location: None,
+                    hint: Some(format!(
+                        "Set `list-representation = \"{found}\"` in the `[glistix.nix]` \
+section of your gleam.toml, or use another version of `{package}`."
+                    )),
+                }]
+            }
+
             Error::CannotPatchHexWithHex { name } => vec![Diagnostic {
                 title: "Cannot patch a Hex dependency through [glistix.preview.hex-patch]".into(),
                 text: format!(
@@ -3991,6 +4918,67 @@ or you can publish it using a different version number"),
                 location: None,
                 hint: Some("Please add the --replace flag if you want to replace the release.".into()),
             }],
+
+            Error::HexCredentialsRequired => vec![Diagnostic {
+                title: "Hex credentials required".into(),
+                text: wrap("Downloading packages of a Hex organisation, or from a Hex \
+mirror configured with `authenticate = true`, requires a Hex API key, but none \
+was set in the HEXPM_API_KEY environment variable or stored in the system keyring."),
+                level: Level::Error,
+                location: None,
+                hint: Some("Run `glistix hex authenticate` or set the HEXPM_API_KEY \
+environment variable.".into()),
+            }],
+
+            Error::RegistryTokenMissing { name } => vec![Diagnostic {
+                title: "Missing registry token".into(),
+                text: wrap_format!("The package is configured to be published to the \
+registry in the `[registry]` section of gleam.toml, but the environment \
+variable `{name}` holding the token to authenticate with it is not set."),
+                level: Level::Error,
+                location: None,
+                hint: Some(format!("Set `{name}` to a token with permission to write packages.")),
+            }],
+
+            Error::RegistryUploadFailed { file, status } => vec![Diagnostic {
+                title: "Registry upload failed".into(),
+                text: wrap_format!("The registry rejected the upload of `{file}`, \
+responding with the HTTP status {status}."),
+                level: Level::Error,
+                location: None,
+                hint: None,
+            }],
+
+            Error::DisallowedDependencyLicences {
+                packages,
+                allowed_licences,
+            } => {
+                let packages = packages
+                    .iter()
+                    .map(|(name, licences)| {
+                        if licences.is_empty() {
+                            format!("  - {name} (no licence)")
+                        } else {
+                            format!("  - {name} ({})", licences.iter().join(", "))
+                        }
+                    })
+                    .join("\n");
+                let allowed_licences = allowed_licences.iter().join(", ");
+                let text = format!(
+                    "{}\n\n{packages}\n\n{}",
+                    wrap("The `[dependencies.policy]` section of gleam.toml doesn't allow \
+the licences of these packages:"),
+                    wrap(&format!("The allowed licences are: {allowed_licences}")),
+                );
+                let hint = "Remove these dependencies or add their licences to `allowed-licences`.";
+                vec![Diagnostic {
+                    title: "Dependency licences not allowed".into(),
+                    text,
+                    level: Level::Error,
+                    location: None,
+                    hint: Some(hint.into()),
+                }]
+            }
         }
     }
 }
@@ -4021,6 +5009,21 @@ fn std_io_error_kind_text(kind: &std::io::ErrorKind) -> String {
     }
 }
 
+/// The label pointing at the definition of a private type used in a public
+/// interface.
+pub(crate) fn private_type_label(type_location: Option<crate::ast::SrcSpan>) -> Vec<ExtraLabel> {
+    type_location
+        .into_iter()
+        .map(|span| ExtraLabel {
+            src_info: None,
+            label: Label {
+                text: Some("This type is private".into()),
+                span,
+            },
+        })
+        .collect()
+}
+
 fn write_cycle(buffer: &mut String, cycle: &[EcoString]) {
     buffer.push_str(
         "
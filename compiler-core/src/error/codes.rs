@@ -0,0 +1,595 @@
+//! Stable codes for the kinds of errors the compiler reports, along with the
+//! extended explanations printed by `glistix explain`.
+//!
+//! Codes are grouped by where the error comes from: `E0xxx` for errors about
+//! the project, its files and its tooling, `E1xxx` for syntax errors, and
+//! `E2xxx` for type errors. A code is never reused or given to another kind of
+//! error: new errors are given the next free code of their group, and the
+//! codes of removed errors are retired.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use super::Error;
+use crate::{parse::error::ParseErrorType, type_::Error as TypeError};
+
+/// The code of a kind of error, written as `E` followed by four digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ErrorCode(u16);
+
+impl ErrorCode {
+    /// Parses a code written as `E2004`, `e2004` or `2004`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let digits = text.strip_prefix(['E', 'e']).unwrap_or(text);
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok().map(Self)
+    }
+
+    /// The description of this code, or `None` if no error has it.
+    pub fn info(self) -> Option<&'static ErrorCodeInfo> {
+        CODES
+            .binary_search_by_key(&self, |info| info.code)
+            .ok()
+            .and_then(|index| CODES.get(index))
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", self.0)
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCodeInfo {
+    pub code: ErrorCode,
+    /// A short description of the kind of error.
+    pub title: &'static str,
+    /// A longer explanation of the error and how to fix it, with examples,
+    /// written in Markdown. Not every code has one yet.
+    pub explanation: Option<&'static str>,
+}
+
+const fn info(code: u16, title: &'static str, explanation: Option<&'static str>) -> ErrorCodeInfo {
+    ErrorCodeInfo {
+        code: ErrorCode(code),
+        title,
+        explanation,
+    }
+}
+
+/// Every code, in ascending order.
+pub const CODES: &[ErrorCodeInfo] = &[
+    info(
+        1,
+        "File embedded with `@embed_file` could not be read",
+        None,
+    ),
+    info(2, "Unknown import", None),
+    info(3, "Duplicate module", None),
+    info(4, "Duplicate source file", None),
+    info(5, "Duplicate native Erlang module", None),
+    info(6, "Gleam module clashes with a native file", None),
+    info(7, "Import cycle", Some(include_str!("codes/E0007.md"))),
+    info(8, "Package cycle", None),
+    info(9, "File IO failure", None),
+    info(10, "Path is not valid UTF-8", None),
+    info(11, "Git repository could not be initialised", None),
+    info(12, "Changed files could not be listed with git", None),
+    info(13, "Git command failed", None),
+    info(14, "Standard IO failure", None),
+    info(15, "Files are not formatted", None),
+    info(16, "Hex API failure", None),
+    info(17, "Tar archive could not be expanded", None),
+    info(18, "File could not be added to a tar archive", None),
+    info(19, "Tar archive could not be finished", None),
+    info(20, "Gzip compression failure", None),
+    info(21, "Shell program not found", None),
+    info(22, "Shell command failed", None),
+    info(23, "Codegen hook failed", None),
+    info(24, "Benchmark failed", None),
+    info(25, "Invalid project name", None),
+    info(26, "Invalid module name", None),
+    info(27, "Invalid env file", None),
+    info(28, "Config key not found", None),
+    info(29, "Config key is not in a table", None),
+    info(30, "Module does not exist", None),
+    info(31, "Module has no main function", None),
+    info(32, "Main function has wrong arity", None),
+    info(33, "Main function does not support the target", None),
+    info(34, "Build is not deterministic", None),
+    info(35, "Generated output differs", None),
+    info(36, "Target not enabled in this build of the compiler", None),
+    info(37, "Invalid version format", None),
+    info(38, "Project root already exists", None),
+    info(39, "Output files already exist", None),
+    info(40, "Removed packages are not dependencies", None),
+    info(41, "Added packages share a path or git source", None),
+    info(42, "Unable to find project root", None),
+    info(43, "Version does not match the .app file", None),
+    info(44, "Module metadata could not be decoded", None),
+    info(45, "Warnings are not permitted", None),
+    info(46, "JavaScript code generation failed", None),
+    info(47, "Invalid runtime", None),
+    info(48, "Package could not be downloaded", None),
+    info(49, "HTTP failure", None),
+    info(50, "Git dependencies are not supported", None),
+    info(51, "Dependency path could not be resolved", None),
+    info(52, "Dependency versions could not be resolved", None),
+    info(53, "Duplicate dependency", None),
+    info(54, "Wrong dependency provided", None),
+    info(55, "Dependency provided from conflicting sources", None),
+    info(56, "Fields required to publish are missing", None),
+    info(57, "Cannot publish with non-Hex dependencies", None),
+    info(58, "Unsupported build tool", None),
+    info(59, "Failed to open docs", None),
+    info(60, "Incompatible compiler version", None),
+    info(61, "JavaScript prelude required", None),
+    info(62, "Nix code generation failed", None),
+    info(63, "Nix prelude required", None),
+    info(
+        64,
+        "Incompatible Nix representation version",
+        Some(include_str!("codes/E0064.md")),
+    ),
+    info(65, "Incompatible list representation", None),
+    info(66, "Hex package patched with another Hex package", None),
+    info(67, "Cannot publish code using `todo`", None),
+    info(
+        68,
+        "Cannot publish an internal type in the public API",
+        None,
+    ),
+    info(69, "Hex package squatting", None),
+    info(70, "Corrupt manifest", None),
+    info(
+        71,
+        "Gleam module would overwrite a standard Erlang module",
+        None,
+    ),
+    info(72, "Publishing requires replacing the release", None),
+    info(73, "Hex credentials required", None),
+    info(74, "Registry token missing", None),
+    info(75, "Registry upload failed", None),
+    info(76, "Disallowed dependency licences", None),
+    info(77, "Cannot publish wrong version", None),
+    info(78, "Encryption failure", None),
+    info(79, "Decryption failure", None),
+    info(80, "Unknown error code", None),
+    info(81, "Cannot publish code failing on supported targets", None),
+    info(82, "Unknown dependency package", None),
+    info(83, "Git dependency is not locked", None),
+    info(
+        84,
+        "Documentation assets path is outside the documentation",
+        None,
+    ),
+    info(85, "Documentation asset overwrites a generated file", None),
+    info(1001, "Expected `=`", None),
+    info(1002, "Expected an expression", None),
+    info(1003, "Expected a name", None),
+    info(1004, "Expected a pattern", None),
+    info(1005, "Expected a type", None),
+    info(1006, "Expected an upper case name", None),
+    info(1007, "Expected a value", None),
+    info(1008, "Expected a statement", None),
+    info(1009, "Expected a definition", None),
+    info(1010, "Expected a deprecation message", None),
+    info(1011, "Expected a version for `@since`", None),
+    info(1012, "Expected a function definition", None),
+    info(1013, "Expected a constant definition", None),
+    info(1014, "Expected a custom type definition", None),
+    info(1015, "Expression starts with a parenthesis", None),
+    info(1016, "Extra separator", None),
+    info(1017, "Invalid lower case name", None),
+    info(1018, "Invalid upper case name", None),
+    info(1019, "Invalid bit array segment", None),
+    info(1020, "Invalid bit array unit", None),
+    info(1021, "Invalid tail pattern", None),
+    info(1022, "Invalid tuple access", None),
+    info(1023, "Invalid token", None),
+    info(1024, "Nested bit array pattern", None),
+    info(1025, "No expression", None),
+    info(1026, "Missing `let`", None),
+    info(1027, "Missing value after `=`", None),
+    info(1028, "Type not allowed in constants", None),
+    info(1029, "Operator without a right hand side", None),
+    info(1030, "Opaque type alias", None),
+    info(1031, "Too many argument holes in a function capture", None),
+    info(1032, "Duplicate attribute", None),
+    info(1033, "Unknown attribute", None),
+    info(1034, "Misplaced module attribute", None),
+    info(1035, "Unknown target", None),
+    info(1036, "Unknown derive", None),
+    info(1037, "List spread without elements to prepend", None),
+    info(1038, "List spread followed by elements", None),
+    info(1039, "Lower case boolean pattern", None),
+    info(1040, "Unexpected label", None),
+    info(1041, "Unexpected end of file", None),
+    info(1042, "Unexpected reserved word", None),
+    info(1043, "Unexpected token", None),
+    info(1044, "Expected a boolean", None),
+    info(1045, "Unexpected function", None),
+    info(
+        1046,
+        "Variable on the left of a string concatenation pattern",
+        None,
+    ),
+    info(1047, "List spread without a tail", None),
+    info(1048, "Expected a function body", None),
+    info(1049, "Redundant `@internal` attribute", None),
+    info(1050, "Module type used as a pattern", None),
+    info(1051, "List pattern spread followed by elements", None),
+    info(1052, "Expected a record constructor", None),
+    info(1053, "Function call in a clause guard", None),
+    info(1054, "`if` expression", None),
+    info(1055, "Constant record constructor without arguments", None),
+    info(1056, "Type constructor without arguments", None),
+    info(1057, "Type definition without parameters", None),
+    info(1058, "Unknown attribute on a record variant", None),
+    info(2001, "Application module importing a test module", None),
+    info(2002, "Invalid bit array segment", None),
+    info(2003, "Unknown labels", None),
+    info(
+        2004,
+        "Unknown variable",
+        Some(include_str!("codes/E2004.md")),
+    ),
+    info(2005, "Unknown type", Some(include_str!("codes/E2005.md"))),
+    info(2006, "Unknown module", Some(include_str!("codes/E2006.md"))),
+    info(2007, "Unknown module type", None),
+    info(2008, "Unknown module value", None),
+    info(2009, "Module alias used as a value", None),
+    info(2010, "Called value is not a function", None),
+    info(2011, "Unknown record field", None),
+    info(
+        2012,
+        "Incorrect arity",
+        Some(include_str!("codes/E2012.md")),
+    ),
+    info(2013, "Unsafe record update", None),
+    info(2014, "Record update spread is unnecessary", None),
+    info(2015, "Incorrect type arity", None),
+    info(2016, "Type mismatch", Some(include_str!("codes/E2016.md"))),
+    info(2017, "Recursive type", None),
+    info(2018, "Duplicate definition", None),
+    info(2019, "Duplicate import", None),
+    info(2020, "Duplicate type name", None),
+    info(2021, "Duplicate argument", None),
+    info(2022, "Duplicate field", None),
+    info(2023, "Private type leak", None),
+    info(2024, "Unexpected labelled argument", None),
+    info(2025, "Positional argument after a labelled one", None),
+    info(2026, "Incorrect number of patterns in a clause", None),
+    info(2027, "Clause guard uses a variable not in scope", None),
+    info(
+        2028,
+        "Variable missing from other alternative patterns",
+        None,
+    ),
+    info(2029, "Variable missing from this alternative pattern", None),
+    info(2030, "Variable defined twice in a pattern", None),
+    info(2031, "Tuple index out of bounds", None),
+    info(2032, "Not a tuple", None),
+    info(2033, "Tuple index on a value of unknown type", None),
+    info(2034, "Record access on a value of unknown type", None),
+    info(2035, "Record update with an invalid constructor", None),
+    info(2036, "Type hole not allowed here", None),
+    info(2037, "Reserved module name", None),
+    info(2038, "Keyword in a module name", None),
+    info(2039, "Pattern match is not exhaustive", None),
+    info(2040, "Argument name already used", None),
+    info(2041, "Unlabelled argument after a labelled one", None),
+    info(2042, "Recursive type alias", None),
+    info(2043, "Recursive constant", None),
+    info(2044, "External function without type annotations", None),
+    info(
+        2045,
+        "Function without an implementation",
+        Some(include_str!("codes/E2045.md")),
+    ),
+    info(
+        2046,
+        "Value not supported on the target",
+        Some(include_str!("codes/E2046.md")),
+    ),
+    info(2047, "Invalid JavaScript external module", None),
+    info(2048, "Invalid JavaScript external function", None),
+    info(
+        2049,
+        "Invalid Nix external module",
+        Some(include_str!("codes/E2049.md")),
+    ),
+    info(
+        2050,
+        "Invalid Nix external function",
+        Some(include_str!("codes/E2050.md")),
+    ),
+    info(
+        2051,
+        "External not allowed",
+        Some(include_str!("codes/E2051.md")),
+    ),
+    info(2052, "Unused `@external(any, ...)` implementation", None),
+    info(
+        2053,
+        "Unknown Nix external function",
+        Some(include_str!("codes/E2053.md")),
+    ),
+    info(
+        2054,
+        "Incorrect Nix external arity",
+        Some(include_str!("codes/E2054.md")),
+    ),
+    info(
+        2055,
+        "Case expression is not exhaustive",
+        Some(include_str!("codes/E2055.md")),
+    ),
+    info(2056, "Let assignment is not exhaustive", None),
+    info(2057, "Unused type alias parameter", None),
+    info(2058, "Duplicate type parameter", None),
+    info(2059, "Public function not supported on the target", None),
+    info(2060, "`use` of a value that is not a function", None),
+    info(
+        2061,
+        "`use` function called with the wrong number of arguments",
+        None,
+    ),
+    info(
+        2062,
+        "`use` callback with the wrong number of arguments",
+        None,
+    ),
+    info(2063, "`use` function does not take a callback", None),
+    info(2064, "Invalid name", None),
+    info(2065, "All variants deprecated", None),
+    info(2066, "Deprecated variant of a deprecated type", None),
+    info(2067, "Cannot derive a decoder", None),
+    info(2068, "Cannot derive attribute set conversion", None),
+    info(2069, "Cannot derive NixOS options", None),
+];
+
+/// The code of the diagnostics of an error. Type errors are reported as one
+/// diagnostic per wrapped error, each with the code given by `type_error_code`,
+/// so they have no code of their own.
+pub(crate) fn error_code(error: &Error) -> Option<ErrorCode> {
+    let code = match error {
+        Error::Parse { error, .. } => return Some(parse_error_code(&error.error)),
+        Error::Type { .. } | Error::AlreadyReported => return None,
+        Error::EmbeddedFile { .. } => 1,
+        Error::UnknownImport { .. } => 2,
+        Error::DuplicateModule { .. } => 3,
+        Error::DuplicateSourceFile { .. } => 4,
+        Error::DuplicateNativeErlangModule { .. } => 5,
+        Error::ClashingGleamModuleAndNativeFileName { .. } => 6,
+        Error::ImportCycle { .. } => 7,
+        Error::PackageCycle { .. } => 8,
+        Error::FileIo { .. } => 9,
+        Error::NonUtf8Path { .. } => 10,
+        Error::GitInitialization { .. } => 11,
+        Error::GitChangedFiles { .. } => 12,
+        Error::GitCommand { .. } => 13,
+        Error::StandardIo { .. } => 14,
+        Error::Format { .. } => 15,
+        Error::Hex { .. } => 16,
+        Error::ExpandTar { .. } => 17,
+        Error::AddTar { .. } => 18,
+        Error::TarFinish { .. } => 19,
+        Error::Gzip { .. } => 20,
+        Error::ShellProgramNotFound { .. } => 21,
+        Error::ShellCommand { .. } => 22,
+        Error::CodegenHook { .. } => 23,
+        Error::BenchmarkFailed { .. } => 24,
+        Error::InvalidProjectName { .. } => 25,
+        Error::InvalidModuleName { .. } => 26,
+        Error::InvalidEnvFile { .. } => 27,
+        Error::ConfigKeyNotFound { .. } => 28,
+        Error::ConfigKeyInNonTable { .. } => 29,
+        Error::ModuleDoesNotExist { .. } => 30,
+        Error::ModuleDoesNotHaveMainFunction { .. } => 31,
+        Error::MainFunctionHasWrongArity { .. } => 32,
+        Error::MainFunctionDoesNotSupportTarget { .. } => 33,
+        Error::NonDeterministicBuild { .. } => 34,
+        Error::GeneratedOutputDiffers { .. } => 35,
+        Error::TargetNotEnabled { .. } => 36,
+        Error::InvalidVersionFormat { .. } => 37,
+        Error::ProjectRootAlreadyExist { .. } => 38,
+        Error::OutputFilesAlreadyExist { .. } => 39,
+        Error::RemovedPackagesNotExist { .. } => 40,
+        Error::AddedPackagesShareSource { .. } => 41,
+        Error::UnableToFindProjectRoot { .. } => 42,
+        Error::VersionDoesNotMatch { .. } => 43,
+        Error::MetadataDecodeError { .. } => 44,
+        Error::ForbiddenWarnings { .. } => 45,
+        Error::JavaScript { .. } => 46,
+        Error::InvalidRuntime { .. } => 47,
+        Error::DownloadPackageError { .. } => 48,
+        Error::Http { .. } => 49,
+        Error::GitDependencyUnsupported => 50,
+        Error::DependencyCanonicalizationFailed { .. } => 51,
+        Error::DependencyResolutionFailed { .. } => 52,
+        Error::DuplicateDependency { .. } => 53,
+        Error::WrongDependencyProvided { .. } => 54,
+        Error::ProvidedDependencyConflict { .. } => 55,
+        Error::MissingHexPublishFields { .. } => 56,
+        Error::PublishNonHexDependencies { .. } => 57,
+        Error::UnsupportedBuildTool { .. } => 58,
+        Error::FailedToOpenDocs { .. } => 59,
+        Error::IncompatibleCompilerVersion { .. } => 60,
+        Error::JavaScriptPreludeRequired => 61,
+        Error::Nix { .. } => 62,
+        Error::NixPreludeRequired => 63,
+        Error::IncompatibleNixRepresentation { .. } => 64,
+        Error::IncompatibleListRepresentation { .. } => 65,
+        Error::CannotPatchHexWithHex { .. } => 66,
+        Error::CannotPublishTodo { .. } => 67,
+        Error::CannotPublishLeakedInternalType { .. } => 68,
+        Error::HexPackageSquatting => 69,
+        Error::CorruptManifest => 70,
+        Error::GleamModuleWouldOverwriteStandardErlangModule { .. } => 71,
+        Error::HexPublishReplaceRequired { .. } => 72,
+        Error::HexCredentialsRequired => 73,
+        Error::RegistryTokenMissing { .. } => 74,
+        Error::RegistryUploadFailed { .. } => 75,
+        Error::DisallowedDependencyLicences { .. } => 76,
+        Error::CannotPublishWrongVersion { .. } => 77,
+        Error::FailedToEncrypt { .. } => 78,
+        Error::FailedToDecrypt { .. } => 79,
+        Error::UnknownErrorCode { .. } => 80,
+        Error::CannotPublishFailingTargets { .. } => 81,
+        Error::UnknownDependency { .. } => 82,
+        Error::GitDependencyNotLocked { .. } => 83,
+        Error::DocumentationAssetsOutsideDocs { .. } => 84,
+        Error::DocumentationAssetOverwritesPage { .. } => 85,
+    };
+    Some(ErrorCode(code))
+}
+
+pub(crate) fn parse_error_code(error: &ParseErrorType) -> ErrorCode {
+    let code = match error {
+        ParseErrorType::ExpectedEqual => 1001,
+        ParseErrorType::ExpectedExpr => 1002,
+        ParseErrorType::ExpectedName => 1003,
+        ParseErrorType::ExpectedPattern => 1004,
+        ParseErrorType::ExpectedType => 1005,
+        ParseErrorType::ExpectedUpName => 1006,
+        ParseErrorType::ExpectedValue => 1007,
+        ParseErrorType::ExpectedStatement => 1008,
+        ParseErrorType::ExpectedDefinition => 1009,
+        ParseErrorType::ExpectedDeprecationMessage => 1010,
+        ParseErrorType::ExpectedSinceVersion => 1011,
+        ParseErrorType::ExpectedFunctionDefinition => 1012,
+        ParseErrorType::ExpectedConstantDefinition => 1013,
+        ParseErrorType::ExpectedCustomTypeDefinition => 1014,
+        ParseErrorType::ExprLparStart => 1015,
+        ParseErrorType::ExtraSeparator => 1016,
+        ParseErrorType::IncorrectName => 1017,
+        ParseErrorType::IncorrectUpName => 1018,
+        ParseErrorType::InvalidBitArraySegment => 1019,
+        ParseErrorType::InvalidBitArrayUnit => 1020,
+        ParseErrorType::InvalidTailPattern => 1021,
+        ParseErrorType::InvalidTupleAccess => 1022,
+        ParseErrorType::LexError { .. } => 1023,
+        ParseErrorType::NestedBitArrayPattern => 1024,
+        ParseErrorType::NoExpression => 1025,
+        ParseErrorType::NoLetBinding => 1026,
+        ParseErrorType::NoValueAfterEqual => 1027,
+        ParseErrorType::NotConstType => 1028,
+        ParseErrorType::OpNakedRight => 1029,
+        ParseErrorType::OpaqueTypeAlias => 1030,
+        ParseErrorType::TooManyArgHoles => 1031,
+        ParseErrorType::DuplicateAttribute => 1032,
+        ParseErrorType::UnknownAttribute => 1033,
+        ParseErrorType::MisplacedModuleAttribute => 1034,
+        ParseErrorType::UnknownTarget => 1035,
+        ParseErrorType::UnknownDerive => 1036,
+        ParseErrorType::ListSpreadWithoutElements => 1037,
+        ParseErrorType::ListSpreadFollowedByElements => 1038,
+        ParseErrorType::LowcaseBooleanPattern => 1039,
+        ParseErrorType::UnexpectedLabel => 1040,
+        ParseErrorType::UnexpectedEof => 1041,
+        ParseErrorType::UnexpectedReservedWord => 1042,
+        ParseErrorType::UnexpectedToken { .. } => 1043,
+        ParseErrorType::ExpectedBoolean => 1044,
+        ParseErrorType::UnexpectedFunction => 1045,
+        ParseErrorType::ConcatPatternVariableLeftHandSide => 1046,
+        ParseErrorType::ListSpreadWithoutTail => 1047,
+        ParseErrorType::ExpectedFunctionBody => 1048,
+        ParseErrorType::RedundantInternalAttribute => 1049,
+        ParseErrorType::InvalidModuleTypePattern => 1050,
+        ParseErrorType::ListPatternSpreadFollowedByElements => 1051,
+        ParseErrorType::ExpectedRecordConstructor { .. } => 1052,
+        ParseErrorType::CallInClauseGuard => 1053,
+        ParseErrorType::IfExpression => 1054,
+        ParseErrorType::ConstantRecordConstructorNoArguments => 1055,
+        ParseErrorType::TypeConstructorNoArguments => 1056,
+        ParseErrorType::TypeDefinitionNoArguments => 1057,
+        ParseErrorType::UnknownAttributeRecordVariant => 1058,
+    };
+    ErrorCode(code)
+}
+
+pub(crate) fn type_error_code(error: &TypeError) -> ErrorCode {
+    let code = match error {
+        TypeError::SrcImportingTest { .. } => 2001,
+        TypeError::BitArraySegmentError { .. } => 2002,
+        TypeError::UnknownLabels { .. } => 2003,
+        TypeError::UnknownVariable { .. } => 2004,
+        TypeError::UnknownType { .. } => 2005,
+        TypeError::UnknownModule { .. } => 2006,
+        TypeError::UnknownModuleType { .. } => 2007,
+        TypeError::UnknownModuleValue { .. } => 2008,
+        TypeError::ModuleAliasUsedAsName { .. } => 2009,
+        TypeError::NotFn { .. } => 2010,
+        TypeError::UnknownRecordField { .. } => 2011,
+        TypeError::IncorrectArity { .. } => 2012,
+        TypeError::UnsafeRecordUpdate { .. } => 2013,
+        TypeError::UnnecessarySpreadOperator { .. } => 2014,
+        TypeError::IncorrectTypeArity { .. } => 2015,
+        TypeError::CouldNotUnify { .. } => 2016,
+        TypeError::RecursiveType { .. } => 2017,
+        TypeError::DuplicateName { .. } => 2018,
+        TypeError::DuplicateImport { .. } => 2019,
+        TypeError::DuplicateTypeName { .. } => 2020,
+        TypeError::DuplicateArgument { .. } => 2021,
+        TypeError::DuplicateField { .. } => 2022,
+        TypeError::PrivateTypeLeak { .. } => 2023,
+        TypeError::UnexpectedLabelledArg { .. } => 2024,
+        TypeError::PositionalArgumentAfterLabelled { .. } => 2025,
+        TypeError::IncorrectNumClausePatterns { .. } => 2026,
+        TypeError::NonLocalClauseGuardVariable { .. } => 2027,
+        TypeError::ExtraVarInAlternativePattern { .. } => 2028,
+        TypeError::MissingVarInAlternativePattern { .. } => 2029,
+        TypeError::DuplicateVarInPattern { .. } => 2030,
+        TypeError::OutOfBoundsTupleIndex { .. } => 2031,
+        TypeError::NotATuple { .. } => 2032,
+        TypeError::NotATupleUnbound { .. } => 2033,
+        TypeError::RecordAccessUnknownType { .. } => 2034,
+        TypeError::RecordUpdateInvalidConstructor { .. } => 2035,
+        TypeError::UnexpectedTypeHole { .. } => 2036,
+        TypeError::ReservedModuleName { .. } => 2037,
+        TypeError::KeywordInModuleName { .. } => 2038,
+        TypeError::NotExhaustivePatternMatch { .. } => 2039,
+        TypeError::ArgumentNameAlreadyUsed { .. } => 2040,
+        TypeError::UnlabelledAfterlabelled { .. } => 2041,
+        TypeError::RecursiveTypeAlias { .. } => 2042,
+        TypeError::RecursiveConstant { .. } => 2043,
+        TypeError::ExternalMissingAnnotation { .. } => 2044,
+        TypeError::NoImplementation { .. } => 2045,
+        TypeError::UnsupportedExpressionTarget { .. } => 2046,
+        TypeError::InvalidExternalJavascriptModule { .. } => 2047,
+        TypeError::InvalidExternalJavascriptFunction { .. } => 2048,
+        TypeError::InvalidExternalNixModule { .. } => 2049,
+        TypeError::InvalidExternalNixFunction { .. } => 2050,
+        TypeError::DeniedExternal { .. } => 2051,
+        TypeError::UnusedAnyExternal { .. } => 2052,
+        TypeError::UnknownNixExternalFunction { .. } => 2053,
+        TypeError::IncorrectNixExternalArity { .. } => 2054,
+        TypeError::InexhaustiveCaseExpression { .. } => 2055,
+        TypeError::InexhaustiveLetAssignment { .. } => 2056,
+        TypeError::UnusedTypeAliasParameter { .. } => 2057,
+        TypeError::DuplicateTypeParameter { .. } => 2058,
+        TypeError::UnsupportedPublicFunctionTarget { .. } => 2059,
+        TypeError::NotFnInUse { .. } => 2060,
+        TypeError::UseFnIncorrectArity { .. } => 2061,
+        TypeError::UseCallbackIncorrectArity { .. } => 2062,
+        TypeError::UseFnDoesntTakeCallback { .. } => 2063,
+        TypeError::BadName { .. } => 2064,
+        TypeError::AllVariantsDeprecated { .. } => 2065,
+        TypeError::DeprecatedVariantOnDeprecatedType { .. } => 2066,
+        TypeError::CannotDeriveDecoder { .. } => 2067,
+        TypeError::CannotDeriveNixAttrs { .. } => 2068,
+        TypeError::CannotDeriveNixosOptions { .. } => 2069,
+    };
+    ErrorCode(code)
+}
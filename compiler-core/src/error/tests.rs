@@ -1,4 +1,5 @@
 use super::*;
+use crate::{ast::SrcSpan, type_::Error as TypeError};
 use insta::assert_snapshot;
 
 #[test]
@@ -35,3 +36,107 @@ fn test_shell_program_not_found_error() {
         }
     }
 }
+
+#[test]
+fn error_codes_are_in_ascending_order() {
+    for (previous, next) in codes::CODES.iter().tuple_windows() {
+        assert!(
+            previous.code < next.code,
+            "{} is listed before {}",
+            previous.code,
+            next.code
+        );
+    }
+}
+
+#[test]
+fn error_code_explanations_start_with_their_title() {
+    for info in codes::CODES {
+        if let Some(explanation) = info.explanation {
+            assert!(
+                explanation.starts_with(&format!("# {}\n\n", info.title)),
+                "the explanation of {} does not start with its title",
+                info.code
+            );
+        }
+    }
+}
+
+#[test]
+fn error_code_parsing() {
+    let code = codes::ErrorCode::parse("E2004").unwrap();
+    assert_eq!(code.to_string(), "E2004");
+    assert_eq!(codes::ErrorCode::parse("e2004"), Some(code));
+    assert_eq!(codes::ErrorCode::parse("2004"), Some(code));
+    assert_eq!(code.info().unwrap().title, "Unknown variable");
+    assert_eq!(codes::ErrorCode::parse("E204"), None);
+    assert_eq!(codes::ErrorCode::parse("E20045"), None);
+    assert_eq!(codes::ErrorCode::parse("X2004"), None);
+    assert_eq!(codes::ErrorCode::parse("E9999").unwrap().info(), None);
+}
+
+#[test]
+fn type_errors_each_have_their_code() {
+    let unknown_variable = TypeError::UnknownVariable {
+        location: SrcSpan::new(0, 1),
+        name: "x".into(),
+        variables: vec![],
+        type_with_name_in_scope: false,
+    };
+    let unknown_module = TypeError::UnknownModule {
+        location: SrcSpan::new(0, 1),
+        name: "x".into(),
+        suggestions: vec![],
+    };
+    let error = Error::Type {
+        path: Utf8PathBuf::from("src/main.gleam"),
+        src: "x".into(),
+        errors: Vec1::try_from_vec(vec![unknown_variable, unknown_module]).unwrap(),
+        names: Names::new(),
+    };
+    let codes = error
+        .to_diagnostics_with_codes()
+        .iter()
+        .map(|(_, code)| code.unwrap().to_string())
+        .collect_vec();
+    assert_eq!(codes, vec!["E2004", "E2006"]);
+}
+
+#[test]
+fn parse_errors_have_their_code() {
+    let error = Error::Parse {
+        path: Utf8PathBuf::from("src/main.gleam"),
+        src: "x".into(),
+        error: crate::parse::error::ParseError {
+            error: ParseErrorType::ExpectedExpr,
+            location: SrcSpan::new(0, 1),
+        },
+    };
+    let diagnostics = error.to_diagnostics_with_codes();
+    assert_eq!(
+        diagnostics.first().unwrap().1,
+        Some(codes::parse_error_code(&ParseErrorType::ExpectedExpr))
+    );
+}
+
+#[test]
+fn other_errors_have_their_code() {
+    let error = Error::UnknownErrorCode {
+        code: "E9999".into(),
+    };
+    let diagnostics = error.to_diagnostics_with_codes();
+    assert_eq!(diagnostics.first().unwrap().1.unwrap().to_string(), "E0080");
+}
+
+#[test]
+fn cannot_publish_failing_targets_lists_targets() {
+    let error = Error::CannotPublishFailingTargets {
+        targets: vec![Target::JavaScript, Target::Nix],
+    };
+    let diagnostics = error.to_diagnostics();
+    assert!(diagnostics
+        .first()
+        .unwrap()
+        .text
+        .contains("\n  - javascript\n  - nix\n"));
+}
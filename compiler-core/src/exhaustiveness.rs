@@ -34,7 +34,7 @@ mod pattern;
 mod pattern_tests;
 pub mod printer;
 
-use self::pattern::{Constructor, Pattern, PatternId};
+use self::pattern::{Pattern, PatternId};
 use crate::{
     ast::AssignName,
     type_::{
@@ -49,7 +49,7 @@ use id_arena::Arena;
 use itertools::Itertools;
 use std::{cell::RefCell, collections::HashMap, sync::Arc};
 
-pub use self::pattern::PatternArena;
+pub use self::pattern::{Constructor, PatternArena};
 
 /// The body of code to evaluate in case of a match.
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -57,10 +57,10 @@ pub struct Body {
     /// Any variables to bind before running the code.
     ///
     /// The tuples are in the form `(name, source)` (i.e `bla = source`).
-    bindings: Vec<(EcoString, Variable)>,
+    pub bindings: Vec<(EcoString, Variable)>,
 
     /// The index of the clause in the case expression that should be run.
-    clause_index: u16,
+    pub clause_index: u16,
 }
 
 impl Body {
@@ -78,8 +78,8 @@ impl Body {
 /// variable/temporary generated by your compiler.
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct Variable {
-    id: usize,
-    type_: Arc<Type>,
+    pub id: usize,
+    pub type_: Arc<Type>,
 }
 
 /// A single case (or row) in a match expression/table.
@@ -125,10 +125,10 @@ impl Column {
 }
 
 /// A case in a decision tree to test against a variable.
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Case {
     /// The constructor to test against an input variable.
-    constructor: Constructor,
+    pub constructor: Constructor,
 
     /// Variables to introduce to the body of this case.
     ///
@@ -144,10 +144,10 @@ pub struct Case {
     ///
     /// In a real compiler you'd assign these variables in your IR first, then
     /// generate the code for the sub tree.
-    arguments: Vec<Variable>,
+    pub arguments: Vec<Variable>,
 
     /// The sub tree of this case.
-    body: Decision,
+    pub body: Decision,
 }
 
 impl Case {
@@ -161,7 +161,7 @@ impl Case {
 }
 
 /// A decision tree
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum Decision {
     /// A pattern is matched and the right-hand value is to be returned.
     Success(Body),
@@ -199,11 +199,11 @@ pub enum Decision {
     },
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub struct NonEmptyListDecision {
-    first: Variable,
-    rest: Variable,
-    decision: Decision,
+    pub first: Variable,
+    pub rest: Variable,
+    pub decision: Decision,
 }
 
 /// A type for storing diagnostics produced by the decision tree compiler.
@@ -230,6 +230,19 @@ pub struct Match {
     pub subject_variables: Vec<Variable>,
 }
 
+/// The decision tree of a case expression, kept in the typed AST so that code
+/// generators can use it to test each part of the subjects at most once.
+///
+/// The tree is not complete for bit array and string prefix patterns, so code
+/// generators must compile case expressions using those some other way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledCase {
+    pub tree: Decision,
+    /// The variables holding the values of the subjects of the case
+    /// expression, in order.
+    pub subject_variables: Vec<Variable>,
+}
+
 /// Whether a clause is reachable, or why it is unreachable.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Reachability {
@@ -252,6 +265,13 @@ impl Match {
     pub fn missing_patterns(&self, environment: &Environment<'_>) -> Vec<EcoString> {
         missing_patterns::missing_patterns(self, environment)
     }
+
+    pub fn into_compiled_case(self) -> CompiledCase {
+        CompiledCase {
+            tree: self.tree,
+            subject_variables: self.subject_variables,
+        }
+    }
 }
 
 /// The `match` compiler itself (shocking, I know).
@@ -1,4 +1,10 @@
+#[cfg(test)]
+mod tests;
+
 use crate::{
+    analyse,
+    ast::{Definition, SrcSpan, TargetedDefinition, UntypedModule},
+    build::Target,
     format::{Formatter, Intermediate},
     warning::WarningEmitter,
     Error, Result,
@@ -28,3 +34,134 @@ pub fn parse_fix_and_format(src: &EcoString, path: &Utf8Path) -> Result<String>
 
     Ok(buffer)
 }
+
+/// A change to make to the source code of a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    /// The span to replace. An empty span inserts at that position.
+    pub location: SrcSpan,
+    pub replacement: String,
+}
+
+/// Information about the module being fixed and its project.
+#[derive(Debug, Clone, Copy)]
+pub struct FixContext<'a> {
+    /// The target of the project.
+    pub target: Target,
+    /// The name of the module being fixed, e.g. `wibble/wobble`.
+    pub module_name: &'a str,
+}
+
+/// A rewrite rule applied by `glistix fix` to each module of the project.
+pub trait FixRule {
+    /// Describes the change this rule makes, printed when it changes a module.
+    fn description(&self) -> &'static str;
+
+    /// The edits to make to the given module. Edits must not overlap.
+    fn edits(&self, context: FixContext<'_>, module: &UntypedModule) -> Vec<Edit>;
+}
+
+/// The rules run by `glistix fix`, in order.
+pub fn rules() -> Vec<Box<dyn FixRule>> {
+    vec![Box::new(NixExternalStubs)]
+}
+
+/// Applies each rule to the source of a module in turn, parsing it again after
+/// each rule that changed it.
+///
+/// Returns the new source and the descriptions of the rules that changed it.
+pub fn apply_rules(
+    src: &EcoString,
+    path: &Utf8Path,
+    context: FixContext<'_>,
+    rules: &[Box<dyn FixRule>],
+) -> Result<(EcoString, Vec<&'static str>)> {
+    let mut src = src.clone();
+    let mut applied = vec![];
+    for rule in rules {
+        let parsed = crate::parse::parse_module(path.to_owned(), &src, &WarningEmitter::null())
+            .map_err(|error| Error::Parse {
+                path: path.to_path_buf(),
+                src: src.clone(),
+                error,
+            })?;
+        let edits = rule.edits(context, &parsed.module);
+        if edits.is_empty() {
+            continue;
+        }
+        src = apply_edits(&src, edits).into();
+        applied.push(rule.description());
+    }
+    Ok((src, applied))
+}
+
+fn apply_edits(src: &str, mut edits: Vec<Edit>) -> String {
+    // Apply from the end of the source so earlier spans remain valid.
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.location.start));
+    let mut src = src.to_string();
+    for edit in edits {
+        let range = edit.location.start as usize..edit.location.end as usize;
+        src.replace_range(range, &edit.replacement);
+    }
+    src
+}
+
+/// Adds a stub `@external(nix, ...)` attribute to functions that have an
+/// Erlang or JavaScript external implementation but neither a Nix one nor a
+/// Gleam body, so that migrating a project to Nix can be started
+/// mechanically. The stubs refer to an FFI file next to the module which must
+/// then be written by hand.
+#[derive(Debug, Clone, Copy)]
+pub struct NixExternalStubs;
+
+impl FixRule for NixExternalStubs {
+    fn description(&self) -> &'static str {
+        "Added Nix external stubs"
+    }
+
+    fn edits(&self, context: FixContext<'_>, module: &UntypedModule) -> Vec<Edit> {
+        if context.target != Target::Nix {
+            return vec![];
+        }
+
+        let module_file = context
+            .module_name
+            .rsplit('/')
+            .next()
+            .unwrap_or(context.module_name);
+
+        module
+            .definitions
+            .iter()
+            .filter_map(|TargetedDefinition { definition, target }| {
+                let Definition::Function(function) = definition else {
+                    return None;
+                };
+                let (_, name) = function.name.as_ref()?;
+                let any_external_on_nix =
+                    function
+                        .external_any
+                        .as_ref()
+                        .is_some_and(|(module, any_function, _)| {
+                            analyse::is_valid_nix_external(module, any_function)
+                        });
+                let needs_stub = matches!(target, None | Some(Target::Nix))
+                    && function.external_nix.is_none()
+                    && !any_external_on_nix
+                    && (function.external_erlang.is_some()
+                        || function.external_javascript.is_some())
+                    && function.body.first().is_placeholder();
+                if !needs_stub {
+                    return None;
+                }
+                Some(Edit {
+                    location: SrcSpan::new(function.location.start, function.location.start),
+                    replacement: format!(
+                        "// TODO: implement `{name}` in `{module_file}_ffi.nix`\n\
+@external(nix, \"./{module_file}_ffi.nix\", \"{name}\")\n"
+                    ),
+                })
+            })
+            .collect()
+    }
+}
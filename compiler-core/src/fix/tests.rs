@@ -0,0 +1,97 @@
+use camino::Utf8Path;
+
+use super::{apply_rules, FixContext, NixExternalStubs};
+use crate::build::Target;
+
+fn fix_with_target(src: &str, target: Target) -> String {
+    let context = FixContext {
+        target,
+        module_name: "wibble/wobble",
+    };
+    let (fixed, _) = apply_rules(
+        &src.into(),
+        Utf8Path::new("src/wibble/wobble.gleam"),
+        context,
+        &[Box::new(NixExternalStubs)],
+    )
+    .expect("should fix");
+    fixed.to_string()
+}
+
+fn fix(src: &str) -> String {
+    fix_with_target(src, Target::Nix)
+}
+
+#[test]
+fn nix_stub_is_added_to_erlang_external() {
+    assert_eq!(
+        fix(r#"@external(erlang, "wibble", "wobble")
+pub fn wobble() -> Int
+"#),
+        r#"@external(erlang, "wibble", "wobble")
+// TODO: implement `wobble` in `wobble_ffi.nix`
+@external(nix, "./wobble_ffi.nix", "wobble")
+pub fn wobble() -> Int
+"#
+    );
+}
+
+#[test]
+fn nix_stub_is_added_to_each_function() {
+    assert_eq!(
+        fix(r#"@external(javascript, "./wibble.mjs", "wibble")
+fn wibble() -> Int
+
+@external(erlang, "wibble", "wobble")
+@external(javascript, "./wibble.mjs", "wobble")
+pub fn wobble() -> Int
+"#),
+        r#"@external(javascript, "./wibble.mjs", "wibble")
+// TODO: implement `wibble` in `wobble_ffi.nix`
+@external(nix, "./wobble_ffi.nix", "wibble")
+fn wibble() -> Int
+
+@external(erlang, "wibble", "wobble")
+@external(javascript, "./wibble.mjs", "wobble")
+// TODO: implement `wobble` in `wobble_ffi.nix`
+@external(nix, "./wobble_ffi.nix", "wobble")
+pub fn wobble() -> Int
+"#
+    );
+}
+
+#[test]
+fn functions_with_nix_externals_are_unchanged() {
+    let src = r#"@external(erlang, "wibble", "wobble")
+@external(nix, "./wibble.nix", "wobble")
+pub fn wobble() -> Int
+"#;
+    assert_eq!(fix(src), src);
+}
+
+#[test]
+fn functions_with_gleam_bodies_are_unchanged() {
+    let src = r#"@external(erlang, "wibble", "wobble")
+pub fn wobble() -> Int {
+  1
+}
+"#;
+    assert_eq!(fix(src), src);
+}
+
+#[test]
+fn functions_for_other_targets_are_unchanged() {
+    let src = r#"@target(erlang)
+@external(erlang, "wibble", "wobble")
+pub fn wobble() -> Int
+"#;
+    assert_eq!(fix(src), src);
+}
+
+#[test]
+fn non_nix_projects_are_unchanged() {
+    let src = r#"@external(erlang, "wibble", "wobble")
+pub fn wobble() -> Int
+"#;
+    assert_eq!(fix_with_target(src, Target::Erlang), src);
+}
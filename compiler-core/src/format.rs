@@ -7,6 +7,7 @@ use crate::{
         TypeAstTuple, TypeAstVar, *,
     },
     build::Target,
+    config::FormatConfig,
     docvec,
     io::Utf8Writer,
     parse::extra::{Comment, ModuleExtra},
@@ -26,7 +27,12 @@ use camino::Utf8Path;
 
 const INDENT: isize = 2;
 
-pub fn pretty(writer: &mut impl Utf8Writer, src: &EcoString, path: &Utf8Path) -> Result<()> {
+pub fn pretty(
+    writer: &mut impl Utf8Writer,
+    src: &EcoString,
+    path: &Utf8Path,
+    config: FormatConfig,
+) -> Result<()> {
     let parsed = crate::parse::parse_module(path.to_owned(), src, &WarningEmitter::null())
         .map_err(|error| Error::Parse {
             path: path.to_path_buf(),
@@ -35,8 +41,9 @@ pub fn pretty(writer: &mut impl Utf8Writer, src: &EcoString, path: &Utf8Path) ->
         })?;
     let intermediate = Intermediate::from_extra(&parsed.extra, src);
     Formatter::with_comments(&intermediate)
+        .with_config(config)
         .module(&parsed.module)
-        .pretty_print(80, writer)
+        .pretty_print(config.line_width as isize, writer)
 }
 
 pub(crate) struct Intermediate<'a> {
@@ -45,6 +52,7 @@ pub(crate) struct Intermediate<'a> {
     module_comments: Vec<Comment<'a>>,
     empty_lines: &'a [u32],
     new_lines: &'a [u32],
+    trailing_commas: &'a [u32],
 }
 
 impl<'a> Intermediate<'a> {
@@ -67,6 +75,7 @@ impl<'a> Intermediate<'a> {
                 .map(|span| Comment::from((span, src)))
                 .collect(),
             new_lines: &extra.new_lines,
+            trailing_commas: &extra.trailing_commas,
         }
     }
 }
@@ -103,6 +112,8 @@ pub struct Formatter<'a> {
     module_comments: &'a [Comment<'a>],
     empty_lines: &'a [u32],
     new_lines: &'a [u32],
+    trailing_commas: &'a [u32],
+    config: FormatConfig,
 }
 
 impl<'comments> Formatter<'comments> {
@@ -117,9 +128,22 @@ impl<'comments> Formatter<'comments> {
             module_comments: &extra.module_comments,
             empty_lines: extra.empty_lines,
             new_lines: extra.new_lines,
+            trailing_commas: extra.trailing_commas,
+            config: FormatConfig::default(),
         }
     }
 
+    pub(crate) fn with_config(self, config: FormatConfig) -> Self {
+        Self { config, ..self }
+    }
+
+    /// Whether the list or tuple literal ending at the given position was
+    /// written with a trailing comma that should keep it split over multiple
+    /// lines.
+    fn keeps_trailing_comma(&self, location: &SrcSpan) -> bool {
+        self.config.trailing_commas && self.trailing_commas.binary_search(&location.end).is_ok()
+    }
+
     fn any_comments(&self, limit: u32) -> bool {
         self.comments
             .first()
@@ -363,6 +387,7 @@ impl<'comments> Formatter<'comments> {
                 type_ast: resolved_type,
                 publicity,
                 deprecation,
+                since,
                 location,
                 ..
             }) => self.type_alias(
@@ -371,6 +396,7 @@ impl<'comments> Formatter<'comments> {
                 args,
                 resolved_type,
                 deprecation,
+                since,
                 location,
             ),
 
@@ -432,9 +458,17 @@ impl<'comments> Formatter<'comments> {
                 name,
                 annotation,
                 value,
+                since,
+                embedded_file,
+                strict,
                 ..
             }) => {
-                let attributes = AttributesPrinter::new().set_internal(*publicity).to_doc();
+                let attributes = AttributesPrinter::new()
+                    .set_since(since)
+                    .set_internal(*publicity)
+                    .set_embedded_file(embedded_file)
+                    .set_strict(*strict)
+                    .to_doc();
                 let head = attributes
                     .append(pub_(*publicity))
                     .append("const ")
@@ -443,7 +477,13 @@ impl<'comments> Formatter<'comments> {
                     None => head,
                     Some(t) => head.append(": ").append(self.type_ast(t)),
                 };
-                head.append(" = ").append(self.const_expr(value))
+                // The value of a constant with an embedded file isn't written
+                // in the source.
+                if embedded_file.is_some() {
+                    head
+                } else {
+                    head.append(" = ").append(self.const_expr(value))
+                }
             }
         }
     }
@@ -746,6 +786,7 @@ impl<'comments> Formatter<'comments> {
         self.wrap_args(args, location.end)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn type_alias<'a>(
         &mut self,
         publicity: Publicity,
@@ -753,10 +794,12 @@ impl<'comments> Formatter<'comments> {
         args: &'a [SpannedString],
         type_: &'a TypeAst,
         deprecation: &'a Deprecation,
+        since: &'a Option<EcoString>,
         location: &SrcSpan,
     ) -> Document<'a> {
         let attributes = AttributesPrinter::new()
             .set_deprecation(deprecation)
+            .set_since(since)
             .set_internal(publicity)
             .to_doc();
 
@@ -785,10 +828,13 @@ impl<'comments> Formatter<'comments> {
     fn statement_fn<'a>(&mut self, function: &'a UntypedFunction) -> Document<'a> {
         let attributes = AttributesPrinter::new()
             .set_deprecation(&function.deprecation)
+            .set_since(&function.since)
             .set_internal(function.publicity)
             .set_external_erlang(&function.external_erlang)
             .set_external_javascript(&function.external_javascript)
             .set_external_nix(&function.external_nix)
+            .set_external_any(&function.external_any)
+            .set_inline(function.inline)
             .to_doc();
 
         // Fn name and args
@@ -1270,6 +1316,16 @@ impl<'comments> Formatter<'comments> {
             };
         }
 
+        if self.keeps_trailing_comma(location) {
+            let elements = elements
+                .iter()
+                .map(|e| self.comma_separated_item(e, elements.len()))
+                .collect_vec();
+            return "#"
+                .to_doc()
+                .append(self.call_args(elements, location).force_break().group());
+        }
+
         self.append_inlinable_wrapped_args(
             "#".to_doc(),
             elements,
@@ -1655,7 +1711,11 @@ impl<'comments> Formatter<'comments> {
 
         let attributes = AttributesPrinter::new()
             .set_deprecation(&ct.deprecation)
+            .set_since(&ct.since)
             .set_internal(ct.publicity)
+            .set_derive_decode(ct.derive_decode)
+            .set_derive_nix_attrs(ct.derive_nix_attrs)
+            .set_derive_nixos_options(ct.derive_nixos_options)
             .to_doc();
 
         let doc = attributes
@@ -2041,7 +2101,13 @@ impl<'comments> Formatter<'comments> {
             };
         }
 
-        let comma = if tail.is_none() && elements.iter().all(UntypedExpr::is_simple_constant) {
+        // A list written with a trailing comma keeps one element per line
+        // instead of packing simple constants together.
+        let keeps_trailing_comma = self.keeps_trailing_comma(location);
+        let comma = if tail.is_none()
+            && !keeps_trailing_comma
+            && elements.iter().all(UntypedExpr::is_simple_constant)
+        {
             flex_break(",", ", ")
         } else {
             break_(",", ", ")
@@ -2086,6 +2152,9 @@ impl<'comments> Formatter<'comments> {
         // Otherwise those would be moved out of the list.
         let comments = self.pop_comments(location.end);
         match printed_comments(comments, false) {
+            None if keeps_trailing_comma => {
+                doc.append(last_break).append("]").force_break().group()
+            }
             None => doc.append(last_break).append("]").group(),
             Some(comment) => doc
                 .append(last_break.nest(INDENT))
@@ -2547,6 +2616,15 @@ impl<'comments> Formatter<'comments> {
     }
 
     pub fn wrap_function_call_args<'a, I>(&mut self, args: I, location: &SrcSpan) -> Document<'a>
+    where
+        I: IntoIterator<Item = Document<'a>>,
+    {
+        self.call_args(args, location).group()
+    }
+
+    /// The arguments of a call wrapped in parentheses, without grouping them
+    /// so that the caller can decide whether to force them to break.
+    fn call_args<'a, I>(&mut self, args: I, location: &SrcSpan) -> Document<'a>
     where
         I: IntoIterator<Item = Document<'a>>,
     {
@@ -2572,7 +2650,7 @@ impl<'comments> Formatter<'comments> {
             }
         };
 
-        "(".to_doc().append(args_doc).append(closing_parens).group()
+        "(".to_doc().append(args_doc).append(closing_parens)
     }
 
     pub fn wrap_args<'a, I>(&mut self, args: I, comments_limit: u32) -> Document<'a>
@@ -3029,8 +3107,16 @@ struct AttributesPrinter<'a> {
     external_erlang: &'a Option<(EcoString, EcoString, SrcSpan)>,
     external_javascript: &'a Option<(EcoString, EcoString, SrcSpan)>,
     external_nix: &'a Option<(EcoString, EcoString, SrcSpan)>,
+    external_any: &'a Option<(EcoString, EcoString, SrcSpan)>,
     deprecation: &'a Deprecation,
+    since: &'a Option<EcoString>,
     internal: bool,
+    embedded_file: &'a Option<EmbeddedFile>,
+    strict: bool,
+    derive_decode: bool,
+    derive_nix_attrs: bool,
+    derive_nixos_options: bool,
+    inline: bool,
 }
 
 impl<'a> AttributesPrinter<'a> {
@@ -3039,8 +3125,16 @@ impl<'a> AttributesPrinter<'a> {
             external_erlang: &None,
             external_javascript: &None,
             external_nix: &None,
+            external_any: &None,
             deprecation: &Deprecation::NotDeprecated,
+            since: &None,
             internal: false,
+            embedded_file: &None,
+            strict: false,
+            derive_decode: false,
+            derive_nix_attrs: false,
+            derive_nixos_options: false,
+            inline: false,
         }
     }
 
@@ -3068,6 +3162,14 @@ impl<'a> AttributesPrinter<'a> {
         self
     }
 
+    pub fn set_external_any(
+        mut self,
+        external: &'a Option<(EcoString, EcoString, SrcSpan)>,
+    ) -> Self {
+        self.external_any = external;
+        self
+    }
+
     pub fn set_internal(mut self, publicity: Publicity) -> Self {
         self.internal = publicity.is_internal();
         self
@@ -3077,6 +3179,41 @@ impl<'a> AttributesPrinter<'a> {
         self.deprecation = deprecation;
         self
     }
+
+    pub fn set_since(mut self, since: &'a Option<EcoString>) -> Self {
+        self.since = since;
+        self
+    }
+
+    pub fn set_embedded_file(mut self, embedded_file: &'a Option<EmbeddedFile>) -> Self {
+        self.embedded_file = embedded_file;
+        self
+    }
+
+    pub fn set_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn set_derive_decode(mut self, derive_decode: Option<SrcSpan>) -> Self {
+        self.derive_decode = derive_decode.is_some();
+        self
+    }
+
+    pub fn set_derive_nix_attrs(mut self, derive_nix_attrs: Option<SrcSpan>) -> Self {
+        self.derive_nix_attrs = derive_nix_attrs.is_some();
+        self
+    }
+
+    pub fn set_derive_nixos_options(mut self, derive_nixos_options: Option<SrcSpan>) -> Self {
+        self.derive_nixos_options = derive_nixos_options.is_some();
+        self
+    }
+
+    pub fn set_inline(mut self, inline: bool) -> Self {
+        self.inline = inline;
+        self
+    }
 }
 
 impl<'a> Documentable<'a> for AttributesPrinter<'a> {
@@ -3088,6 +3225,11 @@ impl<'a> Documentable<'a> for AttributesPrinter<'a> {
             attributes.push(docvec!["@deprecated(\"", message, "\")"])
         };
 
+        // @since attribute
+        if let Some(version) = self.since {
+            attributes.push(docvec!["@since(\"", version, "\")"])
+        };
+
         // @external attributes
         if let Some((m, f, _)) = self.external_erlang {
             attributes.push(docvec!["@external(erlang, \"", m, "\", \"", f, "\")"])
@@ -3101,11 +3243,43 @@ impl<'a> Documentable<'a> for AttributesPrinter<'a> {
             attributes.push(docvec!["@external(nix, \"", m, "\", \"", f, "\")"])
         };
 
+        if let Some((m, f, _)) = self.external_any {
+            attributes.push(docvec!["@external(any, \"", m, "\", \"", f, "\")"])
+        };
+
         // @internal attribute
         if self.internal {
             attributes.push("@internal".to_doc());
         };
 
+        // @embed_file attribute
+        if let Some(EmbeddedFile { path, .. }) = self.embedded_file {
+            attributes.push(docvec!["@embed_file(\"", path, "\")"])
+        };
+
+        // @strict attribute
+        if self.strict {
+            attributes.push("@strict".to_doc());
+        };
+
+        // @derive attributes
+        if self.derive_decode {
+            attributes.push("@derive(decode)".to_doc());
+        };
+
+        if self.derive_nix_attrs {
+            attributes.push("@derive(nix_attrs)".to_doc());
+        };
+
+        if self.derive_nixos_options {
+            attributes.push("@derive(nixos_options)".to_doc());
+        };
+
+        // @inline attribute
+        if self.inline {
+            attributes.push("@inline".to_doc());
+        };
+
         if attributes.is_empty() {
             nil()
         } else {
@@ -6,6 +6,7 @@ mod bit_array;
 mod blocks;
 mod cases;
 mod conditional_compilation;
+mod config;
 mod custom_type;
 mod external_fn;
 mod external_types;
@@ -21,8 +22,13 @@ mod use_;
 macro_rules! assert_format {
     ($src:expr $(,)?) => {
         let mut writer = String::new();
-        $crate::format::pretty(&mut writer, &$src.into(), camino::Utf8Path::new("<stdin>"))
-            .unwrap();
+        $crate::format::pretty(
+            &mut writer,
+            &$src.into(),
+            camino::Utf8Path::new("<stdin>"),
+            Default::default(),
+        )
+        .unwrap();
         assert_eq!($src, writer);
     };
 }
@@ -31,8 +37,13 @@ macro_rules! assert_format {
 macro_rules! assert_format_rewrite {
     ($src:expr, $expected:expr  $(,)?) => {
         let mut writer = String::new();
-        $crate::format::pretty(&mut writer, &$src.into(), camino::Utf8Path::new("<stdin>"))
-            .unwrap();
+        $crate::format::pretty(
+            &mut writer,
+            &$src.into(),
+            camino::Utf8Path::new("<stdin>"),
+            Default::default(),
+        )
+        .unwrap();
         assert_eq!(writer, $expected);
     };
 }
@@ -5177,6 +5188,30 @@ pub type Tiger =
     );
 }
 
+#[test]
+fn since_attribute() {
+    assert_format!(
+        r#"@since("1.2.0")
+pub const wibble = 1
+
+@deprecated("Use wobble")
+@since("1.0.0")
+pub fn wobble() -> Int {
+  wibble
+}
+
+@since("0.3.0")
+pub type Wabble {
+  Wabble
+}
+
+@since("0.3.0")
+pub type Tiger =
+  Nil
+"#
+    );
+}
+
 // https://github.com/gleam-lang/gleam/issues/2423
 #[test]
 fn prefix_as() {
@@ -5754,6 +5789,46 @@ pub const wibble = 1
     );
 }
 
+#[test]
+fn embed_file_attribute_on_const() {
+    assert_format!(
+        r#"@embed_file("priv/data.json")
+pub const data: String
+"#
+    );
+}
+
+#[test]
+fn embed_file_attribute_with_internal_attribute() {
+    assert_format!(
+        r#"@internal
+@embed_file("priv/data.bin")
+pub const data: BitArray
+"#
+    );
+}
+
+#[test]
+fn strict_attribute_on_const() {
+    assert_format!(
+        r#"@internal
+@strict
+pub const wibble = 1
+"#
+    );
+}
+
+#[test]
+fn inline_attribute_on_fn() {
+    assert_format!(
+        r#"@inline
+pub fn wibble(x) {
+  x + 1
+}
+"#
+    );
+}
+
 #[test]
 fn comments_inside_contant_list() {
     assert_format!(
@@ -0,0 +1,146 @@
+use crate::{assert_format_rewrite, config::FormatConfig};
+use pretty_assertions::assert_eq;
+
+fn format_with_config(src: &str, config: FormatConfig) -> String {
+    let mut writer = String::new();
+    crate::format::pretty(
+        &mut writer,
+        &src.into(),
+        camino::Utf8Path::new("<stdin>"),
+        config,
+    )
+    .unwrap();
+    writer
+}
+
+const TRAILING_COMMAS: FormatConfig = FormatConfig {
+    line_width: 80,
+    trailing_commas: true,
+};
+
+#[test]
+fn wider_line_width_keeps_long_lines() {
+    let src = r#"pub fn main() {
+  wibble(wibble_wobble_wabble, wibble_wobble_wabble, wibble_wobble_wabble, wibble_wobble)
+}
+"#;
+    let config = FormatConfig {
+        line_width: 100,
+        trailing_commas: false,
+    };
+    assert_eq!(format_with_config(src, config), src);
+}
+
+#[test]
+fn default_line_width_splits_long_lines() {
+    assert_format_rewrite!(
+        r#"pub fn main() {
+  wibble(wibble_wobble_wabble, wibble_wobble_wabble, wibble_wobble_wabble, wibble_wobble)
+}
+"#,
+        r#"pub fn main() {
+  wibble(
+    wibble_wobble_wabble,
+    wibble_wobble_wabble,
+    wibble_wobble_wabble,
+    wibble_wobble,
+  )
+}
+"#
+    );
+}
+
+#[test]
+fn narrower_line_width_splits_short_lines() {
+    let src = r#"pub fn main() {
+  wibble(wobble, wabble)
+}
+"#;
+    let config = FormatConfig {
+        line_width: 20,
+        trailing_commas: false,
+    };
+    assert_eq!(
+        format_with_config(src, config),
+        r#"pub fn main() {
+  wibble(
+    wobble,
+    wabble,
+  )
+}
+"#
+    );
+}
+
+#[test]
+fn trailing_comma_splits_list() {
+    let src = r#"pub fn main() {
+  [1, 2, 3,]
+}
+"#;
+    assert_eq!(
+        format_with_config(src, TRAILING_COMMAS),
+        r#"pub fn main() {
+  [
+    1,
+    2,
+    3,
+  ]
+}
+"#
+    );
+}
+
+#[test]
+fn trailing_comma_is_removed_by_default() {
+    assert_format_rewrite!(
+        r#"pub fn main() {
+  [1, 2, 3,]
+}
+"#,
+        r#"pub fn main() {
+  [1, 2, 3]
+}
+"#
+    );
+}
+
+#[test]
+fn list_without_trailing_comma_is_not_split() {
+    let src = r#"pub fn main() {
+  [1, 2, 3]
+}
+"#;
+    assert_eq!(format_with_config(src, TRAILING_COMMAS), src);
+}
+
+#[test]
+fn split_list_with_trailing_comma_is_kept() {
+    let src = r#"pub fn main() {
+  [
+    1,
+    2,
+    3,
+  ]
+}
+"#;
+    assert_eq!(format_with_config(src, TRAILING_COMMAS), src);
+}
+
+#[test]
+fn trailing_comma_splits_tuple() {
+    let src = r#"pub fn main() {
+  #(1, 2,)
+}
+"#;
+    assert_eq!(
+        format_with_config(src, TRAILING_COMMAS),
+        r#"pub fn main() {
+  #(
+    1,
+    2,
+  )
+}
+"#
+    );
+}
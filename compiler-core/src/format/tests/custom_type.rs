@@ -352,3 +352,49 @@ It even has multiple lines!
 "#
     );
 }
+
+#[test]
+fn derive_decode() {
+    assert_format!(
+        r#"@derive(decode)
+pub type Wibble {
+  Wibble(name: String, age: Int)
+}
+"#
+    );
+}
+
+#[test]
+fn derive_decode_internal() {
+    assert_format!(
+        r#"@internal
+@derive(decode)
+pub type Wibble {
+  Wibble(name: String)
+}
+"#
+    );
+}
+
+#[test]
+fn derive_nix_attrs() {
+    assert_format!(
+        r#"@derive(decode)
+@derive(nix_attrs)
+pub type Wibble {
+  Wibble(name: String, age: Int)
+}
+"#
+    );
+}
+
+#[test]
+fn derive_nixos_options() {
+    assert_format!(
+        r#"@derive(nixos_options)
+pub type Service {
+  Service(name: String, port: Int)
+}
+"#
+    );
+}
@@ -90,3 +90,13 @@ pub fn register(
 "#
     );
 }
+
+#[test]
+fn any_external() {
+    assert_format!(
+        r#"@external(erlang, "ffi", "wibble")
+@external(any, "./ffi.nix", "wibble")
+pub fn wibble() -> Int
+"#
+    );
+}
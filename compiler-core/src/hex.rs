@@ -1,14 +1,19 @@
-use camino::Utf8Path;
+use std::io::Read;
+
+use camino::{Utf8Path, Utf8PathBuf};
 use debug_ignore::DebugIgnore;
+use ecow::EcoString;
 use flate2::read::GzDecoder;
 use futures::future;
 use hexpm::{version::Version, ApiError};
 use tar::Archive;
 
 use crate::{
+    config::HexConfig,
     io::{FileSystemReader, FileSystemWriter, HttpClient, TarUnpacker},
     manifest::{ManifestPackage, ManifestPackageSource},
     paths::{self, ProjectPaths},
+    version::COMPILER_VERSION,
     Error, Result,
 };
 
@@ -27,6 +32,81 @@ fn key_name(hostname: &str) -> String {
     format!("gleam-{hostname}")
 }
 
+/// Splits the name of a package belonging to a Hex organisation, written
+/// `organisation/package`, into the organisation and the package's name.
+pub fn split_organisation_package(name: &str) -> (Option<&str>, &str) {
+    match name.split_once('/') {
+        Some((organisation, package)) => (Some(organisation), package),
+        None => (None, name),
+    }
+}
+
+/// Names the dependencies of the releases of a package which belong to a
+/// Hex organisation `organisation/package`, as they are named in gleam.toml.
+pub fn qualify_organisation_dependencies(package: &mut hexpm::Package) {
+    for release in package.releases.iter_mut() {
+        release.requirements = std::mem::take(&mut release.requirements)
+            .into_iter()
+            .map(|(name, dependency)| match &dependency.repository {
+                Some(organisation) if organisation != "hexpm" => {
+                    (format!("{organisation}/{name}"), dependency)
+                }
+                _ => (name, dependency),
+            })
+            .collect();
+    }
+}
+
+/// The Hex repository packages are downloaded from, which may be a mirror,
+/// along with the API key to authenticate with, if any.
+#[derive(Debug, Clone, Default)]
+pub struct Repository {
+    mirror: Option<http::Uri>,
+    api_key: Option<String>,
+}
+
+impl Repository {
+    pub fn new(config: &HexConfig, api_key: Option<String>) -> Self {
+        Self {
+            mirror: config.repo_url.clone(),
+            api_key,
+        }
+    }
+
+    pub fn api_key(&self) -> Option<&str> {
+        self.api_key.as_deref()
+    }
+
+    /// The Hex config to make requests about the given package with, along
+    /// with the package's name in its repository, which for the packages of
+    /// an organisation is the one of its organisation.
+    ///
+    pub fn config_for<'a>(&self, package: &'a str) -> (hexpm::Config, &'a str) {
+        let mut config = hexpm::Config::new();
+        if let Some(mirror) = &self.mirror {
+            config.repository_base = uri_with_path_suffix(mirror, "");
+        }
+
+        let (organisation, name) = split_organisation_package(package);
+        if let Some(organisation) = organisation {
+            let suffix = format!("repos/{organisation}/");
+            config.api_base = uri_with_path_suffix(&config.api_base, &suffix);
+            config.repository_base = uri_with_path_suffix(&config.repository_base, &suffix);
+        }
+        (config, name)
+    }
+}
+
+/// Appends the suffix to the path of the URI, which the Hex client expects to
+/// end with a slash.
+fn uri_with_path_suffix(uri: &http::Uri, suffix: &str) -> http::Uri {
+    let base = uri.to_string();
+    let base = base.trim_end_matches('/');
+    format!("{base}/{suffix}")
+        .parse()
+        .unwrap_or_else(|_| uri.clone())
+}
+
 pub async fn publish_package<Http: HttpClient>(
     release_tarball: Vec<u8>,
     version: String,
@@ -132,7 +212,7 @@ pub struct Downloader {
     fs_writer: DebugIgnore<Box<dyn FileSystemWriter>>,
     http: DebugIgnore<Box<dyn HttpClient>>,
     untar: DebugIgnore<Box<dyn TarUnpacker>>,
-    hex_config: hexpm::Config,
+    repository: Repository,
     paths: ProjectPaths,
 }
 
@@ -142,6 +222,7 @@ impl Downloader {
         fs_writer: Box<dyn FileSystemWriter>,
         http: Box<dyn HttpClient>,
         untar: Box<dyn TarUnpacker>,
+        repository: Repository,
         paths: ProjectPaths,
     ) -> Self {
         Self {
@@ -149,7 +230,7 @@ impl Downloader {
             fs_writer: DebugIgnore(fs_writer),
             http: DebugIgnore(http),
             untar: DebugIgnore(untar),
-            hex_config: hexpm::Config::new(),
+            repository,
             paths,
         }
     }
@@ -183,11 +264,12 @@ impl Downloader {
             "downloading_package_to_cache"
         );
 
+        let (config, name) = self.repository.config_for(&package.name);
         let request = hexpm::get_package_tarball_request(
-            &package.name,
+            name,
             &package.version.to_string(),
-            None,
-            &self.hex_config,
+            self.repository.api_key(),
+            &config,
         );
         let response = self.http.send(request).await?;
 
@@ -208,12 +290,18 @@ impl Downloader {
         package: &ManifestPackage,
     ) -> Result<bool> {
         let _ = self.ensure_package_downloaded(package).await?;
-        self.extract_package_from_cache(&package.name, &package.version)
+
+        // Mix writes its build artefacts inside the package directory, so
+        // these packages get a copy of their own instead of sharing the one
+        // in the store.
+        if package.build_tools.iter().any(|tool| tool == "mix") {
+            return self.extract_package_from_cache(&package.name, &package.version);
+        }
+        self.link_package_from_store(package)
     }
 
     // It would be really nice if this was async but the library is sync
     pub fn extract_package_from_cache(&self, name: &str, version: &Version) -> Result<bool> {
-        let contents_path = Utf8Path::new("contents.tar.gz");
         let destination = self.paths.build_packages_package(name);
 
         // If the directory already exists then there's nothing for us to do
@@ -224,7 +312,76 @@ impl Downloader {
 
         tracing::info!(package = name, "writing_package_to_target");
         let tarball = paths::global_package_cache_package_tarball(name, &version.to_string());
-        let reader = self.fs_reader.reader(&tarball)?;
+        self.extract_package(&tarball, &destination)?;
+        Ok(true)
+    }
+
+    /// Links the directory of the package in the build directory to its
+    /// source in the global package store, extracting it there first if no
+    /// other project has done so already.
+    pub fn link_package_from_store(&self, package: &ManifestPackage) -> Result<bool> {
+        // The store is keyed by checksum, so packages from elsewhere get a
+        // copy of their own.
+        let ManifestPackageSource::Hex { outer_checksum } = &package.source else {
+            return self.extract_package_from_cache(&package.name, &package.version);
+        };
+        let version = package.version.to_string();
+        let tarball = paths::global_package_cache_package_tarball(&package.name, &version);
+        let stored = paths::global_package_store_package(
+            &package.name,
+            &version,
+            &outer_checksum.to_string(),
+        );
+        self.link_package(&package.name, &tarball, &stored)
+    }
+
+    /// Links the directory of the package in the build directory to the given
+    /// directory of the package store, extracting the tarball there first if
+    /// it doesn't exist yet. Symlinks can't always be created, on Windows in
+    /// particular, in which case the package is copied instead.
+    pub fn link_package(&self, name: &str, tarball: &Utf8Path, stored: &Utf8Path) -> Result<bool> {
+        let destination = self.paths.build_packages_package(name);
+        if self.fs_reader.is_directory(&destination) {
+            tracing::info!(package = name, "Package already in build directory");
+            return Ok(false);
+        }
+
+        if !self.fs_reader.is_directory(stored) {
+            tracing::info!(package = name, "writing_package_to_store");
+            // The package is extracted next to its place in the store and only
+            // moved there once complete, so that an interrupted extraction is
+            // never taken for a complete one.
+            let partial = Utf8PathBuf::from(format!("{stored}.partial-{}", std::process::id()));
+            self.fs_writer.delete_directory(&partial)?;
+            self.extract_package(tarball, &partial)?;
+            if let Err(error) = self.fs_writer.rename(&partial, stored) {
+                self.fs_writer.delete_directory(&partial)?;
+                // Another project may have stored the package in the meantime.
+                if !self.fs_reader.is_directory(stored) {
+                    return Err(error);
+                }
+            }
+        }
+
+        tracing::info!(package = name, "linking_package_from_store");
+        self.fs_writer
+            .mkdir(&self.paths.build_packages_directory())?;
+        // A link left behind to a store directory which has since been
+        // deleted is not a directory, but it still has to go.
+        self.fs_writer.delete_file(&destination)?;
+        if let Err(error) = self.fs_writer.symlink_dir(stored, &destination) {
+            tracing::info!(package = name, error = %error, "copying_package_from_store");
+            self.fs_writer.mkdir(&destination)?;
+            self.fs_writer.copy_dir(stored, &destination)?;
+        }
+        Ok(true)
+    }
+
+    /// Expands the source code of a package from its tarball into the given
+    /// directory.
+    fn extract_package(&self, tarball: &Utf8Path, destination: &Utf8Path) -> Result<()> {
+        let contents_path = Utf8Path::new("contents.tar.gz");
+        let reader = self.fs_reader.reader(tarball)?;
         let mut archive = Archive::new(reader);
 
         // Find the source code from within the outer tarball
@@ -235,15 +392,15 @@ impl Downloader {
             if path.as_ref() == contents_path {
                 // Expand this inner source code and write to the file system
                 let archive = Archive::new(GzDecoder::new(file));
-                let result = self.untar.unpack(&destination, archive);
+                let result = self.untar.unpack(destination, archive);
 
                 // If we failed to expand the tarball remove any source code
                 // that was partially written so that we don't mistakenly think
                 // the operation succeeded next time we run.
                 return match result {
-                    Ok(()) => Ok(true),
+                    Ok(()) => Ok(()),
                     Err(err) => {
-                        self.fs_writer.delete_directory(&destination)?;
+                        self.fs_writer.delete_directory(destination)?;
                         Err(err)
                     }
                 };
@@ -255,6 +412,33 @@ impl Downloader {
         })
     }
 
+    /// Reads the licences of a package from the metadata in its tarball, which
+    /// must already be in the global package cache.
+    pub fn package_licences(&self, name: &str, version: &Version) -> Result<Vec<EcoString>> {
+        let metadata_path = Utf8Path::new("metadata.config");
+        let tarball = paths::global_package_cache_package_tarball(name, &version.to_string());
+        let reader = self.fs_reader.reader(&tarball)?;
+        let mut archive = Archive::new(reader);
+
+        for entry in self.untar.entries(&mut archive)? {
+            let mut file = entry.map_err(Error::expand_tar)?;
+
+            let path = file.header().path().map_err(Error::expand_tar)?;
+            if path.as_ref() != metadata_path {
+                continue;
+            }
+            let mut metadata = String::new();
+            let _ = file
+                .read_to_string(&mut metadata)
+                .map_err(Error::expand_tar)?;
+            return Ok(metadata_licences(&metadata));
+        }
+
+        Err(Error::ExpandTar {
+            error: "Unable to locate Hex package metadata.config".into(),
+        })
+    }
+
     pub async fn download_hex_packages<'a, Packages: Iterator<Item = &'a ManifestPackage>>(
         &self,
         packages: Packages,
@@ -275,6 +459,23 @@ impl Downloader {
     }
 }
 
+/// Gets the licences listed in the `metadata.config` file of a Hex package,
+/// which holds Erlang terms such as `{<<"licenses">>, [<<"MIT">>]}.`.
+fn metadata_licences(metadata: &str) -> Vec<EcoString> {
+    let Some((_, rest)) = metadata.split_once(r#"<<"licenses">>"#) else {
+        return vec![];
+    };
+    let Some((licences, _)) = rest.split_once(']') else {
+        return vec![];
+    };
+    licences
+        .split(r#"<<""#)
+        .skip(1)
+        .filter_map(|licence| licence.split_once(r#"">>"#))
+        .map(|(licence, _)| licence.into())
+        .collect()
+}
+
 pub async fn publish_documentation<Http: HttpClient>(
     name: &str,
     version: &Version,
@@ -293,7 +494,7 @@ pub async fn publish_documentation<Http: HttpClient>(
 pub async fn get_package_release<Http: HttpClient>(
     name: &str,
     version: &Version,
-    config: &hexpm::Config,
+    repository: &Repository,
     http: &Http,
 ) -> Result<hexpm::Release<hexpm::ReleaseMeta>> {
     let version = version.to_string();
@@ -302,7 +503,177 @@ pub async fn get_package_release<Http: HttpClient>(
         version = version.as_str(),
         "looking_up_package_release"
     );
-    let request = hexpm::get_package_release_request(name, &version, None, config);
+    let (config, name) = repository.config_for(name);
+    let request = hexpm::get_package_release_request(name, &version, repository.api_key(), &config);
     let response = http.send(request).await?;
     hexpm::get_package_release_response(response).map_err(Error::hex)
 }
+
+/// Looks up the licences of a package with the Hex API. These are the ones
+/// of the package as it is currently published, rather than of any particular
+/// release.
+pub async fn get_package_licences<Http: HttpClient>(
+    name: &str,
+    repository: &Repository,
+    http: &Http,
+) -> Result<Vec<EcoString>> {
+    tracing::info!(name = name, "looking_up_package_licences");
+    let (config, name) = repository.config_for(name);
+    let mut request = http::Request::get(format!("{}packages/{name}", config.api_base))
+        .header("accept", "application/json")
+        .header("user-agent", format!("glistix/{COMPILER_VERSION}"));
+    if let Some(api_key) = repository.api_key() {
+        request = request.header("authorization", api_key);
+    }
+    let request = request.body(vec![]).map_err(Error::http)?;
+    let response = http.send(request).await?;
+
+    let status = response.status();
+    if status == http::StatusCode::NOT_FOUND {
+        return Err(Error::hex(ApiError::NotFound));
+    }
+    if !status.is_success() {
+        return Err(Error::Hex(format!(
+            "Unexpected response status {status} when looking up `{name}`"
+        )));
+    }
+    package_response_licences(response.body())
+}
+
+/// Gets the licences from the JSON the Hex API responds with for a package.
+fn package_response_licences(body: &[u8]) -> Result<Vec<EcoString>> {
+    #[derive(serde::Deserialize)]
+    struct Package {
+        meta: Meta,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Meta {
+        #[serde(default)]
+        licenses: Vec<EcoString>,
+    }
+
+    let package: Package = serde_json::from_slice(body).map_err(Error::hex)?;
+    Ok(package.meta.licenses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hexpm::{version::Range, Dependency, Release};
+
+    #[test]
+    fn repository_config_for_public_package() {
+        let repository = Repository::default();
+        let (config, name) = repository.config_for("wibble");
+        assert_eq!(name, "wibble");
+        assert_eq!(config.api_base, hexpm::Config::new().api_base);
+        assert_eq!(config.repository_base, hexpm::Config::new().repository_base);
+    }
+
+    #[test]
+    fn repository_config_for_organisation_package() {
+        let repository = Repository::default();
+        let (config, name) = repository.config_for("acme/wibble");
+        assert_eq!(name, "wibble");
+        assert_eq!(
+            config.api_base.to_string(),
+            "https://hex.pm/api/repos/acme/"
+        );
+        assert_eq!(
+            config.repository_base.to_string(),
+            "https://repo.hex.pm/repos/acme/"
+        );
+    }
+
+    #[test]
+    fn repository_config_with_mirror() {
+        let hex_config = HexConfig {
+            repo_url: Some(http::Uri::from_static("https://hex.example.com/mirror")),
+            authenticate: true,
+        };
+        let repository = Repository::new(&hex_config, Some("key".into()));
+        let (config, _) = repository.config_for("acme/wibble");
+        assert_eq!(repository.api_key(), Some("key"));
+        assert_eq!(
+            config.repository_base.to_string(),
+            "https://hex.example.com/mirror/repos/acme/"
+        );
+    }
+
+    #[test]
+    fn organisation_dependencies_are_qualified() {
+        let dependency = |repository: Option<&str>| Dependency {
+            app: None,
+            optional: false,
+            repository: repository.map(String::from),
+            requirement: Range::new(">= 1.0.0".into()),
+        };
+        let mut package = hexpm::Package {
+            name: "wibble".into(),
+            repository: "acme".into(),
+            releases: vec![Release {
+                version: Version::new(1, 0, 0),
+                requirements: [
+                    ("gleam_stdlib".into(), dependency(None)),
+                    ("wobble".into(), dependency(Some("acme"))),
+                    ("wubble".into(), dependency(Some("hexpm"))),
+                ]
+                .into(),
+                retirement_status: None,
+                outer_checksum: vec![1, 2, 3],
+                meta: (),
+            }],
+        };
+        qualify_organisation_dependencies(&mut package);
+        let mut names = package.releases[0]
+            .requirements
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["acme/wobble", "gleam_stdlib", "wubble"]);
+    }
+
+    #[test]
+    fn licences_from_metadata() {
+        let metadata = r#"{<<"name">>, <<"wibble">>}.
+{<<"licenses">>, [<<"Apache-2.0">>, <<"MIT">>]}.
+{<<"links">>, [{<<"Repository">>, <<"https://example.com">>}]}.
+"#;
+        assert_eq!(
+            metadata_licences(metadata),
+            vec![EcoString::from("Apache-2.0"), "MIT".into()]
+        );
+    }
+
+    #[test]
+    fn licences_from_metadata_without_licences() {
+        assert!(metadata_licences(r#"{<<"licenses">>,[]}."#).is_empty());
+        assert!(metadata_licences(r#"{<<"name">>, <<"wibble">>}."#).is_empty());
+    }
+
+    #[test]
+    fn licences_from_package_response() {
+        let body = r#"{
+  "name": "wibble",
+  "meta": {
+    "description": "A package",
+    "licenses": ["Apache-2.0", "MIT"],
+    "links": {}
+  }
+}"#;
+        assert_eq!(
+            package_response_licences(body.as_bytes()).unwrap(),
+            vec![EcoString::from("Apache-2.0"), EcoString::from("MIT")]
+        );
+    }
+
+    #[test]
+    fn licences_from_package_response_without_licences() {
+        let body = r#"{"name": "wibble", "meta": {"links": {}}}"#;
+        assert!(package_response_licences(body.as_bytes())
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+//! Localisation of compiler diagnostics.
+//!
+//! The messages of the most common diagnostics are looked up in a catalogue
+//! keyed by the kind of diagnostic, rather than being written inline where the
+//! diagnostic is built. Adding a translation only requires adding a catalogue
+//! for the new language: any message it does not translate falls back to
+//! English.
+//!
+//! The language is chosen once at startup with [`set_language`], typically from
+//! the `GLEAM_LANG` environment variable. English is used if it is never set.
+
+use std::sync::OnceLock;
+
+use strum::EnumIter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+}
+
+impl Language {
+    /// Parse a language tag such as `en`, `en-GB` or `en_GB.UTF-8`, as found
+    /// in the `GLEAM_LANG` environment variable. Returns `None` for languages
+    /// that have no catalogue.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_', '.']).next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(Self::English),
+            _ => None,
+        }
+    }
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// Set the language diagnostics are printed in. Only the first call has any
+/// effect.
+pub fn set_language(language: Language) {
+    let _ = LANGUAGE.set(language);
+}
+
+/// The language diagnostics are printed in.
+pub fn language() -> Language {
+    LANGUAGE.get().copied().unwrap_or_default()
+}
+
+/// The kinds of diagnostic that have entries in the message catalogue. These
+/// only name messages: the codes errors are reported with are the
+/// [`ErrorCode`](crate::error::codes::ErrorCode)s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum Key {
+    UnknownVariable,
+    TypeUsedAsValue,
+    UnknownType,
+    UnknownModule,
+    UnknownRecordField,
+    TypeMismatch,
+    IncorrectArity,
+    InexhaustivePatterns,
+    UnusedVariable,
+    UnusedImportedModule,
+    UnusedImportedValue,
+}
+
+/// An entry in a message catalogue. Text and hints may contain `{name}`
+/// placeholders, which are filled in by [`text`] and [`hint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Message {
+    pub title: &'static str,
+    pub text: Option<&'static str>,
+    pub hint: Option<&'static str>,
+}
+
+/// The title of the diagnostic of the given kind, in the current language.
+pub fn title(key: Key) -> &'static str {
+    message(language(), key).title
+}
+
+/// The text of the diagnostic of the given kind, in the current language,
+/// with its placeholders replaced by the given arguments.
+pub fn text(key: Key, arguments: &[(&str, &str)]) -> String {
+    fill(message(language(), key).text.unwrap_or_default(), arguments)
+}
+
+/// The hint of the diagnostic of the given kind, in the current language,
+/// with its placeholders replaced by the given arguments.
+pub fn hint(key: Key, arguments: &[(&str, &str)]) -> Option<String> {
+    message(language(), key)
+        .hint
+        .map(|hint| fill(hint, arguments))
+}
+
+fn message(language: Language, key: Key) -> Message {
+    match language {
+        Language::English => english(key),
+    }
+}
+
+fn fill(template: &str, arguments: &[(&str, &str)]) -> String {
+    arguments
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+fn english(key: Key) -> Message {
+    let (title, text, hint) = match key {
+        Key::UnknownVariable => (
+            "Unknown variable",
+            Some("The name `{name}` is not in scope here."),
+            None,
+        ),
+        Key::TypeUsedAsValue => (
+            "Unknown variable",
+            Some("`{name}` is a type, it cannot be used as a value."),
+            None,
+        ),
+        Key::UnknownType => ("Unknown type", None, None),
+        Key::UnknownModule => (
+            "Unknown module",
+            Some("No module has been found with the name `{name}`."),
+            None,
+        ),
+        Key::UnknownRecordField => ("Unknown record field", None, None),
+        Key::TypeMismatch => ("Type mismatch", None, None),
+        Key::IncorrectArity => ("Incorrect arity", None, None),
+        Key::InexhaustivePatterns => ("Inexhaustive patterns", None, None),
+        Key::UnusedVariable => ("Unused variable", None, None),
+        Key::UnusedImportedModule => (
+            "Unused imported module",
+            None,
+            Some("You can safely remove it."),
+        ),
+        Key::UnusedImportedValue => ("Unused imported value", None, None),
+    };
+    Message { title, text, hint }
+}
+
+#[test]
+fn language_from_tag() {
+    assert_eq!(Language::from_tag("en"), Some(Language::English));
+    assert_eq!(Language::from_tag("en-GB"), Some(Language::English));
+    assert_eq!(Language::from_tag("EN_us.UTF-8"), Some(Language::English));
+    assert_eq!(Language::from_tag("xx"), None);
+    assert_eq!(Language::from_tag(""), None);
+}
+
+#[test]
+fn placeholders_are_filled() {
+    assert_eq!(
+        fill(
+            "The name `{name}` is not in scope here.",
+            &[("name", "wibble")]
+        ),
+        "The name `wibble` is not in scope here."
+    );
+}
+
+#[test]
+fn every_key_has_an_english_title() {
+    use strum::IntoEnumIterator;
+    for key in Key::iter() {
+        assert!(!english(key).title.is_empty(), "{key:?}");
+    }
+}
@@ -271,6 +271,7 @@ pub trait FileSystemReader {
     fn is_file(&self, path: &Utf8Path) -> bool;
     fn is_directory(&self, path: &Utf8Path) -> bool;
     fn modification_time(&self, path: &Utf8Path) -> Result<SystemTime, Error>;
+    fn file_size(&self, path: &Utf8Path) -> Result<u64, Error>;
     fn canonicalise(&self, path: &Utf8Path) -> Result<Utf8PathBuf, Error>;
 }
 
@@ -284,6 +285,16 @@ pub fn gleam_source_files<'a>(
     files_with_extension(io, dir, "gleam")
 }
 
+/// Iterates over Nix source files (`.nix`) in a certain directory.
+/// Symlinks are followed.
+pub fn nix_source_files<'a>(
+    io: &'a impl FileSystemReader,
+    dir: &'a Utf8Path,
+) -> impl Iterator<Item = Utf8PathBuf> + 'a {
+    tracing::trace!("nix_source_files {:?}", dir);
+    files_with_extension(io, dir, "nix")
+}
+
 /// Iterates over Gleam cache files (`.cache`) in a certain directory.
 /// Symlinks are followed.
 pub fn gleam_cache_files<'a>(
@@ -351,10 +362,13 @@ pub trait FileSystemWriter {
     fn write(&self, path: &Utf8Path, content: &str) -> Result<(), Error>;
     fn write_bytes(&self, path: &Utf8Path, content: &[u8]) -> Result<(), Error>;
     fn delete_directory(&self, path: &Utf8Path) -> Result<(), Error>;
+    /// Copies a file, keeping its modification time where possible.
     fn copy(&self, from: &Utf8Path, to: &Utf8Path) -> Result<(), Error>;
     fn copy_dir(&self, from: &Utf8Path, to: &Utf8Path) -> Result<(), Error>;
     fn hardlink(&self, from: &Utf8Path, to: &Utf8Path) -> Result<(), Error>;
     fn symlink_dir(&self, from: &Utf8Path, to: &Utf8Path) -> Result<(), Error>;
+    /// Moves a file or directory, failing if the destination already exists.
+    fn rename(&self, from: &Utf8Path, to: &Utf8Path) -> Result<(), Error>;
     fn delete_file(&self, path: &Utf8Path) -> Result<(), Error>;
     fn exists(&self, path: &Utf8Path) -> bool;
 }
@@ -1,7 +1,7 @@
 use super::*;
 use std::ops::Deref;
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::{HashMap, HashSet},
     rc::Rc,
     time::Duration,
@@ -25,6 +25,26 @@ use camino::{Utf8Path, Utf8PathBuf};
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct InMemoryFileSystem {
     files: Rc<RefCell<HashMap<Utf8PathBuf, InMemoryFile>>>,
+    commands: Rc<RefCell<Vec<RecordedCommand>>>,
+    exit_status: Rc<Cell<i32>>,
+}
+
+/// A command run with an `InMemoryFileSystem`. Commands are not actually run,
+/// they are recorded so that tests can check what would have been run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordedCommand {
+    Exec {
+        program: String,
+        args: Vec<String>,
+        env: Vec<(String, String)>,
+        cwd: Option<Utf8PathBuf>,
+    },
+    CompileBeam {
+        out: Utf8PathBuf,
+        lib: Utf8PathBuf,
+        /// Sorted, so that they can be compared.
+        modules: Vec<Utf8PathBuf>,
+    },
 }
 
 impl Default for InMemoryFileSystem {
@@ -36,6 +56,8 @@ impl Default for InMemoryFileSystem {
 
         Self {
             files: Rc::new(RefCell::new(files)),
+            commands: Rc::new(RefCell::new(vec![])),
+            exit_status: Rc::new(Cell::new(0)),
         }
     }
 }
@@ -64,6 +86,16 @@ impl InMemoryFileSystem {
             .collect()
     }
 
+    /// The commands that have been run, in order.
+    pub fn commands(&self) -> Vec<RecordedCommand> {
+        self.commands.borrow().clone()
+    }
+
+    /// Sets the exit status the commands run from now on return.
+    pub fn set_exit_status(&self, status: i32) {
+        self.exit_status.set(status);
+    }
+
     /// All files currently in the filesystem (directories are not included).
     pub fn files(&self) -> Vec<Utf8PathBuf> {
         self.files
@@ -142,7 +174,9 @@ impl FileSystemWriter for InMemoryFileSystem {
     }
 
     fn copy(&self, from: &Utf8Path, to: &Utf8Path) -> Result<(), Error> {
-        self.write_bytes(to, &self.read_bytes(from)?)
+        self.write_bytes(to, &self.read_bytes(from)?)?;
+        self.try_set_modification_time(to, self.modification_time(from)?)?;
+        Ok(())
     }
 
     fn copy_dir(&self, _: &Utf8Path, _: &Utf8Path) -> Result<(), Error> {
@@ -183,6 +217,31 @@ impl FileSystemWriter for InMemoryFileSystem {
         panic!("unimplemented") // TODO
     }
 
+    fn rename(&self, from: &Utf8Path, to: &Utf8Path) -> Result<(), Error> {
+        let mut files = self.files.deref().borrow_mut();
+        if !files.contains_key(from) || files.contains_key(to) {
+            return Err(Error::FileIo {
+                kind: FileKind::File,
+                action: FileIoAction::Rename,
+                path: from.to_path_buf(),
+                err: None,
+            });
+        }
+
+        // Move the file along with any files in the directory
+        let moved = files
+            .keys()
+            .filter(|file| file.starts_with(from))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in moved {
+            if let (Some(file), Ok(relative)) = (files.remove(&path), path.strip_prefix(from)) {
+                let _ = files.insert(to.join(relative), file);
+            }
+        }
+        Ok(())
+    }
+
     fn delete_file(&self, path: &Utf8Path) -> Result<(), Error> {
         let mut files = self.files.deref().borrow_mut();
         if files.get(path).is_some_and(|f| f.is_directory()) {
@@ -281,9 +340,9 @@ impl FileSystemReader for InMemoryFileSystem {
             .is_some_and(|file| file.is_directory())
     }
 
-    fn reader(&self, _path: &Utf8Path) -> Result<WrappedReader, Error> {
-        // TODO
-        unreachable!("Memory reader unimplemented")
+    fn reader(&self, path: &Utf8Path) -> Result<WrappedReader, Error> {
+        let bytes = self.read_bytes(path)?;
+        Ok(WrappedReader::new(path, Box::new(io::Cursor::new(bytes))))
     }
 
     fn read_dir(&self, path: &Utf8Path) -> Result<ReadDir> {
@@ -311,6 +370,21 @@ impl FileSystemReader for InMemoryFileSystem {
         })?;
         Ok(file.modification_time)
     }
+
+    fn file_size(&self, path: &Utf8Path) -> Result<u64, Error> {
+        let files = self.files.deref().borrow();
+        let buffer = files
+            .get(path)
+            .and_then(|file| file.node.as_file_buffer())
+            .ok_or_else(|| Error::FileIo {
+                kind: FileKind::File,
+                action: FileIoAction::ReadMetadata,
+                path: path.to_path_buf(),
+                err: None,
+            })?;
+        let size = buffer.borrow().len() as u64;
+        Ok(size)
+    }
 }
 
 /// The representation of a file or directory in the in-memory filesystem.
@@ -426,24 +500,42 @@ impl io::Write for InMemoryFile {
 impl CommandExecutor for InMemoryFileSystem {
     fn exec(
         &self,
-        _program: &str,
-        _args: &[String],
-        _env: &[(&str, String)],
-        _cwd: Option<&Utf8Path>,
+        program: &str,
+        args: &[String],
+        env: &[(&str, String)],
+        cwd: Option<&Utf8Path>,
         _stdio: Stdio,
     ) -> Result<i32, Error> {
-        Ok(0) // Always succeed.
+        self.commands.borrow_mut().push(RecordedCommand::Exec {
+            program: program.into(),
+            args: args.to_vec(),
+            env: env
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+            cwd: cwd.map(Utf8Path::to_path_buf),
+        });
+        Ok(self.exit_status.get())
     }
 }
 
 impl BeamCompiler for InMemoryFileSystem {
     fn compile_beam(
         &self,
-        _out: &Utf8Path,
-        _lib: &Utf8Path,
-        _modules: &HashSet<Utf8PathBuf>,
+        out: &Utf8Path,
+        lib: &Utf8Path,
+        modules: &HashSet<Utf8PathBuf>,
         _stdio: Stdio,
     ) -> Result<(), Error> {
+        let mut modules: Vec<_> = modules.iter().cloned().collect();
+        modules.sort();
+        self.commands
+            .borrow_mut()
+            .push(RecordedCommand::CompileBeam {
+                out: out.to_path_buf(),
+                lib: lib.to_path_buf(),
+                modules,
+            });
         Ok(()) // Always succeed.
     }
 }
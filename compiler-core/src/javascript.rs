@@ -98,7 +98,9 @@ impl<'a> Generator<'a> {
         // names.
         self.register_module_definitions_in_scope();
 
-        // Generate JavaScript code for each statement
+        // Generate JavaScript code for each statement. The type checker has
+        // sorted the definitions so that constants come after the ones they
+        // reference, meaning none is read before being initialised.
         let statements = self.collect_definitions().into_iter().chain(
             self.module
                 .definitions
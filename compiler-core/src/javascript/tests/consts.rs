@@ -126,3 +126,14 @@ fn literal_nil_does_not_get_constant_annotation() {
 fn constructor_function_in_constant() {
     assert_js!("pub const a = Ok");
 }
+
+#[test]
+fn constants_are_defined_after_the_constants_they_reference() {
+    assert_js!(
+        r#"
+pub const greeting = prefix <> "world"
+
+pub const prefix = "Hello, "
+"#
+    );
+}
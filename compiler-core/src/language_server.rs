@@ -1,15 +1,20 @@
 mod code_action;
 mod compiler;
 mod completer;
+mod configuration;
 mod edits;
 mod engine;
+mod extensions;
 mod feedback;
 mod files;
+mod inlay_hints;
 mod messages;
 mod progress;
 mod router;
+mod semantic_tokens;
 mod server;
 mod signature_help;
+mod statistics;
 
 #[cfg(test)]
 mod tests;
@@ -49,6 +54,12 @@ pub fn src_span_to_lsp_range(location: SrcSpan, line_numbers: &LineNumbers) -> R
     )
 }
 
+fn path_to_uri(path: Utf8PathBuf) -> Url {
+    let mut file: String = "file://".into();
+    file.push_str(&path.as_os_str().to_string_lossy());
+    Url::parse(&file).expect("path_to_uri URL parse")
+}
+
 fn path(uri: &Url) -> Utf8PathBuf {
     // The to_file_path method is available on these platforms
     #[cfg(any(unix, windows, target_os = "redox", target_os = "wasi"))]
@@ -1,34 +1,39 @@
 use std::{collections::HashSet, iter, sync::Arc};
 
 use crate::{
+    analyse::TargetSupport,
     ast::{
         self,
         visit::{visit_typed_call_arg, visit_typed_pattern_call_arg, Visit as _},
-        AssignName, AssignmentKind, CallArg, FunctionLiteralKind, ImplicitCallArgOrigin, Pattern,
-        SrcSpan, TypedAssignment, TypedExpr, TypedModuleConstant, TypedPattern, TypedStatement,
-        TypedUse,
+        ArgNames, AssignName, AssignmentKind, CallArg, FunctionLiteralKind, ImplicitCallArgOrigin,
+        Pattern, SrcSpan, TypedAssignment, TypedExpr, TypedModuleConstant, TypedPattern,
+        TypedStatement, TypedUse, CAPTURE_VARIABLE, PIPE_VARIABLE, USE_ASSIGNMENT_VARIABLE,
     },
-    build::{Located, Module},
+    build::{Located, Module, Target},
+    exhaustiveness::Compiler,
     line_numbers::LineNumbers,
     parse::extra::ModuleExtra,
     type_::{
         self,
+        environment::Environment,
         error::{ModuleSuggestion, VariableOrigin},
         printer::{Names, Printer},
-        FieldMap, ModuleValueConstructor, Type, TypedCallArg,
+        FieldMap, ModuleInterface, ModuleValueConstructor, Type, TypedCallArg,
     },
+    uid::UniqueIdGenerator,
     Error, STDLIB_PACKAGE_NAME,
 };
 use ecow::{eco_format, EcoString};
 use heck::ToSnakeCase;
+use id_arena::Arena;
 use im::HashMap;
 use itertools::Itertools;
 use lsp_types::{CodeAction, CodeActionKind, CodeActionParams, Position, Range, TextEdit, Url};
 
 use super::{
-    edits::{add_newlines_after_import, get_import_edit, position_of_first_definition_if_import},
+    edits::{ImportInserter, ImportPlacement},
     engine::{overlaps, within},
-    src_span_to_lsp_range,
+    path_to_uri, src_span_to_lsp_range,
 };
 
 #[derive(Debug)]
@@ -800,6 +805,177 @@ impl<'ast> ast::visit::Visit<'ast> for FillInMissingLabelledArgs<'ast> {
     }
 }
 
+/// Builder for code action to add the labels used at the selected call site
+/// to the definition of the called function, when it doesn't accept them.
+///
+/// ```gleam
+/// pub fn main() {
+///   wibble(1, label: 2)
+/// }
+///
+/// pub fn wibble(a, b) { todo }
+/// ```
+///
+/// Becomes:
+///
+/// ```gleam
+/// pub fn wibble(a, label b) { todo }
+/// ```
+///
+/// Each new label is given to the unlabelled argument with the same name, if
+/// any; the others go to the remaining unlabelled arguments in the same order
+/// they are used at the call site.
+///
+pub struct AddMissingLabelsToDefinition<'a> {
+    module: &'a Module,
+    params: &'a CodeActionParams,
+    edits: TextEdits<'a>,
+    selected_call: Option<SelectedCallLabels<'a>>,
+}
+
+struct SelectedCallLabels<'a> {
+    function_name: &'a EcoString,
+    positional_arguments: usize,
+    missing_labels: Vec<&'a EcoString>,
+}
+
+impl<'a> AddMissingLabelsToDefinition<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+    ) -> Self {
+        Self {
+            module,
+            params,
+            edits: TextEdits::new(line_numbers),
+            selected_call: None,
+        }
+    }
+
+    pub fn code_actions(mut self) -> Vec<CodeAction> {
+        self.visit_typed_module(&self.module.ast);
+
+        let Some(SelectedCallLabels {
+            function_name,
+            positional_arguments,
+            missing_labels,
+        }) = self.selected_call.take()
+        else {
+            return vec![];
+        };
+
+        // We can only change the definition if it lives in this same module.
+        let function = self
+            .module
+            .ast
+            .definitions
+            .iter()
+            .filter_map(|def| match def {
+                ast::Definition::Function(function) => Some(function),
+                _ => None,
+            })
+            .find(|function| function.name.as_ref().map(|(_, name)| name) == Some(function_name));
+        let Some(function) = function else {
+            return vec![];
+        };
+
+        // The arguments passed positionally at the call site fill the
+        // unlabelled arguments in order, so the new labels can only go to the
+        // unlabelled arguments left after those.
+        let mut free_arguments = function
+            .arguments
+            .iter()
+            .filter(|argument| argument.names.get_label().is_none())
+            .skip(positional_arguments)
+            .map(Some)
+            .collect_vec();
+
+        // If there aren't enough unlabelled arguments to hold all the new
+        // labels there's no sensible way to add them.
+        if free_arguments.len() < missing_labels.len() {
+            return vec![];
+        }
+
+        // A label goes to the argument with the same name if there is one,
+        // and the remaining labels are given to the arguments that are still
+        // free in the same order they are used at the call site.
+        let mut unmatched_labels = Vec::with_capacity(missing_labels.len());
+        for label in missing_labels {
+            let same_name = free_arguments.iter_mut().find(|argument| {
+                argument.is_some_and(|argument| argument.names.get_variable_name() == Some(label))
+            });
+            match same_name.and_then(Option::take) {
+                Some(argument) => self
+                    .edits
+                    .insert(argument.location.start, format!("{label} ")),
+                None => unmatched_labels.push(label),
+            }
+        }
+
+        for (argument, label) in free_arguments.into_iter().flatten().zip(unmatched_labels) {
+            self.edits
+                .insert(argument.location.start, format!("{label} "));
+        }
+
+        let mut action = Vec::with_capacity(1);
+        CodeActionBuilder::new("Add missing labels")
+            .kind(CodeActionKind::QUICKFIX)
+            .changes(self.params.text_document.uri.clone(), self.edits.edits)
+            .preferred(true)
+            .push_to(&mut action);
+        action
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for AddMissingLabelsToDefinition<'ast> {
+    fn visit_typed_expr_call(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        fun: &'ast TypedExpr,
+        args: &'ast [TypedCallArg],
+    ) {
+        let call_range = self.edits.src_span_to_lsp_range(*location);
+        if !within(self.params.range, call_range) {
+            return;
+        }
+
+        if let TypedExpr::Var {
+            constructor:
+                type_::ValueConstructor {
+                    variant: type_::ValueConstructorVariant::ModuleFn { name, module, .. },
+                    ..
+                },
+            ..
+        } = fun
+        {
+            let missing_labels = args
+                .iter()
+                .filter_map(|arg| arg.label.as_ref())
+                .filter(|label| {
+                    !fun.field_map()
+                        .is_some_and(|field_map| field_map.fields.contains_key(*label))
+                })
+                .collect_vec();
+
+            self.selected_call = if *module == self.module.name && !missing_labels.is_empty() {
+                Some(SelectedCallLabels {
+                    function_name: name,
+                    positional_arguments: args.iter().filter(|arg| arg.label.is_none()).count(),
+                    missing_labels,
+                })
+            } else {
+                None
+            };
+        }
+
+        // Just like when filling in labels, we only want to take into account
+        // the innermost call containing the current selection.
+        ast::visit::visit_typed_expr_call(self, location, type_, fun, args)
+    }
+}
+
 struct MissingImport {
     location: SrcSpan,
     suggestions: Vec<ImportSuggestion>,
@@ -817,6 +993,7 @@ pub fn code_action_import_module(
     line_numbers: &LineNumbers,
     params: &CodeActionParams,
     error: &Option<Error>,
+    import_placement: ImportPlacement,
     actions: &mut Vec<CodeAction>,
 ) {
     let uri = &params.text_document.uri;
@@ -840,12 +1017,7 @@ pub fn code_action_import_module(
         return;
     }
 
-    let first_import_pos = position_of_first_definition_if_import(module, line_numbers);
-    let first_is_import = first_import_pos.is_some();
-    let import_location = first_import_pos.unwrap_or_default();
-
-    let after_import_newlines =
-        add_newlines_after_import(import_location, first_is_import, line_numbers, &module.code);
+    let import_inserter = ImportInserter::new(module, &module.code, line_numbers, import_placement);
 
     for missing_import in missing_imports {
         let range = src_span_to_lsp_range(missing_import.location, line_numbers);
@@ -859,11 +1031,7 @@ pub fn code_action_import_module(
                 new_text: suggestion.name.to_string(),
             }];
             if let Some(import) = &suggestion.import {
-                edits.push(get_import_edit(
-                    import_location,
-                    import,
-                    &after_import_newlines,
-                ))
+                edits.push(import_inserter.import_edit(import))
             };
 
             let title = if let Some(import) = &suggestion.import {
@@ -2616,134 +2784,215 @@ fn turn_expression_into_use(expr: &TypedExpr) -> Option<CallLocations> {
     })
 }
 
-/// Builder for code action to apply the turn into use expression.
+/// Builder for code action to turn a `case` on a `Bool` into a
+/// `use <- bool.guard(...)` expression. The shorter of the two branches is
+/// returned early and the other one follows the `use`:
 ///
-pub struct ExtractVariable<'a> {
+/// ```gleam
+/// case is_admin {
+///   True -> Ok(Nil)
+///   False -> {
+///     let user = fetch_user()
+///     check(user)
+///   }
+/// }
+/// ```
+///
+/// Becomes:
+///
+/// ```gleam
+/// use <- bool.guard(is_admin, Ok(Nil))
+/// let user = fetch_user()
+/// check(user)
+/// ```
+///
+/// When the `True` branch is the longer one the condition is negated. Unless
+/// the branch returned early is a literal or a variable it's wrapped in a
+/// function passed to `bool.lazy_guard`, so it's still only evaluated when
+/// the condition holds.
+///
+/// The action is only offered when `gleam/bool` can be imported.
+///
+pub struct ConvertToBoolGuard<'a> {
     module: &'a Module,
     params: &'a CodeActionParams,
+    importable_modules: &'a HashMap<EcoString, ModuleInterface>,
     edits: TextEdits<'a>,
-    position: Option<ExtractVariablePosition>,
-    selected_expression: Option<SrcSpan>,
-    statement_before_selected_expression: Option<SrcSpan>,
-    latest_statement: Option<SrcSpan>,
+    import_placement: ImportPlacement,
+    selected_case: Option<BoolCase<'a>>,
 }
 
-#[derive(PartialEq, Eq, Copy, Clone)]
-enum ExtractVariablePosition {
-    InsideCaptureBody,
-    TopLevelStatement,
+/// A `case` expression with a single `Bool` subject and a clause for each of
+/// `True` and `False`, without any guards.
+///
+struct BoolCase<'a> {
+    location: SrcSpan,
+    subject: &'a TypedExpr,
+    when_true: &'a ast::TypedClause,
+    when_false: &'a ast::TypedClause,
 }
 
-impl<'a> ExtractVariable<'a> {
+const BOOL_MODULE: &str = "gleam/bool";
+
+impl<'a> ConvertToBoolGuard<'a> {
     pub fn new(
         module: &'a Module,
         line_numbers: &'a LineNumbers,
         params: &'a CodeActionParams,
+        importable_modules: &'a HashMap<EcoString, ModuleInterface>,
+        import_placement: ImportPlacement,
     ) -> Self {
         Self {
             module,
             params,
+            importable_modules,
             edits: TextEdits::new(line_numbers),
-            position: None,
-            selected_expression: None,
-            latest_statement: None,
-            statement_before_selected_expression: None,
+            import_placement,
+            selected_case: None,
         }
     }
 
     pub fn code_actions(mut self) -> Vec<CodeAction> {
         self.visit_typed_module(&self.module.ast);
 
-        let Some(location) = self.selected_expression else {
+        let Some(BoolCase {
+            location,
+            subject,
+            when_true,
+            when_false,
+        }) = self.selected_case.take()
+        else {
             return vec![];
         };
 
-        if let Some(container_location) = self.statement_before_selected_expression {
-            let nesting = self
-                .edits
-                .src_span_to_lsp_range(container_location)
-                .start
-                .character;
-            let nesting = " ".repeat(nesting as usize);
-            let content = self
-                .module
-                .code
-                .get(location.start as usize..location.end as usize)
-                .expect("selected expression");
-            self.edits.insert(
-                container_location.start,
-                format!("let value = {content}\n{nesting}"),
-            );
+        // The shorter branch is the one returned early by `bool.guard`. If
+        // that's the `False` one the condition has to be negated.
+        let length = |clause: &ast::TypedClause| {
+            let location = clause.then.location();
+            location.end - location.start
+        };
+        let (condition, early, rest) = if length(when_false) < length(when_true) {
+            (self.negated(subject), when_false, when_true)
+        } else {
+            (self.code(subject.location()).into(), when_true, when_false)
+        };
+
+        let function = if is_literal_or_variable(&early.then) {
+            "guard"
+        } else {
+            "lazy_guard"
+        };
+        let function_is_importable = self
+            .importable_modules
+            .get(BOOL_MODULE)
+            .is_some_and(|module| module.values.contains_key(function));
+        if !function_is_importable {
+            return vec![];
         }
-        self.edits.replace(location, "value".into());
+
+        // Everything ends up at the nesting level of the `case`, so the
+        // branches lose the indentation they had inside of it.
+        let case_column = self.edits.src_span_to_lsp_range(location).start.character;
+        let indentation = " ".repeat(case_column as usize);
+        let early_column = self.clause_column(early);
+        let rest_column = self.clause_column(rest);
+        let early_text = dedent(
+            self.code(early.then.location()),
+            early_column.saturating_sub(case_column) as usize,
+        );
+        let early_text = match &early.then {
+            _ if function == "guard" => early_text,
+            TypedExpr::Block { .. } => format!("fn() {early_text}"),
+            _ => format!("fn() {{ {early_text} }}"),
+        };
+        // Only the statements of a block are kept, without its braces.
+        let rest_location = match &rest.then {
+            TypedExpr::Block { statements, .. } => statements
+                .first()
+                .location()
+                .merge(&statements.last().last_location()),
+            then => then.location(),
+        };
+        let rest_text = dedent(
+            self.code(rest_location),
+            (rest_column + 2).saturating_sub(case_column) as usize,
+        );
+
+        let bool_module = Printer::new(&self.module.ast.names).print_module(BOOL_MODULE);
+        self.edits.replace(
+            location,
+            format!(
+                "use <- {bool_module}.{function}({condition}, {early_text})\n{indentation}{rest_text}"
+            ),
+        );
+        maybe_import(
+            &mut self.edits,
+            self.module,
+            BOOL_MODULE,
+            self.import_placement,
+        );
 
         let mut action = Vec::with_capacity(1);
-        CodeActionBuilder::new("Extract variable")
-            .kind(CodeActionKind::REFACTOR_EXTRACT)
+        CodeActionBuilder::new(&format!("Convert to `bool.{function}`"))
+            .kind(CodeActionKind::REFACTOR_REWRITE)
             .changes(self.params.text_document.uri.clone(), self.edits.edits)
             .preferred(false)
             .push_to(&mut action);
         action
     }
-}
 
-impl<'ast> ast::visit::Visit<'ast> for ExtractVariable<'ast> {
-    fn visit_typed_statement(&mut self, stmt: &'ast TypedStatement) {
-        // A capture body is comprised of just a single expression statement
-        // that is inserted by the compiler, we don't really want to put
-        // anything before that; so in this case we avoid tracking it.
-        if self.position != Some(ExtractVariablePosition::InsideCaptureBody) {
-            self.latest_statement = Some(stmt.location());
+    fn select_case(&mut self, statement: &'a TypedStatement) {
+        let ast::Statement::Expression(expression) = statement else {
+            return;
+        };
+        let case_range = self.edits.src_span_to_lsp_range(expression.location());
+        if !within(self.params.range, case_range) {
+            return;
         }
+        // Cases nested in the selected one are visited later and replace it,
+        // so the innermost case around the cursor is the one converted.
+        if let Some(bool_case) = bool_case(expression) {
+            self.selected_case = Some(bool_case);
+        }
+    }
 
-        let previous_position = self.position;
-        self.position = Some(ExtractVariablePosition::TopLevelStatement);
-        ast::visit::visit_typed_statement(self, stmt);
-        self.position = previous_position;
+    fn code(&self, location: SrcSpan) -> &'a str {
+        self.module
+            .code
+            .get(location.start as usize..location.end as usize)
+            .expect("code location")
     }
 
-    fn visit_typed_expr(&mut self, expr: &'ast TypedExpr) {
-        let expr_location = expr.location();
-        let expr_range = self.edits.src_span_to_lsp_range(expr_location);
+    fn clause_column(&self, clause: &ast::TypedClause) -> u32 {
+        self.edits
+            .src_span_to_lsp_range(clause.location)
+            .start
+            .character
+    }
 
-        // If the expression is a top level statement we don't want to extract
-        // it into a variable. It would mean we would turn this:
-        //
-        // ```gleam
-        // pub fn main() {
-        //   let wibble = 1
-        //   //           ^ cursor here
-        // }
-        //
-        // // into:
-        //
-        // pub fn main() {
-        //   let value = 1
-        //   let wibble = value
-        // }
-        // ```
-        //
-        // Not all that useful!
-        //
-        if self.position != Some(ExtractVariablePosition::TopLevelStatement)
-            && within(self.params.range, expr_range)
-        {
-            match expr {
-                // We don't extract variables, they're already good.
-                // And we don't extract module selects by themselves but always
-                // want to consider those as part of a function call.
-                TypedExpr::Var { .. } | TypedExpr::ModuleSelect { .. } => (),
-                _ => {
-                    self.selected_expression = Some(expr_location);
-                    self.statement_before_selected_expression = self.latest_statement;
-                }
-            }
+    /// The code of a `Bool` expression negated. Anything more complex than a
+    /// single value is wrapped in a block, so that the negation applies to
+    /// all of it.
+    fn negated(&self, expression: &TypedExpr) -> EcoString {
+        match expression {
+            TypedExpr::NegateBool { value, .. } => self.code(value.location()).into(),
+            TypedExpr::Var { .. }
+            | TypedExpr::Call { .. }
+            | TypedExpr::ModuleSelect { .. }
+            | TypedExpr::RecordAccess { .. }
+            | TypedExpr::TupleIndex { .. }
+            | TypedExpr::Block { .. } => eco_format!("!{}", self.code(expression.location())),
+            _ => eco_format!("!{{ {} }}", self.code(expression.location())),
         }
+    }
+}
 
-        let previous_position = self.position;
-        self.position = None;
-        ast::visit::visit_typed_expr(self, expr);
-        self.position = previous_position;
+impl<'ast> ast::visit::Visit<'ast> for ConvertToBoolGuard<'ast> {
+    fn visit_typed_function(&mut self, fun: &'ast ast::TypedFunction) {
+        // A `use` takes the rest of the block it's in, so only a `case` that
+        // is the last statement can become one.
+        self.select_case(fun.body.last());
+        ast::visit::visit_typed_function(self, fun)
     }
 
     fn visit_typed_expr_fn(
@@ -2755,40 +3004,169 @@ impl<'ast> ast::visit::Visit<'ast> for ExtractVariable<'ast> {
         body: &'ast [TypedStatement],
         return_annotation: &'ast Option<ast::TypeAst>,
     ) {
-        let previous_position = self.position;
-        self.position = match kind {
-            // If a fn is a capture `int.wibble(1, _)` its body will consist of
-            // just a single expression statement. When visiting we must record
-            // we're inside a capture body.
-            FunctionLiteralKind::Capture => Some(ExtractVariablePosition::InsideCaptureBody),
-            FunctionLiteralKind::Anonymous { .. } | FunctionLiteralKind::Use { .. } => {
-                self.position
-            }
-        };
-        ast::visit::visit_typed_expr_fn(self, location, type_, kind, args, body, return_annotation);
-        self.position = previous_position;
-    }
-
-    // We don't want to offer the action if the cursor is over some invalid
-    // piece of code.
-    fn visit_typed_expr_invalid(&mut self, location: &'ast SrcSpan, _type_: &'ast Arc<Type>) {
-        let invalid_range = self.edits.src_span_to_lsp_range(*location);
-        if within(self.params.range, invalid_range) {
-            self.selected_expression = None;
+        if let Some(last_statement) = body.last() {
+            self.select_case(last_statement);
         }
+        ast::visit::visit_typed_expr_fn(self, location, type_, kind, args, body, return_annotation);
     }
-}
 
-/// Builder for code action to apply the "expand function capture" action.
+    fn visit_typed_expr_block(
+        &mut self,
+        location: &'ast SrcSpan,
+        statements: &'ast [TypedStatement],
+    ) {
+        if let Some(last_statement) = statements.last() {
+            self.select_case(last_statement);
+        }
+        ast::visit::visit_typed_expr_block(self, location, statements);
+    }
+}
+
+fn bool_case(expression: &TypedExpr) -> Option<BoolCase<'_>> {
+    let TypedExpr::Case {
+        location,
+        subjects,
+        clauses,
+        ..
+    } = expression
+    else {
+        return None;
+    };
+    let ([subject], [first, second]) = (subjects.as_slice(), clauses.as_slice()) else {
+        return None;
+    };
+    if !subject.type_().is_bool() {
+        return None;
+    }
+
+    match (bool_clause_value(first)?, bool_clause_value(second)?) {
+        (true, false) => Some(BoolCase {
+            location: *location,
+            subject,
+            when_true: first,
+            when_false: second,
+        }),
+        (false, true) => Some(BoolCase {
+            location: *location,
+            subject,
+            when_true: second,
+            when_false: first,
+        }),
+        (true, true) | (false, false) => None,
+    }
+}
+
+/// Whether an expression is a literal or a variable, so it can be evaluated
+/// even when its value isn't needed.
+fn is_literal_or_variable(expression: &TypedExpr) -> bool {
+    matches!(
+        expression,
+        TypedExpr::Int { .. }
+            | TypedExpr::Float { .. }
+            | TypedExpr::String { .. }
+            | TypedExpr::Var { .. }
+    )
+}
+
+/// The value matched by a clause with a single `True` or `False` pattern and
+/// no guard.
+fn bool_clause_value(clause: &ast::TypedClause) -> Option<bool> {
+    if clause.guard.is_some() || !clause.alternative_patterns.is_empty() {
+        return None;
+    }
+    match clause.pattern.as_slice() {
+        [Pattern::Constructor {
+            name, arguments, ..
+        }] if arguments.is_empty() => match name.as_str() {
+            "True" => Some(true),
+            "False" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Removes up to the given number of spaces from the start of every line of
+/// some code but the first one.
+fn dedent(code: &str, spaces: usize) -> String {
+    code.split('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            if index == 0 {
+                return line;
+            }
+            let indentation = line.len() - line.trim_start_matches(' ').len();
+            line.get(indentation.min(spaces)..).unwrap_or(line)
+        })
+        .join("\n")
+}
+
+/// Adds the given number of spaces to the start of every line of some code but
+/// the first one, leaving empty lines as they are.
+fn indent(code: &str, spaces: usize) -> String {
+    let indentation = " ".repeat(spaces);
+    code.split('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            if index == 0 || line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{indentation}{line}")
+            }
+        })
+        .join("\n")
+}
+
+/// Moves every line of some code but the first one from one column to
+/// another.
+fn reindent(code: &str, from: u32, to: u32) -> String {
+    if to >= from {
+        indent(code, (to - from) as usize)
+    } else {
+        dedent(code, (from - to) as usize)
+    }
+}
+
+/// Builder for code action to turn a `use <- bool.guard(...)` or
+/// `use <- bool.lazy_guard(...)` expression back into a `case` on its
+/// condition, the inverse of `ConvertToBoolGuard`:
 ///
-pub struct ExpandFunctionCapture<'a> {
+/// ```gleam
+/// use <- bool.guard(is_admin, Ok(Nil))
+/// let user = fetch_user()
+/// check(user)
+/// ```
+///
+/// Becomes:
+///
+/// ```gleam
+/// case is_admin {
+///   True -> Ok(Nil)
+///   False -> {
+///     let user = fetch_user()
+///     check(user)
+///   }
+/// }
+/// ```
+///
+pub struct ConvertFromBoolGuard<'a> {
     module: &'a Module,
     params: &'a CodeActionParams,
     edits: TextEdits<'a>,
-    location: Option<(SrcSpan, SrcSpan, VariablesNames)>,
+    selected_guard: Option<BoolGuard<'a>>,
 }
 
-impl<'a> ExpandFunctionCapture<'a> {
+/// A `use` calling `bool.guard` or `bool.lazy_guard`.
+///
+struct BoolGuard<'a> {
+    location: SrcSpan,
+    function: &'a EcoString,
+    condition: &'a TypedExpr,
+    consequence: &'a TypedExpr,
+    rest: &'a [TypedStatement],
+}
+
+impl<'a> ConvertFromBoolGuard<'a> {
     pub fn new(
         module: &'a Module,
         line_numbers: &'a LineNumbers,
@@ -2798,158 +3176,487 @@ impl<'a> ExpandFunctionCapture<'a> {
             module,
             params,
             edits: TextEdits::new(line_numbers),
-            location: None,
+            selected_guard: None,
         }
     }
 
     pub fn code_actions(mut self) -> Vec<CodeAction> {
         self.visit_typed_module(&self.module.ast);
 
-        let Some((function, hole, names)) = self.location else {
+        let Some(BoolGuard {
+            location,
+            function,
+            condition,
+            consequence,
+            rest,
+        }) = self.selected_guard.take()
+        else {
+            return vec![];
+        };
+        let (Some(first), Some(last)) = (rest.first(), rest.last()) else {
             return vec![];
         };
 
-        let name = names.first_available_name("value");
-        self.edits.replace(hole, name.clone().into());
-        self.edits.insert(function.end, " }".into());
-        self.edits.insert(function.start, format!("fn({name}) {{ "));
+        let use_column = self.column(location);
+        let indentation = " ".repeat(use_column as usize);
+        let consequence = self.consequence(function, consequence, use_column);
+        let rest_text = reindent(
+            self.code(first.location().merge(&last.last_location())),
+            use_column,
+            use_column + 4,
+        );
+        self.edits.replace(
+            location,
+            format!(
+                "case {condition} {{
+{indentation}  True -> {consequence}
+{indentation}  False -> {{
+{indentation}    {rest_text}
+{indentation}  }}
+{indentation}}}",
+                condition = self.code(condition.location()),
+            ),
+        );
 
         let mut action = Vec::with_capacity(1);
-        CodeActionBuilder::new("Expand function capture")
+        CodeActionBuilder::new(&format!("Convert from `bool.{function}`"))
             .kind(CodeActionKind::REFACTOR_REWRITE)
             .changes(self.params.text_document.uri.clone(), self.edits.edits)
             .preferred(false)
             .push_to(&mut action);
         action
     }
-}
 
-impl<'ast> ast::visit::Visit<'ast> for ExpandFunctionCapture<'ast> {
-    fn visit_typed_expr_fn(
-        &mut self,
-        location: &'ast SrcSpan,
-        type_: &'ast Arc<Type>,
-        kind: &'ast FunctionLiteralKind,
-        args: &'ast [ast::TypedArg],
-        body: &'ast [TypedStatement],
-        return_annotation: &'ast Option<ast::TypeAst>,
-    ) {
-        let fn_range = self.edits.src_span_to_lsp_range(*location);
-        if within(self.params.range, fn_range) && kind.is_capture() {
-            if let [arg] = args {
-                self.location = Some((
-                    *location,
-                    arg.location,
-                    VariablesNames::from_statements(body),
-                ));
-            }
+    /// The code of the `True` branch of the `case`. The function passed to
+    /// `bool.lazy_guard` is inlined when it's a function literal and called
+    /// otherwise.
+    fn consequence(&self, function: &str, consequence: &TypedExpr, use_column: u32) -> String {
+        let code = self.code(consequence.location());
+        if function == "guard" {
+            return reindent(code, use_column, use_column + 2);
+        }
+        let TypedExpr::Fn { body, .. } = consequence else {
+            return format!("{code}()");
+        };
+        let first = body.first();
+        let last = body.last();
+        let column = self.column(first.location());
+        let statements = self.code(first.location().merge(&last.last_location()));
+        if body.len() == 1 && first.is_expression() {
+            reindent(statements, column, use_column + 2)
+        } else {
+            let indentation = " ".repeat(use_column as usize + 2);
+            let statements = reindent(statements, column, use_column + 4);
+            format!("{{\n{indentation}  {statements}\n{indentation}}}")
         }
+    }
 
-        ast::visit::visit_typed_expr_fn(self, location, type_, kind, args, body, return_annotation)
+    fn code(&self, location: SrcSpan) -> &'a str {
+        self.module
+            .code
+            .get(location.start as usize..location.end as usize)
+            .expect("code location")
     }
-}
 
-struct VariablesNames {
-    names: HashSet<EcoString>,
+    fn column(&self, location: SrcSpan) -> u32 {
+        self.edits.src_span_to_lsp_range(location).start.character
+    }
 }
 
-impl VariablesNames {
-    fn from_statements(statements: &[TypedStatement]) -> Self {
-        let mut variables = Self {
-            names: HashSet::new(),
-        };
-
-        for statement in statements {
-            variables.visit_typed_statement(statement);
+impl<'ast> ast::visit::Visit<'ast> for ConvertFromBoolGuard<'ast> {
+    fn visit_typed_use(&mut self, use_: &'ast TypedUse) {
+        let use_src_span = use_.location.merge(&use_.call.location());
+        let use_range = self.edits.src_span_to_lsp_range(use_src_span);
+        if !within(self.params.range, use_range) {
+            return;
         }
-        variables
-    }
-
-    fn first_available_name(&self, name: &str) -> EcoString {
-        let mut i = 0;
-        loop {
-            let name = if i == 0 {
-                EcoString::from(name)
-            } else {
-                eco_format!("{name}{i}")
-            };
-
-            if !self.names.contains(&name) {
-                return name;
-            }
-            i += 1;
+        // Guards nested in the selected one are visited later and replace it,
+        // so the innermost guard around the cursor is the one converted.
+        if let Some(guard) = bool_guard(use_src_span, use_) {
+            self.selected_guard = Some(guard);
         }
+        self.visit_typed_expr(&use_.call);
     }
 }
 
-impl<'ast> ast::visit::Visit<'ast> for VariablesNames {
-    fn visit_typed_expr_var(
-        &mut self,
-        _location: &'ast SrcSpan,
-        _constructor: &'ast type_::ValueConstructor,
-        name: &'ast EcoString,
-    ) {
-        let _ = self.names.insert(name.clone());
+fn bool_guard(location: SrcSpan, use_: &TypedUse) -> Option<BoolGuard<'_>> {
+    let TypedExpr::Call { fun, args, .. } = use_.call.as_ref() else {
+        return None;
+    };
+    let function = match fun.as_ref() {
+        TypedExpr::ModuleSelect {
+            label, module_name, ..
+        } if module_name == BOOL_MODULE => label,
+        TypedExpr::Var {
+            constructor:
+                type_::ValueConstructor {
+                    variant: type_::ValueConstructorVariant::ModuleFn { name, module, .. },
+                    ..
+                },
+            ..
+        } if module == BOOL_MODULE => name,
+        _ => return None,
+    };
+    if function != "guard" && function != "lazy_guard" {
+        return None;
+    }
+    // The arguments of a typed call are in the order of the parameters, no
+    // matter the labels used.
+    let [condition, consequence, callback] = args.as_slice() else {
+        return None;
+    };
+    let TypedExpr::Fn { body, .. } = &callback.value else {
+        return None;
+    };
+    if !callback.is_use_implicit_callback() {
+        return None;
     }
+    Some(BoolGuard {
+        location,
+        function,
+        condition: &condition.value,
+        consequence: &consequence.value,
+        rest: body,
+    })
 }
 
-/// Builder for code action to apply the "generate dynamic decoder action.
+/// Builder for code action to apply the turn into use expression.
 ///
-pub struct GenerateDynamicDecoder<'a> {
+pub struct ExtractVariable<'a> {
     module: &'a Module,
     params: &'a CodeActionParams,
     edits: TextEdits<'a>,
-    printer: Printer<'a>,
-    actions: &'a mut Vec<CodeAction>,
+    position: Option<ExtractVariablePosition>,
+    selected_expression: Option<SrcSpan>,
+    statement_before_selected_expression: Option<SrcSpan>,
+    latest_statement: Option<SrcSpan>,
 }
 
-const DECODE_MODULE: &str = "gleam/dynamic/decode";
+#[derive(PartialEq, Eq, Copy, Clone)]
+enum ExtractVariablePosition {
+    InsideCaptureBody,
+    TopLevelStatement,
+}
 
-impl<'a> GenerateDynamicDecoder<'a> {
+impl<'a> ExtractVariable<'a> {
     pub fn new(
         module: &'a Module,
         line_numbers: &'a LineNumbers,
         params: &'a CodeActionParams,
-        actions: &'a mut Vec<CodeAction>,
     ) -> Self {
-        let printer = Printer::new(&module.ast.names);
         Self {
             module,
             params,
             edits: TextEdits::new(line_numbers),
-            printer,
-            actions,
+            position: None,
+            selected_expression: None,
+            latest_statement: None,
+            statement_before_selected_expression: None,
         }
     }
 
-    pub fn code_actions(&mut self) {
+    pub fn code_actions(mut self) -> Vec<CodeAction> {
         self.visit_typed_module(&self.module.ast);
+
+        let Some(location) = self.selected_expression else {
+            return vec![];
+        };
+
+        if let Some(container_location) = self.statement_before_selected_expression {
+            let nesting = self
+                .edits
+                .src_span_to_lsp_range(container_location)
+                .start
+                .character;
+            let nesting = " ".repeat(nesting as usize);
+            let content = self
+                .module
+                .code
+                .get(location.start as usize..location.end as usize)
+                .expect("selected expression");
+            self.edits.insert(
+                container_location.start,
+                format!("let value = {content}\n{nesting}"),
+            );
+        }
+        self.edits.replace(location, "value".into());
+
+        let mut action = Vec::with_capacity(1);
+        CodeActionBuilder::new("Extract variable")
+            .kind(CodeActionKind::REFACTOR_EXTRACT)
+            .changes(self.params.text_document.uri.clone(), self.edits.edits)
+            .preferred(false)
+            .push_to(&mut action);
+        action
     }
 }
 
-impl<'ast> ast::visit::Visit<'ast> for GenerateDynamicDecoder<'ast> {
-    fn visit_typed_custom_type(&mut self, custom_type: &'ast ast::TypedCustomType) {
-        let range = self.edits.src_span_to_lsp_range(custom_type.location);
-        if !overlaps(self.params.range, range) {
-            return;
+impl<'ast> ast::visit::Visit<'ast> for ExtractVariable<'ast> {
+    fn visit_typed_statement(&mut self, stmt: &'ast TypedStatement) {
+        // A capture body is comprised of just a single expression statement
+        // that is inserted by the compiler, we don't really want to put
+        // anything before that; so in this case we avoid tracking it.
+        if self.position != Some(ExtractVariablePosition::InsideCaptureBody) {
+            self.latest_statement = Some(stmt.location());
         }
 
-        // For now, we only generate dynamic decoders for types with one variant.
-        let constructor = match custom_type.constructors.as_slice() {
-            [constructor] => constructor,
-            _ => return,
-        };
+        let previous_position = self.position;
+        self.position = Some(ExtractVariablePosition::TopLevelStatement);
+        ast::visit::visit_typed_statement(self, stmt);
+        self.position = previous_position;
+    }
 
-        let name = eco_format!("{}_decoder", custom_type.name.to_snake_case());
+    fn visit_typed_expr(&mut self, expr: &'ast TypedExpr) {
+        let expr_location = expr.location();
+        let expr_range = self.edits.src_span_to_lsp_range(expr_location);
 
-        let Some(fields): Option<Vec<_>> = constructor
-            .arguments
-            .iter()
-            .map(|argument| {
-                Some(RecordField {
-                    label: RecordLabel::Labeled(
-                        argument.label.as_ref().map(|(_, name)| name.as_str())?,
-                    ),
+        // If the expression is a top level statement we don't want to extract
+        // it into a variable. It would mean we would turn this:
+        //
+        // ```gleam
+        // pub fn main() {
+        //   let wibble = 1
+        //   //           ^ cursor here
+        // }
+        //
+        // // into:
+        //
+        // pub fn main() {
+        //   let value = 1
+        //   let wibble = value
+        // }
+        // ```
+        //
+        // Not all that useful!
+        //
+        if self.position != Some(ExtractVariablePosition::TopLevelStatement)
+            && within(self.params.range, expr_range)
+        {
+            match expr {
+                // We don't extract variables, they're already good.
+                // And we don't extract module selects by themselves but always
+                // want to consider those as part of a function call.
+                TypedExpr::Var { .. } | TypedExpr::ModuleSelect { .. } => (),
+                _ => {
+                    self.selected_expression = Some(expr_location);
+                    self.statement_before_selected_expression = self.latest_statement;
+                }
+            }
+        }
+
+        let previous_position = self.position;
+        self.position = None;
+        ast::visit::visit_typed_expr(self, expr);
+        self.position = previous_position;
+    }
+
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        args: &'ast [ast::TypedArg],
+        body: &'ast [TypedStatement],
+        return_annotation: &'ast Option<ast::TypeAst>,
+    ) {
+        let previous_position = self.position;
+        self.position = match kind {
+            // If a fn is a capture `int.wibble(1, _)` its body will consist of
+            // just a single expression statement. When visiting we must record
+            // we're inside a capture body.
+            FunctionLiteralKind::Capture => Some(ExtractVariablePosition::InsideCaptureBody),
+            FunctionLiteralKind::Anonymous { .. } | FunctionLiteralKind::Use { .. } => {
+                self.position
+            }
+        };
+        ast::visit::visit_typed_expr_fn(self, location, type_, kind, args, body, return_annotation);
+        self.position = previous_position;
+    }
+
+    // We don't want to offer the action if the cursor is over some invalid
+    // piece of code.
+    fn visit_typed_expr_invalid(&mut self, location: &'ast SrcSpan, _type_: &'ast Arc<Type>) {
+        let invalid_range = self.edits.src_span_to_lsp_range(*location);
+        if within(self.params.range, invalid_range) {
+            self.selected_expression = None;
+        }
+    }
+}
+
+/// Builder for code action to apply the "expand function capture" action.
+///
+pub struct ExpandFunctionCapture<'a> {
+    module: &'a Module,
+    params: &'a CodeActionParams,
+    edits: TextEdits<'a>,
+    location: Option<(SrcSpan, SrcSpan, VariablesNames)>,
+}
+
+impl<'a> ExpandFunctionCapture<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+    ) -> Self {
+        Self {
+            module,
+            params,
+            edits: TextEdits::new(line_numbers),
+            location: None,
+        }
+    }
+
+    pub fn code_actions(mut self) -> Vec<CodeAction> {
+        self.visit_typed_module(&self.module.ast);
+
+        let Some((function, hole, names)) = self.location else {
+            return vec![];
+        };
+
+        let name = names.first_available_name("value");
+        self.edits.replace(hole, name.clone().into());
+        self.edits.insert(function.end, " }".into());
+        self.edits.insert(function.start, format!("fn({name}) {{ "));
+
+        let mut action = Vec::with_capacity(1);
+        CodeActionBuilder::new("Expand function capture")
+            .kind(CodeActionKind::REFACTOR_REWRITE)
+            .changes(self.params.text_document.uri.clone(), self.edits.edits)
+            .preferred(false)
+            .push_to(&mut action);
+        action
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for ExpandFunctionCapture<'ast> {
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        args: &'ast [ast::TypedArg],
+        body: &'ast [TypedStatement],
+        return_annotation: &'ast Option<ast::TypeAst>,
+    ) {
+        let fn_range = self.edits.src_span_to_lsp_range(*location);
+        if within(self.params.range, fn_range) && kind.is_capture() {
+            if let [arg] = args {
+                self.location = Some((
+                    *location,
+                    arg.location,
+                    VariablesNames::from_statements(body),
+                ));
+            }
+        }
+
+        ast::visit::visit_typed_expr_fn(self, location, type_, kind, args, body, return_annotation)
+    }
+}
+
+struct VariablesNames {
+    names: HashSet<EcoString>,
+}
+
+impl VariablesNames {
+    fn from_statements(statements: &[TypedStatement]) -> Self {
+        let mut variables = Self {
+            names: HashSet::new(),
+        };
+
+        for statement in statements {
+            variables.visit_typed_statement(statement);
+        }
+        variables
+    }
+
+    fn first_available_name(&self, name: &str) -> EcoString {
+        let mut i = 0;
+        loop {
+            let name = if i == 0 {
+                EcoString::from(name)
+            } else {
+                eco_format!("{name}{i}")
+            };
+
+            if !self.names.contains(&name) {
+                return name;
+            }
+            i += 1;
+        }
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for VariablesNames {
+    fn visit_typed_expr_var(
+        &mut self,
+        _location: &'ast SrcSpan,
+        _constructor: &'ast type_::ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        let _ = self.names.insert(name.clone());
+    }
+}
+
+/// Builder for code action to apply the "generate dynamic decoder action.
+///
+pub struct GenerateDynamicDecoder<'a> {
+    module: &'a Module,
+    params: &'a CodeActionParams,
+    edits: TextEdits<'a>,
+    printer: Printer<'a>,
+    import_placement: ImportPlacement,
+    actions: &'a mut Vec<CodeAction>,
+}
+
+const DECODE_MODULE: &str = "gleam/dynamic/decode";
+
+impl<'a> GenerateDynamicDecoder<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+        import_placement: ImportPlacement,
+        actions: &'a mut Vec<CodeAction>,
+    ) -> Self {
+        let printer = Printer::new(&module.ast.names);
+        Self {
+            module,
+            params,
+            edits: TextEdits::new(line_numbers),
+            printer,
+            import_placement,
+            actions,
+        }
+    }
+
+    pub fn code_actions(&mut self) {
+        self.visit_typed_module(&self.module.ast);
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for GenerateDynamicDecoder<'ast> {
+    fn visit_typed_custom_type(&mut self, custom_type: &'ast ast::TypedCustomType) {
+        let range = self.edits.src_span_to_lsp_range(custom_type.location);
+        if !overlaps(self.params.range, range) {
+            return;
+        }
+
+        // For now, we only generate dynamic decoders for types with one variant.
+        let constructor = match custom_type.constructors.as_slice() {
+            [constructor] => constructor,
+            _ => return,
+        };
+
+        let name = eco_format!("{}_decoder", custom_type.name.to_snake_case());
+
+        let Some(fields): Option<Vec<_>> = constructor
+            .arguments
+            .iter()
+            .map(|argument| {
+                Some(RecordField {
+                    label: RecordLabel::Labeled(
+                        argument.label.as_ref().map(|(_, name)| name.as_str())?,
+                    ),
                     type_: &argument.type_,
                 })
             })
@@ -3005,7 +3712,12 @@ fn {name}() -> {decoder_type}({type_name}{parameters}) {{
         );
 
         self.edits.insert(custom_type.end_position, function);
-        maybe_import(&mut self.edits, self.module, DECODE_MODULE);
+        maybe_import(
+            &mut self.edits,
+            self.module,
+            DECODE_MODULE,
+            self.import_placement,
+        );
 
         CodeActionBuilder::new("Generate dynamic decoder")
             .kind(CodeActionKind::REFACTOR)
@@ -3018,26 +3730,19 @@ fn {name}() -> {decoder_type}({type_name}{parameters}) {{
     }
 }
 
-fn maybe_import(edits: &mut TextEdits<'_>, module: &Module, module_name: &str) {
+fn maybe_import(
+    edits: &mut TextEdits<'_>,
+    module: &Module,
+    module_name: &str,
+    import_placement: ImportPlacement,
+) {
     if module.ast.names.is_imported(module_name) {
         return;
     }
 
-    let first_import_pos = position_of_first_definition_if_import(module, edits.line_numbers);
-    let first_is_import = first_import_pos.is_some();
-    let import_location = first_import_pos.unwrap_or_default();
-    let after_import_newlines = add_newlines_after_import(
-        import_location,
-        first_is_import,
-        edits.line_numbers,
-        &module.code,
-    );
-
-    edits.edits.push(get_import_edit(
-        import_location,
-        module_name,
-        &after_import_newlines,
-    ));
+    let import_inserter =
+        ImportInserter::new(module, &module.code, edits.line_numbers, import_placement);
+    edits.edits.push(import_inserter.import_edit(module_name));
 }
 
 struct DecoderPrinter<'a> {
@@ -3180,3 +3885,1035 @@ impl<'a> DecoderPrinter<'a> {
         )
     }
 }
+
+/// Builder for code action to pattern match on a variable of a custom type,
+/// adding a `case` expression with a clause for each of the type's
+/// constructors.
+///
+/// The action is offered on function arguments, in which case the expression
+/// is added at the start of the function's body, and on variables assigned
+/// with `let`, in which case it is added after the assignment.
+///
+pub struct PatternMatchOnValue<'a> {
+    module: &'a Module,
+    params: &'a CodeActionParams,
+    importable_modules: &'a HashMap<EcoString, ModuleInterface>,
+    target: Target,
+    edits: TextEdits<'a>,
+    selected_value: Option<SelectedValue<'a>>,
+}
+
+struct SelectedValue<'a> {
+    name: &'a EcoString,
+    type_: Arc<Type>,
+    insertion: CaseInsertion,
+}
+
+enum CaseInsertion {
+    /// The expression goes on the line before this statement.
+    Before(SrcSpan),
+    /// The expression goes on the line after this statement.
+    After(SrcSpan),
+}
+
+impl<'a> PatternMatchOnValue<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+        importable_modules: &'a HashMap<EcoString, ModuleInterface>,
+        target: Target,
+    ) -> Self {
+        Self {
+            module,
+            params,
+            importable_modules,
+            target,
+            edits: TextEdits::new(line_numbers),
+            selected_value: None,
+        }
+    }
+
+    pub fn code_actions(mut self) -> Vec<CodeAction> {
+        self.visit_typed_module(&self.module.ast);
+
+        let Some(SelectedValue {
+            name,
+            type_,
+            insertion,
+        }) = self.selected_value.take()
+        else {
+            return vec![];
+        };
+
+        let Some(patterns) =
+            constructor_patterns(self.module, self.importable_modules, self.target, type_)
+        else {
+            return vec![];
+        };
+
+        let statement_location = match insertion {
+            CaseInsertion::Before(location) | CaseInsertion::After(location) => location,
+        };
+        let indent_size = self
+            .edits
+            .src_span_to_lsp_range(statement_location)
+            .start
+            .character as usize;
+        let clauses = patterns
+            .iter()
+            .map(|pattern| CaseClause {
+                pattern,
+                expression: "todo",
+            })
+            .collect_vec();
+        let mut case_expression = String::new();
+        print_case_expression(indent_size, name, clauses, &mut case_expression);
+
+        let indent = " ".repeat(indent_size);
+        match insertion {
+            CaseInsertion::Before(location) => self
+                .edits
+                .insert(location.start, format!("{case_expression}\n{indent}")),
+            CaseInsertion::After(location) => self
+                .edits
+                .insert(location.end, format!("\n{indent}{case_expression}")),
+        }
+
+        let mut actions = vec![];
+        CodeActionBuilder::new("Pattern match on variable")
+            .kind(CodeActionKind::REFACTOR_REWRITE)
+            .changes(self.params.text_document.uri.clone(), self.edits.edits)
+            .preferred(false)
+            .push_to(&mut actions);
+        actions
+    }
+
+    fn is_selected(&self, location: SrcSpan) -> bool {
+        let range = self.edits.src_span_to_lsp_range(location);
+        overlaps(self.params.range, range)
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for PatternMatchOnValue<'ast> {
+    fn visit_typed_function(&mut self, fun: &'ast ast::TypedFunction) {
+        for argument in &fun.arguments {
+            let (ArgNames::Named { name, location }
+            | ArgNames::NamedLabelled {
+                name,
+                name_location: location,
+                ..
+            }) = &argument.names
+            else {
+                continue;
+            };
+            if self.is_selected(*location) {
+                self.selected_value = Some(SelectedValue {
+                    name,
+                    type_: argument.type_.clone(),
+                    insertion: CaseInsertion::Before(fun.body.first().location()),
+                });
+            }
+        }
+
+        ast::visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_assignment(&mut self, assignment: &'ast TypedAssignment) {
+        if let (
+            AssignmentKind::Let,
+            Pattern::Variable {
+                location,
+                name,
+                type_,
+                ..
+            },
+        ) = (&assignment.kind, &assignment.pattern)
+        {
+            if self.is_selected(*location) {
+                self.selected_value = Some(SelectedValue {
+                    name,
+                    type_: type_.clone(),
+                    insertion: CaseInsertion::After(assignment.location),
+                });
+            }
+        }
+
+        ast::visit::visit_typed_assignment(self, assignment);
+    }
+}
+
+/// A pattern for each of the constructors of a custom type, in the order
+/// they are defined in, with all their fields discarded. These are the
+/// patterns the exhaustiveness checker reports as missing from a `case`
+/// expression without any clauses.
+///
+/// Returns `None` if the type is not a custom type whose constructors
+/// can be used in the module.
+///
+fn constructor_patterns(
+    module: &Module,
+    importable_modules: &HashMap<EcoString, ModuleInterface>,
+    target: Target,
+    type_: Arc<Type>,
+) -> Option<Vec<EcoString>> {
+    let (type_module, type_name, _) = type_.named_type_information()?;
+    let module = &module.ast;
+
+    let mut environment = Environment::new(
+        UniqueIdGenerator::new(),
+        module.type_info.package.clone(),
+        None,
+        module.name.clone(),
+        target,
+        importable_modules,
+        TargetSupport::NotEnforced,
+    );
+    // The environment only knows about the types of the prelude, so we
+    // add the ones of the current module, and the names used to refer to
+    // types and values from other modules.
+    environment
+        .module_types_constructors
+        .extend(module.type_info.types_value_constructors.clone());
+    environment.names = module.names.clone();
+
+    let variants = &environment
+        .get_constructors_for_type(&type_module, &type_name)
+        .ok()?
+        .variants;
+    if variants.is_empty() {
+        return None;
+    }
+
+    // The constructors of opaque types from other modules can't be used.
+    if type_module != module.name {
+        let interface = importable_modules.get(&type_module)?;
+        let all_public = variants.iter().all(|variant| {
+            interface
+                .values
+                .get(&variant.name)
+                .is_some_and(|value| value.publicity.is_importable())
+        });
+        if !all_public {
+            return None;
+        }
+    }
+
+    let mut compiler = Compiler::new(&environment, Arena::new());
+    let _ = compiler.subject_variable(type_);
+    let patterns = compiler.compile(vec![]).missing_patterns(&environment);
+
+    // The missing patterns are sorted alphabetically, so we sort them back
+    // in the order the constructors are defined in.
+    let constructor_index = |pattern: &EcoString| {
+        let constructor = pattern.split('(').next().unwrap_or_default();
+        let name = constructor.rsplit('.').next().unwrap_or_default();
+        variants
+            .iter()
+            .position(|variant| variant.name.as_str() == name)
+    };
+    Some(
+        patterns
+            .into_iter()
+            .sorted_by_key(constructor_index)
+            .collect(),
+    )
+}
+
+/// Builder for code action to generate a function that is called but isn't
+/// defined anywhere.
+///
+/// If the call refers to a function in the current module, the new function
+/// is added after the one the call is in. If it refers to a function from
+/// another module of the project, a public function is added at the end of
+/// that module instead.
+///
+/// The types of the function's arguments and of its return value are the ones
+/// inferred from the call.
+///
+pub struct GenerateFunction<'a> {
+    module: &'a Module,
+    params: &'a CodeActionParams,
+    modules: &'a std::collections::HashMap<EcoString, Module>,
+    edits: TextEdits<'a>,
+    unknown_functions: Vec<UnknownFunction<'a>>,
+    last_visited_function_end: u32,
+    function_to_generate: Option<FunctionToGenerate<'a>>,
+}
+
+/// A name used as a function in a call, which couldn't be found.
+struct UnknownFunction<'a> {
+    location: SrcSpan,
+    name: &'a EcoString,
+    /// The module the function is expected to be in, if the call is
+    /// qualified.
+    module: Option<&'a EcoString>,
+}
+
+struct FunctionToGenerate<'a> {
+    name: &'a EcoString,
+    module: Option<&'a EcoString>,
+    arguments: &'a [TypedCallArg],
+    return_type: Arc<Type>,
+    /// The end of the top level function the call is in.
+    function_end: u32,
+}
+
+impl<'a> GenerateFunction<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+        error: &'a Option<Error>,
+        modules: &'a std::collections::HashMap<EcoString, Module>,
+    ) -> Self {
+        let mut this = Self {
+            module,
+            params,
+            modules,
+            edits: TextEdits::new(line_numbers),
+            unknown_functions: vec![],
+            last_visited_function_end: 0,
+            function_to_generate: None,
+        };
+
+        let Some(Error::Type { errors, .. }) = error else {
+            return this;
+        };
+        this.unknown_functions = errors
+            .iter()
+            .filter_map(|error| match error {
+                type_::Error::UnknownVariable { location, name, .. } => Some(UnknownFunction {
+                    location: *location,
+                    name,
+                    module: None,
+                }),
+                type_::Error::UnknownModuleValue {
+                    location,
+                    name,
+                    module_name,
+                    ..
+                } => Some(UnknownFunction {
+                    location: *location,
+                    name,
+                    module: Some(module_name),
+                }),
+                _ => None,
+            })
+            // Constructors can't be generated as functions.
+            .filter(|unknown| {
+                unknown
+                    .name
+                    .starts_with(|char: char| char.is_ascii_lowercase())
+            })
+            .filter(|unknown| {
+                let range = this.edits.src_span_to_lsp_range(unknown.location);
+                overlaps(this.params.range, range)
+            })
+            .collect_vec();
+        this
+    }
+
+    pub fn code_actions(mut self) -> Vec<CodeAction> {
+        if self.unknown_functions.is_empty() {
+            return vec![];
+        }
+        self.visit_typed_module(&self.module.ast);
+
+        let Some(FunctionToGenerate {
+            name,
+            module,
+            arguments,
+            return_type,
+            function_end,
+        }) = self.function_to_generate.take()
+        else {
+            return vec![];
+        };
+
+        let (uri, edit) = match module {
+            None => {
+                let mut printer = Printer::new(&self.module.ast.names);
+                let function = print_function(name, arguments, &return_type, &mut printer);
+                self.edits
+                    .insert(function_end, format!("\n\nfn {function}"));
+                (self.params.text_document.uri.clone(), self.edits.edits)
+            }
+            Some(module_name) => {
+                // We can only add functions to the modules of the project, not
+                // to the ones of its dependencies.
+                let Some(module) = self.modules.get(module_name) else {
+                    return vec![];
+                };
+                let mut printer = Printer::new(&module.ast.names);
+                let function = print_function(name, arguments, &return_type, &mut printer);
+                let separator = if module.code.is_empty() {
+                    ""
+                } else if module.code.ends_with('\n') {
+                    "\n"
+                } else {
+                    "\n\n"
+                };
+                let end = module.code.len() as u32;
+                let line_numbers = LineNumbers::new(&module.code);
+                let edit = TextEdit {
+                    range: src_span_to_lsp_range(SrcSpan::new(end, end), &line_numbers),
+                    new_text: format!("{separator}pub fn {function}\n"),
+                };
+                (path_to_uri(module.input_path.clone()), vec![edit])
+            }
+        };
+
+        let mut actions = vec![];
+        CodeActionBuilder::new("Generate function")
+            .kind(CodeActionKind::QUICKFIX)
+            .changes(uri, edit)
+            .preferred(true)
+            .push_to(&mut actions);
+        actions
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for GenerateFunction<'ast> {
+    fn visit_typed_function(&mut self, fun: &'ast ast::TypedFunction) {
+        self.last_visited_function_end = fun.end_position;
+        ast::visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_expr_call(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        fun: &'ast TypedExpr,
+        args: &'ast [TypedCallArg],
+    ) {
+        if let TypedExpr::Invalid {
+            location: fun_location,
+            ..
+        } = fun
+        {
+            let unknown_function = self
+                .unknown_functions
+                .iter()
+                .find(|unknown| unknown.location == *fun_location)
+                .map(|unknown| (unknown.name, unknown.module));
+            if let Some((name, module)) = unknown_function {
+                self.function_to_generate = Some(FunctionToGenerate {
+                    name,
+                    module,
+                    arguments: args,
+                    return_type: type_.clone(),
+                    function_end: self.last_visited_function_end,
+                });
+            }
+        }
+
+        ast::visit::visit_typed_expr_call(self, location, type_, fun, args);
+    }
+}
+
+/// Prints the head of a function taking the given arguments, with a `todo`
+/// as its body.
+///
+/// Arguments passed as variables are named after them, labelled ones after
+/// their label, and the other ones are numbered.
+///
+fn print_function(
+    name: &EcoString,
+    arguments: &[TypedCallArg],
+    return_type: &Type,
+    printer: &mut Printer<'_>,
+) -> String {
+    let mut used_names = HashSet::new();
+    let arguments = arguments
+        .iter()
+        .enumerate()
+        .map(|(index, argument)| {
+            let type_ = printer.print_type(&argument.value.type_());
+            if let Some(label) = &argument.label {
+                let _ = used_names.insert(label.clone());
+                return format!("{label} {label}: {type_}");
+            }
+
+            let name = match &argument.value {
+                TypedExpr::Var {
+                    name, constructor, ..
+                } if constructor.is_local_variable() && !used_names.contains(name) => name.clone(),
+                _ => eco_format!("arg{}", index + 1),
+            };
+            let _ = used_names.insert(name.clone());
+            format!("{name}: {type_}")
+        })
+        .join(", ");
+    let return_type = printer.print_type(return_type);
+    format!("{name}({arguments}) -> {return_type} {{\n  todo\n}}")
+}
+
+/// Builder for code actions that surround some code with a new structure,
+/// which comes in handy while exploring:
+///
+/// - The selected statements can be wrapped in a block:
+///
+///   ```gleam
+///   pub fn main() {
+///     let wibble = 1
+///     wibble + 1
+///   }
+///
+///   // Becomes:
+///
+///   pub fn main() {
+///     {
+///       let wibble = 1
+///       wibble + 1
+///     }
+///   }
+///   ```
+///
+/// - The selected expression can be wrapped in a `case` expression matching
+///   on it, with a clause for each of its type's constructors:
+///
+///   ```gleam
+///   let wibble = wobble()
+///
+///   // Becomes:
+///
+///   let wibble = case wobble() {
+///     _ -> todo
+///   }
+///   ```
+///
+/// - The body of a function whose head is selected can be guarded with a
+///   `todo`, so that the rest of it is never run:
+///
+///   ```gleam
+///   pub fn main() {
+///     todo
+///     wibble()
+///   }
+///   ```
+///
+pub struct SurroundWith<'a> {
+    module: &'a Module,
+    line_numbers: &'a LineNumbers,
+    params: &'a CodeActionParams,
+    importable_modules: &'a HashMap<EcoString, ModuleInterface>,
+    target: Target,
+    selected_statements: Option<&'a [TypedStatement]>,
+    selected_expression: Option<&'a TypedExpr>,
+    selected_function_body: Option<&'a [TypedStatement]>,
+    /// The location of every string literal in the module, whose lines must
+    /// not be indented when wrapping them in a block.
+    string_locations: Vec<SrcSpan>,
+}
+
+impl<'a> SurroundWith<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+        importable_modules: &'a HashMap<EcoString, ModuleInterface>,
+        target: Target,
+    ) -> Self {
+        Self {
+            module,
+            line_numbers,
+            params,
+            importable_modules,
+            target,
+            selected_statements: None,
+            selected_expression: None,
+            selected_function_body: None,
+            string_locations: vec![],
+        }
+    }
+
+    pub fn code_actions(mut self) -> Vec<CodeAction> {
+        self.visit_typed_module(&self.module.ast);
+
+        let mut actions = vec![];
+        if let Some(statements) = self.selected_statements {
+            self.wrap_in_block(statements, &mut actions);
+        }
+        if let Some(expression) = self.selected_expression {
+            self.wrap_in_case(expression, &mut actions);
+        }
+        if let Some(body) = self.selected_function_body {
+            self.guard_with_todo(body, &mut actions);
+        }
+        actions
+    }
+
+    fn wrap_in_block(&self, statements: &[TypedStatement], actions: &mut Vec<CodeAction>) {
+        let (Some(first), Some(last)) = (statements.first(), statements.last()) else {
+            return;
+        };
+        let location = SrcSpan::new(first.location().start, last.location().end);
+        let indent = " ".repeat(self.indentation(location.start));
+
+        let mut block = String::from("{\n");
+        block.push_str(&indent);
+        let mut line_start = location.start;
+        for (index, line) in self.code(location).split('\n').enumerate() {
+            if index > 0 {
+                block.push('\n');
+            }
+            // The lines of a multi-line string are part of its value, so they
+            // are left untouched.
+            let in_string = self
+                .string_locations
+                .iter()
+                .any(|string| string.start < line_start && line_start < string.end);
+            if !line.is_empty() && !in_string {
+                block.push_str("  ");
+            }
+            block.push_str(line);
+            line_start += line.len() as u32 + 1;
+        }
+        block.push('\n');
+        block.push_str(&indent);
+        block.push('}');
+
+        let mut edits = TextEdits::new(self.line_numbers);
+        edits.replace(location, block);
+        CodeActionBuilder::new("Wrap in block")
+            .kind(CodeActionKind::REFACTOR_REWRITE)
+            .changes(self.params.text_document.uri.clone(), edits.edits)
+            .preferred(false)
+            .push_to(actions);
+    }
+
+    fn wrap_in_case(&self, expression: &TypedExpr, actions: &mut Vec<CodeAction>) {
+        let location = expression.location();
+        let patterns = constructor_patterns(
+            self.module,
+            self.importable_modules,
+            self.target,
+            expression.type_(),
+        )
+        .unwrap_or_else(|| vec!["_".into()]);
+        let clauses = patterns
+            .iter()
+            .map(|pattern| CaseClause {
+                pattern,
+                expression: "todo",
+            })
+            .collect_vec();
+        let mut case_expression = String::new();
+        print_case_expression(
+            self.indentation(location.start),
+            self.code(location),
+            clauses,
+            &mut case_expression,
+        );
+
+        let mut edits = TextEdits::new(self.line_numbers);
+        edits.replace(location, case_expression);
+        CodeActionBuilder::new("Wrap in case expression")
+            .kind(CodeActionKind::REFACTOR_REWRITE)
+            .changes(self.params.text_document.uri.clone(), edits.edits)
+            .preferred(false)
+            .push_to(actions);
+    }
+
+    fn guard_with_todo(&self, body: &[TypedStatement], actions: &mut Vec<CodeAction>) {
+        let Some(first) = body.first() else {
+            return;
+        };
+        let location = first.location();
+        let indent = " ".repeat(self.indentation(location.start));
+
+        let mut edits = TextEdits::new(self.line_numbers);
+        edits.insert(location.start, format!("todo\n{indent}"));
+        CodeActionBuilder::new("Guard function body with todo")
+            .kind(CodeActionKind::REFACTOR_REWRITE)
+            .changes(self.params.text_document.uri.clone(), edits.edits)
+            .preferred(false)
+            .push_to(actions);
+    }
+
+    fn code(&self, location: SrcSpan) -> &'a str {
+        self.module
+            .code
+            .get(location.start as usize..location.end as usize)
+            .expect("selected code")
+    }
+
+    /// The number of spaces the line containing the given byte index starts
+    /// with.
+    fn indentation(&self, byte_index: u32) -> usize {
+        let before = self
+            .module
+            .code
+            .get(..byte_index as usize)
+            .unwrap_or_default();
+        let line = before.rsplit('\n').next().unwrap_or_default();
+        line.chars().take_while(|char| *char == ' ').count()
+    }
+
+    fn is_selected(&self, location: SrcSpan) -> bool {
+        let range = src_span_to_lsp_range(location, self.line_numbers);
+        overlaps(self.params.range, range)
+    }
+
+    /// Records the statements of a function's body or of a block that are
+    /// selected, if the whole selection is within them. As blocks are visited
+    /// after the statements they are part of, the innermost ones win.
+    fn select_statements(&mut self, statements: &'a [TypedStatement]) {
+        let (Some(first), Some(last)) = (statements.first(), statements.last()) else {
+            return;
+        };
+        let location = SrcSpan::new(first.location().start, last.location().end);
+        let range = src_span_to_lsp_range(location, self.line_numbers);
+        if !within(self.params.range, range) {
+            return;
+        }
+
+        let Some(start) = statements
+            .iter()
+            .position(|statement| self.is_selected(statement.location()))
+        else {
+            return;
+        };
+        let end = statements
+            .iter()
+            .rposition(|statement| self.is_selected(statement.location()))
+            .unwrap_or(start);
+        let Some(selected) = statements.get(start..=end) else {
+            return;
+        };
+
+        // The statements after a `use` are part of its callback, so wrapping
+        // it in a block would change what they mean.
+        if selected.iter().any(|statement| statement.is_use()) {
+            self.selected_statements = None;
+        } else {
+            self.selected_statements = Some(selected);
+        }
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for SurroundWith<'ast> {
+    fn visit_typed_function(&mut self, fun: &'ast ast::TypedFunction) {
+        // External functions without a body are given a placeholder one,
+        // which starts where the function itself does.
+        if fun.body.first().location().start <= fun.location.start {
+            return;
+        }
+
+        if self.is_selected(fun.location) {
+            let starts_with_todo = matches!(
+                fun.body.first(),
+                ast::Statement::Expression(TypedExpr::Todo { .. })
+            );
+            self.selected_function_body = (!starts_with_todo).then_some(fun.body.as_slice());
+        }
+
+        self.select_statements(&fun.body);
+        ast::visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_expr(&mut self, expr: &'ast TypedExpr) {
+        let location = expr.location();
+        let range = src_span_to_lsp_range(location, self.line_numbers);
+        if within(self.params.range, range) && can_be_case_subject(expr) {
+            self.selected_expression = Some(expr);
+        }
+
+        ast::visit::visit_typed_expr(self, expr);
+    }
+
+    fn visit_typed_expr_block(
+        &mut self,
+        location: &'ast SrcSpan,
+        statements: &'ast [TypedStatement],
+    ) {
+        self.select_statements(statements);
+        ast::visit::visit_typed_expr_block(self, location, statements);
+    }
+
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        args: &'ast [ast::TypedArg],
+        body: &'ast [TypedStatement],
+        return_annotation: &'ast Option<ast::TypeAst>,
+    ) {
+        self.select_statements(body);
+        ast::visit::visit_typed_expr_fn(self, location, type_, kind, args, body, return_annotation);
+    }
+
+    fn visit_typed_expr_string(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        value: &'ast EcoString,
+    ) {
+        self.string_locations.push(*location);
+        ast::visit::visit_typed_expr_string(self, location, type_, value);
+    }
+
+    // We don't want to offer the actions if the cursor is over some invalid
+    // piece of code.
+    fn visit_typed_expr_invalid(&mut self, location: &'ast SrcSpan, _type_: &'ast Arc<Type>) {
+        let invalid_range = src_span_to_lsp_range(*location, self.line_numbers);
+        if within(self.params.range, invalid_range) {
+            self.selected_expression = None;
+            self.selected_statements = None;
+        }
+    }
+}
+
+/// Whether an expression can be wrapped in a `case` expression matching on
+/// it. Functions can't be pattern matched on, and the variables and calls
+/// introduced by the compiler for pipes, captures and `use` don't have any
+/// code of their own to wrap.
+fn can_be_case_subject(expr: &TypedExpr) -> bool {
+    match expr {
+        TypedExpr::Var { name, .. }
+            if name == PIPE_VARIABLE
+                || name == CAPTURE_VARIABLE
+                || name == USE_ASSIGNMENT_VARIABLE =>
+        {
+            false
+        }
+        TypedExpr::Call { args, .. } if args.iter().any(|arg| arg.is_use_implicit_callback()) => {
+            false
+        }
+        TypedExpr::ModuleSelect { .. }
+        | TypedExpr::Todo { .. }
+        | TypedExpr::Panic { .. }
+        | TypedExpr::Invalid { .. } => false,
+        _ => !matches!(*expr.type_(), Type::Fn { .. }),
+    }
+}
+
+/// Builder for code action to move a call to a function that can't run on
+/// Nix, such as one only implemented in Erlang, behind a new function of the
+/// module. The new function delegates to the called one on the targets it
+/// supports through externals, while its body, used on Nix, is a `todo` left
+/// to port:
+///
+/// ```gleam
+/// import gleam/erlang/process
+///
+/// pub fn main() {
+///   process.sleep(100)
+/// }
+/// ```
+///
+/// Becomes:
+///
+/// ```gleam
+/// import gleam/erlang/process
+///
+/// pub fn main() {
+///   process_sleep(100)
+/// }
+///
+/// @external(erlang, "gleam@erlang@process", "sleep")
+/// @external(javascript, "../gleam_erlang/gleam/erlang/process.mjs", "sleep")
+/// fn process_sleep(arg1: Int) -> Nil {
+///   todo
+/// }
+/// ```
+///
+/// If the module already has a function with that name, for example because
+/// another call was wrapped before, the call is changed to use it instead.
+///
+pub struct WrapUnsupportedCall<'a> {
+    module: &'a Module,
+    params: &'a CodeActionParams,
+    importable_modules: &'a HashMap<EcoString, ModuleInterface>,
+    /// The package the module belongs to.
+    package: &'a EcoString,
+    edits: TextEdits<'a>,
+    unsupported_values: Vec<UnsupportedValue<'a>>,
+    last_visited_function_end: u32,
+    call_to_wrap: Option<CallToWrap<'a>>,
+}
+
+/// A value used where it has no Nix implementation.
+#[derive(Clone, Copy)]
+struct UnsupportedValue<'a> {
+    location: SrcSpan,
+    module: &'a EcoString,
+    name: &'a EcoString,
+    package: Option<&'a EcoString>,
+    supported_targets: &'a [Target],
+}
+
+struct CallToWrap<'a> {
+    value: UnsupportedValue<'a>,
+    /// The function being called, including the module it is qualified with.
+    function_location: SrcSpan,
+    arguments: &'a [TypedCallArg],
+    /// The end of the top level function the call is in.
+    function_end: u32,
+}
+
+impl<'a> WrapUnsupportedCall<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+        error: &'a Option<Error>,
+        importable_modules: &'a HashMap<EcoString, ModuleInterface>,
+        package: &'a EcoString,
+    ) -> Self {
+        let mut this = Self {
+            module,
+            params,
+            importable_modules,
+            package,
+            edits: TextEdits::new(line_numbers),
+            unsupported_values: vec![],
+            last_visited_function_end: 0,
+            call_to_wrap: None,
+        };
+
+        let Some(Error::Type { errors, .. }) = error else {
+            return this;
+        };
+        this.unsupported_values = errors
+            .iter()
+            .filter_map(|error| match error {
+                type_::Error::UnsupportedExpressionTarget {
+                    location,
+                    target: Target::Nix,
+                    module,
+                    name,
+                    package,
+                    supported_targets,
+                    ..
+                } => Some(UnsupportedValue {
+                    location: *location,
+                    module,
+                    name,
+                    package: package.as_ref(),
+                    supported_targets,
+                }),
+                _ => None,
+            })
+            // Functions of the module itself can be given a Nix external
+            // directly instead.
+            .filter(|value| *value.module != this.module.name)
+            .filter(|value| {
+                let range = this.edits.src_span_to_lsp_range(value.location);
+                overlaps(this.params.range, range)
+            })
+            .collect_vec();
+        this
+    }
+
+    pub fn code_actions(mut self) -> Vec<CodeAction> {
+        if self.unsupported_values.is_empty() {
+            return vec![];
+        }
+        self.visit_typed_module(&self.module.ast);
+
+        let Some(CallToWrap {
+            value,
+            function_location,
+            arguments,
+            function_end,
+        }) = self.call_to_wrap.take()
+        else {
+            return vec![];
+        };
+
+        // Unqualified imports may rename the function, in which case the name
+        // it's defined with isn't known.
+        let Some(function) = self
+            .importable_modules
+            .get(value.module)
+            .and_then(|module| module.values.get(value.name))
+        else {
+            return vec![];
+        };
+        let Some((_, return_type)) = function.type_.fn_types() else {
+            return vec![];
+        };
+
+        let module_name = value.module.rsplit('/').next().unwrap_or(value.module);
+        let wrapper_name = eco_format!("{module_name}_{}", value.name);
+        self.edits
+            .replace(function_location, wrapper_name.to_string());
+
+        let already_defined = self.module.ast.definitions.iter().any(|definition| {
+            matches!(
+                definition,
+                ast::Definition::Function(function)
+                    if function.name.as_ref().map(|(_, name)| name) == Some(&wrapper_name)
+            )
+        });
+        if !already_defined {
+            let mut externals = String::new();
+            for target in value.supported_targets {
+                let module = match target {
+                    Target::Erlang => value.module.replace("/", "@"),
+                    Target::JavaScript => {
+                        let to_build_directory = "../".repeat(self.module.name.split('/').count());
+                        let package = value.package.unwrap_or(self.package);
+                        format!("{to_build_directory}{package}/{}.mjs", value.module).into()
+                    }
+                    Target::Nix => continue,
+                };
+                externals.push_str(&format!(
+                    "@external({target}, \"{module}\", \"{}\")\n",
+                    value.name
+                ));
+            }
+            let mut printer = Printer::new(&self.module.ast.names);
+            let function = print_function(&wrapper_name, arguments, &return_type, &mut printer);
+            self.edits
+                .insert(function_end, format!("\n\n{externals}fn {function}"));
+        }
+
+        let mut actions = vec![];
+        CodeActionBuilder::new("Wrap in function with Nix todo")
+            .kind(CodeActionKind::QUICKFIX)
+            .changes(self.params.text_document.uri.clone(), self.edits.edits)
+            .push_to(&mut actions);
+        actions
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for WrapUnsupportedCall<'ast> {
+    fn visit_typed_function(&mut self, fun: &'ast ast::TypedFunction) {
+        self.last_visited_function_end = fun.end_position;
+        ast::visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_expr_call(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        fun: &'ast TypedExpr,
+        args: &'ast [TypedCallArg],
+    ) {
+        if let TypedExpr::Invalid {
+            location: fun_location,
+            ..
+        } = fun
+        {
+            let value = self
+                .unsupported_values
+                .iter()
+                .find(|value| value.location == *fun_location);
+            // The call starts with the module the function is qualified with.
+            if let Some(value) = value.filter(|_| location.start <= fun_location.start) {
+                self.call_to_wrap = Some(CallToWrap {
+                    value: *value,
+                    function_location: SrcSpan::new(location.start, fun_location.end),
+                    arguments: args,
+                    function_end: self.last_visited_function_end,
+                });
+            }
+        }
+
+        ast::visit::visit_typed_expr_call(self, location, type_, fun, args);
+    }
+}
@@ -11,7 +11,7 @@ use crate::{
     line_numbers::LineNumbers,
     manifest::Manifest,
     paths::ProjectPaths,
-    type_::ModuleInterface,
+    type_::{ModuleInterface, NIX_MODULE_NAME},
     warning::VectorWarningEmitterIO,
     Error, Result, Warning,
 };
@@ -25,12 +25,16 @@ use camino::Utf8PathBuf;
 ///
 #[derive(Debug)]
 pub struct LspProjectCompiler<IO> {
-    pub project_compiler: ProjectCompiler<IO>,
+    pub project_compiler: ProjectCompiler<'static, IO>,
 
     /// Information on compiled modules.
     pub modules: HashMap<EcoString, Module>,
     pub sources: HashMap<EcoString, ModuleSourceInformation>,
 
+    /// How many of the root package's modules were type checked by the last
+    /// compilation, the others having been reused from the cache.
+    pub root_modules_compiled: usize,
+
     /// The storage for the warning emitter.
     pub warnings: Rc<VectorWarningEmitterIO>,
 
@@ -72,6 +76,7 @@ where
             compile: build::Compile::All,
             root_target_support: TargetSupport::Enforced,
             no_print_progress: false,
+            changed_files: None,
         };
         let mut project_compiler = ProjectCompiler::new(
             config,
@@ -93,6 +98,7 @@ where
             project_compiler,
             modules: HashMap::new(),
             sources: HashMap::new(),
+            root_modules_compiled: 0,
         })
     }
 
@@ -131,7 +137,7 @@ where
             // It we already have the source for an importable module it means
             // that we already have all the information we are adding here, so
             // we can skip past to to avoid doing extra work for no gain.
-            if self.sources.contains_key(name) || name == "gleam" {
+            if self.sources.contains_key(name) || name == "gleam" || name == NIX_MODULE_NAME {
                 continue;
             }
             // Create the source information
@@ -158,6 +164,7 @@ where
             Outcome::PartialFailure(package, error) => (package.modules, Some(error)),
             Outcome::TotalFailure(error) => (vec![], Some(error)),
         };
+        self.root_modules_compiled = modules.len();
 
         // Record the compiled dependency modules
         let mut compiled_modules = compiled_dependencies
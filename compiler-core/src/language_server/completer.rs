@@ -1,5 +1,6 @@
 use std::{collections::HashMap, sync::Arc};
 
+use camino::Utf8PathBuf;
 use ecow::EcoString;
 use itertools::Itertools;
 use lsp_types::{
@@ -14,7 +15,7 @@ use crate::{
         self, Arg, CallArg, Definition, Function, FunctionLiteralKind, Pattern, Publicity,
         TypedExpr,
     },
-    build::Module,
+    build::{nix_externals, Module},
     io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter},
     line_numbers::LineNumbers,
     type_::{
@@ -27,10 +28,7 @@ use crate::{
 
 use super::{
     compiler::LspProjectCompiler,
-    edits::{
-        add_newlines_after_import, get_import, get_import_edit,
-        position_of_first_definition_if_import, Newlines,
-    },
+    edits::{get_import, ImportInserter, ImportPlacement},
     files::FileSystemProxy,
     DownloadDependencies, MakeLocker,
 };
@@ -92,6 +90,8 @@ pub struct Completer<'a, IO> {
     /// This is not necessarily the same as src_line_numbers if the module
     /// is in a non-compiling state
     pub module_line_numbers: LineNumbers,
+    /// Where the imports added by completions are placed
+    import_placement: ImportPlacement,
 }
 
 impl<'a, IO> Completer<'a, IO>
@@ -110,6 +110,7 @@ where
         params: &'a TextDocumentPositionParams,
         compiler: &'a LspProjectCompiler<FileSystemProxy<IO>>,
         module: &'a Module,
+        import_placement: ImportPlacement,
     ) -> Self {
         Completer {
             src,
@@ -118,6 +119,7 @@ where
             compiler,
             module,
             module_line_numbers: LineNumbers::new(&module.code),
+            import_placement,
         }
     }
 
@@ -389,6 +391,115 @@ where
             .collect()
     }
 
+    /// Checks if the cursor is within one of the strings of a Nix external
+    /// attribute and provides completions if it is. The first string is
+    /// completed with the paths of the package's Nix files, and the second
+    /// with the helpers of the prelude when the first string refers to it.
+    pub fn nix_external_completions(&'a self) -> Option<Vec<CompletionItem>> {
+        let start_of_line = self
+            .src_line_numbers
+            .byte_index(self.cursor_position.line, 0);
+        let cursor = self
+            .src_line_numbers
+            .byte_index(self.cursor_position.line, self.cursor_position.character);
+        let before = self.src.get(start_of_line as usize..cursor as usize)?;
+        let after = self.src.get(cursor as usize..)?;
+
+        let arguments = before
+            .trim_start()
+            .strip_prefix("@external")?
+            .trim_start()
+            .strip_prefix('(')?
+            .trim_start()
+            .strip_prefix("nix")?;
+
+        // The rest of the string being edited, up to its closing quote if any.
+        let rest = match after.find(['"', '\n']).map(|index| after.split_at(index)) {
+            Some((rest, end)) if end.starts_with('"') => rest,
+            _ => "",
+        };
+        let range = |typed: &str| Range {
+            start: Position::new(
+                self.cursor_position.line,
+                self.cursor_position.character - typed.encode_utf16().count() as u32,
+            ),
+            end: Position::new(
+                self.cursor_position.line,
+                self.cursor_position.character + rest.encode_utf16().count() as u32,
+            ),
+        };
+
+        // An even number of quotes before the cursor means that it is inside
+        // one of the strings.
+        match arguments.split('"').collect_vec().as_slice() {
+            [_, typed] => Some(self.nix_external_path_completions(range(typed))),
+            [_, path, _, typed] => Some(self.nix_external_function_completions(path, range(typed))),
+            _ => None,
+        }
+    }
+
+    fn nix_external_path_completions(&'a self, range: Range) -> Vec<CompletionItem> {
+        let module_name = &self.module.name;
+        let source_root = nix_externals::source_root(module_name, &self.module.input_path);
+        let package_root = source_root.parent().unwrap_or(source_root.as_path());
+        let io = &self.compiler.project_compiler.io;
+
+        let mut paths = vec![Utf8PathBuf::from(nix_externals::PRELUDE)];
+        for directory in ["src", "test"] {
+            let directory = package_root.join(directory);
+            if !io.is_directory(&directory) {
+                continue;
+            }
+            let files = crate::io::nix_source_files(io, &directory)
+                .filter_map(|path| Some(path.strip_prefix(&directory).ok()?.to_path_buf()));
+            paths.extend(files);
+        }
+
+        paths
+            .into_iter()
+            .map(|path| nix_externals::external_path(module_name, &path))
+            .sorted()
+            .dedup()
+            .map(|path| CompletionItem {
+                label: path.clone(),
+                kind: Some(CompletionItemKind::FILE),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: path,
+                })),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn nix_external_function_completions(
+        &'a self,
+        path: &str,
+        range: Range,
+    ) -> Vec<CompletionItem> {
+        let refers_to_prelude = nix_externals::resolve(&self.module.name, path)
+            .is_some_and(|path| path == nix_externals::PRELUDE);
+        if !refers_to_prelude {
+            return vec![];
+        }
+
+        crate::nix::prelude_exports()
+            .map(|name| CompletionItem {
+                label: name.into(),
+                kind: Some(if name.starts_with(char::is_uppercase) {
+                    CompletionItemKind::CONSTRUCTOR
+                } else {
+                    CompletionItemKind::FUNCTION
+                }),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: name.into(),
+                })),
+                ..Default::default()
+            })
+            .collect()
+    }
+
     // NOTE: completion_types and completion_values are really similar
     // but just different enough that an abstraction would
     // be really hard to understand or use a lot of trait magic.
@@ -484,16 +595,11 @@ where
         }
 
         // Importable modules
-        let first_import_pos =
-            position_of_first_definition_if_import(self.module, &self.src_line_numbers);
-        let first_is_import = first_import_pos.is_some();
-        let import_location = first_import_pos.unwrap_or_default();
-
-        let after_import_newlines = add_newlines_after_import(
-            import_location,
-            first_is_import,
-            &self.src_line_numbers,
+        let import_inserter = ImportInserter::new(
+            self.module,
             self.src,
+            &self.src_line_numbers,
+            self.import_placement,
         );
         for (module_full_name, module) in self.completable_modules_for_import() {
             // Do not try to import the prelude.
@@ -528,12 +634,7 @@ where
                     TypeCompletionForm::Default,
                     CompletionKind::ImportableModule,
                 );
-                add_import_to_completion(
-                    &mut completion,
-                    import_location,
-                    module_full_name,
-                    &after_import_newlines,
-                );
+                add_import_to_completion(&mut completion, &import_inserter, module_full_name);
                 completions.push(completion);
             }
         }
@@ -675,15 +776,11 @@ where
         }
 
         // Importable modules
-        let first_import_pos =
-            position_of_first_definition_if_import(self.module, &self.src_line_numbers);
-        let first_is_import = first_import_pos.is_some();
-        let import_location = first_import_pos.unwrap_or_default();
-        let after_import_newlines = add_newlines_after_import(
-            import_location,
-            first_is_import,
-            &self.src_line_numbers,
+        let import_inserter = ImportInserter::new(
+            self.module,
             self.src,
+            &self.src_line_numbers,
+            self.import_placement,
         );
         for (module_full_name, module) in self.completable_modules_for_import() {
             // Do not try to import the prelude.
@@ -718,12 +815,7 @@ where
                     CompletionKind::ImportableModule,
                 );
 
-                add_import_to_completion(
-                    &mut completion,
-                    import_location,
-                    module_full_name,
-                    &after_import_newlines,
-                );
+                add_import_to_completion(&mut completion, &import_inserter, module_full_name);
                 completions.push(completion);
             }
         }
@@ -848,15 +940,10 @@ where
 
 fn add_import_to_completion(
     item: &mut CompletionItem,
-    import_location: Position,
+    import_inserter: &ImportInserter<'_>,
     module_full_name: &EcoString,
-    insert_newlines: &Newlines,
 ) {
-    item.additional_text_edits = Some(vec![get_import_edit(
-        import_location,
-        module_full_name,
-        insert_newlines,
-    )]);
+    item.additional_text_edits = Some(vec![import_inserter.import_edit(module_full_name)]);
 }
 
 fn type_completion(
@@ -881,7 +968,7 @@ fn type_completion(
     CompletionItem {
         label: label.clone(),
         kind,
-        detail: Some("Type".into()),
+        detail: Some(completion_detail("Type".into(), type_.since.as_deref())),
         sort_text: Some(sort_text(priority, &label)),
         text_edit: Some(CompletionTextEdit::Edit(TextEdit {
             range: insert_range,
@@ -929,7 +1016,7 @@ fn value_completion(
     CompletionItem {
         label: label.clone(),
         kind,
-        detail: Some(type_),
+        detail: Some(completion_detail(type_, value.since.as_deref())),
         label_details: Some(CompletionItemLabelDetails {
             detail: None,
             description: Some(module_name.into()),
@@ -944,6 +1031,15 @@ fn value_completion(
     }
 }
 
+/// The detail of a completion item, followed by the version the completed
+/// definition has been available since when it has a `@since` attribute.
+fn completion_detail(detail: String, since: Option<&str>) -> String {
+    match since {
+        Some(since) => format!("{detail} (since {since})"),
+        None => detail,
+    }
+}
+
 fn local_value_completion(
     module_name: &str,
     name: &str,
@@ -0,0 +1,101 @@
+use std::sync::{Arc, RwLock};
+
+use serde::Deserialize;
+use serde_json::Value as Json;
+
+use super::edits::ImportPlacement;
+
+/// The configuration of the language server, shared by every project's
+/// engine so that changes made by the client are seen by all of them.
+pub type SharedConfig = Arc<RwLock<Configuration>>;
+
+/// The settings the client can give the language server, either as the
+/// initialisation options or through the `workspace/didChangeConfiguration`
+/// notification.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Configuration {
+    /// Where the imports added by code actions and completions are placed.
+    pub import_placement: ImportPlacement,
+    /// Which inlay hints are shown.
+    pub inlay_hints: InlayHintsConfig,
+}
+
+/// The kinds of inlay hints the client can enable. All are disabled by
+/// default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InlayHintsConfig {
+    /// The inferred types of function parameters without annotations.
+    pub parameter_types: bool,
+    /// The inferred return types of functions without annotations.
+    pub return_types: bool,
+    /// The labels of arguments passed without them at call sites.
+    pub labels: bool,
+}
+
+impl Configuration {
+    /// Reads the settings sent by the client, which may be nested under a
+    /// `glistix` key. Returns `None` if they are missing or invalid.
+    pub fn from_json(settings: &Json) -> Option<Self> {
+        let settings = settings.get("glistix").unwrap_or(settings);
+        Self::deserialize(settings).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_settings() {
+        let settings = serde_json::json!({ "glistix": { "importPlacement": "alphabetical" } });
+        assert_eq!(
+            Configuration::from_json(&settings),
+            Some(Configuration {
+                import_placement: ImportPlacement::Alphabetical,
+                ..Configuration::default()
+            })
+        );
+    }
+
+    #[test]
+    fn top_level_settings() {
+        let settings = serde_json::json!({ "importPlacement": "afterImports" });
+        assert_eq!(
+            Configuration::from_json(&settings),
+            Some(Configuration {
+                import_placement: ImportPlacement::AfterImports,
+                ..Configuration::default()
+            })
+        );
+    }
+
+    #[test]
+    fn missing_settings_use_defaults() {
+        let settings = serde_json::json!({});
+        assert_eq!(
+            Configuration::from_json(&settings),
+            Some(Configuration::default())
+        );
+    }
+
+    #[test]
+    fn inlay_hints_settings() {
+        let settings = serde_json::json!({ "inlayHints": { "labels": true } });
+        assert_eq!(
+            Configuration::from_json(&settings).map(|config| config.inlay_hints),
+            Some(InlayHintsConfig {
+                labels: true,
+                ..InlayHintsConfig::default()
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_settings() {
+        let settings = serde_json::json!({ "importPlacement": "sideways" });
+        assert_eq!(Configuration::from_json(&settings), None);
+        assert_eq!(Configuration::from_json(&Json::Null), None);
+    }
+}
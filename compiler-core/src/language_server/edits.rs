@@ -1,5 +1,7 @@
 use ecow::EcoString;
+use itertools::Itertools;
 use lsp_types::{Position, Range, TextEdit};
+use serde::Deserialize;
 
 use crate::{
     ast::{Definition, Import, TypedDefinition},
@@ -9,19 +11,19 @@ use crate::{
 
 use super::src_span_to_lsp_range;
 
-// Gets the position of the import statement if it's the first definition in the module.
-pub fn position_of_first_definition_if_import(
-    module: &Module,
-    line_numbers: &LineNumbers,
-) -> Option<Position> {
-    // As "self.module.ast.definitions"  could be sorted, let's find the actual first definition by position.
-    let first_definition = module
-        .ast
-        .definitions
-        .iter()
-        .min_by(|a, b| a.location().start.cmp(&b.location().start));
-    let import = first_definition.and_then(get_import);
-    import.map(|import| src_span_to_lsp_range(import.location, line_numbers).start)
+/// Where the imports added by code actions and completions are placed in a
+/// module.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportPlacement {
+    /// Above the module's existing imports.
+    #[default]
+    Top,
+    /// Below the module's last import.
+    AfterImports,
+    /// Among the module's imports, before the first one whose module name
+    /// comes after the imported module's name alphabetically.
+    Alphabetical,
 }
 
 pub fn get_import(statement: &TypedDefinition) -> Option<&Import<EcoString>> {
@@ -31,48 +33,109 @@ pub fn get_import(statement: &TypedDefinition) -> Option<&Import<EcoString>> {
     }
 }
 
-pub enum Newlines {
-    Single,
-    Double,
+/// Builds the edits adding new imports to a module, placing them according to
+/// an `ImportPlacement`.
+///
+/// Modules without any imports always get the new import at their top,
+/// whatever the placement is.
+pub struct ImportInserter<'a> {
+    /// The module's imports, in the order they appear in the source.
+    imports: Vec<&'a Import<EcoString>>,
+    /// Whether the module starts with an import, rather than some other
+    /// definition.
+    starts_with_import: bool,
+    line_numbers: &'a LineNumbers,
+    src: &'a str,
+    placement: ImportPlacement,
 }
 
-// Returns how many newlines should be added after an import statement. By default `Newlines::Single`,
-// but if there's not any import statement, it returns `Newlines::Double`.
-//
-// * ``import_location`` - The position of the first import statement in the source code.
-pub fn add_newlines_after_import(
-    import_location: Position,
-    has_imports: bool,
-    line_numbers: &LineNumbers,
-    src: &str,
-) -> Newlines {
-    let import_start_cursor =
-        line_numbers.byte_index(import_location.line, import_location.character);
-    let is_new_line = src
-        .chars()
-        .nth(import_start_cursor as usize)
-        .unwrap_or_default()
-        == '\n';
-    match !has_imports && !is_new_line {
-        true => Newlines::Double,
-        false => Newlines::Single,
+impl<'a> ImportInserter<'a> {
+    pub fn new(
+        module: &'a Module,
+        src: &'a str,
+        line_numbers: &'a LineNumbers,
+        placement: ImportPlacement,
+    ) -> Self {
+        // As the module's definitions could be sorted, we order them by
+        // position to find the first one.
+        let first_definition = module
+            .ast
+            .definitions
+            .iter()
+            .min_by_key(|definition| definition.location().start);
+        let imports = module
+            .ast
+            .definitions
+            .iter()
+            .filter_map(get_import)
+            .sorted_by_key(|import| import.location.start)
+            .collect_vec();
+
+        Self {
+            imports,
+            starts_with_import: first_definition.and_then(get_import).is_some(),
+            line_numbers,
+            src,
+            placement,
+        }
+    }
+
+    /// The edit adding an import of the given module.
+    pub fn import_edit(&self, module_name: &str) -> TextEdit {
+        let import = ["import ", module_name].concat();
+        match self.placement {
+            ImportPlacement::Top => self.insert_at_top(import),
+            ImportPlacement::AfterImports => self.insert_after_imports(import),
+            ImportPlacement::Alphabetical => match self
+                .imports
+                .iter()
+                .find(|existing| existing.module.as_str() > module_name)
+            {
+                Some(next) => self.insert_before(next, import),
+                None => self.insert_after_imports(import),
+            },
+        }
+    }
+
+    fn insert_at_top(&self, import: String) -> TextEdit {
+        match self.imports.first() {
+            Some(first) if self.starts_with_import => self.insert_before(first, import),
+            // If there are no imports at the top of the module we separate the
+            // new one from what follows with an empty line, unless there
+            // already is one.
+            _ => {
+                let newlines = if self.src.starts_with('\n') {
+                    "\n"
+                } else {
+                    "\n\n"
+                };
+                insert(Position::default(), import + newlines)
+            }
+        }
+    }
+
+    fn insert_after_imports(&self, import: String) -> TextEdit {
+        match self.imports.last() {
+            Some(last) => {
+                let position = src_span_to_lsp_range(last.location, self.line_numbers).end;
+                insert(position, ["\n", import.as_str()].concat())
+            }
+            None => self.insert_at_top(import),
+        }
+    }
+
+    fn insert_before(&self, existing: &Import<EcoString>, import: String) -> TextEdit {
+        let position = src_span_to_lsp_range(existing.location, self.line_numbers).start;
+        insert(position, import + "\n")
     }
 }
 
-pub fn get_import_edit(
-    import_location: Position,
-    module_full_name: &str,
-    insert_newlines: &Newlines,
-) -> TextEdit {
-    let new_lines = match insert_newlines {
-        Newlines::Single => "\n",
-        Newlines::Double => "\n\n",
-    };
+fn insert(position: Position, new_text: String) -> TextEdit {
     TextEdit {
         range: Range {
-            start: import_location,
-            end: import_location,
+            start: position,
+            end: position,
         },
-        new_text: ["import ", module_full_name, new_lines].concat(),
+        new_text,
     }
 }
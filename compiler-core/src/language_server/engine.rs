@@ -1,21 +1,37 @@
 use crate::{
-    analyse::name::correct_name_case,
+    analyse::{name::correct_name_case, TargetSupport},
     ast::{
-        CustomType, Definition, ModuleConstant, SrcSpan, TypedArg, TypedExpr, TypedFunction,
-        TypedModule, TypedPattern,
+        CustomType, Definition, DefinitionLocation, ModuleConstant, SrcSpan, TypedArg, TypedExpr,
+        TypedFunction, TypedModule, TypedPattern,
     },
-    build::{type_constructor_from_modules, Located, Module, UnqualifiedImport},
+    build::{
+        type_constructor_from_modules, LabelledArgument, Located, Module, Target, UnqualifiedImport,
+    },
+    codegen::TypeScriptDeclarations,
     config::PackageConfig,
+    erlang,
     io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter},
+    javascript,
     language_server::{
-        compiler::LspProjectCompiler, files::FileSystemProxy, progress::ProgressReporter,
+        compiler::LspProjectCompiler,
+        configuration::{Configuration, SharedConfig},
+        extensions::{
+            SearchByTypeParams, SearchByTypeResult, ShowCompiledParams, ShowCompiledResult,
+        },
+        files::FileSystemProxy,
+        inlay_hints,
+        progress::ProgressReporter,
+        semantic_tokens,
+        statistics::SharedStatistics,
     },
     line_numbers::LineNumbers,
+    nix,
     paths::ProjectPaths,
     type_::{
-        self, printer::Printer, Deprecation, ModuleInterface, Type, TypeConstructor,
-        ValueConstructorVariant,
+        self, expression::Implementations, printer::Printer, Deprecation, ModuleInterface, Type,
+        TypeConstructor, ValueConstructorVariant,
     },
+    type_search::{self, TypeQuery},
     Error, Result, Warning,
 };
 use camino::Utf8PathBuf;
@@ -23,18 +39,20 @@ use ecow::EcoString;
 use itertools::Itertools;
 use lsp::CodeAction;
 use lsp_types::{
-    self as lsp, DocumentSymbol, Hover, HoverContents, MarkedString, Position, Range,
-    SignatureHelp, SymbolKind, SymbolTag, TextEdit, Url,
+    self as lsp, DocumentSymbol, Hover, HoverContents, InlayHint, MarkedString, Position, Range,
+    SignatureHelp, SymbolInformation, SymbolKind, SymbolTag, TextEdit, Url,
 };
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use super::{
     code_action::{
         code_action_add_missing_patterns, code_action_convert_qualified_constructor_to_unqualified,
         code_action_convert_unqualified_constructor_to_qualified, code_action_import_module,
-        code_action_inexhaustive_let_to_case, AddAnnotations, CodeActionBuilder, DesugarUse,
+        code_action_inexhaustive_let_to_case, AddAnnotations, AddMissingLabelsToDefinition,
+        CodeActionBuilder, ConvertFromBoolGuard, ConvertToBoolGuard, DesugarUse,
         ExpandFunctionCapture, ExtractVariable, FillInMissingLabelledArgs, GenerateDynamicDecoder,
-        LabelShorthandSyntax, LetAssertToCase, RedundantTupleInCaseSubject, TurnIntoUse,
+        GenerateFunction, LabelShorthandSyntax, LetAssertToCase, PatternMatchOnValue,
+        RedundantTupleInCaseSubject, SurroundWith, TurnIntoUse, WrapUnsupportedCall,
     },
     completer::Completer,
     signature_help, src_span_to_lsp_range, DownloadDependencies, MakeLocker,
@@ -76,6 +94,12 @@ pub struct LanguageServerEngine<IO, Reporter> {
     /// Used to know if to show the "View on HexDocs" link
     /// when hovering on an imported value
     hex_deps: std::collections::HashSet<EcoString>,
+
+    /// The configuration given by the client, shared with the other engines.
+    user_config: SharedConfig,
+
+    /// The statistics of the session, shared with the other engines.
+    statistics: SharedStatistics,
 }
 
 impl<'a, IO, Reporter> LanguageServerEngine<IO, Reporter>
@@ -96,6 +120,8 @@ where
         progress_reporter: Reporter,
         io: FileSystemProxy<IO>,
         paths: ProjectPaths,
+        user_config: SharedConfig,
+        statistics: SharedStatistics,
     ) -> Result<Self> {
         let locker = io.inner().make_locker(&paths, config.target)?;
 
@@ -132,9 +158,19 @@ where
             paths,
             error: None,
             hex_deps,
+            user_config,
+            statistics,
         })
     }
 
+    /// The current configuration given by the client.
+    fn user_config(&self) -> Configuration {
+        *self
+            .user_config
+            .read()
+            .expect("language server configuration lock")
+    }
+
     pub fn compile_please(&mut self) -> Response<()> {
         self.respond(Self::compile)
     }
@@ -144,7 +180,9 @@ where
         self.compiled_since_last_feedback = true;
 
         self.progress_reporter.compilation_started();
+        let started = Instant::now();
         let outcome = self.compiler.compile();
+        let duration = started.elapsed();
         self.progress_reporter.compilation_finished();
 
         let result = outcome
@@ -153,6 +191,13 @@ where
             // Return the error, if present
             .into_result();
 
+        let compiled = self.compiler.root_modules_compiled;
+        let cached = self.compiler.modules.len().saturating_sub(compiled);
+        self.statistics
+            .lock()
+            .expect("language server statistics lock")
+            .record_compile(duration, result.is_ok(), compiled, cached);
+
         self.error = match &result {
             Ok(_) => None,
             Err(error) => Some(error.clone()),
@@ -178,11 +223,24 @@ where
                 None => return Ok(None),
             };
 
-            let location = match node
-                .definition_location(this.compiler.project_compiler.get_importable_modules())
-            {
-                Some(location) => location,
-                None => return Ok(None),
+            let location = match &node {
+                // Labels lead to the declaration of their parameter when the
+                // function is part of the project, or to the function itself.
+                Located::FunctionLabel(argument) | Located::RecordLabel(argument) => {
+                    let span = this
+                        .labelled_parameter(argument)
+                        .map_or(argument.definition, |(span, _)| span);
+                    DefinitionLocation {
+                        module: Some(argument.module.as_str()),
+                        span,
+                    }
+                }
+                _ => match node
+                    .definition_location(this.compiler.project_compiler.get_importable_modules())
+                {
+                    Some(location) => location,
+                    None => return Ok(None),
+                },
             };
 
             let (uri, line_numbers) = match location.module {
@@ -214,7 +272,8 @@ where
                 None => return Ok(None),
             };
 
-            let completer = Completer::new(&src, &params, &this.compiler, module);
+            let import_placement = this.user_config().import_placement;
+            let completer = Completer::new(&src, &params, &this.compiler, module, import_placement);
             let byte_index = completer
                 .module_line_numbers
                 .byte_index(params.position.line, params.position.character);
@@ -231,6 +290,12 @@ where
                 return value;
             }
 
+            // The strings of a Nix external attribute are completed with the
+            // package's Nix files and the prelude's helpers.
+            if let Some(completions) = completer.nix_external_completions() {
+                return Ok(Some(completions));
+            }
+
             let Some(found) = module.find_node(byte_index) else {
                 return Ok(None);
             };
@@ -282,7 +347,7 @@ where
 
                 Located::Annotation(_, _) => Some(completer.completion_types()),
 
-                Located::Label(_, _) => None,
+                Located::Label(_, _) | Located::FunctionLabel(_) | Located::RecordLabel(_) => None,
             };
 
             Ok(completions)
@@ -303,6 +368,7 @@ where
 
             code_action_unused_values(module, &lines, &params, &mut actions);
             code_action_unused_imports(module, &lines, &params, &mut actions);
+            code_action_remove_unused_definitions(module, &lines, &params, &mut actions);
             code_action_convert_qualified_constructor_to_unqualified(
                 module,
                 &lines,
@@ -316,8 +382,31 @@ where
                 &mut actions,
             );
             code_action_fix_names(&lines, &params, &this.error, &mut actions);
-            code_action_import_module(module, &lines, &params, &this.error, &mut actions);
+            let import_placement = this.user_config().import_placement;
+            code_action_import_module(
+                module,
+                &lines,
+                &params,
+                &this.error,
+                import_placement,
+                &mut actions,
+            );
             code_action_add_missing_patterns(module, &lines, &params, &this.error, &mut actions);
+            actions.extend(
+                GenerateFunction::new(module, &lines, &params, &this.error, &this.compiler.modules)
+                    .code_actions(),
+            );
+            actions.extend(
+                WrapUnsupportedCall::new(
+                    module,
+                    &lines,
+                    &params,
+                    &this.error,
+                    this.compiler.project_compiler.get_importable_modules(),
+                    &this.compiler.project_compiler.config.name,
+                )
+                .code_actions(),
+            );
             code_action_inexhaustive_let_to_case(
                 module,
                 &lines,
@@ -330,12 +419,46 @@ where
                 .extend(RedundantTupleInCaseSubject::new(module, &lines, &params).code_actions());
             actions.extend(LabelShorthandSyntax::new(module, &lines, &params).code_actions());
             actions.extend(FillInMissingLabelledArgs::new(module, &lines, &params).code_actions());
+            actions
+                .extend(AddMissingLabelsToDefinition::new(module, &lines, &params).code_actions());
             actions.extend(DesugarUse::new(module, &lines, &params).code_actions());
             actions.extend(TurnIntoUse::new(module, &lines, &params).code_actions());
+            actions.extend(
+                ConvertToBoolGuard::new(
+                    module,
+                    &lines,
+                    &params,
+                    this.compiler.project_compiler.get_importable_modules(),
+                    import_placement,
+                )
+                .code_actions(),
+            );
+            actions.extend(ConvertFromBoolGuard::new(module, &lines, &params).code_actions());
             actions.extend(ExpandFunctionCapture::new(module, &lines, &params).code_actions());
             actions.extend(ExtractVariable::new(module, &lines, &params).code_actions());
-            GenerateDynamicDecoder::new(module, &lines, &params, &mut actions).code_actions();
+            GenerateDynamicDecoder::new(module, &lines, &params, import_placement, &mut actions)
+                .code_actions();
             AddAnnotations::new(module, &lines, &params).code_action(&mut actions);
+            actions.extend(
+                PatternMatchOnValue::new(
+                    module,
+                    &lines,
+                    &params,
+                    this.compiler.project_compiler.get_importable_modules(),
+                    this.compiler.project_compiler.target(),
+                )
+                .code_actions(),
+            );
+            actions.extend(
+                SurroundWith::new(
+                    module,
+                    &lines,
+                    &params,
+                    this.compiler.project_compiler.get_importable_modules(),
+                    this.compiler.project_compiler.target(),
+                )
+                .code_actions(),
+            );
             Ok(if actions.is_empty() {
                 None
             } else {
@@ -344,6 +467,38 @@ where
         })
     }
 
+    pub fn inlay_hint(&mut self, params: lsp::InlayHintParams) -> Response<Vec<InlayHint>> {
+        self.respond(|this| {
+            let config = this.user_config().inlay_hints;
+            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
+                return Ok(vec![]);
+            };
+            let line_numbers = LineNumbers::new(&module.code);
+            Ok(inlay_hints::get_inlay_hints(
+                config,
+                params.range,
+                &module.ast,
+                &line_numbers,
+            ))
+        })
+    }
+
+    pub fn semantic_tokens(
+        &mut self,
+        params: lsp::SemanticTokensParams,
+    ) -> Response<Option<lsp::SemanticTokens>> {
+        self.respond(|this| {
+            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
+                return Ok(None);
+            };
+            let importable_modules = this.compiler.project_compiler.get_importable_modules();
+            Ok(Some(lsp::SemanticTokens {
+                result_id: None,
+                data: semantic_tokens::semantic_tokens(module, importable_modules),
+            }))
+        })
+    }
+
     pub fn document_symbol(
         &mut self,
         params: lsp::DocumentSymbolParams,
@@ -456,7 +611,9 @@ where
                                 .map(|(doc_start, _)| get_doc_marker_pos(*doc_start))
                                 .unwrap_or(constant.location.start),
 
-                            end: constant.value.location().end,
+                            // The value of a constant with an embedded file
+                            // comes before it, at the attribute.
+                            end: constant.value.location().end.max(constant.location.end),
                         };
 
                         // The 'deprecated' field is deprecated, but we have to specify it anyway
@@ -489,6 +646,205 @@ where
         })
     }
 
+    /// Finds the functions, types, and constants of the project's modules whose
+    /// names match the query, using the module interfaces from the last
+    /// compilation.
+    pub fn workspace_symbol(
+        &mut self,
+        params: &lsp::WorkspaceSymbolParams,
+    ) -> Response<Vec<SymbolInformation>> {
+        self.respond(|this| {
+            let package = &this.compiler.project_compiler.config.name;
+            let mut symbols = vec![];
+
+            for (name, module) in this.compiler.project_compiler.get_importable_modules() {
+                if module.package != *package {
+                    continue;
+                }
+                let Some(source) = this.compiler.get_source(name) else {
+                    continue;
+                };
+                let uri = Url::parse(&format!("file:///{}", &source.path))
+                    .expect("workspace symbol URL parse");
+
+                let values = module.values.iter().filter_map(|(value_name, value)| {
+                    let (kind, location) = value_symbol(name, &value.variant)?;
+                    Some((value_name, kind, location, &value.deprecation))
+                });
+                let types = module
+                    .types
+                    .iter()
+                    .filter(|(_, type_)| type_.module == *name)
+                    .map(|(type_name, type_)| {
+                        (
+                            type_name,
+                            SymbolKind::CLASS,
+                            type_.origin,
+                            &type_.deprecation,
+                        )
+                    });
+
+                for (symbol_name, kind, location, deprecation) in values.chain(types) {
+                    if !fuzzy_matches(symbol_name, &params.query) {
+                        continue;
+                    }
+                    // The 'deprecated' field is deprecated, but we have to specify it anyway
+                    // to be able to construct the 'SymbolInformation' type, so
+                    // we suppress the warning. We specify 'None' as specifying 'Some'
+                    // is what is actually deprecated.
+                    #[allow(deprecated)]
+                    symbols.push(SymbolInformation {
+                        name: symbol_name.to_string(),
+                        kind,
+                        tags: make_deprecated_symbol_tag(deprecation),
+                        deprecated: None,
+                        location: lsp::Location {
+                            uri: uri.clone(),
+                            range: src_span_to_lsp_range(location, &source.line_numbers),
+                        },
+                        container_name: Some(name.to_string()),
+                    });
+                }
+            }
+
+            // Module interfaces are hash maps, so the symbols are sorted to
+            // always be listed in the same order.
+            symbols.sort_by(|one, other| {
+                (&one.container_name, &one.name).cmp(&(&other.container_name, &other.name))
+            });
+            Ok(symbols)
+        })
+    }
+
+    /// Generates the code for the requested module and target so that it can
+    /// be shown to the programmer, without writing anything to disk.
+    ///
+    pub fn show_compiled(
+        &mut self,
+        params: ShowCompiledParams,
+    ) -> Response<Option<ShowCompiledResult>> {
+        self.respond(|this| {
+            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
+                return Ok(None);
+            };
+
+            let target = params
+                .target
+                .unwrap_or(this.compiler.project_compiler.config.target);
+            let line_numbers = LineNumbers::new(&module.code);
+
+            // The module was only analysed for the project's target, so we
+            // don't enforce target support here: values lacking an
+            // implementation for the requested target are simply left out.
+            let text = match target {
+                Target::Erlang => erlang::module(&module.ast, &line_numbers)?,
+                Target::JavaScript => javascript::module(
+                    &module.ast,
+                    &line_numbers,
+                    &module.input_path,
+                    &module.code,
+                    TargetSupport::NotEnforced,
+                    TypeScriptDeclarations::None,
+                )?,
+                Target::Nix => nix::module(
+                    &module.ast,
+                    &line_numbers,
+                    &module.input_path,
+                    &module.code,
+                    TargetSupport::NotEnforced,
+                    this.compiler.project_compiler.config.glistix.optimize,
+                    this.compiler.project_compiler.list_representation(),
+                )?,
+            };
+
+            let language_id = match target {
+                Target::Erlang => "erlang",
+                Target::JavaScript => "javascript",
+                Target::Nix => "nix",
+            };
+
+            Ok(Some(ShowCompiledResult {
+                target,
+                language_id: language_id.into(),
+                text,
+            }))
+        })
+    }
+
+    /// Finds the functions of the project and its dependencies whose type
+    /// matches the query. A query which isn't a valid type matches nothing.
+    ///
+    pub fn search_by_type(
+        &mut self,
+        params: &SearchByTypeParams,
+    ) -> Response<Vec<SearchByTypeResult>> {
+        self.respond(|this| {
+            let Ok(query) = TypeQuery::parse(&params.query) else {
+                return Ok(vec![]);
+            };
+            let modules = this.compiler.project_compiler.get_importable_modules();
+
+            let results = type_search::search(&query, modules.values())
+                .into_iter()
+                .map(|result| {
+                    let source = this.compiler.get_source(&result.module);
+                    let location = source.and_then(|source| {
+                        let uri = Url::parse(&format!("file:///{}", &source.path)).ok()?;
+                        Some(lsp::Location {
+                            uri,
+                            range: src_span_to_lsp_range(result.location, &source.line_numbers),
+                        })
+                    });
+                    SearchByTypeResult {
+                        module: result.module.to_string(),
+                        name: result.name.to_string(),
+                        signature: type_::pretty::Printer::new().pretty_print(&result.type_, 0),
+                        location,
+                    }
+                })
+                .collect();
+            Ok(results)
+        })
+    }
+
+    /// Finds the declaration of the parameter or record field a labelled
+    /// argument is given to, along with its documentation. This is only
+    /// possible when the function or record is defined in the project.
+    ///
+    fn labelled_parameter(
+        &self,
+        argument: &LabelledArgument<'_>,
+    ) -> Option<(SrcSpan, Option<&str>)> {
+        let module = self.compiler.modules.get(argument.module)?;
+        let index = argument.index as usize;
+        module
+            .ast
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::Function(function) => {
+                    let (_, name) = function.name.as_ref()?;
+                    if name != argument.name {
+                        return None;
+                    }
+                    let parameter = function.arguments.get(index)?;
+                    Some((parameter.location, None))
+                }
+                Definition::CustomType(custom_type) => {
+                    let constructor = custom_type
+                        .constructors
+                        .iter()
+                        .find(|constructor| constructor.name == *argument.name)?;
+                    let field = constructor.arguments.get(index)?;
+                    let documentation = field.doc.as_ref().map(|(_, doc)| doc.as_str());
+                    Some((field.location, documentation))
+                }
+                Definition::TypeAlias(_)
+                | Definition::Import(_)
+                | Definition::ModuleConstant(_) => None,
+            })
+    }
+
     fn respond<T>(&mut self, handler: impl FnOnce(&mut Self) -> Result<T>) -> Response<T> {
         let result = handler(self);
         let warnings = self.take_warnings();
@@ -603,12 +959,21 @@ Unused labelled fields:
                         range,
                     })
                 }
-                Located::Expression(expression) => Some(hover_for_expression(
-                    expression,
-                    lines,
-                    module,
-                    &this.hex_deps,
-                )),
+                Located::Expression(expression) => {
+                    let importable_modules =
+                        this.compiler.project_compiler.get_importable_modules();
+                    let since = expression_since(expression, importable_modules);
+                    let implementations =
+                        expression_implementations(expression, importable_modules);
+                    Some(hover_for_expression(
+                        expression,
+                        since,
+                        implementations.as_ref(),
+                        lines,
+                        module,
+                        &this.hex_deps,
+                    ))
+                }
                 Located::Arg(arg) => Some(hover_for_function_argument(arg, lines, module)),
                 Located::FunctionBody(_) => None,
                 Located::Annotation(annotation, type_) => {
@@ -627,6 +992,18 @@ Unused labelled fields:
                 Located::Label(location, type_) => {
                     Some(hover_for_label(location, type_, lines, module))
                 }
+                Located::FunctionLabel(argument) | Located::RecordLabel(argument) => {
+                    let documentation = this
+                        .labelled_parameter(&argument)
+                        .and_then(|(_, documentation)| documentation)
+                        .or(argument.documentation);
+                    Some(hover_for_labelled_argument(
+                        &argument,
+                        documentation,
+                        lines,
+                        module,
+                    ))
+                }
             })
         })
     }
@@ -841,13 +1218,15 @@ fn hover_for_function_head(
         .as_ref()
         .map(|(_, doc)| doc)
         .unwrap_or(&empty_str);
+    let since = since_section(fun.since.as_deref());
+    let targets = targets_section(&fun.implementations);
     let function_type = get_function_type(fun);
     let formatted_type = Printer::new(&module.ast.names).print_type(&function_type);
     let contents = format!(
         "```gleam
 {formatted_type}
 ```
-{documentation}"
+{documentation}{since}{targets}"
     );
     Hover {
         contents: HoverContents::Scalar(MarkedString::String(contents)),
@@ -879,6 +1258,7 @@ fn hover_for_annotation(
     let documentation = type_constructor
         .and_then(|t| t.documentation.as_ref())
         .unwrap_or(&empty_str);
+    let since = since_section(type_constructor.and_then(|t| t.since.as_deref()));
     // If a user is hovering an annotation, it's not very useful to show the
     // local representation of that type, since that's probably what they see
     // in the source code anyway. So here, we print the raw type,
@@ -888,7 +1268,7 @@ fn hover_for_annotation(
         "```gleam
 {type_}
 ```
-{documentation}"
+{documentation}{since}"
     );
     Hover {
         contents: HoverContents::Scalar(MarkedString::String(contents)),
@@ -910,6 +1290,23 @@ fn hover_for_label(
     }
 }
 
+fn hover_for_labelled_argument(
+    argument: &LabelledArgument<'_>,
+    documentation: Option<&str>,
+    line_numbers: LineNumbers,
+    module: &Module,
+) -> Hover {
+    let type_ = Printer::new(&module.ast.names).print_type(&argument.type_);
+    let contents = match documentation {
+        Some(documentation) => format!("```gleam\n{type_}\n```\n{documentation}"),
+        None => format!("```gleam\n{type_}\n```"),
+    };
+    Hover {
+        contents: HoverContents::Scalar(MarkedString::String(contents)),
+        range: Some(src_span_to_lsp_range(argument.location, &line_numbers)),
+    }
+}
+
 fn hover_for_module_constant(
     constant: &ModuleConstant<Arc<Type>, EcoString>,
     line_numbers: LineNumbers,
@@ -922,7 +1319,9 @@ fn hover_for_module_constant(
         .as_ref()
         .map(|(_, doc)| doc)
         .unwrap_or(&empty_str);
-    let contents = format!("```gleam\n{type_}\n```\n{documentation}");
+    let since = since_section(constant.since.as_deref());
+    let targets = targets_section(&constant.implementations);
+    let contents = format!("```gleam\n{type_}\n```\n{documentation}{since}{targets}");
     Hover {
         contents: HoverContents::Scalar(MarkedString::String(contents)),
         range: Some(src_span_to_lsp_range(constant.location, &line_numbers)),
@@ -931,11 +1330,15 @@ fn hover_for_module_constant(
 
 fn hover_for_expression(
     expression: &TypedExpr,
+    since: Option<&str>,
+    implementations: Option<&Implementations>,
     line_numbers: LineNumbers,
     module: &Module,
     hex_deps: &std::collections::HashSet<EcoString>,
 ) -> Hover {
     let documentation = expression.get_documentation().unwrap_or_default();
+    let since = since_section(since);
+    let targets = implementations.map_or(String::new(), targets_section);
 
     let link_section = get_expr_qualified_name(expression)
         .and_then(|(module_name, name)| {
@@ -949,7 +1352,7 @@ fn hover_for_expression(
         "```gleam
 {type_}
 ```
-{documentation}{link_section}"
+{documentation}{since}{targets}{link_section}"
     );
     Hover {
         contents: HoverContents::Scalar(MarkedString::String(contents)),
@@ -966,6 +1369,8 @@ fn hover_for_imported_value(
     module: &Module,
 ) -> Hover {
     let documentation = value.get_documentation().unwrap_or_default();
+    let since = since_section(value.since.as_deref());
+    let targets = targets_section(&value.variant.implementations());
 
     let link_section = hex_module_imported_from.map_or("".to_string(), |m| {
         format_hexdocs_link_section(m.package.as_str(), m.name.as_str(), name)
@@ -977,7 +1382,7 @@ fn hover_for_imported_value(
         "```gleam
 {type_}
 ```
-{documentation}{link_section}"
+{documentation}{since}{targets}{link_section}"
     );
     Hover {
         contents: HoverContents::Scalar(MarkedString::String(contents)),
@@ -1122,6 +1527,87 @@ fn code_action_unused_imports(
         .push_to(actions);
 }
 
+fn code_action_remove_unused_definitions(
+    module: &Module,
+    line_numbers: &LineNumbers,
+    params: &lsp::CodeActionParams,
+    actions: &mut Vec<CodeAction>,
+) {
+    let uri = &params.text_document.uri;
+    let unused = module
+        .ast
+        .type_info
+        .warnings
+        .iter()
+        .filter_map(|warning| match warning {
+            type_::Warning::UnusedPrivateFunction {
+                definition_location,
+                ..
+            }
+            | type_::Warning::UnusedType {
+                definition_location: Some(definition_location),
+                ..
+            } => Some(*definition_location),
+            _ => None,
+        });
+
+    for SrcSpan { start, end } in unused {
+        let start = attributes_start(&module.code, start);
+        let definition_range = src_span_to_lsp_range(SrcSpan::new(start, end), line_numbers);
+        if !overlaps(params.range, definition_range) {
+            continue;
+        }
+
+        // Also remove the end of the line and the blank line separating the
+        // definition from the next one, so no gap is left behind.
+        let trailing_newlines = module
+            .code
+            .get(end as usize..)
+            .unwrap_or_default()
+            .bytes()
+            .take(2)
+            .take_while(|byte| *byte == b'\n')
+            .count() as u32;
+        let range =
+            src_span_to_lsp_range(SrcSpan::new(start, end + trailing_newlines), line_numbers);
+
+        CodeActionBuilder::new("Remove unused definition")
+            .kind(lsp_types::CodeActionKind::QUICKFIX)
+            .changes(
+                uri.clone(),
+                vec![TextEdit {
+                    range,
+                    new_text: "".into(),
+                }],
+            )
+            .preferred(true)
+            .push_to(actions);
+    }
+}
+
+/// The start of the attributes written on the lines directly above a
+/// definition, such as `@deprecated("...")`, so they can be removed with it.
+/// The definition location of a function only covers its externals.
+fn attributes_start(code: &str, start: u32) -> u32 {
+    let mut start = start as usize;
+    loop {
+        let before = code.get(..start).unwrap_or_default();
+        let Some(line_end) = before.strip_suffix('\n').map(str::len) else {
+            break;
+        };
+        let line_start = before
+            .get(..line_end)
+            .and_then(|before| before.rfind('\n'))
+            .map_or(0, |newline| newline + 1);
+        let line = code.get(line_start..line_end).unwrap_or_default();
+        if !line.starts_with('@') {
+            break;
+        }
+        start = line_start;
+    }
+    start as u32
+}
+
 struct NameCorrection {
     pub location: SrcSpan,
     pub correction: EcoString,
@@ -1212,6 +1698,70 @@ fn get_expr_qualified_name(expression: &TypedExpr) -> Option<(&EcoString, &EcoSt
     }
 }
 
+/// The version given in the `@since` attribute of the value an expression
+/// refers to, if any. Values used with a module select don't carry it, so it
+/// is found in the interface of their module.
+fn expression_since<'a>(
+    expression: &'a TypedExpr,
+    importable_modules: &'a im::HashMap<EcoString, ModuleInterface>,
+) -> Option<&'a str> {
+    match expression {
+        TypedExpr::Var { constructor, .. } => constructor.since.as_deref(),
+        TypedExpr::ModuleSelect {
+            label, module_name, ..
+        } => importable_modules
+            .get(module_name)
+            .and_then(|module| module.values.get(label))
+            .and_then(|value| value.since.as_deref()),
+        _ => None,
+    }
+}
+
+/// A line telling the version a definition has been available since, shown
+/// after its documentation when hovering it.
+fn since_section(since: Option<&str>) -> String {
+    since.map_or(String::new(), |since| {
+        format!("\n*Available since version {since}*\n")
+    })
+}
+
+fn expression_implementations(
+    expression: &TypedExpr,
+    importable_modules: &im::HashMap<EcoString, ModuleInterface>,
+) -> Option<Implementations> {
+    match expression {
+        TypedExpr::Var { constructor, .. } => Some(constructor.variant.implementations()),
+        TypedExpr::ModuleSelect {
+            label, module_name, ..
+        } => importable_modules
+            .get(module_name)
+            .and_then(|module| module.values.get(label))
+            .map(|value| value.variant.implementations()),
+        _ => None,
+    }
+}
+
+/// A line telling which targets a value can be used on. Pure Gleam values
+/// work everywhere, so it's only shown for those relying on externals.
+fn targets_section(implementations: &Implementations) -> String {
+    if implementations.gleam {
+        return String::new();
+    }
+    let support = |target| {
+        if implementations.supports(target) {
+            "✔"
+        } else {
+            "✘"
+        }
+    };
+    format!(
+        "\ntargets: erlang {} javascript {} nix {}\n",
+        support(Target::Erlang),
+        support(Target::JavaScript),
+        support(Target::Nix),
+    )
+}
+
 fn format_hexdocs_link_section(package_name: &str, module_name: &str, name: &str) -> String {
     let link = format!("https://hexdocs.pm/{package_name}/{module_name}.html#{name}");
     format!("\nView on [HexDocs]({link})")
@@ -1239,6 +1789,54 @@ fn get_doc_marker_pos(content_pos: u32) -> u32 {
     content_pos.saturating_sub(3)
 }
 
+/// The kind of symbol of a module's value and where it is defined, if it is
+/// defined in that module.
+fn value_symbol(module: &str, variant: &ValueConstructorVariant) -> Option<(SymbolKind, SrcSpan)> {
+    match variant {
+        ValueConstructorVariant::ModuleFn {
+            module: value_module,
+            location,
+            ..
+        } if value_module == module => Some((SymbolKind::FUNCTION, *location)),
+
+        ValueConstructorVariant::ModuleConstant {
+            module: value_module,
+            location,
+            ..
+        } if value_module == module => Some((SymbolKind::CONSTANT, *location)),
+
+        ValueConstructorVariant::Record {
+            module: value_module,
+            location,
+            arity,
+            ..
+        } if value_module == module => {
+            let kind = if *arity == 0 {
+                SymbolKind::ENUM_MEMBER
+            } else {
+                SymbolKind::CONSTRUCTOR
+            };
+            Some((kind, *location))
+        }
+
+        ValueConstructorVariant::LocalVariable { .. }
+        | ValueConstructorVariant::LocalConstant { .. }
+        | ValueConstructorVariant::ModuleFn { .. }
+        | ValueConstructorVariant::ModuleConstant { .. }
+        | ValueConstructorVariant::Record { .. } => None,
+    }
+}
+
+/// Whether all characters of the query appear in the name in the same order,
+/// ignoring case, as editors expect workspace symbols to be matched fuzzily.
+fn fuzzy_matches(name: &str, query: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|query_char| name.any(|name_char| name_char == query_char))
+}
+
 fn make_deprecated_symbol_tag(deprecation: &Deprecation) -> Option<Vec<SymbolTag>> {
     deprecation
         .is_deprecated()
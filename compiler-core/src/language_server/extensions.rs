@@ -0,0 +1,113 @@
+//! Glistix-specific extensions to the language server protocol.
+//!
+//! These are custom requests that editor extensions can opt into. They are
+//! namespaced under `glistix/` so they never clash with standard LSP methods.
+
+use lsp_types::{self as lsp, request::Request};
+use serde::{Deserialize, Serialize};
+
+use crate::build::Target;
+
+/// The `glistix/showCompiledCode` request, which returns the code generated
+/// for the module open in the editor, so that it can be displayed in a
+/// virtual read-only document next to the Gleam source. As with any request,
+/// pending edits are compiled before it is handled, so the code is up to date.
+///
+/// The request is also accepted under its original name, `glistix/showCompiled`,
+/// so that editor extensions written against it keep working.
+///
+#[derive(Debug)]
+pub enum ShowCompiled {}
+
+impl Request for ShowCompiled {
+    type Params = ShowCompiledParams;
+    type Result = Option<ShowCompiledResult>;
+    const METHOD: &'static str = "glistix/showCompiledCode";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowCompiledParams {
+    pub text_document: lsp::TextDocumentIdentifier,
+    /// The target to generate code for. Defaults to the project's target.
+    pub target: Option<Target>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowCompiledResult {
+    /// The target the code was generated for.
+    pub target: Target,
+    /// The language identifier editors should use to highlight the text,
+    /// e.g. `nix`.
+    pub language_id: String,
+    /// The generated code.
+    pub text: String,
+}
+
+/// The `glistix/searchByType` request, which finds the functions of the
+/// projects open in the editor and their dependencies whose type matches a
+/// signature, such as `fn(List(a), fn(a) -> b) -> List(b)`.
+///
+#[derive(Debug)]
+pub enum SearchByType {}
+
+impl Request for SearchByType {
+    type Params = SearchByTypeParams;
+    type Result = Vec<SearchByTypeResult>;
+    const METHOD: &'static str = "glistix/searchByType";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchByTypeParams {
+    /// The type to search for, written as a Gleam type annotation.
+    pub query: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchByTypeResult {
+    /// The module defining the function, e.g. `gleam/list`.
+    pub module: String,
+    pub name: String,
+    /// The type of the function, as it would be written in Gleam.
+    pub signature: String,
+    /// Where the function is defined, if its source is available.
+    pub location: Option<lsp::Location>,
+}
+
+/// The `glistix/statistics` request, which returns performance data gathered
+/// by the language server since it started, to help diagnose reports of it
+/// being slow.
+///
+#[derive(Debug)]
+pub enum Statistics {}
+
+impl Request for Statistics {
+    type Params = ();
+    type Result = StatisticsResult;
+    const METHOD: &'static str = "glistix/statistics";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatisticsResult {
+    /// How many times the root packages of the open projects were compiled.
+    pub compiles: u64,
+    /// How many of those compilations had errors.
+    pub failed_compiles: u64,
+    pub mean_compile_milliseconds: f64,
+    /// The 95th percentile compile time, over the most recent compilations.
+    pub p95_compile_milliseconds: f64,
+    /// How many modules were type checked over all compilations.
+    pub modules_compiled: u64,
+    /// How many modules were reused from the cache over all compilations.
+    pub modules_cached: u64,
+    /// The share of modules reused from the cache, between 0 and 1.
+    pub cache_hit_rate: f64,
+    /// How many projects the language server has loaded.
+    pub open_projects: usize,
+    /// The physical memory used by the language server, when known.
+    pub resident_memory_bytes: Option<u64>,
+}
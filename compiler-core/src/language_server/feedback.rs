@@ -1,4 +1,7 @@
-use crate::{diagnostic::Diagnostic, Error, Warning};
+use crate::{
+    diagnostic::{Diagnostic, Label},
+    type_, Error, Warning,
+};
 use std::collections::{HashMap, HashSet};
 
 use camino::Utf8PathBuf;
@@ -8,6 +11,10 @@ use super::engine::Compilation;
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct Feedback {
     pub diagnostics: HashMap<Utf8PathBuf, Vec<Diagnostic>>,
+    /// Definitions that are never used, spanning the whole definition so that
+    /// editors can fade them out. These are sent along with the diagnostics
+    /// for the same file.
+    pub unnecessary: HashMap<Utf8PathBuf, Vec<Diagnostic>>,
     pub messages: Vec<Diagnostic>,
 }
 
@@ -15,6 +22,7 @@ impl Feedback {
     /// Set the diagnostics for a file to an empty vector. This will overwrite
     /// any existing diagnostics on the client.
     pub fn unset_existing_diagnostics(&mut self, path: Utf8PathBuf) {
+        _ = self.unnecessary.remove(&path);
         _ = self.diagnostics.insert(path, vec![]);
     }
 
@@ -22,6 +30,10 @@ impl Feedback {
         self.diagnostics.entry(path).or_default().push(diagnostic);
     }
 
+    pub fn append_unnecessary(&mut self, path: Utf8PathBuf, diagnostic: Diagnostic) {
+        self.unnecessary.entry(path).or_default().push(diagnostic);
+    }
+
     /// No feedback at all.
     ///
     pub fn none() -> Feedback {
@@ -30,9 +42,13 @@ impl Feedback {
 
     /// Add all the content of another feedback to this feedback.
     ///
-    pub fn append_feedback(&mut self, feedback: Feedback) {
+    pub fn append_feedback(&mut self, mut feedback: Feedback) {
         for (path, diagnostics) in feedback.diagnostics {
             // Any new diagnostics for a file will overwrite any existing ones.
+            match feedback.unnecessary.remove(&path) {
+                Some(unnecessary) => _ = self.unnecessary.insert(path.clone(), unnecessary),
+                None => _ = self.unnecessary.remove(&path),
+            }
             _ = self.diagnostics.insert(path, diagnostics);
         }
         for diagnostic in feedback.messages {
@@ -142,15 +158,60 @@ impl FeedbackBookKeeper {
         self.build_with_error(error, Compilation::No, vec![])
     }
 
+    /// Remove all the diagnostics sent so far, for when the project is no
+    /// longer open in the editor.
+    ///
+    pub fn clear(&mut self) -> Feedback {
+        let mut feedback = Feedback::default();
+        for path in self.files_with_warnings.drain() {
+            feedback.unset_existing_diagnostics(path);
+        }
+        self.unset_errors(&mut feedback);
+        feedback
+    }
+
     fn insert_warning(&mut self, feedback: &mut Feedback, warning: Warning) {
         let diagnostic = warning.to_diagnostic();
         if let Some(path) = diagnostic.location.as_ref().map(|l| l.path.clone()) {
             _ = self.files_with_warnings.insert(path.clone());
+            if let Some(unused) = unused_definition(&warning, &diagnostic) {
+                feedback.append_unnecessary(path.clone(), unused);
+            }
             feedback.append_diagnostic(path, diagnostic);
         }
     }
 }
 
+/// For a warning about an unused private function or type, a diagnostic that
+/// spans the whole definition rather than just its name.
+fn unused_definition(warning: &Warning, diagnostic: &Diagnostic) -> Option<Diagnostic> {
+    let definition_location = match warning {
+        Warning::Type {
+            warning:
+                type_::Warning::UnusedPrivateFunction {
+                    definition_location,
+                    ..
+                }
+                | type_::Warning::UnusedType {
+                    definition_location: Some(definition_location),
+                    ..
+                },
+            ..
+        } => *definition_location,
+        _ => return None,
+    };
+    let mut diagnostic = diagnostic.clone();
+    let location = diagnostic.location.as_mut()?;
+    location.label = Label {
+        text: None,
+        span: definition_location,
+    };
+    location.extra_labels = vec![];
+    diagnostic.text = String::new();
+    diagnostic.hint = None;
+    Some(diagnostic)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -200,6 +261,7 @@ mod tests {
                     ),
                     (file2.clone(), vec![warning2.to_diagnostic(),])
                 ]),
+                unnecessary: HashMap::new(),
                 messages: vec![],
             },
             feedback
@@ -218,6 +280,7 @@ mod tests {
                     (file2, vec![]),
                     // File 3 had no diagnostics so does not need to to be unset
                 ]),
+                unnecessary: HashMap::new(),
                 messages: vec![],
             },
             feedback
@@ -251,6 +314,7 @@ mod tests {
         assert_eq!(
             Feedback {
                 diagnostics: HashMap::from([(file1, vec![warning1.to_diagnostic()])]),
+                unnecessary: HashMap::new(),
                 messages: locationless_error.to_diagnostics(),
             },
             feedback
@@ -294,6 +358,7 @@ mod tests {
                     (file1, vec![warning1.to_diagnostic()]),
                     (file3.clone(), error.to_diagnostics()),
                 ]),
+                unnecessary: HashMap::new(),
                 messages: vec![],
             },
             feedback
@@ -306,6 +371,7 @@ mod tests {
         assert_eq!(
             Feedback {
                 diagnostics: HashMap::from([(file3, vec![])]),
+                unnecessary: HashMap::new(),
                 messages: vec![],
             },
             feedback
@@ -348,6 +414,7 @@ mod tests {
         assert_eq!(
             Feedback {
                 diagnostics: HashMap::from([(file1.clone(), error.to_diagnostics())]),
+                unnecessary: HashMap::new(),
                 messages: vec![],
             },
             feedback
@@ -361,6 +428,7 @@ mod tests {
         assert_eq!(
             Feedback {
                 diagnostics: HashMap::from([(file1, vec![])]),
+                unnecessary: HashMap::new(),
                 messages: vec![],
             },
             feedback
@@ -389,6 +457,7 @@ mod tests {
         assert_eq!(
             Feedback {
                 diagnostics: HashMap::from([(file1.clone(), error(&file1).to_diagnostics())]),
+                unnecessary: HashMap::new(),
                 messages: vec![],
             },
             feedback
@@ -405,6 +474,7 @@ mod tests {
                     // Set the new one
                     (file2.clone(), error(&file2).to_diagnostics()),
                 ]),
+                unnecessary: HashMap::new(),
                 messages: vec![],
             },
             feedback
@@ -432,6 +502,7 @@ mod tests {
         assert_eq!(
             Feedback {
                 diagnostics: HashMap::from([(file1, error.to_diagnostics())]),
+                unnecessary: HashMap::new(),
                 messages: vec![],
             },
             feedback
@@ -445,6 +516,7 @@ mod tests {
         assert_eq!(
             Feedback {
                 diagnostics: HashMap::new(),
+                unnecessary: HashMap::new(),
                 messages: vec![],
             },
             feedback
@@ -464,6 +536,7 @@ mod tests {
                     level: Level::Error,
                 }],
             )]),
+            unnecessary: HashMap::new(),
             messages: vec![Diagnostic {
                 location: None,
                 hint: None,
@@ -483,6 +556,7 @@ mod tests {
                     level: Level::Error,
                 }],
             )]),
+            unnecessary: HashMap::new(),
             messages: vec![],
         });
         assert_eq!(
@@ -510,6 +584,7 @@ mod tests {
                         }],
                     ),
                 ]),
+                unnecessary: HashMap::new(),
                 messages: vec![Diagnostic {
                     location: None,
                     hint: None,
@@ -534,6 +609,7 @@ mod tests {
                     level: Level::Error,
                 }],
             )]),
+            unnecessary: HashMap::new(),
             messages: vec![Diagnostic {
                 location: None,
                 hint: None,
@@ -553,6 +629,7 @@ mod tests {
                     level: Level::Error,
                 }],
             )]),
+            unnecessary: HashMap::new(),
             messages: vec![],
         });
         assert_eq!(
@@ -568,6 +645,7 @@ mod tests {
                         level: Level::Error,
                     }],
                 ),]),
+                unnecessary: HashMap::new(),
                 messages: vec![Diagnostic {
                     location: None,
                     hint: None,
@@ -592,6 +670,7 @@ mod tests {
                     level: Level::Error,
                 }],
             )]),
+            unnecessary: HashMap::new(),
             messages: vec![Diagnostic {
                 location: None,
                 hint: None,
@@ -602,6 +681,7 @@ mod tests {
         };
         feedback.append_feedback(Feedback {
             diagnostics: HashMap::from([]),
+            unnecessary: HashMap::new(),
             messages: vec![Diagnostic {
                 location: None,
                 hint: None,
@@ -623,6 +703,7 @@ mod tests {
                         level: Level::Error,
                     },],
                 ),]),
+                unnecessary: HashMap::new(),
                 messages: vec![
                     Diagnostic {
                         location: None,
@@ -656,6 +737,7 @@ mod tests {
                     level: Level::Error,
                 }],
             )]),
+            unnecessary: HashMap::new(),
             messages: vec![Diagnostic {
                 location: None,
                 hint: None,
@@ -666,6 +748,7 @@ mod tests {
         };
         feedback.append_feedback(Feedback {
             diagnostics: HashMap::from([(Utf8PathBuf::from("src/file2.gleam"), vec![])]),
+            unnecessary: HashMap::new(),
             messages: vec![],
         });
         assert_eq!(
@@ -684,6 +767,7 @@ mod tests {
                     ),
                     (Utf8PathBuf::from("src/file2.gleam"), vec![],),
                 ]),
+                unnecessary: HashMap::new(),
                 messages: vec![Diagnostic {
                     location: None,
                     hint: None,
@@ -708,6 +792,7 @@ mod tests {
                     level: Level::Error,
                 }],
             )]),
+            unnecessary: HashMap::new(),
             messages: vec![Diagnostic {
                 location: None,
                 hint: None,
@@ -718,12 +803,14 @@ mod tests {
         };
         feedback.append_feedback(Feedback {
             diagnostics: HashMap::from([(Utf8PathBuf::from("src/file1.gleam"), vec![])]),
+            unnecessary: HashMap::new(),
             messages: vec![],
         });
         assert_eq!(
             feedback,
             Feedback {
                 diagnostics: HashMap::from([(Utf8PathBuf::from("src/file1.gleam"), vec![],),]),
+                unnecessary: HashMap::new(),
                 messages: vec![Diagnostic {
                     location: None,
                     hint: None,
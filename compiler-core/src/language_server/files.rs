@@ -96,6 +96,10 @@ where
         self.io.symlink_dir(from, to)
     }
 
+    fn rename(&self, from: &Utf8Path, to: &Utf8Path) -> Result<()> {
+        self.io.rename(from, to)
+    }
+
     fn delete_file(&self, path: &Utf8Path) -> Result<()> {
         self.io.delete_file(path)
     }
@@ -148,6 +152,13 @@ where
         }
     }
 
+    fn file_size(&self, path: &Utf8Path) -> Result<u64> {
+        match self.edit_cache.file_size(path) {
+            result @ Ok(_) => result,
+            Err(_) => self.io.file_size(path),
+        }
+    }
+
     fn canonicalise(&self, path: &Utf8Path) -> Result<Utf8PathBuf, Error> {
         self.io.canonicalise(path)
     }
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use ecow::{eco_format, EcoString};
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, Position, Range, TextEdit};
+
+use crate::{
+    ast::{
+        visit::{self, Visit},
+        FunctionLiteralKind, SrcSpan, TypeAst, TypedArg, TypedExpr, TypedFunction, TypedModule,
+        TypedStatement,
+    },
+    line_numbers::LineNumbers,
+    type_::{printer::Printer, Type, TypedCallArg},
+};
+
+use super::configuration::InlayHintsConfig;
+
+/// The inlay hints to show in the given range of a module: the inferred types
+/// of un-annotated function parameters and return types, and the labels of
+/// arguments passed without them.
+///
+pub fn get_inlay_hints(
+    config: InlayHintsConfig,
+    range: Range,
+    module: &TypedModule,
+    line_numbers: &LineNumbers,
+) -> Vec<InlayHint> {
+    let mut collector = InlayHintCollector {
+        config,
+        range,
+        line_numbers,
+        printer: Printer::new(&module.names),
+        hints: vec![],
+    };
+    collector.visit_typed_module(module);
+    collector.hints
+}
+
+struct InlayHintCollector<'a> {
+    config: InlayHintsConfig,
+    range: Range,
+    line_numbers: &'a LineNumbers,
+    printer: Printer<'a>,
+    hints: Vec<InlayHint>,
+}
+
+impl InlayHintCollector<'_> {
+    fn position(&self, byte_index: u32) -> Position {
+        let position = self.line_numbers.line_and_column_number(byte_index);
+        Position::new(position.line - 1, position.column - 1)
+    }
+
+    fn parameter_type_hints(&mut self, arguments: &[TypedArg]) {
+        if !self.config.parameter_types {
+            return;
+        }
+        for argument in arguments
+            .iter()
+            .filter(|argument| argument.annotation.is_none())
+        {
+            let type_ = self.printer.print_type(&argument.type_);
+            self.push_type_hint(argument.location.end, eco_format!(": {type_}"));
+        }
+    }
+
+    fn return_type_hint(
+        &mut self,
+        head: SrcSpan,
+        return_annotation: &Option<TypeAst>,
+        type_: &Type,
+    ) {
+        if !self.config.return_types || return_annotation.is_some() {
+            return;
+        }
+        let type_ = self.printer.print_type(type_);
+        self.push_type_hint(head.end, eco_format!(" -> {type_}"));
+    }
+
+    /// Type hints can be inserted into the code as they are, to turn them
+    /// into annotations.
+    fn push_type_hint(&mut self, byte_index: u32, text: EcoString) {
+        let position = self.position(byte_index);
+        self.push(InlayHint {
+            position,
+            label: InlayHintLabel::String(text.to_string()),
+            kind: Some(InlayHintKind::TYPE),
+            text_edits: Some(vec![TextEdit {
+                range: Range::new(position, position),
+                new_text: text.to_string(),
+            }]),
+            tooltip: None,
+            padding_left: None,
+            padding_right: None,
+            data: None,
+        });
+    }
+
+    fn label_hints(&mut self, fun: &TypedExpr, arguments: &[TypedCallArg]) {
+        if !self.config.labels {
+            return;
+        }
+        let Some(field_map) = fun.field_map() else {
+            return;
+        };
+
+        // Once type checked, the arguments of a call are in the same order as
+        // the function's parameters.
+        for (index, argument) in arguments.iter().enumerate() {
+            if argument.label.is_some() || argument.implicit.is_some() {
+                continue;
+            }
+            let Some((label, _)) = field_map
+                .fields
+                .iter()
+                .find(|(_, position)| **position as usize == index)
+            else {
+                continue;
+            };
+            // Passing a variable with the same name as the label is clear
+            // enough without a hint.
+            if let TypedExpr::Var { name, .. } = &argument.value {
+                if name == label {
+                    continue;
+                }
+            }
+
+            self.push(InlayHint {
+                position: self.position(argument.location.start),
+                label: InlayHintLabel::String(format!("{label}:")),
+                kind: Some(InlayHintKind::PARAMETER),
+                text_edits: None,
+                tooltip: None,
+                padding_left: None,
+                padding_right: Some(true),
+                data: None,
+            });
+        }
+    }
+
+    fn push(&mut self, hint: InlayHint) {
+        if self.range.start <= hint.position && hint.position <= self.range.end {
+            self.hints.push(hint);
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for InlayHintCollector<'_> {
+    fn visit_typed_function(&mut self, fun: &'ast TypedFunction) {
+        self.parameter_type_hints(&fun.arguments);
+        self.return_type_hint(fun.location, &fun.return_annotation, &fun.return_type);
+        visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        args: &'ast [TypedArg],
+        body: &'ast [TypedStatement],
+        return_annotation: &'ast Option<TypeAst>,
+    ) {
+        // The arguments of captures are not written by the programmer, and
+        // `use` callbacks have no head a return type could be added to.
+        match kind {
+            FunctionLiteralKind::Anonymous { head } => {
+                self.parameter_type_hints(args);
+                if let Some(return_type) = type_.return_type() {
+                    self.return_type_hint(*head, return_annotation, &return_type);
+                }
+            }
+            FunctionLiteralKind::Use { .. } => self.parameter_type_hints(args),
+            FunctionLiteralKind::Capture => {}
+        }
+        visit::visit_typed_expr_fn(self, location, type_, kind, args, body, return_annotation);
+    }
+
+    fn visit_typed_expr_call(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        fun: &'ast TypedExpr,
+        args: &'ast [TypedCallArg],
+    ) {
+        self.label_hints(fun, args);
+        visit::visit_typed_expr_call(self, location, type_, fun, args);
+    }
+}
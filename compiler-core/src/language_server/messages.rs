@@ -1,6 +1,10 @@
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
+use itertools::Itertools;
 use lsp::{
-    notification::{DidChangeWatchedFiles, DidOpenTextDocument},
+    notification::{
+        DidChangeConfiguration, DidChangeWatchedFiles, DidChangeWorkspaceFolders,
+        DidOpenTextDocument,
+    },
     request::GotoDefinition,
 };
 use lsp_types::{
@@ -8,11 +12,13 @@ use lsp_types::{
     notification::{DidChangeTextDocument, DidCloseTextDocument, DidSaveTextDocument},
     request::{
         CodeActionRequest, Completion, DocumentSymbolRequest, Formatting, HoverRequest,
-        SignatureHelpRequest,
+        InlayHintRequest, SemanticTokensFullRequest, SignatureHelpRequest, WorkspaceSymbolRequest,
     },
 };
 use std::time::Duration;
 
+use super::extensions::{self, SearchByType, ShowCompiled, Statistics};
+
 #[derive(Debug)]
 pub enum Message {
     Request(lsp_server::RequestId, Request),
@@ -28,6 +34,12 @@ pub enum Request {
     CodeAction(lsp::CodeActionParams),
     SignatureHelp(lsp::SignatureHelpParams),
     DocumentSymbol(lsp::DocumentSymbolParams),
+    WorkspaceSymbol(lsp::WorkspaceSymbolParams),
+    InlayHint(lsp::InlayHintParams),
+    SemanticTokens(lsp::SemanticTokensParams),
+    ShowCompiled(extensions::ShowCompiledParams),
+    SearchByType(extensions::SearchByTypeParams),
+    Statistics,
 }
 
 impl Request {
@@ -62,6 +74,35 @@ impl Request {
                 let params = cast_request::<DocumentSymbolRequest>(request);
                 Some(Message::Request(id, Request::DocumentSymbol(params)))
             }
+            "workspace/symbol" => {
+                let params = cast_request::<WorkspaceSymbolRequest>(request);
+                Some(Message::Request(id, Request::WorkspaceSymbol(params)))
+            }
+            "textDocument/inlayHint" => {
+                let params = cast_request::<InlayHintRequest>(request);
+                Some(Message::Request(id, Request::InlayHint(params)))
+            }
+            "textDocument/semanticTokens/full" => {
+                let params = cast_request::<SemanticTokensFullRequest>(request);
+                Some(Message::Request(id, Request::SemanticTokens(params)))
+            }
+            "glistix/showCompiledCode" | "glistix/showCompiled" => {
+                // Normalise the original name so the request can be cast.
+                let request = lsp_server::Request {
+                    method: "glistix/showCompiledCode".into(),
+                    ..request
+                };
+                let params = cast_request::<ShowCompiled>(request);
+                Some(Message::Request(id, Request::ShowCompiled(params)))
+            }
+            "glistix/searchByType" => {
+                let params = cast_request::<SearchByType>(request);
+                Some(Message::Request(id, Request::SearchByType(params)))
+            }
+            "glistix/statistics" => {
+                cast_request::<Statistics>(request);
+                Some(Message::Request(id, Request::Statistics))
+            }
             _ => None,
         }
     }
@@ -73,8 +114,15 @@ pub enum Notification {
     SourceFileChangedInMemory { path: Utf8PathBuf, text: String },
     /// A Gleam file has been saved or closed in the editor.
     SourceFileMatchesDisc { path: Utf8PathBuf },
-    /// gleam.toml has changed.
-    ConfigFileChanged { path: Utf8PathBuf },
+    /// Some gleam.toml or manifest.toml files have changed.
+    ConfigFilesChanged { paths: Vec<Utf8PathBuf> },
+    /// The client has changed the language server's settings.
+    SettingsChanged { settings: serde_json::Value },
+    /// Folders have been added to or removed from the editor's workspace.
+    WorkspaceFoldersChanged {
+        added: Vec<Utf8PathBuf>,
+        removed: Vec<Utf8PathBuf>,
+    },
     /// It's time to compile all open projects.
     CompilePlease,
 }
@@ -116,8 +164,34 @@ impl Notification {
 
             "workspace/didChangeWatchedFiles" => {
                 let params = cast_notification::<DidChangeWatchedFiles>(notification);
-                let notification = Notification::ConfigFileChanged {
-                    path: super::path(&params.changes.into_iter().last()?.uri),
+                // The compiler writes the configuration of dependencies to the
+                // build directory, so reacting to those changes would result
+                // in the projects being compiled over and over again.
+                let paths = params
+                    .changes
+                    .iter()
+                    .map(|change| super::path(&change.uri))
+                    .filter(|path| !is_build_output(path))
+                    .unique()
+                    .collect_vec();
+                if paths.is_empty() {
+                    return None;
+                }
+                let notification = Notification::ConfigFilesChanged { paths };
+                Some(Message::Notification(notification))
+            }
+            "workspace/didChangeWorkspaceFolders" => {
+                let params = cast_notification::<DidChangeWorkspaceFolders>(notification);
+                let notification = Notification::WorkspaceFoldersChanged {
+                    added: folder_paths(&params.event.added),
+                    removed: folder_paths(&params.event.removed),
+                };
+                Some(Message::Notification(notification))
+            }
+            "workspace/didChangeConfiguration" => {
+                let params = cast_notification::<DidChangeConfiguration>(notification);
+                let notification = Notification::SettingsChanged {
+                    settings: params.settings,
                 };
                 Some(Message::Notification(notification))
             }
@@ -126,6 +200,24 @@ impl Notification {
     }
 }
 
+pub fn folder_paths(folders: &[lsp::WorkspaceFolder]) -> Vec<Utf8PathBuf> {
+    folders
+        .iter()
+        .map(|folder| super::path(&folder.uri))
+        .collect()
+}
+
+/// Whether the path is within the build directory of a project, where the
+/// compiler writes its outputs and the source code of dependencies.
+fn is_build_output(path: &Utf8Path) -> bool {
+    path.components()
+        .tuple_windows()
+        .any(|(directory, subdirectory)| {
+            directory.as_str() == "build"
+                && matches!(subdirectory.as_str(), "packages" | "dev" | "prod" | "lsp")
+        })
+}
+
 pub enum Next {
     MorePlease,
     Handle(Vec<Message>),
@@ -139,6 +231,10 @@ pub enum Next {
 ///   stopped typing for a moment and would benefit from feedback.
 /// - A request type message is received, which requires an immediate response.
 ///
+/// Changes to watched files tend to come in bursts, such as when switching
+/// branches, so they are merged together and a longer pause is waited for
+/// after them, to only compile once the burst is over.
+///
 pub struct MessageBuffer {
     messages: Vec<Message>,
 }
@@ -151,7 +247,12 @@ impl MessageBuffer {
     }
 
     pub fn receive(&mut self, conn: &lsp_server::Connection) -> Next {
-        let pause = Duration::from_millis(100);
+        let pause = match self.messages.last() {
+            Some(Message::Notification(Notification::ConfigFilesChanged { .. })) => {
+                Duration::from_millis(500)
+            }
+            _ => Duration::from_millis(100),
+        };
 
         // If the buffer is empty, wait indefinitely for the first message.
         // If the buffer is not empty, wait for a short time to see if more messages are
@@ -200,7 +301,7 @@ impl MessageBuffer {
         // A new notification telling us that an edit has been made, or
         // something along those lines.
         if let Some(message) = Notification::extract(n) {
-            self.messages.push(message);
+            self.push_notification(message);
         }
         // Ask for more messages (or a pause), at which point we'll start processing.
         Next::MorePlease
@@ -211,6 +312,24 @@ impl MessageBuffer {
         Next::MorePlease
     }
 
+    fn push_notification(&mut self, message: Message) {
+        // Consecutive changes to watched files are merged so that the projects
+        // they belong to are only reloaded once.
+        if let (
+            Some(Message::Notification(Notification::ConfigFilesChanged { paths })),
+            Message::Notification(Notification::ConfigFilesChanged { paths: new_paths }),
+        ) = (self.messages.last_mut(), &message)
+        {
+            for path in new_paths {
+                if !paths.contains(path) {
+                    paths.push(path.clone());
+                }
+            }
+            return;
+        }
+        self.messages.push(message);
+    }
+
     /// Add a `CompilePlease` message which will prompt the engine to compile
     /// the projects.
     ///
@@ -251,3 +370,76 @@ where
         .extract::<N::Params>(N::METHOD)
         .expect("cast notification")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_files_changed(paths: &[&str]) -> Message {
+        Message::Notification(Notification::ConfigFilesChanged {
+            paths: paths.iter().map(Utf8PathBuf::from).collect(),
+        })
+    }
+
+    fn show_compiled_request(method: &str) -> lsp_server::Request {
+        let params = extensions::ShowCompiledParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::parse("file:///app/src/app.gleam").expect("uri"),
+            },
+            target: None,
+        };
+        lsp_server::Request::new(1.into(), method.into(), params)
+    }
+
+    #[test]
+    fn show_compiled_is_accepted_under_both_names() {
+        for method in ["glistix/showCompiledCode", "glistix/showCompiled"] {
+            assert!(matches!(
+                Request::extract(show_compiled_request(method)),
+                Some(Message::Request(_, Request::ShowCompiled(_)))
+            ));
+        }
+    }
+
+    #[test]
+    fn build_outputs() {
+        assert!(is_build_output(Utf8Path::new(
+            "/app/build/packages/wibble/gleam.toml"
+        )));
+        assert!(is_build_output(Utf8Path::new(
+            "/app/build/dev/nix/app/app.nix"
+        )));
+        assert!(!is_build_output(Utf8Path::new("/app/gleam.toml")));
+        assert!(!is_build_output(Utf8Path::new("/build/app/gleam.toml")));
+    }
+
+    #[test]
+    fn consecutive_config_file_changes_are_merged() {
+        let mut buffer = MessageBuffer::new();
+        buffer.push_notification(config_files_changed(&["/app/gleam.toml"]));
+        buffer.push_notification(config_files_changed(&[
+            "/app/gleam.toml",
+            "/lib/gleam.toml",
+        ]));
+        let expected = vec![
+            Utf8PathBuf::from("/app/gleam.toml"),
+            Utf8PathBuf::from("/lib/gleam.toml"),
+        ];
+        let messages = buffer.take_messages();
+        assert_eq!(messages.len(), 1);
+        assert!(matches!(
+            messages.first(),
+            Some(Message::Notification(Notification::ConfigFilesChanged { paths }))
+                if paths == &expected
+        ));
+    }
+
+    #[test]
+    fn config_file_changes_are_not_merged_across_other_messages() {
+        let mut buffer = MessageBuffer::new();
+        buffer.push_notification(config_files_changed(&["/app/gleam.toml"]));
+        buffer.push_compile_please_message();
+        buffer.push_notification(config_files_changed(&["/app/gleam.toml"]));
+        assert_eq!(buffer.take_messages().len(), 3);
+    }
+}
@@ -1,22 +1,25 @@
 use crate::{
     build::SourceFingerprint,
+    config::{FormatConfig, PackageConfig},
     error::{FileIoAction, FileKind},
     io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter},
     language_server::{
-        engine::LanguageServerEngine, files::FileSystemProxy, progress::ProgressReporter,
-        DownloadDependencies, MakeLocker,
+        configuration::SharedConfig, engine::LanguageServerEngine, files::FileSystemProxy,
+        progress::ProgressReporter, statistics::SharedStatistics, DownloadDependencies, MakeLocker,
     },
     paths::ProjectPaths,
     Error, Result,
 };
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     time::SystemTime,
 };
 
 use camino::{Utf8Path, Utf8PathBuf};
 
-use super::feedback::FeedbackBookKeeper;
+use itertools::Itertools;
+
+use super::feedback::{Feedback, FeedbackBookKeeper};
 
 /// The language server instance serves a language client, typically a text
 /// editor. The editor could have multiple Gleam projects open at once, so run
@@ -30,6 +33,8 @@ pub(crate) struct Router<IO, Reporter> {
     io: FileSystemProxy<IO>,
     engines: HashMap<Utf8PathBuf, Project<IO, Reporter>>,
     progress_reporter: Reporter,
+    user_config: SharedConfig,
+    statistics: SharedStatistics,
 }
 
 impl<IO, Reporter> Router<IO, Reporter>
@@ -45,11 +50,18 @@ where
     // IO to be supplied from inside of gleam-core
     Reporter: ProgressReporter + Clone,
 {
-    pub fn new(progress_reporter: Reporter, io: FileSystemProxy<IO>) -> Self {
+    pub fn new(
+        progress_reporter: Reporter,
+        io: FileSystemProxy<IO>,
+        user_config: SharedConfig,
+        statistics: SharedStatistics,
+    ) -> Self {
         Self {
             io,
             engines: HashMap::new(),
             progress_reporter,
+            user_config,
+            statistics,
         }
     }
 
@@ -57,6 +69,16 @@ where
         find_gleam_project_parent(&self.io, path)
     }
 
+    /// The formatting settings of the project a file belongs to, read from
+    /// its `gleam.toml` without starting an engine for it. Files outside of
+    /// a project are formatted with the default settings.
+    pub fn format_config(&self, path: &Utf8Path) -> Result<FormatConfig> {
+        match find_gleam_project_parent(&self.io, path) {
+            Some(root) => Ok(read_config(&self.io, ProjectPaths::new(root).root_config())?.format),
+            None => Ok(FormatConfig::default()),
+        }
+    }
+
     pub fn project_for_path(
         &mut self,
         path: Utf8PathBuf,
@@ -72,11 +94,11 @@ where
             path
         };
 
-        // If the gleam.toml has changed then discard the project as the target,
-        // deps, etc may have changed and we need to rebuild taking them into
-        // account.
+        // If the gleam.toml or manifest.toml has changed then discard the
+        // project as the target, deps, etc may have changed and we need to
+        // rebuild taking them into account.
         if let Some(project) = self.engines.get(&path) {
-            if Self::gleam_toml_changed(&path, project, &self.io)? {
+            if Self::config_changed(&path, project, &self.io)? {
                 let _ = self.engines.remove(&path);
             }
         }
@@ -85,68 +107,134 @@ where
         Ok(Some(match self.engines.entry(path.clone()) {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => {
-                let project =
-                    Self::new_project(path, self.io.clone(), self.progress_reporter.clone())?;
+                let project = Self::new_project(
+                    path,
+                    self.io.clone(),
+                    self.progress_reporter.clone(),
+                    self.user_config.clone(),
+                    self.statistics.clone(),
+                )?;
                 entry.insert(project)
             }
         }))
     }
 
-    /// Has gleam.toml changed since the last time we saw this project?
-    fn gleam_toml_changed(
+    /// Has gleam.toml or manifest.toml changed since the last time we saw
+    /// this project?
+    fn config_changed(
         path: &Utf8PathBuf,
         project: &Project<IO, Reporter>,
         io: &FileSystemProxy<IO>,
     ) -> Result<bool, Error> {
-        // Get the location of gleam.toml for this project
         let paths = ProjectPaths::new(path.clone());
-        let config_path = paths.root_config();
+        Ok(project.gleam_toml.changed(io, &paths.root_config())?
+            || project.manifest_toml.changed(io, &paths.manifest())?)
+    }
 
-        // See if the file modification time has changed.
-        if io.modification_time(path)? == project.gleam_toml_modification_time {
-            return Ok(false); // Not changed
-        }
+    /// The root paths of all the projects with a running engine.
+    pub fn project_paths(&self) -> Vec<Utf8PathBuf> {
+        self.engines.keys().cloned().collect()
+    }
 
-        // The mtime has changed. This might not be a content change, so let's
-        // check the hash.
-        let toml = io.read(&config_path)?;
-        let gleam_toml_changed = project.gleam_toml_fingerprint != SourceFingerprint::new(&toml);
-        Ok(gleam_toml_changed)
+    /// Stops the engine of the project a changed `gleam.toml` or
+    /// `manifest.toml` belongs to, so that it's created again with the new
+    /// config and dependencies the next time the project is compiled.
+    ///
+    /// Returns the root of the project if it's still there to be compiled,
+    /// along with the feedback removing the diagnostics it had reported.
+    pub fn reload_project_for_config(
+        &mut self,
+        path: &Utf8Path,
+    ) -> (Option<Utf8PathBuf>, Feedback) {
+        // The config of a project that has just been deleted can't be found
+        // by looking for the nearest gleam.toml.
+        let root = match config_file_project(path) {
+            Some(root) if self.engines.contains_key(root) => Some(root.to_path_buf()),
+            Some(_) | None => find_gleam_project_parent(&self.io, path),
+        };
+        let Some(root) = root else {
+            return (None, Feedback::none());
+        };
+
+        tracing::info!(?root, "reloading_language_server_engine");
+        let feedback = match self.engines.remove(&root) {
+            Some(mut project) => project.feedback.clear(),
+            None => Feedback::none(),
+        };
+        let root = self.io.is_file(&root.join("gleam.toml")).then_some(root);
+        (root, feedback)
+    }
+
+    /// The roots of the outermost projects within a workspace folder opened by
+    /// the client, so that they can be compiled before any of their files are.
+    pub fn projects_in_folder(&self, folder: &Utf8Path) -> Vec<Utf8PathBuf> {
+        find_gleam_projects(&self.io, folder)
     }
 
-    pub fn delete_engine_for_path(&mut self, path: &Utf8Path) {
-        if let Some(path) = find_gleam_project_parent(&self.io, path) {
-            _ = self.engines.remove(&path);
+    /// Stops the engines of the projects within a workspace folder closed by
+    /// the client, removing the diagnostics they have reported.
+    pub fn delete_engines_in_folder(&mut self, folder: &Utf8Path) -> Feedback {
+        let mut feedback = Feedback::none();
+        let paths = self
+            .engines
+            .keys()
+            .filter(|path| path.starts_with(folder))
+            .cloned()
+            .collect_vec();
+        for path in paths {
+            if let Some(mut project) = self.engines.remove(&path) {
+                feedback.append_feedback(project.feedback.clear());
+            }
         }
+        feedback
     }
 
     fn new_project(
         path: Utf8PathBuf,
         io: FileSystemProxy<IO>,
         progress_reporter: Reporter,
+        user_config: SharedConfig,
+        statistics: SharedStatistics,
     ) -> Result<Project<IO, Reporter>, Error> {
         tracing::info!(?path, "creating_new_language_server_engine");
         let paths = ProjectPaths::new(path);
         let config_path = paths.root_config();
-        let modification_time = io.modification_time(&config_path)?;
-        let toml = io.read(&config_path)?;
-        let config = toml::from_str(&toml).map_err(|e| Error::FileIo {
-            action: FileIoAction::Parse,
-            kind: FileKind::File,
-            path: config_path,
-            err: Some(e.to_string()),
-        })?;
-        let engine = LanguageServerEngine::new(config, progress_reporter, io, paths)?;
+        let gleam_toml = WatchedFile::read(&io, &config_path)?;
+        let config = read_config(&io, config_path)?;
+        let engine = LanguageServerEngine::new(
+            config,
+            progress_reporter,
+            io.clone(),
+            paths.clone(),
+            user_config,
+            statistics,
+        )?;
+        // The manifest is read once the engine has been created as creating
+        // it may have resolved the dependencies again, updating the manifest.
+        let manifest_toml = WatchedFile::read(&io, &paths.manifest())?;
         let project = Project {
             engine,
             feedback: FeedbackBookKeeper::default(),
-            gleam_toml_modification_time: modification_time,
-            gleam_toml_fingerprint: SourceFingerprint::new(&toml),
+            gleam_toml,
+            manifest_toml,
         };
         Ok(project)
     }
 }
 
+fn read_config<IO>(io: &IO, path: Utf8PathBuf) -> Result<PackageConfig>
+where
+    IO: FileSystemReader,
+{
+    let toml = io.read(&path)?;
+    PackageConfig::from_toml(&toml).map_err(|e| Error::FileIo {
+        action: FileIoAction::Parse,
+        kind: FileKind::File,
+        path,
+        err: Some(e.to_string()),
+    })
+}
+
 /// Given a given path, find the nearest parent directory containing a
 /// `gleam.toml` file.
 ///
@@ -183,12 +271,110 @@ where
     None
 }
 
+/// Find the roots of the outermost projects within a directory, including the
+/// directory itself. Projects nested within another project, such as its
+/// examples, are left to be found once one of their files is opened, so that
+/// opening a folder doesn't compile every project it holds.
+///
+/// Build directories, hidden directories, the `external` directories where
+/// Glistix projects keep their Git dependencies and the `result` links made by
+/// `nix build` are not searched, and a directory reached again through a
+/// symbolic link is only searched once.
+fn find_gleam_projects<IO>(io: &IO, directory: &Utf8Path) -> Vec<Utf8PathBuf>
+where
+    IO: FileSystemReader,
+{
+    let mut projects = vec![];
+    let mut visited = HashSet::new();
+    let mut directories = vec![directory.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        let canonical = io
+            .canonicalise(&directory)
+            .unwrap_or_else(|_| directory.clone());
+        if !visited.insert(canonical) {
+            continue;
+        }
+
+        if io.is_file(&directory.join("gleam.toml")) {
+            projects.push(directory);
+            continue;
+        }
+
+        let Ok(entries) = io.read_dir(&directory) else {
+            continue;
+        };
+        for entry in entries.into_iter().filter_map(Result::ok) {
+            let path = entry.into_path();
+            let skipped = path.file_name().is_none_or(|name| {
+                name.starts_with('.')
+                    || name.starts_with("result")
+                    || matches!(name, "build" | "external" | "node_modules")
+            });
+            if !skipped && io.is_directory(&path) {
+                directories.push(path);
+            }
+        }
+    }
+
+    projects.sort();
+    projects
+}
+
+/// The project a `gleam.toml` or `manifest.toml` file is the config of.
+fn config_file_project(path: &Utf8Path) -> Option<&Utf8Path> {
+    match path.file_name() {
+        Some("gleam.toml" | "manifest.toml") => path.parent(),
+        Some(_) | None => None,
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct Project<A, B> {
     pub engine: LanguageServerEngine<A, B>,
     pub feedback: FeedbackBookKeeper,
-    pub gleam_toml_modification_time: SystemTime,
-    pub gleam_toml_fingerprint: SourceFingerprint,
+    pub gleam_toml: WatchedFile,
+    pub manifest_toml: WatchedFile,
+}
+
+/// What a config file of a project looked like when its engine was created,
+/// used to tell whether it has changed since. A file which doesn't exist has
+/// no modification time nor fingerprint.
+#[derive(Debug)]
+pub(crate) struct WatchedFile {
+    modification_time: Option<SystemTime>,
+    fingerprint: Option<SourceFingerprint>,
+}
+
+impl WatchedFile {
+    fn read<IO: FileSystemReader>(io: &IO, path: &Utf8Path) -> Result<Self> {
+        if !io.is_file(path) {
+            return Ok(Self {
+                modification_time: None,
+                fingerprint: None,
+            });
+        }
+        Ok(Self {
+            modification_time: Some(io.modification_time(path)?),
+            fingerprint: Some(SourceFingerprint::new(&io.read(path)?)),
+        })
+    }
+
+    fn changed<IO: FileSystemReader>(&self, io: &IO, path: &Utf8Path) -> Result<bool> {
+        if !io.is_file(path) {
+            return Ok(self.fingerprint.is_some());
+        }
+
+        // See if the file modification time has changed.
+        if Some(io.modification_time(path)?) == self.modification_time {
+            return Ok(false); // Not changed
+        }
+
+        // The mtime has changed. This might not be a content change, so let's
+        // check the hash.
+        let contents = io.read(path)?;
+        Ok(self.fingerprint.as_ref() != Some(&SourceFingerprint::new(&contents)))
+    }
 }
 
 #[cfg(test)]
@@ -278,3 +464,139 @@ mod find_gleam_project_parent_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod find_gleam_projects_tests {
+    use super::*;
+    use crate::io::{memory::InMemoryFileSystem, FileSystemWriter};
+
+    #[test]
+    fn no_projects() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/workspace/README.md"), "").unwrap();
+        assert_eq!(
+            find_gleam_projects(&io, Utf8Path::new("/workspace")),
+            Vec::<Utf8PathBuf>::new()
+        );
+    }
+
+    #[test]
+    fn folder_is_a_project() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/app/gleam.toml"), "").unwrap();
+        io.write(Utf8Path::new("/app/src/app.gleam"), "").unwrap();
+        assert_eq!(
+            find_gleam_projects(&io, Utf8Path::new("/app")),
+            vec![Utf8PathBuf::from("/app")]
+        );
+    }
+
+    #[test]
+    fn monorepo() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/repo/README.md"), "").unwrap();
+        io.write(Utf8Path::new("/repo/packages/one/gleam.toml"), "")
+            .unwrap();
+        io.write(Utf8Path::new("/repo/packages/two/gleam.toml"), "")
+            .unwrap();
+        assert_eq!(
+            find_gleam_projects(&io, Utf8Path::new("/repo")),
+            vec![
+                Utf8PathBuf::from("/repo/packages/one"),
+                Utf8PathBuf::from("/repo/packages/two"),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_projects_are_not_searched() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/repo/gleam.toml"), "").unwrap();
+        io.write(Utf8Path::new("/repo/examples/one/gleam.toml"), "")
+            .unwrap();
+        assert_eq!(
+            find_gleam_projects(&io, Utf8Path::new("/repo")),
+            vec![Utf8PathBuf::from("/repo")]
+        );
+    }
+
+    #[test]
+    fn dependencies_are_skipped() {
+        let io = InMemoryFileSystem::new();
+        io.write(Utf8Path::new("/repo/external/stdlib/gleam.toml"), "")
+            .unwrap();
+        io.write(Utf8Path::new("/repo/build/packages/wibble/gleam.toml"), "")
+            .unwrap();
+        io.write(Utf8Path::new("/repo/.git/modules/gleam.toml"), "")
+            .unwrap();
+        io.write(Utf8Path::new("/repo/result/share/gleam.toml"), "")
+            .unwrap();
+        io.write(Utf8Path::new("/repo/result-docs/gleam.toml"), "")
+            .unwrap();
+        io.write(Utf8Path::new("/repo/app/gleam.toml"), "").unwrap();
+        assert_eq!(
+            find_gleam_projects(&io, Utf8Path::new("/repo")),
+            vec![Utf8PathBuf::from("/repo/app")]
+        );
+    }
+}
+
+#[cfg(test)]
+mod watched_file_tests {
+    use super::*;
+    use crate::io::{memory::InMemoryFileSystem, FileSystemWriter};
+    use std::time::Duration;
+
+    #[test]
+    fn config_file_project_of_gleam_toml() {
+        assert_eq!(
+            config_file_project(Utf8Path::new("/app/gleam.toml")),
+            Some(Utf8Path::new("/app"))
+        );
+        assert_eq!(
+            config_file_project(Utf8Path::new("/app/manifest.toml")),
+            Some(Utf8Path::new("/app"))
+        );
+        assert_eq!(
+            config_file_project(Utf8Path::new("/app/src/app.gleam")),
+            None
+        );
+    }
+
+    #[test]
+    fn unchanged() {
+        let io = InMemoryFileSystem::new();
+        let path = Utf8Path::new("/app/manifest.toml");
+        io.write(path, "packages = []").unwrap();
+        let file = WatchedFile::read(&io, path).unwrap();
+        io.set_modification_time(path, SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+        assert!(!file.changed(&io, path).unwrap());
+    }
+
+    #[test]
+    fn changed_contents() {
+        let io = InMemoryFileSystem::new();
+        let path = Utf8Path::new("/app/manifest.toml");
+        io.write(path, "packages = []").unwrap();
+        let file = WatchedFile::read(&io, path).unwrap();
+        io.write(path, "packages = [{ name = \"wibble\" }]")
+            .unwrap();
+        io.set_modification_time(path, SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+        assert!(file.changed(&io, path).unwrap());
+    }
+
+    #[test]
+    fn created_and_deleted() {
+        let io = InMemoryFileSystem::new();
+        let path = Utf8Path::new("/app/manifest.toml");
+        let missing = WatchedFile::read(&io, path).unwrap();
+        assert!(!missing.changed(&io, path).unwrap());
+
+        io.write(path, "packages = []").unwrap();
+        assert!(missing.changed(&io, path).unwrap());
+
+        let file = WatchedFile::read(&io, path).unwrap();
+        io.delete_file(path).unwrap();
+        assert!(file.changed(&io, path).unwrap());
+    }
+}
@@ -0,0 +1,665 @@
+use std::sync::Arc;
+
+use ecow::EcoString;
+use lsp_types::{SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend};
+
+use crate::{
+    analyse::Inferred,
+    ast::{
+        visit::{self, Visit},
+        ArgNames, AssignName, CallArg, Constant, Definition, FunctionLiteralKind, Import, SrcSpan,
+        TypeAst, TypedArg, TypedAssignment, TypedConstant, TypedCustomType, TypedDefinition,
+        TypedExpr, TypedFunction, TypedModuleConstant, TypedPattern, TypedStatement,
+    },
+    build::Module,
+    line_numbers::LineNumbers,
+    type_::{
+        error::VariableOrigin, ModuleInterface, ModuleValueConstructor, PatternConstructor, Type,
+        TypedCallArg, ValueConstructor, ValueConstructorVariant,
+    },
+};
+
+/// The kinds of tokens given to the client. Each is identified by its index
+/// in the legend, which is the order they are declared in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenType {
+    Namespace,
+    Type,
+    TypeParameter,
+    EnumMember,
+    Function,
+    Parameter,
+    Variable,
+    Property,
+}
+
+impl TokenType {
+    const ALL: [TokenType; 8] = [
+        TokenType::Namespace,
+        TokenType::Type,
+        TokenType::TypeParameter,
+        TokenType::EnumMember,
+        TokenType::Function,
+        TokenType::Parameter,
+        TokenType::Variable,
+        TokenType::Property,
+    ];
+
+    fn lsp_type(self) -> SemanticTokenType {
+        match self {
+            TokenType::Namespace => SemanticTokenType::NAMESPACE,
+            TokenType::Type => SemanticTokenType::TYPE,
+            TokenType::TypeParameter => SemanticTokenType::TYPE_PARAMETER,
+            TokenType::EnumMember => SemanticTokenType::ENUM_MEMBER,
+            TokenType::Function => SemanticTokenType::FUNCTION,
+            TokenType::Parameter => SemanticTokenType::PARAMETER,
+            TokenType::Variable => SemanticTokenType::VARIABLE,
+            TokenType::Property => SemanticTokenType::PROPERTY,
+        }
+    }
+}
+
+/// The modifiers of a token, as a set of bits each corresponding to the
+/// modifier at the same index in the legend.
+const NO_MODIFIERS: u32 = 0;
+const DECLARATION: u32 = 1 << 0;
+const READONLY: u32 = 1 << 1;
+
+/// The token types and modifiers the language server uses, given to the
+/// client when it initialises.
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TokenType::ALL
+            .into_iter()
+            .map(TokenType::lsp_type)
+            .collect(),
+        token_modifiers: vec![
+            SemanticTokenModifier::DECLARATION,
+            SemanticTokenModifier::READONLY,
+        ],
+    }
+}
+
+/// The semantic tokens of a module, encoded relative to each other as the
+/// client expects them.
+///
+pub fn semantic_tokens(
+    module: &Module,
+    importable_modules: &im::HashMap<EcoString, ModuleInterface>,
+) -> Vec<SemanticToken> {
+    let mut collector = TokenCollector {
+        code: &module.code,
+        importable_modules,
+        tokens: vec![],
+    };
+    collector.visit_typed_module(&module.ast);
+    encode(collector.tokens, &LineNumbers::new(&module.code))
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    start: u32,
+    length: u32,
+    type_: TokenType,
+    modifiers: u32,
+}
+
+fn encode(mut tokens: Vec<Token>, line_numbers: &LineNumbers) -> Vec<SemanticToken> {
+    tokens.sort_by_key(|token| token.start);
+
+    let mut encoded = Vec::with_capacity(tokens.len());
+    let mut previous_line = 0;
+    let mut previous_column = 0;
+    let mut previous_end = 0;
+    for token in tokens {
+        // Clients don't support overlapping tokens, so only the first one is
+        // kept.
+        if token.start < previous_end {
+            continue;
+        }
+        previous_end = token.start + token.length;
+
+        let position = line_numbers.line_and_column_number(token.start);
+        let line = position.line - 1;
+        let column = position.column - 1;
+        let delta_start = if line == previous_line {
+            column - previous_column
+        } else {
+            column
+        };
+        encoded.push(SemanticToken {
+            delta_line: line - previous_line,
+            delta_start,
+            length: token.length,
+            token_type: token.type_ as u32,
+            token_modifiers_bitset: token.modifiers,
+        });
+        previous_line = line;
+        previous_column = column;
+    }
+    encoded
+}
+
+struct TokenCollector<'a> {
+    code: &'a str,
+    importable_modules: &'a im::HashMap<EcoString, ModuleInterface>,
+    tokens: Vec<Token>,
+}
+
+impl TokenCollector<'_> {
+    /// Adds a token for the given text, starting at the given byte index.
+    ///
+    /// Some nodes of the typed AST are generated by the compiler rather than
+    /// written by the programmer, like the variables introduced by pipelines
+    /// and function captures, so the token is only added if the text is
+    /// actually there in the code.
+    fn push(&mut self, start: u32, text: &str, type_: TokenType, modifiers: u32) {
+        let end = start as usize + text.len();
+        if text.is_empty() || self.code.get(start as usize..end) != Some(text) {
+            return;
+        }
+        self.tokens.push(Token {
+            start,
+            length: text.len() as u32,
+            type_,
+            modifiers,
+        });
+    }
+
+    /// Adds the tokens for a module qualified name, like `option.Some`.
+    fn push_qualified(
+        &mut self,
+        location: SrcSpan,
+        module: &Option<(EcoString, SrcSpan)>,
+        name: &str,
+        type_: TokenType,
+        modifiers: u32,
+    ) {
+        let name_start = match module {
+            Some((module, module_location)) => {
+                self.push(
+                    module_location.start,
+                    module,
+                    TokenType::Namespace,
+                    NO_MODIFIERS,
+                );
+                module_location.end + 1
+            }
+            None => location.start,
+        };
+        self.push(name_start, name, type_, modifiers);
+    }
+
+    fn arguments(&mut self, arguments: &[TypedArg]) {
+        for argument in arguments {
+            match &argument.names {
+                ArgNames::Named { name, location } => {
+                    self.push(location.start, name, TokenType::Parameter, DECLARATION);
+                }
+                ArgNames::NamedLabelled {
+                    label,
+                    label_location,
+                    name,
+                    name_location,
+                } => {
+                    self.push(
+                        label_location.start,
+                        label,
+                        TokenType::Parameter,
+                        NO_MODIFIERS,
+                    );
+                    self.push(name_location.start, name, TokenType::Parameter, DECLARATION);
+                }
+                ArgNames::LabelledDiscard {
+                    label,
+                    label_location,
+                    ..
+                } => {
+                    self.push(
+                        label_location.start,
+                        label,
+                        TokenType::Parameter,
+                        NO_MODIFIERS,
+                    );
+                }
+                ArgNames::Discard { .. } => {}
+            }
+            if let Some(annotation) = &argument.annotation {
+                self.visit_type_ast(annotation);
+            }
+        }
+    }
+
+    /// Adds tokens for the module and function names of an `@external`
+    /// attribute.
+    fn external(&mut self, location: SrcSpan) {
+        let code = self.code;
+        let Some(text) = code.get(location.start as usize..location.end as usize) else {
+            return;
+        };
+
+        let mut strings = vec![];
+        let mut characters = text.char_indices();
+        while let Some((start, character)) = characters.next() {
+            if character != '"' {
+                continue;
+            }
+            let mut escaped = false;
+            for (index, character) in characters.by_ref() {
+                match character {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => {
+                        strings.extend(text.get(start..index + 1).map(|string| (start, string)));
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let [(module_start, module), (function_start, function)] = strings[..] {
+            self.push(
+                location.start + module_start as u32,
+                module,
+                TokenType::Namespace,
+                NO_MODIFIERS,
+            );
+            self.push(
+                location.start + function_start as u32,
+                function,
+                TokenType::Function,
+                NO_MODIFIERS,
+            );
+        }
+    }
+
+    fn constant(&mut self, constant: &TypedConstant) {
+        match constant {
+            Constant::Int { .. }
+            | Constant::Float { .. }
+            | Constant::String { .. }
+            | Constant::Invalid { .. } => {}
+
+            Constant::Tuple { elements, .. } | Constant::List { elements, .. } => {
+                elements.iter().for_each(|element| self.constant(element));
+            }
+
+            Constant::Record {
+                location,
+                module,
+                name,
+                args,
+                ..
+            } => {
+                self.push_qualified(*location, module, name, TokenType::EnumMember, NO_MODIFIERS);
+                for argument in args {
+                    self.call_arg_label(argument);
+                    self.constant(&argument.value);
+                }
+            }
+
+            Constant::BitArray { segments, .. } => {
+                segments
+                    .iter()
+                    .for_each(|segment| self.constant(&segment.value));
+            }
+
+            Constant::Var {
+                location,
+                module,
+                name,
+                constructor,
+                ..
+            } => {
+                let (type_, modifiers) = constructor
+                    .as_ref()
+                    .map(|constructor| value_token(&constructor.variant))
+                    .unwrap_or((TokenType::Variable, READONLY));
+                self.push_qualified(*location, module, name, type_, modifiers);
+            }
+
+            Constant::StringConcatenation { left, right, .. } => {
+                self.constant(left);
+                self.constant(right);
+            }
+        }
+    }
+
+    fn call_arg_label<A>(&mut self, argument: &CallArg<A>) {
+        if let (Some(label), None) = (&argument.label, argument.implicit) {
+            self.push(
+                argument.location.start,
+                label,
+                TokenType::Parameter,
+                NO_MODIFIERS,
+            );
+        }
+    }
+
+    fn import(&mut self, import: &Import<EcoString>) {
+        let start = import.location.start as usize;
+        let end = import.location.end as usize;
+        if let Some(module_start) = self
+            .code
+            .get(start..end)
+            .and_then(|text| text.strip_prefix("import"))
+            .and_then(|text| text.find(import.module.as_str()))
+        {
+            let module_start = import.location.start + "import".len() as u32 + module_start as u32;
+            self.push(
+                module_start,
+                &import.module,
+                TokenType::Namespace,
+                NO_MODIFIERS,
+            );
+        }
+        // The location of the alias includes the `as` keyword before it.
+        if let Some((AssignName::Variable(name), location)) = &import.as_name {
+            let name_start = location.end - name.len() as u32;
+            self.push(name_start, name, TokenType::Namespace, DECLARATION);
+        }
+
+        let module = self.importable_modules.get(&import.module);
+        for value in &import.unqualified_values {
+            let (type_, modifiers) = module
+                .and_then(|module| module.values.get(&value.name))
+                .map(|constructor| value_token(&constructor.variant))
+                .unwrap_or(if value.name.starts_with(char::is_uppercase) {
+                    (TokenType::EnumMember, NO_MODIFIERS)
+                } else {
+                    (TokenType::Function, NO_MODIFIERS)
+                });
+            self.push(value.location.start, &value.name, type_, modifiers);
+            if let Some(alias) = &value.as_name {
+                let alias_start = value.location.end - alias.len() as u32;
+                self.push(alias_start, alias, type_, modifiers | DECLARATION);
+            }
+        }
+        for type_ in &import.unqualified_types {
+            // Unqualified types start with the `type` keyword, which can't
+            // contain their capitalised name.
+            let name_start = self
+                .code
+                .get(type_.location.start as usize..type_.location.end as usize)
+                .and_then(|text| text.find(type_.name.as_str()))
+                .map(|offset| type_.location.start + offset as u32);
+            if let Some(name_start) = name_start {
+                self.push(name_start, &type_.name, TokenType::Type, NO_MODIFIERS);
+            }
+            if let Some(alias) = &type_.as_name {
+                let alias_start = type_.location.end - alias.len() as u32;
+                self.push(alias_start, alias, TokenType::Type, DECLARATION);
+            }
+        }
+    }
+}
+
+fn value_token(variant: &ValueConstructorVariant) -> (TokenType, u32) {
+    match variant {
+        ValueConstructorVariant::LocalVariable { .. } => (TokenType::Variable, NO_MODIFIERS),
+        ValueConstructorVariant::ModuleConstant { .. }
+        | ValueConstructorVariant::LocalConstant { .. } => (TokenType::Variable, READONLY),
+        ValueConstructorVariant::ModuleFn { .. } => (TokenType::Function, NO_MODIFIERS),
+        ValueConstructorVariant::Record { .. } => (TokenType::EnumMember, NO_MODIFIERS),
+    }
+}
+
+fn module_value_token(constructor: &ModuleValueConstructor) -> (TokenType, u32) {
+    match constructor {
+        ModuleValueConstructor::Record { .. } => (TokenType::EnumMember, NO_MODIFIERS),
+        ModuleValueConstructor::Fn { .. } => (TokenType::Function, NO_MODIFIERS),
+        ModuleValueConstructor::Constant { .. } => (TokenType::Variable, READONLY),
+    }
+}
+
+impl<'ast> Visit<'ast> for TokenCollector<'_> {
+    fn visit_typed_definition(&mut self, definition: &'ast TypedDefinition) {
+        match definition {
+            Definition::Function(function) => self.visit_typed_function(function),
+            Definition::CustomType(custom_type) => self.visit_typed_custom_type(custom_type),
+            Definition::ModuleConstant(constant) => self.visit_typed_module_constant(constant),
+            Definition::Import(import) => self.import(import),
+            Definition::TypeAlias(alias) => {
+                self.push(
+                    alias.name_location.start,
+                    &alias.alias,
+                    TokenType::Type,
+                    DECLARATION,
+                );
+                for (location, name) in &alias.parameters {
+                    self.push(location.start, name, TokenType::TypeParameter, DECLARATION);
+                }
+                self.visit_type_ast(&alias.type_ast);
+            }
+        }
+    }
+
+    fn visit_typed_function(&mut self, function: &'ast TypedFunction) {
+        if let Some((location, name)) = &function.name {
+            self.push(location.start, name, TokenType::Function, DECLARATION);
+        }
+        self.arguments(&function.arguments);
+        if let Some(annotation) = &function.return_annotation {
+            self.visit_type_ast(annotation);
+        }
+        let externals = [
+            &function.external_erlang,
+            &function.external_javascript,
+            &function.external_nix,
+        ];
+        // Targets falling back to an `any` external share its location, so
+        // each attribute is only highlighted once.
+        let mut locations = vec![];
+        for (_, _, location) in externals.into_iter().flatten() {
+            if !locations.contains(location) {
+                locations.push(*location);
+                self.external(*location);
+            }
+        }
+        visit::visit_typed_function(self, function);
+    }
+
+    fn visit_typed_module_constant(&mut self, constant: &'ast TypedModuleConstant) {
+        self.push(
+            constant.name_location.start,
+            &constant.name,
+            TokenType::Variable,
+            DECLARATION | READONLY,
+        );
+        if let Some(annotation) = &constant.annotation {
+            self.visit_type_ast(annotation);
+        }
+        self.constant(&constant.value);
+    }
+
+    fn visit_typed_custom_type(&mut self, custom_type: &'ast TypedCustomType) {
+        self.push(
+            custom_type.name_location.start,
+            &custom_type.name,
+            TokenType::Type,
+            DECLARATION,
+        );
+        for (location, name) in &custom_type.parameters {
+            self.push(location.start, name, TokenType::TypeParameter, DECLARATION);
+        }
+        for constructor in &custom_type.constructors {
+            self.push(
+                constructor.name_location.start,
+                &constructor.name,
+                TokenType::EnumMember,
+                DECLARATION,
+            );
+            for argument in &constructor.arguments {
+                if let Some((location, label)) = &argument.label {
+                    self.push(location.start, label, TokenType::Property, DECLARATION);
+                }
+                self.visit_type_ast(&argument.ast);
+            }
+        }
+    }
+
+    fn visit_typed_expr_var(
+        &mut self,
+        location: &'ast SrcSpan,
+        constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        let (type_, modifiers) = value_token(&constructor.variant);
+        self.push(location.start, name, type_, modifiers);
+    }
+
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        args: &'ast [TypedArg],
+        body: &'ast [TypedStatement],
+        return_annotation: &'ast Option<TypeAst>,
+    ) {
+        self.arguments(args);
+        if let Some(annotation) = return_annotation {
+            self.visit_type_ast(annotation);
+        }
+        visit::visit_typed_expr_fn(self, location, type_, kind, args, body, return_annotation);
+    }
+
+    fn visit_typed_expr_record_access(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        label: &'ast EcoString,
+        index: &'ast u64,
+        record: &'ast TypedExpr,
+    ) {
+        let label_start = location.end - label.len() as u32;
+        self.push(label_start, label, TokenType::Property, NO_MODIFIERS);
+        visit::visit_typed_expr_record_access(self, location, type_, label, index, record);
+    }
+
+    fn visit_typed_expr_module_select(
+        &mut self,
+        location: &'ast SrcSpan,
+        _type_: &'ast Arc<Type>,
+        label: &'ast EcoString,
+        _module_name: &'ast EcoString,
+        module_alias: &'ast EcoString,
+        constructor: &'ast ModuleValueConstructor,
+    ) {
+        // The location of a module select starts at the `.` after the module.
+        let module_start = location.start.saturating_sub(module_alias.len() as u32);
+        self.push(
+            module_start,
+            module_alias,
+            TokenType::Namespace,
+            NO_MODIFIERS,
+        );
+        let (type_, modifiers) = module_value_token(constructor);
+        self.push(location.end - label.len() as u32, label, type_, modifiers);
+    }
+
+    fn visit_typed_assignment(&mut self, assignment: &'ast TypedAssignment) {
+        if let Some(annotation) = &assignment.annotation {
+            self.visit_type_ast(annotation);
+        }
+        visit::visit_typed_assignment(self, assignment);
+    }
+
+    fn visit_typed_call_arg(&mut self, argument: &'ast TypedCallArg) {
+        self.call_arg_label(argument);
+        visit::visit_typed_call_arg(self, argument);
+    }
+
+    fn visit_typed_pattern_variable(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        _type_: &'ast Arc<Type>,
+        _origin: &'ast VariableOrigin,
+    ) {
+        self.push(location.start, name, TokenType::Variable, DECLARATION);
+    }
+
+    fn visit_typed_pattern_var_usage(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        _constructor: &'ast Option<ValueConstructor>,
+        _type_: &'ast Arc<Type>,
+    ) {
+        self.push(location.start, name, TokenType::Variable, NO_MODIFIERS);
+    }
+
+    fn visit_typed_pattern_assign(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        pattern: &'ast TypedPattern,
+    ) {
+        let name_start = location.end - name.len() as u32;
+        self.push(name_start, name, TokenType::Variable, DECLARATION);
+        visit::visit_typed_pattern_assign(self, location, name, pattern);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn visit_typed_pattern_constructor(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        arguments: &'ast Vec<CallArg<TypedPattern>>,
+        module: &'ast Option<(EcoString, SrcSpan)>,
+        constructor: &'ast Inferred<PatternConstructor>,
+        spread: &'ast Option<SrcSpan>,
+        type_: &'ast Arc<Type>,
+    ) {
+        self.push_qualified(*location, module, name, TokenType::EnumMember, NO_MODIFIERS);
+        visit::visit_typed_pattern_constructor(
+            self,
+            location,
+            name,
+            arguments,
+            module,
+            constructor,
+            spread,
+            type_,
+        );
+    }
+
+    fn visit_typed_pattern_call_arg(&mut self, argument: &'ast CallArg<TypedPattern>) {
+        self.call_arg_label(argument);
+        visit::visit_typed_pattern_call_arg(self, argument);
+    }
+
+    fn visit_typed_pattern_string_prefix(
+        &mut self,
+        _location: &'ast SrcSpan,
+        _left_location: &'ast SrcSpan,
+        left_side_assignment: &'ast Option<(EcoString, SrcSpan)>,
+        right_location: &'ast SrcSpan,
+        _left_side_string: &'ast EcoString,
+        right_side_assignment: &'ast AssignName,
+    ) {
+        if let Some((name, location)) = left_side_assignment {
+            self.push(location.start, name, TokenType::Variable, DECLARATION);
+        }
+        if let AssignName::Variable(name) = right_side_assignment {
+            self.push(right_location.start, name, TokenType::Variable, DECLARATION);
+        }
+    }
+
+    fn visit_type_ast_constructor(
+        &mut self,
+        location: &'ast SrcSpan,
+        module: &'ast Option<(EcoString, SrcSpan)>,
+        name: &'ast EcoString,
+        arguments: &'ast Vec<TypeAst>,
+    ) {
+        self.push_qualified(*location, module, name, TokenType::Type, NO_MODIFIERS);
+        visit::visit_type_ast_constructor(self, location, module, name, arguments);
+    }
+
+    fn visit_type_ast_var(&mut self, location: &'ast SrcSpan, name: &'ast EcoString) {
+        self.push(location.start, name, TokenType::TypeParameter, NO_MODIFIERS);
+    }
+}
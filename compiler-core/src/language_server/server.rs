@@ -6,11 +6,16 @@ use crate::{
     diagnostic::{Diagnostic, Level},
     io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter},
     language_server::{
+        configuration::{Configuration, SharedConfig},
         engine::{self, LanguageServerEngine},
+        extensions::{SearchByTypeParams, ShowCompiledParams},
         feedback::{Feedback, FeedbackBookKeeper},
         files::FileSystemProxy,
+        path_to_uri,
         router::Router,
-        src_span_to_lsp_range, DownloadDependencies, MakeLocker,
+        semantic_tokens, src_span_to_lsp_range,
+        statistics::SharedStatistics,
+        DownloadDependencies, MakeLocker,
     },
     line_numbers::LineNumbers,
     Result,
@@ -20,7 +25,7 @@ use debug_ignore::DebugIgnore;
 use itertools::Itertools;
 use lsp_types::{
     self as lsp, HoverProviderCapability, InitializeParams, Position, PublishDiagnosticsParams,
-    Range, TextEdit, Url,
+    Range, TextEdit,
 };
 use serde_json::Value as Json;
 use std::collections::{HashMap, HashSet};
@@ -28,7 +33,7 @@ use std::collections::{HashMap, HashSet};
 /// This class is responsible for handling the language server protocol and
 /// delegating the work to the engine.
 ///
-/// - Configuring watching of the `gleam.toml` file.
+/// - Configuring watching of the `gleam.toml` and `manifest.toml` files.
 /// - Decoding requests.
 /// - Encoding responses.
 /// - Sending diagnostics and messages to the client.
@@ -43,6 +48,11 @@ pub struct LanguageServer<'a, IO> {
     router: Router<IO, ConnectionProgressReporter<'a>>,
     changed_projects: HashSet<Utf8PathBuf>,
     io: FileSystemProxy<IO>,
+    user_config: SharedConfig,
+    statistics: SharedStatistics,
+    /// Reads the amount of physical memory used by the process, which only
+    /// the CLI knows how to find out.
+    resident_memory_bytes: fn() -> Option<u64>,
 }
 
 impl<'a, IO> LanguageServer<'a, IO>
@@ -55,11 +65,27 @@ where
         + MakeLocker
         + Clone,
 {
-    pub fn new(connection: &'a lsp_server::Connection, io: IO) -> Result<Self> {
+    pub fn new(
+        connection: &'a lsp_server::Connection,
+        io: IO,
+        resident_memory_bytes: fn() -> Option<u64>,
+    ) -> Result<Self> {
         let initialise_params = initialisation_handshake(connection);
         let reporter = ConnectionProgressReporter::new(connection, &initialise_params);
         let io = FileSystemProxy::new(io);
-        let router = Router::new(reporter, io.clone());
+        let user_config = initialise_params
+            .initialization_options
+            .as_ref()
+            .and_then(Configuration::from_json)
+            .unwrap_or_default();
+        let user_config = SharedConfig::new(user_config.into());
+        let statistics = SharedStatistics::default();
+        let router = Router::new(
+            reporter,
+            io.clone(),
+            user_config.clone(),
+            statistics.clone(),
+        );
         Ok(Self {
             connection: connection.into(),
             initialise_params,
@@ -67,11 +93,23 @@ where
             outside_of_project_feedback: FeedbackBookKeeper::default(),
             router,
             io,
+            user_config,
+            statistics,
+            resident_memory_bytes,
         })
     }
 
     pub fn run(&mut self) -> Result<()> {
-        self.start_watching_gleam_toml();
+        self.start_watching_config_files();
+
+        // Compile the projects of the workspace straight away so that the
+        // editor shows their diagnostics before any of their files are opened.
+        let feedback =
+            self.workspace_folders_changed(workspace_folders(&self.initialise_params), vec![]);
+        self.publish_feedback(feedback);
+        let feedback = self.compile_please();
+        self.publish_feedback(feedback);
+
         let mut buffer = MessageBuffer::new();
 
         loop {
@@ -105,6 +143,12 @@ where
             Request::CodeAction(param) => self.code_action(param),
             Request::SignatureHelp(param) => self.signature_help(param),
             Request::DocumentSymbol(param) => self.document_symbol(param),
+            Request::WorkspaceSymbol(param) => self.workspace_symbol(param),
+            Request::InlayHint(param) => self.inlay_hint(param),
+            Request::SemanticTokens(param) => self.semantic_tokens(param),
+            Request::ShowCompiled(param) => self.show_compiled(param),
+            Request::SearchByType(param) => self.search_by_type(param),
+            Request::Statistics => self.statistics(),
         };
 
         self.publish_feedback(feedback);
@@ -127,21 +171,31 @@ where
             Notification::SourceFileChangedInMemory { path, text } => {
                 self.cache_file_in_memory(path, text)
             }
-            Notification::ConfigFileChanged { path } => self.watched_files_changed(path),
+            Notification::ConfigFilesChanged { paths } => self.watched_files_changed(paths),
+            Notification::SettingsChanged { settings } => self.settings_changed(settings),
+            Notification::WorkspaceFoldersChanged { added, removed } => {
+                self.workspace_folders_changed(added, removed)
+            }
         };
         self.publish_feedback(feedback);
     }
 
     fn publish_feedback(&self, feedback: Feedback) {
-        self.publish_diagnostics(feedback.diagnostics);
+        self.publish_diagnostics(feedback.diagnostics, feedback.unnecessary);
         self.publish_messages(feedback.messages);
     }
 
-    fn publish_diagnostics(&self, diagnostics: HashMap<Utf8PathBuf, Vec<Diagnostic>>) {
+    fn publish_diagnostics(
+        &self,
+        diagnostics: HashMap<Utf8PathBuf, Vec<Diagnostic>>,
+        mut unnecessary: HashMap<Utf8PathBuf, Vec<Diagnostic>>,
+    ) {
         for (path, diagnostics) in diagnostics {
+            let unnecessary = unnecessary.remove(&path).unwrap_or_default();
             let diagnostics = diagnostics
                 .into_iter()
                 .flat_map(diagnostic_to_lsp)
+                .chain(unnecessary.into_iter().map(unnecessary_to_lsp))
                 .collect::<Vec<_>>();
             let uri = path_to_uri(path);
 
@@ -163,7 +217,7 @@ where
         }
     }
 
-    fn start_watching_gleam_toml(&mut self) {
+    fn start_watching_config_files(&mut self) {
         let supports_watch_files = self
             .initialise_params
             .capabilities
@@ -174,21 +228,23 @@ where
             .unwrap_or(false);
 
         if !supports_watch_files {
-            tracing::warn!("lsp_client_cannot_watch_gleam_toml");
+            tracing::warn!("lsp_client_cannot_watch_config_files");
             return;
         }
 
-        // Register gleam.toml as a watched file so we get a notification when
-        // it changes and thus know that we need to rebuild the entire project.
+        // Register gleam.toml and manifest.toml as watched files so we get a
+        // notification when they change and thus know that we need to reload
+        // the config and dependencies and rebuild the entire project.
+        let watcher = |glob_pattern: &str| lsp::FileSystemWatcher {
+            glob_pattern: glob_pattern.to_string().into(),
+            kind: Some(lsp::WatchKind::Create | lsp::WatchKind::Change | lsp::WatchKind::Delete),
+        };
         let watch_config = lsp::Registration {
-            id: "watch-gleam-toml".into(),
+            id: "watch-config-files".into(),
             method: "workspace/didChangeWatchedFiles".into(),
             register_options: Some(
                 serde_json::value::to_value(lsp::DidChangeWatchedFilesRegistrationOptions {
-                    watchers: vec![lsp::FileSystemWatcher {
-                        glob_pattern: "**/gleam.toml".to_string().into(),
-                        kind: Some(lsp::WatchKind::Change),
-                    }],
+                    watchers: vec![watcher("**/gleam.toml"), watcher("**/manifest.toml")],
                 })
                 .expect("workspace/didChangeWatchedFiles to json"),
             ),
@@ -281,7 +337,12 @@ where
             Err(error) => return self.path_error_response(path, error),
         };
 
-        if let Err(error) = crate::format::pretty(&mut new_text, &src, &path) {
+        let config = match self.router.format_config(&path) {
+            Ok(config) => config,
+            Err(error) => return self.path_error_response(path, error),
+        };
+
+        if let Err(error) = crate::format::pretty(&mut new_text, &src, &path, config) {
             return self.path_error_response(path, error);
         }
 
@@ -333,6 +394,66 @@ where
         self.respond_with_engine(path, |engine| engine.document_symbol(params))
     }
 
+    fn workspace_symbol(&mut self, params: lsp::WorkspaceSymbolParams) -> (Json, Feedback) {
+        // The request isn't for any file in particular, so the symbols of all
+        // the projects open in the editor are given.
+        let mut symbols = vec![];
+        let mut accumulator = Feedback::none();
+        for path in self.router.project_paths() {
+            let (json, feedback) =
+                self.respond_with_engine(path, |engine| engine.workspace_symbol(&params));
+            if let Json::Array(project_symbols) = json {
+                symbols.extend(project_symbols);
+            }
+            accumulator.append_feedback(feedback);
+        }
+        (Json::Array(symbols), accumulator)
+    }
+
+    fn inlay_hint(&mut self, params: lsp::InlayHintParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.inlay_hint(params))
+    }
+
+    fn semantic_tokens(&mut self, params: lsp::SemanticTokensParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.semantic_tokens(params))
+    }
+
+    fn show_compiled(&mut self, params: ShowCompiledParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.show_compiled(params))
+    }
+
+    fn search_by_type(&mut self, params: SearchByTypeParams) -> (Json, Feedback) {
+        // As with workspace symbols, the functions of all the projects open in
+        // the editor are searched.
+        let mut results = vec![];
+        let mut accumulator = Feedback::none();
+        for path in self.router.project_paths() {
+            let (json, feedback) =
+                self.respond_with_engine(path, |engine| engine.search_by_type(&params));
+            if let Json::Array(project_results) = json {
+                results.extend(project_results);
+            }
+            accumulator.append_feedback(feedback);
+        }
+        (Json::Array(results), accumulator)
+    }
+
+    fn statistics(&mut self) -> (Json, Feedback) {
+        let summary = self
+            .statistics
+            .lock()
+            .expect("language server statistics lock")
+            .summary(
+                self.router.project_paths().len(),
+                (self.resident_memory_bytes)(),
+            );
+        let json = serde_json::to_value(summary).expect("statistics to json");
+        (json, Feedback::none())
+    }
+
     fn cache_file_in_memory(&mut self, path: Utf8PathBuf, text: String) -> Feedback {
         self.project_changed(&path);
         if let Err(error) = self.io.write_mem_cache(&path, &text) {
@@ -349,11 +470,51 @@ where
         Feedback::none()
     }
 
-    fn watched_files_changed(&mut self, path: Utf8PathBuf) -> Feedback {
-        self.router.delete_engine_for_path(&path);
+    fn watched_files_changed(&mut self, paths: Vec<Utf8PathBuf>) -> Feedback {
+        let mut feedback = Feedback::none();
+        for path in paths {
+            let (project, project_feedback) = self.router.reload_project_for_config(&path);
+            feedback.append_feedback(project_feedback);
+            // The project is compiled again straight away rather than once one
+            // of its files is next edited, so that any problems with its new
+            // config are reported.
+            if let Some(project) = project {
+                _ = self.changed_projects.insert(project);
+            }
+        }
+        feedback
+    }
+
+    fn settings_changed(&mut self, settings: Json) -> Feedback {
+        // Clients which expect the server to request the settings send this
+        // notification without any, in which case the current ones are kept.
+        if let Some(config) = Configuration::from_json(&settings) {
+            *self
+                .user_config
+                .write()
+                .expect("language server configuration lock") = config;
+        }
         Feedback::none()
     }
 
+    fn workspace_folders_changed(
+        &mut self,
+        added: Vec<Utf8PathBuf>,
+        removed: Vec<Utf8PathBuf>,
+    ) -> Feedback {
+        let mut feedback = Feedback::none();
+        for folder in removed {
+            feedback.append_feedback(self.router.delete_engines_in_folder(&folder));
+            self.changed_projects
+                .retain(|project| !project.starts_with(&folder));
+        }
+        for folder in added {
+            self.changed_projects
+                .extend(self.router.projects_in_folder(&folder));
+        }
+        feedback
+    }
+
     fn compile_please(&mut self) -> Feedback {
         let mut accumulator = Feedback::none();
         let projects = std::mem::take(&mut self.changed_projects);
@@ -372,6 +533,17 @@ where
     }
 }
 
+/// The folders opened in the editor, falling back to the root folder for
+/// clients that don't support multiple workspace folders.
+fn workspace_folders(params: &InitializeParams) -> Vec<Utf8PathBuf> {
+    if let Some(folders) = &params.workspace_folders {
+        return super::messages::folder_paths(folders);
+    }
+    #[allow(deprecated)]
+    let root = params.root_uri.as_ref();
+    root.map(super::path).into_iter().collect()
+}
+
 fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializeParams {
     let server_capabilities = lsp::ServerCapabilities {
         text_document_sync: Some(lsp::TextDocumentSyncCapability::Options(
@@ -411,7 +583,7 @@ fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializePa
         references_provider: None,
         document_highlight_provider: None,
         document_symbol_provider: Some(lsp::OneOf::Left(true)),
-        workspace_symbol_provider: None,
+        workspace_symbol_provider: Some(lsp::OneOf::Left(true)),
         code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
         code_lens_provider: None,
         document_formatting_provider: Some(lsp::OneOf::Left(true)),
@@ -423,15 +595,30 @@ fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializePa
         folding_range_provider: None,
         declaration_provider: None,
         execute_command_provider: None,
-        workspace: None,
+        workspace: Some(lsp::WorkspaceServerCapabilities {
+            workspace_folders: Some(lsp::WorkspaceFoldersServerCapabilities {
+                supported: Some(true),
+                change_notifications: Some(lsp::OneOf::Left(true)),
+            }),
+            file_operations: None,
+        }),
         call_hierarchy_provider: None,
-        semantic_tokens_provider: None,
+        semantic_tokens_provider: Some(
+            lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                lsp::SemanticTokensOptions {
+                    work_done_progress_options: lsp::WorkDoneProgressOptions::default(),
+                    legend: semantic_tokens::legend(),
+                    range: None,
+                    full: Some(lsp::SemanticTokensFullOptions::Bool(true)),
+                },
+            ),
+        ),
         moniker_provider: None,
         linked_editing_range_provider: None,
         experimental: None,
         position_encoding: None,
         inline_value_provider: None,
-        inlay_hint_provider: None,
+        inlay_hint_provider: Some(lsp::OneOf::Left(true)),
         diagnostic_provider: None,
     };
     let server_capabilities_json =
@@ -528,8 +715,22 @@ fn diagnostic_to_lsp(diagnostic: Diagnostic) -> Vec<lsp::Diagnostic> {
     }
 }
 
-fn path_to_uri(path: Utf8PathBuf) -> Url {
-    let mut file: String = "file://".into();
-    file.push_str(&path.as_os_str().to_string_lossy());
-    Url::parse(&file).expect("path_to_uri URL parse")
+/// Unused code is reported as a hint with the `UNNECESSARY` tag, which editors
+/// render by fading the code out.
+fn unnecessary_to_lsp(diagnostic: Diagnostic) -> lsp::Diagnostic {
+    let location = diagnostic
+        .location
+        .expect("Unnecessary code diagnostic given to LSP without location");
+    let line_numbers = LineNumbers::new(&location.src);
+    lsp::Diagnostic {
+        range: src_span_to_lsp_range(location.label.span, &line_numbers),
+        severity: Some(lsp::DiagnosticSeverity::HINT),
+        code: None,
+        code_description: None,
+        source: None,
+        message: diagnostic.title,
+        related_information: None,
+        tags: Some(vec![lsp::DiagnosticTag::UNNECESSARY]),
+        data: None,
+    }
 }
@@ -0,0 +1,153 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use super::extensions::StatisticsResult;
+
+/// The statistics of the language server's session, shared by every
+/// project's engine so that they are kept when an engine is reloaded.
+pub type SharedStatistics = Arc<Mutex<Statistics>>;
+
+/// How many of the most recent compile durations are kept to work out the
+/// 95th percentile latency.
+const RECENT_COMPILES_LIMIT: usize = 1000;
+
+/// Performance data gathered while the language server runs, so that it can
+/// be reported by the `glistix/statistics` request.
+#[derive(Debug, Default)]
+pub struct Statistics {
+    compiles: u64,
+    failed_compiles: u64,
+    total_compile_time: Duration,
+    recent_compile_times: VecDeque<Duration>,
+    modules_compiled: u64,
+    modules_cached: u64,
+}
+
+impl Statistics {
+    /// Records a compilation of a project's root package, along with how many
+    /// of its modules were type checked and how many were reused from the
+    /// cache.
+    pub fn record_compile(
+        &mut self,
+        duration: Duration,
+        succeeded: bool,
+        modules_compiled: usize,
+        modules_cached: usize,
+    ) {
+        self.compiles += 1;
+        if !succeeded {
+            self.failed_compiles += 1;
+        }
+        self.total_compile_time += duration;
+        if self.recent_compile_times.len() == RECENT_COMPILES_LIMIT {
+            _ = self.recent_compile_times.pop_front();
+        }
+        self.recent_compile_times.push_back(duration);
+        self.modules_compiled += modules_compiled as u64;
+        self.modules_cached += modules_cached as u64;
+    }
+
+    /// The statistics gathered so far, along with the number of projects
+    /// with a running engine and the memory used by the process, if known.
+    pub fn summary(
+        &self,
+        open_projects: usize,
+        resident_memory_bytes: Option<u64>,
+    ) -> StatisticsResult {
+        let mean_compile_milliseconds = if self.compiles == 0 {
+            0.0
+        } else {
+            milliseconds(self.total_compile_time) / self.compiles as f64
+        };
+
+        let modules = self.modules_compiled + self.modules_cached;
+        let cache_hit_rate = if modules == 0 {
+            0.0
+        } else {
+            self.modules_cached as f64 / modules as f64
+        };
+
+        StatisticsResult {
+            compiles: self.compiles,
+            failed_compiles: self.failed_compiles,
+            mean_compile_milliseconds,
+            p95_compile_milliseconds: self.percentile(95).map_or(0.0, milliseconds),
+            modules_compiled: self.modules_compiled,
+            modules_cached: self.modules_cached,
+            cache_hit_rate,
+            open_projects,
+            resident_memory_bytes,
+        }
+    }
+
+    /// The given percentile of the recent compile durations, using the
+    /// nearest-rank method.
+    fn percentile(&self, percentile: usize) -> Option<Duration> {
+        if self.recent_compile_times.is_empty() {
+            return None;
+        }
+        let mut durations = self
+            .recent_compile_times
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        durations.sort_unstable();
+        let rank = (percentile * durations.len()).div_ceil(100).max(1);
+        durations.get(rank - 1).copied()
+    }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_micros() as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_without_compiles() {
+        let summary = Statistics::default().summary(0, None);
+        assert_eq!(summary.compiles, 0);
+        assert_eq!(summary.mean_compile_milliseconds, 0.0);
+        assert_eq!(summary.p95_compile_milliseconds, 0.0);
+        assert_eq!(summary.cache_hit_rate, 0.0);
+    }
+
+    #[test]
+    fn summary_of_compiles() {
+        let mut statistics = Statistics::default();
+        for milliseconds in 1..=20 {
+            statistics.record_compile(Duration::from_millis(milliseconds), true, 1, 3);
+        }
+        statistics.record_compile(Duration::from_millis(294), false, 0, 0);
+
+        let summary = statistics.summary(2, None);
+        assert_eq!(summary.compiles, 21);
+        assert_eq!(summary.failed_compiles, 1);
+        assert_eq!(summary.mean_compile_milliseconds, 24.0);
+        assert_eq!(summary.p95_compile_milliseconds, 20.0);
+        assert_eq!(summary.modules_compiled, 20);
+        assert_eq!(summary.modules_cached, 60);
+        assert_eq!(summary.cache_hit_rate, 0.75);
+        assert_eq!(summary.open_projects, 2);
+    }
+
+    #[test]
+    fn only_recent_compiles_count_towards_percentile() {
+        let mut statistics = Statistics::default();
+        for _ in 0..RECENT_COMPILES_LIMIT {
+            statistics.record_compile(Duration::from_millis(500), true, 1, 0);
+        }
+        for _ in 0..RECENT_COMPILES_LIMIT {
+            statistics.record_compile(Duration::from_millis(10), true, 1, 0);
+        }
+
+        let summary = statistics.summary(1, None);
+        assert_eq!(summary.p95_compile_milliseconds, 10.0);
+        assert_eq!(summary.mean_compile_milliseconds, 255.0);
+    }
+}
@@ -4,7 +4,12 @@ mod completion;
 mod definition;
 mod document_symbols;
 mod hover;
+mod inlay_hints;
+mod search_by_type;
+mod semantic_tokens;
+mod show_compiled;
 mod signature_help;
+mod workspace_symbols;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -20,13 +25,19 @@ use itertools::Itertools;
 use lsp_types::{Position, TextDocumentIdentifier, TextDocumentPositionParams, Url};
 
 use crate::{
+    build::Target,
     config::PackageConfig,
     io::{
         memory::InMemoryFileSystem, BeamCompiler, CommandExecutor, FileSystemReader,
         FileSystemWriter, ReadDir, WrappedReader,
     },
     language_server::{
-        engine::LanguageServerEngine, files::FileSystemProxy, progress::ProgressReporter,
+        configuration::{Configuration, InlayHintsConfig, SharedConfig},
+        edits::ImportPlacement,
+        engine::LanguageServerEngine,
+        files::FileSystemProxy,
+        progress::ProgressReporter,
+        statistics::SharedStatistics,
         DownloadDependencies, LockGuard, Locker, MakeLocker,
     },
     line_numbers::LineNumbers,
@@ -153,6 +164,10 @@ impl FileSystemReader for LanguageServerTestIO {
         self.io.modification_time(path)
     }
 
+    fn file_size(&self, path: &Utf8Path) -> Result<u64> {
+        self.io.file_size(path)
+    }
+
     fn canonicalise(&self, path: &Utf8Path) -> Result<Utf8PathBuf, crate::Error> {
         self.io.canonicalise(path)
     }
@@ -183,6 +198,10 @@ impl FileSystemWriter for LanguageServerTestIO {
         self.io.symlink_dir(from, to)
     }
 
+    fn rename(&self, from: &Utf8Path, to: &Utf8Path) -> Result<()> {
+        self.io.rename(from, to)
+    }
+
     fn delete_file(&self, path: &Utf8Path) -> Result<()> {
         self.io.delete_file(path)
     }
@@ -239,7 +258,7 @@ impl MakeLocker for LanguageServerTestIO {
     fn make_locker(
         &self,
         _paths: &ProjectPaths,
-        _target: crate::build::Target,
+        _target: Target,
     ) -> Result<Box<dyn Locker>> {
         Ok(Box::new(TestLocker {
             actions: self.actions.clone(),
@@ -363,14 +382,25 @@ fn add_path_dep<B>(engine: &mut LanguageServerEngine<LanguageServerTestIO, B>, n
 
 fn setup_engine(
     io: &LanguageServerTestIO,
+) -> LanguageServerEngine<LanguageServerTestIO, LanguageServerTestIO> {
+    setup_engine_with_config(io, Configuration::default(), Target::Erlang)
+}
+
+fn setup_engine_with_config(
+    io: &LanguageServerTestIO,
+    user_config: Configuration,
+    target: Target,
 ) -> LanguageServerEngine<LanguageServerTestIO, LanguageServerTestIO> {
     let mut config = PackageConfig::default();
     config.name = LSP_TEST_ROOT_PACKAGE_NAME.into();
+    config.target = target;
     LanguageServerEngine::new(
         config,
         io.clone(),
         FileSystemProxy::new(io.clone()),
         io.paths.clone(),
+        SharedConfig::new(user_config.into()),
+        SharedStatistics::default(),
     )
     .unwrap()
 }
@@ -383,6 +413,8 @@ struct TestProject<'a> {
     hex_modules: Vec<(&'a str, &'a str)>,
     dev_hex_modules: Vec<(&'a str, &'a str)>,
     indirect_hex_modules: Vec<(&'a str, &'a str)>,
+    user_config: Configuration,
+    target: Target,
 }
 
 impl<'a> TestProject<'a> {
@@ -395,6 +427,8 @@ impl<'a> TestProject<'a> {
             hex_modules: vec![],
             dev_hex_modules: vec![],
             indirect_hex_modules: vec![],
+            user_config: Configuration::default(),
+            target: Target::Erlang,
         }
     }
 
@@ -456,6 +490,21 @@ impl<'a> TestProject<'a> {
         self
     }
 
+    pub fn import_placement(mut self, import_placement: ImportPlacement) -> Self {
+        self.user_config.import_placement = import_placement;
+        self
+    }
+
+    pub fn inlay_hints(mut self, inlay_hints: InlayHintsConfig) -> Self {
+        self.user_config.inlay_hints = inlay_hints;
+        self
+    }
+
+    pub fn target(mut self, target: Target) -> Self {
+        self.target = target;
+        self
+    }
+
     pub fn build_engine(
         &self,
         io: &mut LanguageServerTestIO,
@@ -473,7 +522,7 @@ impl<'a> TestProject<'a> {
             _ = io.hex_dep_module("indirect_hex", name, code);
         });
 
-        let mut engine = setup_engine(io);
+        let mut engine = setup_engine_with_config(io, self.user_config, self.target);
 
         // Add an external dependency and all its modules
         add_path_dep(&mut engine, "dep");
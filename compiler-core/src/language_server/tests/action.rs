@@ -58,19 +58,31 @@ fn apply_code_edit(src: &str, changes: HashMap<Url, Vec<lsp_types::TextEdit>>) -
 }
 
 const REMOVE_UNUSED_IMPORTS: &str = "Remove unused imports";
+const REMOVE_UNUSED_DEFINITION: &str = "Remove unused definition";
 const REMOVE_REDUNDANT_TUPLES: &str = "Remove redundant tuples";
 const CONVERT_TO_CASE: &str = "Convert to case";
 const USE_LABEL_SHORTHAND_SYNTAX: &str = "Use label shorthand syntax";
 const FILL_LABELS: &str = "Fill labels";
+const ADD_MISSING_LABELS: &str = "Add missing labels";
 const ASSIGN_UNUSED_RESULT: &str = "Assign unused Result value to `_`";
 const ADD_MISSING_PATTERNS: &str = "Add missing patterns";
 const ADD_ANNOTATION: &str = "Add type annotation";
 const ADD_ANNOTATIONS: &str = "Add type annotations";
 const DESUGAR_USE_EXPRESSION: &str = "Convert from `use`";
 const CONVERT_TO_USE: &str = "Convert to `use`";
+const CONVERT_TO_BOOL_GUARD: &str = "Convert to `bool.guard`";
+const CONVERT_TO_BOOL_LAZY_GUARD: &str = "Convert to `bool.lazy_guard`";
+const CONVERT_FROM_BOOL_GUARD: &str = "Convert from `bool.guard`";
+const CONVERT_FROM_BOOL_LAZY_GUARD: &str = "Convert from `bool.lazy_guard`";
 const EXTRACT_VARIABLE: &str = "Extract variable";
 const EXPAND_FUNCTION_CAPTURE: &str = "Expand function capture";
 const GENERATE_DYNAMIC_DECODER: &str = "Generate dynamic decoder";
+const PATTERN_MATCH_ON_VARIABLE: &str = "Pattern match on variable";
+const GENERATE_FUNCTION: &str = "Generate function";
+const WRAP_IN_BLOCK: &str = "Wrap in block";
+const WRAP_IN_CASE: &str = "Wrap in case expression";
+const GUARD_WITH_TODO: &str = "Guard function body with todo";
+const WRAP_WITH_NIX_TODO: &str = "Wrap in function with Nix todo";
 
 macro_rules! assert_code_action {
     ($title:expr, $code:literal, $range:expr $(,)?) => {
@@ -169,6 +181,57 @@ pub fn main() {
     );
 }
 
+#[test]
+fn remove_unused_private_function() {
+    assert_code_action!(
+        REMOVE_UNUSED_DEFINITION,
+        "/// Some documentation
+fn wibble() {
+  1
+}
+
+pub fn main() {
+  Nil
+}
+",
+        find_position_of("wibble").to_selection(),
+    );
+}
+
+#[test]
+fn remove_unused_private_function_with_attributes() {
+    assert_code_action!(
+        REMOVE_UNUSED_DEFINITION,
+        "@deprecated(\"Use main instead\")
+@inline
+fn wibble() {
+  1
+}
+
+pub fn main() {
+  Nil
+}
+",
+        find_position_of("wibble").to_selection(),
+    );
+}
+
+#[test]
+fn no_remove_unused_definition_for_used_function() {
+    assert_no_code_actions!(
+        REMOVE_UNUSED_DEFINITION,
+        "fn wibble() {
+  1
+}
+
+pub fn main() {
+  wibble()
+}
+",
+        find_position_of("wibble").to_selection(),
+    );
+}
+
 #[test]
 fn test_remove_redundant_tuple_in_case_subject_simple() {
     assert_code_action!(
@@ -1063,6 +1126,96 @@ pub fn wibble(arg1 arg1, arg2 arg2) { Nil }
     );
 }
 
+#[test]
+fn add_missing_labels_to_definition() {
+    assert_code_action!(
+        ADD_MISSING_LABELS,
+        r#"
+pub fn main() {
+  wibble(1, label: 2)
+}
+
+pub fn wibble(a, b) { Nil }
+ "#,
+        find_position_of("wibble(").to_selection(),
+    );
+}
+
+#[test]
+fn add_missing_labels_to_definition_uses_call_site_order() {
+    assert_code_action!(
+        ADD_MISSING_LABELS,
+        r#"
+pub fn main() {
+  wibble(second: 1, first: 2)
+}
+
+pub fn wibble(a, b) { Nil }
+ "#,
+        find_position_of("wibble(").to_selection(),
+    );
+}
+
+#[test]
+fn add_missing_labels_to_definition_matches_argument_names() {
+    assert_code_action!(
+        ADD_MISSING_LABELS,
+        r#"
+pub fn main() {
+  wibble(1, c: 2, b: 3)
+}
+
+pub fn wibble(a, b, c) { Nil }
+ "#,
+        find_position_of("wibble(").to_selection(),
+    );
+}
+
+#[test]
+fn add_missing_labels_to_definition_with_existing_labels() {
+    assert_code_action!(
+        ADD_MISSING_LABELS,
+        r#"
+pub fn main() {
+  wibble(1, b: 2, c: 3)
+}
+
+pub fn wibble(a, b, c c) { Nil }
+ "#,
+        find_position_of("wibble(").to_selection(),
+    );
+}
+
+#[test]
+fn no_code_action_to_add_missing_labels_if_all_labels_are_known() {
+    assert_no_code_actions!(
+        ADD_MISSING_LABELS,
+        r#"
+pub fn main() {
+  wibble(1, b: 2)
+}
+
+pub fn wibble(a, b b) { Nil }
+ "#,
+        find_position_of("wibble(").to_selection(),
+    );
+}
+
+#[test]
+fn no_code_action_to_add_missing_labels_if_there_are_too_many() {
+    assert_no_code_actions!(
+        ADD_MISSING_LABELS,
+        r#"
+pub fn main() {
+  wibble(1, b: 2)
+}
+
+pub fn wibble(a) { Nil }
+ "#,
+        find_position_of("wibble(").to_selection(),
+    );
+}
+
 #[test]
 fn use_label_shorthand_works_for_nested_calls() {
     assert_code_action!(
@@ -3713,6 +3866,248 @@ pub fn main() {
     );
 }
 
+const BOOL_MODULE: &str = "
+pub fn guard(when requirement: Bool, return consequence: a, otherwise alternative: fn() -> a) -> a { todo }
+pub fn lazy_guard(when requirement: Bool, return consequence: fn() -> a, otherwise alternative: fn() -> a) -> a { todo }
+";
+
+#[test]
+fn convert_case_on_bool_to_bool_guard() {
+    let src = r#"
+pub fn main(is_admin) {
+  case is_admin {
+    True -> Nil
+    False -> {
+      let user = 1
+      Error(user)
+    }
+  }
+}
+"#;
+    assert_code_action!(
+        CONVERT_TO_BOOL_GUARD,
+        TestProject::for_source(src).add_hex_module("gleam/bool", BOOL_MODULE),
+        find_position_of("case").to_selection(),
+    );
+}
+
+#[test]
+fn convert_case_on_bool_to_bool_lazy_guard() {
+    let src = r#"
+pub fn main(is_admin) {
+  case is_admin {
+    True -> Ok(Nil)
+    False -> {
+      let user = 1
+      Error(user)
+    }
+  }
+}
+"#;
+    assert_code_action!(
+        CONVERT_TO_BOOL_LAZY_GUARD,
+        TestProject::for_source(src).add_hex_module("gleam/bool", BOOL_MODULE),
+        find_position_of("case").to_selection(),
+    );
+}
+
+#[test]
+fn convert_case_on_bool_to_bool_lazy_guard_with_block() {
+    let src = r#"
+pub fn main(is_admin) {
+  case is_admin {
+    True -> {
+      let user = 1
+      Ok(user)
+    }
+    False -> {
+      let user = 2
+      let other = 3
+      Error(user + other)
+    }
+  }
+}
+"#;
+    assert_code_action!(
+        CONVERT_TO_BOOL_LAZY_GUARD,
+        TestProject::for_source(src).add_hex_module("gleam/bool", BOOL_MODULE),
+        find_position_of("case").to_selection(),
+    );
+}
+
+#[test]
+fn convert_case_on_bool_to_bool_guard_with_longer_true_branch() {
+    let src = r#"
+pub fn main(is_admin) {
+  case is_admin {
+    True -> {
+      let user = 1
+      Ok(user)
+    }
+    False -> Error(Nil)
+  }
+}
+"#;
+    assert_code_action!(
+        CONVERT_TO_BOOL_LAZY_GUARD,
+        TestProject::for_source(src).add_hex_module("gleam/bool", BOOL_MODULE),
+        find_position_of("case").to_selection(),
+    );
+}
+
+#[test]
+fn convert_case_on_bool_to_bool_guard_with_aliased_import() {
+    let src = r#"
+import gleam/bool as b
+
+pub fn main(x) {
+  case x > 1 {
+    False -> 0
+    True -> x * 2 + 1
+  }
+}
+"#;
+    assert_code_action!(
+        CONVERT_TO_BOOL_GUARD,
+        TestProject::for_source(src).add_hex_module("gleam/bool", BOOL_MODULE),
+        find_position_of("case").to_selection(),
+    );
+}
+
+#[test]
+fn convert_case_on_bool_to_bool_guard_in_anonymous_function() {
+    let src = r#"
+pub fn main(items) {
+  map(items, fn(item) {
+    case item {
+      True -> 0
+      False -> {
+        let x = 1
+        x + 1
+      }
+    }
+  })
+}
+
+fn map(items, f) {
+  todo
+}
+"#;
+    assert_code_action!(
+        CONVERT_TO_BOOL_GUARD,
+        TestProject::for_source(src).add_hex_module("gleam/bool", BOOL_MODULE),
+        find_position_of("True").to_selection(),
+    );
+}
+
+#[test]
+fn no_bool_guard_code_action_without_gleam_bool() {
+    assert_no_code_actions!(
+        CONVERT_TO_BOOL_GUARD,
+        r#"
+pub fn main(x) {
+  case x {
+    True -> 1
+    False -> {
+      let y = 2
+      y
+    }
+  }
+}
+"#,
+        find_position_of("case").to_selection(),
+    );
+}
+
+#[test]
+fn no_bool_guard_code_action_for_case_followed_by_other_statements() {
+    assert_no_code_actions!(
+        CONVERT_TO_BOOL_GUARD,
+        r#"
+pub fn main(x) {
+  case x {
+    True -> 1
+    False -> 2
+  }
+  3
+}
+"#,
+        find_position_of("case").to_selection(),
+    );
+}
+
+#[test]
+fn no_bool_guard_code_action_for_case_with_guards() {
+    assert_no_code_actions!(
+        CONVERT_TO_BOOL_GUARD,
+        r#"
+pub fn main(x, y) {
+  case x {
+    True if y -> 1
+    _ -> 2
+  }
+}
+"#,
+        find_position_of("case").to_selection(),
+    );
+}
+
+#[test]
+fn convert_bool_guard_to_case() {
+    let src = r#"
+import gleam/bool
+
+pub fn main(is_admin) {
+  use <- bool.guard(is_admin, 0)
+  let user = 1
+  user + 1
+}
+"#;
+    assert_code_action!(
+        CONVERT_FROM_BOOL_GUARD,
+        TestProject::for_source(src).add_hex_module("gleam/bool", BOOL_MODULE),
+        find_position_of("use").to_selection(),
+    );
+}
+
+#[test]
+fn convert_bool_lazy_guard_to_case() {
+    let src = r#"
+import gleam/bool.{lazy_guard}
+
+pub fn main(is_admin) {
+  use <- lazy_guard(when: is_admin, return: fn() { Ok(Nil) })
+  let user = 1
+  Error(user)
+}
+"#;
+    assert_code_action!(
+        CONVERT_FROM_BOOL_LAZY_GUARD,
+        TestProject::for_source(src).add_hex_module("gleam/bool", BOOL_MODULE),
+        find_position_of("user").to_selection(),
+    );
+}
+
+#[test]
+fn convert_bool_lazy_guard_with_statements_to_case() {
+    let src = r#"
+import gleam/bool
+
+pub fn main(is_admin) {
+  use <- bool.lazy_guard(is_admin, fn() {
+    let user = 1
+    Ok(user)
+  })
+  Error(Nil)
+}
+"#;
+    assert_code_action!(
+        CONVERT_FROM_BOOL_LAZY_GUARD,
+        TestProject::for_source(src).add_hex_module("gleam/bool", BOOL_MODULE),
+        find_position_of("use").to_selection(),
+    );
+}
+
 #[test]
 fn inexhaustive_let_result_to_case() {
     assert_code_action!(
@@ -4225,3 +4620,504 @@ pub type Wibble {
         find_position_of("type").to_selection()
     );
 }
+
+#[test]
+fn import_module_alphabetically() {
+    let src = "
+import alpha
+import zeta
+
+pub fn main(res) {
+  case res {
+    result.Ok(_) -> Nil
+    result.Error(_) -> zeta.zeta(alpha.alpha())
+  }
+}
+";
+
+    assert_code_action!(
+        "Import `result`",
+        TestProject::for_source(src)
+            .add_hex_module("alpha", "pub fn alpha() { Nil }")
+            .add_hex_module("zeta", "pub fn zeta(x) { x }")
+            .add_hex_module("result", "pub type Result(v, e) { Ok(v) Error(e) }")
+            .import_placement(ImportPlacement::Alphabetical),
+        find_position_of("result").select_until(find_position_of("."))
+    );
+}
+
+#[test]
+fn import_module_after_imports() {
+    let src = "
+import alpha
+import zeta
+
+pub fn main(res) {
+  case res {
+    result.Ok(_) -> Nil
+    result.Error(_) -> zeta.zeta(alpha.alpha())
+  }
+}
+";
+
+    assert_code_action!(
+        "Import `result`",
+        TestProject::for_source(src)
+            .add_hex_module("alpha", "pub fn alpha() { Nil }")
+            .add_hex_module("zeta", "pub fn zeta(x) { x }")
+            .add_hex_module("result", "pub type Result(v, e) { Ok(v) Error(e) }")
+            .import_placement(ImportPlacement::AfterImports),
+        find_position_of("result").select_until(find_position_of("."))
+    );
+}
+
+#[test]
+fn pattern_match_on_argument() {
+    assert_code_action!(
+        PATTERN_MATCH_ON_VARIABLE,
+        "
+pub type Wibble {
+  Wobble
+  Wibble(Int, String)
+  Wubble(field: Bool)
+}
+
+pub fn main(wibble: Wibble) {
+  todo
+}
+",
+        find_position_of("wibble:").to_selection()
+    );
+}
+
+#[test]
+fn pattern_match_on_let_assigned_variable() {
+    assert_code_action!(
+        PATTERN_MATCH_ON_VARIABLE,
+        "
+pub type Wibble {
+  Wobble
+  Wibble(Int, String)
+  Wubble(field: Bool)
+}
+
+pub fn main(value: Wibble) {
+  let wibble = value
+  wibble
+}
+",
+        find_position_of("wibble =").to_selection()
+    );
+}
+
+#[test]
+fn pattern_match_on_variable_of_imported_type() {
+    let src = "
+import wibble
+
+pub fn main(value: wibble.Wibble) {
+  value
+}
+";
+    assert_code_action!(
+        PATTERN_MATCH_ON_VARIABLE,
+        TestProject::for_source(src).add_module("wibble", "pub type Wibble { Wibble(Int) Wobble }"),
+        find_position_of("value:").to_selection()
+    );
+}
+
+#[test]
+fn no_code_action_to_pattern_match_on_int() {
+    assert_no_code_actions!(
+        PATTERN_MATCH_ON_VARIABLE,
+        "
+pub fn main(value: Int) {
+  value
+}
+",
+        find_position_of("value:").to_selection()
+    );
+}
+
+#[test]
+fn no_code_action_to_pattern_match_on_opaque_type() {
+    let src = "
+import wibble
+
+pub fn main(value: wibble.Wibble) {
+  value
+}
+";
+    assert_no_code_actions!(
+        PATTERN_MATCH_ON_VARIABLE,
+        TestProject::for_source(src)
+            .add_module("wibble", "pub opaque type Wibble { Wibble(Int) Wobble }"),
+        find_position_of("value:").to_selection()
+    );
+}
+
+#[test]
+fn generate_function() {
+    assert_code_action!(
+        GENERATE_FUNCTION,
+        "
+pub fn main() {
+  let name = \"Joe\"
+  wibble(1, name)
+}
+
+pub fn wobble() {
+  Nil
+}
+",
+        find_position_of("wibble").to_selection()
+    );
+}
+
+#[test]
+fn generate_function_with_labels_and_return_type() {
+    assert_code_action!(
+        GENERATE_FUNCTION,
+        "
+pub fn main() -> Int {
+  wibble(1.0, times: 2) + 1
+}
+",
+        find_position_of("wibble").to_selection()
+    );
+}
+
+#[test]
+fn generate_function_in_other_module() {
+    let src = "
+import wibble
+
+pub fn main() {
+  wibble.wobble(1)
+}
+";
+    let project =
+        TestProject::for_source(src).add_module("wibble", "pub fn wubble() {\n  Nil\n}\n");
+    let range = find_position_of("wobble").to_selection().find_range(src);
+    let changes = actions_with_title(vec![GENERATE_FUNCTION], project, range)
+        .pop()
+        .expect("No action with the given title")
+        .edit
+        .expect("No workspace edit found")
+        .changes
+        .expect("No text edit found");
+    let (uri, edits) = changes
+        .into_iter()
+        .exactly_one()
+        .expect("A single file edit");
+    assert!(uri.path().ends_with("/src/wibble.gleam"));
+    assert_eq!(
+        edits
+            .iter()
+            .map(|edit| edit.new_text.as_str())
+            .collect_vec(),
+        vec!["\npub fn wobble(arg1: Int) -> a {\n  todo\n}\n"]
+    );
+}
+
+#[test]
+fn no_code_action_to_generate_function_in_dependency() {
+    let src = "
+import wibble
+
+pub fn main() {
+  wibble.wobble(1)
+}
+";
+    assert_no_code_actions!(
+        GENERATE_FUNCTION,
+        TestProject::for_source(src).add_hex_module("wibble", "pub fn wubble() { Nil }"),
+        find_position_of("wobble").to_selection()
+    );
+}
+
+#[test]
+fn no_code_action_to_generate_function_for_constructor() {
+    assert_no_code_actions!(
+        GENERATE_FUNCTION,
+        "
+pub fn main() {
+  Wibble(1)
+}
+",
+        find_position_of("Wibble").to_selection()
+    );
+}
+
+#[test]
+fn wrap_call_unsupported_on_nix() {
+    let src = "
+import wibble
+
+pub fn main() {
+  wibble.wobble(1)
+}
+";
+    let project = TestProject::for_source(src)
+        .target(Target::Nix)
+        .add_hex_module(
+            "wibble",
+            "@external(erlang, \"wibble_ffi\", \"wobble\")
+pub fn wobble(times: Int) -> Int",
+        );
+    let range = find_position_of("wobble").to_selection().find_range(src);
+    assert_eq!(
+        apply_code_action(WRAP_WITH_NIX_TODO, project, range),
+        "
+import wibble
+
+pub fn main() {
+  wibble_wobble(1)
+}
+
+@external(erlang, \"wibble\", \"wobble\")
+fn wibble_wobble(arg1: Int) -> Int {
+  todo
+}
+"
+    );
+}
+
+#[test]
+fn wrap_call_unsupported_on_nix_with_javascript_external() {
+    let src = "
+import wibble/wobble
+
+pub fn main() {
+  let times = 2
+  wobble.wubble(times)
+}
+";
+    let project = TestProject::for_source(src)
+        .target(Target::Nix)
+        .add_hex_module(
+            "wibble/wobble",
+            "@external(erlang, \"wibble_ffi\", \"wubble\")
+@external(javascript, \"./wibble_ffi.mjs\", \"wubble\")
+pub fn wubble(times: Int) -> String",
+        );
+    let range = find_position_of("wubble").to_selection().find_range(src);
+    assert_eq!(
+        apply_code_action(WRAP_WITH_NIX_TODO, project, range),
+        "
+import wibble/wobble
+
+pub fn main() {
+  let times = 2
+  wobble_wubble(times)
+}
+
+@external(erlang, \"wibble@wobble\", \"wubble\")
+@external(javascript, \"../hex/wibble/wobble.mjs\", \"wubble\")
+fn wobble_wubble(times: Int) -> String {
+  todo
+}
+"
+    );
+}
+
+#[test]
+fn wrap_call_unsupported_on_nix_uses_existing_wrapper() {
+    let src = "
+import wibble
+
+pub fn main() {
+  wibble.wobble(1)
+}
+
+fn wibble_wobble(times: Int) -> Int {
+  todo
+}
+";
+    let project = TestProject::for_source(src)
+        .target(Target::Nix)
+        .add_hex_module(
+            "wibble",
+            "@external(erlang, \"wibble_ffi\", \"wobble\")
+pub fn wobble(times: Int) -> Int",
+        );
+    let range = find_position_of("wobble").to_selection().find_range(src);
+    assert_eq!(
+        apply_code_action(WRAP_WITH_NIX_TODO, project, range),
+        "
+import wibble
+
+pub fn main() {
+  wibble_wobble(1)
+}
+
+fn wibble_wobble(times: Int) -> Int {
+  todo
+}
+"
+    );
+}
+
+#[test]
+fn no_code_action_to_wrap_call_supported_on_nix() {
+    let src = "
+import wibble
+
+pub fn main() {
+  wibble.wobble(1)
+}
+";
+    assert_no_code_actions!(
+        WRAP_WITH_NIX_TODO,
+        TestProject::for_source(src)
+            .target(Target::Nix)
+            .add_hex_module("wibble", "pub fn wobble(times: Int) -> Int { times }"),
+        find_position_of("wobble").to_selection()
+    );
+}
+
+#[test]
+fn wrap_statements_in_block() {
+    assert_code_action!(
+        WRAP_IN_BLOCK,
+        "
+pub fn main() {
+  let wibble = 1
+  let wobble = wibble + 1
+  wobble * 2
+}
+",
+        find_position_of("let wobble").select_until(find_position_of("* 2"))
+    );
+}
+
+#[test]
+fn wrap_statement_in_block_keeps_multiline_strings() {
+    assert_code_action!(
+        WRAP_IN_BLOCK,
+        r#"
+pub fn main() {
+  let wibble = "one
+two"
+  wibble
+}
+"#,
+        find_position_of("let").to_selection()
+    );
+}
+
+#[test]
+fn wrap_statement_of_inner_block_in_block() {
+    assert_code_action!(
+        WRAP_IN_BLOCK,
+        "
+pub fn main() {
+  let wibble = {
+    let wobble = 1
+    wobble + 1
+  }
+  wibble
+}
+",
+        find_position_of("wobble +").to_selection()
+    );
+}
+
+#[test]
+fn no_code_action_to_wrap_use_in_block() {
+    assert_no_code_actions!(
+        WRAP_IN_BLOCK,
+        "
+pub fn main() {
+  use wibble <- wobble()
+  wibble
+}
+
+fn wobble(f) {
+  f(1)
+}
+",
+        find_position_of("use").to_selection()
+    );
+}
+
+#[test]
+fn wrap_expression_in_case() {
+    assert_code_action!(
+        WRAP_IN_CASE,
+        "
+pub type Wibble {
+  Wobble
+  Wubble(Int)
+}
+
+pub fn main() {
+  let wibble = new()
+  wibble
+}
+
+fn new() -> Wibble {
+  Wobble
+}
+",
+        find_position_of("new()").to_selection()
+    );
+}
+
+#[test]
+fn wrap_expression_in_case_with_catch_all() {
+    assert_code_action!(
+        WRAP_IN_CASE,
+        "
+pub fn main() {
+  let wibble = 1 + 2
+  wibble
+}
+",
+        find_position_of("+").to_selection()
+    );
+}
+
+#[test]
+fn no_code_action_to_wrap_function_in_case() {
+    assert_no_code_actions!(
+        WRAP_IN_CASE,
+        "
+pub fn main() {
+  wibble
+}
+
+fn wibble() {
+  1
+}
+",
+        find_position_of("wibble").to_selection()
+    );
+}
+
+#[test]
+fn guard_function_body_with_todo() {
+    assert_code_action!(
+        GUARD_WITH_TODO,
+        "
+pub fn main() {
+  let wibble = 1
+  wibble
+}
+",
+        find_position_of("main").to_selection()
+    );
+}
+
+#[test]
+fn no_code_action_to_guard_function_body_starting_with_todo() {
+    assert_no_code_actions!(
+        GUARD_WITH_TODO,
+        "
+pub fn main() {
+  todo
+}
+",
+        find_position_of("main").to_selection()
+    );
+}
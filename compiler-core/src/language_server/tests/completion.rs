@@ -1886,3 +1886,29 @@ pub fn map(_, _) { [] }
         Position::new(3, 8)
     );
 }
+
+#[test]
+fn nix_external_prelude_helper() {
+    let code = r#"@external(nix, "./gleam.nix", "isO")
+pub fn is_ok(result: Result(a, b)) -> Bool {
+  todo
+}
+"#;
+
+    assert_apply_completion!(TestProject::for_source(code), "isOk", Position::new(0, 34));
+}
+
+#[test]
+fn nix_external_prelude_path() {
+    let code = r#"@external(nix, "./", "isOk")
+pub fn is_ok(result: Result(a, b)) -> Bool {
+  todo
+}
+"#;
+
+    assert_apply_completion!(
+        TestProject::for_source(code),
+        "./gleam.nix",
+        Position::new(0, 18)
+    );
+}
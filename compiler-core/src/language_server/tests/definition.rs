@@ -626,3 +626,57 @@ pub fn main() {
         find_position_of("w: Wibble").under_char('i')
     );
 }
+
+#[test]
+fn goto_definition_of_label_in_call() {
+    let code = "
+fn add(wibble a: Int, wobble b: Int) -> Int {
+  a + b
+}
+
+pub fn main() {
+  add(wibble: 1, wobble: 2)
+}
+";
+
+    assert_goto!(
+        TestProject::for_source(code),
+        find_position_of("wobble: 2").under_char('b')
+    );
+}
+
+#[test]
+fn goto_definition_of_label_in_imported_function_call() {
+    let code = "
+import example_module
+fn main() {
+  example_module.add(wibble: 1, wobble: 2)
+}
+";
+
+    assert_goto!(
+        TestProject::for_source(code).add_module(
+            "example_module",
+            "pub fn add(wibble a: Int, wobble b: Int) -> Int { a + b }"
+        ),
+        find_position_of("wobble:").under_char('b')
+    );
+}
+
+#[test]
+fn goto_definition_of_label_in_record_constructor_call() {
+    let code = "
+pub type Person {
+  Person(name: String, age: Int)
+}
+
+pub fn main() {
+  Person(age: 1, name: \"Lucy\")
+}
+";
+
+    assert_goto!(
+        TestProject::for_source(code),
+        find_position_of("age: 1").under_char('g')
+    );
+}
@@ -208,6 +208,67 @@ fn main() {
     );
 }
 
+#[test]
+fn hover_function_definition_with_since() {
+    assert_hover!(
+        "
+@since(\"1.2.0\")
+pub fn add_2(x) {
+  x + 2
+}
+",
+        find_position_of("add_2")
+    );
+}
+
+#[test]
+fn hover_external_function_definition() {
+    assert_hover!(
+        "
+@external(erlang, \"wibble\", \"wobble\")
+@external(nix, \"./wibble.nix\", \"wobble\")
+pub fn wobble() -> Int
+",
+        find_position_of("wobble()")
+    );
+}
+
+#[test]
+fn hover_local_external_function() {
+    assert_hover!(
+        "
+@external(erlang, \"wibble\", \"wobble\")
+@external(nix, \"./wibble.nix\", \"wobble\")
+fn wobble() -> Int
+
+fn main() -> Int {
+  wobble()
+}
+",
+        find_position_of("wobble()")
+            .under_char('o')
+            .nth_occurrence(2)
+    );
+}
+
+#[test]
+fn hover_imported_function_with_since() {
+    let code = "
+import example_module
+fn main() {
+  example_module.my_fn
+}
+";
+
+    assert_hover!(
+        TestProject::for_source(code).add_module(
+            "example_module",
+            "@since(\"0.3.0\")\npub fn my_fn() { Nil }"
+        ),
+        find_position_of("my_fn").under_char('_'),
+    );
+}
+
 #[test]
 fn hover_external_imported_function() {
     let code = "
@@ -1345,3 +1406,44 @@ pub fn main() {
         find_position_of("Int").under_char('n')
     );
 }
+
+#[test]
+fn hover_for_label_of_documented_function() {
+    let code = "
+/// Adds two numbers.
+fn add(wibble a: Int, wobble b: Int) -> Int {
+  a + b
+}
+
+pub fn main() {
+  add(wibble: 1, wobble: 2)
+}
+";
+
+    assert_hover!(
+        TestProject::for_source(code),
+        find_position_of("wobble: 2").under_char('b')
+    );
+}
+
+#[test]
+fn hover_for_label_of_documented_record_field() {
+    let code = "
+pub type Person {
+  Person(
+    /// The person's name.
+    name: String,
+    age: Int,
+  )
+}
+
+pub fn main() {
+  Person(age: 1, name: \"Lucy\")
+}
+";
+
+    assert_hover!(
+        TestProject::for_source(code),
+        find_position_of("name: \"").under_char('a')
+    );
+}
@@ -0,0 +1,202 @@
+use lsp_types::{InlayHintLabel, InlayHintParams};
+
+use super::*;
+
+const ALL_HINTS: InlayHintsConfig = InlayHintsConfig {
+    parameter_types: true,
+    return_types: true,
+    labels: true,
+};
+
+/// Shows the inlay hints of the whole module as if they were part of its code.
+fn inlay_hints(project: TestProject<'_>) -> String {
+    let src = project.src;
+    let mut hints = project.at(Position::default(), |engine, params, _| {
+        let params = InlayHintParams {
+            work_done_progress_params: Default::default(),
+            text_document: params.text_document,
+            range: lsp_types::Range::new(Position::new(0, 0), Position::new(u32::MAX, 0)),
+        };
+        engine.inlay_hint(params).result.unwrap()
+    });
+    hints.sort_by_key(|hint| (hint.position.line, hint.position.character));
+
+    let line_numbers = LineNumbers::new(src);
+    let mut output = src.to_string();
+    for hint in hints.iter().rev() {
+        let InlayHintLabel::String(label) = &hint.label else {
+            panic!("expected a string label");
+        };
+        let padding = if hint.padding_right == Some(true) {
+            " "
+        } else {
+            ""
+        };
+        let index = line_numbers.byte_index(hint.position.line, hint.position.character);
+        output.insert_str(index as usize, &format!("{label}{padding}"));
+    }
+    output
+}
+
+#[test]
+fn no_hints_by_default() {
+    let code = "
+pub fn add(a, b) {
+  a + b
+}
+";
+
+    assert_eq!(inlay_hints(TestProject::for_source(code)), code);
+}
+
+#[test]
+fn function_parameter_and_return_types() {
+    let code = "
+pub fn add(a, b) {
+  a + b
+}
+";
+
+    assert_eq!(
+        inlay_hints(TestProject::for_source(code).inlay_hints(ALL_HINTS)),
+        "
+pub fn add(a: Int, b: Int) -> Int {
+  a + b
+}
+"
+    );
+}
+
+#[test]
+fn annotated_function_has_no_type_hints() {
+    let code = "
+pub fn add(a: Int, b) -> Int {
+  a + b
+}
+";
+
+    assert_eq!(
+        inlay_hints(TestProject::for_source(code).inlay_hints(ALL_HINTS)),
+        "
+pub fn add(a: Int, b: Int) -> Int {
+  a + b
+}
+"
+    );
+}
+
+#[test]
+fn generic_function_types() {
+    let code = "
+pub fn identity(x) {
+  x
+}
+";
+
+    assert_eq!(
+        inlay_hints(TestProject::for_source(code).inlay_hints(ALL_HINTS)),
+        "
+pub fn identity(x: a) -> a {
+  x
+}
+"
+    );
+}
+
+#[test]
+fn anonymous_function_types() {
+    let code = "
+pub fn main() -> Int {
+  let increment = fn(x) { x + 1 }
+  increment(1)
+}
+";
+
+    assert_eq!(
+        inlay_hints(TestProject::for_source(code).inlay_hints(ALL_HINTS)),
+        "
+pub fn main() -> Int {
+  let increment = fn(x: Int) -> Int { x + 1 }
+  increment(1)
+}
+"
+    );
+}
+
+#[test]
+fn only_return_types() {
+    let code = "
+pub fn add(a, b) {
+  a + b
+}
+";
+
+    let config = InlayHintsConfig {
+        return_types: true,
+        ..InlayHintsConfig::default()
+    };
+    assert_eq!(
+        inlay_hints(TestProject::for_source(code).inlay_hints(config)),
+        "
+pub fn add(a, b) -> Int {
+  a + b
+}
+"
+    );
+}
+
+#[test]
+fn argument_labels() {
+    let code = r#"
+pub fn greet(greeting g: String, name n: String) -> String {
+  g <> n
+}
+
+pub fn main() -> String {
+  let name = "Louis"
+  greet("Hello", name)
+}
+"#;
+
+    assert_eq!(
+        inlay_hints(TestProject::for_source(code).inlay_hints(ALL_HINTS)),
+        r#"
+pub fn greet(greeting g: String, name n: String) -> String {
+  g <> n
+}
+
+pub fn main() -> String {
+  let name = "Louis"
+  greet(greeting: "Hello", name)
+}
+"#
+    );
+}
+
+#[test]
+fn labelled_and_piped_arguments_have_no_label_hints() {
+    let code = r#"
+pub fn greet(greeting g: String, name n: String) -> String {
+  g <> n
+}
+
+pub fn main() -> String {
+  let _ = greet(name: "Louis", greeting: "Hello")
+  "Hello" |> greet("Louis")
+}
+"#;
+
+    assert_eq!(
+        inlay_hints(TestProject::for_source(code).inlay_hints(ALL_HINTS)),
+        r#"
+pub fn greet(greeting g: String, name n: String) -> String {
+  g <> n
+}
+
+pub fn main() -> String {
+  let _ = greet(name: "Louis", greeting: "Hello")
+  "Hello" |> greet(name: "Louis")
+}
+"#
+    );
+}
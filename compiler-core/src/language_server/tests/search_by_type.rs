@@ -0,0 +1,77 @@
+use crate::language_server::extensions::{SearchByTypeParams, SearchByTypeResult};
+
+use super::*;
+
+fn search_by_type(tester: TestProject<'_>, query: &str) -> Vec<SearchByTypeResult> {
+    tester.at(Position::default(), |engine, _, _| {
+        let params = SearchByTypeParams {
+            query: query.into(),
+        };
+        engine.search_by_type(&params).result.unwrap()
+    })
+}
+
+fn result_names(tester: TestProject<'_>, query: &str) -> Vec<(String, String, String)> {
+    search_by_type(tester, query)
+        .into_iter()
+        .map(|result| (result.module, result.name, result.signature))
+        .collect()
+}
+
+const CODE: &str = "
+pub fn map(list: List(a), fun: fn(a) -> b) -> List(b) {
+  todo
+}
+
+pub fn length(list: List(a)) -> Int {
+  todo
+}
+
+fn private_map(list: List(a), fun: fn(a) -> b) -> List(b) {
+  todo
+}
+";
+
+const DEP_CODE: &str = "
+pub fn flip_map(fun: fn(x) -> y, list: List(x)) -> List(y) {
+  todo
+}
+";
+
+#[test]
+fn search_by_type_in_project_and_dependencies() {
+    let tester = TestProject::for_source(CODE).add_dep_module("dep/list", DEP_CODE);
+
+    assert_eq!(
+        result_names(tester, "fn(List(a), fn(a) -> b) -> List(b)"),
+        vec![
+            (
+                "app".into(),
+                "map".into(),
+                "fn(List(a), fn(a) -> b) -> List(b)".into()
+            ),
+            (
+                "dep/list".into(),
+                "flip_map".into(),
+                "fn(fn(a) -> b, List(a)) -> List(b)".into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn search_by_type_gives_locations_of_project_functions() {
+    let results = search_by_type(TestProject::for_source(CODE), "fn(List(a)) -> Int");
+
+    assert_eq!(results.len(), 1);
+    let location = results[0].location.clone().unwrap();
+    assert_eq!(location.range.start, Position::new(5, 0));
+}
+
+#[test]
+fn search_by_invalid_type() {
+    assert_eq!(
+        result_names(TestProject::for_source(CODE), "fn(List(a) ->"),
+        vec![]
+    );
+}
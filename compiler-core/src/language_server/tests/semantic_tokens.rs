@@ -0,0 +1,215 @@
+use lsp_types::SemanticTokensParams;
+
+use super::*;
+use crate::language_server::semantic_tokens::legend;
+
+/// Decodes the semantic tokens of a module, describing each one as its text
+/// followed by its type and modifiers.
+fn semantic_tokens(project: TestProject<'_>) -> Vec<String> {
+    let src = project.src;
+    let tokens = project.at(Position::default(), |engine, params, _| {
+        let params = SemanticTokensParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document: params.text_document,
+        };
+        engine.semantic_tokens(params).result.unwrap().unwrap().data
+    });
+
+    let legend = legend();
+    let line_numbers = LineNumbers::new(src);
+    let mut line = 0;
+    let mut column = 0;
+    tokens
+        .iter()
+        .map(|token| {
+            column = if token.delta_line == 0 {
+                column + token.delta_start
+            } else {
+                token.delta_start
+            };
+            line += token.delta_line;
+            let start = line_numbers.byte_index(line, column) as usize;
+            let text = &src[start..start + token.length as usize];
+            let type_ = legend.token_types[token.token_type as usize].as_str();
+            let modifiers = legend
+                .token_modifiers
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| token.token_modifiers_bitset & (1 << index) != 0)
+                .map(|(_, modifier)| modifier.as_str())
+                .join(", ");
+            if modifiers.is_empty() {
+                format!("{text}: {type_}")
+            } else {
+                format!("{text}: {type_} ({modifiers})")
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn function_definition() {
+    let code = "
+pub fn add(a: Int, b: Int) -> Int {
+  a + b
+}
+";
+
+    assert_eq!(
+        semantic_tokens(TestProject::for_source(code)),
+        vec![
+            "add: function (declaration)",
+            "a: parameter (declaration)",
+            "Int: type",
+            "b: parameter (declaration)",
+            "Int: type",
+            "Int: type",
+            "a: variable",
+            "b: variable",
+        ]
+    );
+}
+
+#[test]
+fn custom_type_and_patterns() {
+    let code = "
+pub type Shape(a) {
+  Circle(radius: Float)
+  Tagged(value: a)
+}
+
+pub fn radius(shape) {
+  case shape {
+    Circle(radius: r) -> r
+    Tagged(..) -> 0.0
+  }
+}
+";
+
+    assert_eq!(
+        semantic_tokens(TestProject::for_source(code)),
+        vec![
+            "Shape: type (declaration)",
+            "a: typeParameter (declaration)",
+            "Circle: enumMember (declaration)",
+            "radius: property (declaration)",
+            "Float: type",
+            "Tagged: enumMember (declaration)",
+            "value: property (declaration)",
+            "a: typeParameter",
+            "radius: function (declaration)",
+            "shape: parameter (declaration)",
+            "shape: variable",
+            "Circle: enumMember",
+            "radius: parameter",
+            "r: variable (declaration)",
+            "r: variable",
+            "Tagged: enumMember",
+        ]
+    );
+}
+
+#[test]
+fn constants() {
+    let code = "
+pub type Wrapper {
+  Wrapper(Int)
+}
+
+const one = 1
+
+pub const wrapped: Wrapper = Wrapper(one)
+";
+
+    assert_eq!(
+        semantic_tokens(TestProject::for_source(code)),
+        vec![
+            "Wrapper: type (declaration)",
+            "Wrapper: enumMember (declaration)",
+            "Int: type",
+            "one: variable (declaration, readonly)",
+            "wrapped: variable (declaration, readonly)",
+            "Wrapper: type",
+            "Wrapper: enumMember",
+            "one: variable (readonly)",
+        ]
+    );
+}
+
+#[test]
+fn imports_and_module_selects() {
+    let code = "
+import gleam/option.{type Option, Some}
+import wibble as w
+
+pub fn main() -> Option(Int) {
+  w.wobble(Some(1))
+}
+";
+
+    assert_eq!(
+        semantic_tokens(
+            TestProject::for_source(code)
+                .add_hex_module("gleam/option", "pub type Option(a) { Some(a) None }")
+                .add_module("wibble", "pub fn wobble(x) { x }")
+        ),
+        vec![
+            "gleam/option: namespace",
+            "Option: type",
+            "Some: enumMember",
+            "wibble: namespace",
+            "w: namespace (declaration)",
+            "main: function (declaration)",
+            "Option: type",
+            "Int: type",
+            "w: namespace",
+            "wobble: function",
+            "Some: enumMember",
+        ]
+    );
+}
+
+#[test]
+fn pipelines_only_have_written_tokens() {
+    let code = "
+fn double(x) {
+  x * 2
+}
+
+pub fn main() {
+  1 |> double
+}
+";
+
+    assert_eq!(
+        semantic_tokens(TestProject::for_source(code)),
+        vec![
+            "double: function (declaration)",
+            "x: parameter (declaration)",
+            "x: variable",
+            "main: function (declaration)",
+            "double: function",
+        ]
+    );
+}
+
+#[test]
+fn external_nix_attribute() {
+    let code = r#"
+@external(nix, "./ffi.nix", "run")
+pub fn run() -> Int {
+  1
+}
+"#;
+
+    assert_eq!(
+        semantic_tokens(TestProject::for_source(code)),
+        vec![
+            "\"./ffi.nix\": namespace",
+            "\"run\": function",
+            "run: function (declaration)",
+            "Int: type",
+        ]
+    );
+}
@@ -0,0 +1,93 @@
+use crate::{
+    build::Target,
+    language_server::extensions::{ShowCompiledParams, ShowCompiledResult},
+};
+
+use super::*;
+
+fn show_compiled(tester: TestProject<'_>, target: Option<Target>) -> Option<ShowCompiledResult> {
+    tester.at(Position::default(), |engine, param, _| {
+        let params = ShowCompiledParams {
+            text_document: param.text_document,
+            target,
+        };
+        let response = engine.show_compiled(params);
+
+        response.result.unwrap()
+    })
+}
+
+#[test]
+fn show_compiled_nix() {
+    let code = "
+pub fn main() {
+  wibble(1)
+}
+
+fn wibble(x) {
+  x + 1
+}
+";
+
+    let result = show_compiled(TestProject::for_source(code), Some(Target::Nix)).unwrap();
+    assert_eq!(result.target, Target::Nix);
+    assert_eq!(result.language_id, "nix");
+    assert!(result.text.contains("main ="));
+    assert!(result.text.contains("wibble ="));
+}
+
+#[test]
+fn show_compiled_erlang() {
+    let code = "
+pub fn main() {
+  1
+}
+";
+
+    let result = show_compiled(TestProject::for_source(code), Some(Target::Erlang)).unwrap();
+    assert_eq!(result.target, Target::Erlang);
+    assert_eq!(result.language_id, "erlang");
+    assert!(result.text.contains("-export([main/0])."));
+}
+
+#[test]
+fn show_compiled_javascript() {
+    let code = "
+pub fn main() {
+  1
+}
+";
+
+    let result = show_compiled(TestProject::for_source(code), Some(Target::JavaScript)).unwrap();
+    assert_eq!(result.target, Target::JavaScript);
+    assert_eq!(result.language_id, "javascript");
+    assert!(result.text.contains("export function main()"));
+}
+
+#[test]
+fn show_compiled_defaults_to_project_target() {
+    let code = "
+pub fn main() {
+  1
+}
+";
+
+    let result = show_compiled(TestProject::for_source(code), None).unwrap();
+    assert_eq!(result.target, PackageConfig::default().target);
+}
+
+#[test]
+fn show_compiled_skips_values_without_implementation_for_target() {
+    let code = "
+@external(nix, \"./ffi.nix\", \"wibble\")
+pub fn wibble() -> Int
+
+pub fn main() {
+  1
+}
+";
+
+    let result = show_compiled(TestProject::for_source(code), Some(Target::Erlang)).unwrap();
+    assert!(result.text.contains("-export([main/0])."));
+    assert!(!result.text.contains("wibble"));
+}
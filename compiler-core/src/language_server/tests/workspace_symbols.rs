@@ -0,0 +1,113 @@
+use lsp_types::{SymbolKind, SymbolTag, WorkspaceSymbolParams};
+
+use super::*;
+
+fn workspace_symbols(tester: TestProject<'_>, query: &str) -> Vec<lsp_types::SymbolInformation> {
+    tester.at(Position::default(), |engine, _, _| {
+        let params = WorkspaceSymbolParams {
+            query: query.into(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        engine.workspace_symbol(&params).result.unwrap()
+    })
+}
+
+fn symbol_names(tester: TestProject<'_>, query: &str) -> Vec<(String, SymbolKind, String)> {
+    workspace_symbols(tester, query)
+        .into_iter()
+        .map(|symbol| {
+            let module = symbol.container_name.unwrap_or_default();
+            (symbol.name, symbol.kind, module)
+        })
+        .collect()
+}
+
+const CODE: &str = "
+pub fn main() {
+  Nil
+}
+
+fn helper() {
+  Nil
+}
+
+pub const answer = 42
+
+pub type Wibble {
+  Wobble(Int)
+  Wubble
+}
+
+pub type Alias =
+  Int
+";
+
+#[test]
+fn workspace_symbols_of_root_package() {
+    let tester = TestProject::for_source(CODE)
+        .add_module("wibble/wobble", "pub fn wobble() { 1 }")
+        .add_dep_module("dep", "pub fn dep_function() { 1 }");
+
+    assert_eq!(
+        symbol_names(tester, ""),
+        vec![
+            ("Alias".into(), SymbolKind::CLASS, "app".into()),
+            ("Wibble".into(), SymbolKind::CLASS, "app".into()),
+            ("Wobble".into(), SymbolKind::CONSTRUCTOR, "app".into()),
+            ("Wubble".into(), SymbolKind::ENUM_MEMBER, "app".into()),
+            ("answer".into(), SymbolKind::CONSTANT, "app".into()),
+            ("helper".into(), SymbolKind::FUNCTION, "app".into()),
+            ("main".into(), SymbolKind::FUNCTION, "app".into()),
+            (
+                "wobble".into(),
+                SymbolKind::FUNCTION,
+                "wibble/wobble".into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn workspace_symbols_are_fuzzy_matched() {
+    let tester = TestProject::for_source(CODE);
+
+    assert_eq!(
+        symbol_names(tester, "WBL"),
+        vec![
+            ("Wibble".into(), SymbolKind::CLASS, "app".into()),
+            ("Wobble".into(), SymbolKind::CONSTRUCTOR, "app".into()),
+            ("Wubble".into(), SymbolKind::ENUM_MEMBER, "app".into()),
+        ]
+    );
+}
+
+#[test]
+fn workspace_symbol_location() {
+    let tester = TestProject::for_source(CODE);
+    let symbols = workspace_symbols(tester, "answer");
+    let [symbol] = symbols.as_slice() else {
+        panic!("expected a single symbol, got {symbols:?}");
+    };
+
+    assert_eq!(
+        symbol.location.range,
+        lsp_types::Range::new(Position::new(9, 0), Position::new(9, 16))
+    );
+}
+
+#[test]
+fn workspace_symbols_are_tagged_deprecated() {
+    let code = r#"
+@deprecated("Use something else")
+pub fn old() {
+  Nil
+}
+"#;
+    let symbols = workspace_symbols(TestProject::for_source(code), "old");
+
+    assert_eq!(
+        symbols.into_iter().map(|symbol| symbol.tags).collect_vec(),
+        vec![Some(vec![SymbolTag::DEPRECATED])]
+    );
+}
@@ -72,8 +72,10 @@ pub mod error;
 pub mod fix;
 pub mod format;
 pub mod hex;
+pub mod i18n;
 pub mod io;
 pub mod javascript;
+#[cfg(feature = "lsp")]
 pub mod language_server;
 pub mod line_numbers;
 pub mod manifest;
@@ -86,6 +88,7 @@ pub mod pretty;
 pub mod requirement;
 pub mod strings;
 pub mod type_;
+pub mod type_search;
 pub mod uid;
 pub mod version;
 pub mod warning;
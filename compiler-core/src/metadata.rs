@@ -8,3 +8,49 @@ mod module_encoder;
 mod tests;
 
 pub use self::{module_decoder::ModuleDecoder, module_encoder::ModuleEncoder};
+
+/// The version of the module interface format, written at the start of each
+/// encoded interface.
+///
+/// Fields can be added to the schema without increasing it, as Cap'n Proto
+/// readers give defaults for the fields a message lacks and skip the ones
+/// they don't know. Removing a field, or changing its type or meaning, makes
+/// the interfaces written by other compiler versions unusable, so it must be
+/// increased then.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The bytes encoded module interfaces start with, before the format version.
+const MAGIC: &[u8; 4] = b"GLXI";
+
+/// The length of the header before the Cap'n Proto message, which is all
+/// [`is_current_format`] needs to read.
+pub const HEADER_LENGTH: usize = 8;
+
+fn header() -> [u8; HEADER_LENGTH] {
+    let mut header = [0; HEADER_LENGTH];
+    let (magic, version) = header.split_at_mut(MAGIC.len());
+    magic.copy_from_slice(MAGIC);
+    version.copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+    header
+}
+
+/// The format version of an encoded module interface, or `None` when it was
+/// written before interfaces had a header.
+fn format_version(header: &[u8; HEADER_LENGTH]) -> Option<u32> {
+    let (magic, version) = header.split_at(MAGIC.len());
+    if magic != MAGIC {
+        return None;
+    }
+    let version = version.try_into().expect("Format version length");
+    Some(u32::from_le_bytes(version))
+}
+
+/// Whether the encoded module interface can be read by this compiler. Caches
+/// written in other formats are compiled again rather than read.
+pub fn is_current_format(bytes: &[u8]) -> bool {
+    bytes
+        .get(..HEADER_LENGTH)
+        .and_then(|header| header.try_into().ok())
+        .and_then(format_version)
+        == Some(FORMAT_VERSION)
+}
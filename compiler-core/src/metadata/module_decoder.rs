@@ -13,12 +13,14 @@ use crate::{
     line_numbers::LineNumbers,
     schema_capnp::{self as schema, *},
     type_::{
-        self, expression::Implementations, AccessorsMap, Deprecation, FieldMap, ModuleInterface,
-        RecordAccessor, Type, TypeConstructor, TypeValueConstructor, TypeValueConstructorField,
-        TypeVariantConstructors, ValueConstructor, ValueConstructorVariant,
+        self,
+        expression::{Implementations, NixBlocker},
+        AccessorsMap, Deprecation, FieldMap, ModuleInterface, RecordAccessor, Type,
+        TypeConstructor, TypeValueConstructor, TypeValueConstructorField, TypeVariantConstructors,
+        ValueConstructor, ValueConstructorVariant,
     },
     uid::UniqueIdGenerator,
-    Result,
+    Error, Result,
 };
 use std::{collections::HashMap, io::BufRead, sync::Arc};
 
@@ -61,10 +63,46 @@ impl ModuleDecoder {
         }
     }
 
-    pub fn read(&mut self, reader: impl BufRead) -> Result<ModuleInterface> {
+    pub fn read(&mut self, mut reader: impl BufRead) -> Result<ModuleInterface> {
+        let mut header = [0; super::HEADER_LENGTH];
+        reader
+            .read_exact(&mut header)
+            .map_err(|error| Error::MetadataDecodeError {
+                error: Some(error.to_string()),
+            })?;
+        match super::format_version(&header) {
+            Some(super::FORMAT_VERSION) => (),
+            Some(version) => {
+                return Err(Error::MetadataDecodeError {
+                    error: Some(format!(
+                        "The module interface has format version {version} but this \
+compiler reads version {}. Compile the package again with this compiler.",
+                        super::FORMAT_VERSION
+                    )),
+                })
+            }
+            None => {
+                return Err(Error::MetadataDecodeError {
+                    error: Some(
+                        "The module interface was written by an older compiler. Compile \
+the package again with this compiler."
+                            .into(),
+                    ),
+                })
+            }
+        }
+
         let message_reader =
             capnp::serialize_packed::read_message(reader, capnp::message::ReaderOptions::new())?;
         let reader = message_reader.get_root::<module::Reader<'_>>()?;
+        let deprecation = reader.get_deprecated()?;
+        let deprecation = if deprecation.is_empty() {
+            Deprecation::NotDeprecated
+        } else {
+            Deprecation::Deprecated {
+                message: self.string(deprecation)?,
+            }
+        };
 
         Ok(ModuleInterface {
             name: self.string(reader.get_name()?)?,
@@ -83,6 +121,7 @@ impl ModuleDecoder {
             src_path: self.str(reader.get_src_path()?)?.into(),
             warnings: vec![],
             minimum_required_version: self.version(&reader.get_required_version()?),
+            deprecation,
         })
     }
 
@@ -116,6 +155,7 @@ impl ModuleDecoder {
             parameters: read_vec!(reader.get_parameters()?, self, type_),
             type_,
             deprecation,
+            since: self.optional_string(self.str(reader.get_since()?)?),
             documentation: self.optional_string(self.str(reader.get_documentation()?)?),
         })
     }
@@ -247,6 +287,7 @@ impl ModuleDecoder {
         };
         Ok(ValueConstructor {
             deprecation,
+            since: self.optional_string(self.str(reader.get_since()?)?),
             publicity,
             type_,
             variant,
@@ -482,7 +523,7 @@ impl ModuleDecoder {
             location: self.src_span(&reader.get_location()?)?,
             literal: self.constant(&reader.get_literal()?)?,
             module: self.string(reader.get_module()?)?,
-            implementations: self.implementations(reader.get_implementations()?),
+            implementations: self.implementations(reader.get_implementations()?)?,
         })
     }
 
@@ -512,15 +553,25 @@ impl ModuleDecoder {
             field_map: self.field_map(&reader.get_field_map()?)?,
             location: self.src_span(&reader.get_location()?)?,
             documentation: self.optional_string(self.str(reader.get_documentation()?)?),
-            implementations: self.implementations(reader.get_implementations()?),
+            implementations: self.implementations(reader.get_implementations()?)?,
             external_erlang: self.optional_external(reader.get_external_erlang()?)?,
             external_javascript: self.optional_external(reader.get_external_javascript()?)?,
             external_nix: self.optional_external(reader.get_external_nix()?)?,
         })
     }
 
-    fn implementations(&self, reader: implementations::Reader<'_>) -> Implementations {
-        Implementations {
+    fn implementations(&self, reader: implementations::Reader<'_>) -> Result<Implementations> {
+        let nix_blocker_name = reader.get_nix_blocker_name()?;
+        let nix_blocker = if nix_blocker_name.is_empty() {
+            None
+        } else {
+            Some(NixBlocker {
+                module: self.string(reader.get_nix_blocker_module()?)?,
+                name: self.string(nix_blocker_name)?,
+                location: self.src_span(&reader.get_nix_blocker_location()?)?,
+            })
+        };
+        Ok(Implementations {
             gleam: reader.get_gleam(),
             uses_erlang_externals: reader.get_uses_erlang_externals(),
             uses_javascript_externals: reader.get_uses_javascript_externals(),
@@ -528,7 +579,8 @@ impl ModuleDecoder {
             can_run_on_erlang: reader.get_can_run_on_erlang(),
             can_run_on_javascript: reader.get_can_run_on_javascript(),
             can_run_on_nix: reader.get_can_run_on_nix(),
-        }
+            nix_blocker,
+        })
     }
 
     fn record(
@@ -12,7 +12,8 @@ use crate::{
         ValueConstructor, ValueConstructorVariant,
     },
 };
-use std::{collections::HashMap, ops::Deref, sync::Arc};
+use itertools::Itertools;
+use std::{cmp::Ordering, collections::HashMap, ops::Deref, sync::Arc};
 
 #[derive(Debug)]
 pub struct ModuleEncoder<'a> {
@@ -33,7 +34,7 @@ impl<'a> ModuleEncoder<'a> {
     pub fn encode(mut self) -> crate::Result<Vec<u8>> {
         let span = tracing::info_span!("metadata");
         let _enter = span.enter();
-        let mut buffer = Vec::new();
+        let mut buffer = super::header().to_vec();
 
         let mut message = capnp::message::Builder::new_default();
 
@@ -42,6 +43,10 @@ impl<'a> ModuleEncoder<'a> {
         module.set_package(&self.data.package);
         module.set_src_path(self.data.src_path.as_str());
         module.set_is_internal(self.data.is_internal);
+        module.set_deprecated(match &self.data.deprecation {
+            Deprecation::NotDeprecated => "",
+            Deprecation::Deprecated { message } => message,
+        });
         self.set_module_types(&mut module);
         self.set_module_values(&mut module);
         self.set_module_accessors(&mut module);
@@ -67,7 +72,7 @@ impl<'a> ModuleEncoder<'a> {
         let mut builder = module
             .reborrow()
             .init_accessors(self.data.accessors.len() as u32);
-        for (i, (key, map)) in self.data.accessors.iter().enumerate() {
+        for (i, (key, map)) in self.data.accessors.iter().sorted_by(by_key).enumerate() {
             let mut property = builder.reborrow().get(i as u32);
             property.set_key(key);
             self.build_accessors_map(property.init_value(), map);
@@ -83,7 +88,12 @@ impl<'a> ModuleEncoder<'a> {
         let mut accessors_builder = builder
             .reborrow()
             .init_shared_accessors(accessors.shared_accessors.len() as u32);
-        for (i, (name, accessor)) in accessors.shared_accessors.iter().enumerate() {
+        for (i, (name, accessor)) in accessors
+            .shared_accessors
+            .iter()
+            .sorted_by(by_key)
+            .enumerate()
+        {
             let mut property = accessors_builder.reborrow().get(i as u32);
             property.set_key(name);
             self.build_record_accessor(property.init_value(), accessor)
@@ -102,7 +112,7 @@ impl<'a> ModuleEncoder<'a> {
         accessors: &HashMap<EcoString, RecordAccessor>,
     ) {
         let mut builder = builder.init_accessors(accessors.len() as u32);
-        for (i, (name, accessor)) in accessors.iter().enumerate() {
+        for (i, (name, accessor)) in accessors.iter().sorted_by(by_key).enumerate() {
             let mut property = builder.reborrow().get(i as u32);
             property.set_key(name);
             self.build_record_accessor(property.init_value(), accessor)
@@ -121,7 +131,7 @@ impl<'a> ModuleEncoder<'a> {
 
     fn set_module_types(&mut self, module: &mut module::Builder<'_>) {
         let mut types = module.reborrow().init_types(self.data.types.len() as u32);
-        for (i, (name, type_)) in self.data.types.iter().enumerate() {
+        for (i, (name, type_)) in self.data.types.iter().sorted_by(by_key).enumerate() {
             let mut property = types.reborrow().get(i as u32);
             property.set_key(name);
             self.build_type_constructor(property.init_value(), type_)
@@ -132,7 +142,13 @@ impl<'a> ModuleEncoder<'a> {
         let mut types_constructors = module
             .reborrow()
             .init_types_constructors(self.data.types_value_constructors.len() as u32);
-        for (i, (name, data)) in self.data.types_value_constructors.iter().enumerate() {
+        for (i, (name, data)) in self
+            .data
+            .types_value_constructors
+            .iter()
+            .sorted_by(by_key)
+            .enumerate()
+        {
             let mut property = types_constructors.reborrow().get(i as u32);
             property.set_key(name);
             self.build_type_variant_constructors(property.init_value(), data)
@@ -161,7 +177,7 @@ impl<'a> ModuleEncoder<'a> {
 
     fn set_module_values(&mut self, module: &mut module::Builder<'_>) {
         let mut values = module.reborrow().init_values(self.data.values.len() as u32);
-        for (i, (name, value)) in self.data.values.iter().enumerate() {
+        for (i, (name, value)) in self.data.values.iter().sorted_by(by_key).enumerate() {
             let mut property = values.reborrow().get(i as u32);
             property.set_key(name);
             self.build_value_constructor(property.init_value(), value)
@@ -185,6 +201,7 @@ impl<'a> ModuleEncoder<'a> {
             Deprecation::NotDeprecated => "",
             Deprecation::Deprecated { message } => message,
         });
+        builder.set_since(constructor.since.as_deref().unwrap_or_default());
         self.build_publicity(builder.reborrow().init_publicity(), constructor.publicity);
         let type_builder = builder.reborrow().init_type();
         self.build_type(type_builder, &constructor.type_);
@@ -236,6 +253,7 @@ impl<'a> ModuleEncoder<'a> {
             Deprecation::NotDeprecated => "",
             Deprecation::Deprecated { message } => message,
         });
+        builder.set_since(constructor.since.as_deref().unwrap_or_default());
 
         self.build_publicity(builder.reborrow().init_publicity(), constructor.publicity);
         self.build_type(builder.reborrow().init_type(), &constructor.type_);
@@ -293,7 +311,7 @@ impl<'a> ModuleEncoder<'a> {
                 self.build_src_span(builder.reborrow().init_location(), *location);
                 self.build_constant(builder.reborrow().init_literal(), literal);
                 builder.reborrow().set_module(module);
-                self.build_implementations(builder.init_implementations(), *implementations)
+                self.build_implementations(builder.init_implementations(), implementations)
             }
 
             ValueConstructorVariant::Record {
@@ -342,7 +360,7 @@ impl<'a> ModuleEncoder<'a> {
                 self.build_external(builder.reborrow().init_external_nix(), external_nix);
                 self.build_optional_field_map(builder.reborrow().init_field_map(), field_map);
                 self.build_src_span(builder.reborrow().init_location(), *location);
-                self.build_implementations(builder.init_implementations(), *implementations);
+                self.build_implementations(builder.init_implementations(), implementations);
             }
         }
     }
@@ -361,7 +379,7 @@ impl<'a> ModuleEncoder<'a> {
     fn build_field_map(&mut self, mut builder: field_map::Builder<'_>, field_map: &FieldMap) {
         builder.set_arity(field_map.arity);
         let mut builder = builder.init_fields(field_map.fields.len() as u32);
-        for (i, (name, &position)) in field_map.fields.iter().enumerate() {
+        for (i, (name, &position)) in field_map.fields.iter().sorted_by(by_key).enumerate() {
             let mut field = builder.reborrow().get(i as u32);
             field.set_key(name);
             field.init_value().set_value(position);
@@ -579,9 +597,9 @@ impl<'a> ModuleEncoder<'a> {
     }
 
     fn build_implementations(
-        &self,
+        &mut self,
         mut builder: implementations::Builder<'_>,
-        implementations: Implementations,
+        implementations: &Implementations,
     ) {
         builder.set_gleam(implementations.gleam);
         builder.set_uses_erlang_externals(implementations.uses_erlang_externals);
@@ -590,6 +608,11 @@ impl<'a> ModuleEncoder<'a> {
         builder.set_can_run_on_erlang(implementations.can_run_on_erlang);
         builder.set_can_run_on_javascript(implementations.can_run_on_javascript);
         builder.set_can_run_on_nix(implementations.can_run_on_nix);
+        if let Some(blocker) = &implementations.nix_blocker {
+            builder.set_nix_blocker_module(&blocker.module);
+            builder.set_nix_blocker_name(&blocker.name);
+            self.build_src_span(builder.init_nix_blocker_location(), blocker.location);
+        }
     }
 
     fn build_external(
@@ -607,3 +630,10 @@ impl<'a> ModuleEncoder<'a> {
         }
     }
 }
+
+/// Orders map entries by their key. Maps are encoded in this order so that the
+/// same module always produces the same bytes, regardless of hash map
+/// iteration order.
+fn by_key<K: Ord, V>((a, _): &(&K, &V), (b, _): &(&K, &V)) -> Ordering {
+    a.cmp(b)
+}
@@ -11,8 +11,10 @@ use crate::{
     build::Origin,
     line_numbers::LineNumbers,
     type_::{
-        self, expression::Implementations, Deprecation, ModuleInterface, Type, TypeConstructor,
-        TypeValueConstructor, TypeValueConstructorField, TypeVariantConstructors, ValueConstructor,
+        self,
+        expression::{Implementations, NixBlocker},
+        Deprecation, ModuleInterface, Type, TypeConstructor, TypeValueConstructor,
+        TypeValueConstructorField, TypeVariantConstructors, ValueConstructor,
         ValueConstructorVariant,
     },
     uid::UniqueIdGenerator,
@@ -44,6 +46,7 @@ fn constant_module(constant: TypedConstant) -> ModuleInterface {
             ValueConstructor {
                 publicity: Publicity::Public,
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: type_::int(),
                 variant: ValueConstructorVariant::ModuleConstant {
                     documentation: Some("Some documentation".into()),
@@ -58,6 +61,7 @@ fn constant_module(constant: TypedConstant) -> ModuleInterface {
                         can_run_on_erlang: true,
                         can_run_on_javascript: true,
                         can_run_on_nix: true,
+                        nix_blocker: None,
                     },
                 },
             },
@@ -66,6 +70,7 @@ fn constant_module(constant: TypedConstant) -> ModuleInterface {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     }
 }
 
@@ -100,6 +105,80 @@ fn empty_module() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
+    };
+    assert_eq!(roundtrip(&module), module);
+}
+
+fn encoded_empty_module() -> Vec<u8> {
+    let module = ModuleInterface {
+        warnings: vec![],
+        is_internal: false,
+        package: "some_package".into(),
+        origin: Origin::Src,
+        name: "one".into(),
+        types: HashMap::new(),
+        types_value_constructors: HashMap::new(),
+        values: HashMap::new(),
+        accessors: HashMap::new(),
+        line_numbers: LineNumbers::new(""),
+        src_path: "some_path".into(),
+        minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
+    };
+    ModuleEncoder::new(&module).encode().unwrap()
+}
+
+#[test]
+fn interface_has_current_format_version() {
+    let bytes = encoded_empty_module();
+    assert!(bytes.starts_with(b"GLXI"));
+    assert!(is_current_format(&bytes));
+}
+
+#[test]
+fn interface_without_header_is_not_read() {
+    let bytes = encoded_empty_module();
+    let without_header = bytes.get(8..).unwrap();
+    assert!(!is_current_format(without_header));
+    let result = ModuleDecoder::new(UniqueIdGenerator::new()).read(without_header);
+    assert!(matches!(
+        result,
+        Err(crate::Error::MetadataDecodeError { .. })
+    ));
+}
+
+#[test]
+fn interface_of_other_format_version_is_not_read() {
+    let mut bytes = encoded_empty_module();
+    let next_version = (FORMAT_VERSION + 1).to_le_bytes();
+    bytes.get_mut(4..8).unwrap().copy_from_slice(&next_version);
+    assert!(!is_current_format(&bytes));
+    let result = ModuleDecoder::new(UniqueIdGenerator::new()).read(bytes.as_slice());
+    assert!(matches!(
+        result,
+        Err(crate::Error::MetadataDecodeError { .. })
+    ));
+}
+
+#[test]
+fn deprecated_module() {
+    let module = ModuleInterface {
+        warnings: vec![],
+        is_internal: false,
+        package: "some_package".into(),
+        origin: Origin::Src,
+        name: "one/two".into(),
+        types: HashMap::new(),
+        types_value_constructors: HashMap::new(),
+        values: HashMap::new(),
+        accessors: HashMap::new(),
+        line_numbers: LineNumbers::new(""),
+        src_path: "some_path".into(),
+        minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::Deprecated {
+            message: "use one/three instead".into(),
+        },
     };
     assert_eq!(roundtrip(&module), module);
 }
@@ -123,6 +202,7 @@ fn with_line_numbers() {
         ),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
     assert_eq!(roundtrip(&module), module);
 }
@@ -144,6 +224,7 @@ fn module_with_private_type() {
                 module: "the/module".into(),
                 parameters: vec![],
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 documentation: None,
             },
         )]
@@ -154,6 +235,7 @@ fn module_with_private_type() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
     assert_eq!(roundtrip(&module), module);
 }
@@ -175,6 +257,7 @@ fn module_with_app_type() {
                 module: "the/module".into(),
                 parameters: vec![],
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 documentation: None,
             },
         )]
@@ -185,6 +268,7 @@ fn module_with_app_type() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
     assert_eq!(roundtrip(&module), module);
 }
@@ -206,6 +290,7 @@ fn module_with_fn_type() {
                 module: "the/module".into(),
                 parameters: vec![],
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 documentation: None,
             },
         )]
@@ -216,6 +301,7 @@ fn module_with_fn_type() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
     assert_eq!(roundtrip(&module), module);
 }
@@ -237,6 +323,7 @@ fn module_with_tuple_type() {
                 module: "the/module".into(),
                 parameters: vec![],
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 documentation: None,
             },
         )]
@@ -247,6 +334,7 @@ fn module_with_tuple_type() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
     assert_eq!(roundtrip(&module), module);
 }
@@ -274,6 +362,7 @@ fn module_with_generic_type() {
                     module: "the/module".into(),
                     parameters: vec![t1, t2],
                     deprecation: Deprecation::NotDeprecated,
+                    since: None,
                     documentation: None,
                 },
             )]
@@ -284,6 +373,7 @@ fn module_with_generic_type() {
             line_numbers: LineNumbers::new(""),
             src_path: "some_path".into(),
             minimum_required_version: Version::new(0, 1, 0),
+            deprecation: Deprecation::NotDeprecated,
         }
     }
 
@@ -311,6 +401,7 @@ fn module_with_type_links() {
                     module: "a".into(),
                     parameters: vec![],
                     deprecation: Deprecation::NotDeprecated,
+                    since: None,
                     documentation: None,
                 },
             )]
@@ -321,6 +412,7 @@ fn module_with_type_links() {
             line_numbers: LineNumbers::new(""),
             src_path: "some_path".into(),
             minimum_required_version: Version::new(0, 1, 0),
+            deprecation: Deprecation::NotDeprecated,
         }
     }
 
@@ -348,6 +440,7 @@ fn module_with_type_constructor_documentation() {
                     module: "a".into(),
                     parameters: vec![],
                     deprecation: Deprecation::NotDeprecated,
+                    since: None,
                     documentation: Some("type documentation".into()),
                 },
             )]
@@ -358,6 +451,7 @@ fn module_with_type_constructor_documentation() {
             line_numbers: LineNumbers::new(""),
             src_path: "some_path".into(),
             minimum_required_version: Version::new(0, 1, 0),
+            deprecation: Deprecation::NotDeprecated,
         }
     }
 
@@ -388,6 +482,7 @@ fn module_with_type_constructor_origin() {
                     module: "a".into(),
                     parameters: vec![],
                     deprecation: Deprecation::NotDeprecated,
+                    since: None,
                     documentation: None,
                 },
             )]
@@ -398,6 +493,7 @@ fn module_with_type_constructor_origin() {
             line_numbers: LineNumbers::new(""),
             src_path: "some_path".into(),
             minimum_required_version: Version::new(0, 1, 0),
+            deprecation: Deprecation::NotDeprecated,
         }
     }
 
@@ -429,6 +525,7 @@ fn module_type_to_constructors_mapping() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -450,6 +547,7 @@ fn module_fn_value() {
             ValueConstructor {
                 publicity: Publicity::Public,
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: type_::int(),
                 variant: ValueConstructorVariant::ModuleFn {
                     documentation: Some("wobble!".into()),
@@ -472,6 +570,7 @@ fn module_fn_value() {
                         can_run_on_erlang: true,
                         can_run_on_javascript: true,
                         can_run_on_nix: true,
+                        nix_blocker: None,
                     },
                 },
             },
@@ -480,6 +579,67 @@ fn module_fn_value() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
+    };
+    assert_eq!(roundtrip(&module), module);
+}
+
+#[test]
+fn module_fn_value_with_nix_blocker() {
+    let module = ModuleInterface {
+        warnings: vec![],
+        is_internal: false,
+        package: "some_package".into(),
+        origin: Origin::Src,
+        name: "a".into(),
+        types: HashMap::new(),
+        types_value_constructors: HashMap::new(),
+        accessors: HashMap::new(),
+        values: [(
+            "one".into(),
+            ValueConstructor {
+                publicity: Publicity::Public,
+                deprecation: Deprecation::NotDeprecated,
+                since: None,
+                type_: type_::int(),
+                variant: ValueConstructorVariant::ModuleFn {
+                    documentation: None,
+                    name: "one".into(),
+                    field_map: None,
+                    module: "a".into(),
+                    arity: 0,
+                    location: SrcSpan {
+                        start: 535,
+                        end: 1100,
+                    },
+                    external_erlang: None,
+                    external_javascript: None,
+                    external_nix: None,
+                    implementations: Implementations {
+                        gleam: false,
+                        uses_erlang_externals: true,
+                        uses_javascript_externals: false,
+                        uses_nix_externals: false,
+                        can_run_on_erlang: true,
+                        can_run_on_javascript: false,
+                        can_run_on_nix: false,
+                        nix_blocker: Some(NixBlocker {
+                            module: "b/c".into(),
+                            name: "erlang_only".into(),
+                            location: SrcSpan {
+                                start: 600,
+                                end: 611,
+                            },
+                        }),
+                    },
+                },
+            },
+        )]
+        .into(),
+        line_numbers: LineNumbers::new(""),
+        src_path: "some_path".into(),
+        minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
     assert_eq!(roundtrip(&module), module);
 }
@@ -502,6 +662,60 @@ fn deprecated_module_fn_value() {
                 deprecation: Deprecation::Deprecated {
                     message: "wibble wobble".into(),
                 },
+                since: None,
+                type_: type_::int(),
+                variant: ValueConstructorVariant::ModuleFn {
+                    documentation: Some("wobble!".into()),
+                    name: "one".into(),
+                    field_map: None,
+                    module: "a".into(),
+                    arity: 5,
+                    location: SrcSpan {
+                        start: 535,
+                        end: 1100,
+                    },
+                    external_erlang: None,
+                    external_javascript: None,
+                    external_nix: None,
+                    implementations: Implementations {
+                        gleam: true,
+                        uses_erlang_externals: false,
+                        uses_javascript_externals: false,
+                        uses_nix_externals: false,
+                        can_run_on_erlang: true,
+                        can_run_on_javascript: true,
+                        can_run_on_nix: true,
+                        nix_blocker: None,
+                    },
+                },
+            },
+        )]
+        .into(),
+        line_numbers: LineNumbers::new(""),
+        src_path: "some_path".into(),
+        minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
+    };
+    assert_eq!(roundtrip(&module), module);
+}
+
+#[test]
+fn module_fn_value_since() {
+    let module = ModuleInterface {
+        warnings: vec![],
+        is_internal: false,
+        package: "some_package".into(),
+        origin: Origin::Src,
+        name: "a".into(),
+        types: HashMap::new(),
+        types_value_constructors: HashMap::new(),
+        accessors: HashMap::new(),
+        values: [(
+            "one".into(),
+            ValueConstructor {
+                publicity: Publicity::Public,
+                deprecation: Deprecation::NotDeprecated,
+                since: Some("1.2.0".into()),
                 type_: type_::int(),
                 variant: ValueConstructorVariant::ModuleFn {
                     documentation: Some("wobble!".into()),
@@ -524,6 +738,7 @@ fn deprecated_module_fn_value() {
                         can_run_on_erlang: true,
                         can_run_on_javascript: true,
                         can_run_on_nix: true,
+                        nix_blocker: None,
                     },
                 },
             },
@@ -532,6 +747,7 @@ fn deprecated_module_fn_value() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
     assert_eq!(roundtrip(&module), module);
 }
@@ -552,6 +768,7 @@ fn private_module_fn_value() {
             ValueConstructor {
                 publicity: Publicity::Private,
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: type_::int(),
                 variant: ValueConstructorVariant::ModuleFn {
                     documentation: Some("wobble!".into()),
@@ -574,6 +791,7 @@ fn private_module_fn_value() {
                         can_run_on_erlang: true,
                         can_run_on_javascript: true,
                         can_run_on_nix: true,
+                        nix_blocker: None,
                     },
                 },
             },
@@ -582,6 +800,7 @@ fn private_module_fn_value() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -604,6 +823,7 @@ fn module_fn_value_regression() {
             ValueConstructor {
                 publicity: Publicity::Public,
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: type_::int(),
                 variant: ValueConstructorVariant::ModuleFn {
                     documentation: Some("wabble!".into()),
@@ -626,6 +846,7 @@ fn module_fn_value_regression() {
                         can_run_on_erlang: true,
                         can_run_on_javascript: true,
                         can_run_on_nix: true,
+                        nix_blocker: None,
                     },
                 },
             },
@@ -634,6 +855,7 @@ fn module_fn_value_regression() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -655,6 +877,7 @@ fn module_fn_value_with_field_map() {
             ValueConstructor {
                 publicity: Publicity::Public,
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: type_::int(),
                 variant: ValueConstructorVariant::ModuleFn {
                     documentation: Some("wubble!".into()),
@@ -677,6 +900,7 @@ fn module_fn_value_with_field_map() {
                         can_run_on_erlang: true,
                         can_run_on_javascript: true,
                         can_run_on_nix: true,
+                        nix_blocker: None,
                     },
                 },
             },
@@ -685,6 +909,7 @@ fn module_fn_value_with_field_map() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -708,6 +933,7 @@ fn record_value() {
             ValueConstructor {
                 publicity: Publicity::Public,
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: type_::int(),
                 variant: ValueConstructorVariant::Record {
                     documentation: Some("webble!".into()),
@@ -728,6 +954,7 @@ fn record_value() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -751,6 +978,7 @@ fn record_value_with_field_map() {
             ValueConstructor {
                 publicity: Publicity::Public,
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: type_::int(),
                 variant: ValueConstructorVariant::Record {
                     documentation: Some("wybble!".into()),
@@ -774,6 +1002,7 @@ fn record_value_with_field_map() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -842,6 +1071,7 @@ fn accessors() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -990,6 +1220,7 @@ fn constant_var() {
         constructor: Some(Box::from(ValueConstructor {
             publicity: Publicity::Public,
             deprecation: Deprecation::NotDeprecated,
+            since: None,
             type_: type_::int(),
             variant: ValueConstructorVariant::ModuleConstant {
                 documentation: Some("some doc".into()),
@@ -1004,6 +1235,7 @@ fn constant_var() {
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: true,
+                    nix_blocker: None,
                 },
             },
         })),
@@ -1024,6 +1256,7 @@ fn constant_var() {
                 ValueConstructor {
                     publicity: Publicity::Public,
                     deprecation: Deprecation::NotDeprecated,
+                    since: None,
                     type_: type_::int(),
                     variant: ValueConstructorVariant::ModuleConstant {
                         documentation: Some("some doc!!!!!!!!!".into()),
@@ -1038,6 +1271,7 @@ fn constant_var() {
                             can_run_on_erlang: true,
                             can_run_on_javascript: true,
                             can_run_on_nix: true,
+                            nix_blocker: None,
                         },
                     },
                 },
@@ -1047,6 +1281,7 @@ fn constant_var() {
                 ValueConstructor {
                     publicity: Publicity::Public,
                     deprecation: Deprecation::NotDeprecated,
+                    since: None,
                     type_: type_::int(),
                     variant: ValueConstructorVariant::ModuleConstant {
                         documentation: Some("some doc yeah".into()),
@@ -1061,6 +1296,7 @@ fn constant_var() {
                             can_run_on_erlang: true,
                             can_run_on_javascript: true,
                             can_run_on_nix: true,
+                            nix_blocker: None,
                         },
                     },
                 },
@@ -1070,6 +1306,7 @@ fn constant_var() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -1252,6 +1489,40 @@ fn deprecated_type() {
                 deprecation: Deprecation::Deprecated {
                     message: "oh no".into(),
                 },
+                since: None,
+                documentation: None,
+            },
+        )]
+        .into(),
+        types_value_constructors: HashMap::new(),
+        values: HashMap::new(),
+        accessors: HashMap::new(),
+        line_numbers: LineNumbers::new(""),
+        src_path: "some_path".into(),
+        minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
+    };
+    assert_eq!(roundtrip(&module), module);
+}
+
+#[test]
+fn type_since() {
+    let module = ModuleInterface {
+        warnings: vec![],
+        is_internal: false,
+        package: "some_package".into(),
+        origin: Origin::Src,
+        name: "a/b".into(),
+        types: [(
+            "ListIntType".into(),
+            TypeConstructor {
+                type_: type_::list(type_::int()),
+                publicity: Publicity::Public,
+                origin: Default::default(),
+                module: "the/module".into(),
+                parameters: vec![],
+                deprecation: Deprecation::NotDeprecated,
+                since: Some("0.3.0".into()),
                 documentation: None,
             },
         )]
@@ -1262,6 +1533,7 @@ fn deprecated_type() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
     assert_eq!(roundtrip(&module), module);
 }
@@ -1282,6 +1554,7 @@ fn module_fn_value_with_external_implementations() {
             ValueConstructor {
                 publicity: Publicity::Public,
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: type_::int(),
                 variant: ValueConstructorVariant::ModuleFn {
                     documentation: Some("wabble!".into()),
@@ -1304,6 +1577,7 @@ fn module_fn_value_with_external_implementations() {
                         can_run_on_erlang: false,
                         can_run_on_javascript: true,
                         can_run_on_nix: false,
+                        nix_blocker: None,
                     },
                 },
             },
@@ -1312,6 +1586,7 @@ fn module_fn_value_with_external_implementations() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -1335,6 +1610,7 @@ fn internal_module_fn() {
                     attribute_location: None,
                 },
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: type_::int(),
                 variant: ValueConstructorVariant::ModuleFn {
                     documentation: Some("wabble!".into()),
@@ -1357,6 +1633,7 @@ fn internal_module_fn() {
                         can_run_on_erlang: true,
                         can_run_on_javascript: true,
                         can_run_on_nix: false,
+                        nix_blocker: None,
                     },
                 },
             },
@@ -1365,6 +1642,7 @@ fn internal_module_fn() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -1391,6 +1669,7 @@ fn internal_annotated_module_fn() {
                     }),
                 },
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: type_::int(),
                 variant: ValueConstructorVariant::ModuleFn {
                     documentation: Some("wabble!".into()),
@@ -1413,6 +1692,7 @@ fn internal_annotated_module_fn() {
                         can_run_on_erlang: true,
                         can_run_on_javascript: true,
                         can_run_on_nix: false,
+                        nix_blocker: None,
                     },
                 },
             },
@@ -1421,6 +1701,7 @@ fn internal_annotated_module_fn() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     assert_eq!(roundtrip(&module), module);
@@ -1461,6 +1742,7 @@ fn type_variable_ids_in_constructors_are_shared() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
 
     let expected = HashMap::from([(
@@ -1511,6 +1793,7 @@ fn type_with_inferred_variant() {
                 module: "the/module".into(),
                 parameters: vec![],
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 documentation: None,
             },
         )]
@@ -1521,6 +1804,21 @@ fn type_with_inferred_variant() {
         line_numbers: LineNumbers::new(""),
         src_path: "some_path".into(),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: Deprecation::NotDeprecated,
     };
     assert_eq!(roundtrip(&module), module);
 }
+
+#[test]
+fn encoding_is_deterministic() {
+    // Each hash map iterates in its own order, so the same module built twice
+    // would be encoded differently if its maps weren't sorted first.
+    let ids = UniqueIdGenerator::new();
+    let first = ModuleEncoder::new(&type_::build_prelude(&ids))
+        .encode()
+        .unwrap();
+    let second = ModuleEncoder::new(&type_::build_prelude(&ids))
+        .encode()
+        .unwrap();
+    assert_eq!(first, second);
+}
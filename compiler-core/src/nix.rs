@@ -1,34 +1,74 @@
+mod decision;
+mod escape;
 mod expression;
+pub mod format;
 mod import;
+mod inline;
 mod pattern;
 pub mod syntax;
 #[cfg(test)]
 mod tests;
 
-use crate::analyse::TargetSupport;
+use crate::analyse::{
+    derive::{self, NixosOptionType},
+    TargetSupport,
+};
 use crate::ast::{
-    AssignName, CustomType, Definition, Function, Import, ModuleConstant, Publicity, SrcSpan,
-    TypeAlias, TypedConstant, TypedDefinition, TypedFunction, TypedModule, TypedRecordConstructor,
-    TypedRecordConstructorArg, UnqualifiedImport,
+    visit::{self, Visit},
+    AssignName, ClauseGuard, Constant, CustomType, Definition, Function, Import, ModuleConstant,
+    Publicity, SrcSpan, TypeAlias, TypedClause, TypedClauseGuard, TypedConstant, TypedDefinition,
+    TypedFunction, TypedModule, TypedRecordConstructor, TypedRecordConstructorArg,
+    UnqualifiedImport,
 };
 use crate::build::Target;
+use crate::config::ListRepresentation;
 use crate::docvec;
 use crate::line_numbers::LineNumbers;
 use crate::nix::import::{Imports, Member};
 use crate::pretty::{break_, concat, line, nil, Document, Documentable};
-use crate::type_::PRELUDE_MODULE_NAME;
+use crate::type_::{Type, ValueConstructor, PRELUDE_MODULE_NAME};
 use camino::Utf8Path;
 use ecow::{eco_format, EcoString};
 use itertools::Itertools;
+use std::collections::{BTreeMap, HashSet};
 
 pub const INDENT: isize = 2;
 
 pub const PRELUDE: &str = include_str!("../templates/prelude.nix");
 
+/// The version of the representation of Gleam values in generated Nix code.
+/// The prelude and the packages compiled against it must all agree on it, so
+/// it is increased whenever generated code stops being compatible with code
+/// generated by previous compiler versions.
+pub const REPRESENTATION_VERSION: u32 = 1;
+
+/// The comment at the top of the prelude re-export of each package, recording
+/// the representation version the package was generated for.
+const REPRESENTATION_VERSION_HEADER: &str = "# glistix-representation-version: ";
+
+/// The comment following the representation version in the prelude
+/// re-export of packages using native Nix lists.
+const LIST_REPRESENTATION_HEADER: &str = "# glistix-list-representation: ";
+
+/// The implementation of the `glistix/nix` module shipped with the compiler.
+pub const NIX_MODULE: &str = include_str!("../templates/nix_module.nix");
+
+/// The names exported by the prelude, which Nix externals referring to the
+/// prelude can use.
+pub fn prelude_exports() -> impl Iterator<Item = &'static str> {
+    PRELUDE
+        .rsplit_once("inherit")
+        .and_then(|(_, names)| names.split_once(';'))
+        .map(|(names, _)| names)
+        .unwrap_or_default()
+        .split_whitespace()
+}
+
 struct Generator<'module> {
     module: &'module TypedModule,
     line_numbers: &'module LineNumbers,
     target_support: TargetSupport,
+    list_representation: ListRepresentation,
     module_scope: im::HashMap<EcoString, usize>,
     tracker: UsageTracker,
     /// Used when determining relative import paths.
@@ -45,6 +85,9 @@ struct ModuleDeclaration<'a> {
     name: Document<'a>,
     /// The value of the variable being declared.
     value: Document<'a>,
+    /// If the variable must be evaluated when the module is imported, rather
+    /// than lazily when it is first used.
+    strict: bool,
 }
 
 impl<'module> Generator<'module> {
@@ -52,6 +95,7 @@ impl<'module> Generator<'module> {
         line_numbers: &'module LineNumbers,
         module: &'module TypedModule,
         target_support: TargetSupport,
+        list_representation: ListRepresentation,
     ) -> Self {
         let current_module_name_segments_count = module.name.split('/').count();
 
@@ -59,6 +103,7 @@ impl<'module> Generator<'module> {
             module,
             line_numbers,
             target_support,
+            list_representation,
             module_scope: im::HashMap::new(),
             tracker: UsageTracker::default(),
             current_module_name_segments_count,
@@ -110,6 +155,24 @@ impl<'module> Generator<'module> {
             "{ }".to_doc()
         };
 
+        // Strict constants are forced before the module's attribute set is
+        // returned, so any error in them is raised as soon as the module is
+        // imported.
+        let exports = statements
+            .iter()
+            .filter(|declaration| declaration.strict)
+            .map(|declaration| declaration.name.clone())
+            .rev()
+            .enumerate()
+            .fold(exports, |exports, (index, name)| {
+                let exports = if index == 0 {
+                    exports
+                } else {
+                    docvec!["(", exports, ")"]
+                };
+                syntax::fn_call("builtins.seq".to_doc(), [name, exports])
+            });
+
         // Assignment of top-level module names, exported or not.
         let assignments: Vec<_> = statements
             .into_iter()
@@ -156,8 +219,16 @@ impl<'module> Generator<'module> {
                 publicity,
                 name,
                 value,
+                strict,
+                embedded_file,
                 ..
-            }) => Some(self.module_constant(*publicity, name.as_ref(), value)),
+            }) => Some(self.module_constant(
+                *publicity,
+                name.as_ref(),
+                value,
+                *strict,
+                embedded_file.is_some(),
+            )),
 
             Definition::Function(function) => {
                 // If there's an external Nix implementation then it will be imported,
@@ -183,11 +254,24 @@ impl<'module> Generator<'module> {
             .iter()
             .flat_map(|statement| match statement {
                 Definition::CustomType(CustomType {
+                    name,
                     publicity,
                     constructors,
                     opaque,
+                    derive_nix_attrs,
+                    derive_nixos_options,
                     ..
-                }) => self.custom_type_definition(constructors, *publicity, *opaque),
+                }) => {
+                    let mut definitions =
+                        self.custom_type_definition(constructors, *publicity, *opaque);
+                    if derive_nix_attrs.is_some() {
+                        definitions.extend(self.nix_attrs_definitions(name, constructors));
+                    }
+                    if derive_nixos_options.is_some() {
+                        definitions.extend(self.nixos_options_definitions(name, constructors));
+                    }
+                    definitions
+                }
 
                 Definition::Function(Function { .. })
                 | Definition::TypeAlias(TypeAlias { .. })
@@ -202,11 +286,19 @@ impl<'module> Generator<'module> {
         publicity: Publicity,
         name: &'a str,
         value: &'a TypedConstant,
+        strict: bool,
+        embedded: bool,
     ) -> Result<ModuleDeclaration<'a>, Error> {
+        let value = if embedded {
+            expression::embedded_file_constant(&mut self.tracker, value)?
+        } else {
+            expression::constant_expression(&mut self.tracker, value)?
+        };
         Ok(ModuleDeclaration {
             exported: !publicity.is_private(),
             name: maybe_escape_identifier_doc(name),
-            value: expression::constant_expression(&mut self.tracker, value)?,
+            value,
+            strict,
         })
     }
 
@@ -223,6 +315,7 @@ impl<'module> Generator<'module> {
             self.line_numbers,
             Some(name.clone()),
             self.module_scope.clone(),
+            self.list_representation,
             &mut self.tracker,
         );
 
@@ -248,6 +341,7 @@ impl<'module> Generator<'module> {
             exported: !function.publicity.is_private(),
             name,
             value: def_body,
+            strict: false,
         }))
     }
 
@@ -268,6 +362,261 @@ impl<'module> Generator<'module> {
             .collect()
     }
 
+    /// Returns the definitions of the functions converting the records of a
+    /// type with the `@derive(nix_attrs)` attribute from and to attribute
+    /// sets, which are handed the labels of each constructor's fields, along
+    /// with the type their values are checked to have, as named by
+    /// `builtins.typeOf`, or `null` when they aren't checked:
+    ///
+    /// ```nix
+    /// from_attrs_wibble = recordFromAttrs "Wibble" { Wibble = { name = "string"; }; };
+    /// to_attrs_wibble = recordToAttrs { Wibble = { name = "string"; }; };
+    /// ```
+    fn nix_attrs_definitions<'a>(
+        &mut self,
+        type_name: &'a str,
+        constructors: &'a [TypedRecordConstructor],
+    ) -> Vec<Result<ModuleDeclaration<'a>, Error>> {
+        self.tracker.record_from_attrs_used = true;
+        self.tracker.record_to_attrs_used = true;
+
+        let constructors = syntax::wrap_attr_set(constructors.iter().map(|constructor| {
+            let fields = syntax::wrap_attr_set(constructor.arguments.iter().filter_map(|arg| {
+                let (_, label) = arg.label.as_ref()?;
+                let type_ = match nix_type_name(&arg.type_) {
+                    Some(name) => syntax::string_without_escapes_or_backslashes(name),
+                    None => "null".to_doc(),
+                };
+                Some((
+                    syntax::maybe_quoted_attr_set_label_from_identifier(label),
+                    Some(type_),
+                ))
+            }));
+            (
+                syntax::maybe_quoted_attr_set_label_from_identifier(&constructor.name),
+                Some(fields),
+            )
+        }));
+        let [from_attrs, to_attrs] = derive::nix_attrs_function_names(type_name);
+
+        vec![
+            Ok(ModuleDeclaration {
+                exported: true,
+                name: from_attrs.to_doc(),
+                value: syntax::fn_call(
+                    "recordFromAttrs".to_doc(),
+                    [
+                        syntax::string_without_escapes_or_backslashes(type_name),
+                        constructors.clone(),
+                    ],
+                ),
+                strict: false,
+            }),
+            Ok(ModuleDeclaration {
+                exported: true,
+                name: to_attrs.to_doc(),
+                value: syntax::fn_call("recordToAttrs".to_doc(), [constructors]),
+                strict: false,
+            }),
+        ]
+    }
+
+    /// Returns the NixOS option declaration of a type with the
+    /// `@derive(nixos_options)` attribute, which takes Nixpkgs' `lib`, and the
+    /// function building values of the type from the options' values in the
+    /// NixOS configuration. The fields of a record are declared as options:
+    ///
+    /// ```nix
+    /// nixos_options_service = lib: { port = lib.mkOption { type = lib.types.int; }; };
+    /// from_nixos_options_service = config: Service config.port;
+    /// ```
+    ///
+    /// While the constructors of a type without fields become an enumeration:
+    ///
+    /// ```nix
+    /// nixos_options_level = lib: lib.types.enum [ "Low" "High" ];
+    /// from_nixos_options_level = value: { __gleamTag = value; };
+    /// ```
+    fn nixos_options_definitions<'a>(
+        &mut self,
+        type_name: &'a str,
+        constructors: &'a [TypedRecordConstructor],
+    ) -> Vec<Result<ModuleDeclaration<'a>, Error>> {
+        let [options_name, from_options_name] = derive::nixos_options_function_names(type_name);
+
+        let (options, from_options) = match constructors {
+            [constructor] if !constructor.arguments.is_empty() => {
+                let derived_types: HashSet<_> =
+                    derive::nixos_options_types(&self.module.definitions)
+                        .map(|custom_type| custom_type.name.clone())
+                        .collect();
+                let option_types = constructor
+                    .arguments
+                    .iter()
+                    .map(|argument| {
+                        NixosOptionType::from_type(
+                            &argument.type_,
+                            &self.module.name,
+                            &derived_types,
+                        )
+                        .expect("Option types are checked during analysis")
+                    })
+                    .collect_vec();
+
+                let options = constructor.arguments.iter().zip(&option_types).map(
+                    |(argument, option_type)| {
+                        let (_, label) = argument
+                            .label
+                            .as_ref()
+                            .expect("Labels are checked during analysis");
+                        (
+                            syntax::maybe_quoted_attr_set_label_from_identifier(label),
+                            Some(self.nixos_option(option_type, argument.doc.as_ref())),
+                        )
+                    },
+                );
+                let options = syntax::wrap_attr_set(options.collect_vec());
+
+                let values = constructor.arguments.iter().zip(&option_types).map(
+                    |(argument, option_type)| {
+                        let (_, label) = argument
+                            .label
+                            .as_ref()
+                            .expect("Labels are checked during analysis");
+                        let value = docvec![
+                            "config.",
+                            syntax::maybe_quoted_attr_set_label_from_identifier(label)
+                        ];
+                        match self.nixos_option_converter(option_type) {
+                            Some(converter) => {
+                                docvec!["(", syntax::fn_call(converter, [value]), ")"]
+                            }
+                            None => value,
+                        }
+                    },
+                );
+                let values = values.collect_vec();
+                let record =
+                    syntax::fn_call(maybe_escape_identifier_doc(&constructor.name), values);
+
+                (
+                    nixos_options_function("lib", options),
+                    nixos_options_function("config", record),
+                )
+            }
+
+            _ => {
+                let names = constructors.iter().map(|constructor| {
+                    syntax::string_without_escapes_or_backslashes(&constructor.name)
+                });
+                let record = syntax::attr_set(syntax::assignment_line(
+                    "__gleamTag".to_doc(),
+                    "value".to_doc(),
+                ));
+                (
+                    nixos_options_function(
+                        "lib",
+                        syntax::fn_call("lib.types.enum".to_doc(), [syntax::list(names)]),
+                    ),
+                    nixos_options_function("value", record),
+                )
+            }
+        };
+
+        vec![
+            Ok(ModuleDeclaration {
+                exported: true,
+                name: options_name.to_doc(),
+                value: options,
+                strict: false,
+            }),
+            Ok(ModuleDeclaration {
+                exported: true,
+                name: from_options_name.to_doc(),
+                value: from_options,
+                strict: false,
+            }),
+        ]
+    }
+
+    /// Declares an option of the given type with `lib.mkOption`. Optional
+    /// options default to `null`, and the documentation of the field becomes
+    /// the option's description.
+    fn nixos_option<'a>(
+        &self,
+        option_type: &NixosOptionType,
+        doc: Option<&'a (u32, EcoString)>,
+    ) -> Document<'a> {
+        let default = match option_type {
+            NixosOptionType::Option(_) => Some(("default".to_doc(), Some("null".to_doc()))),
+            _ => None,
+        };
+        let description = doc.map(|(_, doc)| {
+            (
+                "description".to_doc(),
+                Some(syntax::string_escaping_backslashes_and_quotes(doc.trim())),
+            )
+        });
+        let attributes = std::iter::once((
+            "type".to_doc(),
+            Some(nixos_option_type(option_type, &self.module.definitions).0),
+        ))
+        .chain(default)
+        .chain(description);
+        syntax::fn_call(
+            "lib.mkOption".to_doc(),
+            [syntax::wrap_attr_set(attributes.collect_vec())],
+        )
+    }
+
+    /// The function converting values of the given option type in the NixOS
+    /// configuration to Gleam values, or `None` if they are already the same.
+    /// It is parenthesised if it isn't a name.
+    fn nixos_option_converter<'a>(
+        &mut self,
+        option_type: &NixosOptionType,
+    ) -> Option<Document<'a>> {
+        match option_type {
+            NixosOptionType::Int
+            | NixosOptionType::Float
+            | NixosOptionType::String
+            | NixosOptionType::Bool
+            | NixosOptionType::Dynamic => None,
+
+            NixosOptionType::List(element) => {
+                self.tracker.list_used = true;
+                Some(match self.nixos_option_converter(element) {
+                    None => "toList".to_doc(),
+                    Some(converter) => docvec![
+                        "(value: toList (",
+                        syntax::fn_call("builtins.map".to_doc(), [converter, "value".to_doc()]),
+                        "))"
+                    ],
+                })
+            }
+
+            NixosOptionType::Option(inner) => {
+                let some = match self.nixos_option_converter(inner) {
+                    None => "value".to_doc(),
+                    Some(converter) => {
+                        docvec!["(", syntax::fn_call(converter, ["value".to_doc()]), ")"]
+                    }
+                };
+                Some(docvec![
+                    "(value: if value == null then { __gleamTag = \"None\"; } \
+else { __gleamTag = \"Some\"; _0 = ",
+                    some,
+                    "; })"
+                ])
+            }
+
+            NixosOptionType::Derived(name) => {
+                let [_, from_options_name] = derive::nixos_options_function_names(name);
+                Some(from_options_name.to_doc())
+            }
+        }
+    }
+
     /// Returns a record definition, of the form:
     ///
     /// ```nix
@@ -300,6 +649,7 @@ impl<'module> Generator<'module> {
                 exported: should_export,
                 name,
                 value: result,
+                strict: false,
             };
         }
 
@@ -367,6 +717,7 @@ impl<'module> Generator<'module> {
             exported: should_export,
             name,
             value: constructor_fun,
+            strict: false,
         }
     }
 
@@ -405,6 +756,7 @@ impl<'module> Generator<'module> {
 impl<'module> Generator<'module> {
     fn collect_imports(&mut self) -> Imports<'module> {
         let mut imports = Imports::new();
+        let inlined = self.inlined_unqualified_values();
 
         for statement in &self.module.definitions {
             match statement {
@@ -415,7 +767,14 @@ impl<'module> Generator<'module> {
                     package,
                     ..
                 }) => {
-                    self.register_import(&mut imports, package, module, as_name, unqualified);
+                    self.register_import(
+                        &mut imports,
+                        package,
+                        module,
+                        as_name,
+                        unqualified,
+                        &inlined,
+                    );
                 }
 
                 Definition::Function(Function {
@@ -470,6 +829,7 @@ impl<'module> Generator<'module> {
         module: &'a str,
         as_name: &'a Option<(AssignName, SrcSpan)>,
         unqualified: &'a [UnqualifiedImport],
+        inlined: &HashSet<EcoString>,
     ) {
         let get_name = |module: &'a str| {
             module
@@ -486,27 +846,51 @@ impl<'module> Generator<'module> {
 
         let module_name = module_var_name(module_name);
         let path = self.import_path(package, module);
-        let unqualified_imports = unqualified.iter().map(|i| {
-            let alias = i.as_name.as_ref().map(|n| {
-                self.register_in_scope(n);
-                maybe_escape_identifier_doc(n)
+        let unqualified_imports = unqualified
+            .iter()
+            .filter(|i| !inlined.contains(i.used_name()))
+            .map(|i| {
+                let alias = i.as_name.as_ref().map(|n| {
+                    self.register_in_scope(n);
+                    maybe_escape_identifier_doc(n)
+                });
+
+                // Here we escape as an identifier, not as an arbitrary string,
+                // since we're using Gleam's built-in import mechanism, which can
+                // only import identifiers (and variables can be bound to the
+                // imported names). This is different from functions imported through
+                // @external, which can have arbitrary names, but that's OK since
+                // we always rename the imported function when it wouldn't match
+                // the chosen identifier for the function receiving @external.
+                let name = maybe_escape_identifier_doc(&i.name);
+                Member { name, alias }
             });
 
-            // Here we escape as an identifier, not as an arbitrary string,
-            // since we're using Gleam's built-in import mechanism, which can
-            // only import identifiers (and variables can be bound to the
-            // imported names). This is different from functions imported through
-            // @external, which can have arbitrary names, but that's OK since
-            // we always rename the imported function when it wouldn't match
-            // the chosen identifier for the function receiving @external.
-            let name = maybe_escape_identifier_doc(&i.name);
-            Member { name, alias }
-        });
-
         let aliases = if discarded { vec![] } else { vec![module_name] };
         imports.register_module(path, aliases, unqualified_imports);
     }
 
+    /// The values imported unqualified which are only used as the constructors
+    /// of records in constants. Those records are generated as attribute set
+    /// literals, so the constructors don't need to be imported.
+    fn inlined_unqualified_values(&self) -> HashSet<EcoString> {
+        let mut names = UnqualifiedNames::default();
+        for definition in &self.module.definitions {
+            match definition {
+                Definition::Function(function) => names.visit_typed_function(function),
+                Definition::ModuleConstant(ModuleConstant { value, .. }) => {
+                    names.constant(value, false)
+                }
+                Definition::Import(_) | Definition::TypeAlias(_) | Definition::CustomType(_) => (),
+            }
+        }
+        names
+            .inlined
+            .difference(&names.referenced)
+            .cloned()
+            .collect()
+    }
+
     fn register_external_function<'a>(
         &mut self,
         imports: &mut Imports<'a>,
@@ -590,6 +974,10 @@ impl<'module> Generator<'module> {
             register_prelude_member("listHasLength", None);
         }
 
+        if self.tracker.list_drop_used {
+            register_prelude_member("listDrop", None);
+        }
+
         if self.tracker.make_error_used {
             register_prelude_member("makeError", None);
         }
@@ -653,6 +1041,130 @@ impl<'module> Generator<'module> {
         if self.tracker.seq_all_used {
             register_prelude_member("seqAll", None);
         }
+
+        if self.tracker.record_from_attrs_used {
+            register_prelude_member("recordFromAttrs", None);
+        }
+
+        if self.tracker.record_to_attrs_used {
+            register_prelude_member("recordToAttrs", None);
+        }
+    }
+}
+
+/// The names the module uses for values, such as the ones it imports
+/// unqualified, sorted by whether the generated code refers to them.
+#[derive(Debug, Default)]
+struct UnqualifiedNames {
+    /// The constructors of records in constants, which are generated as
+    /// attribute set literals.
+    inlined: HashSet<EcoString>,
+    /// The names the generated code refers to.
+    referenced: HashSet<EcoString>,
+}
+
+impl UnqualifiedNames {
+    /// Records in guards are generated as calls to their constructor, rather
+    /// than as literals.
+    fn constant(&mut self, constant: &TypedConstant, in_guard: bool) {
+        match constant {
+            Constant::Record {
+                module, name, args, ..
+            } => {
+                if module.is_none() {
+                    let names = if args.is_empty() || in_guard {
+                        &mut self.referenced
+                    } else {
+                        &mut self.inlined
+                    };
+                    let _ = names.insert(name.clone());
+                }
+                for arg in args {
+                    self.constant(&arg.value, in_guard);
+                }
+            }
+            Constant::Var {
+                module: None, name, ..
+            } => {
+                let _ = self.referenced.insert(name.clone());
+            }
+            Constant::Tuple { elements, .. } | Constant::List { elements, .. } => {
+                for element in elements {
+                    self.constant(element, in_guard);
+                }
+            }
+            Constant::BitArray { segments, .. } => {
+                for segment in segments {
+                    self.constant(&segment.value, in_guard);
+                    for option in &segment.options {
+                        if let Some(value) = option.value() {
+                            self.constant(value, in_guard);
+                        }
+                    }
+                }
+            }
+            Constant::StringConcatenation { left, right, .. } => {
+                self.constant(left, in_guard);
+                self.constant(right, in_guard);
+            }
+            Constant::Int { .. }
+            | Constant::Float { .. }
+            | Constant::String { .. }
+            | Constant::Var { .. }
+            | Constant::Invalid { .. } => (),
+        }
+    }
+
+    fn guard(&mut self, guard: &TypedClauseGuard) {
+        match guard {
+            ClauseGuard::Equals { left, right, .. }
+            | ClauseGuard::NotEquals { left, right, .. }
+            | ClauseGuard::GtInt { left, right, .. }
+            | ClauseGuard::GtEqInt { left, right, .. }
+            | ClauseGuard::LtInt { left, right, .. }
+            | ClauseGuard::LtEqInt { left, right, .. }
+            | ClauseGuard::GtFloat { left, right, .. }
+            | ClauseGuard::GtEqFloat { left, right, .. }
+            | ClauseGuard::LtFloat { left, right, .. }
+            | ClauseGuard::LtEqFloat { left, right, .. }
+            | ClauseGuard::AddInt { left, right, .. }
+            | ClauseGuard::AddFloat { left, right, .. }
+            | ClauseGuard::SubInt { left, right, .. }
+            | ClauseGuard::SubFloat { left, right, .. }
+            | ClauseGuard::MultInt { left, right, .. }
+            | ClauseGuard::MultFloat { left, right, .. }
+            | ClauseGuard::DivInt { left, right, .. }
+            | ClauseGuard::DivFloat { left, right, .. }
+            | ClauseGuard::RemainderInt { left, right, .. }
+            | ClauseGuard::Or { left, right, .. }
+            | ClauseGuard::And { left, right, .. } => {
+                self.guard(left);
+                self.guard(right);
+            }
+            ClauseGuard::Not { expression, .. } => self.guard(expression),
+            ClauseGuard::TupleIndex { tuple, .. } => self.guard(tuple),
+            ClauseGuard::FieldAccess { container, .. } => self.guard(container),
+            ClauseGuard::Constant(constant) => self.constant(constant, true),
+            ClauseGuard::Var { .. } | ClauseGuard::ModuleSelect { .. } => (),
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UnqualifiedNames {
+    fn visit_typed_expr_var(
+        &mut self,
+        _location: &'ast SrcSpan,
+        _constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        let _ = self.referenced.insert(name.clone());
+    }
+
+    fn visit_typed_clause(&mut self, clause: &'ast TypedClause) {
+        if let Some(guard) = &clause.guard {
+            self.guard(guard);
+        }
+        visit::visit_typed_clause(self, clause);
     }
 }
 
@@ -662,8 +1174,21 @@ pub fn module(
     path: &Utf8Path,
     src: &EcoString,
     target_support: TargetSupport,
+    optimize: u8,
+    list_representation: ListRepresentation,
 ) -> Result<String, crate::Error> {
-    let document = Generator::new(line_numbers, module, target_support)
+    let inlined = inline::inline(module);
+    let module = inlined.as_ref().unwrap_or(module);
+
+    let optimised;
+    let module = if optimize >= escape::OPTIMIZE_LEVEL {
+        optimised = escape::optimise(module);
+        &optimised
+    } else {
+        module
+    };
+
+    let document = Generator::new(line_numbers, module, target_support, list_representation)
         .compile()
         .map_err(|error| crate::Error::Nix {
             path: path.to_path_buf(),
@@ -673,6 +1198,208 @@ pub fn module(
     Ok(document.to_pretty_string(80))
 }
 
+/// A function taking the given argument, defined by a type with the
+/// `@derive(nixos_options)` attribute.
+fn nixos_options_function<'a>(argument: &'a str, body: Document<'a>) -> Document<'a> {
+    docvec![
+        syntax::wrap_args([argument.to_doc()]),
+        break_("", " "),
+        body
+    ]
+    .nest(INDENT)
+    .group()
+}
+
+/// The NixOS option type of values of the given type, and whether it needs
+/// parentheses to be passed as an argument. The definitions of the module are
+/// needed to tell the types with fields, declared as submodules, from the
+/// enumerations.
+fn nixos_option_type<'a>(
+    option_type: &NixosOptionType,
+    definitions: &[TypedDefinition],
+) -> (Document<'a>, bool) {
+    let argument = |option_type: &NixosOptionType| {
+        let (type_, parenthesise) = nixos_option_type(option_type, definitions);
+        if parenthesise {
+            docvec!["(", type_, ")"]
+        } else {
+            type_
+        }
+    };
+    match option_type {
+        NixosOptionType::Int => ("lib.types.int".to_doc(), false),
+        NixosOptionType::Float => ("lib.types.float".to_doc(), false),
+        NixosOptionType::String => ("lib.types.str".to_doc(), false),
+        NixosOptionType::Bool => ("lib.types.bool".to_doc(), false),
+        NixosOptionType::Dynamic => ("lib.types.anything".to_doc(), false),
+        NixosOptionType::List(element) => (
+            syntax::fn_call("lib.types.listOf".to_doc(), [argument(element)]),
+            true,
+        ),
+        NixosOptionType::Option(inner) => (
+            syntax::fn_call("lib.types.nullOr".to_doc(), [argument(inner)]),
+            true,
+        ),
+        NixosOptionType::Derived(name) => {
+            let [options_name, _] = derive::nixos_options_function_names(name);
+            let options = syntax::fn_call(options_name.to_doc(), ["lib".to_doc()]);
+            let has_fields = derive::nixos_options_types(definitions)
+                .filter(|custom_type| &custom_type.name == name)
+                .flat_map(|custom_type| &custom_type.constructors)
+                .any(|constructor| !constructor.arguments.is_empty());
+            if has_fields {
+                let submodule =
+                    syntax::attr_set(syntax::assignment_line("options".to_doc(), options));
+                (
+                    syntax::fn_call("lib.types.submodule".to_doc(), [submodule]),
+                    true,
+                )
+            } else {
+                (options, true)
+            }
+        }
+    }
+}
+
+/// The name `builtins.typeOf` gives to the Nix values of a type with
+/// `@derive(nix_attrs)` checks the fields of, which are those of the prelude
+/// types with a single representation.
+fn nix_type_name(type_: &Type) -> Option<&'static str> {
+    if type_.is_int() {
+        Some("int")
+    } else if type_.is_float() {
+        Some("float")
+    } else if type_.is_string() {
+        Some("string")
+    } else if type_.is_bool() {
+        Some("bool")
+    } else {
+        None
+    }
+}
+
+/// Generates the `gleam.nix` file of a package, through which its modules
+/// import the prelude at the given location. Importing it fails with an
+/// explanation when the prelude uses another representation of values than
+/// the package, instead of evaluation failing somewhere in generated code.
+///
+/// With native lists, the prelude's functions handling lists are replaced
+/// with ones expecting Nix lists, and the representation is recorded on the
+/// second line of the file.
+pub fn prelude_reexport(prelude_location: &str, list_representation: ListRepresentation) -> String {
+    let version = REPRESENTATION_VERSION;
+    let prelude = syntax::path(prelude_location);
+    let (list_header, selected) = match list_representation {
+        ListRepresentation::Cons => (String::new(), "prelude".to_string()),
+        ListRepresentation::Native => (
+            format!("{LIST_REPRESENTATION_HEADER}native\n"),
+            r#"prelude.withListRepresentation or (_: builtins.throw "This package was generated by Glistix to use native Nix lists, which the prelude doesn't support. It must be updated to a newer Glistix version.") "native""#.to_string(),
+        ),
+    };
+    format!(
+        r#"{REPRESENTATION_VERSION_HEADER}{version}
+{list_header}let
+  prelude = builtins.import {prelude};
+  preludeVersion = prelude.reprVersion or 0;
+in
+if preludeVersion == {version}
+then {selected}
+else builtins.throw "This package was generated by Glistix for version {version} of the Nix representation of Gleam values, but the prelude uses version ${{builtins.toString preludeVersion}}. All packages must be compiled by compatible Glistix versions."
+"#
+    )
+}
+
+/// Reads the representation version a package was generated for from its
+/// `gleam.nix` file, if it records one.
+pub fn prelude_reexport_representation_version(reexport: &str) -> Option<u32> {
+    reexport
+        .lines()
+        .next()?
+        .strip_prefix(REPRESENTATION_VERSION_HEADER)?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Reads the representation of lists a package was generated for from its
+/// `gleam.nix` file. Packages which don't record one use cons cells.
+pub fn prelude_reexport_list_representation(reexport: &str) -> ListRepresentation {
+    let native = reexport
+        .lines()
+        .nth(1)
+        .and_then(|line| line.strip_prefix(LIST_REPRESENTATION_HEADER))
+        .is_some_and(|representation| representation.trim() == "native");
+    if native {
+        ListRepresentation::Native
+    } else {
+        ListRepresentation::Cons
+    }
+}
+
+/// The file generated in the output directory of each package, importing all
+/// of its modules.
+pub const PACKAGE_INDEX_FILE: &str = "default.nix";
+
+/// A module path segment in the package index, which may be a module, the
+/// parent of other modules, or both.
+#[derive(Debug, Default)]
+struct IndexEntry<'a> {
+    module: Option<&'a str>,
+    children: BTreeMap<&'a str, IndexEntry<'a>>,
+}
+
+/// Generates the index of a package, evaluating to an attribute set with the
+/// (lazily) imported modules of the package nested according to their names,
+/// such that `gleam/list` is at `gleam.list`.
+/// When a module is also the parent of other modules, its submodules are
+/// merged into its exports.
+pub fn package_index(module_names: &[EcoString]) -> String {
+    let mut root = IndexEntry::default();
+    for name in module_names {
+        let entry = name.split('/').fold(&mut root, |entry, segment| {
+            entry.children.entry(segment).or_default()
+        });
+        entry.module = Some(name);
+    }
+
+    let mut output = String::from("{\n");
+    write_index_entries(&mut output, &root.children, 1);
+    output.push_str("}\n");
+    output
+}
+
+fn write_index_entries(
+    output: &mut String,
+    entries: &BTreeMap<&str, IndexEntry<'_>>,
+    depth: usize,
+) {
+    let indent = " ".repeat(depth * INDENT as usize);
+    for (segment, entry) in entries {
+        let label = if syntax::is_nix_keyword(segment) {
+            eco_format!("\"{segment}\"")
+        } else {
+            EcoString::from(*segment)
+        };
+        let import = entry
+            .module
+            .map(|module| format!("builtins.import ./{module}.nix"));
+
+        match import {
+            Some(import) if entry.children.is_empty() => {
+                output.push_str(&format!("{indent}{label} = {import};\n"));
+            }
+            _ => {
+                let merged_import = import
+                    .map(|import| format!("{import} // "))
+                    .unwrap_or_default();
+                output.push_str(&format!("{indent}{label} = {merged_import}{{\n"));
+                write_index_entries(output, &entry.children, depth + 1);
+                output.push_str(&format!("{indent}}};\n"));
+            }
+        }
+    }
+}
+
 /// Generates the variable name in Nix for the given module.
 pub fn module_var_name(name: &str) -> EcoString {
     eco_format!("{}'", maybe_escape_identifier_string(name))
@@ -733,6 +1460,7 @@ pub(crate) struct UsageTracker {
     pub prepend_used: bool,
     pub list_has_at_least_length_used: bool,
     pub list_has_length_used: bool,
+    pub list_drop_used: bool,
     pub error_used: bool,
     pub str_has_prefix_used: bool,
     pub parse_escape_used: bool,
@@ -754,11 +1482,14 @@ pub(crate) struct UsageTracker {
     pub bit_array_binary_from_slice_used: bool,
     pub bit_array_slice_after_used: bool,
     pub seq_all_used: bool,
+    pub record_from_attrs_used: bool,
+    pub record_to_attrs_used: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Error {
     Unsupported { feature: String, location: SrcSpan },
+    Syntax { problem: String, location: SrcSpan },
 }
 
 impl Error {
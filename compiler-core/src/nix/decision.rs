@@ -0,0 +1,641 @@
+//! Compiles case expressions using the decision tree built when checking
+//! their exhaustiveness.
+//!
+//! Case expressions are otherwise compiled clause by clause, testing each of
+//! their patterns from scratch. For a case expression such as
+//!
+//! ```gleam
+//! case x {
+//!   Ok(1) -> 1
+//!   Ok(2) -> 2
+//!   Ok(_) -> 3
+//!   Error(_) -> 4
+//! }
+//! ```
+//!
+//! that means checking whether `x` is `Ok` three times. Following the decision
+//! tree instead, each part of the subjects is tested at most once:
+//!
+//! ```nix
+//! if x.__gleamTag == "Ok" then
+//!   if x._0 == 1 then 1 else if x._0 == 2 then 2 else 3
+//! else 4
+//! ```
+
+use std::collections::HashMap;
+
+use ecow::{eco_format, EcoString};
+use itertools::Itertools;
+
+use crate::analyse::Inferred;
+use crate::ast::{Pattern, TypedClause, TypedPattern};
+use crate::config::ListRepresentation;
+use crate::docvec;
+use crate::exhaustiveness::{Body, Case, CompiledCase, Constructor, Decision, Variable};
+use crate::nix::{expression, pattern, syntax, Error, Output, INDENT};
+use crate::pretty::{break_, nil, Document, Documentable};
+use crate::type_::{FieldMap, PatternConstructor};
+
+/// A custom type's constructor, identified by the module and name of the type
+/// and its index within the type.
+type ConstructorKey = (EcoString, EcoString, u16);
+
+/// Whether a case expression should be compiled following its decision tree.
+///
+/// The decision tree isn't complete for bit array and string prefix patterns,
+/// so it can't be used when there are any. Otherwise, it is only worth it
+/// when testing the clauses one by one would repeat a test already made for
+/// an earlier clause; when it wouldn't, both ways make the same tests, and
+/// testing clause by clause produces shorter code, with each clause body
+/// appearing exactly once.
+pub(crate) fn should_follow_decision_tree(
+    clauses: &[TypedClause],
+    compiled_case: &CompiledCase,
+) -> bool {
+    let multi_patterns = clauses
+        .iter()
+        .flat_map(|clause| std::iter::once(&clause.pattern).chain(&clause.alternative_patterns))
+        .collect_vec();
+
+    if multi_patterns
+        .iter()
+        .flat_map(|patterns| patterns.iter())
+        .any(is_unsupported)
+    {
+        return false;
+    }
+
+    let mut variant_types = vec![];
+    branching_variant_types(&compiled_case.tree, &mut variant_types);
+
+    // The last clause doesn't test anything when compiled clause by clause,
+    // as it's the fallback when the other clauses don't match.
+    let mut seen_tests = vec![];
+    for patterns in multi_patterns.iter().dropping_back(1) {
+        let mut tests = vec![];
+        for (index, pattern) in patterns.iter().enumerate() {
+            pattern_tests(pattern, eco_format!("{index}"), &variant_types, &mut tests);
+        }
+        for test in tests.into_iter().unique() {
+            if seen_tests.contains(&test) {
+                return true;
+            }
+            seen_tests.push(test);
+        }
+    }
+    false
+}
+
+/// Whether the decision tree doesn't describe how to match the pattern.
+fn is_unsupported(pattern: &TypedPattern) -> bool {
+    match pattern {
+        Pattern::BitArray { .. } | Pattern::StringPrefix { .. } | Pattern::VarUsage { .. } => true,
+
+        Pattern::Int { .. }
+        | Pattern::Float { .. }
+        | Pattern::String { .. }
+        | Pattern::Variable { .. }
+        | Pattern::Discard { .. }
+        | Pattern::Invalid { .. } => false,
+
+        Pattern::Assign { pattern, .. } => is_unsupported(pattern),
+        Pattern::Tuple { elems, .. } => elems.iter().any(is_unsupported),
+        Pattern::List { elements, tail, .. } => {
+            elements.iter().any(is_unsupported) || tail.as_deref().is_some_and(is_unsupported)
+        }
+        Pattern::Constructor { arguments, .. } => arguments
+            .iter()
+            .any(|argument| is_unsupported(&argument.value)),
+    }
+}
+
+/// Collects the custom types with more than one constructor to choose from
+/// in the decision tree. Patterns for the other types always match, as the
+/// type of the value already tells which constructor it was built with.
+fn branching_variant_types(decision: &Decision, types: &mut Vec<(EcoString, EcoString)>) {
+    match decision {
+        Decision::Success(_) | Decision::Failure => {}
+        Decision::Guard(_, _, fallback) => branching_variant_types(fallback, types),
+        Decision::Switch(_, cases, fallback) => {
+            let variant_type = match cases.first().map(|case| &case.constructor) {
+                Some(Constructor::Variant { type_, .. }) if cases.len() > 1 => {
+                    type_.named_type_name()
+                }
+                _ => None,
+            };
+            if let Some(name) = variant_type {
+                if !types.contains(&name) {
+                    types.push(name);
+                }
+            }
+            for case in cases {
+                branching_variant_types(&case.body, types);
+            }
+            if let Some(fallback) = fallback {
+                branching_variant_types(fallback, types);
+            }
+        }
+        Decision::List {
+            empty, non_empty, ..
+        } => {
+            branching_variant_types(empty, types);
+            branching_variant_types(&non_empty.decision, types);
+        }
+    }
+}
+
+/// Describes the tests made on the subjects when matching a pattern clause by
+/// clause, each as the path to the part of the subject tested and what it is
+/// tested for.
+fn pattern_tests(
+    pattern: &TypedPattern,
+    path: EcoString,
+    variant_types: &[(EcoString, EcoString)],
+    tests: &mut Vec<EcoString>,
+) {
+    match pattern {
+        Pattern::Int { value, .. } | Pattern::Float { value, .. } => {
+            tests.push(eco_format!("{path} == {value}"))
+        }
+        Pattern::String { value, .. } => tests.push(eco_format!("{path} == \"{value}\"")),
+
+        Pattern::Variable { .. }
+        | Pattern::Discard { .. }
+        | Pattern::Invalid { .. }
+        | Pattern::BitArray { .. }
+        | Pattern::StringPrefix { .. }
+        | Pattern::VarUsage { .. } => {}
+
+        Pattern::Assign { pattern, .. } => pattern_tests(pattern, path, variant_types, tests),
+
+        Pattern::Tuple { elems, .. } => {
+            for (index, element) in elems.iter().enumerate() {
+                pattern_tests(element, eco_format!("{path}.{index}"), variant_types, tests);
+            }
+        }
+
+        Pattern::List { elements, tail, .. } => {
+            let operator = if tail.is_some() { ">=" } else { "==" };
+            tests.push(eco_format!("length {path} {operator} {}", elements.len()));
+            let mut path = path;
+            for element in elements {
+                pattern_tests(element, eco_format!("{path}.head"), variant_types, tests);
+                path = eco_format!("{path}.tail");
+            }
+            if let Some(tail) = tail {
+                pattern_tests(tail, path, variant_types, tests);
+            }
+        }
+
+        Pattern::Constructor {
+            constructor: Inferred::Known(PatternConstructor { name, .. }),
+            arguments,
+            type_,
+            ..
+        } => {
+            if type_
+                .named_type_name()
+                .is_some_and(|type_name| variant_types.contains(&type_name))
+            {
+                tests.push(eco_format!("{path} is {name}"));
+            }
+            for (index, argument) in arguments.iter().enumerate() {
+                let path = eco_format!("{path}.{index}");
+                pattern_tests(&argument.value, path, variant_types, tests);
+            }
+        }
+
+        Pattern::Constructor {
+            constructor: Inferred::Unknown,
+            ..
+        } => panic!("Nix generation performed with uninferred pattern constructor"),
+    }
+}
+
+/// Compiles a case expression following its decision tree.
+#[derive(Debug)]
+pub(crate) struct Generator<'module_ctx, 'expression_gen, 'a> {
+    expression_generator: &'expression_gen mut expression::Generator<'module_ctx>,
+    clauses: &'a [TypedClause],
+    /// The Nix expressions evaluating to the value of each variable of the
+    /// decision tree, by id.
+    variables: HashMap<usize, Document<'a>>,
+    /// The native lists which are the rest of another list, by id, as that
+    /// list and the number of elements before them. Their elements are
+    /// accessed by index in that list, as copying the rest of the list for
+    /// each element tested would take quadratic time.
+    list_suffixes: HashMap<usize, (Document<'a>, usize)>,
+    /// The constructors of custom types used in the patterns. Only these can
+    /// have their fields accessed, as constructors that aren't used in any
+    /// pattern only ever lead to the clauses matching them with `_`.
+    constructors: HashMap<ConstructorKey, &'a PatternConstructor>,
+}
+
+impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, 'a> {
+    pub fn new(
+        expression_generator: &'expression_gen mut expression::Generator<'module_ctx>,
+        clauses: &'a [TypedClause],
+        compiled_case: &CompiledCase,
+        subjects: &[Document<'a>],
+    ) -> Self {
+        let variables = compiled_case
+            .subject_variables
+            .iter()
+            .zip_eq(subjects)
+            .map(|(variable, subject)| (variable.id, subject.clone()))
+            .collect();
+
+        let mut constructors = HashMap::new();
+        for clause in clauses {
+            let multi_patterns =
+                std::iter::once(&clause.pattern).chain(&clause.alternative_patterns);
+            for pattern in multi_patterns.flatten() {
+                register_constructors(pattern, &mut constructors);
+            }
+        }
+
+        Self {
+            expression_generator,
+            clauses,
+            variables,
+            list_suffixes: HashMap::new(),
+            constructors,
+        }
+    }
+
+    pub fn generate(&mut self, decision: &'a Decision) -> Output<'a> {
+        Ok(self.decision(decision)?.into_doc())
+    }
+
+    fn decision(&mut self, decision: &'a Decision) -> Result<IfElse<'a>, Error> {
+        match decision {
+            Decision::Success(body) => Ok(IfElse::value(self.body(body)?)),
+
+            Decision::Failure => {
+                panic!("inexhaustive case expressions should not reach code generation")
+            }
+
+            Decision::Guard(clause_index, body, fallback) => {
+                let guard = self
+                    .clause(*clause_index)
+                    .guard
+                    .as_ref()
+                    .expect("Clause with a guard in the decision tree");
+                let bindings = body
+                    .bindings
+                    .iter()
+                    .map(|(name, variable)| (name.as_str(), self.variable(variable)))
+                    .collect_vec();
+                let condition = pattern::Generator::new(self.expression_generator)
+                    .bound_guard(bindings, guard)?;
+                let body = self.body(body)?;
+                let fallback = self.decision(fallback)?;
+                Ok(IfElse::new(vec![(condition, body)], fallback))
+            }
+
+            Decision::Switch(variable, cases, fallback) => {
+                self.switch(variable, cases, fallback.as_deref())
+            }
+
+            Decision::List {
+                variable,
+                empty,
+                non_empty,
+            } => {
+                let (first, rest, condition) = match self.expression_generator.list_representation {
+                    ListRepresentation::Cons => {
+                        let subject = self.variable(variable);
+                        (
+                            docvec![subject.clone(), ".head"],
+                            docvec![subject.clone(), ".tail"],
+                            docvec![subject, ".__gleamTag == \"Empty\""],
+                        )
+                    }
+                    ListRepresentation::Native => {
+                        let (list, offset) = match self.list_suffixes.get(&variable.id) {
+                            Some(suffix) => suffix.clone(),
+                            None => (self.variable(variable), 0),
+                        };
+                        let condition = if offset == 0 {
+                            docvec![list.clone(), " == [ ]"]
+                        } else {
+                            docvec!["builtins.length ", list.clone(), " == ", offset]
+                        };
+                        let first = docvec!["(builtins.elemAt ", list.clone(), " ", offset, ")"];
+                        let rest = docvec!["(listDrop ", offset + 1, " ", list.clone(), ")"];
+                        let _ = self
+                            .list_suffixes
+                            .insert(non_empty.rest.id, (list, offset + 1));
+                        (first, rest, condition)
+                    }
+                };
+                let _ = self.variables.insert(non_empty.first.id, first);
+                let _ = self.variables.insert(non_empty.rest.id, rest);
+
+                let empty = self.generate(empty)?;
+                let non_empty = self.decision(&non_empty.decision)?;
+                Ok(IfElse::new(vec![(condition, empty)], non_empty))
+            }
+        }
+    }
+
+    fn switch(
+        &mut self,
+        variable: &Variable,
+        cases: &'a [Case],
+        fallback: Option<&'a Decision>,
+    ) -> Result<IfElse<'a>, Error> {
+        let subject = self.variable(variable);
+
+        let Some(first) = cases.first() else {
+            return self.decision(fallback.expect("Switch without cases has a fallback"));
+        };
+
+        match &first.constructor {
+            // Tuples always match, so we only need to access their elements.
+            Constructor::Tuple(_) => {
+                for (index, argument) in first.arguments.iter().enumerate() {
+                    let element = docvec!["(builtins.elemAt ", subject.clone(), " ", index, ")"];
+                    let _ = self.variables.insert(argument.id, element);
+                }
+                self.decision(&first.body)
+            }
+
+            Constructor::Int(_) | Constructor::Float(_) | Constructor::String(_) => {
+                let mut branches = Vec::with_capacity(cases.len());
+                for case in cases {
+                    let value = match &case.constructor {
+                        Constructor::Int(value) => {
+                            expression::int(value, self.expression_generator.tracker)
+                        }
+                        Constructor::Float(value) => expression::float(value),
+                        Constructor::String(value) => {
+                            expression::string(value, self.expression_generator.tracker)
+                        }
+                        Constructor::Tuple(_)
+                        | Constructor::Variant { .. }
+                        | Constructor::BitArray
+                        | Constructor::StringPrefix => {
+                            unreachable!("Mixed constructors in a switch")
+                        }
+                    };
+                    let condition = docvec![subject.clone(), " == ", value];
+                    branches.push((condition, self.generate(&case.body)?));
+                }
+                let fallback = fallback.expect("Switch on literals has a fallback");
+                Ok(IfElse::new(branches, self.decision(fallback)?))
+            }
+
+            Constructor::Variant { type_, .. } if type_.is_bool() => match cases {
+                [only] => self.decision(&only.body),
+                // `True` is the first constructor of `Bool`.
+                [true_case, false_case] => {
+                    let when_true = self.generate(&true_case.body)?;
+                    let when_false = self.decision(&false_case.body)?;
+                    Ok(IfElse::new(vec![(subject, when_true)], when_false))
+                }
+                _ => unreachable!("Bool has two constructors"),
+            },
+
+            Constructor::Variant { .. } => self.variant_switch(subject, cases),
+
+            Constructor::BitArray | Constructor::StringPrefix => {
+                unreachable!("Bit array and string prefix patterns have no decision tree")
+            }
+        }
+    }
+
+    /// Compiles a switch on the constructor of a custom type, testing for the
+    /// constructors used in the patterns, and falling back to the decision
+    /// shared by all the other constructors, if there are any.
+    fn variant_switch(
+        &mut self,
+        subject: Document<'a>,
+        cases: &'a [Case],
+    ) -> Result<IfElse<'a>, Error> {
+        if let [only] = cases {
+            let _ = self.register_fields(&subject, only);
+            return self.decision(&only.body);
+        }
+
+        let mut branches = Vec::with_capacity(cases.len());
+        let mut fallback = None;
+        for case in cases {
+            match self.register_fields(&subject, case) {
+                Some(name) => {
+                    let condition = docvec![
+                        subject.clone(),
+                        ".__gleamTag == ",
+                        syntax::string_without_escapes_or_backslashes(name)
+                    ];
+                    branches.push((condition, &case.body));
+                }
+                // The constructors which aren't used in any pattern all lead
+                // to the same decision, so only the first one needs compiling.
+                None if fallback.is_none() => fallback = Some(&case.body),
+                None => {}
+            }
+        }
+
+        // When every constructor is used in the patterns, the last one doesn't
+        // need testing.
+        let fallback = match fallback {
+            Some(fallback) => fallback,
+            None => branches.pop().expect("Switch with many cases").1,
+        };
+
+        let branches = branches
+            .into_iter()
+            .map(|(condition, decision)| Ok((condition, self.generate(decision)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(IfElse::new(branches, self.decision(fallback)?))
+    }
+
+    /// Registers the variables for the fields of the constructor the case
+    /// tests for, returning the name of the constructor. Nothing is registered
+    /// if the constructor isn't used in any pattern, as then its fields are
+    /// never accessed.
+    fn register_fields(&mut self, subject: &Document<'a>, case: &Case) -> Option<&'a str> {
+        let Constructor::Variant { type_, index } = &case.constructor else {
+            return None;
+        };
+        let (module, name) = type_.named_type_name()?;
+        let constructor = *self.constructors.get(&(module, name, *index))?;
+
+        for (index, argument) in case.arguments.iter().enumerate() {
+            let label = constructor
+                .field_map
+                .as_ref()
+                .and_then(|FieldMap { fields, .. }| {
+                    fields
+                        .iter()
+                        .find(|(_, position)| **position as usize == index)
+                        .map(|(label, _)| label)
+                });
+            let field = match label {
+                Some(label) => docvec![
+                    subject.clone(),
+                    ".",
+                    syntax::maybe_quoted_attr_set_label_from_identifier(label)
+                ],
+                None => docvec![subject.clone(), eco_format!("._{index}")],
+            };
+            let _ = self.variables.insert(argument.id, field);
+        }
+
+        Some(constructor.name.as_str())
+    }
+
+    /// Compiles the body of the clause the decision tree reached, binding the
+    /// variables from its patterns.
+    fn body(&mut self, body: &'a Body) -> Output<'a> {
+        let clause = self.clause(body.clause_index as usize);
+
+        let scope = self.expression_generator.current_scope_vars.clone();
+        let mut assignments = Vec::with_capacity(body.bindings.len());
+        for (name, variable) in &body.bindings {
+            let value = self.variable(variable);
+            let name = self.expression_generator.next_local_var(name, false);
+            assignments.push(syntax::assignment_line(name, value));
+        }
+        let consequence = self.expression_generator.expression(&clause.then)?;
+
+        // The variables bound by the patterns go out of scope once the body
+        // is compiled.
+        self.expression_generator.current_scope_vars = scope;
+
+        Ok(if assignments.is_empty() {
+            consequence
+        } else {
+            syntax::let_in(assignments, consequence, false)
+        })
+    }
+
+    fn clause(&self, index: usize) -> &'a TypedClause {
+        self.clauses
+            .get(index)
+            .expect("Clause in the decision tree exists")
+    }
+
+    fn variable(&mut self, variable: &Variable) -> Document<'a> {
+        if self.list_suffixes.contains_key(&variable.id) {
+            self.expression_generator.tracker.list_drop_used = true;
+        }
+        self.variables
+            .get(&variable.id)
+            .expect("Variable used in the decision tree was assigned")
+            .clone()
+    }
+}
+
+fn register_constructors<'a>(
+    pattern: &'a TypedPattern,
+    constructors: &mut HashMap<ConstructorKey, &'a PatternConstructor>,
+) {
+    match pattern {
+        Pattern::Int { .. }
+        | Pattern::Float { .. }
+        | Pattern::String { .. }
+        | Pattern::Variable { .. }
+        | Pattern::Discard { .. }
+        | Pattern::Invalid { .. }
+        | Pattern::BitArray { .. }
+        | Pattern::StringPrefix { .. }
+        | Pattern::VarUsage { .. } => {}
+
+        Pattern::Assign { pattern, .. } => register_constructors(pattern, constructors),
+
+        Pattern::Tuple { elems, .. } => {
+            for element in elems {
+                register_constructors(element, constructors);
+            }
+        }
+
+        Pattern::List { elements, tail, .. } => {
+            for element in elements {
+                register_constructors(element, constructors);
+            }
+            if let Some(tail) = tail {
+                register_constructors(tail, constructors);
+            }
+        }
+
+        Pattern::Constructor {
+            constructor: Inferred::Known(constructor),
+            arguments,
+            type_,
+            ..
+        } => {
+            if let Some((module, name)) = type_.named_type_name() {
+                let key = (module, name, constructor.constructor_index);
+                let _ = constructors.insert(key, constructor);
+            }
+            for argument in arguments {
+                register_constructors(&argument.value, constructors);
+            }
+        }
+
+        Pattern::Constructor {
+            constructor: Inferred::Unknown,
+            ..
+        } => panic!("Nix generation performed with uninferred pattern constructor"),
+    }
+}
+
+/// An `if a then x else if b then y else z` expression, kept as its branches
+/// so that the expression taken when all the tests fail can be chained to it
+/// with more branches, rather than nesting it in the final `else`.
+struct IfElse<'a> {
+    branches: Vec<(Document<'a>, Document<'a>)>,
+    otherwise: Document<'a>,
+}
+
+impl<'a> IfElse<'a> {
+    /// A value which doesn't need testing anything.
+    fn value(value: Document<'a>) -> Self {
+        Self {
+            branches: vec![],
+            otherwise: value,
+        }
+    }
+
+    fn new(mut branches: Vec<(Document<'a>, Document<'a>)>, otherwise: IfElse<'a>) -> Self {
+        branches.extend(otherwise.branches);
+        Self {
+            branches,
+            otherwise: otherwise.otherwise,
+        }
+    }
+
+    /// Renders the expression, laid out the same way as case expressions
+    /// compiled clause by clause.
+    fn into_doc(self) -> Document<'a> {
+        if self.branches.is_empty() {
+            return self.otherwise;
+        }
+
+        let mut doc = nil();
+        for (index, (condition, body)) in self.branches.into_iter().enumerate() {
+            doc = doc
+                .append(if index == 0 {
+                    "if".to_doc()
+                } else {
+                    docvec!(break_("", " "), "else if")
+                })
+                .append(
+                    docvec!(
+                        docvec!(break_("", " "), condition).nest(INDENT),
+                        break_("", " "),
+                        "then"
+                    )
+                    .group(),
+                )
+                .append(docvec!(break_("", " "), body).nest(INDENT).group());
+        }
+        doc.append(break_("", " ")).append("else").append(
+            docvec!(break_("", " "), self.otherwise)
+                .nest(INDENT)
+                .group(),
+        )
+    }
+}
@@ -0,0 +1,1047 @@
+//! Escape analysis of the tuples and `Result`s returned by functions, run on a
+//! module before generating its Nix code when `optimize = 2` is set in the
+//! `[glistix]` section of `gleam.toml`.
+//!
+//! Returning a tuple or a `Result` in Nix allocates a list or an attribute
+//! set, which is usually taken apart by the caller right away. When a
+//! function of the module only ever returns literal tuples or `Ok` and
+//! `Error` records, and one of its calls in the same module is immediately
+//! destructured, the call is made to a variant of the function which takes
+//! continuations instead, so the wrapper never escapes and is never built.
+//!
+//! ```gleam
+//! let #(quotient, remainder) = divide(a, b)
+//! quotient + remainder
+//! ```
+//!
+//! is generated as `divide'cps a b (quotient: remainder: quotient + remainder)`.
+//!
+//! The destructuring calls which are rewritten are `let` assignments of a
+//! tuple of variables and discards followed by more statements, and `case`
+//! expressions with one `Ok` and one `Error` clause, each binding a variable
+//! or a discard, without guards or alternative patterns.
+//!
+//! Like on the other targets, the called function is then evaluated along
+//! with the rest of the block, even if none of the values it returns end up
+//! being used.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use ecow::{eco_format, EcoString};
+use itertools::Itertools;
+use vec1::Vec1;
+
+use crate::analyse::Inferred;
+use crate::ast::{
+    Arg, ArgNames, AssignmentKind, BitArraySegment, CallArg, Clause, Definition,
+    FunctionLiteralKind, Pattern, Publicity, SrcSpan, Statement, TypedArg, TypedAssignment,
+    TypedClause, TypedExpr, TypedFunction, TypedModule, TypedPattern, TypedStatement,
+};
+use crate::build::Target;
+use crate::type_::{
+    prelude, Deprecation, ModuleValueConstructor, Type, ValueConstructor, ValueConstructorVariant,
+    PRELUDE_MODULE_NAME,
+};
+
+/// The optimisation level from which this pass is run.
+pub const OPTIMIZE_LEVEL: u8 = 2;
+
+/// The value a function returns its results wrapped in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wrapper {
+    /// A tuple with the given number of elements.
+    Tuple(usize),
+    /// A `Result`, built with either `Ok` or `Error`.
+    Result,
+}
+
+impl Wrapper {
+    fn of(type_: &Type) -> Option<Self> {
+        if let Some(elements) = type_.tuple_types() {
+            Some(Self::Tuple(elements.len()))
+        } else if type_.is_result() {
+            Some(Self::Result)
+        } else {
+            None
+        }
+    }
+
+    /// The names and types of the continuations taken by the variant of a
+    /// function returning this wrapper. The values they return are only known
+    /// at each call site, so they are typed as generic.
+    fn continuations(self, type_: &Type) -> Vec<(&'static str, Arc<Type>)> {
+        let returned = prelude::generic_var(0);
+        match self {
+            Wrapper::Tuple(_) => {
+                let elements = type_.tuple_types().unwrap_or_default();
+                vec![("k'tuple", prelude::fn_(elements, returned))]
+            }
+            Wrapper::Result => {
+                let mut types = type_.constructor_types().unwrap_or_default().into_iter();
+                let ok = types.next().unwrap_or_else(|| prelude::generic_var(1));
+                let error = types.next().unwrap_or_else(|| prelude::generic_var(2));
+                vec![
+                    ("k'ok", prelude::fn_(vec![ok], returned.clone())),
+                    ("k'error", prelude::fn_(vec![error], returned)),
+                ]
+            }
+        }
+    }
+}
+
+/// The name of the continuation-passing variant of a module function.
+fn variant_name(name: &str) -> EcoString {
+    eco_format!("{name}'cps")
+}
+
+/// Returns a copy of the module where the immediately destructured calls to
+/// its functions are made to their continuation-passing variants, which are
+/// added right after the functions they are generated from.
+pub fn optimise(module: &TypedModule) -> TypedModule {
+    let functions = wrapping_functions(module);
+    let mut rewriter = CallRewriter {
+        module: &module.name,
+        functions: &functions,
+        used: HashSet::new(),
+    };
+
+    let definitions = module
+        .definitions
+        .iter()
+        .cloned()
+        .map(|definition| match definition {
+            Definition::Function(function) => Definition::Function(TypedFunction {
+                body: rewriter.statements(function.body),
+                ..function
+            }),
+            definition => definition,
+        })
+        .collect_vec();
+
+    // Variants can call the variants of other functions in tail position,
+    // which must then be generated as well.
+    let mut pending = rewriter.used.into_iter().collect_vec();
+    let mut variants = HashMap::new();
+    while let Some(name) = pending.pop() {
+        if variants.contains_key(&name) {
+            continue;
+        }
+        let function = definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::Function(function) if function_name(function) == Some(&name) => {
+                    Some(function)
+                }
+                _ => None,
+            })
+            .expect("Called variant of a function missing from the module");
+        let mut builder = VariantBuilder {
+            module: &module.name,
+            wrapper: *functions
+                .get(&name)
+                .expect("Called variant of a function which has no wrapper"),
+            continuations: vec![],
+            called: vec![],
+        };
+        let variant = builder.variant(function);
+        pending.extend(builder.called);
+        let _ = variants.insert(name, variant);
+    }
+
+    let definitions = definitions
+        .into_iter()
+        .flat_map(|definition| {
+            let variant = match &definition {
+                Definition::Function(function) => function_name(function)
+                    .and_then(|name| variants.remove(name))
+                    .map(Definition::Function),
+                _ => None,
+            };
+            std::iter::once(definition).chain(variant)
+        })
+        .collect();
+
+    TypedModule {
+        definitions,
+        ..module.clone()
+    }
+}
+
+fn function_name(function: &TypedFunction) -> Option<&EcoString> {
+    function.name.as_ref().map(|(_, name)| name)
+}
+
+/// Finds the functions of the module which always return a literal wrapper,
+/// and so can have a continuation-passing variant.
+fn wrapping_functions(module: &TypedModule) -> HashMap<EcoString, Wrapper> {
+    let bodies: HashMap<_, _> = module
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Function(function)
+                if function.external_nix.is_none()
+                    && function.implementations.supports(Target::Nix) =>
+            {
+                Some((function_name(function)?, function))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut functions: HashMap<_, _> = bodies
+        .iter()
+        .filter_map(|(name, function)| Some(((*name).clone(), Wrapper::of(&function.return_type)?)))
+        .collect();
+
+    // A function returning the result of another one's call can only have a
+    // variant if that other function has one too, so functions are discarded
+    // until all of those which are left qualify.
+    loop {
+        let discarded = functions
+            .iter()
+            .filter(|(name, wrapper)| {
+                let checker = TailChecker {
+                    module: &module.name,
+                    functions: &functions,
+                    wrapper: **wrapper,
+                };
+                bodies
+                    .get(name)
+                    .is_some_and(|function| !checker.statements(&function.body))
+            })
+            .map(|(name, _)| name.clone())
+            .collect_vec();
+
+        if discarded.is_empty() {
+            return functions;
+        }
+        for name in discarded {
+            let _ = functions.remove(&name);
+        }
+    }
+}
+
+/// The function of this module being called, if any.
+fn called_module_function<'a>(module: &str, fun: &'a TypedExpr) -> Option<&'a EcoString> {
+    match fun {
+        TypedExpr::Var {
+            constructor:
+                ValueConstructor {
+                    variant:
+                        ValueConstructorVariant::ModuleFn {
+                            name,
+                            module: function_module,
+                            ..
+                        },
+                    ..
+                },
+            ..
+        } if function_module == module => Some(name),
+        _ => None,
+    }
+}
+
+/// Whether the function called builds a `Result`, with the index of its
+/// continuation: 0 for `Ok` and 1 for `Error`.
+fn result_constructor(fun: &TypedExpr) -> Option<usize> {
+    let name = match fun {
+        TypedExpr::Var {
+            constructor:
+                ValueConstructor {
+                    variant: ValueConstructorVariant::Record { name, module, .. },
+                    ..
+                },
+            ..
+        } if module == PRELUDE_MODULE_NAME => name,
+        TypedExpr::ModuleSelect {
+            constructor: ModuleValueConstructor::Record { name, .. },
+            module_name,
+            ..
+        } if module_name == PRELUDE_MODULE_NAME => name,
+        _ => return None,
+    };
+    match name.as_str() {
+        "Ok" => Some(0),
+        "Error" => Some(1),
+        _ => None,
+    }
+}
+
+/// Checks whether the values in tail position of a function body are all
+/// literal wrappers, calls to functions with a variant for the same wrapper,
+/// or expressions which never return.
+struct TailChecker<'a> {
+    module: &'a str,
+    functions: &'a HashMap<EcoString, Wrapper>,
+    wrapper: Wrapper,
+}
+
+impl TailChecker<'_> {
+    fn statements(&self, statements: &[TypedStatement]) -> bool {
+        match statements.last() {
+            Some(Statement::Expression(expression)) => self.expression(expression),
+            Some(Statement::Assignment(_) | Statement::Use(_)) | None => false,
+        }
+    }
+
+    fn expression(&self, expression: &TypedExpr) -> bool {
+        match expression {
+            TypedExpr::Tuple { .. } => matches!(self.wrapper, Wrapper::Tuple(_)),
+
+            TypedExpr::Call { fun, args, .. } if result_constructor(fun).is_some() => {
+                self.wrapper == Wrapper::Result && args.len() == 1
+            }
+
+            TypedExpr::Call { fun, .. } => called_module_function(self.module, fun)
+                .is_some_and(|name| self.functions.get(name) == Some(&self.wrapper)),
+
+            TypedExpr::Block { statements, .. } => self.statements(statements),
+            TypedExpr::Pipeline { finally, .. } => self.expression(finally),
+            TypedExpr::Case { clauses, .. } => {
+                clauses.iter().all(|clause| self.expression(&clause.then))
+            }
+
+            TypedExpr::Panic { .. } | TypedExpr::Todo { .. } => true,
+
+            TypedExpr::Int { .. }
+            | TypedExpr::Float { .. }
+            | TypedExpr::String { .. }
+            | TypedExpr::Var { .. }
+            | TypedExpr::Fn { .. }
+            | TypedExpr::List { .. }
+            | TypedExpr::BinOp { .. }
+            | TypedExpr::RecordAccess { .. }
+            | TypedExpr::ModuleSelect { .. }
+            | TypedExpr::TupleIndex { .. }
+            | TypedExpr::BitArray { .. }
+            | TypedExpr::RecordUpdate { .. }
+            | TypedExpr::NegateBool { .. }
+            | TypedExpr::NegateInt { .. }
+            | TypedExpr::Invalid { .. } => false,
+        }
+    }
+}
+
+/// Builds the continuation-passing variant of a function, which calls its
+/// continuations with the elements of the wrappers it would have returned.
+struct VariantBuilder<'a> {
+    module: &'a str,
+    wrapper: Wrapper,
+    /// References to the continuations taken by the variant.
+    continuations: Vec<TypedExpr>,
+    /// The functions whose variant is called in tail position.
+    called: Vec<EcoString>,
+}
+
+impl VariantBuilder<'_> {
+    fn variant(&mut self, function: &TypedFunction) -> TypedFunction {
+        let location = function.location;
+        let mut arguments = function.arguments.clone();
+        for (name, type_) in self.wrapper.continuations(&function.return_type) {
+            arguments.push(Arg {
+                names: ArgNames::Named {
+                    name: name.into(),
+                    location,
+                },
+                location,
+                annotation: None,
+                type_: type_.clone(),
+            });
+            self.continuations
+                .push(local_variable(location, name.into(), type_));
+        }
+
+        TypedFunction {
+            name: function
+                .name
+                .as_ref()
+                .map(|(location, name)| (*location, variant_name(name))),
+            arguments,
+            body: self.statements(function.body.clone()),
+            publicity: Publicity::Private,
+            documentation: None,
+            return_annotation: None,
+            return_type: prelude::generic_var(0),
+            ..function.clone()
+        }
+    }
+
+    fn statements(&mut self, statements: Vec1<TypedStatement>) -> Vec1<TypedStatement> {
+        let mut statements = statements.into_vec();
+        let last = match statements.pop().expect("Statements can't be empty") {
+            Statement::Expression(expression) => Statement::Expression(self.expression(expression)),
+            Statement::Assignment(_) | Statement::Use(_) => {
+                unreachable!("Variant built for a function not returning a wrapper")
+            }
+        };
+        statements.push(last);
+        Vec1::try_from_vec(statements).expect("Statements can't be empty")
+    }
+
+    fn expression(&mut self, expression: TypedExpr) -> TypedExpr {
+        match expression {
+            TypedExpr::Tuple {
+                location, elems, ..
+            } => self.continue_with(location, 0, elems),
+
+            TypedExpr::Call {
+                location,
+                fun,
+                args,
+                ..
+            } if result_constructor(&fun).is_some() => {
+                let index = result_constructor(&fun).unwrap_or_default();
+                let values = args.into_iter().map(|arg| arg.value).collect();
+                self.continue_with(location, index, values)
+            }
+
+            TypedExpr::Call {
+                location,
+                type_,
+                fun,
+                args,
+            } => {
+                let name = called_module_function(self.module, &fun)
+                    .expect("Variant built for a function not returning a wrapper")
+                    .clone();
+                self.called.push(name);
+                let call = TypedExpr::Call {
+                    location,
+                    type_,
+                    fun,
+                    args,
+                };
+                variant_call(call, self.continuations.clone())
+            }
+
+            TypedExpr::Block {
+                location,
+                statements,
+            } => TypedExpr::Block {
+                location,
+                statements: self.statements(statements),
+            },
+
+            TypedExpr::Pipeline {
+                location,
+                assignments,
+                finally,
+            } => TypedExpr::Pipeline {
+                location,
+                assignments,
+                finally: Box::new(self.expression(*finally)),
+            },
+
+            TypedExpr::Case {
+                location,
+                type_,
+                subjects,
+                clauses,
+                compiled_case,
+            } => TypedExpr::Case {
+                location,
+                type_,
+                subjects,
+                clauses: clauses
+                    .into_iter()
+                    .map(|clause| Clause {
+                        then: self.expression(clause.then),
+                        ..clause
+                    })
+                    .collect(),
+                compiled_case,
+            },
+
+            expression @ (TypedExpr::Panic { .. } | TypedExpr::Todo { .. }) => expression,
+
+            _ => unreachable!("Variant built for a function not returning a wrapper"),
+        }
+    }
+
+    /// Calls the continuation with the given index with the values which
+    /// would have been wrapped.
+    fn continue_with(&self, location: SrcSpan, index: usize, values: Vec<TypedExpr>) -> TypedExpr {
+        let continuation = self
+            .continuations
+            .get(index)
+            .cloned()
+            .expect("Continuation index out of bounds");
+        TypedExpr::Call {
+            location,
+            type_: prelude::generic_var(0),
+            fun: Box::new(continuation),
+            args: values.into_iter().map(call_arg).collect(),
+        }
+    }
+}
+
+/// Rewrites the immediately destructured calls to functions with a
+/// continuation-passing variant into calls to that variant.
+struct CallRewriter<'a> {
+    module: &'a str,
+    functions: &'a HashMap<EcoString, Wrapper>,
+    /// The functions whose variant has been called.
+    used: HashSet<EcoString>,
+}
+
+impl CallRewriter<'_> {
+    fn statements(&mut self, statements: Vec1<TypedStatement>) -> Vec1<TypedStatement> {
+        let statements = statements
+            .into_iter()
+            .map(|statement| self.statement(statement))
+            .collect_vec();
+        let statements = self.destructure_in_continuations(statements);
+        Vec1::try_from_vec(statements).expect("Statements can't be empty")
+    }
+
+    /// Replaces the first destructured call followed by other statements with
+    /// a call to the function's variant, whose continuation holds those
+    /// statements, themselves rewritten the same way.
+    fn destructure_in_continuations(
+        &mut self,
+        mut statements: Vec<TypedStatement>,
+    ) -> Vec<TypedStatement> {
+        let Some(index) = statements
+            .iter()
+            .take(statements.len().saturating_sub(1))
+            .position(|statement| match statement {
+                Statement::Assignment(assignment) => self.destructured_tuple(assignment).is_some(),
+                Statement::Expression(_) | Statement::Use(_) => false,
+            })
+        else {
+            return statements;
+        };
+
+        let rest = statements.split_off(index + 1);
+        let Some(Statement::Assignment(assignment)) = statements.pop() else {
+            unreachable!("Destructured call is not an assignment")
+        };
+        let parameters = self
+            .destructured_tuple(&assignment)
+            .expect("Destructured call is not a tuple assignment");
+
+        let rest = self.destructure_in_continuations(rest);
+        let body = Vec1::try_from_vec(rest).expect("Statements can't be empty");
+        let continuation = continuation(assignment.location, parameters, body);
+        statements.push(Statement::Expression(
+            self.variant_call(*assignment.value, vec![continuation]),
+        ));
+        statements
+    }
+
+    /// The parameters of the continuation to pass to the variant of the
+    /// function called in a `let` assignment destructuring a tuple.
+    fn destructured_tuple(&self, assignment: &TypedAssignment) -> Option<Vec<TypedArg>> {
+        let TypedAssignment {
+            value,
+            pattern: Pattern::Tuple { elems, .. },
+            kind: AssignmentKind::Let,
+            ..
+        } = assignment
+        else {
+            return None;
+        };
+        let wrapper = self.called_wrapper(value)?;
+        if wrapper != Wrapper::Tuple(elems.len()) {
+            return None;
+        }
+        elems.iter().map(parameter).collect()
+    }
+
+    /// The continuations to pass to the variant of the function called as
+    /// the subject of a `case` expression matching on `Ok` and `Error`,
+    /// ordered like the variant's.
+    fn destructured_result(
+        &self,
+        subjects: &[TypedExpr],
+        clauses: &[TypedClause],
+    ) -> Option<[(usize, TypedArg); 2]> {
+        let [subject] = subjects else {
+            return None;
+        };
+        if self.called_wrapper(subject)? != Wrapper::Result {
+            return None;
+        }
+
+        let [first, second] = clauses else {
+            return None;
+        };
+        let mut continuations = [first, second]
+            .into_iter()
+            .enumerate()
+            .map(|(clause_index, clause)| {
+                if clause.guard.is_some() || !clause.alternative_patterns.is_empty() {
+                    return None;
+                }
+                let [Pattern::Constructor {
+                    arguments,
+                    constructor: Inferred::Known(constructor),
+                    ..
+                }] = clause.pattern.as_slice()
+                else {
+                    return None;
+                };
+                if constructor.module != PRELUDE_MODULE_NAME {
+                    return None;
+                }
+                let index = match constructor.name.as_str() {
+                    "Ok" => 0,
+                    "Error" => 1,
+                    _ => return None,
+                };
+                let [argument] = arguments.as_slice() else {
+                    return None;
+                };
+                Some((index, (clause_index, parameter(&argument.value)?)))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        continuations.sort_by_key(|(index, _)| *index);
+        match continuations.as_slice() {
+            [(0, ok), (1, error)] => Some([ok.clone(), error.clone()]),
+            _ => None,
+        }
+    }
+
+    /// The wrapper returned by the called function, if it has a variant.
+    fn called_wrapper(&self, value: &TypedExpr) -> Option<Wrapper> {
+        let TypedExpr::Call { fun, .. } = value else {
+            return None;
+        };
+        let name = called_module_function(self.module, fun)?;
+        self.functions.get(name).copied()
+    }
+
+    fn variant_call(&mut self, call: TypedExpr, continuations: Vec<TypedExpr>) -> TypedExpr {
+        if let TypedExpr::Call { fun, .. } = &call {
+            if let Some(name) = called_module_function(self.module, fun) {
+                let _ = self.used.insert(name.clone());
+            }
+        }
+        variant_call(call, continuations)
+    }
+
+    fn statement(&mut self, statement: TypedStatement) -> TypedStatement {
+        match statement {
+            Statement::Expression(expression) => Statement::Expression(self.expression(expression)),
+            Statement::Assignment(assignment) => Statement::Assignment(self.assignment(assignment)),
+            Statement::Use(use_) => Statement::Use(use_),
+        }
+    }
+
+    fn assignment(&mut self, assignment: TypedAssignment) -> TypedAssignment {
+        TypedAssignment {
+            value: self.boxed(assignment.value),
+            ..assignment
+        }
+    }
+
+    fn boxed(&mut self, expression: Box<TypedExpr>) -> Box<TypedExpr> {
+        Box::new(self.expression(*expression))
+    }
+
+    fn call_args(&mut self, args: Vec<CallArg<TypedExpr>>) -> Vec<CallArg<TypedExpr>> {
+        args.into_iter()
+            .map(|arg| CallArg {
+                value: self.expression(arg.value),
+                ..arg
+            })
+            .collect()
+    }
+
+    fn expression(&mut self, expression: TypedExpr) -> TypedExpr {
+        match expression {
+            TypedExpr::Block {
+                location,
+                statements,
+            } => TypedExpr::Block {
+                location,
+                statements: self.statements(statements),
+            },
+
+            TypedExpr::Pipeline {
+                location,
+                assignments,
+                finally,
+            } => TypedExpr::Pipeline {
+                location,
+                assignments: assignments
+                    .into_iter()
+                    .map(|assignment| self.assignment(assignment))
+                    .collect(),
+                finally: self.boxed(finally),
+            },
+
+            TypedExpr::Fn {
+                location,
+                type_,
+                kind,
+                args,
+                body,
+                return_annotation,
+            } => TypedExpr::Fn {
+                location,
+                type_,
+                kind,
+                args,
+                body: self.statements(body),
+                return_annotation,
+            },
+
+            TypedExpr::List {
+                location,
+                type_,
+                elements,
+                tail,
+            } => TypedExpr::List {
+                location,
+                type_,
+                elements: elements
+                    .into_iter()
+                    .map(|element| self.expression(element))
+                    .collect(),
+                tail: tail.map(|tail| self.boxed(tail)),
+            },
+
+            TypedExpr::Call {
+                location,
+                type_,
+                fun,
+                args,
+            } => TypedExpr::Call {
+                location,
+                type_,
+                fun: self.boxed(fun),
+                args: self.call_args(args),
+            },
+
+            TypedExpr::BinOp {
+                location,
+                type_,
+                name,
+                left,
+                right,
+            } => TypedExpr::BinOp {
+                location,
+                type_,
+                name,
+                left: self.boxed(left),
+                right: self.boxed(right),
+            },
+
+            TypedExpr::Case {
+                location,
+                type_,
+                subjects,
+                clauses,
+                compiled_case,
+            } => {
+                let subjects = subjects
+                    .into_iter()
+                    .map(|subject| self.expression(subject))
+                    .collect_vec();
+                let clauses = clauses
+                    .into_iter()
+                    .map(|clause| Clause {
+                        then: self.expression(clause.then),
+                        ..clause
+                    })
+                    .collect_vec();
+
+                let Some(continuations) = self.destructured_result(&subjects, &clauses) else {
+                    return TypedExpr::Case {
+                        location,
+                        type_,
+                        subjects,
+                        clauses,
+                        compiled_case,
+                    };
+                };
+
+                let mut clauses = clauses.into_iter().map(Some).collect_vec();
+                let continuations = continuations
+                    .into_iter()
+                    .map(|(clause_index, parameter)| {
+                        let clause = clauses
+                            .get_mut(clause_index)
+                            .and_then(Option::take)
+                            .expect("Clause used for both continuations");
+                        let body = Vec1::new(Statement::Expression(clause.then));
+                        continuation(clause.location, vec![parameter], body)
+                    })
+                    .collect();
+                let subject = subjects
+                    .into_iter()
+                    .next()
+                    .expect("Destructured result without a subject");
+                self.variant_call(subject, continuations)
+            }
+
+            TypedExpr::RecordAccess {
+                location,
+                type_,
+                label,
+                index,
+                record,
+            } => TypedExpr::RecordAccess {
+                location,
+                type_,
+                label,
+                index,
+                record: self.boxed(record),
+            },
+
+            TypedExpr::Tuple {
+                location,
+                type_,
+                elems,
+            } => TypedExpr::Tuple {
+                location,
+                type_,
+                elems: elems
+                    .into_iter()
+                    .map(|element| self.expression(element))
+                    .collect(),
+            },
+
+            TypedExpr::TupleIndex {
+                location,
+                type_,
+                index,
+                tuple,
+            } => TypedExpr::TupleIndex {
+                location,
+                type_,
+                index,
+                tuple: self.boxed(tuple),
+            },
+
+            TypedExpr::Todo {
+                location,
+                message,
+                kind,
+                type_,
+            } => TypedExpr::Todo {
+                location,
+                message: message.map(|message| self.boxed(message)),
+                kind,
+                type_,
+            },
+
+            TypedExpr::Panic {
+                location,
+                message,
+                type_,
+            } => TypedExpr::Panic {
+                location,
+                message: message.map(|message| self.boxed(message)),
+                type_,
+            },
+
+            TypedExpr::BitArray {
+                location,
+                type_,
+                segments,
+            } => TypedExpr::BitArray {
+                location,
+                type_,
+                segments: segments
+                    .into_iter()
+                    .map(|segment| BitArraySegment {
+                        value: self.boxed(segment.value),
+                        ..segment
+                    })
+                    .collect(),
+            },
+
+            TypedExpr::RecordUpdate {
+                location,
+                type_,
+                record,
+                constructor,
+                args,
+            } => TypedExpr::RecordUpdate {
+                location,
+                type_,
+                record: self.assignment(record),
+                constructor,
+                args: self.call_args(args),
+            },
+
+            TypedExpr::NegateBool { location, value } => TypedExpr::NegateBool {
+                location,
+                value: self.boxed(value),
+            },
+
+            TypedExpr::NegateInt { location, value } => TypedExpr::NegateInt {
+                location,
+                value: self.boxed(value),
+            },
+
+            expression @ (TypedExpr::Int { .. }
+            | TypedExpr::Float { .. }
+            | TypedExpr::String { .. }
+            | TypedExpr::Var { .. }
+            | TypedExpr::ModuleSelect { .. }
+            | TypedExpr::Invalid { .. }) => expression,
+        }
+    }
+}
+
+/// The parameter of a continuation binding the same variable as a pattern.
+fn parameter(pattern: &TypedPattern) -> Option<TypedArg> {
+    let (names, location, type_) = match pattern {
+        Pattern::Variable {
+            location,
+            name,
+            type_,
+            ..
+        } => (
+            ArgNames::Named {
+                name: name.clone(),
+                location: *location,
+            },
+            *location,
+            type_,
+        ),
+        Pattern::Discard {
+            name,
+            location,
+            type_,
+        } => (
+            ArgNames::Discard {
+                name: name.clone(),
+                location: *location,
+            },
+            *location,
+            type_,
+        ),
+        _ => return None,
+    };
+
+    Some(Arg {
+        names,
+        location,
+        annotation: None,
+        type_: type_.clone(),
+    })
+}
+
+/// An anonymous function receiving the values of a destructured wrapper.
+fn continuation(
+    location: SrcSpan,
+    parameters: Vec<TypedArg>,
+    body: Vec1<TypedStatement>,
+) -> TypedExpr {
+    let type_ = prelude::fn_(
+        parameters
+            .iter()
+            .map(|parameter| parameter.type_.clone())
+            .collect(),
+        body.last().type_(),
+    );
+    TypedExpr::Fn {
+        location,
+        type_,
+        kind: FunctionLiteralKind::Anonymous { head: location },
+        args: parameters,
+        body,
+        return_annotation: None,
+    }
+}
+
+/// Turns a call to a module function into a call to its variant, passing
+/// the given continuations after the other arguments.
+fn variant_call(call: TypedExpr, continuations: Vec<TypedExpr>) -> TypedExpr {
+    let TypedExpr::Call {
+        location,
+        fun,
+        mut args,
+        ..
+    } = call
+    else {
+        unreachable!("Variant call of an expression which is not a call")
+    };
+
+    let returned = continuations
+        .first()
+        .and_then(|continuation| continuation.type_().fn_types())
+        .map(|(_, returned)| returned)
+        .unwrap_or_else(|| prelude::generic_var(0));
+
+    let fun = match *fun {
+        TypedExpr::Var {
+            location,
+            constructor,
+            name,
+        } => {
+            let variant = match constructor.variant {
+                ValueConstructorVariant::ModuleFn {
+                    name,
+                    field_map,
+                    module,
+                    arity,
+                    location,
+                    documentation,
+                    implementations,
+                    external_erlang,
+                    external_javascript,
+                    external_nix,
+                } => ValueConstructorVariant::ModuleFn {
+                    name: variant_name(&name),
+                    field_map,
+                    module,
+                    arity: arity + continuations.len(),
+                    location,
+                    documentation,
+                    implementations,
+                    external_erlang,
+                    external_javascript,
+                    external_nix,
+                },
+                variant => variant,
+            };
+            TypedExpr::Var {
+                location,
+                constructor: ValueConstructor {
+                    variant,
+                    ..constructor
+                },
+                name: variant_name(&name),
+            }
+        }
+        fun => fun,
+    };
+
+    args.extend(continuations.into_iter().map(call_arg));
+    TypedExpr::Call {
+        location,
+        type_: returned,
+        fun: Box::new(fun),
+        args,
+    }
+}
+
+fn call_arg(value: TypedExpr) -> CallArg<TypedExpr> {
+    CallArg {
+        label: None,
+        location: value.location(),
+        value,
+        implicit: None,
+    }
+}
+
+fn local_variable(location: SrcSpan, name: EcoString, type_: Arc<Type>) -> TypedExpr {
+    TypedExpr::Var {
+        location,
+        constructor: ValueConstructor {
+            publicity: Publicity::Private,
+            deprecation: Deprecation::NotDeprecated,
+            since: None,
+            variant: ValueConstructorVariant::LocalVariable { location },
+            type_,
+        },
+        name,
+    }
+}
@@ -3,18 +3,23 @@ use crate::ast::{
     TypedAssignment, TypedClause, TypedConstant, TypedConstantBitArraySegment, TypedExpr,
     TypedExprBitArraySegment, TypedModule, TypedPattern, TypedStatement,
 };
+use crate::config::ListRepresentation;
 use crate::docvec;
+use crate::exhaustiveness::CompiledCase;
 use crate::line_numbers::LineNumbers;
 use crate::nix::{
-    maybe_escape_identifier_doc, maybe_escape_identifier_string, module_var_name_doc, pattern,
-    syntax, Error, Output, UsageTracker, INDENT,
+    decision, maybe_escape_identifier_doc, maybe_escape_identifier_string, module_var_name_doc,
+    pattern, syntax, Error, Output, UsageTracker, INDENT,
 };
-use crate::pretty::{break_, join, nil, Document, Documentable};
+use crate::pretty::{break_, concat, join, nil, Document, Documentable};
+use crate::strings::convert_string_escape_chars;
 use crate::type_::{
-    ModuleValueConstructor, Type, TypedCallArg, ValueConstructor, ValueConstructorVariant,
+    FieldMap, ModuleValueConstructor, Type, TypedCallArg, ValueConstructor, ValueConstructorVariant,
 };
 use ecow::{eco_format, EcoString};
 use itertools::Itertools;
+use num_bigint::BigInt;
+use num_traits::Signed;
 use regex::Regex;
 use std::borrow::Cow;
 use std::sync::{Arc, OnceLock};
@@ -29,10 +34,11 @@ pub(crate) struct Generator<'module> {
     module: &'module TypedModule,
     line_numbers: &'module LineNumbers,
     function_name: Option<EcoString>,
-    current_scope_vars: im::HashMap<EcoString, usize>,
+    pub(crate) current_scope_vars: im::HashMap<EcoString, usize>,
     /// Variables which must be forcibly evaluated at the end of the scope.
     /// These include assertions and unassigned expressions.
     strict_eval_vars: Vec<(EcoString, usize)>,
+    pub(crate) list_representation: ListRepresentation,
     // We register whether these features are used within an expression so that
     // the module generator can output a suitable function if it is needed.
     pub(crate) tracker: &'module mut UsageTracker,
@@ -47,6 +53,7 @@ impl<'module> Generator<'module> {
         line_numbers: &'module LineNumbers,
         function_name: Option<EcoString>,
         current_scope_vars: im::HashMap<EcoString, usize>,
+        list_representation: ListRepresentation,
         tracker: &'module mut UsageTracker,
     ) -> Self {
         Self {
@@ -55,6 +62,7 @@ impl<'module> Generator<'module> {
             function_name,
             current_scope_vars,
             strict_eval_vars: vec![],
+            list_representation,
             tracker,
         }
     }
@@ -114,6 +122,22 @@ impl<'module> Generator<'module> {
             TypedExpr::String { value, .. } => Ok(string(value, self.tracker)),
             TypedExpr::Int { value, .. } => Ok(int(value, self.tracker)),
             TypedExpr::Float { value, .. } => Ok(float(value)),
+            TypedExpr::List { elements, tail, .. }
+                if self.list_representation == ListRepresentation::Native =>
+            {
+                let elements = elements
+                    .iter()
+                    .map(|e| self.wrap_child_expression(e))
+                    .collect_vec();
+                match tail {
+                    Some(tail) => {
+                        let tail = self.wrap_child_expression(tail)?;
+                        native_prepend(elements, tail)
+                    }
+                    None => native_list(elements),
+                }
+            }
+
             TypedExpr::List { elements, tail, .. } => match tail {
                 Some(tail) => {
                     // A tail without prepended elements is a syntax error.
@@ -172,8 +196,11 @@ impl<'module> Generator<'module> {
             } => Ok(self.module_select(module_alias, label, constructor)),
 
             TypedExpr::Case {
-                subjects, clauses, ..
-            } => self.case(subjects, clauses),
+                subjects,
+                clauses,
+                compiled_case,
+                ..
+            } => self.case(subjects, clauses, compiled_case),
 
             TypedExpr::BitArray { segments, .. } => self.bit_array(segments),
 
@@ -477,12 +504,43 @@ impl<'module> Generator<'module> {
         &mut self,
         subject_values: &'a [TypedExpr],
         clauses: &'a [TypedClause],
+        compiled_case: &'a CompiledCase,
     ) -> Output<'a> {
         let (subjects, subject_assignments): (Vec<_>, Vec<_>) =
             pattern::assign_subjects(self, subject_values)
                 .into_iter()
                 .unzip();
 
+        let doc = if decision::should_follow_decision_tree(clauses, compiled_case) {
+            decision::Generator::new(self, clauses, compiled_case, &subjects)
+                .generate(&compiled_case.tree)?
+        } else {
+            self.case_clauses(&subjects, clauses)?
+        };
+
+        // If there is a subject name given create a variable to hold it for
+        // use in patterns
+        let subject_assignments: Vec<_> = subject_assignments
+            .into_iter()
+            .zip(subject_values)
+            .flat_map(|(assignment_name, value)| assignment_name.map(|name| (name, value)))
+            .map(|(name, value)| Ok(syntax::assignment_line(name, self.expression(value)?)))
+            .try_collect()?;
+
+        Ok(if subject_assignments.is_empty() {
+            doc
+        } else {
+            syntax::let_in(subject_assignments, doc, false)
+        })
+    }
+
+    /// Compiles the clauses of a case expression one by one, testing whether
+    /// each of them matches in turn.
+    fn case_clauses<'a>(
+        &mut self,
+        subjects: &[Document<'a>],
+        clauses: &'a [TypedClause],
+    ) -> Output<'a> {
         let mut gen = pattern::Generator::new(self);
 
         let mut doc = nil();
@@ -505,7 +563,7 @@ impl<'module> Generator<'module> {
             // A clause can have many patterns `pattern, pattern ->...`
             for multipatterns in multipatterns {
                 let scope = gen.expression_generator.current_scope_vars.clone();
-                let mut compiled = gen.generate(&subjects, multipatterns, clause.guard.as_ref())?;
+                let mut compiled = gen.generate(subjects, multipatterns, clause.guard.as_ref())?;
                 let consequence = gen.expression_generator.expression(&clause.then)?;
 
                 // We've seen one more clause
@@ -561,20 +619,7 @@ impl<'module> Generator<'module> {
             }
         }
 
-        // If there is a subject name given create a variable to hold it for
-        // use in patterns
-        let subject_assignments: Vec<_> = subject_assignments
-            .into_iter()
-            .zip(subject_values)
-            .flat_map(|(assignment_name, value)| assignment_name.map(|name| (name, value)))
-            .map(|(name, value)| Ok(syntax::assignment_line(name, self.expression(value)?)))
-            .try_collect()?;
-
-        Ok(if subject_assignments.is_empty() {
-            doc
-        } else {
-            syntax::let_in(subject_assignments, doc, false)
-        })
+        Ok(doc)
     }
 
     /// Outputs the expression which would replace a statement if it were the
@@ -663,6 +708,13 @@ impl<'module> Generator<'module> {
                 self.wrap_child_expression(finally)
             }
 
+            // Native lists without a tail are plain list literals.
+            TypedExpr::List { tail: None, .. }
+                if self.list_representation == ListRepresentation::Native =>
+            {
+                self.expression(expression)
+            }
+
             // Integers with 0x, 0o, 0b require a function call to 'parseNumber'
             TypedExpr::Int { value, .. } if int_requires_parsing(value) => {
                 Ok(docvec!["(", self.expression(expression)?, ")"])
@@ -1366,16 +1418,9 @@ pub(crate) fn constant_expression<'a>(
             name,
             tag,
             type_,
+            field_map,
             ..
         } => {
-            if type_.is_result() {
-                if tag == "Ok" {
-                    tracker.ok_used = true;
-                } else {
-                    tracker.error_used = true;
-                }
-            }
-
             // If there's no arguments and the type is a function that takes
             // arguments then this is the constructor being referenced, not the
             // function being called.
@@ -1385,21 +1430,26 @@ pub(crate) fn constant_expression<'a>(
                 }
             }
 
+            if args.is_empty() {
+                return Ok(construct_record(
+                    module.as_ref().map(|(module, _)| module.as_str()),
+                    name,
+                    vec![],
+                ));
+            }
+
             let field_values = args
                 .iter()
-                .map(|arg| wrap_child_constant_expression(tracker, &arg.value))
+                .map(|arg| constant_expression(tracker, &arg.value))
                 .try_collect()?;
 
-            Ok(construct_record(
-                module.as_ref().map(|(module, _)| module.as_str()),
-                name,
-                field_values,
-            ))
+            Ok(constant_record(tag, field_map.as_ref(), field_values))
         }
 
-        Constant::BitArray { segments, .. } => {
-            constant_bit_array(tracker, segments, wrap_child_constant_expression)
-        }
+        Constant::BitArray { segments, .. } => match constant_bit_array_buffer(segments) {
+            Some(parts) => bit_array_literal(tracker, parts),
+            None => constant_bit_array(tracker, segments, wrap_child_constant_expression),
+        },
 
         Constant::Var { name, module, .. } => Ok({
             match module {
@@ -1422,6 +1472,26 @@ pub(crate) fn constant_expression<'a>(
     }
 }
 
+/// The value of a constant embedding a file with `@embed_file`. A text file
+/// embedded as a bit array is a single UTF-8 string segment, which is kept as
+/// a string literal instead of being computed into one list element per byte.
+pub(crate) fn embedded_file_constant<'a>(
+    tracker: &mut UsageTracker,
+    constant: &'a TypedConstant,
+) -> Output<'a> {
+    match constant {
+        Constant::BitArray { segments, .. }
+            if matches!(
+                segments.as_slice(),
+                [segment] if matches!(segment.options.as_slice(), [BitArrayOption::Utf8 { .. }])
+            ) =>
+        {
+            constant_bit_array(tracker, segments, wrap_child_constant_expression)
+        }
+        _ => constant_expression(tracker, constant),
+    }
+}
+
 /// Same as [`constant_expression`], but wraps the result in parentheses if needed.
 fn wrap_child_constant_expression<'a>(
     tracker: &mut UsageTracker,
@@ -1435,12 +1505,13 @@ fn wrap_child_constant_expression<'a>(
         Constant::Int { value, .. } | Constant::Float { value, .. } if value.starts_with('-') => {
             Ok(docvec!("(", constant_expression(tracker, expression)?, ")"))
         }
-        Constant::List { .. }
-        | Constant::BitArray { .. }
-        | Constant::StringConcatenation { .. } => {
+        Constant::List { .. } | Constant::StringConcatenation { .. } => {
             Ok(docvec!("(", constant_expression(tracker, expression)?, ")"))
         }
-        Constant::Record { args, .. } if !args.is_empty() => {
+        // Bit arrays which can be computed at compile time and records with
+        // fields are generated as attribute set literals, which don't need
+        // parentheses.
+        Constant::BitArray { segments, .. } if constant_bit_array_buffer(segments).is_none() => {
             Ok(docvec!("(", constant_expression(tracker, expression)?, ")"))
         }
         _ => constant_expression(tracker, expression),
@@ -1507,6 +1578,43 @@ fn construct_record<'a>(
     syntax::fn_call(name, arguments)
 }
 
+/// A record in a constant is generated as an attribute set literal, the same
+/// one its constructor would return, so that using the constant doesn't need
+/// to call the constructor:
+///
+/// ```nix
+/// { __gleamTag = "Ctor"; named1 = value; _1 = value; }
+/// ```
+fn constant_record<'a>(
+    tag: &'a str,
+    field_map: Option<&'a FieldMap>,
+    field_values: Vec<Document<'a>>,
+) -> Document<'a> {
+    let tag_field = syntax::assignment_line(
+        "__gleamTag".to_doc(),
+        syntax::string_without_escapes_or_backslashes(tag),
+    );
+
+    let fields = field_values.into_iter().enumerate().map(|(index, value)| {
+        let label = field_map.and_then(|field_map| {
+            field_map
+                .fields
+                .iter()
+                .find(|(_, field_index)| **field_index as usize == index)
+                .map(|(label, _)| label)
+        });
+        // Positional fields are represented as '_INDEX', like in the record's
+        // constructor.
+        let label = match label {
+            Some(label) => syntax::maybe_quoted_attr_set_label_from_identifier(label),
+            None => eco_format!("_{index}").to_doc(),
+        };
+        docvec![break_("", " "), syntax::assignment_line(label, value)]
+    });
+
+    syntax::attr_set(docvec![tag_field, concat(fields)])
+}
+
 /// Generates a valid Nix string from a Gleam string's contents.
 ///
 /// If there are Unicode (`\u{...}`) escapes or `\f` escapes,
@@ -1663,6 +1771,29 @@ pub fn list<'a, Elements: IntoIterator<Item = Output<'a>>>(elements: Elements) -
     Ok(syntax::fn_call("toList".to_doc(), [element_list]))
 }
 
+/// Constructs a Gleam list represented as a Nix list, which needs no
+/// conversion:
+///
+/// ```nix
+/// [ elem1 elem2 elem3 ... elemN ]
+/// ```
+fn native_list<'a>(elements: impl IntoIterator<Item = Output<'a>>) -> Output<'a> {
+    let elements: Vec<_> = elements.into_iter().try_collect()?;
+    Ok(syntax::list(elements))
+}
+
+/// Prepends elements before an existing list represented as a Nix list:
+///
+/// ```nix
+/// [ elem1 elem2 ... elemN ] ++ tail
+/// ```
+fn native_prepend<'a>(
+    elements: impl IntoIterator<Item = Output<'a>>,
+    tail: Document<'a>,
+) -> Output<'a> {
+    Ok(docvec![native_list(elements)?, " ++ ", tail])
+}
+
 fn constant_bit_array<'a>(
     tracker: &mut UsageTracker,
     segments: &'a [TypedConstantBitArraySegment],
@@ -1740,6 +1871,121 @@ fn constant_bit_array<'a>(
     Ok(syntax::fn_call("toBitArray".to_doc(), [segments_array]))
 }
 
+/// A part of the buffer of a constant bit array which needs no prelude
+/// function to be computed.
+enum ConstantBufferPart<'a> {
+    /// Bytes computed at compile time.
+    Bytes(Vec<u8>),
+    /// The buffer of another constant bit array, included with `:bits`.
+    Buffer(&'a TypedConstant),
+}
+
+/// Splits the buffer of a constant bit array into the bytes that can be
+/// computed at compile time, when all of its segments are literals, and the
+/// buffers of the constants it includes, so that it can be generated without
+/// calling the prelude. Returns `None` for any other segment, which is then
+/// left for [`constant_bit_array`] to handle or reject.
+fn constant_bit_array_buffer(
+    segments: &[TypedConstantBitArraySegment],
+) -> Option<Vec<ConstantBufferPart<'_>>> {
+    fn push_bytes(parts: &mut Vec<ConstantBufferPart<'_>>, new_bytes: &[u8]) {
+        match parts.last_mut() {
+            Some(ConstantBufferPart::Bytes(bytes)) => bytes.extend(new_bytes),
+            _ => parts.push(ConstantBufferPart::Bytes(new_bytes.to_vec())),
+        }
+    }
+
+    let mut parts = vec![];
+    for segment in segments {
+        match (segment.value.as_ref(), segment.options.as_slice()) {
+            // Like 'toBitArray', only keep the lowest byte of the int. Negative
+            // ints are left to the prelude.
+            (Constant::Int { int_value, .. }, [] | [BitArrayOption::Int { .. }]) => {
+                if int_value.is_negative() {
+                    return None;
+                }
+                push_bytes(&mut parts, &[u8::try_from(int_value % 256).ok()?]);
+            }
+
+            // Like 'sizedInt', store the int in big endian two's complement.
+            (Constant::Int { int_value, .. }, [BitArrayOption::Size { value: size, .. }]) => {
+                let Constant::Int { value: size, .. } = size.as_ref() else {
+                    return None;
+                };
+                let size: i64 = size.parse().ok()?;
+                if size % 8 != 0 {
+                    return None;
+                }
+                let mut value = int_value.clone();
+                let mut int_bytes = vec![];
+                for _ in 0..size / 8 {
+                    let byte = &value & BigInt::from(255);
+                    value = (value - &byte) / 256;
+                    int_bytes.push(u8::try_from(byte).ok()?);
+                }
+                int_bytes.reverse();
+                push_bytes(&mut parts, &int_bytes);
+            }
+
+            (Constant::String { value, .. }, [BitArrayOption::Utf8 { .. }]) => {
+                push_bytes(&mut parts, convert_string_escape_chars(value).as_bytes());
+            }
+
+            (Constant::BitArray { segments, .. }, [BitArrayOption::Bits { .. }]) => {
+                for part in constant_bit_array_buffer(segments)? {
+                    match part {
+                        ConstantBufferPart::Bytes(bytes) => push_bytes(&mut parts, &bytes),
+                        part @ ConstantBufferPart::Buffer(_) => parts.push(part),
+                    }
+                }
+            }
+
+            (constant @ Constant::Var { .. }, [BitArrayOption::Bits { .. }]) => {
+                parts.push(ConstantBufferPart::Buffer(constant));
+            }
+
+            _ => return None,
+        }
+    }
+    Some(parts)
+}
+
+/// A bit array whose buffer needs no prelude function, generated as the
+/// attribute set 'BitArray' from the prelude would return. The buffers of
+/// other constants are concatenated to the bytes computed at compile time:
+///
+/// ```nix
+/// { __gleamTag = "BitArray"; __gleamBuiltIn = "BitArray"; buffer = [ 1 2 ] ++ other.buffer; }
+/// ```
+fn bit_array_literal<'a>(
+    tracker: &mut UsageTracker,
+    parts: Vec<ConstantBufferPart<'a>>,
+) -> Output<'a> {
+    let buffer = if parts.is_empty() {
+        syntax::list(std::iter::empty::<Document<'a>>())
+    } else {
+        let parts: Vec<_> = parts
+            .into_iter()
+            .map(|part| match part {
+                ConstantBufferPart::Bytes(bytes) => Ok(syntax::list(
+                    bytes.into_iter().map(|byte| eco_format!("{byte}").to_doc()),
+                )),
+                ConstantBufferPart::Buffer(constant) => {
+                    Ok(docvec![constant_expression(tracker, constant)?, ".buffer"])
+                }
+            })
+            .try_collect()?;
+        join(parts, " ++ ".to_doc())
+    };
+    Ok(syntax::attr_set(docvec![
+        syntax::assignment_line("__gleamTag".to_doc(), "\"BitArray\"".to_doc()),
+        break_("", " "),
+        syntax::assignment_line("__gleamBuiltIn".to_doc(), "\"BitArray\"".to_doc()),
+        break_("", " "),
+        syntax::assignment_line("buffer".to_doc(), buffer),
+    ]))
+}
+
 /// Prepends elements before an existing list:
 ///
 /// ```nix
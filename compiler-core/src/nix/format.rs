@@ -0,0 +1,1880 @@
+//! A formatter for hand-written Nix files, such as the ones implementing
+//! `@external(nix, ...)` functions, which lays them out following the same
+//! conventions as the code generated by the compiler.
+//!
+//! Formatting never changes the meaning of a file: every token and comment
+//! of the source is printed back in the same order, only the whitespace
+//! between them changes. Strings are kept exactly as they were written.
+
+#[cfg(test)]
+mod tests;
+
+use crate::ast::SrcSpan;
+use crate::docvec;
+use crate::nix::{Error, INDENT};
+use crate::pretty::{
+    break_, concat, join, line, lines, nil, Document, Documentable, NextBreakFitsMode,
+};
+
+/// Formats the given Nix source code, keeping lines within the given width
+/// where possible.
+pub fn pretty(src: &str, line_width: isize) -> Result<String, Error> {
+    let tokens = Lexer { src }.tokens()?;
+    let expression = Parser {
+        tokens: &tokens,
+        position: 0,
+    }
+    .file()?;
+
+    let mut printer = Printer { tokens };
+    let document = printer.file(&expression);
+    let mut output = document.to_pretty_string(line_width);
+    output.push('\n');
+    Ok(output)
+}
+
+fn syntax_error(problem: impl Into<String>, start: usize, end: usize) -> Error {
+    Error::Syntax {
+        problem: problem.into(),
+        location: SrcSpan::new(start as u32, end as u32),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Identifier,
+    Int,
+    Float,
+    Path,
+    SearchPath,
+    Uri,
+    String,
+    Let,
+    In,
+    Rec,
+    Inherit,
+    With,
+    Assert,
+    If,
+    Then,
+    Else,
+    LeftBrace,
+    RightBrace,
+    LeftSquare,
+    RightSquare,
+    LeftParen,
+    RightParen,
+    DollarBrace,
+    Semicolon,
+    Colon,
+    Comma,
+    Dot,
+    Ellipsis,
+    At,
+    Question,
+    Equal,
+    Operator,
+    EndOfFile,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+    start: usize,
+    /// The comments between the previous token and this one.
+    comments: Vec<Comment<'a>>,
+    /// Whether there is a newline between this token and the previous token
+    /// or comment.
+    newline_before: bool,
+    /// Whether there is an empty line between this token and the previous
+    /// token or comment.
+    blank_line_before: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Comment<'a> {
+    text: &'a str,
+    newline_before: bool,
+    blank_line_before: bool,
+}
+
+impl Comment<'_> {
+    fn is_line_comment(&self) -> bool {
+        self.text.starts_with('#')
+    }
+}
+
+/// Splits Nix source code into tokens, following the same longest match
+/// rules as Nix's own lexer so that paths, URIs and identifiers containing
+/// `-` or `'` are told apart in the same way.
+struct Lexer<'a> {
+    src: &'a str,
+}
+
+const KEYWORDS: [(&str, Kind); 9] = [
+    ("let", Kind::Let),
+    ("in", Kind::In),
+    ("rec", Kind::Rec),
+    ("inherit", Kind::Inherit),
+    ("with", Kind::With),
+    ("assert", Kind::Assert),
+    ("if", Kind::If),
+    ("then", Kind::Then),
+    ("else", Kind::Else),
+];
+
+/// Punctuation, longest first so that `==` isn't read as two `=`.
+const PUNCTUATION: [(&str, Kind); 31] = [
+    ("...", Kind::Ellipsis),
+    ("${", Kind::DollarBrace),
+    ("==", Kind::Operator),
+    ("!=", Kind::Operator),
+    ("<=", Kind::Operator),
+    (">=", Kind::Operator),
+    ("&&", Kind::Operator),
+    ("||", Kind::Operator),
+    ("->", Kind::Operator),
+    ("++", Kind::Operator),
+    ("//", Kind::Operator),
+    ("{", Kind::LeftBrace),
+    ("}", Kind::RightBrace),
+    ("[", Kind::LeftSquare),
+    ("]", Kind::RightSquare),
+    ("(", Kind::LeftParen),
+    (")", Kind::RightParen),
+    (";", Kind::Semicolon),
+    (":", Kind::Colon),
+    (",", Kind::Comma),
+    (".", Kind::Dot),
+    ("@", Kind::At),
+    ("?", Kind::Question),
+    ("=", Kind::Equal),
+    ("<", Kind::Operator),
+    (">", Kind::Operator),
+    ("!", Kind::Operator),
+    ("+", Kind::Operator),
+    ("-", Kind::Operator),
+    ("*", Kind::Operator),
+    ("/", Kind::Operator),
+];
+
+impl<'a> Lexer<'a> {
+    fn tokens(&self) -> Result<Vec<Token<'a>>, Error> {
+        let mut tokens = vec![];
+        let mut position = 0;
+        loop {
+            let (comments, newlines, start) = self.trivia(position)?;
+            let (kind, end) = if start == self.src.len() {
+                (Kind::EndOfFile, start)
+            } else {
+                self.token(start)?
+            };
+            tokens.push(Token {
+                kind,
+                text: self.slice(start, end),
+                start,
+                comments,
+                newline_before: newlines > 0,
+                blank_line_before: newlines > 1,
+            });
+            if kind == Kind::EndOfFile {
+                return Ok(tokens);
+            }
+            position = end;
+        }
+    }
+
+    fn byte(&self, position: usize) -> Option<u8> {
+        self.src.as_bytes().get(position).copied()
+    }
+
+    fn bytes(&self, position: usize) -> &'a [u8] {
+        after(self.src.as_bytes(), position)
+    }
+
+    fn slice(&self, start: usize, end: usize) -> &'a str {
+        self.src.get(start..end).unwrap_or_default()
+    }
+
+    fn starts_with(&self, position: usize, prefix: &str) -> bool {
+        self.bytes(position).starts_with(prefix.as_bytes())
+    }
+
+    /// Skips the whitespace and comments starting at the given position,
+    /// returning the comments, the number of newlines since the last of them
+    /// and the position of the next token.
+    fn trivia(&self, mut position: usize) -> Result<(Vec<Comment<'a>>, usize, usize), Error> {
+        let mut comments = vec![];
+        let mut newlines = 0;
+        loop {
+            match self.byte(position) {
+                Some(b'\n') => {
+                    newlines += 1;
+                    position += 1;
+                }
+                Some(b' ' | b'\t' | b'\r') => position += 1,
+                Some(b'#') => {
+                    let end = self
+                        .slice(position, self.src.len())
+                        .find('\n')
+                        .map_or(self.src.len(), |end| position + end);
+                    comments.push(Comment {
+                        text: self.slice(position, end).trim_end(),
+                        newline_before: newlines > 0,
+                        blank_line_before: newlines > 1,
+                    });
+                    newlines = 0;
+                    position = end;
+                }
+                Some(b'/') if self.starts_with(position, "/*") => {
+                    let end = self
+                        .slice(position + 2, self.src.len())
+                        .find("*/")
+                        .map(|end| position + 2 + end + 2)
+                        .ok_or_else(|| {
+                            syntax_error("This comment is never closed", position, position + 2)
+                        })?;
+                    comments.push(Comment {
+                        text: self.slice(position, end),
+                        newline_before: newlines > 0,
+                        blank_line_before: newlines > 1,
+                    });
+                    newlines = 0;
+                    position = end;
+                }
+                _ => return Ok((comments, newlines, position)),
+            }
+        }
+    }
+
+    /// Reads the token starting at the given position, returning its kind and
+    /// where it ends.
+    fn token(&self, start: usize) -> Result<(Kind, usize), Error> {
+        if self.byte(start) == Some(b'"') {
+            return Ok((Kind::String, self.string(start)?));
+        }
+        if self.starts_with(start, "''") {
+            return Ok((Kind::String, self.indented_string(start)?));
+        }
+
+        // When several rules match, the longest match wins, and the rule that
+        // comes first wins a tie.
+        let rest = self.bytes(start);
+        let candidates = [
+            (Kind::Identifier, identifier_length(rest)),
+            (
+                Kind::Int,
+                rest.iter().take_while(|c| c.is_ascii_digit()).count(),
+            ),
+            (Kind::Float, float_length(rest)),
+            (Kind::Path, self.path_length(start)?),
+            (Kind::SearchPath, search_path_length(rest)),
+            (Kind::Uri, uri_length(rest)),
+        ];
+        let (kind, length) =
+            candidates
+                .into_iter()
+                .fold((Kind::EndOfFile, 0), |longest, candidate| {
+                    if candidate.1 > longest.1 {
+                        candidate
+                    } else {
+                        longest
+                    }
+                });
+        if length > 0 {
+            let kind = match kind {
+                Kind::Identifier => KEYWORDS
+                    .iter()
+                    .find(|(keyword, _)| rest.get(..length) == Some(keyword.as_bytes()))
+                    .map_or(kind, |(_, keyword)| *keyword),
+                _ => kind,
+            };
+            return Ok((kind, start + length));
+        }
+
+        match PUNCTUATION
+            .iter()
+            .find(|(text, _)| self.starts_with(start, text))
+        {
+            Some((text, kind)) => Ok((*kind, start + text.len())),
+            None => {
+                let length = self
+                    .slice(start, self.src.len())
+                    .chars()
+                    .next()
+                    .map_or(1, char::len_utf8);
+                Err(syntax_error(
+                    "This character is not valid Nix syntax",
+                    start,
+                    start + length,
+                ))
+            }
+        }
+    }
+
+    /// The length of the path starting at the given position, including any
+    /// `${...}` interpolated into it, or 0 if there is no path there.
+    fn path_length(&self, start: usize) -> Result<usize, Error> {
+        let rest = self.bytes(start);
+        let mut length = if rest.first() == Some(&b'~') {
+            1
+        } else {
+            path_chars(rest)
+        };
+        let mut segments = 0;
+        while rest.get(length) == Some(&b'/') && rest.get(length + 1).is_some_and(is_path_char) {
+            length += 1 + path_chars(after(rest, length + 1));
+            segments += 1;
+        }
+        if segments == 0 {
+            // A path can also start with an interpolation after its first
+            // slash, such as in `./${name}`.
+            if rest.get(length) == Some(&b'/') && after(rest, length + 1).starts_with(b"${") {
+                length += 1;
+            } else {
+                return Ok(0);
+            }
+        }
+
+        loop {
+            let position = start + length;
+            if self.starts_with(position, "${") {
+                length = self.interpolation_end(position)? - start;
+            } else if rest
+                .get(length)
+                .is_some_and(|c| *c == b'/' || is_path_char(c))
+            {
+                length += 1;
+            } else {
+                return Ok(length);
+            }
+        }
+    }
+
+    /// Where the `${...}` interpolation starting at the given position ends.
+    fn interpolation_end(&self, start: usize) -> Result<usize, Error> {
+        let mut depth = 0;
+        let mut position = start;
+        loop {
+            let (_, _, token_start) = self.trivia(position)?;
+            if token_start == self.src.len() {
+                return Err(syntax_error(
+                    "This interpolation is never closed",
+                    start,
+                    start + 2,
+                ));
+            }
+            let (kind, end) = self.token(token_start)?;
+            match kind {
+                Kind::LeftBrace | Kind::DollarBrace => depth += 1,
+                Kind::RightBrace => depth -= 1,
+                _ => (),
+            }
+            if depth == 0 {
+                return Ok(end);
+            }
+            position = end;
+        }
+    }
+
+    /// Where the `"..."` string starting at the given position ends.
+    fn string(&self, start: usize) -> Result<usize, Error> {
+        let mut position = start + 1;
+        loop {
+            match self.byte(position) {
+                None => {
+                    return Err(syntax_error(
+                        "This string is never closed",
+                        start,
+                        start + 1,
+                    ))
+                }
+                Some(b'"') => return Ok(position + 1),
+                Some(b'\\' | b'$') if self.starts_with(position + 1, "$") => position += 2,
+                Some(b'\\') => position += 2,
+                Some(b'$') if self.starts_with(position, "${") => {
+                    position = self.interpolation_end(position)?;
+                }
+                Some(_) => position += 1,
+            }
+        }
+    }
+
+    /// Where the `''...''` string starting at the given position ends.
+    fn indented_string(&self, start: usize) -> Result<usize, Error> {
+        let mut position = start + 2;
+        loop {
+            if position >= self.src.len() {
+                return Err(syntax_error(
+                    "This string is never closed",
+                    start,
+                    start + 2,
+                ));
+            } else if self.starts_with(position, "'''") || self.starts_with(position, "''$") {
+                position += 3;
+            } else if self.starts_with(position, "''\\") {
+                position += 4;
+            } else if self.starts_with(position, "''") {
+                return Ok(position + 2);
+            } else if self.starts_with(position, "${") {
+                position = self.interpolation_end(position)?;
+            } else if self.starts_with(position, "$$") {
+                position += 2;
+            } else {
+                position += 1;
+            }
+        }
+    }
+}
+
+/// The bytes after the given index, or none if it is past the end.
+fn after(src: &[u8], index: usize) -> &[u8] {
+    src.get(index..).unwrap_or_default()
+}
+
+fn is_path_char(c: &u8) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, b'.' | b'_' | b'-' | b'+')
+}
+
+fn path_chars(src: &[u8]) -> usize {
+    src.iter().take_while(|c| is_path_char(c)).count()
+}
+
+fn identifier_length(src: &[u8]) -> usize {
+    match src.first() {
+        Some(c) if c.is_ascii_alphabetic() || *c == b'_' => {
+            1 + after(src, 1)
+                .iter()
+                .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, b'_' | b'\'' | b'-'))
+                .count()
+        }
+        _ => 0,
+    }
+}
+
+fn digits(src: &[u8]) -> usize {
+    src.iter().take_while(|c| c.is_ascii_digit()).count()
+}
+
+/// Matches `(([1-9][0-9]*\.[0-9]*)|(0?\.[0-9]+))([Ee][+-]?[0-9]+)?`.
+fn float_length(src: &[u8]) -> usize {
+    let mut length = match src.first() {
+        Some(b'1'..=b'9') => {
+            let integer = digits(src);
+            if src.get(integer) != Some(&b'.') {
+                return 0;
+            }
+            integer + 1 + digits(after(src, integer + 1))
+        }
+        Some(b'0' | b'.') => {
+            let dot = usize::from(src.first() == Some(&b'0'));
+            let fraction = match src.get(dot) {
+                Some(b'.') => digits(after(src, dot + 1)),
+                _ => 0,
+            };
+            if fraction == 0 {
+                return 0;
+            }
+            dot + 1 + fraction
+        }
+        _ => return 0,
+    };
+    if matches!(src.get(length), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(src.get(length + 1), Some(b'+' | b'-')));
+        let exponent = digits(after(src, length + 1 + sign));
+        if exponent > 0 {
+            length += 1 + sign + exponent;
+        }
+    }
+    length
+}
+
+/// Matches paths such as `<nixpkgs/lib>`.
+fn search_path_length(src: &[u8]) -> usize {
+    if src.first() != Some(&b'<') {
+        return 0;
+    }
+    let mut length = 1 + path_chars(after(src, 1));
+    if length == 1 {
+        return 0;
+    }
+    while src.get(length) == Some(&b'/') {
+        let segment = path_chars(after(src, length + 1));
+        if segment == 0 {
+            return 0;
+        }
+        length += 1 + segment;
+    }
+    if src.get(length) == Some(&b'>') {
+        length + 1
+    } else {
+        0
+    }
+}
+
+/// Matches unquoted URIs such as `https://nixos.org`.
+fn uri_length(src: &[u8]) -> usize {
+    if !src.first().is_some_and(u8::is_ascii_alphabetic) {
+        return 0;
+    }
+    let scheme = 1 + after(src, 1)
+        .iter()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, b'+' | b'-' | b'.'))
+        .count();
+    if src.get(scheme) != Some(&b':') {
+        return 0;
+    }
+    let rest = after(src, scheme + 1)
+        .iter()
+        .take_while(|c| c.is_ascii_alphanumeric() || b"%/?:@&=+$,-_.!~*'".contains(c))
+        .count();
+    if rest == 0 {
+        0
+    } else {
+        scheme + 1 + rest
+    }
+}
+
+/// The index of a token.
+type Tok = usize;
+
+#[derive(Debug)]
+enum Expr {
+    /// An identifier, literal or path.
+    Atom(Tok),
+    Paren {
+        open: Tok,
+        inner: Box<Expr>,
+        close: Tok,
+    },
+    List {
+        open: Tok,
+        elements: Vec<Expr>,
+        close: Tok,
+    },
+    AttrSet {
+        rec: Option<Tok>,
+        open: Tok,
+        bindings: Vec<Binding>,
+        close: Tok,
+    },
+    Let {
+        let_: Tok,
+        bindings: Vec<Binding>,
+        in_: Tok,
+        body: Box<Expr>,
+    },
+    /// `with` and `assert`.
+    Statement {
+        keyword: Tok,
+        value: Box<Expr>,
+        semicolon: Tok,
+        body: Box<Expr>,
+    },
+    If {
+        if_: Tok,
+        condition: Box<Expr>,
+        then: Tok,
+        consequence: Box<Expr>,
+        else_: Tok,
+        alternative: Box<Expr>,
+    },
+    Lambda {
+        parameter: Parameter,
+        colon: Tok,
+        body: Box<Expr>,
+    },
+    Apply {
+        function: Box<Expr>,
+        arguments: Vec<Expr>,
+    },
+    Select {
+        target: Box<Expr>,
+        path: Vec<(Tok, AttrName)>,
+        default: Option<(Tok, Box<Expr>)>,
+    },
+    HasAttr {
+        target: Box<Expr>,
+        question: Tok,
+        path: AttrPath,
+    },
+    Binary {
+        left: Box<Expr>,
+        operator: Tok,
+        right: Box<Expr>,
+    },
+    Unary {
+        operator: Tok,
+        operand: Box<Expr>,
+    },
+}
+
+#[derive(Debug)]
+struct AttrPath {
+    first: AttrName,
+    rest: Vec<(Tok, AttrName)>,
+}
+
+#[derive(Debug)]
+enum AttrName {
+    /// An identifier or a string.
+    Plain(Tok),
+    Dynamic {
+        open: Tok,
+        expression: Box<Expr>,
+        close: Tok,
+    },
+}
+
+#[derive(Debug)]
+enum Binding {
+    Attribute {
+        path: AttrPath,
+        equal: Tok,
+        value: Expr,
+        semicolon: Tok,
+    },
+    Inherit {
+        inherit: Tok,
+        from: Option<(Tok, Expr, Tok)>,
+        names: Vec<Tok>,
+        semicolon: Tok,
+    },
+}
+
+#[derive(Debug)]
+enum Parameter {
+    Name(Tok),
+    Formals {
+        /// A name bound before the formals, as in `args@{ ... }`.
+        before: Option<(Tok, Tok)>,
+        open: Tok,
+        formals: Vec<Formal>,
+        close: Tok,
+        /// A name bound after the formals, as in `{ ... }@args`.
+        after: Option<(Tok, Tok)>,
+    },
+}
+
+/// A named argument, with its default value, or the `...` of a function's
+/// formals.
+#[derive(Debug)]
+struct Formal {
+    name: Tok,
+    default: Option<(Tok, Expr)>,
+    comma: Option<Tok>,
+}
+
+impl Expr {
+    fn first_token(&self) -> Tok {
+        match self {
+            Expr::Atom(token)
+            | Expr::Paren { open: token, .. }
+            | Expr::List { open: token, .. }
+            | Expr::Let { let_: token, .. }
+            | Expr::Statement { keyword: token, .. }
+            | Expr::If { if_: token, .. }
+            | Expr::Unary {
+                operator: token, ..
+            } => *token,
+            Expr::AttrSet { rec, open, .. } => rec.unwrap_or(*open),
+            Expr::Lambda { parameter, .. } => match parameter {
+                Parameter::Name(name) => *name,
+                Parameter::Formals {
+                    before: Some((name, _)),
+                    ..
+                } => *name,
+                Parameter::Formals { open, .. } => *open,
+            },
+            Expr::Apply { function: left, .. }
+            | Expr::Select { target: left, .. }
+            | Expr::HasAttr { target: left, .. }
+            | Expr::Binary { left, .. } => left.first_token(),
+        }
+    }
+}
+
+impl Binding {
+    fn first_token(&self) -> Tok {
+        match self {
+            Binding::Attribute { path, .. } => match &path.first {
+                AttrName::Plain(token) | AttrName::Dynamic { open: token, .. } => *token,
+            },
+            Binding::Inherit { inherit, .. } => *inherit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Associativity {
+    Left,
+    Right,
+    None,
+}
+
+const IMPLICATION: u8 = 0;
+const NOT: u8 = 6;
+const HAS_ATTR: u8 = 10;
+const NEGATION: u8 = 11;
+
+/// The precedence and associativity of a binary operator, from lowest to
+/// highest.
+fn binary_operator(operator: &str) -> Option<(u8, Associativity)> {
+    match operator {
+        "->" => Some((IMPLICATION, Associativity::Right)),
+        "||" => Some((1, Associativity::Left)),
+        "&&" => Some((2, Associativity::Left)),
+        "==" | "!=" => Some((3, Associativity::None)),
+        "<" | "<=" | ">" | ">=" => Some((4, Associativity::None)),
+        "//" => Some((5, Associativity::Right)),
+        "+" | "-" => Some((7, Associativity::Left)),
+        "*" | "/" => Some((8, Associativity::Left)),
+        "++" => Some((9, Associativity::Right)),
+        _ => None,
+    }
+}
+
+struct Parser<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    position: usize,
+}
+
+impl<'t, 'a> Parser<'t, 'a> {
+    fn file(&mut self) -> Result<Expr, Error> {
+        let expression = self.expression()?;
+        let _ = self.expect(Kind::EndOfFile, "the end of the file")?;
+        Ok(expression)
+    }
+
+    fn peek(&self) -> &'t Token<'a> {
+        self.peek_at(0)
+    }
+
+    /// The token at the given offset from the current one. The tokens always
+    /// end with the end of the file, which is returned for any position past
+    /// it.
+    fn peek_at(&self, offset: usize) -> &'t Token<'a> {
+        self.tokens
+            .get(self.position + offset)
+            .or(self.tokens.last())
+            .expect("The tokens end with the end of the file")
+    }
+
+    fn advance(&mut self) -> Tok {
+        let index = self.position;
+        if self.peek().kind != Kind::EndOfFile {
+            self.position += 1;
+        }
+        index
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        let token = self.peek();
+        let found = if token.kind == Kind::EndOfFile {
+            "the end of the file".into()
+        } else {
+            format!("`{}`", token.text)
+        };
+        syntax_error(
+            format!("Expected {expected}, found {found}"),
+            token.start,
+            token.start + token.text.len().max(1),
+        )
+    }
+
+    fn expect(&mut self, kind: Kind, expected: &str) -> Result<Tok, Error> {
+        if self.peek().kind == kind {
+            Ok(self.advance())
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, Error> {
+        match self.peek().kind {
+            Kind::Let if self.peek_at(1).kind == Kind::LeftBrace => {
+                let start = self.peek().start;
+                Err(syntax_error(
+                    "The legacy `let { ... }` syntax is not supported",
+                    start,
+                    start + "let".len(),
+                ))
+            }
+            Kind::Let => {
+                let let_ = self.advance();
+                let bindings = self.bindings(Kind::In)?;
+                let in_ = self.expect(Kind::In, "`in`")?;
+                let body = Box::new(self.expression()?);
+                Ok(Expr::Let {
+                    let_,
+                    bindings,
+                    in_,
+                    body,
+                })
+            }
+            Kind::With | Kind::Assert => {
+                let keyword = self.advance();
+                let value = Box::new(self.expression()?);
+                let semicolon = self.expect(Kind::Semicolon, "`;`")?;
+                let body = Box::new(self.expression()?);
+                Ok(Expr::Statement {
+                    keyword,
+                    value,
+                    semicolon,
+                    body,
+                })
+            }
+            Kind::If => {
+                let if_ = self.advance();
+                let condition = Box::new(self.expression()?);
+                let then = self.expect(Kind::Then, "`then`")?;
+                let consequence = Box::new(self.expression()?);
+                let else_ = self.expect(Kind::Else, "`else`")?;
+                let alternative = Box::new(self.expression()?);
+                Ok(Expr::If {
+                    if_,
+                    condition,
+                    then,
+                    consequence,
+                    else_,
+                    alternative,
+                })
+            }
+            Kind::Identifier if matches!(self.peek_at(1).kind, Kind::Colon | Kind::At) => {
+                self.lambda()
+            }
+            Kind::LeftBrace if self.at_formals() => self.lambda(),
+            _ => self.operators(IMPLICATION),
+        }
+    }
+
+    /// Whether the `{` at the current position starts a function's formals
+    /// rather than an attribute set.
+    fn at_formals(&self) -> bool {
+        let ends_formals = |offset| matches!(self.peek_at(offset).kind, Kind::Colon | Kind::At);
+        match self.peek_at(1).kind {
+            Kind::RightBrace => ends_formals(2),
+            Kind::Ellipsis => true,
+            Kind::Identifier => match self.peek_at(2).kind {
+                Kind::Comma | Kind::Question => true,
+                Kind::RightBrace => ends_formals(3),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn lambda(&mut self) -> Result<Expr, Error> {
+        let parameter = if self.peek().kind == Kind::Identifier {
+            let name = self.advance();
+            if self.peek().kind == Kind::At {
+                let at = self.advance();
+                self.formals(Some((name, at)))?
+            } else {
+                Parameter::Name(name)
+            }
+        } else {
+            self.formals(None)?
+        };
+        let colon = self.expect(Kind::Colon, "`:`")?;
+        let body = Box::new(self.expression()?);
+        Ok(Expr::Lambda {
+            parameter,
+            colon,
+            body,
+        })
+    }
+
+    fn formals(&mut self, before: Option<(Tok, Tok)>) -> Result<Parameter, Error> {
+        let open = self.expect(Kind::LeftBrace, "`{`")?;
+        let mut formals = vec![];
+        loop {
+            match self.peek().kind {
+                Kind::RightBrace => break,
+                Kind::Ellipsis => {
+                    let name = self.advance();
+                    formals.push(Formal {
+                        name,
+                        default: None,
+                        comma: None,
+                    });
+                    break;
+                }
+                _ => (),
+            }
+            let name = self.expect(Kind::Identifier, "an argument name")?;
+            let default = if self.peek().kind == Kind::Question {
+                let question = self.advance();
+                Some((question, self.expression()?))
+            } else {
+                None
+            };
+            let comma = (self.peek().kind == Kind::Comma).then(|| self.advance());
+            let last = comma.is_none();
+            formals.push(Formal {
+                name,
+                default,
+                comma,
+            });
+            if last {
+                break;
+            }
+        }
+        let close = self.expect(Kind::RightBrace, "`}`")?;
+        let after = if before.is_none() && self.peek().kind == Kind::At {
+            let at = self.advance();
+            Some((at, self.expect(Kind::Identifier, "a name")?))
+        } else {
+            None
+        };
+        Ok(Parameter::Formals {
+            before,
+            open,
+            formals,
+            close,
+            after,
+        })
+    }
+
+    fn operators(&mut self, minimum: u8) -> Result<Expr, Error> {
+        let mut left = self.prefix()?;
+        loop {
+            let token = self.peek();
+            if token.kind == Kind::Question && minimum <= HAS_ATTR {
+                let question = self.advance();
+                let path = self.attr_path()?;
+                left = Expr::HasAttr {
+                    target: Box::new(left),
+                    question,
+                    path,
+                };
+                continue;
+            }
+            let Some((precedence, associativity)) = (token.kind == Kind::Operator)
+                .then(|| binary_operator(token.text))
+                .flatten()
+            else {
+                return Ok(left);
+            };
+            if precedence < minimum {
+                return Ok(left);
+            }
+            let operator = self.advance();
+            let right_minimum = match associativity {
+                Associativity::Right => precedence,
+                Associativity::Left | Associativity::None => precedence + 1,
+            };
+            let right = self.operators(right_minimum)?;
+            left = Expr::Binary {
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+            };
+        }
+    }
+
+    fn prefix(&mut self) -> Result<Expr, Error> {
+        let token = self.peek();
+        let operand_minimum = match (token.kind, token.text) {
+            (Kind::Operator, "!") => NOT,
+            (Kind::Operator, "-") => NEGATION,
+            _ => return self.application(),
+        };
+        let operator = self.advance();
+        let operand = Box::new(self.operators(operand_minimum)?);
+        Ok(Expr::Unary { operator, operand })
+    }
+
+    fn application(&mut self) -> Result<Expr, Error> {
+        let function = self.select()?;
+        let mut arguments = vec![];
+        while matches!(
+            self.peek().kind,
+            Kind::Identifier
+                | Kind::Int
+                | Kind::Float
+                | Kind::Path
+                | Kind::SearchPath
+                | Kind::Uri
+                | Kind::String
+                | Kind::LeftParen
+                | Kind::LeftSquare
+                | Kind::LeftBrace
+                | Kind::Rec
+        ) {
+            arguments.push(self.select()?);
+        }
+        if arguments.is_empty() {
+            Ok(function)
+        } else {
+            Ok(Expr::Apply {
+                function: Box::new(function),
+                arguments,
+            })
+        }
+    }
+
+    fn select(&mut self) -> Result<Expr, Error> {
+        let target = self.simple()?;
+        if self.peek().kind != Kind::Dot {
+            return Ok(target);
+        }
+        let mut path = vec![];
+        while self.peek().kind == Kind::Dot {
+            let dot = self.advance();
+            path.push((dot, self.attr_name()?));
+        }
+        let token = self.peek();
+        let default = if token.kind == Kind::Identifier && token.text == "or" {
+            let or = self.advance();
+            Some((or, Box::new(self.select()?)))
+        } else {
+            None
+        };
+        Ok(Expr::Select {
+            target: Box::new(target),
+            path,
+            default,
+        })
+    }
+
+    fn simple(&mut self) -> Result<Expr, Error> {
+        match self.peek().kind {
+            Kind::Identifier
+            | Kind::Int
+            | Kind::Float
+            | Kind::Path
+            | Kind::SearchPath
+            | Kind::Uri
+            | Kind::String => Ok(Expr::Atom(self.advance())),
+            Kind::LeftParen => {
+                let open = self.advance();
+                let inner = Box::new(self.expression()?);
+                let close = self.expect(Kind::RightParen, "`)`")?;
+                Ok(Expr::Paren { open, inner, close })
+            }
+            Kind::LeftSquare => {
+                let open = self.advance();
+                let mut elements = vec![];
+                while !matches!(self.peek().kind, Kind::RightSquare | Kind::EndOfFile) {
+                    elements.push(self.select()?);
+                }
+                let close = self.expect(Kind::RightSquare, "`]`")?;
+                Ok(Expr::List {
+                    open,
+                    elements,
+                    close,
+                })
+            }
+            Kind::Rec | Kind::LeftBrace => {
+                let rec = (self.peek().kind == Kind::Rec).then(|| self.advance());
+                let open = self.expect(Kind::LeftBrace, "`{`")?;
+                let bindings = self.bindings(Kind::RightBrace)?;
+                let close = self.expect(Kind::RightBrace, "`}`")?;
+                Ok(Expr::AttrSet {
+                    rec,
+                    open,
+                    bindings,
+                    close,
+                })
+            }
+            _ => Err(self.unexpected("an expression")),
+        }
+    }
+
+    fn bindings(&mut self, end: Kind) -> Result<Vec<Binding>, Error> {
+        let mut bindings = vec![];
+        while !matches!(self.peek().kind, Kind::EndOfFile) && self.peek().kind != end {
+            bindings.push(self.binding()?);
+        }
+        Ok(bindings)
+    }
+
+    fn binding(&mut self) -> Result<Binding, Error> {
+        if self.peek().kind == Kind::Inherit {
+            let inherit = self.advance();
+            let from = if self.peek().kind == Kind::LeftParen {
+                let open = self.advance();
+                let expression = self.expression()?;
+                let close = self.expect(Kind::RightParen, "`)`")?;
+                Some((open, expression, close))
+            } else {
+                None
+            };
+            let mut names = vec![];
+            while matches!(self.peek().kind, Kind::Identifier | Kind::String) {
+                names.push(self.advance());
+            }
+            let semicolon = self.expect(Kind::Semicolon, "`;`")?;
+            return Ok(Binding::Inherit {
+                inherit,
+                from,
+                names,
+                semicolon,
+            });
+        }
+
+        let path = self.attr_path()?;
+        let equal = self.expect(Kind::Equal, "`=`")?;
+        let value = self.expression()?;
+        let semicolon = self.expect(Kind::Semicolon, "`;`")?;
+        Ok(Binding::Attribute {
+            path,
+            equal,
+            value,
+            semicolon,
+        })
+    }
+
+    fn attr_path(&mut self) -> Result<AttrPath, Error> {
+        let first = self.attr_name()?;
+        let mut rest = vec![];
+        while self.peek().kind == Kind::Dot {
+            let dot = self.advance();
+            rest.push((dot, self.attr_name()?));
+        }
+        Ok(AttrPath { first, rest })
+    }
+
+    fn attr_name(&mut self) -> Result<AttrName, Error> {
+        match self.peek().kind {
+            Kind::Identifier | Kind::String => Ok(AttrName::Plain(self.advance())),
+            Kind::DollarBrace => {
+                let open = self.advance();
+                let expression = Box::new(self.expression()?);
+                let close = self.expect(Kind::RightBrace, "`}`")?;
+                Ok(AttrName::Dynamic {
+                    open,
+                    expression,
+                    close,
+                })
+            }
+            _ => Err(self.unexpected("an attribute name")),
+        }
+    }
+}
+
+/// Lays out the parsed expression, printing each token through
+/// [`Printer::token`] so that the comments before it are printed too.
+struct Printer<'a> {
+    tokens: Vec<Token<'a>>,
+}
+
+/// The elements of a list, attribute set, `let` or function formals, with the
+/// comments around them.
+struct Elements<'a> {
+    /// The comments on the same line as the opening delimiter.
+    opening: Document<'a>,
+    elements: Document<'a>,
+    is_empty: bool,
+    /// Whether the elements must be broken on separate lines, because there
+    /// are comments or empty lines between them.
+    force_break: bool,
+}
+
+impl<'a> Printer<'a> {
+    /// The token at the given index, which always comes from the parser.
+    fn at(&self, index: Tok) -> &Token<'a> {
+        self.tokens.get(index).expect("Token index out of bounds")
+    }
+
+    fn at_mut(&mut self, index: Tok) -> &mut Token<'a> {
+        self.tokens
+            .get_mut(index)
+            .expect("Token index out of bounds")
+    }
+
+    fn file(&mut self, expression: &Expr) -> Document<'a> {
+        let expression = self.expression(expression);
+        let comments = self
+            .tokens
+            .last_mut()
+            .map(|end| std::mem::take(&mut end.comments))
+            .unwrap_or_default();
+        let mut comments_doc = vec![];
+        for comment in comments {
+            if comment.newline_before {
+                comments_doc.push(if comment.blank_line_before {
+                    lines(2)
+                } else {
+                    line()
+                });
+            } else {
+                comments_doc.push(" ".to_doc());
+            }
+            comments_doc.push(comment.text.to_doc());
+        }
+        docvec![expression, comments_doc]
+    }
+
+    /// Prints a token, preceded by the comments before it. Comments which
+    /// are placed more carefully by the caller have already been taken out.
+    fn token(&mut self, index: Tok) -> Document<'a> {
+        let token = self.at_mut(index);
+        let comments = std::mem::take(&mut token.comments);
+        let text = text(token.text);
+        if comments.is_empty() {
+            return text;
+        }
+        let next = (token.newline_before, token.blank_line_before);
+        docvec![comments_before(&comments, next), text]
+    }
+
+    /// A break before an expression, or an empty line if there is one before
+    /// it in the source.
+    fn break_before(&self, expression: &Expr) -> Document<'a> {
+        let token = self.at(expression.first_token());
+        let blank_line_before = token
+            .comments
+            .first()
+            .map_or(token.blank_line_before, |comment| comment.blank_line_before);
+        if blank_line_before {
+            lines(2)
+        } else {
+            break_("", " ")
+        }
+    }
+
+    fn take_comments(&mut self, index: Tok) -> Vec<Comment<'a>> {
+        std::mem::take(&mut self.at_mut(index).comments)
+    }
+
+    /// Moves the comments before a token to the token after it, for tokens
+    /// such as commas whose comments belong with the next element.
+    fn move_comments(&mut self, from: Tok, to: Tok) {
+        let mut comments = self.take_comments(from);
+        let to = self.at_mut(to);
+        comments.append(&mut to.comments);
+        to.comments = comments;
+    }
+
+    fn elements<T>(
+        &mut self,
+        items: &[T],
+        first_token: impl Fn(&T) -> Tok,
+        close: Tok,
+        mut element: impl FnMut(&mut Self, &T) -> Document<'a>,
+    ) -> Elements<'a> {
+        let mut opening = nil();
+        let mut elements = vec![];
+        let mut force_break = false;
+
+        let starts = items
+            .iter()
+            .map(|item| (first_token(item), Some(item)))
+            .chain(std::iter::once((close, None)));
+        for (index, (start, item)) in starts.enumerate() {
+            let comments = self.take_comments(start);
+            force_break |= !comments.is_empty();
+            let trailing = comments
+                .iter()
+                .take_while(|comment| !comment.newline_before)
+                .count();
+
+            // Comments on the same line as the end of the previous element
+            // stay there.
+            let (trailing, leading) = comments.split_at(trailing);
+            let trailing_doc = concat(trailing.iter().map(|comment| docvec![" ", comment.text]));
+            if index == 0 {
+                opening = trailing_doc;
+            } else {
+                elements.push(trailing_doc);
+            }
+
+            let token = self.at(start);
+            let blank_line_before = leading
+                .first()
+                .map_or(token.blank_line_before, |comment| comment.blank_line_before);
+            if index > 0 && (item.is_some() || !leading.is_empty()) {
+                elements.push(if blank_line_before {
+                    force_break = true;
+                    lines(2)
+                } else {
+                    break_("", " ")
+                });
+            }
+
+            if let Some(item) = item {
+                if !leading.is_empty() {
+                    let next = (token.newline_before, token.blank_line_before);
+                    elements.push(comments_before(leading, next));
+                }
+                // A hugged value at the end of an element only fits if the
+                // element is on its own line, so it doesn't count when
+                // checking whether all the elements fit on one line.
+                let element = element(self, item);
+                force_break |= is_force_broken(&element);
+                elements.push(element.next_break_fits(NextBreakFitsMode::Disabled));
+            } else if !leading.is_empty() {
+                let comments = leading.iter().enumerate().map(|(index, comment)| {
+                    let separator = match index {
+                        0 => nil(),
+                        _ if comment.blank_line_before => lines(2),
+                        _ => line(),
+                    };
+                    docvec![separator, comment.text]
+                });
+                elements.push(concat(comments));
+            }
+        }
+
+        Elements {
+            opening,
+            is_empty: elements.is_empty(),
+            elements: concat(elements),
+            force_break,
+        }
+    }
+
+    /// Lays out elements between delimiters, all on one line if they fit or
+    /// each on its own line otherwise:
+    ///
+    /// ```nix
+    /// { a = 1; b = 2; }
+    /// ```
+    fn delimited(
+        &mut self,
+        open: Document<'a>,
+        elements: Elements<'a>,
+        close: Tok,
+    ) -> Document<'a> {
+        let close = self.token(close);
+        if elements.is_empty && elements.force_break {
+            return docvec![open, elements.opening, line(), close].force_break();
+        }
+        if elements.is_empty {
+            return docvec![open, " ", close];
+        }
+        let inner = docvec![
+            docvec![open, elements.opening, break_("", " "), elements.elements].nest(INDENT),
+            break_("", " ")
+        ];
+        let inner = if elements.force_break {
+            inner.force_break()
+        } else {
+            inner
+        };
+        docvec![inner.group(), close]
+    }
+
+    /// Prints an expression. The comments before it are printed outside of
+    /// the groups it is made of, so they are aligned with its first line.
+    fn expression(&mut self, expression: &Expr) -> Document<'a> {
+        let first = expression.first_token();
+        let comments = self.take_comments(first);
+        let document = self.expression_without_comments(expression);
+        if comments.is_empty() {
+            return document;
+        }
+        let token = self.at(first);
+        let next = (token.newline_before, token.blank_line_before);
+        docvec![comments_before(&comments, next), document]
+    }
+
+    fn expression_without_comments(&mut self, expression: &Expr) -> Document<'a> {
+        match expression {
+            Expr::Atom(token) => self.token(*token),
+
+            Expr::Paren { open, inner, close } => {
+                docvec![
+                    self.token(*open),
+                    self.expression(inner),
+                    self.token(*close)
+                ]
+            }
+
+            Expr::List {
+                open,
+                elements,
+                close,
+            } => {
+                let open = self.token(*open);
+                let elements = self.elements(elements, Expr::first_token, *close, Self::expression);
+                self.delimited(open, elements, *close)
+            }
+
+            Expr::AttrSet {
+                rec,
+                open,
+                bindings,
+                close,
+            } => {
+                let rec = match rec {
+                    Some(rec) => docvec![self.token(*rec), " "],
+                    None => nil(),
+                };
+                let open = docvec![rec, self.token(*open)];
+                let elements = self.elements(bindings, Binding::first_token, *close, Self::binding);
+                self.delimited(open, elements, *close)
+            }
+
+            Expr::Let {
+                let_,
+                bindings,
+                in_,
+                body,
+            } => {
+                let let_ = self.token(*let_);
+                let elements = self.elements(bindings, Binding::first_token, *in_, Self::binding);
+                let bindings = if elements.is_empty {
+                    elements.opening
+                } else {
+                    docvec![elements.opening, break_("", " "), elements.elements].nest(INDENT)
+                };
+                let let_in = docvec![
+                    let_,
+                    bindings,
+                    break_("", " "),
+                    self.token(*in_),
+                    break_("", " "),
+                    self.expression(body)
+                ];
+                if elements.force_break {
+                    let_in.force_break().group()
+                } else {
+                    let_in.group()
+                }
+            }
+
+            Expr::Statement {
+                keyword,
+                value,
+                semicolon,
+                body,
+            } => docvec![
+                self.token(*keyword),
+                " ",
+                self.expression(value),
+                self.token(*semicolon),
+                self.break_before(body),
+                self.expression(body)
+            ]
+            .group(),
+
+            Expr::If { .. } => self.if_(expression),
+
+            Expr::Lambda { .. } => self.lambda(expression),
+
+            Expr::Apply {
+                function,
+                arguments,
+            } => self.application(function, arguments),
+
+            Expr::Select {
+                target,
+                path,
+                default,
+            } => {
+                let target = self.expression(target);
+                let path = concat(
+                    path.iter()
+                        .map(|(dot, name)| docvec![self.token(*dot), self.attr_name(name)])
+                        .collect::<Vec<_>>(),
+                );
+                let default = match default {
+                    Some((or, default)) => {
+                        docvec![" ", self.token(*or), " ", self.expression(default)]
+                    }
+                    None => nil(),
+                };
+                docvec![target, path, default]
+            }
+
+            Expr::HasAttr {
+                target,
+                question,
+                path,
+            } => docvec![
+                self.expression(target),
+                " ",
+                self.token(*question),
+                " ",
+                self.attr_path(path)
+            ],
+
+            Expr::Binary { operator, .. } => {
+                let precedence = binary_operator(self.at(*operator).text);
+                let mut operands = vec![];
+                let mut operators = vec![];
+                flatten_binary(
+                    &self.tokens,
+                    expression,
+                    precedence,
+                    &mut operands,
+                    &mut operators,
+                );
+
+                let mut operands = operands.into_iter();
+                let first = match operands.next() {
+                    Some(first) => self.expression(first),
+                    None => nil(),
+                };
+                let rest = operators
+                    .into_iter()
+                    .zip(operands)
+                    .map(|(operator, operand)| {
+                        docvec![
+                            break_("", " "),
+                            self.token(operator),
+                            " ",
+                            self.expression(operand)
+                        ]
+                    })
+                    .collect::<Vec<_>>();
+                docvec![first, rest].nest_if_broken(INDENT).group()
+            }
+
+            Expr::Unary { operator, operand } => {
+                docvec![self.token(*operator), self.expression(operand)]
+            }
+        }
+    }
+
+    /// Generates `if` expressions, keeping chained `else if`s at the same
+    /// level:
+    ///
+    /// ```nix
+    /// if a then b
+    /// else if c then d
+    /// else e
+    /// ```
+    fn if_(&mut self, mut expression: &Expr) -> Document<'a> {
+        let mut docs = vec![];
+        while let Expr::If {
+            if_,
+            condition,
+            then,
+            consequence,
+            else_,
+            alternative,
+        } = expression
+        {
+            docs.push(self.token(*if_));
+            docs.push(
+                docvec![
+                    docvec![break_("", " "), self.expression(condition)].nest(INDENT),
+                    break_("", " "),
+                    self.token(*then),
+                ]
+                .group(),
+            );
+            docs.push(
+                docvec![break_("", " "), self.expression(consequence)]
+                    .nest_if_broken(INDENT)
+                    .group(),
+            );
+            docs.push(break_("", " "));
+            docs.push(self.token(*else_));
+            if matches!(alternative.as_ref(), Expr::If { .. }) {
+                docs.push(" ".to_doc());
+            }
+            expression = alternative;
+        }
+        docs.push(
+            docvec![break_("", " "), self.expression(expression)]
+                .nest_if_broken(INDENT)
+                .group(),
+        );
+        concat(docs).group()
+    }
+
+    /// Generates a function, with the parameters of curried functions on the
+    /// same line:
+    ///
+    /// ```nix
+    /// a: b: body
+    /// ```
+    fn lambda(&mut self, mut expression: &Expr) -> Document<'a> {
+        let mut parameters = vec![];
+        while let Expr::Lambda {
+            parameter,
+            colon,
+            body,
+        } = expression
+        {
+            let parameter = self.parameter(parameter);
+            parameters.push(docvec![parameter, self.token(*colon)]);
+            expression = body;
+        }
+        let separator = self.break_before(expression);
+        let body = self.expression(expression);
+        join(parameters, break_("", " "))
+            .group()
+            .append(docvec![separator, body].group())
+    }
+
+    fn parameter(&mut self, parameter: &Parameter) -> Document<'a> {
+        match parameter {
+            Parameter::Name(name) => self.token(*name),
+            Parameter::Formals {
+                before,
+                open,
+                formals,
+                close,
+                after,
+            } => self.formals(*before, *open, formals, *close, *after),
+        }
+    }
+
+    fn formals(
+        &mut self,
+        before: Option<(Tok, Tok)>,
+        open: Tok,
+        formals: &[Formal],
+        close: Tok,
+        after: Option<(Tok, Tok)>,
+    ) -> Document<'a> {
+        let before = match before {
+            Some((name, at)) => docvec![self.token(name), self.token(at)],
+            None => nil(),
+        };
+        let open = docvec![before, self.token(open)];
+
+        // The comments before a comma are kept with the next argument.
+        for (index, formal) in formals.iter().enumerate() {
+            if let Some(comma) = formal.comma {
+                let next = formals.get(index + 1).map_or(close, |formal| formal.name);
+                self.move_comments(comma, next);
+            }
+        }
+
+        let elements = self.elements(
+            formals,
+            |formal| formal.name,
+            close,
+            |printer, formal| {
+                let default = match &formal.default {
+                    Some((question, default)) => {
+                        docvec![
+                            " ",
+                            printer.token(*question),
+                            " ",
+                            printer.expression(default)
+                        ]
+                    }
+                    None => nil(),
+                };
+                let comma = match formal.comma {
+                    Some(comma) => printer.token(comma),
+                    None => nil(),
+                };
+                docvec![printer.token(formal.name), default, comma]
+            },
+        );
+        let formals = self.delimited(open, elements, close);
+
+        match after {
+            Some((at, name)) => docvec![formals, self.token(at), self.token(name)],
+            None => formals,
+        }
+    }
+
+    /// Generates a function call, like [`super::syntax::fn_call`], except that
+    /// a last argument which is an attribute set or list starts on the same
+    /// line as the function when the arguments before it fit:
+    ///
+    /// ```nix
+    /// f a {
+    ///   b = 1;
+    /// }
+    /// ```
+    fn application(&mut self, function: &Expr, arguments: &[Expr]) -> Document<'a> {
+        let function = self.expression(function);
+        let hugs_last = hugs(arguments.last());
+        let count = arguments.len();
+        let arguments = arguments
+            .iter()
+            .enumerate()
+            .map(|(index, argument)| {
+                let argument = self.expression(argument);
+                let argument = if hugs_last && index == count - 1 {
+                    hug(argument)
+                } else {
+                    argument
+                };
+                break_("", " ").append(argument)
+            })
+            .collect::<Vec<_>>();
+        docvec![function, arguments].nest_if_broken(INDENT).group()
+    }
+
+    /// Generates an attribute, like [`super::syntax::assignment_line`], except
+    /// that attribute sets and lists start on the same line as the name.
+    fn binding(&mut self, binding: &Binding) -> Document<'a> {
+        match binding {
+            Binding::Attribute {
+                path,
+                equal,
+                value,
+                semicolon,
+            } => {
+                let name = self.attr_path(path);
+                let equal = self.token(*equal);
+                let hugs_value = match value {
+                    Expr::Apply { arguments, .. } => hugs(arguments.last()),
+                    _ => hugs(Some(value)),
+                };
+                let value = match value {
+                    Expr::Apply { .. } => self.expression(value),
+                    _ if hugs_value => hug(self.expression(value)),
+                    _ => self.expression(value),
+                };
+                let semicolon = self.token(*semicolon);
+                docvec![
+                    name,
+                    " ",
+                    equal,
+                    docvec![break_("", " "), value, semicolon]
+                        .nest_if_broken(INDENT)
+                        .group()
+                ]
+            }
+
+            Binding::Inherit {
+                inherit,
+                from,
+                names,
+                semicolon,
+            } => {
+                let inherit = self.token(*inherit);
+                let from = match from {
+                    Some((open, from, close)) => docvec![
+                        break_("", " "),
+                        self.token(*open),
+                        self.expression(from),
+                        self.token(*close)
+                    ],
+                    None => nil(),
+                };
+                let names = names
+                    .iter()
+                    .map(|name| docvec![break_("", " "), self.token(*name)])
+                    .collect::<Vec<_>>();
+                docvec![inherit, from, names, self.token(*semicolon)]
+                    .nest(INDENT)
+                    .group()
+            }
+        }
+    }
+
+    fn attr_path(&mut self, path: &AttrPath) -> Document<'a> {
+        let first = self.attr_name(&path.first);
+        let rest = path
+            .rest
+            .iter()
+            .map(|(dot, name)| docvec![self.token(*dot), self.attr_name(name)])
+            .collect::<Vec<_>>();
+        docvec![first, rest]
+    }
+
+    fn attr_name(&mut self, name: &AttrName) -> Document<'a> {
+        match name {
+            AttrName::Plain(token) => self.token(*token),
+            AttrName::Dynamic {
+                open,
+                expression,
+                close,
+            } => docvec![
+                self.token(*open),
+                self.expression(expression),
+                self.token(*close)
+            ],
+        }
+    }
+}
+
+/// Whether the expression is an attribute set or list, which can start on
+/// the same line as what comes before it.
+fn hugs(expression: Option<&Expr>) -> bool {
+    matches!(expression, Some(Expr::AttrSet { .. } | Expr::List { .. }))
+}
+
+/// Lets an attribute set or list start on the line before it, as long as its
+/// opening delimiter fits there.
+fn hug(document: Document<'_>) -> Document<'_> {
+    document.next_break_fits(NextBreakFitsMode::Enabled)
+}
+
+/// Whether the document has comments or empty lines, which force the
+/// elements containing it to be broken too.
+fn is_force_broken(document: &Document<'_>) -> bool {
+    match document {
+        Document::ForceBroken(_) => true,
+        Document::Vec(documents) => documents.iter().any(is_force_broken),
+        Document::Nest(_, _, _, document)
+        | Document::Group(document)
+        | Document::NextBreakFits(document, _) => is_force_broken(document),
+        Document::Line(_)
+        | Document::Break { .. }
+        | Document::Str { .. }
+        | Document::EcoString { .. } => false,
+    }
+}
+
+/// Text which might span several lines, such as an indented string. The
+/// lines after the first one are printed exactly as they were written,
+/// without being indented.
+fn text(text: &str) -> Document<'_> {
+    if !text.contains('\n') {
+        return text.to_doc();
+    }
+    let mut lines = text.split('\n');
+    let first = lines.next().unwrap_or_default();
+    docvec![first, concat(lines.map(|text| docvec![line(), text]))].set_nesting(0)
+}
+
+/// Prints comments, each on its own line unless they are block comments on
+/// the same line as what follows them. `next` is whether there is a newline,
+/// and an empty line, between the last comment and what follows it.
+fn comments_before<'a>(comments: &[Comment<'a>], next: (bool, bool)) -> Document<'a> {
+    let mut docs = vec![];
+    let mut broken = false;
+    for (index, comment) in comments.iter().enumerate() {
+        let (newline_after, blank_line_after) = comments
+            .get(index + 1)
+            .map_or(next, |next| (next.newline_before, next.blank_line_before));
+        docs.push(text(comment.text));
+        docs.push(if blank_line_after {
+            broken = true;
+            lines(2)
+        } else if newline_after || comment.is_line_comment() {
+            broken = true;
+            line()
+        } else {
+            " ".to_doc()
+        });
+    }
+    if broken {
+        concat(docs).force_break()
+    } else {
+        concat(docs)
+    }
+}
+
+/// Collects the operands of a chain of binary operators with the same
+/// precedence, such as `a + b - c`, so they can be laid out together.
+fn flatten_binary<'e>(
+    tokens: &[Token<'_>],
+    expression: &'e Expr,
+    precedence: Option<(u8, Associativity)>,
+    operands: &mut Vec<&'e Expr>,
+    operators: &mut Vec<Tok>,
+) {
+    match expression {
+        Expr::Binary {
+            left,
+            operator,
+            right,
+        } if tokens
+            .get(*operator)
+            .map(|operator| binary_operator(operator.text))
+            == Some(precedence) =>
+        {
+            flatten_binary(tokens, left, precedence, operands, operators);
+            operators.push(*operator);
+            flatten_binary(tokens, right, precedence, operands, operators);
+        }
+        _ => operands.push(expression),
+    }
+}
@@ -0,0 +1,273 @@
+use super::{pretty, Lexer};
+use crate::{ast::SrcSpan, nix::Error};
+use pretty_assertions::assert_eq;
+
+/// The text of the tokens and of the comments in some Nix source, which
+/// formatting must never change.
+fn tokens(src: &str) -> (Vec<&str>, Vec<&str>) {
+    let tokens = Lexer { src }.tokens().expect("Lexing failed");
+    let comments = tokens
+        .iter()
+        .flat_map(|token| token.comments.iter().map(|comment| comment.text))
+        .collect();
+    (tokens.iter().map(|token| token.text).collect(), comments)
+}
+
+macro_rules! assert_nix_format_rewrite {
+    ($src:expr, $expected:expr $(,)?) => {
+        let output = pretty($src, 80).expect("Formatting failed");
+        assert_eq!(output, $expected);
+        assert_eq!(pretty(&output, 80).expect("Formatting failed"), output);
+        assert_eq!(tokens(&output), tokens($src));
+    };
+}
+
+macro_rules! assert_nix_format {
+    ($src:expr $(,)?) => {
+        assert_nix_format_rewrite!($src, $src);
+    };
+}
+
+#[test]
+fn attribute_set_that_fits() {
+    assert_nix_format_rewrite!(
+        "{wibble=1;wobble=\"two\";}",
+        "{ wibble = 1; wobble = \"two\"; }\n"
+    );
+}
+
+#[test]
+fn attribute_set_that_does_not_fit() {
+    assert_nix_format_rewrite!(
+        r#"{ wibble = "a fairly long string value"; wobble = "another fairly long string value"; }"#,
+        r#"{
+  wibble = "a fairly long string value";
+  wobble = "another fairly long string value";
+}
+"#
+    );
+}
+
+#[test]
+fn let_in() {
+    assert_nix_format!(
+        r#"let
+  wibble = "a fairly long string value";
+  wobble = "another fairly long string value";
+in
+wibble + wobble
+"#
+    );
+}
+
+#[test]
+fn inherit_and_attribute_paths() {
+    assert_nix_format!(
+        r#"{ inherit (builtins) map filter; inherit x; ${"dyn"} = 1; a.b."c" = 2; }
+"#
+    );
+}
+
+#[test]
+fn curried_function_with_hugged_list() {
+    assert_nix_format_rewrite!(
+        r#"{ wibble = a: b: c: builtins.concatStringsSep ", " [ (toString a) (toString b) (toString c) ]; }"#,
+        r#"{
+  wibble = a: b: c: builtins.concatStringsSep ", " [
+    (toString a)
+    (toString b)
+    (toString c)
+  ];
+}
+"#
+    );
+}
+
+#[test]
+fn call_with_hugged_attribute_set() {
+    assert_nix_format_rewrite!(
+        "{ wibble = builtins.mapAttrs (name: value: value + 1) { a = 1; b = 2; c = 3; d = 4; e = 5; }; }",
+        r#"{
+  wibble = builtins.mapAttrs (name: value: value + 1) {
+    a = 1;
+    b = 2;
+    c = 3;
+    d = 4;
+    e = 5;
+  };
+}
+"#
+    );
+}
+
+#[test]
+fn formals_that_fit() {
+    assert_nix_format!("{ wibble, wobble ? \"default\", ... }@args: wibble\n");
+}
+
+#[test]
+fn formals_that_do_not_fit() {
+    assert_nix_format_rewrite!(
+        r#"{ wibble, wobble ? "a default value which is quite long", wabble ? "another default value" }: wibble"#,
+        r#"{
+  wibble,
+  wobble ? "a default value which is quite long",
+  wabble ? "another default value"
+}: wibble
+"#
+    );
+}
+
+#[test]
+fn formals_with_leading_commas() {
+    assert_nix_format_rewrite!(
+        "{ wibble\n, wobble\n# dangling\n}: [ ]",
+        r#"{
+  wibble,
+  wobble
+  # dangling
+}: [ ]
+"#
+    );
+}
+
+#[test]
+fn else_if_chain() {
+    assert_nix_format_rewrite!(
+        r#"x: if x == 1 then "one" else if x == 2 then "two" else if x == 3 then "three" else "many numbers""#,
+        r#"x:
+if x == 1 then "one"
+else if x == 2 then "two"
+else if x == 3 then "three"
+else "many numbers"
+"#
+    );
+}
+
+#[test]
+fn with_and_assert() {
+    assert_nix_format!("wibble: with builtins; assert isString wibble; wibble\n");
+}
+
+#[test]
+fn operators() {
+    assert_nix_format_rewrite!(
+        "x: x.wibble.wobble or (throw \"missing\") == 1 && x ? wibble.wobble || !x.wabble -> -x.wubble < 0",
+        r#"x:
+x.wibble.wobble or (throw "missing") == 1 && x ? wibble.wobble || !x.wabble
+  -> -x.wubble < 0
+"#
+    );
+}
+
+#[test]
+fn paths_and_uris() {
+    assert_nix_format_rewrite!(
+        "x: [ ./relative/path ~/home/path <nixpkgs> https://example.com/a?b=c ./path/${x}/file ]",
+        r#"x:
+[
+  ./relative/path
+  ~/home/path
+  <nixpkgs>
+  https://example.com/a?b=c
+  ./path/${x}/file
+]
+"#
+    );
+}
+
+#[test]
+fn indented_strings_are_kept() {
+    assert_nix_format!(
+        r#"{ wibble = x: builtins.throw ''
+  Something went wrong: ${x}
+    with some ''${indentation}
+''; }
+"#
+    );
+}
+
+#[test]
+fn comments() {
+    assert_nix_format_rewrite!(
+        r#"# Header comment
+
+{ wibble, # the wibble
+  wobble ? 1 # the wobble
+}:
+let
+  # leading
+  a = 1; # trailing
+
+
+  b = 2;
+  /* block */
+in
+a + b
+# end of file
+"#,
+        r#"# Header comment
+
+{
+  wibble, # the wibble
+  wobble ? 1 # the wobble
+}:
+let
+  # leading
+  a = 1; # trailing
+
+  b = 2;
+  /* block */
+in
+a + b
+# end of file
+"#
+    );
+}
+
+#[test]
+fn comments_in_empty_containers() {
+    assert_nix_format_rewrite!(
+        "{ a = [\n  # just a comment\n]; b = { }; }",
+        r#"{
+  a = [
+    # just a comment
+  ];
+  b = { };
+}
+"#
+    );
+}
+
+#[test]
+fn unclosed_string() {
+    assert_eq!(
+        pretty("{ a = \"unclosed; }", 80),
+        Err(Error::Syntax {
+            problem: "This string is never closed".into(),
+            location: SrcSpan::new(6, 7),
+        })
+    );
+}
+
+#[test]
+fn missing_semicolon() {
+    assert_eq!(
+        pretty("{ a = 1 }", 80),
+        Err(Error::Syntax {
+            problem: "Expected `;`, found `}`".into(),
+            location: SrcSpan::new(8, 9),
+        })
+    );
+}
+
+#[test]
+fn legacy_let() {
+    assert_eq!(
+        pretty("let { a = 1; body = a; }", 80),
+        Err(Error::Syntax {
+            problem: "The legacy `let { ... }` syntax is not supported".into(),
+            location: SrcSpan::new(0, 3),
+        })
+    );
+}
@@ -0,0 +1,542 @@
+//! Inlining of the functions with the `@inline` attribute, run on a module
+//! before generating its Nix code.
+//!
+//! Calling a function in Nix allocates a thunk for each of its arguments and
+//! a new environment for its body, which adds up in hot paths such as folds
+//! over large attribute sets. Calls to an `@inline` function of the same
+//! module are therefore replaced with a block binding its parameters to the
+//! arguments, followed by its body:
+//!
+//! ```gleam
+//! @inline
+//! fn add(x, y) { x + y }
+//!
+//! pub fn main() { add(1, 2) }
+//! ```
+//!
+//! is generated as `main = { }: let x = 1; y = 2; in x + y;`.
+//!
+//! The generated code refers to variables by name, so a call is left as it
+//! is whenever inlining it could make a name refer to another variable: when
+//! the calling function binds a variable named like a module-level value the
+//! inlined body uses, when an argument uses a module-level value named like
+//! one of the parameters, or when an argument uses a variable named like one
+//! of the parameters bound before it. Calls within an inlined body, and calls
+//! of a function to itself, are never inlined.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use ecow::EcoString;
+use itertools::Itertools;
+use vec1::Vec1;
+
+use crate::ast::{
+    visit::{self, Visit},
+    Arg, AssignName, AssignmentKind, BitArraySegment, CallArg, Clause, Definition,
+    FunctionLiteralKind, Pattern, SrcSpan, Statement, TypeAst, TypedArg, TypedAssignment,
+    TypedExpr, TypedFunction, TypedModule, TypedPattern, TypedStatement, TypedUse,
+};
+use crate::build::Target;
+use crate::type_::{error::VariableOrigin, Type, ValueConstructor, ValueConstructorVariant};
+
+/// Returns a copy of the module where the calls to its `@inline` functions
+/// are replaced with their bodies, or `None` if it has no such function.
+pub fn inline(module: &TypedModule) -> Option<TypedModule> {
+    let functions: HashMap<_, _> = module
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Function(function)
+                if function.inline
+                    && function.external_nix.is_none()
+                    && function.implementations.supports(Target::Nix) =>
+            {
+                Some((function_name(function)?.clone(), function))
+            }
+            _ => None,
+        })
+        .collect();
+    if functions.is_empty() {
+        return None;
+    }
+
+    let definitions = module
+        .definitions
+        .iter()
+        .cloned()
+        .map(|definition| match definition {
+            Definition::Function(function) => {
+                let mut bound = BoundNames::default();
+                bound.visit_typed_function(&function);
+                bound.arguments(&function.arguments);
+                let mut inliner = Inliner {
+                    module: &module.name,
+                    functions: &functions,
+                    caller: function_name(&function).cloned(),
+                    bound: bound.names,
+                };
+                Definition::Function(TypedFunction {
+                    body: inliner.statements(function.body),
+                    ..function
+                })
+            }
+            definition => definition,
+        })
+        .collect();
+
+    Some(TypedModule {
+        definitions,
+        ..module.clone()
+    })
+}
+
+fn function_name(function: &TypedFunction) -> Option<&EcoString> {
+    function.name.as_ref().map(|(_, name)| name)
+}
+
+/// Replaces the calls to `@inline` functions in the body of a function.
+struct Inliner<'a> {
+    module: &'a str,
+    functions: &'a HashMap<EcoString, &'a TypedFunction>,
+    /// The function whose body is being rewritten.
+    caller: Option<EcoString>,
+    /// The names of the variables bound anywhere in that function.
+    bound: HashSet<EcoString>,
+}
+
+impl Inliner<'_> {
+    /// The function called, if the call can be replaced with its body.
+    fn inlinable(&self, fun: &TypedExpr, args: &[CallArg<TypedExpr>]) -> Option<&TypedFunction> {
+        let TypedExpr::Var {
+            constructor:
+                ValueConstructor {
+                    variant: ValueConstructorVariant::ModuleFn { name, module, .. },
+                    ..
+                },
+            ..
+        } = fun
+        else {
+            return None;
+        };
+        if module != self.module || self.caller.as_ref() == Some(name) {
+            return None;
+        }
+        let function = *self.functions.get(name)?;
+
+        // The parameters are bound in the same `let` as the arguments, so
+        // they would shadow the module values named like them.
+        let parameter_names: HashSet<_> = function
+            .arguments
+            .iter()
+            .filter_map(Arg::get_variable_name)
+            .cloned()
+            .collect();
+
+        let mut referenced = ReferencedNames::default();
+        referenced.visit_typed_function(function);
+        if !referenced.module_values.is_disjoint(&self.bound) {
+            return None;
+        }
+
+        let mut parameters = HashSet::new();
+        for (parameter, arg) in function.arguments.iter().zip_eq(args) {
+            let mut referenced = ReferencedNames::default();
+            referenced.visit_typed_expr(&arg.value);
+            if !referenced.locals.is_disjoint(&parameters)
+                || !referenced.module_values.is_disjoint(&parameter_names)
+            {
+                return None;
+            }
+            if let Some(name) = parameter.get_variable_name() {
+                let _ = parameters.insert(name.clone());
+            }
+        }
+
+        Some(function)
+    }
+
+    /// The block of the function's body, preceded by the assignments of its
+    /// parameters. Discarded parameters are left out, as Nix would never
+    /// evaluate their arguments either.
+    fn inlined(
+        location: SrcSpan,
+        function: &TypedFunction,
+        args: Vec<CallArg<TypedExpr>>,
+    ) -> TypedExpr {
+        let assignments = function
+            .arguments
+            .iter()
+            .zip_eq(args)
+            .filter_map(|(parameter, arg)| {
+                let name = parameter.get_variable_name()?;
+                Some(Statement::Assignment(TypedAssignment {
+                    location: arg.location,
+                    value: Box::new(arg.value),
+                    pattern: Pattern::Variable {
+                        location: parameter.location,
+                        name: name.clone(),
+                        type_: parameter.type_.clone(),
+                        origin: VariableOrigin::Generated,
+                    },
+                    kind: AssignmentKind::Let,
+                    annotation: None,
+                }))
+            });
+        let statements = assignments.chain(function.body.iter().cloned()).collect();
+        TypedExpr::Block {
+            location,
+            statements: Vec1::try_from_vec(statements).expect("Function body can't be empty"),
+        }
+    }
+
+    fn statements(&mut self, statements: Vec1<TypedStatement>) -> Vec1<TypedStatement> {
+        let statements = statements
+            .into_iter()
+            .map(|statement| self.statement(statement))
+            .collect_vec();
+        Vec1::try_from_vec(statements).expect("Statements can't be empty")
+    }
+
+    fn statement(&mut self, statement: TypedStatement) -> TypedStatement {
+        match statement {
+            Statement::Expression(expression) => Statement::Expression(self.expression(expression)),
+            Statement::Assignment(assignment) => Statement::Assignment(self.assignment(assignment)),
+            Statement::Use(use_) => Statement::Use(TypedUse {
+                call: self.boxed(use_.call),
+                ..use_
+            }),
+        }
+    }
+
+    fn assignment(&mut self, assignment: TypedAssignment) -> TypedAssignment {
+        TypedAssignment {
+            value: self.boxed(assignment.value),
+            ..assignment
+        }
+    }
+
+    fn boxed(&mut self, expression: Box<TypedExpr>) -> Box<TypedExpr> {
+        Box::new(self.expression(*expression))
+    }
+
+    fn call_args(&mut self, args: Vec<CallArg<TypedExpr>>) -> Vec<CallArg<TypedExpr>> {
+        args.into_iter()
+            .map(|arg| CallArg {
+                value: self.expression(arg.value),
+                ..arg
+            })
+            .collect()
+    }
+
+    fn expression(&mut self, expression: TypedExpr) -> TypedExpr {
+        match expression {
+            TypedExpr::Call {
+                location,
+                type_,
+                fun,
+                args,
+            } => {
+                let args = self.call_args(args);
+                match self.inlinable(&fun, &args) {
+                    Some(function) => Self::inlined(location, function, args),
+                    None => TypedExpr::Call {
+                        location,
+                        type_,
+                        fun: self.boxed(fun),
+                        args,
+                    },
+                }
+            }
+
+            TypedExpr::Block {
+                location,
+                statements,
+            } => TypedExpr::Block {
+                location,
+                statements: self.statements(statements),
+            },
+
+            TypedExpr::Pipeline {
+                location,
+                assignments,
+                finally,
+            } => TypedExpr::Pipeline {
+                location,
+                assignments: assignments
+                    .into_iter()
+                    .map(|assignment| self.assignment(assignment))
+                    .collect(),
+                finally: self.boxed(finally),
+            },
+
+            TypedExpr::Fn {
+                location,
+                type_,
+                kind,
+                args,
+                body,
+                return_annotation,
+            } => TypedExpr::Fn {
+                location,
+                type_,
+                kind,
+                args,
+                body: self.statements(body),
+                return_annotation,
+            },
+
+            TypedExpr::List {
+                location,
+                type_,
+                elements,
+                tail,
+            } => TypedExpr::List {
+                location,
+                type_,
+                elements: elements
+                    .into_iter()
+                    .map(|element| self.expression(element))
+                    .collect(),
+                tail: tail.map(|tail| self.boxed(tail)),
+            },
+
+            TypedExpr::BinOp {
+                location,
+                type_,
+                name,
+                left,
+                right,
+            } => TypedExpr::BinOp {
+                location,
+                type_,
+                name,
+                left: self.boxed(left),
+                right: self.boxed(right),
+            },
+
+            TypedExpr::Case {
+                location,
+                type_,
+                subjects,
+                clauses,
+                compiled_case,
+            } => TypedExpr::Case {
+                location,
+                type_,
+                subjects: subjects
+                    .into_iter()
+                    .map(|subject| self.expression(subject))
+                    .collect(),
+                clauses: clauses
+                    .into_iter()
+                    .map(|clause| Clause {
+                        then: self.expression(clause.then),
+                        ..clause
+                    })
+                    .collect(),
+                compiled_case,
+            },
+
+            TypedExpr::RecordAccess {
+                location,
+                type_,
+                label,
+                index,
+                record,
+            } => TypedExpr::RecordAccess {
+                location,
+                type_,
+                label,
+                index,
+                record: self.boxed(record),
+            },
+
+            TypedExpr::Tuple {
+                location,
+                type_,
+                elems,
+            } => TypedExpr::Tuple {
+                location,
+                type_,
+                elems: elems
+                    .into_iter()
+                    .map(|element| self.expression(element))
+                    .collect(),
+            },
+
+            TypedExpr::TupleIndex {
+                location,
+                type_,
+                index,
+                tuple,
+            } => TypedExpr::TupleIndex {
+                location,
+                type_,
+                index,
+                tuple: self.boxed(tuple),
+            },
+
+            TypedExpr::Todo {
+                location,
+                message,
+                kind,
+                type_,
+            } => TypedExpr::Todo {
+                location,
+                message: message.map(|message| self.boxed(message)),
+                kind,
+                type_,
+            },
+
+            TypedExpr::Panic {
+                location,
+                message,
+                type_,
+            } => TypedExpr::Panic {
+                location,
+                message: message.map(|message| self.boxed(message)),
+                type_,
+            },
+
+            TypedExpr::BitArray {
+                location,
+                type_,
+                segments,
+            } => TypedExpr::BitArray {
+                location,
+                type_,
+                segments: segments
+                    .into_iter()
+                    .map(|segment| BitArraySegment {
+                        value: self.boxed(segment.value),
+                        ..segment
+                    })
+                    .collect(),
+            },
+
+            TypedExpr::RecordUpdate {
+                location,
+                type_,
+                record,
+                constructor,
+                args,
+            } => TypedExpr::RecordUpdate {
+                location,
+                type_,
+                record: self.assignment(record),
+                constructor,
+                args: self.call_args(args),
+            },
+
+            TypedExpr::NegateBool { location, value } => TypedExpr::NegateBool {
+                location,
+                value: self.boxed(value),
+            },
+
+            TypedExpr::NegateInt { location, value } => TypedExpr::NegateInt {
+                location,
+                value: self.boxed(value),
+            },
+
+            expression @ (TypedExpr::Int { .. }
+            | TypedExpr::Float { .. }
+            | TypedExpr::String { .. }
+            | TypedExpr::Var { .. }
+            | TypedExpr::ModuleSelect { .. }
+            | TypedExpr::Invalid { .. }) => expression,
+        }
+    }
+}
+
+/// Collects the names of the variables bound by a function: its arguments
+/// and those of its anonymous functions, and the variables of its patterns.
+#[derive(Debug, Default)]
+struct BoundNames {
+    names: HashSet<EcoString>,
+}
+
+impl BoundNames {
+    fn arguments(&mut self, arguments: &[TypedArg]) {
+        self.names
+            .extend(arguments.iter().filter_map(Arg::get_variable_name).cloned());
+    }
+}
+
+impl<'ast> Visit<'ast> for BoundNames {
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        args: &'ast [TypedArg],
+        body: &'ast [TypedStatement],
+        return_annotation: &'ast Option<TypeAst>,
+    ) {
+        self.arguments(args);
+        visit::visit_typed_expr_fn(self, location, type_, kind, args, body, return_annotation);
+    }
+
+    fn visit_typed_pattern_variable(
+        &mut self,
+        _location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        _type: &'ast Arc<Type>,
+        _origin: &'ast VariableOrigin,
+    ) {
+        let _ = self.names.insert(name.clone());
+    }
+
+    fn visit_typed_pattern_assign(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        pattern: &'ast TypedPattern,
+    ) {
+        let _ = self.names.insert(name.clone());
+        visit::visit_typed_pattern_assign(self, location, name, pattern);
+    }
+
+    fn visit_typed_pattern_string_prefix(
+        &mut self,
+        _location: &'ast SrcSpan,
+        _left_location: &'ast SrcSpan,
+        left_side_assignment: &'ast Option<(EcoString, SrcSpan)>,
+        _right_location: &'ast SrcSpan,
+        _left_side_string: &'ast EcoString,
+        right_side_assignment: &'ast AssignName,
+    ) {
+        if let Some((name, _)) = left_side_assignment {
+            let _ = self.names.insert(name.clone());
+        }
+        if let AssignName::Variable(name) = right_side_assignment {
+            let _ = self.names.insert(name.clone());
+        }
+    }
+}
+
+/// Collects the names used to refer to variables, and to the functions and
+/// constants defined or imported at the top level of the module.
+#[derive(Debug, Default)]
+struct ReferencedNames {
+    locals: HashSet<EcoString>,
+    module_values: HashSet<EcoString>,
+}
+
+impl<'ast> Visit<'ast> for ReferencedNames {
+    fn visit_typed_expr_var(
+        &mut self,
+        _location: &'ast SrcSpan,
+        constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        let names = match constructor.variant {
+            ValueConstructorVariant::LocalVariable { .. } => &mut self.locals,
+            ValueConstructorVariant::ModuleFn { .. }
+            | ValueConstructorVariant::ModuleConstant { .. } => &mut self.module_values,
+            ValueConstructorVariant::LocalConstant { .. }
+            | ValueConstructorVariant::Record { .. } => return,
+        };
+        let _ = names.insert(name.clone());
+    }
+}
@@ -4,6 +4,7 @@ use std::sync::OnceLock;
 
 use crate::analyse::Inferred;
 use crate::ast::{AssignName, ClauseGuard, Pattern, TypedClauseGuard, TypedExpr, TypedPattern};
+use crate::config::ListRepresentation;
 use crate::docvec;
 use crate::nix::{
     expression, maybe_escape_identifier_doc, module_var_name_doc, syntax, Error, Output,
@@ -27,6 +28,7 @@ enum Index<'a> {
     BinaryFromSlice(usize, usize),
     SliceAfter(usize),
     StringPrefixSlice(usize),
+    ListDrop(usize),
 }
 
 /// Compiles clauses with patterns into individual checks.
@@ -132,6 +134,13 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
         self.path.push(Index::BinaryFromSlice(start, end));
     }
 
+    /// For this pattern, access the rest of a native list after its first
+    /// elements.
+    fn push_list_drop(&mut self, count: usize) {
+        self.expression_generator.tracker.list_drop_used = true;
+        self.path.push(Index::ListDrop(count));
+    }
+
     fn push_rest_from(&mut self, i: usize) {
         self.expression_generator.tracker.bit_array_slice_after_used = true;
         self.path.push(Index::SliceAfter(i));
@@ -185,6 +194,9 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
                     }
                     Index::StringPrefixSlice(i) => path
                         .add_component(docvec!("(builtins.substring ", i, " (-1) "), ")".to_doc()),
+                    Index::ListDrop(count) => {
+                        path.add_component(docvec!("(listDrop ", count, " "), ")".to_doc())
+                    }
                 };
         }
 
@@ -239,6 +251,20 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
         }
     }
 
+    /// Compiles a clause guard on its own, with the variables from the clause's
+    /// patterns being replaced by the given values.
+    pub fn bound_guard(
+        &mut self,
+        bindings: impl IntoIterator<Item = (&'a str, Document<'a>)>,
+        guard: &'a TypedClauseGuard,
+    ) -> Output<'a> {
+        for (name, value) in bindings {
+            self.assignments
+                .push(Assignment::reassign_subject(name, nil(), value));
+        }
+        self.guard(guard)
+    }
+
     fn push_guard_check(&mut self, guard: &'a TypedClauseGuard) -> Result<(), Error> {
         let expression = self.guard(guard)?;
         self.checks.push(Check::Guard { expression });
@@ -469,6 +495,24 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
                 self.traverse_pattern(subject, pattern)
             }
 
+            Pattern::List { elements, tail, .. }
+                if self.expression_generator.list_representation == ListRepresentation::Native =>
+            {
+                // The elements of a Nix list are accessed by their index.
+                self.push_list_length_check(subject.clone(), elements.len(), tail.is_some());
+                for (index, pattern) in elements.iter().enumerate() {
+                    self.push_tuple_index(index);
+                    self.traverse_pattern(subject, pattern)?;
+                    self.pop_segment();
+                }
+                if let Some(pattern) = tail {
+                    self.push_list_drop(elements.len());
+                    self.traverse_pattern(subject, pattern)?;
+                    self.pop_segment();
+                }
+                Ok(())
+            }
+
             Pattern::List { elements, tail, .. } => {
                 self.push_list_length_check(subject.clone(), elements.len(), tail.is_some());
                 for pattern in elements {
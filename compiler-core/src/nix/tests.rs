@@ -1,5 +1,5 @@
 use crate::ast::TypedModule;
-use crate::config::PackageConfig;
+use crate::config::{ListRepresentation, PackageConfig};
 use crate::type_::PRELUDE_MODULE_NAME;
 use crate::{
     analyse::TargetSupport,
@@ -19,11 +19,17 @@ mod case;
 mod case_clause_guards;
 mod consts;
 mod custom_types;
+mod escape_analysis;
 mod externals;
 mod functions;
+mod inline;
 mod lists;
 mod modules;
+mod native_lists;
+mod nix_attrs;
+mod nixos_options;
 mod numbers;
+mod package_index;
 mod panic;
 mod prelude;
 mod records;
@@ -86,6 +92,19 @@ macro_rules! assert_nix {
     }};
 }
 
+#[macro_export]
+macro_rules! assert_nix_optimised {
+    ($src:expr $(,)?) => {{
+        let compiled =
+            $crate::nix::tests::compile_nix_optimised($src, vec![], 2).expect("compilation failed");
+        let output = format!(
+            "----- SOURCE CODE\n{}\n\n----- COMPILED NIX\n{}",
+            $src, compiled
+        );
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+}
+
 #[macro_export]
 macro_rules! assert_nix_error {
     ($src:expr $(,)?) => {{
@@ -106,6 +125,10 @@ pub fn compile(src: &str, deps: Vec<(&str, &str, &str)>) -> TypedModule {
         PRELUDE_MODULE_NAME.into(),
         crate::type_::build_prelude(&ids),
     );
+    let _ = modules.insert(
+        crate::type_::NIX_MODULE_NAME.into(),
+        crate::type_::build_nix_module(),
+    );
     let mut direct_dependencies = std::collections::HashMap::from_iter(vec![]);
 
     deps.iter().for_each(|(dep_package, dep_name, dep_src)| {
@@ -161,6 +184,23 @@ pub fn compile(src: &str, deps: Vec<(&str, &str, &str)>) -> TypedModule {
 }
 
 pub fn compile_nix(src: &str, deps: Vec<(&str, &str, &str)>) -> Result<String, crate::Error> {
+    compile_nix_optimised(src, deps, 0)
+}
+
+pub fn compile_nix_optimised(
+    src: &str,
+    deps: Vec<(&str, &str, &str)>,
+    optimize: u8,
+) -> Result<String, crate::Error> {
+    compile_nix_with_lists(src, deps, optimize, ListRepresentation::Cons)
+}
+
+pub fn compile_nix_with_lists(
+    src: &str,
+    deps: Vec<(&str, &str, &str)>,
+    optimize: u8,
+    list_representation: ListRepresentation,
+) -> Result<String, crate::Error> {
     let ast = compile(src, deps);
     let line_numbers = LineNumbers::new(src);
     module(
@@ -169,6 +209,8 @@ pub fn compile_nix(src: &str, deps: Vec<(&str, &str, &str)>) -> Result<String, c
         Utf8Path::new(""),
         &"".into(),
         TargetSupport::Enforced,
+        optimize,
+        list_representation,
     )
 }
 
@@ -332,3 +332,74 @@ pub fn main() {
 "#
     )
 }
+
+#[test]
+fn decision_tree_tests_constructor_once() {
+    assert_nix!(
+        r#"
+fn go(x) {
+  case x {
+    Ok(1) -> 1
+    Ok(2) -> 2
+    Ok(_) -> 3
+    Error(_) -> 4
+  }
+}
+"#
+    )
+}
+
+#[test]
+fn decision_tree_with_guard() {
+    assert_nix!(
+        r#"
+fn go(x) {
+  case x {
+    Ok(y) if y > 10 -> y
+    Ok(0) -> 0
+    Ok(_) -> 1
+    Error(_) -> 2
+  }
+}
+"#
+    )
+}
+
+#[test]
+fn decision_tree_list() {
+    assert_nix!(
+        r#"
+fn go(xs) {
+  case xs {
+    [1, ..] -> 1
+    [_, 2, ..] -> 2
+    [_, _, ..] -> 3
+    _ -> 0
+  }
+}
+"#
+    )
+}
+
+#[test]
+fn decision_tree_unmatched_constructors() {
+    assert_nix!(
+        r#"
+type Shape {
+  Circle(radius: Int)
+  Square(Int)
+  Triangle
+  Line
+}
+
+fn go(x) {
+  case x {
+    Circle(radius: 0) -> 0
+    Circle(radius: r) -> r
+    Square(side) -> side
+    _ -> 1
+  }
+}
+"#
+    )
+}
@@ -39,3 +39,118 @@ pub const y = gleam.Ok
 fn constructor_function_in_constant() {
     assert_nix!("pub const a = Ok");
 }
+
+#[test]
+fn strict_constant() {
+    assert_nix!(
+        r#"pub const lazy = 1
+
+@strict
+pub const eager = 2
+"#,
+    );
+}
+
+#[test]
+fn multiple_strict_constants() {
+    assert_nix!(
+        r#"@strict
+pub const wibble = "wibble"
+
+@strict
+const wobble = 2
+
+pub const wubble = wobble
+"#,
+    );
+}
+
+#[test]
+fn record_with_labelled_fields() {
+    assert_nix!(
+        r#"pub type Point {
+  Point(x: Int, y: Int)
+}
+
+pub type Line {
+  Line(from: Point, to: Point)
+}
+
+pub const line = Line(Point(0, 0), to: Point(y: 2, x: 1))
+"#
+    );
+}
+
+#[test]
+fn record_with_unlabelled_fields() {
+    assert_nix!(
+        r#"pub type Pair {
+  Pair(Int, second: Int)
+}
+
+pub const pair = Pair(1, 2)
+"#
+    );
+}
+
+#[test]
+fn record_with_keyword_label() {
+    assert_nix!(
+        r#"pub type Scope {
+  Scope(inherit: Bool)
+}
+
+pub const scope = Scope(inherit: True)
+"#
+    );
+}
+
+#[test]
+fn nested_results() {
+    assert_nix!(
+        r#"pub const result: Result(Result(Int, String), Nil) = Ok(Error("nested"))
+"#
+    );
+}
+
+#[test]
+fn list_of_records() {
+    assert_nix!(
+        r#"pub type Point {
+  Point(x: Int, y: Int)
+}
+
+pub const points = [Point(1, 2), Point(3, 4), Point(5, 6)]
+"#
+    );
+}
+
+#[test]
+fn bit_array() {
+    assert_nix!(
+        r#"pub const bytes = <<-1:size(16), 0, "é":utf8>>
+"#
+    );
+}
+
+#[test]
+fn bit_array_in_record() {
+    assert_nix!(
+        r#"pub type Wrapper {
+  Wrapper(BitArray)
+}
+
+pub const wrapped = #(Wrapper(<<1, 2>>), "x")
+"#
+    );
+}
+
+#[test]
+fn bit_array_referencing_constant() {
+    assert_nix!(
+        r#"const header = <<1, 2>>
+
+pub const packet = <<header:bits, 3>>
+"#
+    );
+}
@@ -516,6 +516,37 @@ pub const main = Two(b: 2, c: 3, a: 1)
     );
 }
 
+#[test]
+fn const_unqualified_imported_aliased() {
+    assert_nix!(
+        (
+            CURRENT_PACKAGE,
+            "other",
+            r#"pub type One { Two(field: Int) }"#
+        ),
+        r#"import other.{Two as Deux}
+pub const main = Deux(1)
+"#,
+    );
+}
+
+#[test]
+fn const_unqualified_imported_aliased_and_called() {
+    assert_nix!(
+        (
+            CURRENT_PACKAGE,
+            "other",
+            r#"pub type One { Two(field: Int) }"#
+        ),
+        r#"import other.{Two as Deux}
+pub const main = Deux(1)
+pub fn make() {
+  Deux(2)
+}
+"#,
+    );
+}
+
 #[test]
 fn imported_pattern() {
     assert_nix!(
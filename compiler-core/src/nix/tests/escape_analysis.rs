@@ -0,0 +1,185 @@
+use crate::assert_nix_optimised;
+
+use super::{compile_nix, compile_nix_optimised};
+
+/// Asserts that the code generated with the escape analysis is the same as
+/// the code generated without it.
+fn assert_not_optimised(src: &str) {
+    let optimised = compile_nix_optimised(src, vec![], 2).expect("compilation failed");
+    let unoptimised = compile_nix(src, vec![]).expect("compilation failed");
+    assert_eq!(optimised, unoptimised);
+}
+
+#[test]
+fn destructured_tuple() {
+    assert_nix_optimised!(
+        r#"
+fn pair(x) {
+  #(x, x + 1)
+}
+
+pub fn main() {
+  let #(a, b) = pair(1)
+  a * b
+}
+"#
+    );
+}
+
+#[test]
+fn destructured_result() {
+    assert_nix_optimised!(
+        r#"
+fn check(x) {
+  case x {
+    0 -> Error(Nil)
+    _ -> Ok(x)
+  }
+}
+
+pub fn main() {
+  case check(1) {
+    Ok(x) -> x
+    Error(_) -> 0
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn tail_call_to_function_with_variant() {
+    assert_nix_optimised!(
+        r#"
+fn pair(x) {
+  #(x, x)
+}
+
+fn twice(x) {
+  pair(x * 2)
+}
+
+pub fn main() {
+  let #(a, b) = twice(1)
+  a + b
+}
+"#
+    );
+}
+
+#[test]
+fn disabled_below_level_2() {
+    let src = r#"
+fn pair(x) {
+  #(x, x + 1)
+}
+
+pub fn main() {
+  let #(a, b) = pair(1)
+  a * b
+}
+"#;
+    assert_eq!(
+        compile_nix_optimised(src, vec![], 1).expect("compilation failed"),
+        compile_nix(src, vec![]).expect("compilation failed"),
+    );
+}
+
+#[test]
+fn tuple_not_destructured() {
+    assert_not_optimised(
+        r#"
+fn pair(x) {
+  #(x, x + 1)
+}
+
+pub fn main() {
+  pair(1).0
+}
+"#,
+    );
+}
+
+#[test]
+fn destructuring_returned() {
+    assert_not_optimised(
+        r#"
+fn pair(x) {
+  #(x, x + 1)
+}
+
+pub fn main() {
+  let #(a, b) = pair(1)
+}
+"#,
+    );
+}
+
+#[test]
+fn asserted_destructuring() {
+    assert_not_optimised(
+        r#"
+fn pair(x) {
+  #(x, x + 1)
+}
+
+pub fn main() {
+  let assert #(1, b) = pair(1)
+  b
+}
+"#,
+    );
+}
+
+#[test]
+fn tuple_not_built_in_tail_position() {
+    assert_not_optimised(
+        r#"
+fn pair(x) {
+  let pair = #(x, x + 1)
+  pair
+}
+
+pub fn main() {
+  let #(a, b) = pair(1)
+  a * b
+}
+"#,
+    );
+}
+
+#[test]
+fn result_matched_with_guard() {
+    assert_not_optimised(
+        r#"
+fn check(x) {
+  Ok(x)
+}
+
+pub fn main() {
+  case check(1) {
+    Ok(x) if x > 0 -> x
+    Ok(_) -> 1
+    Error(_) -> 0
+  }
+}
+"#,
+    );
+}
+
+#[test]
+fn function_from_other_module() {
+    let dep = ("thepackage", "other", "pub fn pair(x) { #(x, x) }");
+    let src = r#"
+import other
+
+pub fn main() {
+  let #(a, b) = other.pair(1)
+  a + b
+}
+"#;
+    assert_eq!(
+        compile_nix_optimised(src, vec![dep], 2).expect("compilation failed"),
+        compile_nix(src, vec![dep]).expect("compilation failed"),
+    );
+}
@@ -14,6 +14,16 @@ fn show(x: anything) -> Nil"#,
     );
 }
 
+#[test]
+fn any_external_fallback() {
+    assert_nix!(
+        r#"
+@external(erlang, "utils", "inspect")
+@external(any, "./utils", "inspect")
+pub fn show(x: anything) -> Nil"#,
+    );
+}
+
 #[test]
 fn pub_module_fn() {
     assert_nix!(
@@ -0,0 +1,89 @@
+use crate::assert_nix;
+
+#[test]
+fn inlined_call() {
+    assert_nix!(
+        r#"
+@inline
+fn add(x, y) {
+  x + y
+}
+
+pub fn main() {
+  add(1, 2)
+}
+"#
+    );
+}
+
+#[test]
+fn inlined_call_with_discarded_argument() {
+    assert_nix!(
+        r#"
+@inline
+fn first(x, _y) {
+  x
+}
+
+pub fn main() {
+  first(1, 2)
+}
+"#
+    );
+}
+
+#[test]
+fn not_inlined_when_caller_shadows_module_value() {
+    assert_nix!(
+        r#"
+fn double(x) {
+  x * 2
+}
+
+@inline
+fn quadruple(x) {
+  double(double(x))
+}
+
+pub fn main(double) {
+  quadruple(double)
+}
+"#
+    );
+}
+
+#[test]
+fn not_inlined_when_argument_uses_earlier_parameter_name() {
+    assert_nix!(
+        r#"
+@inline
+fn sub(x, y) {
+  x - y
+}
+
+pub fn main(y, x) {
+  sub(y, x)
+}
+"#
+    );
+}
+
+#[test]
+fn not_inlined_when_parameter_shadows_module_value() {
+    assert_nix!(
+        r#"
+fn double(x) {
+  x * 2
+}
+
+@inline
+fn f(double, y) {
+  double + y
+}
+
+pub fn main() {
+  f(1, double(2))
+}
+"#
+    );
+}
@@ -0,0 +1,92 @@
+use crate::config::ListRepresentation;
+
+use super::compile_nix_with_lists;
+
+fn compile(src: &str) -> String {
+    compile_nix_with_lists(src, vec![], 0, ListRepresentation::Native).expect("compilation failed")
+}
+
+#[test]
+fn list_literals_are_nix_lists() {
+    let compiled = compile(
+        r#"
+pub fn go(x) {
+  #([], [1, 2], [1, 2, ..x])
+}
+"#,
+    );
+    assert!(
+        compiled.contains("[ [ ] [ 1 2 ] ([ 1 2 ] ++ x) ]"),
+        "{compiled}"
+    );
+    assert!(!compiled.contains("toList"), "{compiled}");
+    assert!(!compiled.contains("listPrepend"), "{compiled}");
+}
+
+#[test]
+fn list_patterns_use_indices() {
+    let compiled = compile(
+        r#"
+pub fn go(xs) {
+  let assert [a, b, ..rest] = xs
+  #(a, b, rest)
+}
+"#,
+    );
+    assert!(compiled.contains("listHasAtLeastLength xs 2"), "{compiled}");
+    assert!(compiled.contains("builtins.elemAt xs 0"), "{compiled}");
+    assert!(compiled.contains("builtins.elemAt xs 1"), "{compiled}");
+    assert!(compiled.contains("listDrop 2 xs"), "{compiled}");
+    assert!(!compiled.contains(".head"), "{compiled}");
+    assert!(!compiled.contains(".tail"), "{compiled}");
+}
+
+#[test]
+fn list_decision_trees_use_builtins() {
+    let compiled = compile(
+        r#"
+pub fn go(xs) {
+  case xs {
+    [1, ..] -> 1
+    [2, ..] -> 2
+    [_, ..] -> 3
+    [] -> 0
+  }
+}
+"#,
+    );
+    assert!(compiled.contains("xs == [ ]"), "{compiled}");
+    assert!(compiled.contains("builtins.elemAt xs 0"), "{compiled}");
+    assert!(!compiled.contains("__gleamTag"), "{compiled}");
+}
+
+#[test]
+fn list_decision_trees_index_the_subject() {
+    let compiled = compile(
+        r#"
+pub fn go(xs) {
+  case xs {
+    [1, 2] -> []
+    [1, ..rest] -> rest
+    _ -> xs
+  }
+}
+"#,
+    );
+    assert!(compiled.contains("builtins.length xs == 1"), "{compiled}");
+    assert!(compiled.contains("builtins.elemAt xs 1"), "{compiled}");
+    assert!(compiled.contains("listDrop 1 xs"), "{compiled}");
+    assert!(!compiled.contains("builtins.tail"), "{compiled}");
+}
+
+#[test]
+fn cons_lists_are_unchanged() {
+    let compiled = compile_nix_with_lists(
+        "pub fn go(x) { [1, ..x] }",
+        vec![],
+        0,
+        ListRepresentation::Cons,
+    )
+    .expect("compilation failed");
+    assert!(compiled.contains("listPrepend 1 x"), "{compiled}");
+}
@@ -0,0 +1,93 @@
+use super::compile_nix;
+
+fn compile(src: &str) -> String {
+    compile_nix(src, vec![]).expect("compilation failed")
+}
+
+#[test]
+fn single_constructor() {
+    let compiled = compile(
+        r#"
+@derive(nix_attrs)
+pub type Unit {
+  Unit(name: String)
+}
+"#,
+    );
+    assert!(
+        compiled.contains(
+            r#"from_attrs_unit = recordFromAttrs "Unit" { Unit = { name = "string"; }; };"#
+        ),
+        "{compiled}"
+    );
+    assert!(
+        compiled.contains(r#"to_attrs_unit = recordToAttrs { Unit = { name = "string"; }; };"#),
+        "{compiled}"
+    );
+    assert!(
+        compiled.contains("inherit Unit from_attrs_unit to_attrs_unit;"),
+        "{compiled}"
+    );
+    assert!(compiled.contains("recordFromAttrs"), "{compiled}");
+}
+
+#[test]
+fn multiple_constructors() {
+    let compiled = compile(
+        r#"
+@derive(nix_attrs)
+pub type Port {
+  Tcp(at: Int)
+  Any
+}
+"#,
+    );
+    assert!(compiled.contains(r#"Tcp = { at = "int"; };"#), "{compiled}");
+    assert!(compiled.contains("Any = { };"), "{compiled}");
+    assert!(compiled.contains("from_attrs_port ="), "{compiled}");
+    assert!(compiled.contains("to_attrs_port ="), "{compiled}");
+}
+
+#[test]
+fn keyword_labels_are_kept() {
+    let compiled = compile(
+        r#"
+@derive(nix_attrs)
+pub type Rule {
+  Rule(with: Int)
+}
+"#,
+    );
+    assert!(
+        compiled.contains(r#"{ Rule = { "with" = "int"; }; }"#),
+        "{compiled}"
+    );
+}
+
+#[test]
+fn only_prelude_types_are_checked() {
+    let compiled = compile(
+        r#"
+@derive(nix_attrs)
+pub type Package {
+  Package(name: String, enable: Bool, weight: Float, tags: List(String))
+}
+"#,
+    );
+    assert!(compiled.contains(r#"name = "string";"#), "{compiled}");
+    assert!(compiled.contains(r#"enable = "bool";"#), "{compiled}");
+    assert!(compiled.contains(r#"weight = "float";"#), "{compiled}");
+    assert!(compiled.contains("tags = null;"), "{compiled}");
+}
+
+#[test]
+fn not_generated_without_attribute() {
+    let compiled = compile(
+        r#"
+pub type Unit {
+  Unit(name: String)
+}
+"#,
+    );
+    assert!(!compiled.contains("attrs"), "{compiled}");
+}
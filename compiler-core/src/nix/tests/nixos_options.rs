@@ -0,0 +1,113 @@
+use super::compile_nix;
+
+const OPTION_MODULE: (&str, &str, &str) = (
+    "gleam_stdlib",
+    "gleam/option",
+    "pub type Option(a) { Some(a) None }",
+);
+
+fn compile(src: &str) -> String {
+    compile_nix(src, vec![OPTION_MODULE]).expect("compilation failed")
+}
+
+#[test]
+fn record_fields_become_options() {
+    let compiled = compile(
+        r#"
+import gleam/option.{type Option}
+
+@derive(nixos_options)
+pub type Service {
+  Service(
+    /// The port to listen on.
+    port: Int,
+    hosts: List(String),
+    user: Option(String),
+  )
+}
+"#,
+    );
+    assert!(
+        compiled.contains(r#"description = "The port to listen on.";"#),
+        "{compiled}"
+    );
+    assert!(compiled.contains("type = lib.types.int;"), "{compiled}");
+    assert!(
+        compiled.contains("hosts = lib.mkOption { type = lib.types.listOf lib.types.str; };"),
+        "{compiled}"
+    );
+    assert!(
+        compiled.contains("type = lib.types.nullOr lib.types.str;"),
+        "{compiled}"
+    );
+    assert!(compiled.contains("default = null;"), "{compiled}");
+    assert!(
+        compiled.contains("from_nixos_options_service ="),
+        "{compiled}"
+    );
+    assert!(compiled.contains("(toList config.hosts)"), "{compiled}");
+    assert!(
+        compiled.contains(r#"(value: if value == null then { __gleamTag = "None"; }"#),
+        "{compiled}"
+    );
+}
+
+#[test]
+fn constructors_without_fields_become_an_enumeration() {
+    let compiled = compile(
+        r#"
+@derive(nixos_options)
+pub type Level {
+  Low
+  High
+}
+"#,
+    );
+    assert!(
+        compiled.contains(r#"nixos_options_level = lib: lib.types.enum [ "Low" "High" ];"#),
+        "{compiled}"
+    );
+    assert!(
+        compiled.contains("from_nixos_options_level = value: { __gleamTag = value; };"),
+        "{compiled}"
+    );
+}
+
+#[test]
+fn derived_types_are_nested() {
+    let compiled = compile(
+        r#"
+@derive(nixos_options)
+pub type Level {
+  Low
+  High
+}
+
+@derive(nixos_options)
+pub type Host {
+  Host(name: String)
+}
+
+@derive(nixos_options)
+pub type Service {
+  Service(level: Level, hosts: List(Host))
+}
+"#,
+    );
+    assert!(
+        compiled.contains("level = lib.mkOption { type = nixos_options_level lib; };"),
+        "{compiled}"
+    );
+    assert!(
+        compiled.contains("(lib.types.submodule { options = nixos_options_host lib; })"),
+        "{compiled}"
+    );
+    assert!(
+        compiled.contains("(from_nixos_options_level config.level)"),
+        "{compiled}"
+    );
+    assert!(
+        compiled.contains("(value: toList (builtins.map from_nixos_options_host value))"),
+        "{compiled}"
+    );
+}
@@ -0,0 +1,60 @@
+use crate::nix::package_index;
+
+#[test]
+fn flat_modules() {
+    assert_eq!(
+        package_index(&["wibble".into(), "wobble".into()]),
+        r#"{
+  wibble = builtins.import ./wibble.nix;
+  wobble = builtins.import ./wobble.nix;
+}
+"#
+    );
+}
+
+#[test]
+fn nested_modules() {
+    assert_eq!(
+        package_index(&[
+            "wibble/wobble".into(),
+            "wibble/wubble/wabble".into(),
+            "wobble".into(),
+        ]),
+        r#"{
+  wibble = {
+    wobble = builtins.import ./wibble/wobble.nix;
+    wubble = {
+      wabble = builtins.import ./wibble/wubble/wabble.nix;
+    };
+  };
+  wobble = builtins.import ./wobble.nix;
+}
+"#
+    );
+}
+
+#[test]
+fn module_with_submodules() {
+    assert_eq!(
+        package_index(&["wibble".into(), "wibble/wobble".into()]),
+        r#"{
+  wibble = builtins.import ./wibble.nix // {
+    wobble = builtins.import ./wibble/wobble.nix;
+  };
+}
+"#
+    );
+}
+
+#[test]
+fn keyword_module_names() {
+    assert_eq!(
+        package_index(&["let/in".into()]),
+        r#"{
+  "let" = {
+    "in" = builtins.import ./let/in.nix;
+  };
+}
+"#
+    );
+}
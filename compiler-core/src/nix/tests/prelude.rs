@@ -1,4 +1,5 @@
 use crate::assert_nix;
+use crate::config::ListRepresentation;
 
 #[test]
 fn qualified_ok() {
@@ -26,3 +27,84 @@ pub fn go() { gleam.Nil }
 "#,
     );
 }
+
+#[test]
+fn nix_module() {
+    assert_nix!(
+        r#"import glistix/nix
+pub fn go(path) { nix.path_join(path, "default.nix") }
+"#,
+    );
+}
+
+#[test]
+fn nix_module_debug() {
+    assert_nix!(
+        r#"import glistix/nix
+pub fn go(x) { nix.debug(#(x, nix.inspect(x))) }
+"#,
+    );
+}
+
+#[test]
+fn prelude_exports() {
+    let exports: Vec<_> = crate::nix::prelude_exports().collect();
+    assert!(exports.contains(&"Ok"));
+    assert!(exports.contains(&"byteArrayToUtf8String"));
+    assert!(exports.contains(&"classifyDynamic"));
+    assert!(exports.contains(&"decodeField"));
+    assert!(exports.contains(&"recordFromAttrs"));
+    assert!(exports.contains(&"recordToAttrs"));
+    assert!(exports.contains(&"inspect"));
+    assert!(exports.contains(&"debug"));
+    assert!(!exports.contains(&"inherit"));
+}
+
+#[test]
+fn prelude_representation_version() {
+    let version = crate::nix::REPRESENTATION_VERSION;
+    assert!(crate::nix::PRELUDE.contains(&format!("  reprVersion = {version};\n")));
+    assert!(crate::nix::prelude_exports().any(|name| name == "reprVersion"));
+}
+
+#[test]
+fn prelude_reexport_records_representation_version() {
+    let reexport = crate::nix::prelude_reexport("../prelude.nix", ListRepresentation::Cons);
+    assert!(reexport.contains("builtins.import ./../prelude.nix;"));
+    assert_eq!(
+        crate::nix::prelude_reexport_representation_version(&reexport),
+        Some(crate::nix::REPRESENTATION_VERSION)
+    );
+    assert_eq!(
+        crate::nix::prelude_reexport_list_representation(&reexport),
+        ListRepresentation::Cons
+    );
+}
+
+#[test]
+fn prelude_reexport_records_native_lists() {
+    let reexport = crate::nix::prelude_reexport("../prelude.nix", ListRepresentation::Native);
+    assert!(reexport.contains("prelude.withListRepresentation or"));
+    assert_eq!(
+        crate::nix::prelude_reexport_representation_version(&reexport),
+        Some(crate::nix::REPRESENTATION_VERSION)
+    );
+    assert_eq!(
+        crate::nix::prelude_reexport_list_representation(&reexport),
+        ListRepresentation::Native
+    );
+}
+
+#[test]
+fn prelude_supports_native_lists() {
+    assert!(crate::nix::prelude_exports().any(|name| name == "withListRepresentation"));
+    assert!(crate::nix::prelude_exports().any(|name| name == "listDrop"));
+}
+
+#[test]
+fn old_prelude_reexport_has_no_representation_version() {
+    assert_eq!(
+        crate::nix::prelude_reexport_representation_version("builtins.import ../prelude.nix\n"),
+        None
+    );
+}
@@ -8,12 +8,13 @@ use serde::Serialize;
 mod tests;
 
 use crate::{
-    ast::{CustomType, Definition, Function, ModuleConstant, Publicity, TypeAlias},
+    ast::{CustomType, Definition, Function, ModuleConstant, Publicity, SrcSpan, TypeAlias},
     io::ordered_map,
+    line_numbers::LineNumbers,
     type_::{expression::Implementations, Deprecation, Type, TypeVar},
 };
 
-use crate::build::{Module, Package};
+use crate::build::{Module, Package, Target};
 
 /// The public interface of a package that gets serialised as a json object.
 #[derive(Serialize, Debug)]
@@ -23,6 +24,9 @@ pub struct PackageInterface {
     version: EcoString,
     /// The Gleam version constraint that the package specifies in its `gleam.toml`.
     gleam_version_constraint: Option<EcoString>,
+    /// The target the package is compiled for by default, as specified in its
+    /// `gleam.toml`.
+    target: Target,
     /// A map from module name to its interface.
     #[serde(serialize_with = "ordered_map")]
     modules: HashMap<EcoString, ModuleInterface>,
@@ -145,13 +149,14 @@ pub struct FunctionInterface {
     /// this field will hold the reason of the deprecation.
     deprecation: Option<DeprecationInterface>,
     implementations: ImplementationsInterface,
+    externals: ExternalsInterface,
     parameters: Vec<ParameterInterface>,
     #[serde(rename = "return")]
     return_: TypeInterface,
 }
 
 /// Informations about how a value is implemented.
-#[derive(Debug, Serialize, Copy, Clone)]
+#[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub struct ImplementationsInterface {
     /// Set to `true` if the const/function has a pure Gleam implementation
@@ -249,10 +254,44 @@ pub struct ImplementationsInterface {
     /// to a pure Gleam implementation or an implementation that uses some
     /// Nix externals.
     can_run_on_nix: bool,
+    /// The first value used by the const/function that can't be called on
+    /// the Nix target, making it unable to run on Nix as well. This is `null`
+    /// if the const/function can run on Nix, or if it is the one lacking a Nix
+    /// implementation.
+    ///
+    /// ```gleam
+    /// @external(erlang, "wibble", "wobble")
+    /// pub fn erlang_only() -> Int
+    ///
+    /// pub fn uses_erlang_only() -> Int {
+    ///   erlang_only() + 1
+    /// }
+    /// ```
+    ///
+    /// Here `uses_erlang_only` would have the following blocker:
+    ///
+    /// ```json
+    /// { module: "my/module", name: "erlang_only", line: 5 }
+    /// ```
+    nix_blocker: Option<NixBlockerInterface>,
+}
+
+/// A value stopping a const/function from running on Nix.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct NixBlockerInterface {
+    /// The module defining the value.
+    module: EcoString,
+    name: EcoString,
+    /// The line of the const/function's module where the value is used.
+    line: u32,
 }
 
 impl ImplementationsInterface {
-    fn from_implementations(implementations: &Implementations) -> ImplementationsInterface {
+    fn from_implementations(
+        implementations: &Implementations,
+        line_numbers: &LineNumbers,
+    ) -> ImplementationsInterface {
         // It might look a bit silly to just recreate an identical structure with
         // a different name. However, this way we won't inadvertently cause breaking
         // changes if we were to change the names used by the `Implementations` struct
@@ -270,6 +309,7 @@ impl ImplementationsInterface {
             can_run_on_erlang,
             can_run_on_javascript,
             can_run_on_nix,
+            nix_blocker,
         } = implementations;
 
         ImplementationsInterface {
@@ -280,10 +320,55 @@ impl ImplementationsInterface {
             can_run_on_erlang: *can_run_on_erlang,
             can_run_on_javascript: *can_run_on_javascript,
             can_run_on_nix: *can_run_on_nix,
+            nix_blocker: nix_blocker.as_ref().map(|blocker| NixBlockerInterface {
+                module: blocker.module.clone(),
+                name: blocker.name.clone(),
+                line: line_numbers.line_number(blocker.location.start),
+            }),
         }
     }
 }
 
+/// The external implementations of a function for each target, as given by
+/// its `@external` attributes.
+/// ```gleam
+/// @external(erlang, "wibble", "wobble")
+/// @external(nix, "./wibble.nix", "wobble")
+/// pub fn wobble() -> Int
+/// ```
+///
+/// A target that has no `@external` attribute is set to `null`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExternalsInterface {
+    erlang: Option<ExternalInterface>,
+    javascript: Option<ExternalInterface>,
+    nix: Option<ExternalInterface>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExternalInterface {
+    /// The external module. For the JavaScript and Nix targets this is the
+    /// path to the file implementing the function.
+    module: EcoString,
+    /// The name of the external function.
+    function: EcoString,
+}
+
+impl ExternalInterface {
+    fn from_external(
+        external: &Option<(EcoString, EcoString, SrcSpan)>,
+    ) -> Option<ExternalInterface> {
+        external
+            .as_ref()
+            .map(|(module, function, _)| ExternalInterface {
+                module: module.clone(),
+                function: function.clone(),
+            })
+    }
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct DeprecationInterface {
@@ -378,6 +463,7 @@ impl PackageInterface {
                 .gleam_version
                 .clone()
                 .map(|version| EcoString::from(version.to_string())),
+            target: package.config.target,
             modules: package
                 .modules
                 .iter()
@@ -390,6 +476,7 @@ impl PackageInterface {
 
 impl ModuleInterface {
     fn from_module(module: &Module) -> ModuleInterface {
+        let line_numbers = LineNumbers::new(&module.code);
         let mut types = HashMap::new();
         let mut type_aliases = HashMap::new();
         let mut constants = HashMap::new();
@@ -404,11 +491,15 @@ impl ModuleInterface {
                     documentation,
                     opaque,
                     deprecation,
+                    since: _,
                     typed_parameters,
                     parameters: _,
                     location: _,
                     name_location: _,
                     end_position: _,
+                    derive_decode: _,
+                    derive_nix_attrs: _,
+                    derive_nixos_options: _,
                 }) => {
                     let mut id_map = IdMap::new();
 
@@ -464,6 +555,7 @@ impl ModuleInterface {
                     type_,
                     documentation,
                     deprecation,
+                    since: _,
                     location: _,
                     name_location: _,
                     type_ast: _,
@@ -487,16 +579,20 @@ impl ModuleInterface {
                     documentation,
                     implementations,
                     deprecation,
+                    since: _,
                     location: _,
                     name_location: _,
                     annotation: _,
                     value: _,
+                    embedded_file: _,
+                    strict: _,
                 }) => {
                     let _ = constants.insert(
                         name.clone(),
                         ConstantInterface {
                             implementations: ImplementationsInterface::from_implementations(
                                 implementations,
+                                &line_numbers,
                             ),
                             type_: TypeInterface::from_type(type_.as_ref()),
                             deprecation: DeprecationInterface::from_deprecation(deprecation),
@@ -511,6 +607,7 @@ impl ModuleInterface {
                     name,
                     arguments,
                     deprecation,
+                    since: _,
                     return_type,
                     documentation,
                     implementations,
@@ -518,9 +615,11 @@ impl ModuleInterface {
                     end_position: _,
                     body: _,
                     return_annotation: _,
-                    external_erlang: _,
-                    external_javascript: _,
-                    external_nix: _,
+                    external_erlang,
+                    external_javascript,
+                    external_nix,
+                    external_any: _,
+                    inline: _,
                 }) => {
                     let mut id_map = IdMap::new();
                     let (_, name) = name
@@ -531,7 +630,13 @@ impl ModuleInterface {
                         FunctionInterface {
                             implementations: ImplementationsInterface::from_implementations(
                                 implementations,
+                                &line_numbers,
                             ),
+                            externals: ExternalsInterface {
+                                erlang: ExternalInterface::from_external(external_erlang),
+                                javascript: ExternalInterface::from_external(external_javascript),
+                                nix: ExternalInterface::from_external(external_nix),
+                            },
                             deprecation: DeprecationInterface::from_deprecation(deprecation),
                             documentation: documentation.as_ref().map(|(_, doc)| doc.clone()),
                             parameters: arguments
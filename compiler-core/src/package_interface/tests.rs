@@ -160,13 +160,21 @@ fn package_from_module(module: Module) -> Package {
             ),
             licences: vec![],
             description: "description".into(),
-            documentation: Docs { pages: vec![] },
+            documentation: Docs {
+                pages: vec![],
+                assets: vec![],
+            },
             dependencies: std::collections::HashMap::new(),
             dev_dependencies: std::collections::HashMap::new(),
             repository: Repository::default(),
             links: vec![],
             erlang: ErlangConfig::default(),
             javascript: JavaScriptConfig::default(),
+            format: Default::default(),
+            registry: None,
+            dependency_policy: Default::default(),
+            dependency_targets: Default::default(),
+            hex: Default::default(),
             glistix: GlistixConfig::default(),
             target: Target::Erlang,
             internal_modules: Some(vec![GlobBuilder::new("internals/*")
@@ -307,3 +315,28 @@ pub type Box(a, b) {
 pub fn internal_modules_are_not_exported() {
     assert_package_interface_with_name!("internals/internal_module", "pub fn main() { 1 }");
 }
+
+#[test]
+pub fn externals_are_included() {
+    assert_package_interface!(
+        r#"
+@external(erlang, "wibble", "wobble")
+@external(nix, "./wibble.nix", "wobble")
+pub fn wobble() -> Int
+"#
+    );
+}
+
+#[test]
+pub fn nix_blocker_is_included() {
+    assert_package_interface!(
+        r#"
+@external(erlang, "wibble", "wobble")
+pub fn erlang_only() -> Int
+
+pub fn uses_erlang_only() -> Int {
+  erlang_only() + 1
+}
+"#
+    );
+}
@@ -57,13 +57,14 @@ mod token;
 use crate::analyse::Inferred;
 use crate::ast::{
     Arg, ArgNames, AssignName, Assignment, AssignmentKind, BinOp, BitArrayOption, BitArraySegment,
-    CallArg, Clause, ClauseGuard, Constant, CustomType, Definition, Function, FunctionLiteralKind,
-    HasLocation, Import, Module, ModuleConstant, Pattern, Publicity, RecordBeingUpdated,
-    RecordConstructor, RecordConstructorArg, SrcSpan, Statement, TargetedDefinition, TodoKind,
-    TypeAlias, TypeAst, TypeAstConstructor, TypeAstFn, TypeAstHole, TypeAstTuple, TypeAstVar,
-    UnqualifiedImport, UntypedArg, UntypedClause, UntypedClauseGuard, UntypedConstant,
-    UntypedDefinition, UntypedExpr, UntypedModule, UntypedPattern, UntypedRecordUpdateArg,
-    UntypedStatement, UntypedUseAssignment, Use, UseAssignment, CAPTURE_VARIABLE,
+    CallArg, Clause, ClauseGuard, Constant, CustomType, Definition, EmbeddedFile, Function,
+    FunctionLiteralKind, HasLocation, Import, Module, ModuleConstant, Pattern, Publicity,
+    RecordBeingUpdated, RecordConstructor, RecordConstructorArg, SrcSpan, Statement,
+    TargetedDefinition, TodoKind, TypeAlias, TypeAst, TypeAstConstructor, TypeAstFn, TypeAstHole,
+    TypeAstTuple, TypeAstVar, UnqualifiedImport, UntypedArg, UntypedClause, UntypedClauseGuard,
+    UntypedConstant, UntypedDefinition, UntypedExpr, UntypedModule, UntypedPattern,
+    UntypedRecordUpdateArg, UntypedStatement, UntypedUseAssignment, Use, UseAssignment,
+    CAPTURE_VARIABLE,
 };
 use crate::build::Target;
 use crate::error::wrap;
@@ -76,6 +77,7 @@ use crate::Warning;
 use camino::Utf8PathBuf;
 use ecow::EcoString;
 use error::{LexicalError, ParseError, ParseErrorType};
+use hexpm::version::Version;
 use lexer::{LexResult, Spanned};
 use num_bigint::BigInt;
 use std::cmp::Ordering;
@@ -119,10 +121,18 @@ enum InternalAttribute {
 struct Attributes {
     target: Option<Target>,
     deprecated: Deprecation,
+    since: Option<EcoString>,
     external_erlang: Option<(EcoString, EcoString, SrcSpan)>,
     external_javascript: Option<(EcoString, EcoString, SrcSpan)>,
     external_nix: Option<(EcoString, EcoString, SrcSpan)>,
+    external_any: Option<(EcoString, EcoString, SrcSpan)>,
     internal: InternalAttribute,
+    embed_file: Option<EmbeddedFile>,
+    strict: bool,
+    derive_decode: Option<SrcSpan>,
+    derive_nix_attrs: Option<SrcSpan>,
+    derive_nixos_options: Option<SrcSpan>,
+    inline: bool,
 }
 
 impl Attributes {
@@ -130,6 +140,8 @@ impl Attributes {
         self.external_erlang.is_some()
             || self.external_javascript.is_some()
             || self.external_nix.is_some()
+            || self.external_any.is_some()
+            || self.inline
     }
 
     fn has_external_for(&self, target: Target) -> bool {
@@ -209,6 +221,18 @@ pub fn parse_const_value(src: &str) -> Result<Constant<(), ()>, ParseError> {
     }
 }
 
+pub fn parse_type_annotation(src: &str) -> Result<TypeAst, ParseError> {
+    let lex = lexer::make_tokenizer(src);
+    let mut parser = Parser::new(lex);
+    let type_ = parser.parse_type();
+    let type_ = parser.ensure_no_errors_or_remaining_input(type_)?;
+    if let Some(type_) = type_ {
+        Ok(type_)
+    } else {
+        parse_error(ParseErrorType::ExpectedType, SrcSpan { start: 0, end: 0 })
+    }
+}
+
 //
 // Parser
 //
@@ -242,6 +266,8 @@ where
     }
 
     fn parse_module(&mut self) -> Result<Parsed, ParseError> {
+        let deprecation = self.parse_module_deprecation();
+        let deprecation = self.ensure_no_errors(deprecation)?;
         let definitions = Parser::series_of(self, &Parser::parse_definition, None);
         let definitions = self.ensure_no_errors_or_remaining_input(definitions)?;
         let module = Module {
@@ -250,6 +276,7 @@ where
             type_info: (),
             definitions,
             names: Default::default(),
+            deprecation,
         };
         Ok(Parsed {
             module,
@@ -257,6 +284,26 @@ where
         })
     }
 
+    /// Parses the `@deprecated_module("...")` attribute, which can only be
+    /// given once at the top of a module, before any of its definitions.
+    fn parse_module_deprecation(&mut self) -> Result<Deprecation, ParseError> {
+        match (&self.tok0, &self.tok1) {
+            (Some((_, Token::At, _)), Some((_, Token::Name { name }, _)))
+                if name == "deprecated_module" => {}
+            _ => return Ok(Deprecation::NotDeprecated),
+        }
+
+        let (start, _) = self.expect_one(&Token::At)?;
+        let (_, _, end) = self.expect_name()?;
+        let _ = self.expect_one(&Token::LeftParen)?;
+        let (_, message, _) = self.expect_string().map_err(|_| ParseError {
+            error: ParseErrorType::ExpectedDeprecationMessage,
+            location: SrcSpan { start, end },
+        })?;
+        let _ = self.expect_one(&Token::RightParen)?;
+        Ok(Deprecation::Deprecated { message })
+    }
+
     // The way the parser is currently implemented, it cannot exit immediately while advancing
     // the token stream upon seeing a LexError. That is to avoid having to put `?` all over the
     // place and instead we collect LexErrors in `self.lex_errors` and attempt to continue parsing.
@@ -360,6 +407,22 @@ where
         }?;
 
         match (def, location) {
+            (Some(definition), Some(location))
+                if (attributes.embed_file.is_some() || attributes.strict)
+                    && !matches!(definition, Definition::ModuleConstant(_)) =>
+            {
+                parse_error(ParseErrorType::ExpectedConstantDefinition, location)
+            }
+
+            (Some(definition), Some(location))
+                if (attributes.derive_decode.is_some()
+                    || attributes.derive_nix_attrs.is_some()
+                    || attributes.derive_nixos_options.is_some())
+                    && !matches!(definition, Definition::CustomType(_)) =>
+            {
+                parse_error(ParseErrorType::ExpectedCustomTypeDefinition, location)
+            }
+
             (Some(definition), _) if definition.is_function() => Ok(Some(TargetedDefinition {
                 definition,
                 target: attributes.target,
@@ -556,10 +619,15 @@ where
             Some((start, Token::Hash, _)) => {
                 self.advance();
                 let _ = self.expect_one(&Token::LeftParen)?;
-                let elems =
-                    Parser::series_of(self, &Parser::parse_expression, Some(&Token::Comma))?;
+                let (elems, elems_end_with_comma) = self.series_of_has_trailing_separator(
+                    &Parser::parse_expression,
+                    Some(&Token::Comma),
+                )?;
                 let (_, end) =
                     self.expect_one_following_series(&Token::RightParen, "an expression")?;
+                if elems_end_with_comma {
+                    self.extra.trailing_commas.push(end);
+                }
                 UntypedExpr::Tuple {
                     location: SrcSpan { start, end },
                     elems,
@@ -639,6 +707,10 @@ where
                     _ => {}
                 }
 
+                if tail.is_none() && elements_end_with_comma {
+                    self.extra.trailing_commas.push(end);
+                }
+
                 UntypedExpr::List {
                     location: SrcSpan { start, end },
                     elements,
@@ -1932,9 +2004,12 @@ where
             return_type: (),
             return_annotation,
             deprecation: std::mem::take(&mut attributes.deprecated),
+            since: attributes.since.take(),
             external_erlang: attributes.external_erlang.take(),
             external_javascript: attributes.external_javascript.take(),
             external_nix: attributes.external_nix.take(),
+            external_any: attributes.external_any.take(),
+            inline: attributes.inline,
             implementations: Implementations {
                 gleam: true,
                 can_run_on_erlang: true,
@@ -1943,6 +2018,7 @@ where
                 uses_erlang_externals: false,
                 uses_javascript_externals: false,
                 uses_nix_externals: false,
+                nix_blocker: None,
             },
         })))
     }
@@ -2222,8 +2298,13 @@ where
                         if attributes.external_erlang.is_some()
                             || attributes.external_javascript.is_some()
                             || attributes.external_nix.is_some()
+                            || attributes.external_any.is_some()
                             || attributes.target.is_some()
                             || attributes.internal != InternalAttribute::Missing
+                            || attributes.derive_decode.is_some()
+                            || attributes.derive_nix_attrs.is_some()
+                            || attributes.derive_nixos_options.is_some()
+                            || attributes.since.is_some()
                         {
                             return parse_error(
                                 ParseErrorType::UnknownAttributeRecordVariant,
@@ -2274,6 +2355,7 @@ where
                     type_ast: t,
                     type_: (),
                     deprecation: std::mem::take(&mut attributes.deprecated),
+                    since: attributes.since.take(),
                 })));
             } else {
                 return parse_error(ParseErrorType::ExpectedType, SrcSpan::new(eq_s, eq_e));
@@ -2294,6 +2376,10 @@ where
             constructors,
             typed_parameters: vec![],
             deprecation: std::mem::take(&mut attributes.deprecated),
+            since: attributes.since.take(),
+            derive_decode: attributes.derive_decode,
+            derive_nix_attrs: attributes.derive_nix_attrs,
+            derive_nixos_options: attributes.derive_nixos_options,
         })))
     }
 
@@ -2709,46 +2795,65 @@ where
 
         let annotation = self.parse_type_annotation(&Token::Colon)?;
 
-        let (eq_s, eq_e) = self.expect_one(&Token::Equal)?;
-        if let Some(value) = self.parse_const_value()? {
-            Ok(Some(Definition::ModuleConstant(ModuleConstant {
-                documentation,
-                location: SrcSpan {
-                    start,
+        let value = match &attributes.embed_file {
+            // The value of a constant with an embedded file is the contents of
+            // that file, which replaces this placeholder when the module is
+            // loaded.
+            Some(embedded_file) => Constant::String {
+                location: embedded_file.location,
+                value: "".into(),
+            },
 
-                    // End after the type annotation if it's there, otherwise after the name
-                    end: annotation
-                        .as_ref()
-                        .map(|annotation| annotation.location().end)
-                        .unwrap_or(0)
-                        .max(name_end),
-                },
-                publicity: self.publicity(public, attributes.internal)?,
-                name,
-                name_location: SrcSpan::new(name_start, name_end),
-                annotation,
-                value: Box::new(value),
-                type_: (),
-                deprecation: attributes.deprecated.clone(),
-                implementations: Implementations {
-                    gleam: true,
-                    can_run_on_erlang: true,
-                    can_run_on_javascript: true,
-                    can_run_on_nix: true,
-                    uses_erlang_externals: false,
-                    uses_javascript_externals: false,
-                    uses_nix_externals: false,
-                },
-            })))
-        } else {
-            parse_error(
-                ParseErrorType::NoValueAfterEqual,
-                SrcSpan {
-                    start: eq_s,
-                    end: eq_e,
-                },
-            )
-        }
+            None => {
+                let (eq_s, eq_e) = self.expect_one(&Token::Equal)?;
+                match self.parse_const_value()? {
+                    Some(value) => value,
+                    None => {
+                        return parse_error(
+                            ParseErrorType::NoValueAfterEqual,
+                            SrcSpan {
+                                start: eq_s,
+                                end: eq_e,
+                            },
+                        )
+                    }
+                }
+            }
+        };
+
+        Ok(Some(Definition::ModuleConstant(ModuleConstant {
+            documentation,
+            location: SrcSpan {
+                start,
+
+                // End after the type annotation if it's there, otherwise after the name
+                end: annotation
+                    .as_ref()
+                    .map(|annotation| annotation.location().end)
+                    .unwrap_or(0)
+                    .max(name_end),
+            },
+            publicity: self.publicity(public, attributes.internal)?,
+            name,
+            name_location: SrcSpan::new(name_start, name_end),
+            annotation,
+            value: Box::new(value),
+            type_: (),
+            deprecation: attributes.deprecated.clone(),
+            since: attributes.since.clone(),
+            implementations: Implementations {
+                gleam: true,
+                can_run_on_erlang: true,
+                can_run_on_javascript: true,
+                can_run_on_nix: true,
+                uses_erlang_externals: false,
+                uses_javascript_externals: false,
+                uses_nix_externals: false,
+                nix_blocker: None,
+            },
+            embedded_file: attributes.embed_file.clone(),
+            strict: attributes.strict,
+        })))
     }
 
     // examples:
@@ -3638,9 +3743,16 @@ functions are declared separately from types.";
         start: u32,
         attributes: &mut Attributes,
     ) -> Result<u32, ParseError> {
-        // Parse the name of the attribute.
+        // Parse the name of the attribute. `derive` is a reserved word, so it
+        // is lexed as a keyword rather than as a name.
 
-        let (_, name, end) = self.expect_name()?;
+        let (name, end) = match self.maybe_one(&Token::Derive) {
+            Some((_, end)) => ("derive".into(), end),
+            None => {
+                let (_, name, end) = self.expect_name()?;
+                (name, end)
+            }
+        };
 
         let end = match name.as_str() {
             "external" => {
@@ -3655,7 +3767,25 @@ functions are declared separately from types.";
                 let _ = self.expect_one(&Token::LeftParen)?;
                 self.parse_deprecated_attribute(start, end, attributes)
             }
+            "since" => {
+                let _ = self.expect_one(&Token::LeftParen)?;
+                self.parse_since_attribute(start, end, attributes)
+            }
             "internal" => self.parse_internal_attribute(start, end, attributes),
+            "embed_file" => {
+                let _ = self.expect_one(&Token::LeftParen)?;
+                self.parse_embed_file_attribute(start, attributes)
+            }
+            "strict" => self.parse_strict_attribute(start, end, attributes),
+            "inline" => self.parse_inline_attribute(start, end, attributes),
+            "derive" => {
+                let _ = self.expect_one(&Token::LeftParen)?;
+                self.parse_derive_attribute(start, attributes)
+            }
+            "deprecated_module" => parse_error(
+                ParseErrorType::MisplacedModuleAttribute,
+                SrcSpan { start, end },
+            ),
             _ => parse_error(ParseErrorType::UnknownAttribute, SrcSpan { start, end }),
         }?;
 
@@ -3688,10 +3818,13 @@ functions are declared separately from types.";
     ) -> Result<u32, ParseError> {
         let (_, name, _) = self.expect_name()?;
 
+        // `any` gives the implementation used by every target without one of
+        // its own.
         let target = match name.as_str() {
-            "erlang" => Target::Erlang,
-            "javascript" => Target::JavaScript,
-            "nix" => Target::Nix,
+            "erlang" => Some(Target::Erlang),
+            "javascript" => Some(Target::JavaScript),
+            "nix" => Some(Target::Nix),
+            "any" => None,
             _ => return parse_error(ParseErrorType::UnknownTarget, SrcSpan::new(start, end)),
         };
 
@@ -3702,12 +3835,23 @@ functions are declared separately from types.";
         let _ = self.maybe_one(&Token::Comma);
         let (_, end) = self.expect_one(&Token::RightParen)?;
 
-        if attributes.has_external_for(target) {
-            return parse_error(ParseErrorType::DuplicateAttribute, SrcSpan { start, end });
+        let external = Some((module, function, SrcSpan { start, end }));
+        match target {
+            Some(target) if attributes.has_external_for(target) => {
+                parse_error(ParseErrorType::DuplicateAttribute, SrcSpan { start, end })
+            }
+            Some(target) => {
+                attributes.set_external_for(target, external);
+                Ok(end)
+            }
+            None if attributes.external_any.is_some() => {
+                parse_error(ParseErrorType::DuplicateAttribute, SrcSpan { start, end })
+            }
+            None => {
+                attributes.external_any = external;
+                Ok(end)
+            }
         }
-
-        attributes.set_external_for(target, Some((module, function, SrcSpan { start, end })));
-        Ok(end)
     }
 
     fn parse_deprecated_attribute(
@@ -3728,6 +3872,99 @@ functions are declared separately from types.";
         Ok(end)
     }
 
+    fn parse_since_attribute(
+        &mut self,
+        start: u32,
+        end: u32,
+        attributes: &mut Attributes,
+    ) -> Result<u32, ParseError> {
+        if attributes.since.is_some() {
+            return parse_error(ParseErrorType::DuplicateAttribute, SrcSpan::new(start, end));
+        }
+        let (version_start, version, version_end) =
+            self.expect_string().map_err(|_| ParseError {
+                error: ParseErrorType::ExpectedSinceVersion,
+                location: SrcSpan { start, end },
+            })?;
+        if Version::parse(&version).is_err() {
+            return parse_error(
+                ParseErrorType::ExpectedSinceVersion,
+                SrcSpan::new(version_start, version_end),
+            );
+        }
+        let (_, end) = self.expect_one(&Token::RightParen)?;
+        attributes.since = Some(version);
+        Ok(end)
+    }
+
+    fn parse_embed_file_attribute(
+        &mut self,
+        start: u32,
+        attributes: &mut Attributes,
+    ) -> Result<u32, ParseError> {
+        let (_, path, _) = self.expect_string()?;
+        let (_, end) = self.expect_one(&Token::RightParen)?;
+        if attributes.embed_file.is_some() {
+            return parse_error(ParseErrorType::DuplicateAttribute, SrcSpan { start, end });
+        }
+        attributes.embed_file = Some(EmbeddedFile {
+            path,
+            location: SrcSpan { start, end },
+        });
+        Ok(end)
+    }
+
+    fn parse_strict_attribute(
+        &mut self,
+        start: u32,
+        end: u32,
+        attributes: &mut Attributes,
+    ) -> Result<u32, ParseError> {
+        if attributes.strict {
+            return parse_error(ParseErrorType::DuplicateAttribute, SrcSpan::new(start, end));
+        }
+        attributes.strict = true;
+        Ok(end)
+    }
+
+    fn parse_inline_attribute(
+        &mut self,
+        start: u32,
+        end: u32,
+        attributes: &mut Attributes,
+    ) -> Result<u32, ParseError> {
+        if attributes.inline {
+            return parse_error(ParseErrorType::DuplicateAttribute, SrcSpan::new(start, end));
+        }
+        attributes.inline = true;
+        Ok(end)
+    }
+
+    fn parse_derive_attribute(
+        &mut self,
+        start: u32,
+        attributes: &mut Attributes,
+    ) -> Result<u32, ParseError> {
+        let (name_start, name, name_end) = self.expect_name()?;
+        let derive = match name.as_str() {
+            "decode" => &mut attributes.derive_decode,
+            "nix_attrs" => &mut attributes.derive_nix_attrs,
+            "nixos_options" => &mut attributes.derive_nixos_options,
+            _ => {
+                return parse_error(
+                    ParseErrorType::UnknownDerive,
+                    SrcSpan::new(name_start, name_end),
+                )
+            }
+        };
+        let (_, end) = self.expect_one(&Token::RightParen)?;
+        if derive.is_some() {
+            return parse_error(ParseErrorType::DuplicateAttribute, SrcSpan::new(start, end));
+        }
+        *derive = Some(SrcSpan::new(start, end));
+        Ok(end)
+    }
+
     fn parse_internal_attribute(
         &mut self,
         start: u32,
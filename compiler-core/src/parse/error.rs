@@ -57,9 +57,19 @@ impl ParseError {
                 "A deprecation attribute must have a string message.",
                 vec![],
             ),
+            ParseErrorType::ExpectedSinceVersion => (
+                "A since attribute must have a version string.",
+                vec!["Versions look like `1.2.0`.".into()],
+            ),
             ParseErrorType::ExpectedFunctionDefinition => {
                 ("I was expecting a function definition after this", vec![])
             }
+            ParseErrorType::ExpectedConstantDefinition => {
+                ("I was expecting a constant definition after this", vec![])
+            }
+            ParseErrorType::ExpectedCustomTypeDefinition => {
+                ("I was expecting a custom type definition after this", vec![])
+            }
             ParseErrorType::ExtraSeparator => (
                 "This is an extra delimiter",
                 vec!["Hint: Try removing it?".into()],
@@ -242,10 +252,19 @@ utf16_codepoint, utf32_codepoint, signed, unsigned, big, little, native, size, u
                 "I don't recognise this attribute",
                 vec!["Try `deprecated`, `external` or `target` instead.".into()],
             ),
+            ParseErrorType::MisplacedModuleAttribute => (
+                "This attribute must be at the top of the module",
+                vec!["It can only be given once, before any definitions.".into()],
+            ),
             ParseErrorType::DuplicateAttribute => (
                 "Duplicate attribute",
                 vec!["This attribute has already been given.".into()],
             ),
+            ParseErrorType::UnknownDerive => (
+                "I don't know how to derive this",
+                vec!["The only things that can be derived are `decode`, `nix_attrs` and \
+`nixos_options`.".into()],
+            ),
             ParseErrorType::UnknownTarget => (
                 "I don't recognise this target",
                 vec!["Try `erlang`, `javascript`, `nix`.".into()],
@@ -340,25 +359,28 @@ utf16_codepoint, utf32_codepoint, signed, unsigned, big, little, native, size, u
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseErrorType {
-    ExpectedEqual,              // expect "="
-    ExpectedExpr,               // after "->" in a case clause
-    ExpectedName,               // any token used when a Name was expected
-    ExpectedPattern,            // after ':' where a pattern is expected
-    ExpectedType,               // after ':' or '->' where a type annotation is expected
-    ExpectedUpName,             // any token used when a UpName was expected
-    ExpectedValue,              // no value after "="
-    ExpectedStatement,          // no statement after "@<name>"
-    ExpectedDefinition,         // after attributes
-    ExpectedDeprecationMessage, // after "deprecated"
-    ExpectedFunctionDefinition, // after function-only attributes
-    ExprLparStart,              // it seems "(" was used to start an expression
-    ExtraSeparator,             // #(1,,) <- the 2nd comma is an extra separator
-    IncorrectName,              // UpName or DiscardName used when Name was expected
-    IncorrectUpName,            // Name or DiscardName used when UpName was expected
-    InvalidBitArraySegment,     // <<7:hello>> `hello` is an invalid BitArray segment
-    InvalidBitArrayUnit,        // in <<1:unit(x)>> x must be 1 <= x <= 256
-    InvalidTailPattern,         // only name and _name are allowed after ".." in list pattern
-    InvalidTupleAccess,         // only positive int literals for tuple access
+    ExpectedEqual,                // expect "="
+    ExpectedExpr,                 // after "->" in a case clause
+    ExpectedName,                 // any token used when a Name was expected
+    ExpectedPattern,              // after ':' where a pattern is expected
+    ExpectedType,                 // after ':' or '->' where a type annotation is expected
+    ExpectedUpName,               // any token used when a UpName was expected
+    ExpectedValue,                // no value after "="
+    ExpectedStatement,            // no statement after "@<name>"
+    ExpectedDefinition,           // after attributes
+    ExpectedDeprecationMessage,   // after "deprecated"
+    ExpectedSinceVersion,         // after "since"
+    ExpectedFunctionDefinition,   // after function-only attributes
+    ExpectedConstantDefinition,   // after constant-only attributes
+    ExpectedCustomTypeDefinition, // after custom type-only attributes
+    ExprLparStart,                // it seems "(" was used to start an expression
+    ExtraSeparator,               // #(1,,) <- the 2nd comma is an extra separator
+    IncorrectName,                // UpName or DiscardName used when Name was expected
+    IncorrectUpName,              // Name or DiscardName used when UpName was expected
+    InvalidBitArraySegment,       // <<7:hello>> `hello` is an invalid BitArray segment
+    InvalidBitArrayUnit,          // in <<1:unit(x)>> x must be 1 <= x <= 256
+    InvalidTailPattern,           // only name and _name are allowed after ".." in list pattern
+    InvalidTupleAccess,           // only positive int literals for tuple access
     LexError {
         error: LexicalError,
     },
@@ -372,7 +394,9 @@ pub enum ParseErrorType {
     TooManyArgHoles, // a function call can have at most 1 arg hole
     DuplicateAttribute, // an attribute was used more than once
     UnknownAttribute, // an attribute was used that is not known
+    MisplacedModuleAttribute, // `@deprecated_module` given after the start of the module
     UnknownTarget, // an unknown target was used
+    UnknownDerive, // something other than `decode`, `nix_attrs` or `nixos_options` was given to `@derive`
     ListSpreadWithoutElements, // Pointless spread: `[..xs]`
     ListSpreadFollowedByElements, // trying to append something after the spread: `[..xs, x]`
     LowcaseBooleanPattern, // most likely user meant True or False in patterns
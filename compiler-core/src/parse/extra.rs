@@ -11,6 +11,9 @@ pub struct ModuleExtra {
     pub comments: Vec<SrcSpan>,
     pub empty_lines: Vec<u32>,
     pub new_lines: Vec<u32>,
+    /// The end of the list and tuple literals whose last element is followed
+    /// by a comma, such as `[1, 2,]`.
+    pub trailing_commas: Vec<u32>,
 }
 
 impl ModuleExtra {
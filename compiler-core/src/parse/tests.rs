@@ -4,6 +4,7 @@ use crate::parse::error::{
 };
 use crate::parse::lexer::make_tokenizer;
 use crate::parse::token::Token;
+use crate::type_::Deprecation;
 use crate::warning::WarningEmitter;
 use camino::Utf8PathBuf;
 
@@ -641,6 +642,67 @@ pub fn main() -> Nil {
     );
 }
 
+#[test]
+fn module_deprecation_attribute() {
+    let parsed = crate::parse::parse_module(
+        Utf8PathBuf::from("test/path"),
+        r#"@deprecated_module("Use wobble instead")
+
+pub fn main() -> Nil {
+  Nil
+}
+"#,
+        &WarningEmitter::null(),
+    )
+    .expect("should parse");
+    assert_eq!(
+        parsed.module.deprecation,
+        Deprecation::Deprecated {
+            message: "Use wobble instead".into()
+        }
+    );
+}
+
+#[test]
+fn misplaced_module_deprecation_attribute() {
+    assert_module_error!(
+        r#"
+pub fn main() -> Nil {
+  Nil
+}
+
+@deprecated_module("1")
+pub fn wibble() -> Nil {
+  Nil
+}
+"#
+    );
+}
+
+#[test]
+fn since_without_version() {
+    assert_module_error!(
+        r#"
+@since()
+pub fn main() -> Nil {
+  Nil
+}
+"#
+    );
+}
+
+#[test]
+fn since_with_invalid_version() {
+    assert_module_error!(
+        r#"
+@since("1.2")
+pub fn main() -> Nil {
+  Nil
+}
+"#
+    );
+}
+
 #[test]
 fn multiple_internal_attributes() {
     assert_module_error!(
@@ -674,6 +736,122 @@ pub type Fun
     );
 }
 
+#[test]
+fn embed_file_attribute_with_non_const_definition() {
+    assert_module_error!(
+        r#"
+@embed_file("priv/data.txt")
+pub fn main() -> String {
+  ""
+}
+"#
+    );
+}
+
+#[test]
+fn multiple_embed_file_attributes() {
+    assert_module_error!(
+        r#"
+@embed_file("priv/data.txt")
+@embed_file("priv/data.txt")
+pub const data: String
+"#
+    );
+}
+
+#[test]
+fn strict_attribute_with_non_const_definition() {
+    assert_module_error!(
+        r#"
+@strict
+pub fn main() -> Int {
+  1
+}
+"#
+    );
+}
+
+#[test]
+fn multiple_strict_attributes() {
+    assert_module_error!(
+        r#"
+@strict
+@strict
+pub const wibble = 1
+"#
+    );
+}
+
+#[test]
+fn inline_attribute_with_non_fn_definition() {
+    assert_module_error!(
+        r#"
+@inline
+pub const wibble = 1
+"#
+    );
+}
+
+#[test]
+fn multiple_inline_attributes() {
+    assert_module_error!(
+        r#"
+@inline
+@inline
+pub fn wibble() {
+  1
+}
+"#
+    );
+}
+
+#[test]
+fn derive_attribute_with_non_custom_type_definition() {
+    assert_module_error!(
+        r#"
+@derive(decode)
+pub fn main() -> Int {
+  1
+}
+"#
+    );
+}
+
+#[test]
+fn derive_nix_attrs_with_non_custom_type_definition() {
+    assert_module_error!(
+        r#"
+@derive(nix_attrs)
+const wibble = 1
+"#
+    );
+}
+
+#[test]
+fn unknown_derive() {
+    assert_module_error!(
+        r#"
+@derive(json)
+pub type Wibble {
+  Wibble(name: String)
+}
+"#
+    );
+}
+
+#[test]
+fn multiple_derive_attributes() {
+    assert_module_error!(
+        r#"
+@derive(decode)
+@derive(decode)
+pub type Wibble {
+  Wibble(name: String)
+}
+"#
+    );
+}
+
 #[test]
 fn attributes_with_improper_definition() {
     assert_module_error!(
@@ -82,6 +82,18 @@ impl ProjectPaths {
         self.build_directory().join("erlang-shipment")
     }
 
+    pub fn nix_overlay_directory(&self) -> Utf8PathBuf {
+        self.build_directory().join("nix-overlay")
+    }
+
+    pub fn nix_closure_directory(&self) -> Utf8PathBuf {
+        self.build_directory().join("nix-closure")
+    }
+
+    pub fn nix_closure_tarball(&self) -> Utf8PathBuf {
+        self.build_directory().join("nix-closure.tar.gz")
+    }
+
     pub fn build_documentation_directory(&self, package: &str) -> Utf8PathBuf {
         self.build_directory_for_mode(Mode::Dev)
             .join("docs")
@@ -126,6 +138,26 @@ pub fn global_package_cache_package_tarball(package_name: &str, version: &str) -
     global_packages_cache().join(format!("{package_name}-{version}.tar"))
 }
 
+/// The extracted source of a release of a Hex package, shared by all the
+/// projects that depend on it. The checksum is part of the key so that a
+/// release republished with different contents gets its own directory.
+pub fn global_package_store_package(
+    package_name: &str,
+    version: &str,
+    outer_checksum: &str,
+) -> Utf8PathBuf {
+    default_global_gleam_cache()
+        .join("store")
+        .join(format!("{package_name}-{version}-{outer_checksum}"))
+}
+
+/// The licences of a release of a Hex package, as looked up with the Hex API.
+pub fn global_package_cache_package_licences(package_name: &str, version: &str) -> Utf8PathBuf {
+    global_hexpm_cache()
+        .join("licences")
+        .join(format!("{package_name}-{version}.json"))
+}
+
 pub fn global_hexpm_credentials_path() -> Utf8PathBuf {
     global_hexpm_cache().join("credentials")
 }
@@ -168,4 +200,7 @@ fn paths() {
 
     assert!(global_package_cache_package_tarball("elli", "1.0.0")
         .ends_with("hex/hexpm/packages/elli-1.0.0.tar"));
+
+    assert!(global_package_store_package("elli", "1.0.0", "0316")
+        .ends_with("gleam/store/elli-1.0.0-0316"));
 }
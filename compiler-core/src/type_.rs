@@ -635,6 +635,7 @@ impl ValueConstructorVariant {
                 uses_javascript_externals: false,
                 uses_erlang_externals: false,
                 uses_nix_externals: false,
+                nix_blocker: None,
             },
 
             ValueConstructorVariant::ModuleFn {
@@ -642,7 +643,7 @@ impl ValueConstructorVariant {
             }
             | ValueConstructorVariant::ModuleConstant {
                 implementations, ..
-            } => *implementations,
+            } => implementations.clone(),
         }
     }
 
@@ -744,6 +745,9 @@ pub struct ModuleInterface {
     pub warnings: Vec<Warning>,
     /// The minimum Gleam version needed to use this module.
     pub minimum_required_version: Version,
+    /// Whether the whole module has been deprecated, in which case importing
+    /// it emits a warning.
+    pub deprecation: Deprecation,
 }
 
 impl ModuleInterface {
@@ -851,6 +855,45 @@ impl ModuleInterface {
         })
     }
 
+    /// The names of the benchmarks defined by this module which can be run on
+    /// the given target: its public functions without arguments whose names
+    /// start with `bench_`, in alphabetical order.
+    pub fn benchmark_functions(&self, target: Target) -> Vec<EcoString> {
+        self.runnable_functions(target, |name| name.starts_with("bench_"))
+    }
+
+    /// The names of the tests defined by this module which can be run on the
+    /// given target: its public functions without arguments whose names end
+    /// with `_test`, in alphabetical order. These are the functions gleeunit
+    /// runs.
+    pub fn test_functions(&self, target: Target) -> Vec<EcoString> {
+        self.runnable_functions(target, |name| name.ends_with("_test"))
+    }
+
+    fn runnable_functions(
+        &self,
+        target: Target,
+        is_selected: impl Fn(&str) -> bool,
+    ) -> Vec<EcoString> {
+        self.values
+            .iter()
+            .filter(|(name, value)| {
+                is_selected(name)
+                    && value.publicity.is_importable()
+                    && matches!(
+                        &value.variant,
+                        ValueConstructorVariant::ModuleFn {
+                            arity: 0,
+                            implementations,
+                            ..
+                        } if implementations.supports(target)
+                    )
+            })
+            .map(|(name, _)| name.clone())
+            .sorted()
+            .collect_vec()
+    }
+
     pub fn public_value_names(&self) -> Vec<EcoString> {
         self.values
             .iter()
@@ -1064,6 +1107,7 @@ pub struct TypeConstructor {
     pub parameters: Vec<Arc<Type>>,
     pub type_: Arc<Type>,
     pub deprecation: Deprecation,
+    pub since: Option<EcoString>,
     pub documentation: Option<EcoString>,
 }
 impl TypeConstructor {
@@ -1077,6 +1121,7 @@ impl TypeConstructor {
 pub struct ValueConstructor {
     pub publicity: Publicity,
     pub deprecation: Deprecation,
+    pub since: Option<EcoString>,
     pub variant: ValueConstructorVariant,
     pub type_: Arc<Type>,
 }
@@ -5,6 +5,10 @@ use crate::{
     ast::{Publicity, PIPE_VARIABLE},
     build::Target,
     error::edit_distance,
+    type_::{
+        error::{CallSite, NixBlockerCall},
+        expression::{Implementations, NixBlocker},
+    },
     uid::UniqueIdGenerator,
 };
 
@@ -38,6 +42,11 @@ pub struct Environment<'a> {
     pub imported_module_aliases: HashMap<EcoString, SrcSpan>,
     pub unused_module_aliases: HashMap<EcoString, UnusedModuleAlias>,
 
+    /// Unqualified imported values that have been shadowed by a definition
+    /// with the same name, along with the location of the first such
+    /// definition. Used to explain why the import is unused.
+    pub shadowed_imported_values: HashMap<EcoString, SrcSpan>,
+
     /// Values defined in the current function (or the prelude)
     pub scope: im::HashMap<EcoString, ValueConstructor>,
 
@@ -111,6 +120,7 @@ impl<'a> Environment<'a> {
             importable_modules,
             imported_module_aliases: HashMap::new(),
             unused_module_aliases: HashMap::new(),
+            shadowed_imported_values: HashMap::new(),
             current_module,
             entity_usages: vec![HashMap::new()],
             target_support,
@@ -131,11 +141,17 @@ pub enum EntityKind {
     PrivateConstant,
     // String here is the type constructor's type name
     PrivateTypeConstructor(EcoString),
-    PrivateFunction,
+    PrivateFunction {
+        /// The span of the whole function definition.
+        definition_location: SrcSpan,
+    },
     ImportedConstructor,
     ImportedType,
     ImportedValue,
-    PrivateType,
+    PrivateType {
+        /// The span of the whole type definition.
+        definition_location: SrcSpan,
+    },
     Variable {
         /// The origin of the variable so we know how it could be
         /// rewritten to ignore it when unused.
@@ -223,6 +239,7 @@ impl Environment<'_> {
             name,
             ValueConstructor {
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 publicity: Publicity::Private,
                 variant: ValueConstructorVariant::LocalVariable { location },
                 type_,
@@ -240,6 +257,7 @@ impl Environment<'_> {
             name,
             ValueConstructor {
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 publicity: Publicity::Private,
                 variant: ValueConstructorVariant::LocalConstant {
                     literal: literal.clone(),
@@ -258,12 +276,14 @@ impl Environment<'_> {
         type_: Arc<Type>,
         publicity: Publicity,
         deprecation: Deprecation,
+        since: Option<EcoString>,
     ) {
         let _ = self.scope.insert(
             name,
             ValueConstructor {
                 publicity,
                 deprecation,
+                since,
                 variant,
                 type_,
             },
@@ -282,6 +302,65 @@ impl Environment<'_> {
         self.scope.get(name)
     }
 
+    /// Follows the values that stop a value of the given module from running
+    /// on Nix, returning the calls leading to the one that has no Nix
+    /// implementation at all.
+    ///
+    pub fn nix_blocker_calls(
+        &self,
+        module: &EcoString,
+        implementations: &Implementations,
+    ) -> Vec<NixBlockerCall> {
+        let mut calls: Vec<NixBlockerCall> = vec![];
+        let mut caller_module = module.clone();
+        let mut blocker = implementations.nix_blocker.clone();
+
+        while let Some(NixBlocker {
+            module,
+            name,
+            location,
+        }) = blocker
+        {
+            // Recursive functions could otherwise take us around in circles.
+            if calls
+                .iter()
+                .any(|call| call.module == module && call.name == name)
+            {
+                break;
+            }
+
+            let site = if caller_module == self.current_module {
+                CallSite::Local(location)
+            } else {
+                match self.importable_modules.get(&caller_module) {
+                    Some(interface) => CallSite::Module {
+                        path: interface.src_path.clone(),
+                        line: interface.line_numbers.line_number(location.start),
+                    },
+                    None => break,
+                }
+            };
+
+            let value = if module == self.current_module {
+                self.module_values.get(&name)
+            } else {
+                self.importable_modules
+                    .get(&module)
+                    .and_then(|interface| interface.values.get(&name))
+            };
+            blocker = value.and_then(|value| value.variant.implementations().nix_blocker);
+
+            calls.push(NixBlockerCall {
+                module: module.clone(),
+                name,
+                site,
+            });
+            caller_module = module;
+        }
+
+        calls
+    }
+
     /// Lookup a module constant in the current scope.
     ///
     pub fn get_module_const(&mut self, name: &EcoString) -> Option<&ValueConstructor> {
@@ -586,6 +665,20 @@ impl Environment<'_> {
     ) {
         use EntityKind::*;
 
+        // A local variable can shadow an unqualified imported value, making
+        // the import unused if it's never referred to before that.
+        if let (Variable { .. }, Some((ImportedValue, _, _))) = (
+            &kind,
+            self.entity_usages
+                .first()
+                .and_then(|imports| imports.get(&name)),
+        ) {
+            let _ = self
+                .shadowed_imported_values
+                .entry(name.clone())
+                .or_insert(location);
+        }
+
         match self
             .entity_usages
             .last_mut()
@@ -597,12 +690,18 @@ impl Environment<'_> {
             // TODO: Improve this so that we can tell if an imported overridden
             // type is actually used or not by tracking whether usages apply to
             // the value or type scope
-            Some((ImportedType | PrivateType, _, _)) => {}
+            Some((ImportedType | PrivateType { .. }, _, _)) => {}
 
-            Some((kind, location, false)) => {
+            Some((kind, previous_location, false)) => {
                 // an entity was overwritten in the top most scope without being used
+                if kind == ImportedValue {
+                    let _ = self
+                        .shadowed_imported_values
+                        .entry(name.clone())
+                        .or_insert(location);
+                }
                 let mut unused = HashMap::with_capacity(1);
-                let _ = unused.insert(name, (kind, location, false));
+                let _ = unused.insert(name, (kind, previous_location, false));
                 self.handle_unused(unused, problems);
             }
 
@@ -670,6 +769,7 @@ impl Environment<'_> {
                     name,
                     imported: true,
                     location,
+                    definition_location: None,
                 },
                 EntityKind::ImportedConstructor => Warning::UnusedConstructor {
                     name,
@@ -684,13 +784,26 @@ impl Environment<'_> {
                     imported: false,
                     location,
                 },
-                EntityKind::PrivateFunction => Warning::UnusedPrivateFunction { name, location },
-                EntityKind::PrivateType => Warning::UnusedType {
+                EntityKind::PrivateFunction {
+                    definition_location,
+                } => Warning::UnusedPrivateFunction {
+                    name,
+                    location,
+                    definition_location,
+                },
+                EntityKind::PrivateType {
+                    definition_location,
+                } => Warning::UnusedType {
                     name,
                     imported: false,
                     location,
+                    definition_location: Some(definition_location),
+                },
+                EntityKind::ImportedValue => Warning::UnusedImportedValue {
+                    shadowed_by: self.shadowed_imported_values.get(&name).copied(),
+                    name,
+                    location,
                 },
-                EntityKind::ImportedValue => Warning::UnusedImportedValue { name, location },
                 EntityKind::Variable { origin } => Warning::UnusedVariable { location, origin },
             };
 
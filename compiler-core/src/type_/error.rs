@@ -108,6 +108,25 @@ pub enum UnknownField {
     NoFields,
 }
 
+/// A call made by a value that can't run on Nix, to another value that can't
+/// either. Following these calls leads to the value actually lacking a Nix
+/// implementation.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct NixBlockerCall {
+    /// The module of the value being called.
+    pub module: EcoString,
+    pub name: EcoString,
+    pub site: CallSite,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum CallSite {
+    /// The call is in the module being compiled.
+    Local(SrcSpan),
+    /// The call is in another module, at the given line of its source file.
+    Module { path: Utf8PathBuf, line: u32 },
+}
+
 /// A suggestion for an unknown module
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ModuleSuggestion {
@@ -286,9 +305,13 @@ pub enum Error {
         label: EcoString,
     },
 
+    /// A public definition uses a private type in its signature.
+    /// `type_location` is the location of the private type's definition, if
+    /// it is in the current module.
     PrivateTypeLeak {
         location: SrcSpan,
         leaked: Type,
+        type_location: Option<SrcSpan>,
     },
 
     UnexpectedLabelledArg {
@@ -399,6 +422,16 @@ pub enum Error {
         cycle: Vec<EcoString>,
     },
 
+    /// Module constants were defined in terms of each other, so there is no
+    /// order in which they could be evaluated.
+    /// e.g.
+    ///     const a = b
+    ///     const b = a
+    RecursiveConstant {
+        location: SrcSpan,
+        cycle: Vec<EcoString>,
+    },
+
     /// A function has been given an external implementation but not all the
     /// type annotations have been given. The annotations are required as we
     /// cannot infer the types of external implementations.
@@ -418,6 +451,16 @@ pub enum Error {
     UnsupportedExpressionTarget {
         location: SrcSpan,
         target: Target,
+        module: EcoString,
+        name: EcoString,
+        /// The package defining the value, or `None` if it's the package being
+        /// compiled.
+        package: Option<EcoString>,
+        /// The targets the value does have an implementation for.
+        supported_targets: Vec<Target>,
+        /// When compiling to Nix, the calls leading from the value to the one
+        /// that has no Nix implementation.
+        nix_blocker_calls: Vec<NixBlockerCall>,
     },
 
     /// A function's JavaScript implementation has been given but it does not
@@ -452,6 +495,40 @@ pub enum Error {
         name: EcoString,
     },
 
+    /// A function has an external implementation for a target the package
+    /// has denied externals for in its config.
+    DeniedExternal {
+        location: SrcSpan,
+        name: EcoString,
+        target: Target,
+    },
+
+    /// A function has an `@external(any, ...)` implementation along with an
+    /// external implementation for every target it's valid on, so it would
+    /// never be used.
+    UnusedAnyExternal {
+        location: SrcSpan,
+        name: EcoString,
+    },
+
+    /// A function's Nix implementation is not exported by the Nix file it
+    /// refers to.
+    UnknownNixExternalFunction {
+        location: SrcSpan,
+        module: EcoString,
+        function: EcoString,
+    },
+
+    /// A function's Nix implementation doesn't take as many curried
+    /// arguments as the Gleam function.
+    IncorrectNixExternalArity {
+        location: SrcSpan,
+        module: EcoString,
+        function: EcoString,
+        expected: usize,
+        given: usize,
+    },
+
     /// A case expression is missing one or more patterns to match all possible
     /// values of the type.
     InexhaustiveCaseExpression {
@@ -598,6 +675,109 @@ pub enum Error {
     DeprecatedVariantOnDeprecatedType {
         location: SrcSpan,
     },
+
+    /// A custom type has the `@derive(decode)` attribute but a decoder can't
+    /// be generated for it.
+    ///
+    /// ```gleam
+    /// @derive(decode)
+    /// pub type Wibble {
+    ///   Wibble(fn() -> Int)
+    /// }
+    /// ```
+    CannotDeriveDecoder {
+        location: SrcSpan,
+        reason: DeriveDecoderProblem,
+    },
+
+    /// A custom type has the `@derive(nix_attrs)` attribute but its attribute
+    /// set conversion functions can't be generated.
+    ///
+    /// ```gleam
+    /// @derive(nix_attrs)
+    /// pub type Wibble {
+    ///   Wibble(Int)
+    /// }
+    /// ```
+    CannotDeriveNixAttrs {
+        location: SrcSpan,
+        reason: DeriveNixAttrsProblem,
+    },
+
+    /// A custom type has the `@derive(nixos_options)` attribute but a NixOS
+    /// option declaration can't be generated for it.
+    ///
+    /// ```gleam
+    /// @derive(nixos_options)
+    /// pub type Wibble {
+    ///   Wibble(callback: fn() -> Int)
+    /// }
+    /// ```
+    CannotDeriveNixosOptions {
+        location: SrcSpan,
+        reason: DeriveNixosOptionsProblem,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeriveDecoderProblem {
+    /// The `gleam/dynamic` module, whose functions the decoder is built out
+    /// of, hasn't been imported.
+    MissingDynamicImport,
+    /// The decoder refers to the prelude as `gleam`, but another module is
+    /// imported with that name.
+    PreludeAliasTaken {
+        module: EcoString,
+    },
+    TypeParameters,
+    NoConstructors,
+    UnlabelledField {
+        constructor: EcoString,
+    },
+    TooManyFields {
+        constructor: EcoString,
+        limit: usize,
+    },
+    /// A field has a type there's no known decoder for.
+    UnsupportedType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeriveNixAttrsProblem {
+    /// The type is private or opaque, so its records can't be handed to Nix
+    /// code outside of the module.
+    NotPublic,
+    NoConstructors,
+    UnlabelledField {
+        constructor: EcoString,
+    },
+    /// A field is labelled `type` while the type has several constructors,
+    /// which the `type` attribute tells apart.
+    TypeField {
+        constructor: EcoString,
+    },
+    /// A generated function would have the same name as a top level value
+    /// of the module.
+    NameClash {
+        name: EcoString,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeriveNixosOptionsProblem {
+    NotPublic,
+    TypeParameters,
+    /// The type is neither a single record, whose fields become options, nor
+    /// an enumeration of constructors without fields.
+    UnsupportedShape,
+    UnlabelledField {
+        constructor: EcoString,
+    },
+    NameClash {
+        name: EcoString,
+    },
+    /// A field has a type with no matching NixOS option type.
+    UnsupportedType,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -631,7 +811,7 @@ pub enum LiteralCollectionKind {
     Record,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum Named {
     Type,
     TypeAlias,
@@ -722,6 +902,9 @@ pub enum Warning {
         location: SrcSpan,
         imported: bool,
         name: EcoString,
+        /// The span of the whole definition of a private type, including its
+        /// documentation, so that it can be removed entirely.
+        definition_location: Option<SrcSpan>,
     },
 
     UnusedConstructor {
@@ -733,6 +916,9 @@ pub enum Warning {
     UnusedImportedValue {
         location: SrcSpan,
         name: EcoString,
+        /// The definition with the same name that shadows the imported value,
+        /// if there is one.
+        shadowed_by: Option<SrcSpan>,
     },
 
     UnusedImportedModule {
@@ -754,6 +940,9 @@ pub enum Warning {
     UnusedPrivateFunction {
         location: SrcSpan,
         name: EcoString,
+        /// The span of the whole definition, including its documentation and
+        /// attributes, so that it can be removed entirely.
+        definition_location: SrcSpan,
     },
 
     UnusedVariable {
@@ -784,6 +973,16 @@ pub enum Warning {
         location: SrcSpan,
         message: EcoString,
         layer: Layer,
+        /// The module the deprecated item is defined in.
+        module: EcoString,
+        name: EcoString,
+    },
+
+    /// An import of a module marked with `@deprecated_module`.
+    DeprecatedModule {
+        location: SrcSpan,
+        name: EcoString,
+        message: EcoString,
     },
 
     UnreachableCaseClause {
@@ -859,6 +1058,19 @@ pub enum Warning {
         leaked: Type,
     },
 
+    /// A public definition uses a private type in its signature, and the
+    /// package is configured to report this as a warning rather than an error.
+    ///
+    /// ```toml
+    /// [glistix]
+    /// private-type-leaks = "warning"
+    /// ```
+    PrivateTypeLeak {
+        location: SrcSpan,
+        leaked: Type,
+        type_location: Option<SrcSpan>,
+    },
+
     RedundantAssertAssignment {
         location: SrcSpan,
     },
@@ -917,6 +1129,56 @@ pub enum Warning {
     JavaScriptIntUnsafe {
         location: SrcSpan,
     },
+
+    /// When a function's Nix implementation is in a file that doesn't exist.
+    /// The path is relative to the module declaring the external.
+    ///
+    MissingNixExternalFile {
+        location: SrcSpan,
+        module: EcoString,
+    },
+
+    /// When a custom type constructor has the same name as a constructor from
+    /// the prelude, which the generated code for some targets can't tell apart
+    /// from it.
+    ///
+    PreludeConstructorShadowed {
+        location: SrcSpan,
+        name: EcoString,
+        targets: Vec<Target>,
+    },
+
+    /// When a type, function or constant of a module compiled to Nix has the
+    /// same name as something the prelude exports through the package's
+    /// `gleam.nix`, which Nix code bringing both into scope can't tell apart.
+    ///
+    PreludeNameShadowed {
+        location: SrcSpan,
+        name: EcoString,
+        kind: Named,
+    },
+
+    /// When a string literal passed as a relative path to a function of the
+    /// `glistix/nix` module isn't a valid one, which would only be noticed
+    /// once the generated code is evaluated.
+    ///
+    InvalidNixPath {
+        location: SrcSpan,
+        path: EcoString,
+        reason: InvalidNixPathReason,
+    },
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub enum InvalidNixPathReason {
+    Empty,
+    /// The path starts with `/`, so it can't be relative to another one.
+    Absolute,
+    /// The path starts with `~`, which Nix only expands in path literals.
+    HomeRelative,
+    /// The path contains whitespace, which Nix path literals can't contain and
+    /// is most likely a typo.
+    Whitespace,
 }
 
 #[derive(Debug, Eq, Copy, PartialEq, Clone, serde::Serialize, serde::Deserialize)]
@@ -1037,6 +1299,7 @@ impl Error {
             | Error::ArgumentNameAlreadyUsed { location, .. }
             | Error::UnlabelledAfterlabelled { location, .. }
             | Error::RecursiveTypeAlias { location, .. }
+            | Error::RecursiveConstant { location, .. }
             | Error::ExternalMissingAnnotation { location, .. }
             | Error::NoImplementation { location, .. }
             | Error::UnsupportedExpressionTarget { location, .. }
@@ -1044,6 +1307,10 @@ impl Error {
             | Error::InvalidExternalJavascriptFunction { location, .. }
             | Error::InvalidExternalNixModule { location, .. }
             | Error::InvalidExternalNixFunction { location, .. }
+            | Error::DeniedExternal { location, .. }
+            | Error::UnusedAnyExternal { location, .. }
+            | Error::UnknownNixExternalFunction { location, .. }
+            | Error::IncorrectNixExternalArity { location, .. }
             | Error::InexhaustiveCaseExpression { location, .. }
             | Error::InexhaustiveLetAssignment { location, .. }
             | Error::UnusedTypeAliasParameter { location, .. }
@@ -1058,7 +1325,10 @@ impl Error {
             | Error::UseFnIncorrectArity { location, .. }
             | Error::BadName { location, .. }
             | Error::AllVariantsDeprecated { location }
-            | Error::DeprecatedVariantOnDeprecatedType { location } => location.start,
+            | Error::DeprecatedVariantOnDeprecatedType { location }
+            | Error::CannotDeriveDecoder { location, .. }
+            | Error::CannotDeriveNixAttrs { location, .. }
+            | Error::CannotDeriveNixosOptions { location, .. } => location.start,
             Error::UnknownLabels { unknown, .. } => {
                 unknown.iter().map(|(_, s)| s.start).min().unwrap_or(0)
             }
@@ -1110,17 +1380,23 @@ impl Warning {
             | Warning::InefficientEmptyListCheck { location, .. }
             | Warning::TransitiveDependencyImported { location, .. }
             | Warning::DeprecatedItem { location, .. }
+            | Warning::DeprecatedModule { location, .. }
             | Warning::UnreachableCaseClause { location, .. }
             | Warning::CaseMatchOnLiteralCollection { location, .. }
             | Warning::CaseMatchOnLiteralValue { location, .. }
             | Warning::OpaqueExternalType { location, .. }
             | Warning::InternalTypeLeak { location, .. }
+            | Warning::PrivateTypeLeak { location, .. }
             | Warning::RedundantAssertAssignment { location, .. }
             | Warning::TodoOrPanicUsedAsFunction { location, .. }
             | Warning::UnreachableCodeAfterPanic { location, .. }
             | Warning::RedundantPipeFunctionCapture { location, .. }
             | Warning::FeatureRequiresHigherGleamVersion { location, .. }
-            | Warning::JavaScriptIntUnsafe { location, .. } => *location,
+            | Warning::JavaScriptIntUnsafe { location, .. }
+            | Warning::MissingNixExternalFile { location, .. }
+            | Warning::PreludeConstructorShadowed { location, .. }
+            | Warning::PreludeNameShadowed { location, .. }
+            | Warning::InvalidNixPath { location, .. } => *location,
         }
     }
 
@@ -12,16 +12,17 @@ use crate::{
         UseAssignment, RECORD_UPDATE_VARIABLE, USE_ASSIGNMENT_VARIABLE,
     },
     build::Target,
-    exhaustiveness::{self, Reachability},
+    exhaustiveness::{self, CompiledCase, Reachability},
 };
 use hexpm::version::Version;
 use id_arena::Arena;
 use im::hashmap;
 use itertools::Itertools;
 use num_bigint::BigInt;
+use strum::IntoEnumIterator;
 use vec1::Vec1;
 
-#[derive(Clone, Copy, Debug, Eq, PartialOrd, Ord, PartialEq, Serialize)]
+#[derive(Clone, Debug, Eq, PartialOrd, Ord, PartialEq, Serialize)]
 pub struct Implementations {
     /// Wether the function has a pure-gleam implementation.
     ///
@@ -51,6 +52,19 @@ pub struct Implementations {
     /// Whether the function has an implementation that uses external Nix
     /// code.
     pub uses_nix_externals: bool,
+    /// The first value used by the function that stopped it from running on
+    /// Nix, if any.
+    pub nix_blocker: Option<NixBlocker>,
+}
+
+/// A value that can't run on Nix, used by a function that would otherwise
+/// support it.
+#[derive(Clone, Debug, Eq, PartialOrd, Ord, PartialEq, Serialize)]
+pub struct NixBlocker {
+    pub module: EcoString,
+    pub name: EcoString,
+    /// Where the value is used, in the module of the function using it.
+    pub location: SrcSpan,
 }
 
 impl Implementations {
@@ -63,6 +77,7 @@ impl Implementations {
             uses_javascript_externals: false,
             uses_erlang_externals: false,
             uses_nix_externals: false,
+            nix_blocker: None,
         }
     }
 }
@@ -112,6 +127,7 @@ impl Implementations {
             can_run_on_erlang: other_can_run_on_erlang,
             can_run_on_javascript: other_can_run_on_javascript,
             can_run_on_nix: other_can_run_on_nix,
+            nix_blocker: _,
         } = implementations;
         let FunctionDefinition {
             has_body: _,
@@ -157,6 +173,14 @@ impl Implementations {
         self.uses_nix_externals = self.uses_nix_externals || *other_uses_nix_externals;
     }
 
+    /// The targets supported by the given implementations, when they aren't
+    /// pure Gleam.
+    pub fn supported_targets(&self) -> Vec<Target> {
+        Target::iter()
+            .filter(|target| self.supports(*target))
+            .collect()
+    }
+
     /// Returns true if the current target is supported by the given
     /// implementations.
     /// If something has a pure gleam implementation then it supports all
@@ -257,6 +281,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             uses_erlang_externals: definition.has_erlang_external,
             uses_javascript_externals: definition.has_javascript_external,
             uses_nix_externals: definition.has_nix_external,
+            nix_blocker: None,
         };
 
         hydrator.permit_holes(true);
@@ -939,7 +964,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
 
     fn infer_var(&mut self, name: EcoString, location: SrcSpan) -> Result<TypedExpr, Error> {
         let constructor = self.infer_value_constructor(&None, &name, &location)?;
-        self.narrow_implementations(location, &constructor.variant)?;
+        self.narrow_implementations(location, &name, &constructor.variant)?;
         Ok(TypedExpr::Var {
             constructor,
             location,
@@ -950,22 +975,40 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
     fn narrow_implementations(
         &mut self,
         location: SrcSpan,
+        name: &EcoString,
         variant: &ValueConstructorVariant,
     ) -> Result<(), Error> {
-        let variant_implementations = match variant {
+        // Functions may have been imported under a different name, but
+        // constants don't keep track of theirs.
+        let (module, defined_name, variant_implementations) = match variant {
             ValueConstructorVariant::ModuleConstant {
-                implementations, ..
-            } => implementations,
+                module,
+                implementations,
+                ..
+            } => (module, name, implementations),
             ValueConstructorVariant::ModuleFn {
-                implementations, ..
-            } => implementations,
+                module,
+                name: defined_name,
+                implementations,
+                ..
+            } => (module, defined_name, implementations),
             ValueConstructorVariant::Record { .. }
             | ValueConstructorVariant::LocalVariable { .. }
             | ValueConstructorVariant::LocalConstant { .. } => return Ok(()),
         };
 
+        let could_run_on_nix = self.implementations.can_run_on_nix;
         self.implementations
             .update_from_use(variant_implementations, &self.current_function_definition);
+        // Remember what first stopped the function from running on Nix, so
+        // uses of it can point to the actual culprit.
+        if could_run_on_nix && !self.implementations.can_run_on_nix {
+            self.implementations.nix_blocker = Some(NixBlocker {
+                module: module.clone(),
+                name: defined_name.clone(),
+                location,
+            });
+        }
 
         if self.environment.target_support.is_enforced()
             // If the value used doesn't have an implementation that can be used
@@ -976,9 +1019,25 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                     .current_function_definition
                     .has_external_for_target(self.environment.target)
         {
+            let package = self
+                .environment
+                .importable_modules
+                .get(module)
+                .map(|module| module.package.clone())
+                .filter(|package| *package != self.environment.current_package);
             Err(Error::UnsupportedExpressionTarget {
                 target: self.environment.target,
                 location,
+                module: module.clone(),
+                name: name.clone(),
+                package,
+                supported_targets: variant_implementations.supported_targets(),
+                nix_blocker_calls: match self.environment.target {
+                    Target::Nix => self
+                        .environment
+                        .nix_blocker_calls(module, variant_implementations),
+                    Target::Erlang | Target::JavaScript => vec![],
+                },
             })
         } else {
             Ok(())
@@ -1497,9 +1556,8 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
 
         self.previous_panics = all_clauses_panic || any_subject_panics;
 
-        if let Err(e) = self.check_case_exhaustiveness(location, &subject_types, &typed_clauses) {
-            self.problems.error(e);
-        };
+        let compiled_case =
+            self.check_case_exhaustiveness(location, &subject_types, &typed_clauses);
 
         // We track if the case expression is used like an if: that is all its
         // patterns are discarded and there's at least a guard. For example:
@@ -1524,6 +1582,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             type_: return_type,
             subjects: typed_subjects,
             clauses: typed_clauses,
+            compiled_case,
         }
     }
 
@@ -2252,6 +2311,8 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                     location: select_location,
                     message: message.clone(),
                     layer: Layer::Value,
+                    module: module.name.clone(),
+                    name: label.clone(),
                 })
             }
 
@@ -2265,7 +2326,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
 
         let type_ = self.instantiate(constructor.type_, &mut hashmap![]);
 
-        self.narrow_implementations(select_location, &constructor.variant)?;
+        self.narrow_implementations(select_location, &label, &constructor.variant)?;
 
         let constructor = match &constructor.variant {
             variant @ ValueConstructorVariant::ModuleFn { name, module, .. } => {
@@ -2464,6 +2525,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             constructor: ValueConstructor {
                 publicity: Publicity::Private,
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: record_type,
                 variant: ValueConstructorVariant::LocalVariable {
                     location: record_location,
@@ -2816,24 +2878,37 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             variant,
             type_,
             deprecation,
+            since,
         } = constructor;
 
         // Emit a warning if the value being used is deprecated.
         if let Deprecation::Deprecated { message } = &deprecation {
+            let module = match &variant {
+                ValueConstructorVariant::ModuleConstant { module, .. }
+                | ValueConstructorVariant::ModuleFn { module, .. }
+                | ValueConstructorVariant::Record { module, .. } => module.clone(),
+                ValueConstructorVariant::LocalVariable { .. }
+                | ValueConstructorVariant::LocalConstant { .. } => {
+                    self.environment.current_module.clone()
+                }
+            };
             self.problems.warning(Warning::DeprecatedItem {
                 location: *location,
                 message: message.clone(),
                 layer: Layer::Value,
+                module,
+                name: name.clone(),
             })
         }
 
-        self.narrow_implementations(*location, &variant)?;
+        self.narrow_implementations(*location, name, &variant)?;
 
         // Instantiate generic variables into unbound variables for this usage
         let type_ = self.instantiate(type_, &mut hashmap![]);
         Ok(ValueConstructor {
             publicity,
             deprecation,
+            since,
             variant,
             type_,
         })
@@ -3603,6 +3678,51 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                 location,
             ),
 
+            // If this is the callback of a `use` expression and the number of
+            // patterns on the left hand side of `<-` doesn't match what the
+            // function expects we report a dedicated error right away, instead
+            // of inferring the callback on its own and failing to unify it
+            // afterwards.
+            // The callback is still typed using the argument types we know
+            // about so that we can keep reporting errors in its body.
+            (
+                Type::Fn {
+                    args: expected_arguments,
+                    ..
+                },
+                UntypedExpr::Fn {
+                    arguments,
+                    body,
+                    return_annotation,
+                    location,
+                    kind: function_kind,
+                    ..
+                },
+            ) if matches!(kind, ArgumentKind::UseCallback { .. }) => {
+                if let ArgumentKind::UseCallback {
+                    function_location,
+                    assignments_location,
+                    ..
+                } = kind
+                {
+                    self.problems.error(Error::UseCallbackIncorrectArity {
+                        call_location: function_location,
+                        pattern_location: assignments_location,
+                        expected: expected_arguments.len(),
+                        given: arguments.len(),
+                    });
+                }
+
+                return self.infer_fn(
+                    arguments,
+                    expected_arguments,
+                    body,
+                    function_kind,
+                    return_annotation,
+                    location,
+                );
+            }
+
             // Otherwise just perform normal type inference.
             (_, value) => self.infer(value),
         }?;
@@ -3767,12 +3887,14 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
         Ok(())
     }
 
+    /// Checks the clauses of a case expression cover every value of its
+    /// subjects, returning the decision tree built to do so.
     fn check_case_exhaustiveness(
         &mut self,
         location: SrcSpan,
         subject_types: &[Arc<Type>],
         clauses: &[TypedClause],
-    ) -> Result<(), Error> {
+    ) -> CompiledCase {
         use exhaustiveness::{Body, Column, Compiler, PatternArena, Row};
 
         let mut compiler = Compiler::new(self.environment, Arena::new());
@@ -3813,10 +3935,11 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
 
         // Error for missing clauses that would cause a crash
         if output.diagnostics.missing {
-            return Err(Error::InexhaustiveCaseExpression {
+            self.problems.error(Error::InexhaustiveCaseExpression {
                 location,
                 missing: output.missing_patterns(self.environment),
             });
+            return output.into_compiled_case();
         }
 
         // Emit warnings for unreachable clauses
@@ -3832,7 +3955,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             }
         }
 
-        Ok(())
+        output.into_compiled_case()
     }
 
     fn track_feature_usage(&mut self, feature_kind: FeatureKind, location: SrcSpan) {
@@ -129,6 +129,7 @@ impl Hydrator {
                     parameters,
                     type_: return_type,
                     deprecation,
+                    module: type_module,
                     ..
                 } = environment
                     .get_type_constructor(module, name)
@@ -148,6 +149,8 @@ impl Hydrator {
                             location: *location,
                             message: message.clone(),
                             layer: Layer::Type,
+                            module: type_module,
+                            name: name.clone(),
                         })
                     }
                 }
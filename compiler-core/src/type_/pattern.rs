@@ -755,6 +755,8 @@ impl<'a, 'b> PatternTyper<'a, 'b> {
                             location,
                             message: message.clone(),
                             layer: Layer::Value,
+                            module: constructor.module.clone(),
+                            name: constructor.name.clone(),
                         })
                     }
                 }
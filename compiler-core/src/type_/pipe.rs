@@ -172,6 +172,7 @@ impl<'a, 'b, 'c> PipeTyper<'a, 'b, 'c> {
             constructor: ValueConstructor {
                 publicity: Publicity::Public,
                 deprecation: Deprecation::NotDeprecated,
+                since: None,
                 type_: self.argument_type.clone(),
                 variant: ValueConstructorVariant::LocalVariable {
                     location: self.argument_location,
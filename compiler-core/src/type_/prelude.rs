@@ -9,8 +9,9 @@ use crate::{
 };
 
 use super::{
-    ModuleInterface, Type, TypeConstructor, TypeValueConstructor, TypeValueConstructorField,
-    TypeVar, TypeVariantConstructors, ValueConstructor, ValueConstructorVariant,
+    expression::Implementations, ModuleInterface, Type, TypeConstructor, TypeValueConstructor,
+    TypeValueConstructorField, TypeVar, TypeVariantConstructors, ValueConstructor,
+    ValueConstructorVariant,
 };
 use crate::type_::Deprecation::NotDeprecated;
 use std::{cell::RefCell, collections::HashMap, sync::Arc};
@@ -222,6 +223,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
     let value = |variant, type_| ValueConstructor {
         publicity: Publicity::Public,
         deprecation: NotDeprecated,
+        since: None,
         variant,
         type_,
     };
@@ -241,6 +243,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
         // prelude doesn't have real line numbers
         line_numbers: LineNumbers::new(""),
         minimum_required_version: Version::new(0, 1, 0),
+        deprecation: NotDeprecated,
     };
 
     for t in PreludeType::iter() {
@@ -253,6 +256,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
                     module: PRELUDE_MODULE_NAME.into(),
                     publicity: Publicity::Public,
                     deprecation: NotDeprecated,
+                    since: None,
                     documentation: None,
                 };
                 let _ = prelude.types.insert(BIT_ARRAY.into(), v.clone());
@@ -316,6 +320,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
                         module: PRELUDE_MODULE_NAME.into(),
                         publicity: Publicity::Public,
                         deprecation: NotDeprecated,
+                        since: None,
                         documentation: None,
                     },
                 );
@@ -331,6 +336,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
                         module: PRELUDE_MODULE_NAME.into(),
                         publicity: Publicity::Public,
                         deprecation: NotDeprecated,
+                        since: None,
                         documentation: None,
                     },
                 );
@@ -346,6 +352,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
                         module: PRELUDE_MODULE_NAME.into(),
                         publicity: Publicity::Public,
                         deprecation: NotDeprecated,
+                        since: None,
                         documentation: None,
                     },
                 );
@@ -362,6 +369,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
                         module: PRELUDE_MODULE_NAME.into(),
                         publicity: Publicity::Public,
                         deprecation: NotDeprecated,
+                        since: None,
                         documentation: None,
                     },
                 );
@@ -393,6 +401,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
                         module: PRELUDE_MODULE_NAME.into(),
                         publicity: Publicity::Public,
                         deprecation: NotDeprecated,
+                        since: None,
                         documentation: None,
                     },
                 );
@@ -422,6 +431,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
                         module: PRELUDE_MODULE_NAME.into(),
                         publicity: Publicity::Public,
                         deprecation: NotDeprecated,
+                        since: None,
                         documentation: None,
                     },
                 );
@@ -493,6 +503,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
                         module: PRELUDE_MODULE_NAME.into(),
                         publicity: Publicity::Public,
                         deprecation: NotDeprecated,
+                        since: None,
                         documentation: None,
                     },
                 );
@@ -508,6 +519,7 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
                         module: PRELUDE_MODULE_NAME.into(),
                         publicity: Publicity::Public,
                         deprecation: NotDeprecated,
+                        since: None,
                         documentation: None,
                     },
                 );
@@ -517,3 +529,191 @@ pub fn build_prelude(ids: &UniqueIdGenerator) -> ModuleInterface {
 
     prelude
 }
+
+pub const NIX_MODULE_NAME: &str = "glistix/nix";
+
+const NIX_PATH: &str = "Path";
+const NIX_DERIVATION: &str = "Derivation";
+const NIX_ATTR_SET: &str = "AttrSet";
+
+fn nix_type(name: &str) -> Arc<Type> {
+    named(
+        PRELUDE_PACKAGE_NAME,
+        NIX_MODULE_NAME,
+        name,
+        Publicity::Public,
+        vec![],
+    )
+}
+
+pub fn nix_path() -> Arc<Type> {
+    nix_type(NIX_PATH)
+}
+
+pub fn nix_derivation() -> Arc<Type> {
+    nix_type(NIX_DERIVATION)
+}
+
+pub fn nix_attr_set() -> Arc<Type> {
+    nix_type(NIX_ATTR_SET)
+}
+
+/// Builds the interface of the `glistix/nix` module, which ships with the
+/// compiler and is only available on the Nix target. Its types are opaque
+/// Nix values, and its functions are implemented by the `glistix/nix.nix`
+/// file written next to the compiled modules.
+///
+pub fn build_nix_module() -> ModuleInterface {
+    let mut module = ModuleInterface {
+        name: NIX_MODULE_NAME.into(),
+        package: PRELUDE_PACKAGE_NAME.into(),
+        origin: Origin::Src,
+        types: HashMap::new(),
+        types_value_constructors: HashMap::new(),
+        values: HashMap::new(),
+        accessors: HashMap::new(),
+        is_internal: false,
+        warnings: vec![],
+        // this module doesn't have real src
+        src_path: "".into(),
+        // this module doesn't have real line numbers
+        line_numbers: LineNumbers::new(""),
+        minimum_required_version: Version::new(0, 1, 0),
+        deprecation: NotDeprecated,
+    };
+
+    let types = [
+        (NIX_PATH, nix_path(), "A Nix path, such as `./default.nix`."),
+        (NIX_DERIVATION, nix_derivation(), "A Nix derivation."),
+        (
+            NIX_ATTR_SET,
+            nix_attr_set(),
+            "A Nix attribute set with values of any type.",
+        ),
+    ];
+    for (name, type_, documentation) in types {
+        let _ = module.types.insert(
+            name.into(),
+            TypeConstructor {
+                origin: Default::default(),
+                parameters: vec![],
+                type_,
+                module: NIX_MODULE_NAME.into(),
+                publicity: Publicity::Public,
+                deprecation: NotDeprecated,
+                since: None,
+                documentation: Some(documentation.into()),
+            },
+        );
+    }
+
+    let functions = [
+        (
+            "path_to_string",
+            vec![nix_path()],
+            string(),
+            "Returns the path as a string, without copying it to the Nix store.",
+        ),
+        (
+            "path_base_name",
+            vec![nix_path()],
+            string(),
+            "Returns the last component of the path.",
+        ),
+        (
+            "path_exists",
+            vec![nix_path()],
+            bool(),
+            "Returns whether a file or directory exists at the path.",
+        ),
+        (
+            "path_join",
+            vec![nix_path(), string()],
+            nix_path(),
+            "Appends a relative path, such as `\"src/main.c\"`, to the path.",
+        ),
+        (
+            "derivation_name",
+            vec![nix_derivation()],
+            string(),
+            "Returns the name of the derivation.",
+        ),
+        (
+            "derivation_out_path",
+            vec![nix_derivation()],
+            string(),
+            "Returns the store path of the derivation's default output.",
+        ),
+        (
+            "derivation_to_attr_set",
+            vec![nix_derivation()],
+            nix_attr_set(),
+            "Returns the attribute set the derivation is represented by.",
+        ),
+        (
+            "attr_set_names",
+            vec![nix_attr_set()],
+            list(string()),
+            "Returns the names of the attributes in the set, sorted alphabetically.",
+        ),
+        (
+            "attr_set_has",
+            vec![nix_attr_set(), string()],
+            bool(),
+            "Returns whether the set has an attribute with the given name.",
+        ),
+        (
+            "attr_set_merge",
+            vec![nix_attr_set(), nix_attr_set()],
+            nix_attr_set(),
+            "Merges two sets, preferring the attributes of the second one.",
+        ),
+        (
+            "inspect",
+            vec![generic_var(1)],
+            string(),
+            "Returns the value written the way it would be in Gleam, such as `Ok([1, 2])`.",
+        ),
+        (
+            "debug",
+            vec![generic_var(1)],
+            generic_var(1),
+            "Prints the value with `builtins.trace` when it is evaluated, returning it.",
+        ),
+    ];
+    for (name, arguments, return_, documentation) in functions {
+        let variant = ValueConstructorVariant::ModuleFn {
+            name: name.into(),
+            field_map: None,
+            module: NIX_MODULE_NAME.into(),
+            arity: arguments.len(),
+            location: SrcSpan::default(),
+            documentation: Some(documentation.into()),
+            implementations: Implementations {
+                gleam: false,
+                can_run_on_erlang: false,
+                can_run_on_javascript: false,
+                can_run_on_nix: true,
+                uses_erlang_externals: false,
+                uses_javascript_externals: false,
+                uses_nix_externals: true,
+                nix_blocker: None,
+            },
+            external_erlang: None,
+            external_javascript: None,
+            external_nix: None,
+        };
+        let _ = module.values.insert(
+            name.into(),
+            ValueConstructor {
+                publicity: Publicity::Public,
+                deprecation: NotDeprecated,
+                since: None,
+                variant,
+                type_: fn_(arguments, return_),
+            },
+        );
+    }
+
+    module
+}
@@ -22,6 +22,7 @@ mod assert;
 mod assignments;
 mod conditional_compilation;
 mod custom_types;
+mod derive;
 mod errors;
 mod exhaustiveness;
 mod externals;
@@ -125,6 +126,19 @@ macro_rules! assert_js_module_error {
     };
 }
 
+#[macro_export]
+macro_rules! assert_nix_module_error {
+    ($src:expr) => {
+        let error = $crate::type_::tests::module_error_with_target(
+            $src,
+            vec![],
+            $crate::build::Target::Nix,
+        );
+        let output = format!("----- SOURCE CODE\n{}\n\n----- ERROR\n{}", $src, error);
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    };
+}
+
 #[macro_export]
 macro_rules! assert_module_syntax_error {
     ($src:expr) => {
@@ -318,6 +332,34 @@ macro_rules! assert_js_warning {
     };
 }
 
+#[macro_export]
+macro_rules! assert_nix_warning {
+    ($src:expr) => {
+        let warning = $crate::type_::tests::get_printed_warnings(
+            $src,
+            vec![],
+            $crate::build::Target::Nix,
+            None,
+        );
+        assert!(!warning.is_empty());
+        let output = format!("----- SOURCE CODE\n{}\n\n----- WARNING\n{}", $src, warning);
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    };
+}
+
+#[macro_export]
+macro_rules! assert_nix_no_warnings {
+    ($src:expr) => {
+        let warning = $crate::type_::tests::get_printed_warnings(
+            $src,
+            vec![],
+            $crate::build::Target::Nix,
+            None,
+        );
+        assert!(warning.is_empty());
+    };
+}
+
 #[macro_export]
 macro_rules! assert_js_no_warnings {
     ($src:expr) => {
@@ -479,6 +521,29 @@ pub fn compile_module_with_opts(
     target: Target,
     target_support: TargetSupport,
     gleam_version: Option<Range<Version>>,
+) -> Result<TypedModule, (Vec<crate::type_::Error>, Names)> {
+    let mut config = PackageConfig::default();
+    config.name = "thepackage".into();
+    config.gleam_version = gleam_version;
+    compile_module_with_config(
+        module_name,
+        src,
+        warnings,
+        dep,
+        target,
+        target_support,
+        config,
+    )
+}
+
+pub fn compile_module_with_config(
+    module_name: &str,
+    src: &str,
+    warnings: Option<Rc<dyn WarningEmitterIO>>,
+    dep: Vec<DependencyModule<'_>>,
+    target: Target,
+    target_support: TargetSupport,
+    config: PackageConfig,
 ) -> Result<TypedModule, (Vec<crate::type_::Error>, Names)> {
     let ids = UniqueIdGenerator::new();
     let mut modules = im::HashMap::new();
@@ -491,6 +556,9 @@ pub fn compile_module_with_opts(
     // to have one place where we create all this required state for use in each
     // place.
     let _ = modules.insert(PRELUDE_MODULE_NAME.into(), build_prelude(&ids));
+    if target == Target::Nix {
+        let _ = modules.insert(NIX_MODULE_NAME.into(), build_nix_module());
+    }
     let mut direct_dependencies = HashMap::from_iter(vec![]);
 
     for (package, name, module_src) in dep {
@@ -512,7 +580,11 @@ pub fn compile_module_with_opts(
             target_support,
             package_config: &config,
         }
-        .infer_module(ast, line_numbers, "".into())
+        .infer_module(
+            ast,
+            line_numbers,
+            format!("/build/packages/{package}/src/{name}.gleam").into(),
+        )
         .expect("should successfully infer");
         let _ = modules.insert(name.into(), module.type_info);
 
@@ -525,9 +597,6 @@ pub fn compile_module_with_opts(
         .expect("syntax error");
     let mut ast = parsed.module;
     ast.name = module_name.into();
-    let mut config = PackageConfig::default();
-    config.name = "thepackage".into();
-    config.gleam_version = gleam_version;
 
     let warnings = TypeWarningEmitter::new("/src/warning/wrn.gleam".into(), src.into(), emitter);
     let inference_result = crate::analyse::ModuleAnalyzerConstructor::<()> {
@@ -577,6 +646,26 @@ pub fn module_error_with_target(
     error.pretty_string()
 }
 
+pub fn module_error_with_config(src: &str, target: Target, config: PackageConfig) -> String {
+    let (error, names) = compile_module_with_config(
+        "themodule",
+        src,
+        None,
+        vec![],
+        target,
+        TargetSupport::NotEnforced,
+        config,
+    )
+    .expect_err("should infer an error");
+    let error = Error::Type {
+        names,
+        src: src.into(),
+        path: Utf8PathBuf::from("/src/one/two.gleam"),
+        errors: Vec1::try_from_vec(error).expect("should have at least one error"),
+    };
+    error.pretty_string()
+}
+
 pub fn internal_module_error(src: &str, deps: Vec<DependencyModule<'_>>) -> String {
     internal_module_error_with_target(src, deps, Target::Erlang)
 }
@@ -758,6 +847,7 @@ fn infer_module_type_retention_test() {
         definitions: vec![],
         type_info: (),
         names: Default::default(),
+        deprecation: Deprecation::NotDeprecated,
     };
     let direct_dependencies = HashMap::from_iter(vec![]);
     let ids = UniqueIdGenerator::new();
@@ -846,6 +936,7 @@ fn infer_module_type_retention_test() {
             line_numbers: LineNumbers::new(""),
             src_path: "".into(),
             minimum_required_version: Version::new(0, 1, 0),
+            deprecation: Deprecation::NotDeprecated,
         }
     );
 }
@@ -2684,6 +2775,7 @@ fn assert_suitable_main_function_not_module_function() {
     let value = ValueConstructor {
         publicity: Publicity::Public,
         deprecation: Deprecation::NotDeprecated,
+        since: None,
         type_: fn_(vec![], int()),
         variant: ValueConstructorVariant::ModuleConstant {
             documentation: None,
@@ -2702,6 +2794,7 @@ fn assert_suitable_main_function_not_module_function() {
                 can_run_on_erlang: true,
                 can_run_on_javascript: true,
                 can_run_on_nix: true,
+                nix_blocker: None,
             },
         },
     };
@@ -2713,6 +2806,7 @@ fn assert_suitable_main_function_wrong_arity() {
     let value = ValueConstructor {
         publicity: Publicity::Public,
         deprecation: Deprecation::NotDeprecated,
+        since: None,
         type_: fn_(vec![], int()),
         variant: ValueConstructorVariant::ModuleFn {
             name: "name".into(),
@@ -2732,6 +2826,7 @@ fn assert_suitable_main_function_wrong_arity() {
                 can_run_on_erlang: true,
                 can_run_on_javascript: true,
                 can_run_on_nix: true,
+                nix_blocker: None,
             },
         },
     };
@@ -2743,6 +2838,7 @@ fn assert_suitable_main_function_ok() {
     let value = ValueConstructor {
         publicity: Publicity::Public,
         deprecation: Deprecation::NotDeprecated,
+        since: None,
         type_: fn_(vec![], int()),
         variant: ValueConstructorVariant::ModuleFn {
             name: "name".into(),
@@ -2762,6 +2858,7 @@ fn assert_suitable_main_function_ok() {
                 can_run_on_erlang: true,
                 can_run_on_javascript: true,
                 can_run_on_nix: true,
+                nix_blocker: None,
             },
         },
     };
@@ -2773,6 +2870,7 @@ fn assert_suitable_main_function_erlang_not_supported() {
     let value = ValueConstructor {
         publicity: Publicity::Public,
         deprecation: Deprecation::NotDeprecated,
+        since: None,
         type_: fn_(vec![], int()),
         variant: ValueConstructorVariant::ModuleFn {
             name: "name".into(),
@@ -2792,6 +2890,7 @@ fn assert_suitable_main_function_erlang_not_supported() {
                 can_run_on_erlang: false,
                 can_run_on_javascript: true,
                 can_run_on_nix: false,
+                nix_blocker: None,
             },
         },
     };
@@ -2803,6 +2902,7 @@ fn assert_suitable_main_function_javascript_not_supported() {
     let value = ValueConstructor {
         publicity: Publicity::Public,
         deprecation: Deprecation::NotDeprecated,
+        since: None,
         type_: fn_(vec![], int()),
         variant: ValueConstructorVariant::ModuleFn {
             name: "name".into(),
@@ -2822,6 +2922,7 @@ fn assert_suitable_main_function_javascript_not_supported() {
                 can_run_on_erlang: true,
                 can_run_on_javascript: false,
                 can_run_on_nix: false,
+                nix_blocker: None,
             },
         },
     };
@@ -3054,3 +3155,85 @@ type Wibble {
 "
     );
 }
+
+#[test]
+fn benchmark_functions() {
+    let src = r#"
+pub fn bench_wobble() { 1 }
+pub fn bench_wibble() { 2 }
+pub fn bench_with_argument(x) { x }
+fn bench_private() { 3 }
+pub fn main() { bench_private() }
+
+@external(javascript, "./wibble.mjs", "wibble")
+pub fn bench_javascript_only() -> Int
+"#;
+    // The module is compiled for JavaScript so that the JavaScript only
+    // function is allowed to be public.
+    let module = compile_module_with_opts(
+        "wibble",
+        src,
+        None,
+        vec![],
+        Target::JavaScript,
+        TargetSupport::NotEnforced,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        module.type_info.benchmark_functions(Target::Erlang),
+        vec![
+            EcoString::from("bench_wibble"),
+            EcoString::from("bench_wobble")
+        ]
+    );
+    assert_eq!(
+        module.type_info.benchmark_functions(Target::JavaScript),
+        vec![
+            EcoString::from("bench_javascript_only"),
+            EcoString::from("bench_wibble"),
+            EcoString::from("bench_wobble")
+        ]
+    );
+}
+
+#[test]
+fn test_functions() {
+    let src = r#"
+pub fn wobble_test() { 1 }
+pub fn wibble_test() { 2 }
+pub fn with_argument_test(x) { x }
+fn private_test() { 3 }
+pub fn test_helper() { private_test() }
+
+@external(javascript, "./wibble.mjs", "wibble")
+pub fn javascript_only_test() -> Int
+"#;
+    // The module is compiled for JavaScript so that the JavaScript only
+    // function is allowed to be public.
+    let module = compile_module_with_opts(
+        "wibble_test",
+        src,
+        None,
+        vec![],
+        Target::JavaScript,
+        TargetSupport::NotEnforced,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        module.type_info.test_functions(Target::Nix),
+        vec![
+            EcoString::from("wibble_test"),
+            EcoString::from("wobble_test")
+        ]
+    );
+    assert_eq!(
+        module.type_info.test_functions(Target::JavaScript),
+        vec![
+            EcoString::from("javascript_only_test"),
+            EcoString::from("wibble_test"),
+            EcoString::from("wobble_test")
+        ]
+    );
+}
@@ -0,0 +1,368 @@
+use crate::{analyse::TargetSupport, assert_module_error, assert_with_module_error, build::Target};
+
+use super::{compile_module_with_opts, infer_module, stringify_tuple_strs};
+
+/// The parts of the `gleam/dynamic` module the derived decoders use.
+const DYNAMIC_MODULE: &str = r#"
+pub type Dynamic
+
+pub type DecodeError {
+  DecodeError(expected: String, found: String, path: List(String))
+}
+
+pub type Decoder(t) =
+  fn(Dynamic) -> Result(t, List(DecodeError))
+
+pub fn int(data: Dynamic) -> Result(Int, List(DecodeError)) {
+  todo
+}
+
+pub fn string(data: Dynamic) -> Result(String, List(DecodeError)) {
+  todo
+}
+
+pub fn list(of decoder: Decoder(t)) -> Decoder(List(t)) {
+  todo
+}
+
+pub fn field(named name: String, of decoder: Decoder(t)) -> Decoder(t) {
+  todo
+}
+
+pub fn any(of decoders: List(Decoder(t))) -> Decoder(t) {
+  todo
+}
+
+pub fn decode1(constructor: fn(a) -> t, t1: Decoder(a)) -> Decoder(t) {
+  todo
+}
+
+pub fn decode2(
+  constructor: fn(a, b) -> t,
+  t1: Decoder(a),
+  t2: Decoder(b),
+) -> Decoder(t) {
+  todo
+}
+"#;
+
+#[test]
+fn derive_decode_record() {
+    let src = r#"
+import gleam/dynamic
+
+@derive(decode)
+pub type Person {
+  Person(name: String, age: Int)
+}
+"#;
+    assert_eq!(
+        infer_module(src, vec![("gleam_stdlib", "gleam/dynamic", DYNAMIC_MODULE)]),
+        stringify_tuple_strs(vec![
+            ("Person", "fn(String, Int) -> Person"),
+            (
+                "decode_person",
+                "fn(Dynamic) -> Result(Person, List(DecodeError))"
+            ),
+        ])
+    );
+}
+
+#[test]
+fn derive_decode_constructors_without_fields() {
+    let src = r#"
+import gleam/dynamic
+
+@derive(decode)
+pub type Colour {
+  Red
+  Green
+  Blue
+}
+"#;
+    assert_eq!(
+        infer_module(src, vec![("gleam_stdlib", "gleam/dynamic", DYNAMIC_MODULE)]),
+        stringify_tuple_strs(vec![
+            ("Blue", "Colour"),
+            ("Green", "Colour"),
+            ("Red", "Colour"),
+            (
+                "decode_colour",
+                "fn(Dynamic) -> Result(Colour, List(DecodeError))"
+            ),
+        ])
+    );
+}
+
+#[test]
+fn derive_decode_recursive_type() {
+    let src = r#"
+import gleam/dynamic as dyn
+
+@derive(decode)
+pub type FileTree {
+  File(name: String)
+  Directory(name: String, children: List(FileTree))
+}
+"#;
+    assert_eq!(
+        infer_module(src, vec![("gleam_stdlib", "gleam/dynamic", DYNAMIC_MODULE)]),
+        stringify_tuple_strs(vec![
+            ("Directory", "fn(String, List(FileTree)) -> FileTree"),
+            ("File", "fn(String) -> FileTree"),
+            (
+                "decode_file_tree",
+                "fn(Dynamic) -> Result(FileTree, List(DecodeError))"
+            ),
+        ])
+    );
+}
+
+#[test]
+fn derive_decode_private_type_is_not_unused() {
+    let src = r#"
+import gleam/dynamic
+
+@derive(decode)
+type Wibble {
+  Wibble(count: Int)
+}
+
+pub fn main() {
+  let _ = Wibble(1)
+  Nil
+}
+"#;
+    // Defining `Ok` and `Error` only warns about shadowing the prelude, while
+    // the derived decoder still uses the prelude's constructors.
+    let module = compile_module_with_opts(
+        "test_module",
+        src,
+        None,
+        vec![("gleam_stdlib", "gleam/dynamic", DYNAMIC_MODULE)],
+        Target::Erlang,
+        TargetSupport::NotEnforced,
+        None,
+    );
+    assert!(module.is_ok());
+}
+
+#[test]
+fn derive_decode_without_dynamic_import() {
+    assert_module_error!(
+        r#"
+@derive(decode)
+pub type Wibble {
+  Wibble(name: String)
+}
+"#
+    );
+}
+
+#[test]
+fn derive_decode_unlabelled_field() {
+    assert_with_module_error!(
+        ("gleam/dynamic", "pub type Dynamic"),
+        r#"
+import gleam/dynamic
+
+@derive(decode)
+pub type Wibble {
+  Wibble(String)
+}
+"#
+    );
+}
+
+#[test]
+fn derive_decode_unsupported_field_type() {
+    assert_with_module_error!(
+        ("gleam/dynamic", "pub type Dynamic"),
+        r#"
+import gleam/dynamic
+
+@derive(decode)
+pub type Wibble {
+  Wibble(callback: fn() -> Int)
+}
+"#
+    );
+}
+
+#[test]
+fn derive_decode_type_parameters() {
+    assert_with_module_error!(
+        ("gleam/dynamic", "pub type Dynamic"),
+        r#"
+import gleam/dynamic
+
+@derive(decode)
+pub type Box(a) {
+  Box(inner: a)
+}
+"#
+    );
+}
+
+#[test]
+fn derive_nix_attrs_adds_no_gleam_values() {
+    let src = r#"
+@derive(nix_attrs)
+pub type Service {
+  Service(name: String, enable: Bool)
+}
+"#;
+    assert_eq!(
+        infer_module(src, vec![]),
+        stringify_tuple_strs(vec![("Service", "fn(String, Bool) -> Service")])
+    );
+}
+
+#[test]
+fn derive_nix_attrs_unlabelled_field() {
+    assert_module_error!(
+        r#"
+@derive(nix_attrs)
+pub type Wibble {
+  Wibble(name: String)
+  Wobble(String)
+}
+"#
+    );
+}
+
+#[test]
+fn derive_nix_attrs_private_type() {
+    assert_module_error!(
+        r#"
+@derive(nix_attrs)
+type Wibble {
+  Wibble(name: String)
+}
+"#
+    );
+}
+
+#[test]
+fn derive_nix_attrs_name_clash() {
+    assert_module_error!(
+        r#"
+@derive(nix_attrs)
+pub type Wibble {
+  Wibble(name: String)
+}
+
+pub fn to_attrs_wibble() {
+  Nil
+}
+"#
+    );
+}
+
+#[test]
+fn derive_nixos_options_adds_no_gleam_values() {
+    let src = r#"
+import gleam/option.{type Option}
+
+@derive(nixos_options)
+pub type Level {
+  Low
+  High
+}
+
+@derive(nixos_options)
+pub type Service {
+  Service(name: Option(String), ports: List(Int), level: Level)
+}
+"#;
+    assert_eq!(
+        infer_module(
+            src,
+            vec![(
+                "gleam_stdlib",
+                "gleam/option",
+                "pub type Option(a) { Some(a) None }"
+            )]
+        ),
+        stringify_tuple_strs(vec![
+            ("High", "Level"),
+            ("Low", "Level"),
+            ("Service", "fn(Option(String), List(Int), Level) -> Service"),
+        ])
+    );
+}
+
+#[test]
+fn derive_nixos_options_unsupported_field_type() {
+    assert_module_error!(
+        r#"
+@derive(nixos_options)
+pub type Service {
+  Service(callback: fn() -> Int)
+}
+"#
+    );
+}
+
+#[test]
+fn derive_nixos_options_constructors_with_fields() {
+    assert_module_error!(
+        r#"
+@derive(nixos_options)
+pub type Wibble {
+  Wibble(name: String)
+  Wobble
+}
+"#
+    );
+}
+
+#[test]
+fn derive_decode_with_shadowed_prelude_names() {
+    let src = r#"
+import gleam/dynamic
+
+pub type Result {
+  Ok
+  Error
+}
+
+@derive(decode)
+pub type Wibble {
+  Wibble(count: Int)
+  Wobble
+}
+"#;
+    // Defining `Ok` and `Error` only warns about shadowing the prelude, while
+    // the derived decoder still uses the prelude's constructors.
+    let module = compile_module_with_opts(
+        "test_module",
+        src,
+        None,
+        vec![("gleam_stdlib", "gleam/dynamic", DYNAMIC_MODULE)],
+        Target::Erlang,
+        TargetSupport::NotEnforced,
+        None,
+    );
+    assert!(module.is_ok());
+}
+
+#[test]
+fn derive_decode_option_from_other_module() {
+    assert_with_module_error!(
+        ("gleam/dynamic", "pub type Dynamic"),
+        r#"
+import gleam/dynamic
+
+pub type Option(a) {
+  Some(a)
+  None
+}
+
+@derive(decode)
+pub type Wibble {
+  Wibble(count: Option(Int))
+}
+"#
+    );
+}
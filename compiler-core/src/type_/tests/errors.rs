@@ -1,8 +1,16 @@
+use std::rc::Rc;
+
 use crate::{
+    analyse::TargetSupport,
     assert_error, assert_internal_module_error, assert_module_error, assert_module_syntax_error,
     assert_with_module_error,
+    build::Target,
+    config::{PackageConfig, PrivateTypeLeaks},
+    warning::VectorWarningEmitterIO,
 };
 
+use super::{compile_module_with_config, print_warnings};
+
 #[test]
 fn bit_array_invalid_type() {
     assert_module_error!(
@@ -858,6 +866,39 @@ pub type LeakType { Variant(PrivateType) }"#
     );
 }
 
+#[test]
+fn module_private_type_leak_in_type_alias() {
+    assert_module_error!(
+        r#"type PrivateType
+pub type Alias = PrivateType"#
+    );
+}
+
+#[test]
+fn module_private_type_leak_as_warning() {
+    let src = r#"type PrivateType
+
+@external(erlang, "a", "b")
+pub fn leak_type() -> PrivateType
+"#;
+    let mut config = PackageConfig::default();
+    config.glistix.private_type_leaks = PrivateTypeLeaks::Warning;
+    let warnings = VectorWarningEmitterIO::default();
+    let result = compile_module_with_config(
+        "themodule",
+        src,
+        Some(Rc::new(warnings.clone())),
+        vec![],
+        Target::Erlang,
+        TargetSupport::NotEnforced,
+        config,
+    );
+    assert!(result.is_ok());
+    let warning = print_warnings(warnings.take());
+    let output = format!("----- SOURCE CODE\n{}\n\n----- WARNING\n{}", src, warning);
+    insta::assert_snapshot!(insta::internals::AutoName, output, src);
+}
+
 #[test]
 fn unexpected_labelled_arg() {
     assert_module_error!(r#"fn id(x) { x } fn y() { id(x: 4) }"#);
@@ -2655,3 +2696,14 @@ todo
 "#
     );
 }
+
+#[test]
+fn constant_cycle() {
+    assert_module_error!(
+        r#"
+const a = b
+const b = c
+const c = a
+"#
+    );
+}
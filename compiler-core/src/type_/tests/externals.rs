@@ -1,8 +1,11 @@
 use crate::{
-    assert_infer_with_module, assert_js_module_error, assert_js_module_infer, assert_module_error,
-    assert_module_infer, assert_with_module_error,
+    analyse::TargetSupport, assert_infer_with_module, assert_js_module_error,
+    assert_js_module_infer, assert_module_error, assert_module_infer, assert_nix_module_error,
+    assert_with_module_error, build::Target, config::PackageConfig,
 };
 
+use super::{compile_module_with_config, module_error_with_config, module_error_with_target};
+
 // https://github.com/gleam-lang/gleam/issues/2324
 #[test]
 fn javascript_only_function_used_by_erlang_module() {
@@ -234,3 +237,151 @@ pub const wobble = wobble",
         vec![("wobble", "fn() -> Int")],
     );
 }
+
+#[test]
+fn denied_nix_external() {
+    let src = r#"@external(nix, "./ffi.nix", "wibble")
+pub fn wibble() -> Int
+"#;
+    let mut config = PackageConfig::default();
+    config.glistix.deny_externals = vec![Target::Nix];
+    let error = module_error_with_config(src, Target::Nix, config);
+    let output = format!("----- SOURCE CODE\n{}\n\n----- ERROR\n{}", src, error);
+    insta::assert_snapshot!(insta::internals::AutoName, output, src);
+}
+
+#[test]
+fn externals_for_other_targets_are_not_denied() {
+    let src = r#"@external(erlang, "ffi", "wibble")
+@external(nix, "./ffi.nix", "wibble")
+pub fn wibble() -> Int
+"#;
+    let mut config = PackageConfig::default();
+    config.glistix.deny_externals = vec![Target::JavaScript];
+    let result = compile_module_with_config(
+        "themodule",
+        src,
+        None,
+        vec![],
+        Target::Erlang,
+        TargetSupport::NotEnforced,
+        config,
+    );
+    assert!(result.is_ok());
+}
+
+#[test]
+fn any_external_supports_valid_targets() {
+    let module = r#"@external(any, "ffi", "wibble")
+fn wibble() -> Int
+
+pub fn main() {
+  wibble()
+}
+"#;
+    assert_module_infer!(module, vec![("main", "fn() -> Int")]);
+    assert_js_module_infer!(module, vec![("main", "fn() -> Int")]);
+}
+
+#[test]
+fn any_external_alongside_target_specific_external() {
+    assert_js_module_infer!(
+        r#"@external(erlang, "ffi", "wibble")
+@external(any, "./ffi.mjs", "wibble")
+fn wibble() -> Int
+
+pub fn main() {
+  wibble()
+}
+"#,
+        vec![("main", "fn() -> Int")]
+    );
+}
+
+#[test]
+fn unused_any_external() {
+    let src = r#"@external(erlang, "ffi", "wibble")
+@external(javascript, "./ffi.mjs", "wibble")
+@external(nix, "./ffi.nix", "wibble")
+@external(any, "./ffi", "wibble")
+pub fn wibble() -> Int
+"#;
+    let error = module_error_with_config(src, Target::Erlang, PackageConfig::default());
+    let output = format!("----- SOURCE CODE\n{}\n\n----- ERROR\n{}", src, error);
+    insta::assert_snapshot!(insta::internals::AutoName, output, src);
+}
+
+#[test]
+fn function_without_nix_implementation_used_by_nix_module() {
+    assert_nix_module_error!(
+        r#"@external(erlang, "one", "two")
+@external(javascript, "./one.mjs", "two")
+fn foreign() -> Int
+
+pub fn main() {
+  foreign()
+}
+"#
+    );
+}
+
+#[test]
+fn dependency_function_without_nix_implementation_used_by_nix_module() {
+    let module_src = r#"@external(erlang, "one", "two")
+pub fn erlang_only() -> Int
+"#;
+    let src = "import wibble
+
+pub fn main() {
+  wibble.erlang_only()
+}
+";
+    let error = module_error_with_target(
+        src,
+        vec![("wibble_package", "wibble", module_src)],
+        Target::Nix,
+    );
+    let output = format!("----- SOURCE CODE\n{}\n\n----- ERROR\n{}", src, error);
+    insta::assert_snapshot!(insta::internals::AutoName, output, src);
+}
+
+#[test]
+fn calls_stopping_a_function_from_running_on_nix_are_reported() {
+    let module_src = r#"@external(erlang, "one", "two")
+fn erlang_only() -> Int
+
+pub fn helper() -> Int {
+  erlang_only() + 1
+}
+"#;
+    let src = "import wibble
+
+fn run() -> Int {
+  wibble.helper()
+}
+
+pub fn main() {
+  run()
+}
+";
+    let error = module_error_with_target(
+        src,
+        vec![("wibble_package", "wibble", module_src)],
+        Target::Nix,
+    );
+    let output = format!("----- SOURCE CODE\n{}\n\n----- ERROR\n{}", src, error);
+    insta::assert_snapshot!(insta::internals::AutoName, output, src);
+}
+
+#[test]
+fn any_external_not_valid_on_remaining_target() {
+    let src = r#"@external(javascript, "./ffi.mjs", "wibble")
+@external(nix, "./ffi.nix", "wibble")
+@external(any, "./ffi", "wibble")
+pub fn wibble() -> Int
+"#;
+    // Compiled for JavaScript, as the function has no Erlang implementation.
+    let error = module_error_with_target(src, vec![], Target::JavaScript);
+    let output = format!("----- SOURCE CODE\n{}\n\n----- ERROR\n{}", src, error);
+    insta::assert_snapshot!(insta::internals::AutoName, output, src);
+}
@@ -2,29 +2,37 @@ use ecow::EcoString;
 use itertools::Itertools;
 
 use crate::{
-    analyse::TargetSupport, assert_module_error, build::Target, type_::expression::Implementations,
+    analyse::TargetSupport,
+    assert_module_error,
+    ast::SrcSpan,
+    build::Target,
+    type_::expression::{Implementations, NixBlocker},
 };
 
 use super::compile_module_with_opts;
 
 macro_rules! assert_targets {
     ($src:expr, $implementations:expr $(,)?) => {
-        let result = $crate::type_::tests::target_implementations::implementations($src);
+        assert_targets!($src, $implementations, Target::Erlang);
+    };
+
+    ($src:expr, $implementations:expr, $target:expr $(,)?) => {
+        let result = $crate::type_::tests::target_implementations::implementations($src, $target);
         let expected = $implementations
             .iter()
-            .map(|(name, expected_impl)| ((*name).into(), *expected_impl))
+            .map(|(name, expected_impl)| ((*name).into(), expected_impl.clone()))
             .collect_vec();
         assert_eq!(expected, result);
     };
 }
 
-pub fn implementations(src: &str) -> Vec<(EcoString, Implementations)> {
+pub fn implementations(src: &str, target: Target) -> Vec<(EcoString, Implementations)> {
     compile_module_with_opts(
         "test_module",
         src,
         None,
         vec![],
-        Target::Erlang,
+        target,
         TargetSupport::NotEnforced,
         None,
     )
@@ -55,6 +63,7 @@ pub fn pure_gleam_2() { pure_gleam_1() * 2 }
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: true,
+                    nix_blocker: None,
                 }
             ),
             (
@@ -67,6 +76,7 @@ pub fn pure_gleam_2() { pure_gleam_1() * 2 }
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: true,
+                    nix_blocker: None,
                 }
             )
         ],
@@ -93,6 +103,7 @@ pub fn erlang_only_2() { erlang_only_1() * 2 }
                     can_run_on_erlang: true,
                     can_run_on_javascript: false,
                     can_run_on_nix: false,
+                    nix_blocker: None,
                 }
             ),
             (
@@ -105,12 +116,43 @@ pub fn erlang_only_2() { erlang_only_1() * 2 }
                     can_run_on_erlang: true,
                     can_run_on_javascript: false,
                     can_run_on_nix: false,
+                    nix_blocker: Some(NixBlocker {
+                        module: "test_module".into(),
+                        name: "erlang_only_1".into(),
+                        location: SrcSpan::new(95, 108),
+                    }),
                 }
             )
         ],
     );
 }
 
+#[test]
+pub fn any_external_only_used_on_valid_targets() {
+    // A path is no Erlang module name, so the function has no Erlang
+    // implementation and the module is compiled for JavaScript.
+    assert_targets!(
+        r#"
+@external(any, "./wibble", "wobble")
+pub fn any_external() -> Int
+"#,
+        [(
+            "any_external",
+            Implementations {
+                gleam: false,
+                uses_erlang_externals: false,
+                uses_javascript_externals: true,
+                uses_nix_externals: true,
+                can_run_on_erlang: false,
+                can_run_on_javascript: true,
+                can_run_on_nix: true,
+                nix_blocker: None,
+            }
+        )],
+        Target::JavaScript,
+    );
+}
+
 #[test]
 pub fn externals_only_function() {
     assert_targets!(
@@ -132,6 +174,7 @@ pub fn all_externals_2() { all_externals_1() * 2 }
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: false,
+                    nix_blocker: None,
                 }
             ),
             (
@@ -144,6 +187,11 @@ pub fn all_externals_2() { all_externals_1() * 2 }
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: false,
+                    nix_blocker: Some(NixBlocker {
+                        module: "test_module".into(),
+                        name: "all_externals_1".into(),
+                        location: SrcSpan::new(141, 156),
+                    }),
                 }
             )
         ],
@@ -175,6 +223,7 @@ pub fn pure_gleam() {
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: true,
+                    nix_blocker: None,
                 }
             ),
             (
@@ -187,6 +236,7 @@ pub fn pure_gleam() {
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: true,
+                    nix_blocker: None,
                 }
             ),
             (
@@ -199,6 +249,7 @@ pub fn pure_gleam() {
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: true,
+                    nix_blocker: None,
                 }
             )
         ],
@@ -228,6 +279,11 @@ pub fn all_externals() -> Int { erlang_external_and_javascript_body() }
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: false,
+                    nix_blocker: Some(NixBlocker {
+                        module: "test_module".into(),
+                        name: "erlang_external_and_javascript_body".into(),
+                        location: SrcSpan::new(217, 252),
+                    }),
                 }
             ),
             (
@@ -240,6 +296,11 @@ pub fn all_externals() -> Int { erlang_external_and_javascript_body() }
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: false,
+                    nix_blocker: Some(NixBlocker {
+                        module: "test_module".into(),
+                        name: "javascript_only".into(),
+                        location: SrcSpan::new(164, 179),
+                    }),
                 }
             ),
             (
@@ -252,6 +313,7 @@ pub fn all_externals() -> Int { erlang_external_and_javascript_body() }
                     can_run_on_erlang: false,
                     can_run_on_javascript: true,
                     can_run_on_nix: false,
+                    nix_blocker: None,
                 }
             )
         ],
@@ -281,6 +343,11 @@ pub fn all_externals() -> Int { javascript_external_and_erlang_body() }
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: false,
+                    nix_blocker: Some(NixBlocker {
+                        module: "test_module".into(),
+                        name: "javascript_external_and_erlang_body".into(),
+                        location: SrcSpan::new(213, 248),
+                    }),
                 }
             ),
             (
@@ -293,6 +360,7 @@ pub fn all_externals() -> Int { javascript_external_and_erlang_body() }
                     can_run_on_erlang: true,
                     can_run_on_javascript: false,
                     can_run_on_nix: false,
+                    nix_blocker: None,
                 }
             ),
             (
@@ -305,6 +373,11 @@ pub fn all_externals() -> Int { javascript_external_and_erlang_body() }
                     can_run_on_erlang: true,
                     can_run_on_javascript: true,
                     can_run_on_nix: false,
+                    nix_blocker: Some(NixBlocker {
+                        module: "test_module".into(),
+                        name: "erlang_only".into(),
+                        location: SrcSpan::new(164, 175),
+                    }),
                 }
             )
         ],
@@ -285,6 +285,17 @@ use _, _ <- x()
     );
 }
 
+#[test]
+fn wrong_callback_arity_4() {
+    assert_error!(
+        r#"
+let x = fn(f) { f(1, 2) }
+use a <- x()
+a + 1
+"#
+    );
+}
+
 #[test]
 fn wrong_callback_arg() {
     assert_error!(
@@ -1,7 +1,8 @@
 use super::*;
 use crate::{
-    assert_js_no_warnings, assert_js_warning, assert_no_warnings, assert_warning,
-    assert_warnings_with_gleam_version, assert_warnings_with_imports,
+    assert_js_no_warnings, assert_js_warning, assert_nix_no_warnings, assert_nix_warning,
+    assert_no_warnings, assert_warning, assert_warnings_with_gleam_version,
+    assert_warnings_with_imports,
 };
 
 #[test]
@@ -353,6 +354,21 @@ fn unused_imported_module_with_alias_and_unqualified_name_no_warnings_test() {
     );
 }
 
+#[test]
+fn unused_imported_value_shadowed_by_variable() {
+    assert_warning!(
+        ("thepackage", "gleam/one", "pub fn two() { 1 }"),
+        r#"
+import gleam/one.{two}
+
+pub fn main() {
+  let two = 2
+  two
+}
+"#
+    );
+}
+
 #[test]
 fn unused_imported_module_no_warning_on_used_function_test() {
     assert_no_warnings!(
@@ -826,6 +842,25 @@ pub fn a() {
     );
 }
 
+#[test]
+fn deprecated_module_imported() {
+    assert_warning!(
+        (
+            "package",
+            "module",
+            r#"@deprecated_module("Use other instead")
+pub const a = Nil"#
+        ),
+        r#"
+import module
+
+pub fn a() {
+  module.a
+}
+"#
+    );
+}
+
 #[test]
 fn deprecated_imported_unqualified_constant() {
     assert_warning!(
@@ -2989,3 +3024,105 @@ pub fn main() {
 "#
     );
 }
+
+#[test]
+fn constructor_shadowing_prelude_constructor() {
+    assert_warning!("pub type Wibble { Ok(Int) }");
+}
+
+#[test]
+fn constructor_shadowing_prelude_bool() {
+    assert_warning!("pub type Wibble { True }");
+}
+
+#[test]
+fn function_shadowing_nix_prelude_name() {
+    assert_nix_warning!("pub fn inspect(x) { x }");
+}
+
+#[test]
+fn type_shadowing_nix_prelude_name() {
+    assert_nix_warning!("pub type BitArray { Wibble }");
+}
+
+#[test]
+fn function_shadowing_nix_prelude_name_on_other_targets() {
+    assert_no_warnings!("pub fn inspect(x) { x }");
+}
+
+#[test]
+fn absolute_nix_path() {
+    assert_nix_warning!(
+        r#"
+import glistix/nix
+
+pub fn main(path) {
+  nix.path_join(path, "/src/main.c")
+}
+"#
+    );
+}
+
+#[test]
+fn nix_path_with_whitespace() {
+    assert_nix_warning!(
+        r#"
+import glistix/nix.{path_join}
+
+pub fn main(path) {
+  path_join(path, "src/main file.c")
+}
+"#
+    );
+}
+
+#[test]
+fn home_relative_nix_path() {
+    assert_nix_warning!(
+        r#"
+import glistix/nix
+
+pub fn main(path) {
+  nix.path_join(path, "~/src")
+}
+"#
+    );
+}
+
+#[test]
+fn valid_nix_path() {
+    assert_nix_no_warnings!(
+        r#"
+import glistix/nix
+
+pub fn main(path) {
+  nix.path_join(path, "src/main.c")
+}
+"#
+    );
+}
+
+#[test]
+fn invalid_nix_path_allowed_by_config() {
+    let src = r#"
+import glistix/nix
+
+pub fn main(path) {
+  nix.path_join(path, "/src/main.c")
+}
+"#;
+    let mut config = PackageConfig::default();
+    config.glistix.allow_invalid_nix_paths = true;
+    let warnings = VectorWarningEmitterIO::default();
+    _ = compile_module_with_config(
+        "test_module",
+        src,
+        Some(Rc::new(warnings.clone())),
+        vec![],
+        Target::Nix,
+        TargetSupport::NotEnforced,
+        config,
+    )
+    .expect("Compilation should succeed");
+    assert!(warnings.take().is_empty());
+}
@@ -0,0 +1,271 @@
+//! Searching the functions of module interfaces by their type, so that for
+//! example `fn(List(a), fn(a) -> b) -> List(b)` finds `list.map`.
+//!
+//! A function matches a query when their types are the same once their type
+//! variables are renamed, in any order of the function's arguments. Types are
+//! compared by name, and a type in the query can be qualified with the last
+//! segment of the name of the module defining it, as in `option.Option(a)`.
+//! Holes such as `_` in the query match any type.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, sync::Arc};
+
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{
+    ast::{SrcSpan, TypeAst, TypeAstConstructor, TypeAstFn, TypeAstTuple, TypeAstVar},
+    parse::{self, error::ParseError},
+    type_::{ModuleInterface, Type, TypeVar, ValueConstructorVariant},
+};
+
+/// Functions with more arguments than this are only matched with their
+/// arguments in the order of the query, as trying every order of their
+/// arguments would be too slow.
+const MAX_REORDERED_ARGUMENTS: usize = 6;
+
+/// A type to search functions by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeQuery {
+    shape: Shape,
+}
+
+impl TypeQuery {
+    /// Parses a query written as a Gleam type annotation.
+    ///
+    pub fn parse(query: &str) -> Result<Self, ParseError> {
+        let type_ = parse::parse_type_annotation(query)?;
+        let shape = Shape::from_type_ast(&type_, &mut HashMap::new());
+        Ok(Self { shape })
+    }
+
+    pub fn matches(&self, type_: &Type) -> bool {
+        let candidate = Shape::from_type(type_);
+        match (&self.shape, &candidate) {
+            (
+                Shape::Fn { args, return_ },
+                Shape::Fn {
+                    args: candidate_args,
+                    return_: candidate_return,
+                },
+            ) if args.len() == candidate_args.len() && args.len() <= MAX_REORDERED_ARGUMENTS => {
+                candidate_args
+                    .iter()
+                    .permutations(candidate_args.len())
+                    .any(|candidate_args| {
+                        let mut variables = Variables::default();
+                        args.iter()
+                            .zip(candidate_args)
+                            .all(|(arg, candidate_arg)| variables.unify(arg, candidate_arg))
+                            && variables.unify(return_, candidate_return)
+                    })
+            }
+            _ => Variables::default().unify(&self.shape, &candidate),
+        }
+    }
+}
+
+/// A function matching a type query.
+#[derive(Debug, Clone)]
+pub struct TypeSearchResult {
+    pub module: EcoString,
+    pub name: EcoString,
+    pub type_: Arc<Type>,
+    pub location: SrcSpan,
+}
+
+/// Finds the functions and record constructors of the given modules which
+/// match the query and can be used from other modules, sorted by module and
+/// name.
+///
+pub fn search<'a>(
+    query: &TypeQuery,
+    modules: impl IntoIterator<Item = &'a ModuleInterface>,
+) -> Vec<TypeSearchResult> {
+    let mut results = vec![];
+    for module in modules {
+        for (name, value) in &module.values {
+            let location = match &value.variant {
+                ValueConstructorVariant::ModuleFn { location, .. }
+                | ValueConstructorVariant::Record { location, .. } => *location,
+                ValueConstructorVariant::LocalVariable { .. }
+                | ValueConstructorVariant::ModuleConstant { .. }
+                | ValueConstructorVariant::LocalConstant { .. } => continue,
+            };
+            if value.publicity.is_private() || !query.matches(&value.type_) {
+                continue;
+            }
+            results.push(TypeSearchResult {
+                module: module.name.clone(),
+                name: name.clone(),
+                type_: value.type_.clone(),
+                location,
+            });
+        }
+    }
+    results.sort_by(|one, other| (&one.module, &one.name).cmp(&(&other.module, &other.name)));
+    results
+}
+
+/// The structure of a type, as far as searching is concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Shape {
+    Named {
+        module: Option<EcoString>,
+        name: EcoString,
+        args: Vec<Shape>,
+    },
+    Fn {
+        args: Vec<Shape>,
+        return_: Box<Shape>,
+    },
+    Tuple {
+        elems: Vec<Shape>,
+    },
+    Var {
+        id: u64,
+    },
+    Any,
+}
+
+impl Shape {
+    fn from_type(type_: &Type) -> Self {
+        match type_ {
+            Type::Named {
+                module, name, args, ..
+            } => Shape::Named {
+                module: Some(module.clone()),
+                name: name.clone(),
+                args: args.iter().map(|arg| Shape::from_type(arg)).collect(),
+            },
+            Type::Fn { args, retrn } => Shape::Fn {
+                args: args.iter().map(|arg| Shape::from_type(arg)).collect(),
+                return_: Box::new(Shape::from_type(retrn)),
+            },
+            Type::Tuple { elems } => Shape::Tuple {
+                elems: elems.iter().map(|elem| Shape::from_type(elem)).collect(),
+            },
+            Type::Var { type_ } => match &*type_.borrow() {
+                TypeVar::Link { type_ } => Shape::from_type(type_),
+                TypeVar::Unbound { id } | TypeVar::Generic { id } => Shape::Var { id: *id },
+            },
+        }
+    }
+
+    fn from_type_ast(type_: &TypeAst, variables: &mut HashMap<EcoString, u64>) -> Self {
+        match type_ {
+            TypeAst::Constructor(TypeAstConstructor {
+                module,
+                name,
+                arguments,
+                ..
+            }) => Shape::Named {
+                module: module.as_ref().map(|(module, _)| module.clone()),
+                name: name.clone(),
+                args: arguments
+                    .iter()
+                    .map(|arg| Shape::from_type_ast(arg, variables))
+                    .collect(),
+            },
+            TypeAst::Fn(TypeAstFn {
+                arguments, return_, ..
+            }) => Shape::Fn {
+                args: arguments
+                    .iter()
+                    .map(|arg| Shape::from_type_ast(arg, variables))
+                    .collect(),
+                return_: Box::new(Shape::from_type_ast(return_, variables)),
+            },
+            TypeAst::Tuple(TypeAstTuple { elems, .. }) => Shape::Tuple {
+                elems: elems
+                    .iter()
+                    .map(|elem| Shape::from_type_ast(elem, variables))
+                    .collect(),
+            },
+            TypeAst::Var(TypeAstVar { name, .. }) => {
+                let next_id = variables.len() as u64;
+                let id = *variables.entry(name.clone()).or_insert(next_id);
+                Shape::Var { id }
+            }
+            TypeAst::Hole(_) => Shape::Any,
+        }
+    }
+}
+
+/// The renaming of the type variables of the query to the ones of the
+/// candidate type, which must be one to one.
+#[derive(Debug, Default)]
+struct Variables {
+    query_to_candidate: HashMap<u64, u64>,
+    candidate_to_query: HashMap<u64, u64>,
+}
+
+impl Variables {
+    fn unify(&mut self, query: &Shape, candidate: &Shape) -> bool {
+        match (query, candidate) {
+            (Shape::Any, _) => true,
+
+            (Shape::Var { id }, Shape::Var { id: candidate_id }) => {
+                let candidate_match = *self.query_to_candidate.entry(*id).or_insert(*candidate_id);
+                let query_match = *self.candidate_to_query.entry(*candidate_id).or_insert(*id);
+                candidate_match == *candidate_id && query_match == *id
+            }
+
+            (
+                Shape::Named { module, name, args },
+                Shape::Named {
+                    module: candidate_module,
+                    name: candidate_name,
+                    args: candidate_args,
+                },
+            ) => {
+                name == candidate_name
+                    && module_matches(module.as_deref(), candidate_module.as_deref())
+                    && self.unify_all(args, candidate_args)
+            }
+
+            (
+                Shape::Fn { args, return_ },
+                Shape::Fn {
+                    args: candidate_args,
+                    return_: candidate_return,
+                },
+            ) => self.unify_all(args, candidate_args) && self.unify(return_, candidate_return),
+
+            (
+                Shape::Tuple { elems },
+                Shape::Tuple {
+                    elems: candidate_elems,
+                },
+            ) => self.unify_all(elems, candidate_elems),
+
+            (
+                Shape::Named { .. } | Shape::Fn { .. } | Shape::Tuple { .. } | Shape::Var { .. },
+                _,
+            ) => false,
+        }
+    }
+
+    fn unify_all(&mut self, query: &[Shape], candidate: &[Shape]) -> bool {
+        query.len() == candidate.len()
+            && query
+                .iter()
+                .zip(candidate)
+                .all(|(query, candidate)| self.unify(query, candidate))
+    }
+}
+
+/// A type qualified with a module in the query only matches the types of the
+/// modules whose name ends with that module, so `option.Option` matches the
+/// `Option` type of `gleam/option`.
+fn module_matches(query: Option<&str>, candidate: Option<&str>) -> bool {
+    match (query, candidate) {
+        (None, _) => true,
+        (Some(query), Some(candidate)) => {
+            candidate == query || candidate.ends_with(&format!("/{query}"))
+        }
+        (Some(_), None) => false,
+    }
+}
@@ -0,0 +1,153 @@
+use ecow::EcoString;
+
+use super::*;
+use crate::type_::{
+    prelude::{fn_, generic_var, int, list, string, tuple},
+    tests::compile_module,
+};
+
+fn query(query: &str) -> TypeQuery {
+    TypeQuery::parse(query).expect("query should parse")
+}
+
+fn search_module(module: &str, src: &str, query_src: &str) -> Vec<EcoString> {
+    let module = compile_module(module, src, None, vec![])
+        .expect("module should compile")
+        .type_info;
+    search(&query(query_src), [&module])
+        .into_iter()
+        .map(|result| format!("{}.{}", result.module, result.name).into())
+        .collect()
+}
+
+#[test]
+fn matches_same_type() {
+    let map = fn_(
+        vec![
+            list(generic_var(1)),
+            fn_(vec![generic_var(1)], generic_var(2)),
+        ],
+        list(generic_var(2)),
+    );
+    assert!(query("fn(List(a), fn(a) -> b) -> List(b)").matches(&map));
+}
+
+#[test]
+fn matches_renamed_type_variables() {
+    let map = fn_(
+        vec![
+            list(generic_var(7)),
+            fn_(vec![generic_var(7)], generic_var(3)),
+        ],
+        list(generic_var(3)),
+    );
+    assert!(query("fn(List(x), fn(x) -> y) -> List(y)").matches(&map));
+}
+
+#[test]
+fn type_variables_must_be_renamed_one_to_one() {
+    let map = fn_(
+        vec![
+            list(generic_var(1)),
+            fn_(vec![generic_var(1)], generic_var(2)),
+        ],
+        list(generic_var(2)),
+    );
+    assert!(!query("fn(List(a), fn(a) -> a) -> List(a)").matches(&map));
+
+    let identity_map = fn_(
+        vec![
+            list(generic_var(1)),
+            fn_(vec![generic_var(1)], generic_var(1)),
+        ],
+        list(generic_var(1)),
+    );
+    assert!(!query("fn(List(a), fn(a) -> b) -> List(b)").matches(&identity_map));
+}
+
+#[test]
+fn matches_arguments_in_any_order() {
+    let map = fn_(
+        vec![
+            list(generic_var(1)),
+            fn_(vec![generic_var(1)], generic_var(2)),
+        ],
+        list(generic_var(2)),
+    );
+    assert!(query("fn(fn(a) -> b, List(a)) -> List(b)").matches(&map));
+}
+
+#[test]
+fn nested_arguments_are_not_reordered() {
+    let fold = fn_(vec![fn_(vec![int(), string()], int())], int());
+    assert!(!query("fn(fn(String, Int) -> Int) -> Int").matches(&fold));
+}
+
+#[test]
+fn holes_match_any_type() {
+    let pair = fn_(vec![int()], tuple(vec![int(), string()]));
+    assert!(query("fn(Int) -> #(_, String)").matches(&pair));
+    assert!(!query("fn(Int) -> #(_, Int)").matches(&pair));
+}
+
+#[test]
+fn different_types_do_not_match() {
+    let length = fn_(vec![list(generic_var(1))], int());
+    assert!(!query("fn(List(a)) -> String").matches(&length));
+    assert!(!query("fn(List(a), Int) -> Int").matches(&length));
+}
+
+#[test]
+fn invalid_query() {
+    assert!(TypeQuery::parse("fn(List(a) ->").is_err());
+    assert!(TypeQuery::parse("").is_err());
+}
+
+#[test]
+fn search_module_functions() {
+    let src = r#"
+pub fn map(list: List(a), with fun: fn(a) -> b) -> List(b) {
+  case list {
+    [] -> []
+    [first, ..rest] -> [fun(first), ..map(rest, fun)]
+  }
+}
+
+pub fn each(list: List(a), with fun: fn(a) -> b) -> Nil {
+  let _ = map(list, fun)
+  Nil
+}
+
+fn private_map(list: List(a), with fun: fn(a) -> b) -> List(b) {
+  map(list, fun)
+}
+
+pub fn flip_map(fun: fn(a) -> b, list: List(a)) -> List(b) {
+  private_map(list, fun)
+}
+"#;
+    assert_eq!(
+        search_module("wibble/list", src, "fn(List(a), fn(a) -> b) -> List(b)"),
+        vec![
+            EcoString::from("wibble/list.flip_map"),
+            "wibble/list.map".into()
+        ]
+    );
+}
+
+#[test]
+fn search_record_constructors() {
+    let src = r#"
+pub type Wibble {
+  Wibble(Int, String)
+}
+"#;
+    assert_eq!(
+        search_module("wobble", src, "fn(String, Int) -> wobble.Wibble"),
+        vec![EcoString::from("wobble.Wibble")]
+    );
+    assert_eq!(
+        search_module("wobble", src, "fn(String, Int) -> other.Wibble"),
+        Vec::<EcoString>::new()
+    );
+}
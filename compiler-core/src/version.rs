@@ -4,3 +4,8 @@
 /// Note that this should be updated to correspond to the Gleam version
 /// we are basing Glistix on. This is checked by packages.
 pub const COMPILER_VERSION: &str = "1.7.0";
+
+/// The version of Glistix itself. Builds written by any other version are
+/// not reused, as the code it generates may differ even when the formats of
+/// its caches don't.
+pub const GLISTIX_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,12 +1,13 @@
 use crate::{
     ast::{SrcSpan, TodoKind},
     build::Target,
-    diagnostic::{self, Diagnostic, Location},
+    diagnostic::{self, Diagnostic, ExtraLabel, Location},
     error::wrap,
+    i18n::{self, Key},
     type_::{
         self,
         error::{
-            FeatureKind, LiteralCollectionKind, PanicPosition, TodoOrPanic,
+            FeatureKind, InvalidNixPathReason, LiteralCollectionKind, PanicPosition, TodoOrPanic,
             UnreachableCaseClauseReason,
         },
         pretty::Printer,
@@ -15,6 +16,7 @@ use crate::{
 use camino::Utf8PathBuf;
 use debug_ignore::DebugIgnore;
 use ecow::EcoString;
+use itertools::Itertools;
 use std::{
     io::Write,
     sync::{atomic::Ordering, Arc},
@@ -162,6 +164,14 @@ pub enum Warning {
         src: EcoString,
         warning: DeprecatedSyntaxWarning,
     },
+
+    /// When generating the documentation of a package that targets Nix but
+    /// whose public API contains values that cannot be used on Nix.
+    ///
+    NixIncompatibleDocumentedApi {
+        package: EcoString,
+        values: Vec<EcoString>,
+    },
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Copy)]
@@ -233,6 +243,27 @@ only lowercase alphanumeric characters or underscores."
                 )),
             },
 
+            Warning::NixIncompatibleDocumentedApi { package, values } => {
+                let values = values.iter().map(|value| format!("  - {value}")).join("\n");
+                Diagnostic {
+                    title: "Public API is not fully Nix-compatible".into(),
+                    text: format!(
+                        "{}\n\n{values}",
+                        wrap(&format!(
+                            "The `{package}` package targets Nix, but the following \
+public values cannot be used on the Nix target:"
+                        ))
+                    ),
+                    level: diagnostic::Level::Warning,
+                    location: None,
+                    hint: Some(
+                        "Add an `@external(nix, ...)` implementation or a pure Gleam \
+body to these values."
+                            .into(),
+                    ),
+                }
+            }
+
             Warning::DeprecatedSyntax {
                 path,
                 src,
@@ -538,10 +569,12 @@ A use expression must always be followed by at least one expression.",
                     }
                 }
 
-                type_::Warning::UnusedImportedModule { location, .. } => Diagnostic {
-                    title: "Unused imported module".into(),
-                    text: "".into(),
-                    hint: Some("You can safely remove it.".into()),
+                type_::Warning::UnusedImportedModule { location, name } => Diagnostic {
+                    title: i18n::title(Key::UnusedImportedModule).into(),
+                    text: format!(
+                        "No values or types are referred to as `{name}.name` in this module."
+                    ),
+                    hint: i18n::hint(Key::UnusedImportedModule, &[]),
                     level: diagnostic::Level::Warning,
                     location: Some(Location {
                         src: src.clone(),
@@ -583,21 +616,51 @@ Hint: You can safely remove it.
                     }
                 }
 
-                type_::Warning::UnusedImportedValue { location, .. } => Diagnostic {
-                    title: "Unused imported value".into(),
-                    text: "".into(),
-                    hint: Some("You can safely remove it.".into()),
-                    level: diagnostic::Level::Warning,
-                    location: Some(Location {
-                        src: src.clone(),
-                        path: path.to_path_buf(),
-                        label: diagnostic::Label {
-                            text: Some("This imported value is never used".into()),
-                            span: *location,
-                        },
-                        extra_labels: Vec::new(),
-                    }),
-                },
+                type_::Warning::UnusedImportedValue {
+                    location,
+                    name,
+                    shadowed_by,
+                } => {
+                    let (text, hint, extra_labels) = match shadowed_by {
+                        None => (
+                            String::new(),
+                            String::from("You can safely remove it."),
+                            Vec::new(),
+                        ),
+                        Some(shadowed_by) => (
+                            format!(
+                                "The name `{name}` is shadowed by another definition, \
+so it never refers to the imported value."
+                            ),
+                            String::from(
+                                "Rename the definition if you meant to use the imported \
+value, or else remove the import.",
+                            ),
+                            vec![ExtraLabel {
+                                src_info: None,
+                                label: diagnostic::Label {
+                                    text: Some(format!("This shadows the imported `{name}`")),
+                                    span: *shadowed_by,
+                                },
+                            }],
+                        ),
+                    };
+                    Diagnostic {
+                        title: i18n::title(Key::UnusedImportedValue).into(),
+                        text,
+                        hint: Some(hint),
+                        level: diagnostic::Level::Warning,
+                        location: Some(Location {
+                            src: src.clone(),
+                            path: path.to_path_buf(),
+                            label: diagnostic::Label {
+                                text: Some("This imported value is never used".into()),
+                                span: *location,
+                            },
+                            extra_labels,
+                        }),
+                    }
+                }
 
                 type_::Warning::UnusedPrivateModuleConstant { location, .. } => Diagnostic {
                     title: "Unused private constant".into(),
@@ -632,7 +695,7 @@ Hint: You can safely remove it.
                 },
 
                 type_::Warning::UnusedVariable { location, origin } => Diagnostic {
-                    title: "Unused variable".into(),
+                    title: i18n::title(Key::UnusedVariable).into(),
                     text: "".into(),
                     hint: origin.how_to_ignore(),
                     level: diagnostic::Level::Warning,
@@ -740,10 +803,33 @@ Run this command to add it to your dependencies:
                     }
                 }
 
+                type_::Warning::DeprecatedModule {
+                    location,
+                    name,
+                    message,
+                } => Diagnostic {
+                    title: "Deprecated module imported".into(),
+                    text: wrap(&format!(
+                        "The module `{name}` was deprecated with this message: {message}"
+                    )),
+                    hint: None,
+                    level: diagnostic::Level::Warning,
+                    location: Some(Location {
+                        src: src.clone(),
+                        path: path.to_path_buf(),
+                        label: diagnostic::Label {
+                            text: Some("This module has been deprecated".into()),
+                            span: *location,
+                        },
+                        extra_labels: Vec::new(),
+                    }),
+                },
+
                 type_::Warning::DeprecatedItem {
                     location,
                     message,
                     layer,
+                    ..
                 } => {
                     let text = wrap(&format!("It was deprecated with this message: {message}"));
                     let (title, diagnostic_label_text) = if layer.is_value() {
@@ -924,6 +1010,36 @@ hidden from the package's documentation.",
                         }),
                     }
                 }
+                type_::Warning::PrivateTypeLeak {
+                    location,
+                    leaked,
+                    type_location,
+                } => {
+                    let mut printer = Printer::new();
+                    let text = format!(
+                        "The following type is private, but is being used by this public export.
+
+{}
+
+Private types can only be used within the module that defines them.",
+                        printer.pretty_print(leaked, 4),
+                    );
+                    Diagnostic {
+                        title: "Private type used in public interface".into(),
+                        text,
+                        hint: None,
+                        level: diagnostic::Level::Warning,
+                        location: Some(Location {
+                            label: diagnostic::Label {
+                                text: None,
+                                span: *location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: crate::error::private_type_label(*type_location),
+                        }),
+                    }
+                }
                 type_::Warning::RedundantAssertAssignment { location } => Diagnostic {
                     title: "Redundant assertion".into(),
                     text: "This assertion is redundant since the pattern covers all possibilities."
@@ -1135,6 +1251,142 @@ information.",
                         extra_labels: Vec::new(),
                     }),
                 },
+
+                type_::Warning::MissingNixExternalFile { location, module } => Diagnostic {
+                    title: "Missing Nix external file".into(),
+                    text: wrap(&format!(
+                        "The Nix implementation of this function is in `{module}`, \
+but no such file exists relative to this module."
+                    )),
+                    hint: Some(
+                        "Nix external paths are relative to the Gleam module declaring them."
+                            .into(),
+                    ),
+                    level: diagnostic::Level::Warning,
+                    location: Some(Location {
+                        path: path.to_path_buf(),
+                        src: src.clone(),
+                        label: diagnostic::Label {
+                            text: Some("This file does not exist".into()),
+                            span: *location,
+                        },
+                        extra_labels: Vec::new(),
+                    }),
+                },
+
+                type_::Warning::PreludeConstructorShadowed {
+                    location,
+                    name,
+                    targets,
+                } => {
+                    let mut text = format!(
+                        "The `{name}` constructor has the same name as a constructor \
+from the prelude."
+                    );
+                    if targets.contains(&Target::Erlang) {
+                        text.push_str(&format!(
+                            " On Erlang its values can't be told apart from the \
+prelude's `{name}` at runtime."
+                        ));
+                    }
+                    let other_targets = targets
+                        .iter()
+                        .filter_map(|target| match target {
+                            Target::Erlang => None,
+                            Target::JavaScript => Some("JavaScript"),
+                            Target::Nix => Some("Nix"),
+                        })
+                        .join(" and ");
+                    if !other_targets.is_empty() {
+                        text.push_str(&format!(
+                            " On {other_targets} the generated module fails to load if \
+it also uses the prelude's `{name}`."
+                        ));
+                    }
+                    Diagnostic {
+                        title: "Prelude constructor shadowed".into(),
+                        text: wrap(&text),
+                        hint: Some("Rename the constructor.".into()),
+                        level: diagnostic::Level::Warning,
+                        location: Some(Location {
+                            path: path.to_path_buf(),
+                            src: src.clone(),
+                            label: diagnostic::Label {
+                                text: None,
+                                span: *location,
+                            },
+                            extra_labels: Vec::new(),
+                        }),
+                    }
+                }
+
+                type_::Warning::PreludeNameShadowed {
+                    location,
+                    name,
+                    kind,
+                } => {
+                    let kind = kind.as_str();
+                    let text = wrap(&format!(
+                        "The `{name}` {kind} has the same name as a value the prelude \
+exports through the package's `gleam.nix`. Nix code bringing both into scope, \
+such as Nix externals using `with`, can't tell them apart."
+                    ));
+                    Diagnostic {
+                        title: "Prelude name shadowed".into(),
+                        text,
+                        hint: Some(format!("Rename the {kind}.")),
+                        level: diagnostic::Level::Warning,
+                        location: Some(Location {
+                            path: path.to_path_buf(),
+                            src: src.clone(),
+                            label: diagnostic::Label {
+                                text: None,
+                                span: *location,
+                            },
+                            extra_labels: Vec::new(),
+                        }),
+                    }
+                }
+
+                type_::Warning::InvalidNixPath {
+                    location,
+                    path: nix_path,
+                    reason,
+                } => {
+                    let text = match reason {
+                        InvalidNixPathReason::Empty => {
+                            "This path is empty, so joining it to another path has no effect."
+                                .into()
+                        }
+                        InvalidNixPathReason::Absolute => format!(
+                            "The path `{nix_path}` is absolute, but it is joined to another \
+path as if it were relative to it."
+                        ),
+                        InvalidNixPathReason::HomeRelative => format!(
+                            "The path `{nix_path}` starts with `~`, which Nix only expands \
+in path literals, so it refers to a directory named `~` rather than to the home directory."
+                        ),
+                        InvalidNixPathReason::Whitespace => format!(
+                            "The path `{nix_path}` contains whitespace, which is most \
+likely a mistake."
+                        ),
+                    };
+                    Diagnostic {
+                        title: "Invalid Nix path".into(),
+                        text: wrap(&text),
+                        hint: None,
+                        level: diagnostic::Level::Warning,
+                        location: Some(Location {
+                            path: path.to_path_buf(),
+                            src: src.clone(),
+                            label: diagnostic::Label {
+                                text: Some("This is not a valid relative path".into()),
+                                span: *location,
+                            },
+                            extra_labels: Vec::new(),
+                        }),
+                    }
+                }
             },
         }
     }
@@ -0,0 +1,41 @@
+# The implementation of the `glistix/nix` module, which ships with the
+# compiler. It is written next to the compiled modules as `glistix/nix.nix`.
+
+let
+  inherit (builtins.import ../gleam.nix) toList inspect debug;
+
+  path_to_string = path: builtins.toString path;
+
+  path_base_name = path: builtins.baseNameOf path;
+
+  path_exists = path: builtins.pathExists path;
+
+  path_join = path: child: path + "/${child}";
+
+  derivation_name = drv: drv.name;
+
+  derivation_out_path = drv: drv.outPath;
+
+  derivation_to_attr_set = drv: drv;
+
+  attr_set_names = set: toList (builtins.attrNames set);
+
+  attr_set_has = set: name: builtins.hasAttr name set;
+
+  attr_set_merge = left: right: left // right;
+in
+{
+  inherit
+    path_to_string
+    path_base_name
+    path_exists
+    path_join
+    derivation_name
+    derivation_out_path
+    derivation_to_attr_set
+    attr_set_names
+    attr_set_has
+    attr_set_merge
+    inspect
+    debug;
+}
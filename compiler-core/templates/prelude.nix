@@ -2,528 +2,842 @@
 # without notice.
 
 let
-  Ok = x0: { __gleamTag = "Ok"; _0 = x0; };
+  # Builds the prelude for the given representation of Gleam lists: "cons",
+  # where each element is held by an attribute set along with the rest of
+  # the list, or "native", where lists are Nix lists. Packages generated
+  # with native lists select it through `withListRepresentation`.
+  mkPrelude =
+    listRepresentation:
+    let
+      # The version of the representation of Gleam values used by this prelude.
+      # Packages generated for another version reject it when imported.
+      reprVersion = 1;
 
-  Error = x0: { __gleamTag = "Error"; _0 = x0; };
+      Ok = x0: { __gleamTag = "Ok"; _0 = x0; };
 
-  isOk = res: res.__gleamTag == "Ok";
+      Error = x0: { __gleamTag = "Error"; _0 = x0; };
 
-  # @internal
-  remainderInt = a: b: if b == 0 then 0 else a - (b * (a / b));
+      isOk = res: res.__gleamTag == "Ok";
 
-  # @internal
-  divideInt = a: b: if b == 0 then 0 else a / b;
+      # @internal
+      remainderInt = a: b: if b == 0 then 0 else a - (b * (a / b));
 
-  # @internal
-  divideFloat = a: b: if b == 0 then 0 else a / b;
+      # @internal
+      divideInt = a: b: if b == 0 then 0 else a / b;
 
-  toList = foldr prepend { __gleamTag = "Empty"; __gleamBuiltIn = "List"; };
+      # @internal
+      divideFloat = a: b: if b == 0 then 0 else a / b;
 
-  prepend = head: tail: { __gleamTag = "NotEmpty"; __gleamBuiltIn = "List"; inherit head tail; };
+      # @internal
+      nativeLists = listRepresentation == "native";
 
-  listIsEmpty = list: list.__gleamTag == "Empty";
+      # The prelude for the other representation of lists.
+      withListRepresentation = mkPrelude;
 
-  listToArray = list: if list.__gleamTag == "Empty" then [] else [ list.head ] ++ listToArray list.tail;
+      toList =
+        if nativeLists
+        then list: list
+        else foldr prepend { __gleamTag = "Empty"; __gleamBuiltIn = "List"; };
 
-  # @internal
-  listHasAtLeastLength =
-    lst: len:
-      len <= 0 || !(listIsEmpty lst) && listHasAtLeastLength lst.tail (len - 1);
+      prepend =
+        if nativeLists
+        then head: tail: [ head ] ++ tail
+        else head: tail: { __gleamTag = "NotEmpty"; __gleamBuiltIn = "List"; inherit head tail; };
 
-  # @internal
-  listHasLength =
-    lst: len:
-      if listIsEmpty lst
-      then len == 0
-      else len > 0 && listHasLength lst.tail (len - 1);
+      listIsEmpty =
+        if nativeLists
+        then list: list == [ ]
+        else list: list.__gleamTag == "Empty";
 
-  # @internal
-  foldr = fun: init: lst:
-    let
-      len = builtins.length lst;
-      fold' = index:
-        if index == len
-        then init
-        else fun (builtins.elemAt lst index) (fold' (index + 1));
-    in fold' 0;
-
-  # @internal
-  strHasPrefix =
-    prefix: haystack:
-      prefix == (builtins.substring 0 (builtins.stringLength prefix) haystack);
-
-  # @internal
-  parseTOML = value: (builtins.fromTOML "x = ${value}").x;
-
-  # @internal
-  parseNumber =
-    format:
-      let
-        hasMinus = strHasPrefix "-" format;
-        numberToParse =
-          if hasMinus
-          then builtins.substring 1 (-1) format
-          else format;
-        parsedNumber = parseTOML numberToParse;
-      in if hasMinus then -parsedNumber else parsedNumber;
-
-  # @internal
-  parseEscape = content: parseTOML "\"${content}\"";
-
-  # @internal
-  # Strictly evaluates each expression and returns the second parameter.
-  seqAll =
-    exprs: returning:
-      builtins.seq
-        (builtins.foldl' (acc: elem: builtins.seq elem acc) null exprs)
-        returning;
-
-  # @internal
-  # Create a friendly error indicating where it occurred.
-  makeError =
-    variant: module: line: function: message: extra:
-      let
-        mappedExtraProperties =
-          builtins.map
-            (name: "\n${name} = ${simpleInspect extra.${name} 0}")
-            (builtins.attrNames extra);
-
-        extraProperties =
-          builtins.concatStringsSep
-            "\n"
-            mappedExtraProperties;
-      in
-        ''
-          ${message}
-
-          gleam_error = ${builtins.toJSON variant}
-          module = ${builtins.toJSON module}
-          line = ${builtins.toString line}
-          function = ${builtins.toJSON function}${extraProperties}
-        '';
-
-  # @internal
-  # An attempt at rendering arbitrary Nix values in a friendlier way.
-  # Currently this leaks the internal representation of records,
-  # linked lists and so on, but this is consistent with other Gleam
-  # targets.
-  simpleInspect =
-    x: depth:
-      if builtins.isInt x || builtins.isPath x then builtins.toString x
-      else if
-        builtins.isBool x
-        || builtins.isFloat x
-        || builtins.isNull x
-        || builtins.isString x
-      then builtins.toJSON x
-      else if builtins.isFunction x
-      then surroundWithAngleBrackets "lambda"
-      else if builtins.isList x
-      then
-        if depth == 0
-        then
+      listToArray =
+        if nativeLists
+        then list: list
+        else list: if list.__gleamTag == "Empty" then [] else [ list.head ] ++ listToArray list.tail;
+
+      # @internal
+      listHasAtLeastLength =
+        if nativeLists
+        then lst: len: builtins.length lst >= len
+        else
+          lst: len:
+            len <= 0 || !(listIsEmpty lst) && listHasAtLeastLength lst.tail (len - 1);
+
+      # @internal
+      listHasLength =
+        if nativeLists
+        then lst: len: builtins.length lst == len
+        else
+          lst: len:
+            if listIsEmpty lst
+            then len == 0
+            else len > 0 && listHasLength lst.tail (len - 1);
+
+      # @internal
+      # The rest of a list after its first `count` elements, which must exist.
+      listDrop =
+        if nativeLists
+        then count: lst: builtins.genList (index: builtins.elemAt lst (index + count)) (builtins.length lst - count)
+        else count: lst: if count <= 0 then lst else listDrop (count - 1) lst.tail;
+
+      # @internal
+      foldr = fun: init: lst:
+        let
+          len = builtins.length lst;
+          fold' = index:
+            if index == len
+            then init
+            else fun (builtins.elemAt lst index) (fold' (index + 1));
+        in fold' 0;
+
+      # @internal
+      strHasPrefix =
+        prefix: haystack:
+          prefix == (builtins.substring 0 (builtins.stringLength prefix) haystack);
+
+      # @internal
+      parseTOML = value: (builtins.fromTOML "x = ${value}").x;
+
+      # @internal
+      parseNumber =
+        format:
           let
-            mappedElems = builtins.map (el: " ${simpleInspect el (depth + 1)}") x;
-          in "[${builtins.concatStringsSep "" mappedElems} ]"
-        else "[ ... ]"
-      else if builtins.isAttrs x
-      then
-        if x.type or null == "derivation"
-        then surroundWithAngleBrackets "derivation ${x.outPath or "???"}"
-        else if depth == 0
-        then
+            hasMinus = strHasPrefix "-" format;
+            numberToParse =
+              if hasMinus
+              then builtins.substring 1 (-1) format
+              else format;
+            parsedNumber = parseTOML numberToParse;
+          in if hasMinus then -parsedNumber else parsedNumber;
+
+      # @internal
+      parseEscape = content: parseTOML "\"${content}\"";
+
+      # @internal
+      # Strictly evaluates each expression and returns the second parameter.
+      seqAll =
+        exprs: returning:
+          builtins.seq
+            (builtins.foldl' (acc: elem: builtins.seq elem acc) null exprs)
+            returning;
+
+      # @internal
+      # Create a friendly error indicating where it occurred.
+      makeError =
+        variant: module: line: function: message: extra:
           let
-            names = builtins.attrNames x;
-            inspectAttr = name: " ${builtins.toJSON name} = ${simpleInspect x.${name} (depth + 1)};";
-            mappedAttrs = builtins.map inspectAttr names;
-          in "{${builtins.concatStringsSep "" mappedAttrs} }"
-        else "{ ... }"
-      else surroundWithAngleBrackets "unknown";
-
-  # @internal
-  surroundWithAngleBrackets =
-    let
-      leftAngleBracket = builtins.fromJSON ''"\u00AB"'';
-      rightAngleBracket = builtins.fromJSON ''"\u00BB"'';
-    in x: "${leftAngleBracket}${x}${rightAngleBracket}";
+            mappedExtraProperties =
+              builtins.map
+                (name: "\n${name} = ${simpleInspect extra.${name} 0}")
+                (builtins.attrNames extra);
+
+            extraProperties =
+              builtins.concatStringsSep
+                "\n"
+                mappedExtraProperties;
+          in
+            ''
+              ${message}
+
+              gleam_error = ${builtins.toJSON variant}
+              module = ${builtins.toJSON module}
+              line = ${builtins.toString line}
+              function = ${builtins.toJSON function}${extraProperties}
+            '';
+
+      # @internal
+      # An attempt at rendering arbitrary Nix values in a friendlier way.
+      # Currently this leaks the internal representation of records,
+      # linked lists and so on, but this is consistent with other Gleam
+      # targets.
+      simpleInspect =
+        x: depth:
+          if builtins.isInt x || builtins.isPath x then builtins.toString x
+          else if
+            builtins.isBool x
+            || builtins.isFloat x
+            || builtins.isNull x
+            || builtins.isString x
+          then builtins.toJSON x
+          else if builtins.isFunction x
+          then surroundWithAngleBrackets "lambda"
+          else if builtins.isList x
+          then
+            if depth == 0
+            then
+              let
+                mappedElems = builtins.map (el: " ${simpleInspect el (depth + 1)}") x;
+              in "[${builtins.concatStringsSep "" mappedElems} ]"
+            else "[ ... ]"
+          else if builtins.isAttrs x
+          then
+            if x.type or null == "derivation"
+            then surroundWithAngleBrackets "derivation ${x.outPath or "???"}"
+            else if depth == 0
+            then
+              let
+                names = builtins.attrNames x;
+                inspectAttr = name: " ${builtins.toJSON name} = ${simpleInspect x.${name} (depth + 1)};";
+                mappedAttrs = builtins.map inspectAttr names;
+              in "{${builtins.concatStringsSep "" mappedAttrs} }"
+            else "{ ... }"
+          else surroundWithAngleBrackets "unknown";
+
+      # Renders a Gleam value the way it would be written in Gleam, such as
+      # `Ok([1, 2])` or `#("wibble", 1.5)`, matching `string.inspect` on the
+      # other targets. The order in which the labelled fields of a record were
+      # declared isn't known, so they are rendered in alphabetical order after
+      # any unlabelled ones. Values which don't come from Gleam are rendered as
+      # `//nix(...)`.
+      inspect =
+        x:
+          if builtins.isNull x then "Nil"
+          else if builtins.isBool x then (if x then "True" else "False")
+          else if builtins.isInt x then builtins.toString x
+          else if builtins.isFloat x then inspectFloat x
+          else if builtins.isString x then inspectString x
+          else if builtins.isPath x then "//nix(${builtins.toString x})"
+          else if builtins.isFunction x then "//fn(...) { ... }"
+          # Tuples are Nix lists too, so they can't be told apart from native
+          # lists, which are rendered as lists.
+          else if builtins.isList x
+          then (if nativeLists then "[${inspectAll x}]" else "#(${inspectAll x})")
+          else if !(builtins.isAttrs x) then "//nix(${simpleInspect x 0})"
+          else if x.__gleamBuiltIn or null == "List" then "[${inspectAll (listToArray x)}]"
+          else if x.__gleamBuiltIn or null == "BitArray"
+          then "<<${builtins.concatStringsSep ", " (builtins.map builtins.toString x.buffer)}>>"
+          else if x.__gleamTag or null == "UtfCodepoint"
+          then "//utfcodepoint(${intCodepointToString x.value})"
+          else if x ? __gleamTag then inspectRecord x
+          else "//nix(${simpleInspect x 0})";
+
+      # @internal
+      # Floats always have a decimal point, and large and small ones use an
+      # exponent without a plus sign or leading zeroes, as in `1.0e21` and
+      # `1.5e-7`, the way Erlang writes them.
+      inspectFloat =
+        x:
+          let
+            json = builtins.toJSON x;
+            parts = builtins.match "(-?[0-9]+)([.][0-9]+)?(e([+-]?)0*([0-9]+))?" json;
+            whole = builtins.elemAt parts 0;
+            fraction = builtins.elemAt parts 1;
+            exponentSign = builtins.elemAt parts 3;
+            exponent = builtins.elemAt parts 4;
+          in
+            if parts == null then json
+            else
+              whole
+              + (if fraction == null then ".0" else fraction)
+              + (if exponent == null then "" else "e${if exponentSign == "-" then "-" else ""}${exponent}");
+
+      # @internal
+      # Strings are quoted and escaped the way `string.inspect` does it on
+      # Erlang and JavaScript, where control characters without a short escape,
+      # as well as the delete and C1 control characters, are written as
+      # `\u{XXXX}`. This rewrites the escapes used by `builtins.toJSON`, which
+      # can write `\u000c` or `\f` depending on the Nix version. Escaped
+      # backslashes are replaced by themselves so that the backslash of `\\b`
+      # isn't mistaken for the start of an escape.
+      inspectString =
+        let
+          paddedHex =
+            code:
+              let hex = decToHex code;
+              in "${builtins.substring 0 (4 - builtins.stringLength hex) "0000"}${hex}";
+          lowerHex = builtins.replaceStrings [ "A" "B" "C" "D" "E" "F" ] [ "a" "b" "c" "d" "e" "f" ];
+          gleamEscape = code: "\\u{${paddedHex code}}";
+          # NUL can't be part of a Nix string, and tab, line feed, form feed and
+          # carriage return have short escapes.
+          controlCodes =
+            builtins.filter
+              (code: code != 9 && code != 10 && code != 12 && code != 13)
+              (builtins.genList (i: i + 1) 31);
+          # The delete character followed by the C1 control characters.
+          unprintableCodes = builtins.genList (i: i + 127) 33;
+          from =
+            [ "\\\\" "\\b" "\\u000c" ]
+            ++ builtins.map (code: "\\u${lowerHex (paddedHex code)}") controlCodes
+            ++ builtins.map intCodepointToString unprintableCodes;
+          to =
+            [ "\\\\" (gleamEscape 8) "\\f" ]
+            ++ builtins.map gleamEscape controlCodes
+            ++ builtins.map gleamEscape unprintableCodes;
+        in x: builtins.replaceStrings from to (builtins.toJSON x);
+
+      # @internal
+      inspectAll = values: builtins.concatStringsSep ", " (builtins.map inspect values);
+
+      # @internal
+      inspectRecord =
+        record:
+          let
+            names = builtins.filter (name: name != "__gleamTag") (builtins.attrNames record);
+            isPositional = name: builtins.match "_[0-9]+" name != null;
+            position = name: builtins.fromJSON (builtins.substring 1 (-1) name);
+            positional =
+              builtins.sort
+                (a: b: position a < position b)
+                (builtins.filter isPositional names);
+            labelled = builtins.filter (name: !(isPositional name)) names;
+            fields =
+              builtins.map (name: inspect record.${name}) positional
+              ++ builtins.map (name: "${name}: ${inspect record.${name}}") labelled;
+          in
+            if fields == []
+            then record.__gleamTag
+            else "${record.__gleamTag}(${builtins.concatStringsSep ", " fields})";
+
+      # Prints the value, rendered by `inspect`, once it is evaluated, and returns
+      # it unchanged. This is how `io.debug` and the like are implemented on Nix,
+      # where the only way to print while evaluating is `builtins.trace`, which
+      # writes to stderr with a `trace:` prefix.
+      debug = value: builtins.trace (inspect value) value;
+
+      # @internal
+      surroundWithAngleBrackets =
+        let
+          leftAngleBracket = builtins.fromJSON ''"\u00AB"'';
+          rightAngleBracket = builtins.fromJSON ''"\u00BB"'';
+        in x: "${leftAngleBracket}${x}${rightAngleBracket}";
 
-  # --- UTF-8 ---
+      # --- UTF-8 ---
 
-  UtfCodepoint =
-    value:
-      {
-        __gleamTag = "UtfCodepoint";
-        inherit value;
-      };
+      UtfCodepoint =
+        value:
+          {
+            __gleamTag = "UtfCodepoint";
+            inherit value;
+          };
 
-  decToHex =
-    let
-      digitMap = [ "0" "1" "2" "3" "4" "5" "6" "7" "8" "9" "A" "B" "C" "D" "E" "F" ];
-      remHex = n: n - (16 * (n / 16));
-    in
-      n:
+      decToHex =
         let
-          lastDigitValue = remHex n;
-          lastDigit = builtins.elemAt digitMap lastDigitValue;
-          otherDigits = n / 16;
+          digitMap = [ "0" "1" "2" "3" "4" "5" "6" "7" "8" "9" "A" "B" "C" "D" "E" "F" ];
+          remHex = n: n - (16 * (n / 16));
         in
-          if n < 16
-          then lastDigit
-          else decToHex otherDigits + lastDigit;
-
-  # @internal
-  # Converts a codepoint's integer value to its UTF-8 string representation
-  # by invoking a \U(hex) escape sequence within TOML and reading it.
-  # Using TOML over JSON is necessary because JSON restricts the \u escape
-  # sequence to up to 4 hex digits instead of 6, requiring workarounds.
-  intCodepointToStringInternal =
-    n:
-      let
-        hex = decToHex n;
-        zeroes = builtins.substring 0 (8 - (builtins.stringLength hex)) "00000000";
-      in (builtins.fromTOML "x = \"\\U${zeroes}${hex}\"").x;
-
-
-  # @internal
-  asciiChars = builtins.genList (i: intCodepointToStringInternal i) 128;
-
-  # @internal
-  # See comment at 'intCodepointToStringInternal'.
-  # Also applies a fast ASCII table lookup if possible.
-  intCodepointToString =
-    n:
-      if n < 128
-      then builtins.elemAt asciiChars n
-      else intCodepointToStringInternal n;
-
-  # @internal
-  # Prepares a table mapping each possible UTF-8 byte as a string to the corresponding integers.
-  # Valid UTF-8 byte integers include 0-243, excluding 192 and 193.
-  # What this function does is generate one UTF-8 codepoint for each valid UTF-8 byte and extract
-  # a single byte from that codepoint's representation as a string.
-  # We then map each pair (char, value) through the provided function. For example, a pair can be
-  # mapped to '{ name = char; value = value; }' in order to be able to use 'builtins.listToAttrs'
-  # to generate an attribute set mapping each single UTF-8 byte string to its integer value.
-  # Then, we return arrays corresponding to each kind of UTF-8 byte
-  # (ASCII, 10..., 110..., 1110... or 11110...). This is because it might be necessary to insert some
-  # padding between 10... and 110... characters before joining them into a single array, given that bytes
-  # 192 and 193, a.k.a. 0b1100_0000 and 0b1100_0001, do not appear in any valid UTF-8 codepoint's representation.
-  utf8ByteTableGen = charValueMapper:
-    let
-      charAt = i: builtins.substring i 1;
-      first10Codepoint = 8 * 16; # 0x0080
-      first110Codepoint = 8 * 16; # 0x0080
-      first1110Codepoint = 8 * 16 * 16; # 0x0800
-      first11110Codepoint = 16 * 16 * 16 * 16; # 0x10000
-      minInvalidChar = 55296; # 0xd800 - 0xdfff (57343) are invalid UTF-8
-      asciiBytes = builtins.genList (i: charValueMapper { char = builtins.elemAt asciiChars i; value = i; }) 128;
-
-      # Generator for arrays of UTF-8 bytes following a certain pattern. Generates the codepoints
-      # necessary to extract their bytes. 'byteIndex' is the index of the byte to extract from each
-      # generated codepoint; 'k_i' is a function which converts the current iteration index to the
-      # corresponding codepoint (usually in the form 'firstCodepointInRange + offsetToIncreaseByte * i');
-      # 'v_i' is a function which returns the actual numeric value of the byte we expect to extract
-      # (usually in the form 'firstPossibleByte + i'); and 'max_i' is the maximum iteration number
-      # (amount of bytes to generate - 1).
-      codepointGen =
-        { byteIndex, k_i, v_i, max_i }:
+          n:
+            let
+              lastDigitValue = remHex n;
+              lastDigit = builtins.elemAt digitMap lastDigitValue;
+              otherDigits = n / 16;
+            in
+              if n < 16
+              then lastDigit
+              else decToHex otherDigits + lastDigit;
+
+      # @internal
+      # Converts a codepoint's integer value to its UTF-8 string representation
+      # by invoking a \U(hex) escape sequence within TOML and reading it.
+      # Using TOML over JSON is necessary because JSON restricts the \u escape
+      # sequence to up to 4 hex digits instead of 6, requiring workarounds.
+      intCodepointToStringInternal =
+        n:
           let
-            gen =
-              i:
-                let
-                  code = k_i i;
-
-                  # It appears that 0xd800 is actually reached in our algorithm for
-                  # bytes starting with 1110... after 13 iterations.
-                  # This results in a missing 237 byte (with leading 'd' in hexadecimal).
-                  # Therefore, go back to 0xd799 so we still get 'd'.
-                  actualCode =
-                    if code == minInvalidChar
-                    then minInvalidChar - 1
-                    else code;
-
-                  # Obtain the byte by converting the codepoint to a string and obtaining
-                  # the byte at the relevant index.
-                  character = charAt byteIndex (intCodepointToStringInternal actualCode);
-                in charValueMapper { char = character; value = v_i i; };
-          in builtins.genList gen (max_i + 1);
-
-      # 0x0080 + 0..63, converted to string, will generate all possible 0x10... bytes at the second byte.
-      startsWith10 =
-        codepointGen { byteIndex = 1; k_i = i: first10Codepoint + i; v_i = i: first10Codepoint + i; max_i = 63; };
-
-      # 0x0080 + 64 * (0..29), converted to string, will generate all possible 0x110... bytes at the first byte.
-      startsWith110 =
-        codepointGen { byteIndex = 0; k_i = i: first110Codepoint + 64 * i; v_i = i: 194 + i; max_i = 29; };
-
-      # 0x0800 + 4096 * (0..15), converted to string, will generate all possible 0x1110... bytes at the first byte.
-      startsWith1110 =
-        codepointGen { byteIndex = 0; k_i = i: first1110Codepoint + 4096 * i; v_i = i: 224 + i; max_i = 15; };
-
-      # 0x10000 + 262144 * (0..3), converted to string, will generate all possible 0x11110... bytes at the first byte.
-      startsWith11110 =
-        codepointGen { byteIndex = 0; k_i = i: first11110Codepoint + 262144 * i; v_i = i: 240 + i; max_i = 3; };
-    in { inherit asciiBytes startsWith10 startsWith110 startsWith1110 startsWith11110; };
-
-  # @internal
-  # Attribute set mapping each possible UTF-8 byte as a string to its integer value.
-  # Used to quickly map bytes in a string to integers.
-  utf8ByteTable =
-    let
-      # Convert each (char, value) to a format understood by 'builtins.listToAttrs'.
-      gen = { char, value }: { name = char; inherit value; };
-
-      # Join all UTF-8 byte kinds into one large array of name/value pairs.
-      listsToAttrsList =
-        { asciiBytes, startsWith10, startsWith110, startsWith1110, startsWith11110 }:
-          asciiBytes ++ startsWith10 ++ startsWith110 ++ startsWith1110 ++ startsWith11110;
-    in builtins.listToAttrs (listsToAttrsList (utf8ByteTableGen gen));
-
-  # @internal
-  # The inverse of 'utf8ByteTable'.
-  # Contains a list with all possible UTF-8 bytes. Their indices correspond to their integer values.
-  # As such, one can index into this list to convert an integer to the corresponding UTF-8 byte.
-  # Note that only indices up to 243 are valid, excluding 192 and 193.
-  utf8ByteInvTable =
-    let
-      # In the resulting arrays, just keep the byte strings, as we'll use list indexing instead of
-      # attribute sets.
-      gen = { char, value }: char;
-
-      # Bytes 192 and 193 don't exist, so we insert two empty strings at their positions
-      # to "pad" indices. All other bytes (up to 243) are present and sorted in ascending order.
-      supplement = [ "" "" ];
-
-      # Join all the lists of UTF-8 byte kinds, with the padding above where 192 and 193 would be.
-      genList =
-        { asciiBytes, startsWith10, startsWith110, startsWith1110, startsWith11110 }:
-          asciiBytes ++ startsWith10 ++ supplement ++ startsWith110 ++ startsWith1110 ++ startsWith11110;
-    in genList (utf8ByteTableGen gen);
-
-  # @internal
-  # Convert a string to an array of UTF-8 bytes as unsigned 8-bit integers.
-  stringBits =
-    s:
-      let
-        charAt = n: builtins.substring n 1 s;
-
-        # Invalid UTF-8 bytes are represented as 0 instead of throwing.
-        byteStringToInt = char: utf8ByteTable."${char}" or 0;
-      in
-        builtins.genList (i: byteStringToInt (charAt i)) (builtins.stringLength s);
-
-  # @internal
-  # Convert a codepoint (integer) to an array of UTF-8 bytes as unsigned 8-bit integers.
-  intCodepointBits =
-    let
-      last1Byte = 127;  # 0x007f
-      last2Bytes = 2047;  # 0x07ff
-      last3Bytes = 65535;  # 0xffff
-      last4Bytes = 1114111;  # 0x10ffff
-      oneOneHeader = 128;  # 0b1000_0000
-      twoOnesHeader = 128 + 64;  # 0b1100_0000
-      threeOnesHeader = 128 + 64 + 32;  # 0b1110_0000
-      fourOnesHeader = 128 + 64 + 32 + 16;  # 0b1111_0000
-      withMask = builtins.bitAnd;
-      maskHalfByte0 = withMask 15;                                 # 0x00000f
-      maskHalfByte1h1 = n: (withMask 48 n) / 16;                   # 0x000030 (lower half) >> 4
-      maskHalfByte1h2 = n: (withMask 192 n) / (16*4);              # 0x0000c0 (higher half) >> 6
-      maskHalfByte2 = n: (withMask 3840 n) / (16*16);              # 0x000f00 >> 16
-      maskHalfByte3 = n: (withMask 61440 n) / (16*16*16);          # 0x00f000 >> 16
-      maskHalfByte4h1 = n: (withMask 196608 n) / (16*16*16*16);    # 0x030000 (lower half) >> 64
-      maskHalfByte4h2 = n: (withMask 786432 n) / (16*16*16*16*4);  # 0x0c0000 (higher half) >> 96
-      maskHalfByte5 = n: (withMask 15728640 n) / (16*16*16*16*16); # 0xf00000 >> 256
-    in
-      c:
-        if c <= last1Byte
-        then [ c ]
-        else if c > last4Bytes
-        then [ 0 ]
-        else if c <= last2Bytes
-        then
-          [
-            # (110)22211 (10)110000
-            (twoOnesHeader + (maskHalfByte2 c) * 4 + (maskHalfByte1h2 c))
-            (oneOneHeader + (maskHalfByte1h1 c) * 16 + (maskHalfByte0 c))
-          ]
-        else if c <= last3Bytes
-        then
-          [
-            # (1110)3333 (10)222211 (10)110000
-            (threeOnesHeader + (maskHalfByte3 c))
-            (oneOneHeader + (maskHalfByte2 c) * 4 + (maskHalfByte1h2 c))
-            (oneOneHeader + (maskHalfByte1h1 c) * 16 + (maskHalfByte0 c))
-          ]
-        else
-          [
-            # (11110)544 (10)443333 (10)222211 (10)110000
-            (fourOnesHeader + (maskHalfByte5 c) * 4 + (maskHalfByte4h2 c))
-            (oneOneHeader + (maskHalfByte4h1 c) * 16 + (maskHalfByte3 c))
-            (oneOneHeader + (maskHalfByte2 c) * 4 + (maskHalfByte1h2 c))
-            (oneOneHeader + (maskHalfByte1h1 c) * 16 + (maskHalfByte0 c))
-          ];
-
-  # @internal
-  # Convert a codepoint (UtfCodepoint) to an array of UTF-8 bytes as unsigned 8-bit integers.
-  codepointBits = c: intCodepointBits c.value;
-
-  # --- bit array ---
-
-  BitArray =
-    buffer:
-      if !(builtins.isList buffer)
-      then builtins.throw "Bit arrays can only be constructed from Nix lists"
-      else
-        { __gleamTag = "BitArray"; __gleamBuiltIn = "BitArray"; inherit buffer; };
-
-  # @internal
-  # Repeats an element 'n' times in a Nix list.
-  repeated = x: n: builtins.genList (_: x) n;
-
-  # @internal
-  sizedInt =
-    int: size:
-      if size <= 0
-      then []
-      else if remainderInt size 8 != 0
-      then builtins.throw "Bit arrays must be byte aligned on Nix, got size of ${builtins.toString(size)} bits"
-      else
+            hex = decToHex n;
+            zeroes = builtins.substring 0 (8 - (builtins.stringLength hex)) "00000000";
+          in (builtins.fromTOML "x = \"\\U${zeroes}${hex}\"").x;
+
+
+      # @internal
+      asciiChars = builtins.genList (i: intCodepointToStringInternal i) 128;
+
+      # @internal
+      # See comment at 'intCodepointToStringInternal'.
+      # Also applies a fast ASCII table lookup if possible.
+      intCodepointToString =
+        n:
+          if n < 128
+          then builtins.elemAt asciiChars n
+          else intCodepointToStringInternal n;
+
+      # @internal
+      # Prepares a table mapping each possible UTF-8 byte as a string to the corresponding integers.
+      # Valid UTF-8 byte integers include 0-243, excluding 192 and 193.
+      # What this function does is generate one UTF-8 codepoint for each valid UTF-8 byte and extract
+      # a single byte from that codepoint's representation as a string.
+      # We then map each pair (char, value) through the provided function. For example, a pair can be
+      # mapped to '{ name = char; value = value; }' in order to be able to use 'builtins.listToAttrs'
+      # to generate an attribute set mapping each single UTF-8 byte string to its integer value.
+      # Then, we return arrays corresponding to each kind of UTF-8 byte
+      # (ASCII, 10..., 110..., 1110... or 11110...). This is because it might be necessary to insert some
+      # padding between 10... and 110... characters before joining them into a single array, given that bytes
+      # 192 and 193, a.k.a. 0b1100_0000 and 0b1100_0001, do not appear in any valid UTF-8 codepoint's representation.
+      utf8ByteTableGen = charValueMapper:
         let
-          byteArray = repeated 0 (size / 8);
-          foldFun =
-            acc: elem:
+          charAt = i: builtins.substring i 1;
+          first10Codepoint = 8 * 16; # 0x0080
+          first110Codepoint = 8 * 16; # 0x0080
+          first1110Codepoint = 8 * 16 * 16; # 0x0800
+          first11110Codepoint = 16 * 16 * 16 * 16; # 0x10000
+          minInvalidChar = 55296; # 0xd800 - 0xdfff (57343) are invalid UTF-8
+          asciiBytes = builtins.genList (i: charValueMapper { char = builtins.elemAt asciiChars i; value = i; }) 128;
+
+          # Generator for arrays of UTF-8 bytes following a certain pattern. Generates the codepoints
+          # necessary to extract their bytes. 'byteIndex' is the index of the byte to extract from each
+          # generated codepoint; 'k_i' is a function which converts the current iteration index to the
+          # corresponding codepoint (usually in the form 'firstCodepointInRange + offsetToIncreaseByte * i');
+          # 'v_i' is a function which returns the actual numeric value of the byte we expect to extract
+          # (usually in the form 'firstPossibleByte + i'); and 'max_i' is the maximum iteration number
+          # (amount of bytes to generate - 1).
+          codepointGen =
+            { byteIndex, k_i, v_i, max_i }:
               let
-                value = acc.value;
-                arr = acc.arr;
-                byte = builtins.bitAnd value 255;
-              in { value = (value - byte) / 256; arr = [ byte ] ++ arr; };
-        in (builtins.foldl' foldFun { value = int; arr = []; } byteArray).arr;
-
-  # @internal
-  # Joins an array of bit array segments (arrays themselves) into one BitArray.
-  toBitArray =
-    segments:
-      let
-        intoBuffer = elem:
-          if builtins.isList elem
-          then elem
-          else if builtins.isInt elem
-          then [ (remainderInt elem 256) ]
-          else [ elem ];
-        buffer = builtins.concatMap intoBuffer segments;
-      in BitArray buffer;
-
-  # Get the amount of bytes in the bitarray.
-  byteSize = array: builtins.length array.buffer;
-
-  # @internal
-  byteAt = array: i: if i >= byteSize array then null else builtins.elemAt array.buffer i;
-
-  # @internal
-  # A list with elements between i=start and i<end within list 'l'.
-  sublist =
-    l: start: end:
-      let
-        len = builtins.length l;
-        fixedEnd = if end > len then len else end;
-        fixedStart = if start > end then end else start;
-      in builtins.genList (i: builtins.elemAt l (i + fixedStart)) (fixedEnd - fixedStart);
-
-  # @internal
-  # Bytes after the given index within the bitarray.
-  bitSliceAfter = array: start: binaryFromBitSlice array start (byteSize array);
-
-  # @internal
-  # BitArray with bytes between i=start and i<end within existing BitArray 'array'.
-  binaryFromBitSlice = array: start: end: BitArray (sublist array.buffer start end);
-
-  # @internal
-  intFromBitSlice =
-    array: start: end:
-      let
-        subArray = binaryFromBitSlice array start end;
-      in byteArrayToInt subArray;
-
-  # @internal
-  byteArrayToInt = array: builtins.foldl' (acc: elem: acc * 256 + elem) 0 array.buffer;
-
-  # @internal
-  byteArrayToUtf8String =
-    let
-      lastAsciiByte = 127; # 0b0111_1111
-      lastSingleByte = 191; # 0b1011_1111
-      lastDoubleByte = 223; # 0b1101_1111
-      lastTripleByte = 239; # 0b1110_1111
-      maybeInvalidInitialByte = 237;  # 0b1110_1101, aka codepoints in the form 0xd???
-      firstInvalidFollowupByte = 160;  # 0b1010_0000, aka second byte in 0xd800, since 0xd800 - 0xdfff is invalid
-      byteAsString = builtins.elemAt utf8ByteInvTable;
-      buildString =
-        builtins.foldl'
-        (acc@{ string, maybeInvalidByteFound, remainingSingleBytes, invalid }: byte:
+                gen =
+                  i:
+                    let
+                      code = k_i i;
+
+                      # It appears that 0xd800 is actually reached in our algorithm for
+                      # bytes starting with 1110... after 13 iterations.
+                      # This results in a missing 237 byte (with leading 'd' in hexadecimal).
+                      # Therefore, go back to 0xd799 so we still get 'd'.
+                      actualCode =
+                        if code == minInvalidChar
+                        then minInvalidChar - 1
+                        else code;
+
+                      # Obtain the byte by converting the codepoint to a string and obtaining
+                      # the byte at the relevant index.
+                      character = charAt byteIndex (intCodepointToStringInternal actualCode);
+                    in charValueMapper { char = character; value = v_i i; };
+              in builtins.genList gen (max_i + 1);
+
+          # 0x0080 + 0..63, converted to string, will generate all possible 0x10... bytes at the second byte.
+          startsWith10 =
+            codepointGen { byteIndex = 1; k_i = i: first10Codepoint + i; v_i = i: first10Codepoint + i; max_i = 63; };
+
+          # 0x0080 + 64 * (0..29), converted to string, will generate all possible 0x110... bytes at the first byte.
+          startsWith110 =
+            codepointGen { byteIndex = 0; k_i = i: first110Codepoint + 64 * i; v_i = i: 194 + i; max_i = 29; };
+
+          # 0x0800 + 4096 * (0..15), converted to string, will generate all possible 0x1110... bytes at the first byte.
+          startsWith1110 =
+            codepointGen { byteIndex = 0; k_i = i: first1110Codepoint + 4096 * i; v_i = i: 224 + i; max_i = 15; };
+
+          # 0x10000 + 262144 * (0..3), converted to string, will generate all possible 0x11110... bytes at the first byte.
+          startsWith11110 =
+            codepointGen { byteIndex = 0; k_i = i: first11110Codepoint + 262144 * i; v_i = i: 240 + i; max_i = 3; };
+        in { inherit asciiBytes startsWith10 startsWith110 startsWith1110 startsWith11110; };
+
+      # @internal
+      # Attribute set mapping each possible UTF-8 byte as a string to its integer value.
+      # Used to quickly map bytes in a string to integers.
+      utf8ByteTable =
+        let
+          # Convert each (char, value) to a format understood by 'builtins.listToAttrs'.
+          gen = { char, value }: { name = char; inherit value; };
+
+          # Join all UTF-8 byte kinds into one large array of name/value pairs.
+          listsToAttrsList =
+            { asciiBytes, startsWith10, startsWith110, startsWith1110, startsWith11110 }:
+              asciiBytes ++ startsWith10 ++ startsWith110 ++ startsWith1110 ++ startsWith11110;
+        in builtins.listToAttrs (listsToAttrsList (utf8ByteTableGen gen));
+
+      # @internal
+      # The inverse of 'utf8ByteTable'.
+      # Contains a list with all possible UTF-8 bytes. Their indices correspond to their integer values.
+      # As such, one can index into this list to convert an integer to the corresponding UTF-8 byte.
+      # Note that only indices up to 243 are valid, excluding 192 and 193.
+      utf8ByteInvTable =
+        let
+          # In the resulting arrays, just keep the byte strings, as we'll use list indexing instead of
+          # attribute sets.
+          gen = { char, value }: char;
+
+          # Bytes 192 and 193 don't exist, so we insert two empty strings at their positions
+          # to "pad" indices. All other bytes (up to 243) are present and sorted in ascending order.
+          supplement = [ "" "" ];
+
+          # Join all the lists of UTF-8 byte kinds, with the padding above where 192 and 193 would be.
+          genList =
+            { asciiBytes, startsWith10, startsWith110, startsWith1110, startsWith11110 }:
+              asciiBytes ++ startsWith10 ++ supplement ++ startsWith110 ++ startsWith1110 ++ startsWith11110;
+        in genList (utf8ByteTableGen gen);
+
+      # @internal
+      # Convert a string to an array of UTF-8 bytes as unsigned 8-bit integers.
+      stringBits =
+        s:
           let
-            updatedState =
-              acc
-                // {
-                  string = string + byteAsString byte;
-
-                  # If the current byte is 237, then check next byte to see if it
-                  # would place us within the invalid codepoint range
-                  maybeInvalidByteFound = byte == maybeInvalidInitialByte;
-                };
+            charAt = n: builtins.substring n 1 s;
+
+            # Invalid UTF-8 bytes are represented as 0 instead of throwing.
+            byteStringToInt = char: utf8ByteTable."${char}" or 0;
           in
-            if invalid
-            then acc
-            else if byte == 192 || byte == 193 || byte >= builtins.length utf8ByteInvTable
-            then acc // { invalid = true; } # no codepoint has this byte
-            else if maybeInvalidByteFound && byte >= firstInvalidFollowupByte
-            then acc // { invalid = true; } # found a codepoint within 0xd800 - 0xdfff, which is invalid in UTF-8
-            else if byte <= lastAsciiByte # 0b0???_????
-            then updatedState // { remainingSingleBytes = 0; invalid = remainingSingleBytes > 0; }
-            else if byte <= lastSingleByte # 0b10??_????
-            then updatedState // { remainingSingleBytes = remainingSingleBytes - 1; invalid = remainingSingleBytes <= 0; }
-            else if byte <= lastDoubleByte # 0b110?_????
-            then updatedState // { remainingSingleBytes = 1; invalid = remainingSingleBytes > 0; }
-            else if byte <= lastTripleByte # 0b1110_????
-            then updatedState // { remainingSingleBytes = 2; invalid = remainingSingleBytes > 0; }
-            else # 0b1111_0???
-              updatedState // { remainingSingleBytes = 3; invalid = remainingSingleBytes > 0; })
-        { string = ""; maybeInvalidByteFound = false; remainingSingleBytes = 0; invalid = false; };
-    in
-      array:
+            builtins.genList (i: byteStringToInt (charAt i)) (builtins.stringLength s);
+
+      # @internal
+      # Convert a codepoint (integer) to an array of UTF-8 bytes as unsigned 8-bit integers.
+      intCodepointBits =
+        let
+          last1Byte = 127;  # 0x007f
+          last2Bytes = 2047;  # 0x07ff
+          last3Bytes = 65535;  # 0xffff
+          last4Bytes = 1114111;  # 0x10ffff
+          oneOneHeader = 128;  # 0b1000_0000
+          twoOnesHeader = 128 + 64;  # 0b1100_0000
+          threeOnesHeader = 128 + 64 + 32;  # 0b1110_0000
+          fourOnesHeader = 128 + 64 + 32 + 16;  # 0b1111_0000
+          withMask = builtins.bitAnd;
+          maskHalfByte0 = withMask 15;                                 # 0x00000f
+          maskHalfByte1h1 = n: (withMask 48 n) / 16;                   # 0x000030 (lower half) >> 4
+          maskHalfByte1h2 = n: (withMask 192 n) / (16*4);              # 0x0000c0 (higher half) >> 6
+          maskHalfByte2 = n: (withMask 3840 n) / (16*16);              # 0x000f00 >> 16
+          maskHalfByte3 = n: (withMask 61440 n) / (16*16*16);          # 0x00f000 >> 16
+          maskHalfByte4h1 = n: (withMask 196608 n) / (16*16*16*16);    # 0x030000 (lower half) >> 64
+          maskHalfByte4h2 = n: (withMask 786432 n) / (16*16*16*16*4);  # 0x0c0000 (higher half) >> 96
+          maskHalfByte5 = n: (withMask 15728640 n) / (16*16*16*16*16); # 0xf00000 >> 256
+        in
+          c:
+            if c <= last1Byte
+            then [ c ]
+            else if c > last4Bytes
+            then [ 0 ]
+            else if c <= last2Bytes
+            then
+              [
+                # (110)22211 (10)110000
+                (twoOnesHeader + (maskHalfByte2 c) * 4 + (maskHalfByte1h2 c))
+                (oneOneHeader + (maskHalfByte1h1 c) * 16 + (maskHalfByte0 c))
+              ]
+            else if c <= last3Bytes
+            then
+              [
+                # (1110)3333 (10)222211 (10)110000
+                (threeOnesHeader + (maskHalfByte3 c))
+                (oneOneHeader + (maskHalfByte2 c) * 4 + (maskHalfByte1h2 c))
+                (oneOneHeader + (maskHalfByte1h1 c) * 16 + (maskHalfByte0 c))
+              ]
+            else
+              [
+                # (11110)544 (10)443333 (10)222211 (10)110000
+                (fourOnesHeader + (maskHalfByte5 c) * 4 + (maskHalfByte4h2 c))
+                (oneOneHeader + (maskHalfByte4h1 c) * 16 + (maskHalfByte3 c))
+                (oneOneHeader + (maskHalfByte2 c) * 4 + (maskHalfByte1h2 c))
+                (oneOneHeader + (maskHalfByte1h1 c) * 16 + (maskHalfByte0 c))
+              ];
+
+      # @internal
+      # Convert a codepoint (UtfCodepoint) to an array of UTF-8 bytes as unsigned 8-bit integers.
+      codepointBits = c: intCodepointBits c.value;
+
+      # --- bit array ---
+
+      BitArray =
+        buffer:
+          if !(builtins.isList buffer)
+          then builtins.throw "Bit arrays can only be constructed from Nix lists"
+          else
+            { __gleamTag = "BitArray"; __gleamBuiltIn = "BitArray"; inherit buffer; };
+
+      # @internal
+      # Repeats an element 'n' times in a Nix list.
+      repeated = x: n: builtins.genList (_: x) n;
+
+      # @internal
+      sizedInt =
+        int: size:
+          if size <= 0
+          then []
+          else if remainderInt size 8 != 0
+          then builtins.throw "Bit arrays must be byte aligned on Nix, got size of ${builtins.toString(size)} bits"
+          else
+            let
+              byteArray = repeated 0 (size / 8);
+              foldFun =
+                acc: elem:
+                  let
+                    value = acc.value;
+                    arr = acc.arr;
+                    byte = builtins.bitAnd value 255;
+                  in { value = (value - byte) / 256; arr = [ byte ] ++ arr; };
+            in (builtins.foldl' foldFun { value = int; arr = []; } byteArray).arr;
+
+      # @internal
+      # Joins an array of bit array segments (arrays themselves) into one BitArray.
+      toBitArray =
+        segments:
+          let
+            intoBuffer = elem:
+              if builtins.isList elem
+              then elem
+              else if builtins.isInt elem
+              then [ (remainderInt elem 256) ]
+              else [ elem ];
+            buffer = builtins.concatMap intoBuffer segments;
+          in BitArray buffer;
+
+      # Get the amount of bytes in the bitarray.
+      byteSize = array: builtins.length array.buffer;
+
+      # @internal
+      byteAt = array: i: if i >= byteSize array then null else builtins.elemAt array.buffer i;
+
+      # @internal
+      # A list with elements between i=start and i<end within list 'l'.
+      sublist =
+        l: start: end:
+          let
+            len = builtins.length l;
+            fixedEnd = if end > len then len else end;
+            fixedStart = if start > end then end else start;
+          in builtins.genList (i: builtins.elemAt l (i + fixedStart)) (fixedEnd - fixedStart);
+
+      # @internal
+      # Bytes after the given index within the bitarray.
+      bitSliceAfter = array: start: binaryFromBitSlice array start (byteSize array);
+
+      # @internal
+      # BitArray with bytes between i=start and i<end within existing BitArray 'array'.
+      binaryFromBitSlice = array: start: end: BitArray (sublist array.buffer start end);
+
+      # @internal
+      intFromBitSlice =
+        array: start: end:
+          let
+            subArray = binaryFromBitSlice array start end;
+          in byteArrayToInt subArray;
+
+      # @internal
+      byteArrayToInt = array: builtins.foldl' (acc: elem: acc * 256 + elem) 0 array.buffer;
+
+      # @internal
+      byteArrayToUtf8String =
         let
-          result = buildString array.buffer;
-        in if result.invalid || result.remainingSingleBytes > 0 then null else result.string;
-
-in {
-  inherit
-    Ok
-    Error
-    UtfCodepoint
-    BitArray
-    isOk
-    remainderInt
-    divideInt
-    divideFloat
-    toList
-    prepend
-    listIsEmpty
-    listToArray
-    listHasAtLeastLength
-    listHasLength
-    strHasPrefix
-    parseNumber
-    parseEscape
-    seqAll
-    makeError
-    stringBits
-    codepointBits
-    sizedInt
-    toBitArray
-    byteSize
-    byteAt
-    bitSliceAfter
-    binaryFromBitSlice
-    intFromBitSlice
-    byteArrayToInt
-    byteArrayToUtf8String;
-}
+          lastAsciiByte = 127; # 0b0111_1111
+          lastSingleByte = 191; # 0b1011_1111
+          lastDoubleByte = 223; # 0b1101_1111
+          lastTripleByte = 239; # 0b1110_1111
+          maybeInvalidInitialByte = 237;  # 0b1110_1101, aka codepoints in the form 0xd???
+          firstInvalidFollowupByte = 160;  # 0b1010_0000, aka second byte in 0xd800, since 0xd800 - 0xdfff is invalid
+          byteAsString = builtins.elemAt utf8ByteInvTable;
+          buildString =
+            builtins.foldl'
+            (acc@{ string, maybeInvalidByteFound, remainingSingleBytes, invalid }: byte:
+              let
+                updatedState =
+                  acc
+                    // {
+                      string = string + byteAsString byte;
+
+                      # If the current byte is 237, then check next byte to see if it
+                      # would place us within the invalid codepoint range
+                      maybeInvalidByteFound = byte == maybeInvalidInitialByte;
+                    };
+              in
+                if invalid
+                then acc
+                else if byte == 192 || byte == 193 || byte >= builtins.length utf8ByteInvTable
+                then acc // { invalid = true; } # no codepoint has this byte
+                else if maybeInvalidByteFound && byte >= firstInvalidFollowupByte
+                then acc // { invalid = true; } # found a codepoint within 0xd800 - 0xdfff, which is invalid in UTF-8
+                else if byte <= lastAsciiByte # 0b0???_????
+                then updatedState // { remainingSingleBytes = 0; invalid = remainingSingleBytes > 0; }
+                else if byte <= lastSingleByte # 0b10??_????
+                then updatedState // { remainingSingleBytes = remainingSingleBytes - 1; invalid = remainingSingleBytes <= 0; }
+                else if byte <= lastDoubleByte # 0b110?_????
+                then updatedState // { remainingSingleBytes = 1; invalid = remainingSingleBytes > 0; }
+                else if byte <= lastTripleByte # 0b1110_????
+                then updatedState // { remainingSingleBytes = 2; invalid = remainingSingleBytes > 0; }
+                else # 0b1111_0???
+                  updatedState // { remainingSingleBytes = 3; invalid = remainingSingleBytes > 0; })
+            { string = ""; maybeInvalidByteFound = false; remainingSingleBytes = 0; invalid = false; };
+        in
+          array:
+            let
+              result = buildString array.buffer;
+            in if result.invalid || result.remainingSingleBytes > 0 then null else result.string;
+
+      # --- dynamic ---
+
+      # @internal
+      # Describes the type of an arbitrary Nix value, as used in decode errors.
+      classifyDynamic =
+        value:
+          if builtins.isInt value then "Int"
+          else if builtins.isFloat value then "Float"
+          else if builtins.isString value then "String"
+          else if builtins.isBool value then "Bool"
+          else if builtins.isNull value then "Nil"
+          else if builtins.isPath value then "Path"
+          else if builtins.isFunction value then "Function"
+          else if builtins.isList value
+          then
+            if nativeLists
+            then "List"
+            else "Tuple of ${builtins.toString (builtins.length value)} elements"
+          else if value.__gleamBuiltIn or null == "List" then "List"
+          else if value.__gleamBuiltIn or null == "BitArray" then "BitArray"
+          else if value.__gleamBuiltIn or null == "UtfCodepoint" then "UtfCodepoint"
+          else if value.__gleamTag or null == "Ok" || value.__gleamTag or null == "Error" then "Result"
+          else "Dict";
+
+      # @internal
+      # The representation of `gleam/dynamic.DecodeError`.
+      DecodeError = expected: found: path: { __gleamTag = "DecodeError"; inherit expected found path; };
+
+      # @internal
+      decodeError =
+        expected: value:
+          Error (toList [ (DecodeError expected (classifyDynamic value) (toList [])) ]);
+
+      # @internal
+      # Returns the value if it satisfies the check, or a decode error otherwise.
+      decodeWith = check: expected: value: if check value then Ok value else decodeError expected value;
+
+      # @internal
+      decodeInt = decodeWith builtins.isInt "Int";
+
+      # @internal
+      decodeFloat = decodeWith builtins.isFloat "Float";
+
+      # @internal
+      decodeString = decodeWith builtins.isString "String";
+
+      # @internal
+      decodeBool = decodeWith builtins.isBool "Bool";
+
+      # @internal
+      decodeBitArray = decodeWith (value: value.__gleamBuiltIn or null == "BitArray") "BitArray";
+
+      # @internal
+      decodeTuple = decodeWith builtins.isList "Tuple";
+
+      # @internal
+      decodeResult =
+        decodeWith
+          (value: value.__gleamTag or null == "Ok" || value.__gleamTag or null == "Error")
+          "Result";
+
+      # @internal
+      # Nix lists, such as the ones in Nixpkgs, are converted to Gleam lists.
+      decodeList =
+        value:
+          if builtins.isList value then Ok (toList value)
+          else if value.__gleamBuiltIn or null == "List" then Ok value
+          else decodeError "List" value;
+
+      # @internal
+      # Returns `Some` with the attribute's value, or `None` if the attribute set
+      # doesn't have it.
+      decodeField =
+        value: name:
+          if builtins.isAttrs value && !(value ? __gleamBuiltIn)
+          then Ok (if builtins.hasAttr name value then { __gleamTag = "Some"; _0 = value.${name}; } else { __gleamTag = "None"; })
+          else decodeError "Dict" value;
+
+      # @internal
+      decodeOption =
+        value: decoder:
+          if builtins.isNull value || value.__gleamTag or null == "None"
+          then Ok { __gleamTag = "None"; }
+          else
+            let
+              result = decoder value;
+            in if isOk result then Ok { __gleamTag = "Some"; _0 = result._0; } else result;
+
+      # @internal
+      # Converts a record of a type with `@derive(nix_attrs)` to an attribute
+      # set of its fields. `constructors` maps the type's constructors to
+      # attribute sets mapping the labels of their fields to the types their
+      # values must have, as named by `builtins.typeOf`, or to `null` if any
+      # value is accepted. If there is more than one constructor, the name of
+      # the record's constructor is kept in the `type` attribute.
+      recordToAttrs =
+        constructors: record:
+          let
+            fields = builtins.removeAttrs record [ "__gleamTag" ];
+          in
+            if builtins.length (builtins.attrNames constructors) == 1
+            then fields
+            else fields // { type = record.__gleamTag; };
+
+      # @internal
+      # Converts an attribute set made by `recordToAttrs` back to a record,
+      # throwing if it is missing a field of the constructor, has attributes
+      # which aren't fields of it, or has a field whose value is of the wrong
+      # type.
+      recordFromAttrs =
+        typeName: constructors: attrs:
+          let
+            names = builtins.attrNames constructors;
+            single = builtins.length names == 1;
+            tag =
+              if single then builtins.head names
+              else attrs.type or (builtins.throw
+                "Expected a `type` attribute holding a constructor of ${typeName}: ${builtins.concatStringsSep ", " names}");
+            types =
+              constructors.${tag} or (builtins.throw "Unknown constructor of ${typeName}: ${tag}");
+            labels = builtins.attrNames types;
+            fields = if single then attrs else builtins.removeAttrs attrs [ "type" ];
+            missing = builtins.filter (label: !(fields ? ${label})) labels;
+            unknown = builtins.filter (name: !(types ? ${name})) (builtins.attrNames fields);
+            mistyped =
+              builtins.filter
+                (label: types.${label} != null && builtins.typeOf fields.${label} != types.${label})
+                labels;
+            describe = label: "`${label}` should be a ${types.${label}}, got a ${builtins.typeOf fields.${label}}";
+          in
+            if !builtins.isAttrs attrs
+            then builtins.throw "Expected an attribute set for ${typeName}, got a ${builtins.typeOf attrs}"
+            else if missing != [ ]
+            then builtins.throw "Missing attributes for ${tag}: ${builtins.concatStringsSep ", " missing}"
+            else if unknown != [ ]
+            then builtins.throw "Unknown attributes for ${tag}: ${builtins.concatStringsSep ", " unknown}"
+            else if mistyped != [ ]
+            then builtins.throw "Wrongly typed attributes for ${tag}: ${builtins.concatStringsSep ", " (map describe mistyped)}"
+            else fields // { __gleamTag = tag; };
+
+    in {
+      inherit
+        reprVersion
+        Ok
+        Error
+        UtfCodepoint
+        BitArray
+        isOk
+        remainderInt
+        divideInt
+        divideFloat
+        toList
+        prepend
+        listIsEmpty
+        listToArray
+        listHasAtLeastLength
+        listHasLength
+        listDrop
+        listRepresentation
+        withListRepresentation
+        strHasPrefix
+        parseNumber
+        parseEscape
+        seqAll
+        makeError
+        stringBits
+        codepointBits
+        sizedInt
+        toBitArray
+        byteSize
+        byteAt
+        bitSliceAfter
+        binaryFromBitSlice
+        intFromBitSlice
+        byteArrayToInt
+        byteArrayToUtf8String
+        classifyDynamic
+        decodeInt
+        decodeFloat
+        decodeString
+        decodeBool
+        decodeBitArray
+        decodeTuple
+        decodeResult
+        decodeList
+        decodeField
+        decodeOption
+        recordToAttrs
+        recordFromAttrs
+        inspect
+        debug;
+    };
+in
+mkPrelude "cons"
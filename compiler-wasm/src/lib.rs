@@ -5,19 +5,13 @@ mod wasm_filesystem;
 
 use camino::Utf8PathBuf;
 use glistix_core::{
-    analyse::TargetSupport,
-    build::{
-        Mode, NullTelemetry, PackageCompiler, StaleTracker, Target, TargetCodegenConfiguration,
-    },
-    config::PackageConfig,
+    build::{self, Target},
     io::{FileSystemReader, FileSystemWriter},
-    uid::UniqueIdGenerator,
-    warning::{VectorWarningEmitterIO, WarningEmitter},
+    warning::VectorWarningEmitterIO,
     Error,
 };
-use hexpm::version::Version;
 use im::HashMap;
-use std::{cell::RefCell, collections::HashSet, rc::Rc};
+use std::{cell::RefCell, rc::Rc};
 use wasm_filesystem::WasmFileSystem;
 
 use wasm_bindgen::prelude::*;
@@ -179,57 +173,5 @@ pub fn pop_warning(project_id: usize) -> Option<String> {
 }
 
 fn do_compile_package(project: Project, target: Target) -> Result<(), Error> {
-    let ids = UniqueIdGenerator::new();
-    let mut type_manifests = im::HashMap::new();
-    let mut defined_modules = im::HashMap::new();
-    #[allow(clippy::arc_with_non_send_sync)]
-    let warning_emitter = WarningEmitter::new(Rc::new(project.warnings));
-    let config = PackageConfig {
-        name: "library".into(),
-        version: Version::new(1, 0, 0),
-        target,
-        ..Default::default()
-    };
-
-    let target = match target {
-        Target::Erlang => TargetCodegenConfiguration::Erlang { app_file: None },
-        Target::JavaScript => TargetCodegenConfiguration::JavaScript {
-            emit_typescript_definitions: false,
-            prelude_location: Utf8PathBuf::from("./gleam_prelude.mjs"),
-        },
-        Target::Nix => TargetCodegenConfiguration::Nix {
-            prelude_location: Utf8PathBuf::from("./gleam_prelude.nix"),
-        },
-    };
-
-    tracing::info!("Compiling package");
-
-    let lib = Utf8PathBuf::from("/lib");
-    let out = Utf8PathBuf::from("/build");
-    let package = Utf8PathBuf::from("/");
-    let mut compiler = PackageCompiler::new(
-        &config,
-        Mode::Dev,
-        &package,
-        &out,
-        &lib,
-        &target,
-        ids,
-        project.fs,
-    );
-    compiler.write_entrypoint = false;
-    compiler.write_metadata = false;
-    compiler.compile_beam_bytecode = true;
-    compiler.target_support = TargetSupport::Enforced;
-    compiler
-        .compile(
-            &warning_emitter,
-            &mut type_manifests,
-            &mut defined_modules,
-            &mut StaleTracker::default(),
-            &mut HashSet::new(),
-            &NullTelemetry,
-        )
-        .into_result()
-        .map(|_| ())
+    build::in_memory::compile_package(project.fs, target, Rc::new(project.warnings))
 }
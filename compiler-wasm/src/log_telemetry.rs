@@ -1,4 +1,5 @@
-use glistix_core::build::Telemetry;
+use ecow::EcoString;
+use glistix_core::build::{Phase, Telemetry};
 #[derive(Debug)]
 pub struct LogTelemetry;
 
@@ -19,6 +20,10 @@ impl Telemetry for LogTelemetry {
         tracing::info!("Checking package: {}", name);
     }
 
+    fn skipped_modules(&self, modules: &[EcoString]) {
+        tracing::info!("Skipped modules: {}", modules.join(", "));
+    }
+
     fn downloading_package(&self, name: &str) {
         tracing::info!("Downloading package: {}", name);
     }
@@ -38,6 +43,12 @@ impl Telemetry for LogTelemetry {
     fn waiting_for_build_directory_lock(&self) {
         tracing::info!("Waiting for build directory lock");
     }
+
+    fn phase_started(&self, phase: Phase, package: &str, module: Option<&str>) {
+        tracing::debug!("Started {} of {}", phase, module.unwrap_or(package));
+    }
+
+    fn phase_finished(&self) {}
 }
 
 pub fn seconds(duration: std::time::Duration) -> String {
@@ -70,6 +70,11 @@ impl FileSystemWriter for WasmFileSystem {
         Ok(())
     }
 
+    fn rename(&self, from: &Utf8Path, to: &Utf8Path) -> Result<(), Error> {
+        tracing::trace!("rename {:?} to {:?}", from, to);
+        self.imfs.rename(from, to)
+    }
+
     fn delete_file(&self, path: &Utf8Path) -> Result<(), Error> {
         tracing::trace!("delete file {:?}", path);
         self.imfs.delete_file(path)
@@ -120,6 +125,10 @@ impl FileSystemReader for WasmFileSystem {
         self.imfs.modification_time(path)
     }
 
+    fn file_size(&self, path: &Utf8Path) -> Result<u64, Error> {
+        self.imfs.file_size(path)
+    }
+
     fn read_bytes(&self, path: &Utf8Path) -> Result<Vec<u8>, Error> {
         self.imfs.read_bytes(path)
     }
@@ -1,7 +1,7 @@
 use camino::{Utf8Path, Utf8PathBuf};
 use glistix_core::{
     io::{memory::InMemoryFileSystem, Content, FileSystemWriter},
-    version::COMPILER_VERSION,
+    version::{COMPILER_VERSION, GLISTIX_VERSION},
 };
 use itertools::Itertools;
 use regex::Regex;
@@ -54,7 +54,8 @@ impl TestCompileOutput {
                             let line_number = caps.get(2).expect("line number").as_str();
                             format!("-file(\"{path}\", {line_number}).")
                         })
-                        .replace(COMPILER_VERSION, "<gleam compiler version string>");
+                        .replace(COMPILER_VERSION, "<gleam compiler version string>")
+                        .replace(GLISTIX_VERSION, "<glistix compiler version string>");
                     buffer.push_str(&text)
                 }
             };
@@ -35,6 +35,7 @@ pub fn prepare(path: &str) -> String {
         },
         Target::Nix => TargetCodegenConfiguration::Nix {
             prelude_location: Utf8PathBuf::from("../prelude.nix"),
+            list_representation: config.glistix.nix.list_representation.unwrap_or_default(),
         },
     };
 
@@ -0,0 +1,107 @@
+# Checks that `inspect` from the Nix prelude renders values the same way
+# `string.inspect` does on Erlang and JavaScript, so that test failures and
+# debug output read the same on every target.
+let
+  prelude = builtins.import ./prelude.nix;
+  inherit (prelude) inspect toList Ok Error BitArray UtfCodepoint recordFromAttrs recordToAttrs;
+
+  # The constructors of `Person` and `Port` types with `@derive(nix_attrs)`.
+  persons = { Person = { age = "int"; name = "string"; }; };
+  ports = { Tcp = { at = "int"; }; Any = { }; };
+
+  # With native lists, Gleam lists are Nix lists.
+  native = prelude.withListRepresentation "native";
+
+  # A string holding the character with the given four digit hex code.
+  char = code: builtins.fromJSON ''"\u${code}"'';
+
+  checks = [
+    { value = null; expected = "Nil"; }
+    { value = true; expected = "True"; }
+    { value = false; expected = "False"; }
+    { value = 1; expected = "1"; }
+    { value = -12; expected = "-12"; }
+    { value = 1.5; expected = "1.5"; }
+    { value = 2.0; expected = "2.0"; }
+    { value = -0.25; expected = "-0.25"; }
+    { value = 1.0e21; expected = "1.0e21"; }
+    { value = 1.5e-7; expected = "1.5e-7"; }
+
+    { value = "wibble"; expected = ''"wibble"''; }
+    { value = "été 🌊"; expected = ''"été 🌊"''; }
+    { value = "a\"b\\c"; expected = ''"a\"b\\c"''; }
+    { value = "\n\r\t"; expected = ''"\n\r\t"''; }
+    { value = "\\b and \\u0007"; expected = ''"\\b and \\u0007"''; }
+    { value = char "000c"; expected = ''"\f"''; }
+    { value = char "0008"; expected = ''"\u{0008}"''; }
+    { value = char "001b"; expected = ''"\u{001B}"''; }
+    { value = char "007f"; expected = ''"\u{007F}"''; }
+    { value = char "0085"; expected = ''"\u{0085}"''; }
+    { value = char "00a0"; expected = ''"${char "00a0"}"''; }
+
+    { value = [ ]; expected = "#()"; }
+    { value = [ 1 "a" [ null ] ]; expected = ''#(1, "a", #(Nil))''; }
+    { value = toList [ ]; expected = "[]"; }
+    { value = toList [ 1 2 3 ]; expected = "[1, 2, 3]"; }
+    { value = toList [ (toList [ "a" ]) ]; expected = ''[["a"]]''; }
+    { value = BitArray [ ]; expected = "<<>>"; }
+    { value = BitArray [ 1 255 ]; expected = "<<1, 255>>"; }
+    { value = UtfCodepoint 65; expected = "//utfcodepoint(A)"; }
+
+    { value = Ok (toList [ 1 2 ]); expected = "Ok([1, 2])"; }
+    { value = Error null; expected = "Error(Nil)"; }
+    { value = { __gleamTag = "Wibble"; }; expected = "Wibble"; }
+    # As declared with `Person(age: Int, name: String)`.
+    {
+      value = { __gleamTag = "Person"; name = "Lucy"; age = 10; };
+      expected = ''Person(age: 10, name: "Lucy")'';
+    }
+    {
+      value = { __gleamTag = "Point"; _0 = 1.0; _1 = -2.5; };
+      expected = "Point(1.0, -2.5)";
+    }
+
+    {
+      value = recordFromAttrs "Person" persons { name = "Lucy"; age = 10; };
+      expected = ''Person(age: 10, name: "Lucy")'';
+    }
+    {
+      value = recordToAttrs persons { __gleamTag = "Person"; name = "Lucy"; age = 10; } == { name = "Lucy"; age = 10; };
+      expected = "True";
+    }
+    { value = recordFromAttrs "Port" ports { type = "Tcp"; at = 22; }; expected = "Tcp(at: 22)"; }
+    { value = (recordToAttrs ports { __gleamTag = "Any"; }).type; expected = ''"Any"''; }
+    {
+      value = (builtins.tryEval (recordFromAttrs "Person" persons { name = "Lucy"; })).success;
+      expected = "False";
+    }
+    {
+      value = (builtins.tryEval (recordFromAttrs "Port" ports { type = "Any"; at = 22; })).success;
+      expected = "False";
+    }
+    {
+      value = (builtins.tryEval (recordFromAttrs "Person" persons { name = "Lucy"; age = "10"; })).success;
+      expected = "False";
+    }
+  ];
+
+  nativeChecks = [
+    { value = native.toList [ 1 2 3 ]; expected = "[1, 2, 3]"; }
+    { value = native.prepend 0 [ 1 ]; expected = "[0, 1]"; }
+    { value = native.listDrop 2 [ 1 2 3 ]; expected = "[3]"; }
+    { value = native.listHasAtLeastLength [ 1 2 ] 2; expected = "True"; }
+    { value = native.listHasLength [ 1 2 ] 3; expected = "False"; }
+    { value = native.Ok [ "a" ]; expected = ''Ok(["a"])''; }
+  ];
+
+  failures =
+    builtins.filter (check: inspect check.value != check.expected) checks
+    ++ builtins.filter (check: native.inspect check.value != check.expected) nativeChecks;
+
+  describe = check: "\n\t${inspect check.value}\n\t!=\n\t${check.expected}";
+in
+  if builtins.length failures == 0
+  then "${builtins.toString (builtins.length checks + builtins.length nativeChecks)} passes"
+  else
+    builtins.throw
+      "${builtins.toString (builtins.length failures)} failures:${builtins.concatStringsSep "" (builtins.map describe failures)}"